@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::headless::*;
+use crate::libs::*;
+
+// Timings for the game's hot paths, so performance work can compare numbers
+// instead of guessing. Each benchmark is a test that prints what it measured;
+// they only build with the `bench` feature:
+//
+//   cargo test --features bench bench:: -- --nocapture --test-threads 1
+//
+// One thread, so the benchmarks don't compete for cores. The numbers are only
+// comparable on one machine, before and after a change.
+
+// Runs `f` `iterations` times after one warm-up run, prints the mean time per
+// run, and returns it.
+fn measure<T>(
+    name: &str,
+    iterations: u32,
+    mut f: impl FnMut() -> T,
+) -> Duration {
+    black_box(f());
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    let per_iteration = start.elapsed() / iterations;
+    println!("{}: {:?}", name, per_iteration);
+    per_iteration
+}
+
+fn test_palette() -> image_gen::ColorPalette {
+    let mut palette = image_gen::ColorPalette::new();
+    palette
+        .add_colorant(image_gen::Colorant::new(120, 90, 60, 255, 3, 20, 0))
+        .add_colorant(image_gen::Colorant::new(200, 180, 40, 255, 1, 10, 0));
+    palette
+}
+
+fn every_rune() -> Vec<rune::Rune> {
+    (0..=u8::MAX)
+        .map_while(|v| rune::Rune::try_from(v).ok())
+        .collect()
+}
+
+#[test]
+fn image_gen_drawing() {
+    let palette = test_palette();
+    let mut rand = Random::new(1);
+    for size in [32, 128] {
+        measure(&format!("draw_block {}", size), 200, || {
+            palette.draw_block(&mut rand, size)
+        });
+        measure(&format!("draw_ball {}", size), 200, || {
+            palette.draw_ball(&mut rand, size)
+        });
+        measure(&format!("draw_powder {}", size), 200, || {
+            palette.draw_powder(&mut rand, size)
+        });
+        measure(&format!("draw_lump {}", size), 200, || {
+            palette.draw_lump(&mut rand, size)
+        });
+        measure(&format!("draw_shovel_head {}", size), 200, || {
+            palette.draw_shovel_head(&mut rand, size)
+        });
+    }
+    let runes = every_rune();
+    measure("draw_rune (every rune)", 200, || {
+        runes
+            .iter()
+            .map(|r| image_gen::draw_rune(*r))
+            .collect::<Vec<_>>()
+    });
+}
+
+#[test]
+fn pixels_to_rune() {
+    let patterns: Vec<_> = every_rune()
+        .iter()
+        .map(|r| {
+            // padded, so stripping the empty edges has work to do
+            let pixels = rune::rune_to_pixels(r);
+            let width = pixels[0].len() + 4;
+            let mut padded = vec![vec![false; width]; 2];
+            for row in pixels {
+                let mut padded_row = vec![false, false];
+                padded_row.extend(row);
+                padded_row.extend([false, false]);
+                padded.push(padded_row);
+            }
+            padded.extend(vec![vec![false; width]; 2]);
+            padded
+        })
+        .collect();
+    measure("pixels_to_rune (every rune)", 10_000, || {
+        patterns.iter().filter_map(rune::pixels_to_rune).count()
+    });
+}
+
+#[test]
+fn filter_items() {
+    // every catalog type, with bulk items at every quality
+    let inventory: HashMap<ItemType, f32> = ItemType::catalog()
+        .into_iter()
+        .flat_map(|item_type| match item_type {
+            ItemType::Physical(PhysicalItem::Bulk(bulk)) => (0..16)
+                .map(|quality| {
+                    ItemType::Physical(PhysicalItem::Bulk(BulkItem {
+                        quality,
+                        ..bulk
+                    }))
+                })
+                .collect(),
+            _ => vec![item_type],
+        })
+        .map(|item_type| (item_type, 1.0))
+        .collect();
+    println!("{} item types", inventory.len());
+    assert!(inventory.len() >= 1000);
+    let last_page = inventory.len() / 20 - 1;
+    measure("filter_items unfiltered, first page", 200, || {
+        inventory::filter_items(&inventory, String::new(), 20, 0)
+    });
+    measure("filter_items unfiltered, last page", 200, || {
+        inventory::filter_items(&inventory, String::new(), 20, last_page)
+    });
+    measure("filter_items \"iron\"", 200, || {
+        inventory::filter_items(&inventory, "iron".to_string(), 20, 0)
+    });
+    measure("count_filtered_items \"iron\"", 200, || {
+        inventory::count_filtered_items(&inventory, "iron")
+    });
+}
+
+#[test]
+fn combine_storm() {
+    const ITEMS: usize = 1000;
+    // long enough for the pile to settle into a few dozen puddles
+    const TICKS: u32 = 200;
+    // well clear of every minigame on the starting board
+    const GROUND: Vec2 = Vec2::new(0.0, -600.0);
+    let mut app = app(1);
+    // a tight square of overlapping puddles, so most touch at once
+    let side = (ITEMS as f32).sqrt().ceil() as usize;
+    for i in 0..ITEMS {
+        let offset = Vec2::new((i % side) as f32, (i / side) as f32) * 4.0;
+        spawn_item(
+            &mut app,
+            Item::liquid(Substance::SaltWater, 1.0),
+            GROUND + offset,
+        );
+    }
+
+    let start = Instant::now();
+    tick(&mut app, TICKS as usize);
+    let elapsed = start.elapsed();
+    let left = loose_items(&mut app);
+    println!(
+        "combine storm: {:?} over {} ticks ({:?} per tick), {} items left",
+        elapsed,
+        TICKS,
+        elapsed / TICKS,
+        left.len()
+    );
+    assert!(left.len() < ITEMS);
+    // some may drift into a minigame; none may vanish
+    let unaccounted = app.world().resource::<AmountAudit>().unaccounted;
+    assert_eq!(unaccounted, 0.0);
+}
@@ -0,0 +1,282 @@
+use bevy::ecs::system::RunSystemOnce;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::{MouseButtonInput, MouseWheel};
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::headless::*;
+use crate::libs::*;
+
+// A programmatic player for soak tests, behind the `bot` feature (which turns
+// on `headless`). A `Bot` runs the headless app and gives it input the way a
+// replay does, as `ReplayInput`s written ahead of Bevy's input systems. There
+// is no window, so a click names its world position instead of a cursor.
+// `summary` sums the world up for a soak to check.
+//
+// `--soak <minutes>` (and `--seed <n>`) plays at random for that many minutes
+// of game time, printing a summary every minute. It panics if the entities
+// pass `MAX_ENTITIES`, if the audit finds matter unaccounted for, or if the
+// process's memory grows past `MAX_MEMORY_GROWTH` times what it was after the
+// first minute.
+
+const MAX_ENTITIES: usize = 20_000;
+const MAX_MEMORY_GROWTH: f64 = 2.0;
+// frames between the soak's changes of mind
+const PLAY_FRAMES: u64 = 20;
+const MOVES: [KeyCode; 4] =
+    [KeyCode::KeyW, KeyCode::KeyA, KeyCode::KeyS, KeyCode::KeyD];
+
+// The world at a glance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldSummary {
+    pub frame: u64,
+    pub entities: usize,
+    pub loose_items: usize,
+    pub stuck_items: usize,
+    // over every item, loose or stuck
+    pub item_amount: f32,
+    pub images: usize,
+    // the unlocked ones, with their levels
+    pub minigames: Vec<(String, u8)>,
+    pub player: Vec2,
+    pub unaccounted: f32,
+}
+
+pub struct Bot {
+    pub app: App,
+    frame: u64,
+}
+
+impl Bot {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            app: app(seed),
+            frame: 0,
+        }
+    }
+
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    // Queues `inputs` for the next frame.
+    pub fn send(&mut self, inputs: Vec<ReplayInput>) {
+        let mut inputs = inputs;
+        self.app
+            .world_mut()
+            .run_system_once(
+                move |mut mouse: ResMut<Messages<MouseButtonInput>>,
+                      mut keyboard: ResMut<Messages<KeyboardInput>>,
+                      mut wheel: ResMut<Messages<MouseWheel>>| {
+                    write_inputs(
+                        std::mem::take(&mut inputs),
+                        Entity::PLACEHOLDER,
+                        &mut None,
+                        &mut mouse,
+                        &mut keyboard,
+                        &mut wheel,
+                    );
+                },
+            )
+            .unwrap();
+    }
+
+    pub fn key(&mut self, key: KeyCode, pressed: bool) {
+        let Some(code) = variant_name(&key) else {
+            return;
+        };
+        self.send(vec![ReplayInput::Key {
+            code,
+            pressed,
+            text: None,
+        }]);
+    }
+
+    // Presses `key` and lets go the frame after. Takes a frame.
+    pub fn tap(&mut self, key: KeyCode) {
+        self.key(key, true);
+        self.step(1);
+        self.key(key, false);
+    }
+
+    pub fn step(&mut self, frames: u64) {
+        for _ in 0..frames {
+            self.app.update();
+            self.frame += 1;
+        }
+    }
+
+    // Presses the left button at `at`, in the world, and lets go the frame
+    // after. Takes two frames.
+    pub fn click(&mut self, at: Vec2) {
+        self.app
+            .world_mut()
+            .resource_mut::<mouse::MouseState>()
+            .current_position = at;
+        for pressed in [true, false] {
+            self.send(vec![ReplayInput::Mouse {
+                button: "Left".into(),
+                pressed,
+            }]);
+            self.step(1);
+        }
+    }
+
+    pub fn summary(&mut self) -> WorldSummary {
+        let frame = self.frame;
+        let player = player_position(&mut self.app);
+        let world = self.app.world_mut();
+        let (mut loose_items, mut stuck_items, mut item_amount) = (0, 0, 0.0);
+        for (item, stuck) in world.query::<(&Item, Has<Stuck>)>().iter(world) {
+            if stuck {
+                stuck_items += 1;
+            } else {
+                loose_items += 1;
+            }
+            item_amount += item.amount;
+        }
+        let resource = world.resource::<MinigamesResource>();
+        let minigames = resource
+            .ids()
+            .filter(|id| resource.is_unlocked(id))
+            .map(|id| (id.to_string(), resource.level(id)))
+            .collect();
+        WorldSummary {
+            frame,
+            entities: world.entities().count_spawned() as usize,
+            loose_items,
+            stuck_items,
+            item_amount,
+            images: world.resource::<Assets<Image>>().len(),
+            minigames,
+            player,
+            unaccounted: world.resource::<AmountAudit>().unaccounted,
+        }
+    }
+
+    // Every unlocked minigame's position.
+    pub fn minigame_positions(&mut self) -> Vec<Vec2> {
+        let world = self.app.world_mut();
+        world
+            .query_filtered::<&GlobalTransform, With<Minigame>>()
+            .iter(world)
+            .map(|transform| transform.translation().truncate())
+            .collect()
+    }
+}
+
+// Looks for `--soak <minutes>` on the command line.
+pub fn soak_from_args(args: impl IntoIterator<Item = String>) -> Option<f32> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--soak" {
+            return args.next().and_then(|minutes| minutes.parse().ok());
+        }
+    }
+    None
+}
+
+// What the process holds in memory, where that can be read.
+pub fn resident_bytes() -> Option<u64> {
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(pages * 4096)
+}
+
+// One change of mind: which way to head, and maybe a Space, an R or a click
+// on a minigame.
+fn play(bot: &mut Bot, random: &mut Random, held: &mut Vec<KeyCode>) {
+    let wanted: Vec<KeyCode> =
+        MOVES.into_iter().filter(|_| random.chance(0.3)).collect();
+    bot.send(key_changes(held, &wanted));
+    *held = wanted;
+    for key in [KeyCode::Space, KeyCode::KeyR] {
+        if random.chance(0.1) {
+            bot.tap(key);
+        }
+    }
+    if random.chance(0.2) {
+        let positions = bot.minigame_positions();
+        if let Some(at) = random.pick(&positions) {
+            bot.click(*at);
+        }
+    }
+}
+
+pub fn soak(seed: u64, minutes: f32) {
+    let mut bot = Bot::new(seed);
+    let mut random = Random::new(seed);
+    let mut held = vec![];
+    let frames_per_minute = (time_scale::BASE_FIXED_HZ * 60.0) as u64;
+    let frames = (minutes as f64 * frames_per_minute as f64) as u64;
+    let mut next_summary = frames_per_minute;
+    let mut baseline = None;
+    println!("Soaking seed {} for {} minutes", seed, minutes);
+    while bot.frame() < frames {
+        play(&mut bot, &mut random, &mut held);
+        bot.step(PLAY_FRAMES);
+        if bot.frame() < next_summary {
+            continue;
+        }
+        next_summary += frames_per_minute;
+        let summary = bot.summary();
+        let memory = resident_bytes();
+        println!("{:?} memory {:?}", summary, memory);
+        assert!(
+            summary.entities <= MAX_ENTITIES,
+            "{} entities at frame {}",
+            summary.entities,
+            summary.frame
+        );
+        assert_eq!(summary.unaccounted, 0.0, "matter went unaccounted for");
+        let (Some(memory), Some(baseline)) =
+            (memory, *baseline.get_or_insert(memory))
+        else {
+            continue;
+        };
+        assert!(
+            memory as f64 <= baseline as f64 * MAX_MEMORY_GROWTH,
+            "memory grew from {} to {} bytes",
+            baseline,
+            memory
+        );
+    }
+    println!("Soak passed: {:?}", bot.summary());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_soak_from_args() {
+        let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+        assert_eq!(soak_from_args(args("galaxia")), None);
+        assert_eq!(soak_from_args(args("galaxia --soak 90")), Some(90.0));
+        assert_eq!(soak_from_args(args("galaxia --soak")), None);
+    }
+
+    // A short soak: the bot's keys move the player, and the world stays
+    // within bounds.
+    #[test]
+    fn bot_plays_within_bounds() {
+        let mut bot = Bot::new(3);
+        let start = bot.summary();
+        assert!(!start.minigames.is_empty());
+        bot.key(KeyCode::KeyD, true);
+        bot.step(15);
+        bot.key(KeyCode::KeyD, false);
+        bot.step(1);
+        assert!(bot.summary().player.x > start.player.x);
+
+        let mut random = Random::new(3);
+        let mut held = vec![];
+        while bot.frame() < 600 {
+            play(&mut bot, &mut random, &mut held);
+            bot.step(PLAY_FRAMES);
+        }
+        let summary = bot.summary();
+        assert!(summary.entities <= MAX_ENTITIES);
+        assert_eq!(summary.unaccounted, 0.0);
+    }
+}
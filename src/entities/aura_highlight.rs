@@ -0,0 +1,94 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// While the player carries items, the minigames that would take at least one
+// of them pulse a translucent fill over their area, so what eats what can be
+// found by walking around with it instead of by trial and error. Asks each
+// minigame's `can_ingest`, which takes nothing. Minigames leveling up take
+// nothing, so they don't light up.
+
+const HIGHLIGHT_COLOR: Color = Color::srgb(0.3, 0.9, 0.4);
+const MIN_ALPHA: f32 = 0.08;
+const MAX_ALPHA: f32 = 0.25;
+const PULSE_SECONDS: f32 = 1.2;
+
+#[derive(Debug, Component)]
+pub struct AuraHighlight;
+
+// The fill's opacity `seconds` into the pulse: from `MIN_ALPHA` up to
+// `MAX_ALPHA` and back, once per `PULSE_SECONDS`.
+pub fn pulse_alpha(seconds: f32) -> f32 {
+    let phase = (seconds / PULSE_SECONDS * TAU).cos();
+    MIN_ALPHA + (MAX_ALPHA - MIN_ALPHA) * (1.0 - phase) / 2.0
+}
+
+// Adds and removes highlights as what the player carries changes.
+pub fn highlight_accepting_auras(
+    mut commands: Commands,
+    carried_query: Query<&Item, With<Stuck>>,
+    minigame_query: Query<(Entity, &Minigame), Without<LevelingUp>>,
+    highlight_query: Query<(Entity, &ChildOf), With<AuraHighlight>>,
+) {
+    let carried: Vec<&Item> = carried_query.iter().collect();
+    let wanted: Vec<(Entity, RectangularArea)> = minigame_query
+        .iter()
+        .filter(|(_, minigame)| {
+            carried.iter().any(|item| minigame.can_ingest(item))
+        })
+        .map(|(entity, minigame)| (entity, minigame.area()))
+        .collect();
+    for (highlight_entity, child_of) in highlight_query.iter() {
+        if !wanted
+            .iter()
+            .any(|(entity, _)| *entity == child_of.parent())
+        {
+            commands.entity(highlight_entity).despawn();
+        }
+    }
+    for (minigame_entity, area) in wanted {
+        let lit = highlight_query
+            .iter()
+            .any(|(_, child_of)| child_of.parent() == minigame_entity);
+        if lit {
+            continue;
+        }
+        commands.entity(minigame_entity).with_child((
+            AuraHighlight,
+            Sprite::from_color(
+                HIGHLIGHT_COLOR.with_alpha(MIN_ALPHA),
+                area.dimensions(),
+            ),
+            // over the minigame's background, under its contents
+            Transform::from_xyz(0.0, 0.0, -0.5),
+        ));
+    }
+}
+
+pub fn pulse_highlights(
+    time: Res<Time<Real>>,
+    mut highlight_query: Query<&mut Sprite, With<AuraHighlight>>,
+) {
+    let alpha = pulse_alpha(time.elapsed_secs());
+    for mut sprite in highlight_query.iter_mut() {
+        sprite.color.set_alpha(alpha);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pulse_stays_faint() {
+        assert_eq!(pulse_alpha(0.0), MIN_ALPHA);
+        assert!((pulse_alpha(PULSE_SECONDS / 2.0) - MAX_ALPHA).abs() < 1e-6);
+        for step in 0..100 {
+            let alpha = pulse_alpha(step as f32 * 0.05);
+            assert!((MIN_ALPHA - 1e-6..=MAX_ALPHA + 1e-6).contains(&alpha));
+        }
+    }
+}
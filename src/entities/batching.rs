@@ -0,0 +1,127 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Idle production, batched. A minigame that turns out an item at a time on
+// its own (the quarry's ore, the composter's soil) passes each through
+// `IdleBatches::batch` before putting it out. While the player is near, it
+// comes straight back; while the player is more than `NEAR_DISTANCE` away,
+// it's held, and comes back only once `BATCH_AMOUNT` of its type has built
+// up, as one item. So an unwatched minigame puts out a tenth of the items,
+// and nothing piles up that nobody is looking at.
+//
+// Tree fruit isn't batched. It doesn't come out on its own: a few at most
+// wait on the tree to be picked by hand, so an unwatched tree stops at that
+// and makes no items to batch.
+//
+// Held matter isn't in the world, so the amount audit counts it as emitted
+// when the batch is put out. `put_out_near_batches` puts out what's held for
+// each minigame the player comes near. Batches are kept by minigame id, so
+// they outlive a levelup.
+
+// about a screen away
+pub const NEAR_DISTANCE: f32 = 800.0;
+pub const BATCH_AMOUNT: f32 = 10.0;
+
+#[derive(Debug, Default, Resource)]
+pub struct IdleBatches {
+    // minigames the player is far from, as of the last tick
+    far: HashSet<&'static str>,
+    held: HashMap<(&'static str, ItemType), f32>,
+}
+
+impl IdleBatches {
+    // What to put out now for `item`, just made by the minigame `id`.
+    pub fn batch(&mut self, id: &'static str, item: Item) -> Option<Item> {
+        if !self.far.contains(id) {
+            return Some(item);
+        }
+        let held = self.held.entry((id, item.r#type)).or_default();
+        *held += item.amount;
+        if *held < BATCH_AMOUNT {
+            return None;
+        }
+        let batch = Item::new(item.r#type, *held);
+        self.held.remove(&(id, item.r#type));
+        Some(batch)
+    }
+
+    // Everything held for the minigame `id`, as one item a type.
+    fn take(&mut self, id: &str) -> Vec<Item> {
+        let mut taken = Vec::new();
+        self.held.retain(|(held_id, r#type), amount| {
+            if *held_id != id {
+                return true;
+            }
+            taken.push(Item::new(*r#type, *amount));
+            false
+        });
+        taken
+    }
+}
+
+pub fn put_out_near_batches(
+    mut commands: Commands,
+    mut batches: ResMut<IdleBatches>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    player_query: Query<&Transform, With<Player>>,
+    minigame_query: Query<(&Minigame, &GlobalTransform, &RectangularArea)>,
+) {
+    let Ok(player) = player_query.single() else {
+        return;
+    };
+    let player = player.translation.truncate();
+    for (minigame, transform, area) in minigame_query.iter() {
+        let id = minigame.id();
+        let center = transform.translation().truncate();
+        if player.distance(center) > NEAR_DISTANCE {
+            batches.far.insert(id);
+            continue;
+        }
+        if !batches.far.remove(id) {
+            continue;
+        }
+        for item in batches.take(id) {
+            commands.spawn(ItemBundle::new_from_minigame(
+                &mut images,
+                &mut generated_image_assets,
+                item,
+                transform,
+                area,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn amount(item: Option<Item>) -> Option<f32> {
+        item.map(|item| item.amount)
+    }
+
+    #[test]
+    fn far_output_comes_out_in_batches() {
+        let copper = Item::ore(Substance::Copper, 1.0);
+        let tin = Item::ore(Substance::Tin, 1.0);
+        let mut batches = IdleBatches::default();
+        assert_eq!(amount(batches.batch("quarry", copper)), Some(1.0));
+        batches.far.insert("quarry");
+        for _ in 0..9 {
+            assert_eq!(amount(batches.batch("quarry", copper)), None);
+        }
+        assert_eq!(amount(batches.batch("quarry", tin)), None);
+        let batch = batches.batch("quarry", copper).unwrap();
+        assert_eq!(batch.r#type, copper.r#type);
+        assert_eq!(batch.amount, BATCH_AMOUNT);
+        let rest = batches.take("quarry");
+        assert_eq!(rest.len(), 1);
+        assert_eq!((rest[0].r#type, rest[0].amount), (tin.r#type, 1.0));
+        assert!(batches.held.is_empty());
+    }
+}
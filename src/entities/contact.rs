@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::minigames::ball_breaker::{Ball, Block, Paddle};
+use crate::entities::*;
+
+// Contacts starting, sorted once. Rapier reports every pair that starts
+// touching as a `CollisionEvent`; `classify_contacts` runs right after it, in
+// PostUpdate, and passes each pair on as the kind of contact it is, with its
+// entities in a fixed order. Each system that reacts to contacts reads only
+// the kind it cares about, so none of them walks the whole stream or works
+// out which side is which, and those that share no other data can run side
+// by side.
+//
+// The entities are named as they were when Rapier reported them; a reader
+// still checks they have what it needs, since they can lose it (or be gone)
+// by the time it runs.
+
+// Two items.
+#[derive(Message, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ItemItem(pub Entity, pub Entity);
+
+// The player and an item.
+#[derive(Message, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PlayerItem {
+    pub player: Entity,
+    pub item: Entity,
+    // whether Rapier named the player first, which says which of the contact
+    // manifold's normals is the player's
+    pub player_first: bool,
+}
+
+// An item entering a minigame's aura.
+#[derive(Message, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ItemAura {
+    pub item: Entity,
+    pub aura: Entity,
+}
+
+// A Ball Breaker ball hitting a block or the paddle.
+#[derive(Message, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BallBlock {
+    pub ball: Entity,
+    pub block: Entity,
+}
+
+// What an entity is, as far as contacts go.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Body {
+    Item,
+    Player,
+    Aura,
+    Ball,
+    // a block or a paddle
+    Block,
+    Other,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Contact {
+    ItemItem(ItemItem),
+    PlayerItem(PlayerItem),
+    ItemAura(ItemAura),
+    BallBlock(BallBlock),
+}
+
+// The kind of contact two bodies make, if it's one anything reacts to.
+pub fn classify(
+    (entity1, body1): (Entity, Body),
+    (entity2, body2): (Entity, Body),
+) -> Option<Contact> {
+    use Body::*;
+    let contact = match (body1, body2) {
+        (Item, Item) => Contact::ItemItem(ItemItem(entity1, entity2)),
+        (Player, Item) | (Item, Player) => {
+            let player_first = body1 == Player;
+            let (player, item) = if player_first {
+                (entity1, entity2)
+            } else {
+                (entity2, entity1)
+            };
+            Contact::PlayerItem(PlayerItem {
+                player,
+                item,
+                player_first,
+            })
+        }
+        (Item, Aura) => Contact::ItemAura(ItemAura {
+            item: entity1,
+            aura: entity2,
+        }),
+        (Aura, Item) => Contact::ItemAura(ItemAura {
+            item: entity2,
+            aura: entity1,
+        }),
+        (Ball, Block) => Contact::BallBlock(BallBlock {
+            ball: entity1,
+            block: entity2,
+        }),
+        (Block, Ball) => Contact::BallBlock(BallBlock {
+            ball: entity2,
+            block: entity1,
+        }),
+        _ => return None,
+    };
+    Some(contact)
+}
+
+pub fn classify_contacts(
+    mut collision_events: MessageReader<CollisionEvent>,
+    body_query: Query<(
+        Has<Item>,
+        Has<Player>,
+        Has<MinigameAura>,
+        Has<Ball>,
+        Has<Block>,
+        Has<Paddle>,
+    )>,
+    mut item_item: MessageWriter<ItemItem>,
+    mut player_item: MessageWriter<PlayerItem>,
+    mut item_aura: MessageWriter<ItemAura>,
+    mut ball_block: MessageWriter<BallBlock>,
+) {
+    let body = |entity: Entity| {
+        let Ok((item, player, aura, ball, block, paddle)) =
+            body_query.get(entity)
+        else {
+            return Body::Other;
+        };
+        if item {
+            Body::Item
+        } else if player {
+            Body::Player
+        } else if aura {
+            Body::Aura
+        } else if ball {
+            Body::Ball
+        } else if block || paddle {
+            Body::Block
+        } else {
+            Body::Other
+        }
+    };
+    for event in collision_events.read() {
+        let CollisionEvent::Started(entity1, entity2, _) = event else {
+            continue;
+        };
+        let contact =
+            classify((*entity1, body(*entity1)), (*entity2, body(*entity2)));
+        match contact {
+            Some(Contact::ItemItem(contact)) => {
+                item_item.write(contact);
+            }
+            Some(Contact::PlayerItem(contact)) => {
+                player_item.write(contact);
+            }
+            Some(Contact::ItemAura(contact)) => {
+                item_aura.write(contact);
+            }
+            Some(Contact::BallBlock(contact)) => {
+                ball_block.write(contact);
+            }
+            None => {}
+        }
+    }
+}
+
+// The contact messages, and the pass that sorts Rapier's into them.
+pub struct ContactPlugin;
+
+impl Plugin for ContactPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_message::<ItemItem>()
+            .add_message::<PlayerItem>()
+            .add_message::<ItemAura>()
+            .add_message::<BallBlock>()
+            .add_systems(
+                PostUpdate,
+                classify_contacts.after(PhysicsSet::Writeback),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contacts_name_their_entities_in_order() {
+        let [a, b] = [1, 2].map(|i| Entity::from_raw_u32(i).unwrap());
+        assert_eq!(
+            classify((a, Body::Item), (b, Body::Item)),
+            Some(Contact::ItemItem(ItemItem(a, b)))
+        );
+        assert_eq!(
+            classify((a, Body::Item), (b, Body::Player)),
+            Some(Contact::PlayerItem(PlayerItem {
+                player: b,
+                item: a,
+                player_first: false,
+            }))
+        );
+        assert_eq!(
+            classify((a, Body::Aura), (b, Body::Item)),
+            Some(Contact::ItemAura(ItemAura { item: b, aura: a }))
+        );
+        assert_eq!(
+            classify((a, Body::Block), (b, Body::Ball)),
+            Some(Contact::BallBlock(BallBlock { ball: b, block: a }))
+        );
+    }
+
+    #[test]
+    fn other_contacts_are_dropped() {
+        let [a, b] = [1, 2].map(|i| Entity::from_raw_u32(i).unwrap());
+        for (body1, body2) in [
+            (Body::Item, Body::Other),
+            (Body::Player, Body::Aura),
+            (Body::Ball, Body::Item),
+            (Body::Aura, Body::Aura),
+            (Body::Other, Body::Other),
+        ] {
+            assert_eq!(classify((a, body1), (b, body2)), None);
+        }
+    }
+}
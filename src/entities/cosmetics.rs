@@ -0,0 +1,291 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// How the player looks. Each `Skin` in `SKINS` is a palette and a pattern,
+// drawn with `image_gen` into a texture laid over the player's circle. The
+// plain one is always there; the others are earned, like the magnet, by a
+// minigame reaching a level, and announced in the event log when they are.
+// The F1 settings panel cycles through the earned ones (3), and the save
+// keeps the one chosen. A chosen skin not earned yet (as on a save imported
+// before its levels are back) shows as plain until it is.
+
+pub const PLAIN: &str = "plain";
+// the player's diameter, and so the texture's
+const SKIN_SIZE: u32 = 50;
+const OUTLINE: [u8; 3] = [0, 0, 0];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    Plain,
+    // horizontal bands
+    Stripes,
+    // a scatter of dots, the same every time
+    Spots,
+    // rings out from the middle
+    Rings,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Skin {
+    pub id: &'static str,
+    pub base: [u8; 3],
+    pub accent: [u8; 3],
+    pub pattern: Pattern,
+    // the minigame level that earns it; None for always
+    pub earned_by: Option<(&'static str, u8)>,
+}
+
+pub const SKINS: [Skin; 5] = [
+    Skin {
+        id: PLAIN,
+        base: [160, 240, 232],
+        accent: [160, 240, 232],
+        pattern: Pattern::Plain,
+        earned_by: None,
+    },
+    Skin {
+        id: "tide",
+        base: [60, 120, 200],
+        accent: [200, 230, 255],
+        pattern: Pattern::Stripes,
+        earned_by: Some((minigames::primordial_ocean::ID, 3)),
+    },
+    Skin {
+        id: "glyph",
+        base: [90, 50, 140],
+        accent: [230, 200, 90],
+        pattern: Pattern::Rings,
+        earned_by: Some((minigames::rune::ID, 3)),
+    },
+    Skin {
+        id: "ember",
+        base: [200, 80, 30],
+        accent: [255, 200, 60],
+        pattern: Pattern::Spots,
+        earned_by: Some((minigames::foundry::ID, 2)),
+    },
+    Skin {
+        id: "moss",
+        base: [70, 120, 50],
+        accent: [150, 190, 90],
+        pattern: Pattern::Spots,
+        earned_by: Some((minigames::composter::ID, 2)),
+    },
+];
+
+impl Skin {
+    pub fn by_id(id: &str) -> Option<&'static Skin> {
+        SKINS.iter().find(|skin| skin.id == id)
+    }
+
+    pub fn earned(&self, minigames: &MinigamesResource) -> bool {
+        self.earned_by
+            .is_none_or(|(id, level)| minigames.level(id) >= level)
+    }
+
+    pub fn name(&self) -> String {
+        tr(&format!("skin.{}", self.id))
+    }
+
+    // Which colour the pixel `offset` from the middle takes, `radius` being
+    // the skin's.
+    fn pixel(&self, offset: Vec2, radius: f32) -> [u8; 3] {
+        let accented = match self.pattern {
+            Pattern::Plain => false,
+            Pattern::Stripes => (offset.y / 6.0).floor() as i32 % 2 == 0,
+            Pattern::Spots => {
+                let cell = (offset / 10.0).floor() * 10.0 + 5.0;
+                offset.distance(cell) < 3.0
+            }
+            Pattern::Rings => (offset.length() / 5.0) as i32 % 2 == 1,
+        };
+        if radius - offset.length() < 1.0 {
+            OUTLINE
+        } else if accented {
+            self.accent
+        } else {
+            self.base
+        }
+    }
+
+    pub fn draw(&self, size: u32) -> Image {
+        let radius = size as f32 / 2.0;
+        let mut colors = image_gen::Colors::new(size, size);
+        for y in 0..size {
+            for x in 0..size {
+                let offset = Vec2::new(x as f32, y as f32) + 0.5 - radius;
+                if offset.length() > radius {
+                    colors.add_color(image_gen::Color::new_clear());
+                    continue;
+                }
+                let [red, green, blue] = self.pixel(offset, radius);
+                colors.add_color(image_gen::Color::new(red, green, blue, 255));
+            }
+        }
+        colors.to_image()
+    }
+}
+
+// The player's chosen skin, by id, and the ones already announced.
+#[derive(Debug, Clone, Resource)]
+pub struct Cosmetics {
+    pub selected: String,
+    announced: Vec<&'static str>,
+}
+
+impl Default for Cosmetics {
+    fn default() -> Self {
+        Self {
+            selected: PLAIN.to_string(),
+            announced: Vec::new(),
+        }
+    }
+}
+
+impl Cosmetics {
+    // What the player wears: the chosen skin once it's earned, else plain.
+    pub fn shown(&self, minigames: &MinigamesResource) -> &'static Skin {
+        Skin::by_id(&self.selected)
+            .filter(|skin| skin.earned(minigames))
+            .unwrap_or(&SKINS[0])
+    }
+
+    // Chooses `id`, if there's such a skin.
+    pub fn select(&mut self, id: &str) {
+        if Skin::by_id(id).is_some() {
+            self.selected = id.to_string();
+        }
+    }
+
+    // Chooses the next earned skin after the one shown, round to plain.
+    pub fn cycle(&mut self, minigames: &MinigamesResource) {
+        let shown = self.shown(minigames).id;
+        let at = SKINS.iter().position(|skin| skin.id == shown).unwrap_or(0);
+        let next = (1..=SKINS.len())
+            .map(|step| &SKINS[(at + step) % SKINS.len()])
+            .find(|skin| skin.earned(minigames))
+            .unwrap_or(&SKINS[0]);
+        self.selected = next.id.to_string();
+    }
+
+    // The line in the settings panel.
+    pub fn settings_line(&self, minigames: &MinigamesResource) -> String {
+        let earned = SKINS.iter().filter(|skin| skin.earned(minigames)).count();
+        tr_fmt(
+            "skin.setting",
+            &[
+                ("skin", &self.shown(minigames).name()),
+                ("earned", &earned),
+                ("total", &SKINS.len()),
+            ],
+        )
+    }
+}
+
+// The texture over the player, naming the skin it shows.
+#[derive(Debug, Component)]
+pub struct PlayerSkin(pub &'static str);
+
+// Announce each skin as it's earned. The plain one goes without saying.
+pub fn announce_skins(
+    minigames: Res<MinigamesResource>,
+    mut cosmetics: ResMut<Cosmetics>,
+    mut game_events: MessageWriter<GameEvent>,
+) {
+    if !minigames.is_changed() {
+        return;
+    }
+    for skin in SKINS.iter().filter(|skin| skin.earned_by.is_some()) {
+        if !skin.earned(&minigames) || cosmetics.announced.contains(&skin.id) {
+            continue;
+        }
+        cosmetics.announced.push(skin.id);
+        game_events.write(GameEvent::SkinEarned { skin: skin.name() });
+    }
+}
+
+// Lay the shown skin over the player, when it changes.
+pub fn dress_player(
+    mut commands: Commands,
+    cosmetics: Res<Cosmetics>,
+    minigames: Res<MinigamesResource>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    player_query: Query<Entity, With<Player>>,
+    skin_query: Query<(Entity, &PlayerSkin)>,
+) {
+    let Ok(player_entity) = player_query.single() else {
+        return;
+    };
+    let skin = cosmetics.shown(&minigames);
+    let mut dressed = false;
+    for (entity, worn) in skin_query.iter() {
+        if worn.0 == skin.id {
+            dressed = true;
+        } else {
+            commands.entity(entity).despawn();
+        }
+    }
+    if dressed {
+        return;
+    }
+    let uid = format!("skin/{}", skin.id);
+    let image = generated_image_assets.get(&uid).unwrap_or_else(|| {
+        let handle = images.add(skin.draw(SKIN_SIZE));
+        generated_image_assets.insert(uid, &handle);
+        handle
+    });
+    commands.entity(player_entity).with_child((
+        PlayerSkin(skin.id),
+        Sprite {
+            image,
+            custom_size: Some(Vec2::splat(SKIN_SIZE as f32)),
+            ..default()
+        },
+        // over the circle, under what the player carries
+        Transform::from_xyz(0.0, 0.0, 0.1),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skins_are_earned_by_levels() {
+        let mut minigames = MinigamesResource::default();
+        minigames.insert(minigames::primordial_ocean::ID, vec![]);
+        let mut cosmetics = Cosmetics::default();
+        cosmetics.select("tide");
+        assert_eq!(cosmetics.shown(&minigames).id, PLAIN);
+        cosmetics.cycle(&minigames);
+        assert_eq!(cosmetics.selected, PLAIN, "only plain to cycle through");
+        let ocean = Minigame::from_id(minigames::primordial_ocean::ID).unwrap();
+        for _ in 0..3 {
+            minigames.set_level(&ocean);
+        }
+        cosmetics.cycle(&minigames);
+        assert_eq!(cosmetics.shown(&minigames).id, "tide");
+        cosmetics.cycle(&minigames);
+        assert_eq!(cosmetics.shown(&minigames).id, PLAIN);
+        cosmetics.select("no such skin");
+        assert_eq!(cosmetics.selected, PLAIN);
+    }
+
+    #[test]
+    fn skins_are_round_and_outlined() {
+        let skin = Skin::by_id("ember").unwrap();
+        let image = skin.draw(SKIN_SIZE);
+        let pixel = |x: u32, y: u32| {
+            let at = ((y * SKIN_SIZE + x) * 4) as usize;
+            image.data.as_ref().unwrap()[at..at + 4].to_vec()
+        };
+        assert_eq!(pixel(0, 0)[3], 0, "corners are clear");
+        assert_eq!(pixel(SKIN_SIZE / 2, 0), vec![0, 0, 0, 255]);
+        let middle = pixel(SKIN_SIZE / 2, SKIN_SIZE / 2);
+        assert_eq!(middle[3], 255);
+        assert!(SKINS.iter().all(|skin| Skin::by_id(skin.id).is_some()));
+    }
+}
@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// `crate::entities::*` exports an item `Shape` enum too; the trail's is
+// bevy_prototype_lyon's.
+use bevy_prototype_lyon::prelude::Shape;
+
+// bevy_rapier2d's prelude exports a `Real` too; the clock's is Bevy's.
+use bevy::time::Real;
+
+// The player's dash and trail. Tapping a direction key twice within
+// `dash.double_tap_seconds` throws the player that way with one big impulse,
+// after which the dash rests for `dash.cooldown_seconds` (assets/balance.ron).
+// Space already switches stickiness, so it's double taps only. While the
+// player moves fast they leave a trail of fading discs, so speed reads on
+// screen.
+
+const DIRECTIONS: [(KeyCode, Vec2); 4] = [
+    (KeyCode::KeyW, Vec2::Y),
+    (KeyCode::KeyS, Vec2::NEG_Y),
+    (KeyCode::KeyA, Vec2::NEG_X),
+    (KeyCode::KeyD, Vec2::X),
+];
+// pixels per second the player must be going to leave a trail
+pub const TRAIL_MIN_SPEED: f32 = 400.0;
+const TRAIL_INTERVAL: f32 = 0.03;
+pub const TRAIL_SECONDS: f32 = 0.4;
+const TRAIL_COLOR: Color = Color::srgba(0.625, 0.94, 0.91, 0.5);
+
+#[derive(Debug, Default, Resource)]
+pub struct Dash {
+    // seconds until the player can dash again
+    pub cooldown: f32,
+    // the last direction key pressed, and when
+    last_tap: Option<(KeyCode, f32)>,
+}
+
+impl Dash {
+    // Records a press of `key` at `now` seconds. Whether it's the second of a
+    // double tap: the same key again within `window` seconds. A third tap
+    // starts over.
+    pub fn tap(&mut self, key: KeyCode, now: f32, window: f32) -> bool {
+        let double = matches!(
+            self.last_tap,
+            Some((last, at)) if last == key && now - at <= window
+        );
+        self.last_tap = if double { None } else { Some((key, now)) };
+        double
+    }
+}
+
+// A disc left behind where the player was, shrinking and fading over
+// `TRAIL_SECONDS`.
+#[derive(Debug, Component)]
+pub struct TrailMark {
+    pub seconds_left: f32,
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct Trail {
+    since_mark: f32,
+}
+
+pub fn dash(
+    time: Res<Time<Real>>,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    balance: Res<BalanceConfig>,
+    mut dash: ResMut<Dash>,
+    mut player_query: Query<&mut ExternalImpulse, With<Player>>,
+) {
+    dash.cooldown = (dash.cooldown - time.delta_secs()).max(0.0);
+    let now = time.elapsed_secs();
+    for (key, direction) in DIRECTIONS {
+        if !kb_input.just_pressed(key) {
+            continue;
+        }
+        let double = dash.tap(key, now, balance.dash.double_tap_seconds);
+        if !double || dash.cooldown > 0.0 {
+            continue;
+        }
+        dash.cooldown = balance.dash.cooldown_seconds;
+        for mut external_impulse in player_query.iter_mut() {
+            external_impulse.impulse += direction * balance.dash.impulse;
+        }
+    }
+}
+
+pub fn leave_trail(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut trail: ResMut<Trail>,
+    player_query: Query<(&Transform, &Velocity, &CircularArea), With<Player>>,
+) {
+    trail.since_mark += time.delta_secs();
+    if trail.since_mark < TRAIL_INTERVAL {
+        return;
+    }
+    for (transform, velocity, area) in player_query.iter() {
+        if velocity.linear.length() < TRAIL_MIN_SPEED {
+            continue;
+        }
+        trail.since_mark = 0.0;
+        commands.spawn((
+            TrailMark {
+                seconds_left: TRAIL_SECONDS,
+            },
+            ShapeBuilder::with(&shapes::Circle {
+                radius: area.radius,
+                ..default()
+            })
+            .fill(Fill::color(TRAIL_COLOR))
+            .build(),
+            // under the player
+            Transform::from_translation(transform.translation - Vec3::Z * 0.5),
+        ));
+    }
+}
+
+pub fn fade_trail(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut mark_query: Query<(Entity, &mut TrailMark, &mut Shape, &mut Transform)>,
+) {
+    for (mark_entity, mut mark, mut shape, mut transform) in
+        mark_query.iter_mut()
+    {
+        mark.seconds_left -= time.delta_secs();
+        if mark.seconds_left <= 0.0 {
+            commands.entity(mark_entity).despawn();
+            continue;
+        }
+        let left = mark.seconds_left / TRAIL_SECONDS;
+        transform.scale = Vec3::splat(left);
+        if let Some(mut fill) = shape.fill {
+            fill.color.set_alpha(TRAIL_COLOR.alpha() * left);
+            shape.fill = Some(fill);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn double_taps_are_the_same_key_in_time() {
+        let mut dash = Dash::default();
+        assert!(!dash.tap(KeyCode::KeyW, 0.0, 0.3));
+        assert!(dash.tap(KeyCode::KeyW, 0.2, 0.3));
+        // a third tap starts over
+        assert!(!dash.tap(KeyCode::KeyW, 0.3, 0.3));
+        // too slow
+        assert!(!dash.tap(KeyCode::KeyW, 0.7, 0.3));
+        // another key in between
+        assert!(!dash.tap(KeyCode::KeyA, 0.8, 0.3));
+        assert!(!dash.tap(KeyCode::KeyW, 0.9, 0.3));
+        assert!(dash.tap(KeyCode::KeyW, 1.0, 0.3));
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Minigame output, let out one item at a time. `ItemBundle::new_from_minigame`
+// puts an item at the minigame's emit point hidden and out of the physics, as
+// `Ejecting`; it counts as put out there at once (for the audit, routing and
+// the throughput meters). `eject_items` then lets each minigame's items out in
+// the order they came, no faster than one every `EJECT_SECONDS`, each at the
+// first point along the minigame's right edge with room for it. So a burst of
+// output comes out spread along the edge rather than stacked inside itself,
+// where it used to merge all at once or shoot through walls. An item that
+// finds no room in `PATIENCE_SECONDS` comes out at the emit point anyway.
+// Let out, it grows to its size over `EMERGE_SECONDS`.
+
+pub const EJECT_SECONDS: f32 = 0.1;
+const PATIENCE_SECONDS: f32 = 2.0;
+const EMERGE_SECONDS: f32 = 0.25;
+// how big it starts, of its full size
+const EMERGE_FROM: f32 = 0.4;
+// room kept between an item let out and those already out
+const GAP: f32 = 2.0;
+// as `ItemBundle::eject_from_minigame` sends them
+const EJECT_VELOCITY: Vec2 = Vec2::new(70.0, -70.0);
+
+// Waiting to be let out of the minigame at `center` with `area`, as they were
+// when it was put out.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct Ejecting {
+    pub center: Vec2,
+    pub area: RectangularArea,
+    pub waited: f32,
+}
+
+impl Ejecting {
+    // Which queue it's in: one a minigame, by where its emit point is. Kept
+    // apart from the minigame's entity, which a levelup replaces.
+    fn queue(&self, at: Vec2) -> IVec2 {
+        at.round().as_ivec2()
+    }
+}
+
+// Just let out, growing to its size.
+#[derive(Debug, Copy, Clone, Default, Component)]
+pub struct Emerging {
+    pub elapsed: f32,
+}
+
+// What `new_from_minigame` adds to an item so it waits its turn.
+pub fn waiting(
+    minigame_global_transform: &GlobalTransform,
+    minigame_area: &RectangularArea,
+) -> (Ejecting, ColliderDisabled, RigidBodyDisabled, Visibility) {
+    (
+        Ejecting {
+            center: minigame_global_transform.translation().truncate(),
+            area: *minigame_area,
+            waited: 0.0,
+        },
+        ColliderDisabled,
+        RigidBodyDisabled,
+        Visibility::Hidden,
+    )
+}
+
+// Seconds until each queue may let out its next item.
+#[derive(Debug, Default, Resource)]
+pub struct Ejections {
+    cooldowns: HashMap<IVec2, f32>,
+}
+
+// Where an item of `radius` may come out of a minigame at `center`: its emit
+// point first, then on down the right edge an item apart, as far as the
+// bottom.
+pub fn exit_points(
+    emit_point: Vec2,
+    center: Vec2,
+    area: &RectangularArea,
+    radius: f32,
+) -> Vec<Vec2> {
+    let step = radius * 2.0 + GAP;
+    let bottom = center.y + area.bottom();
+    (0..)
+        .map(|n| emit_point - Vec2::Y * step * n as f32)
+        .take_while(|point| point.y >= bottom)
+        .collect()
+}
+
+// Whether an item of `radius` at `point` keeps clear of every one of `others`,
+// each a position and radius.
+pub fn has_room(point: Vec2, radius: f32, others: &[(Vec2, f32)]) -> bool {
+    others.iter().all(|(other, other_radius)| {
+        point.distance(*other) >= radius + other_radius + GAP
+    })
+}
+
+pub fn eject_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ejections: ResMut<Ejections>,
+    item_grid: Res<ItemGrid>,
+    mut ejecting_query: Query<(
+        Entity,
+        &mut Ejecting,
+        &Transform,
+        &CircularArea,
+    )>,
+    loose_query: Query<
+        (&Transform, &CircularArea),
+        (With<Item>, Without<Ejecting>),
+    >,
+) {
+    let dt = time.delta_secs();
+    ejections.cooldowns.retain(|_, cooldown| {
+        *cooldown -= dt;
+        *cooldown > 0.0
+    });
+    if ejecting_query.is_empty() {
+        return;
+    }
+    // the longest waiting first, in each queue
+    let mut waiting: Vec<(IVec2, Ejecting, Entity, Vec3, f32)> = ejecting_query
+        .iter_mut()
+        .map(|(entity, mut ejecting, transform, area)| {
+            ejecting.waited += dt;
+            let at = transform.translation;
+            (
+                ejecting.queue(at.truncate()),
+                *ejecting,
+                entity,
+                at,
+                area.radius,
+            )
+        })
+        .collect();
+    waiting.sort_by(|a, b| {
+        (a.0.x, a.0.y)
+            .cmp(&(b.0.x, b.0.y))
+            .then(b.1.waited.total_cmp(&a.1.waited))
+            .then(a.2.cmp(&b.2))
+    });
+    // what's come out this tick, which the grid doesn't have yet
+    let mut let_out: Vec<(Vec2, f32)> = Vec::new();
+    let mut last_queue = None;
+    for (queue, ejecting, entity, at, radius) in waiting {
+        if last_queue == Some(queue) {
+            continue;
+        }
+        last_queue = Some(queue);
+        if ejections.cooldowns.contains_key(&queue) {
+            continue;
+        }
+        let exit =
+            exit_points(at.truncate(), ejecting.center, &ejecting.area, radius)
+                .into_iter()
+                .find(|point| {
+                    let reach = Vec2::splat(radius + Item::MAX_RADIUS + GAP);
+                    let nearby: Vec<(Vec2, f32)> = item_grid
+                        .in_rect(Rect::from_center_half_size(*point, reach))
+                        .into_iter()
+                        .filter_map(|other| loose_query.get(other).ok())
+                        .map(|(transform, area)| {
+                            (transform.translation.truncate(), area.radius)
+                        })
+                        .chain(let_out.iter().copied())
+                        .collect();
+                    has_room(*point, radius, &nearby)
+                });
+        let exit = match exit {
+            Some(exit) => exit,
+            None if ejecting.waited >= PATIENCE_SECONDS => at.truncate(),
+            None => continue,
+        };
+        release(&mut commands, entity, exit.extend(at.z));
+        let_out.push((exit, radius));
+        ejections.cooldowns.insert(queue, EJECT_SECONDS);
+    }
+}
+
+fn release(commands: &mut Commands, entity: Entity, at: Vec3) {
+    commands
+        .entity(entity)
+        .insert((
+            Transform::from_translation(at),
+            Velocity::linear(EJECT_VELOCITY),
+            Visibility::Inherited,
+            Emerging::default(),
+        ))
+        .remove::<(Ejecting, ColliderDisabled, RigidBodyDisabled)>();
+}
+
+pub fn grow_ejected_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut item_query: Query<(Entity, &mut Emerging, &mut Sprite, &CircularArea)>,
+) {
+    for (entity, mut emerging, mut sprite, area) in item_query.iter_mut() {
+        emerging.elapsed += time.delta_secs();
+        let grown = (emerging.elapsed / EMERGE_SECONDS).min(1.0);
+        let size: Vec2 = (*area).into();
+        let scale = EMERGE_FROM + (1.0 - EMERGE_FROM) * grown;
+        sprite.custom_size = Some(size * scale);
+        if grown >= 1.0 {
+            commands.entity(entity).remove::<Emerging>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_points_run_down_the_right_edge() {
+        let area = RectangularArea::new(120.0, 90.0);
+        let center = Vec2::new(100.0, 0.0);
+        let emit = Vec2::new(180.0, 60.0);
+        let points = exit_points(emit, center, &area, 10.0);
+        assert_eq!(points[0], emit);
+        assert_eq!(points[1], Vec2::new(180.0, 38.0));
+        assert!(points.iter().all(|point| point.x == emit.x));
+        let last = points.last().unwrap();
+        assert!(last.y >= center.y + area.bottom());
+        assert!(last.y - 22.0 < center.y + area.bottom());
+    }
+
+    #[test]
+    fn room_is_kept_from_every_other_item() {
+        let others = [(Vec2::ZERO, 10.0), (Vec2::new(100.0, 0.0), 5.0)];
+        assert!(!has_room(Vec2::new(15.0, 0.0), 6.0, &others));
+        assert!(has_room(Vec2::new(50.0, 0.0), 6.0, &others));
+        assert!(!has_room(Vec2::new(90.0, 0.0), 6.0, &others));
+        assert!(has_room(Vec2::ZERO, 10.0, &[]));
+    }
+}
@@ -0,0 +1,210 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Electricity. Every `electric.arc_seconds`, each loose Electric energy item
+// arcs to the nearest metal item (see `heat::is_metal`) within
+// `electric.arc_range`, moving up to `electric.arc_amount` of itself into the
+// metal's `Charge`, and a bolt flashes between them. A charged metal that
+// touches the battery's aura deposits its charge there as Electric energy, as
+// much as the battery takes, and stays where it is. Metals that merge pool
+// their charge, on contact or when crowded; one ingested anywhere else takes
+// its charge with it.
+
+const BOLT_COLOR: Color = Color::srgb(0.75, 0.85, 1.0);
+const BOLT_THICKNESS: f32 = 2.0;
+const BOLT_SECONDS: f32 = 0.25;
+// corners along the bolt, and how far each strays from the straight line
+const BOLT_KINKS: usize = 6;
+const BOLT_JITTER: f32 = 6.0;
+
+// Electric energy held by a metal item, not counted in its amount.
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct Charge(pub f32);
+
+#[derive(Debug, Copy, Clone, Component)]
+pub struct Bolt {
+    seconds_left: f32,
+}
+
+pub fn is_electric(item: &Item) -> bool {
+    matches!(
+        item.r#type,
+        ItemType::Energy(EnergyItem {
+            kind: EnergyKind::Electric
+        })
+    )
+}
+
+// The closest of `metals` to `from` within `range`.
+pub fn nearest_in_range(
+    from: Vec2,
+    range: f32,
+    metals: impl IntoIterator<Item = (Entity, Vec2)>,
+) -> Option<(Entity, Vec2)> {
+    metals
+        .into_iter()
+        .map(|(entity, at)| (entity, at, at.distance_squared(from)))
+        .filter(|&(_, _, distance_squared)| distance_squared <= range * range)
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(entity, at, _)| (entity, at))
+}
+
+// The bolt's corners from `from` to `to`, each kink pushed off the straight
+// line by up to `BOLT_JITTER` either way.
+pub fn bolt_points(from: Vec2, to: Vec2, random: &mut Random) -> Vec<Vec2> {
+    let across = (to - from).perp().normalize_or_zero();
+    let mut points = vec![from];
+    for kink in 1..BOLT_KINKS {
+        let along = from.lerp(to, kink as f32 / BOLT_KINKS as f32);
+        let jitter = (random.next_f32() * 2.0 - 1.0) * BOLT_JITTER;
+        points.push(along + across * jitter);
+    }
+    points.push(to);
+    points
+}
+
+pub fn arc_to_metals(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut due: Local<f32>,
+    mut random: ResMut<Random>,
+    balance: Res<BalanceConfig>,
+    item_grid: Res<ItemGrid>,
+    mut audit: ResMut<AmountAudit>,
+    mut item_query: Query<(Entity, &mut Item, &Transform)>,
+    mut charge_query: Query<&mut Charge>,
+) {
+    *due -= time.delta_secs();
+    if *due > 0.0 {
+        return;
+    }
+    let electric = &balance.electric;
+    *due = electric.arc_seconds;
+
+    let sources: Vec<(Entity, Vec2)> = item_query
+        .iter()
+        .filter(|(_, item, _)| is_electric(item))
+        .map(|(entity, _, transform)| {
+            (entity, transform.translation.truncate())
+        })
+        .collect();
+    for (source, from) in sources {
+        let metals = item_query
+            .iter_many(item_grid.near(from, electric.arc_range))
+            .filter(|(_, item, _)| heat::is_metal(item))
+            .map(|(entity, _, transform)| {
+                (entity, transform.translation.truncate())
+            });
+        let Some((metal, to)) =
+            nearest_in_range(from, electric.arc_range, metals)
+        else {
+            continue;
+        };
+        let Ok((_, mut item, _)) = item_query.get_mut(source) else {
+            continue;
+        };
+        let moved = item.amount.min(electric.arc_amount);
+        item.amount -= moved;
+        if item.amount <= 0.0 {
+            commands.entity(source).despawn();
+        }
+        audit.reacted -= moved;
+        if let Ok(mut charge) = charge_query.get_mut(metal) {
+            charge.0 += moved;
+        } else {
+            commands.entity(metal).insert(Charge(moved));
+        }
+        commands.spawn((
+            Bolt {
+                seconds_left: BOLT_SECONDS,
+            },
+            ShapeBuilder::with(&shapes::Polygon {
+                points: bolt_points(from, to, &mut random),
+                closed: false,
+            })
+            .stroke(Stroke::new(BOLT_COLOR, BOLT_THICKNESS))
+            .build(),
+            // over the items
+            Transform::from_xyz(0.0, 0.0, 2.0),
+        ));
+    }
+}
+
+pub fn fade_bolts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bolt_query: Query<(Entity, &mut Bolt, &mut Shape)>,
+) {
+    for (entity, mut bolt, mut shape) in bolt_query.iter_mut() {
+        bolt.seconds_left -= time.delta_secs();
+        if bolt.seconds_left <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        if let Some(mut stroke) = shape.stroke {
+            stroke.color.set_alpha(bolt.seconds_left / BOLT_SECONDS);
+            shape.stroke = Some(stroke);
+        }
+    }
+}
+
+// Charged metals touching the battery's aura hand their charge over.
+pub fn deposit_charges(
+    mut commands: Commands,
+    mut contacts: MessageReader<contact::ItemAura>,
+    aura_query: Query<&MinigameAura>,
+    mut minigame_query: Query<&mut Minigame, Without<LevelingUp>>,
+    mut charge_query: Query<(Entity, &mut Charge)>,
+) {
+    for contact in contacts.read() {
+        let metal = contact.item;
+        let Ok(aura) = aura_query.get(contact.aura) else {
+            continue;
+        };
+        let Ok(minigame) = minigame_query.get_mut(aura.minigame) else {
+            continue;
+        };
+        let Minigame::Battery(battery) = minigame.into_inner() else {
+            continue;
+        };
+        let Ok((metal, mut charge)) = charge_query.get_mut(metal) else {
+            continue;
+        };
+        let energy = Item::energy(EnergyKind::Electric, charge.0);
+        charge.0 -= battery.ingest_item(&mut commands, aura.minigame, &energy);
+        if charge.0 <= 0.0 {
+            commands.entity(metal).remove::<Charge>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arcs_reach_the_nearest_metal_in_range() {
+        let [near, far] = [1, 2].map(|i| Entity::from_raw_u32(i).unwrap());
+        let metals =
+            [(far, Vec2::new(30.0, 0.0)), (near, Vec2::new(0.0, 20.0))];
+        assert_eq!(
+            nearest_in_range(Vec2::ZERO, 50.0, metals).map(|(e, _)| e),
+            Some(near)
+        );
+        assert_eq!(nearest_in_range(Vec2::ZERO, 10.0, metals), None);
+    }
+
+    #[test]
+    fn bolts_run_end_to_end_and_stray_a_little() {
+        let mut random = Random::new(1);
+        let (from, to) = (Vec2::ZERO, Vec2::new(100.0, 0.0));
+        let points = bolt_points(from, to, &mut random);
+        assert_eq!(points.len(), BOLT_KINKS + 1);
+        assert_eq!(points.first(), Some(&from));
+        assert_eq!(points.last(), Some(&to));
+        assert!(points.iter().all(|point| point.y.abs() <= BOLT_JITTER));
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Gases. A loose gas item thins out into the air: each tick it loses
+// `gas.dissipate_per_tick` of its amount, and once it's down to
+// `gas.min_amount` it's gone. Only a flask keeps it, by ingesting it; a gas
+// carried by the player goes on thinning on the way there. A gas just put
+// out starts thinning the tick after, once the amount audit has counted it
+// at the amount it came out with.
+
+pub fn is_gas(item: &Item) -> bool {
+    matches!(
+        item.r#type,
+        ItemType::Physical(PhysicalItem::Bulk(bulk))
+            if bulk.structure == BulkStructure::Gas
+    )
+}
+
+// What's left of `amount` after a tick out in the open.
+pub fn dissipated(amount: f32, gas: &GasBalance) -> f32 {
+    amount * (1.0 - gas.dissipate_per_tick.clamp(0.0, 1.0))
+}
+
+pub fn dissipate_gases(
+    mut commands: Commands,
+    balance: Res<BalanceConfig>,
+    mut audit: ResMut<AmountAudit>,
+    mut item_query: Query<(Entity, &mut Item), Without<Emitted>>,
+) {
+    let gas = &balance.gas;
+    for (entity, mut item) in item_query.iter_mut() {
+        if !is_gas(&item) {
+            continue;
+        }
+        let left = dissipated(item.amount, gas);
+        if left <= gas.min_amount {
+            audit.dissipated += item.amount;
+            commands.entity(entity).despawn();
+        } else {
+            audit.dissipated += item.amount - left;
+            item.amount = left;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_gases_dissipate() {
+        assert!(is_gas(&Item::gas(Substance::FreshWater, 1.0)));
+        assert!(!is_gas(&Item::liquid(Substance::FreshWater, 1.0)));
+        assert!(!is_gas(&Item::powder(Substance::Salt, 1.0)));
+        assert!(!is_gas(&Item::fruit(Species::Apple, 1.0)));
+    }
+
+    #[test]
+    fn dissipates_a_share_a_tick() {
+        let gas = GasBalance {
+            dissipate_per_tick: 0.1,
+            min_amount: 0.5,
+        };
+        assert!((dissipated(10.0, &gas) - 9.0).abs() < 1e-6);
+        assert!((dissipated(1.0, &gas) - 0.9).abs() < 1e-6);
+    }
+}
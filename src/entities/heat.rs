@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+
+// Heat. What a Thermal energy item does to what it touches is a set of rules
+// in `reaction::REACTION_RULES`:
+// - fresh water warms, which does nothing yet;
+// - living wood (trees and bushes) burns, leaving its corpse and giving off
+//   `heat.burn_heat` Thermal energy a unit, without using up the heat that
+//   lit it;
+// - a metal softens, if the heat holds `heat.soften_heat` a unit of it, which
+//   the softening spends. A softened metal (`Softened`, tinted) smelts for
+//   less heat in the foundry. Merging with another item cools it again.
+// The matchers and what burning leaves are here.
+
+pub const SOFTENED_TINT: Color = Color::srgb(1.0, 0.65, 0.45);
+
+#[derive(Debug, Default, Copy, Clone, Component)]
+#[component(storage = "SparseSet")]
+pub struct Softened;
+
+pub fn is_thermal(item: &Item) -> bool {
+    matches!(
+        item.r#type,
+        ItemType::Energy(EnergyItem {
+            kind: EnergyKind::Thermal
+        })
+    )
+}
+
+pub fn is_fresh_water(item: &Item) -> bool {
+    matches!(
+        item.r#type,
+        ItemType::Physical(PhysicalItem::Bulk(bulk))
+            if bulk.substance == Substance::FreshWater
+    )
+}
+
+pub fn is_living_wood(item: &Item) -> bool {
+    matches!(
+        item.r#type,
+        ItemType::Physical(PhysicalItem::Discrete(DiscreteItem {
+            species: Species::Tree | Species::Bush,
+            state: item::State::Stage(stage),
+        })) if stage != LifeStage::Corpse
+    )
+}
+
+pub fn is_metal(item: &Item) -> bool {
+    matches!(
+        item.r#type,
+        ItemType::Physical(PhysicalItem::Bulk(bulk))
+            if bulk.structure == BulkStructure::Solid
+                && bulk.substance.is_metal()
+    )
+}
+
+// The corpse a burning tree or bush leaves.
+pub fn burnt(wood: &Item) -> Item {
+    let ItemType::Physical(PhysicalItem::Discrete(discrete)) = wood.r#type
+    else {
+        return *wood;
+    };
+    Item::organism(discrete.species, LifeStage::Corpse, wood.amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_living_burn() {
+        let tree = Item::organism(Species::Tree, LifeStage::Adult, 2.0);
+        let stump = burnt(&tree);
+        assert!(is_living_wood(&tree));
+        assert!(!is_living_wood(&stump));
+        let corpse = Item::organism(Species::Tree, LifeStage::Corpse, 2.0);
+        assert_eq!(stump.r#type, corpse.r#type);
+        assert_eq!(stump.amount, 2.0);
+        assert!(!is_living_wood(&Item::fruit(Species::Apple, 1.0)));
+    }
+
+    #[test]
+    fn metal_is_solid_metal() {
+        assert!(is_metal(&Item::ore(Substance::Iron, 1.0)));
+        let granite = Item::solid(Substance::Granite, BulkShape::Lump, 1.0);
+        assert!(!is_metal(&granite));
+        assert!(!is_metal(&Item::gas(Substance::Iron, 1.0)));
+    }
+}
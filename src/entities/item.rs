@@ -1,16 +1,20 @@
 use std::collections::HashSet;
 use std::mem::discriminant;
 
+use bevy::log::tracing::field;
+use bevy::platform::time::Instant;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use int_enum::IntEnum;
-use wyrand::WyRand;
+use serde::Deserialize;
 
 use crate::entities::*;
 use crate::libs::*;
 
 pub const MAX_ITEM_DISTANCE: f32 = 10000.0;
-pub const SEED: u64 = 91;
+// How far from a minigame's emit point an item may have got and still count
+// as just put out by it.
+const EMIT_RADIUS: f32 = 8.0;
 
 #[derive(Debug, Bundle)]
 pub struct ItemBundle {
@@ -27,8 +31,6 @@ pub struct ItemBundle {
     pub active_events: ActiveEvents,
 }
 
-// TODO fn for altering item components when amount changes
-//      IIRC can simply insert components on the entity and they overwrite them
 impl ItemBundle {
     pub fn new(
         images: &mut Assets<Image>,
@@ -41,14 +43,9 @@ impl ItemBundle {
             radius: item.size(),
         };
         let density = item.density();
-        let texture: Handle<Image> = generated_image_assets
-            .get(&item.uid())
-            .unwrap_or_else(|| {
-                let image = item.draw(&mut WyRand::new(SEED));
-                let texture = images.add(image.clone());
-                generated_image_assets.insert(item.uid(), &texture);
-                texture
-            });
+        let texture =
+            item.r#type
+                .texture(images, generated_image_assets, area.radius);
         Self {
             item,
             area,
@@ -71,24 +68,108 @@ impl ItemBundle {
         }
     }
 
+    // Where minigame output comes out: just past the minigame's top-right
+    // corner.
+    pub fn emit_point(
+        minigame_global_transform: &GlobalTransform,
+        minigame_area: &RectangularArea,
+    ) -> Vec3 {
+        minigame_global_transform.translation()
+            + minigame_area.dimensions3() / 1.5
+    }
+
+    // Whether an item at `position` sits at the minigame's emit point, i.e.
+    // is what it just put out.
+    pub fn at_emit_point(
+        position: Vec2,
+        minigame_global_transform: &GlobalTransform,
+        minigame_area: &RectangularArea,
+    ) -> bool {
+        Self::emit_point(minigame_global_transform, minigame_area)
+            .truncate()
+            .distance(position)
+            <= EMIT_RADIUS
+    }
+
+    // Whether an item just put out at `position` came from this minigame:
+    // dropped by it, or at its emit point.
+    pub fn put_out_by(
+        position: Vec2,
+        dropping: Option<&Dropping>,
+        minigame: Entity,
+        minigame_global_transform: &GlobalTransform,
+        minigame_area: &RectangularArea,
+    ) -> bool {
+        match dropping {
+            Some(dropping) => dropping.minigame == minigame,
+            None => Self::at_emit_point(
+                position,
+                minigame_global_transform,
+                minigame_area,
+            ),
+        }
+    }
+
+    // Minigame output. Tagged `Emitted` so the amount audit counts it as
+    // entering the world rather than as matter appearing from nowhere. It
+    // waits at the emit point for its turn to come out (see ejection.rs).
     pub fn new_from_minigame(
         images: &mut Assets<Image>,
         generated_image_assets: &mut image_gen::GeneratedImageAssets,
         item: Item,
         minigame_global_transform: &GlobalTransform,
         minigame_area: &RectangularArea,
-    ) -> Self {
-        let transform = Transform::from_translation(
-            minigame_global_transform.translation()
-                + minigame_area.dimensions3() / 1.5,
-        );
-        Self::new(
+    ) -> (
+        Self,
+        Emitted,
+        (
+            ejection::Ejecting,
+            ColliderDisabled,
+            RigidBodyDisabled,
+            Visibility,
+        ),
+    ) {
+        let transform = Transform::from_translation(Self::emit_point(
+            minigame_global_transform,
+            minigame_area,
+        ));
+        let bundle = Self::new(
             images,
             generated_image_assets,
             item,
             transform,
             Velocity::linear(Vec2::new(70.0, -70.0)),
-        )
+        );
+        let waiting =
+            ejection::waiting(minigame_global_transform, minigame_area);
+        (bundle, Emitted, waiting)
+    }
+
+    // Put out at `from`, inside the minigame, falling out through the gate in
+    // its bottom wall fast enough to clear it. See `Dropping`.
+    pub fn drop_from_minigame(
+        images: &mut Assets<Image>,
+        generated_image_assets: &mut image_gen::GeneratedImageAssets,
+        item: Item,
+        from: Vec2,
+        minigame: Entity,
+        minigame_global_transform: &GlobalTransform,
+        minigame_area: &RectangularArea,
+    ) -> (Self, Emitted, Dropping) {
+        let z = Self::emit_point(minigame_global_transform, minigame_area).z;
+        let bottom =
+            minigame_global_transform.translation().y + minigame_area.bottom();
+        // linear damping of 1.0 stops an item after about its speed's worth
+        // of travel, so twice the distance out leaves it moving
+        let fall = 2.0 * (from.y - bottom + Item::MAX_RADIUS);
+        let bundle = Self::new(
+            images,
+            generated_image_assets,
+            item,
+            Transform::from_translation(from.extend(z)),
+            Velocity::linear(Vec2::new(0.0, -fall)),
+        );
+        (bundle, Emitted, Dropping { minigame })
     }
 
     pub fn eject_from_minigame(
@@ -97,10 +178,10 @@ impl ItemBundle {
         minigame_global_transform: &GlobalTransform,
         minigame_area: &RectangularArea,
     ) {
-        let transform = Transform::from_translation(
-            minigame_global_transform.translation()
-                + minigame_area.dimensions3() / 1.5,
-        );
+        let transform = Transform::from_translation(Self::emit_point(
+            minigame_global_transform,
+            minigame_area,
+        ));
         let velocity = Velocity::linear(Vec2::new(70.0, -70.0));
         commands.entity(item_entity).insert((transform, velocity));
     }
@@ -178,6 +259,25 @@ impl Item {
         )
     }
 
+    pub fn xp(amount: f32) -> Self {
+        Self::new_abstract(AbstractKind::XP, 0, amount)
+    }
+
+    pub fn energy(kind: EnergyKind, amount: f32) -> Self {
+        Self::new(ItemType::Energy(EnergyItem { kind }), amount)
+    }
+
+    pub fn mana(kind: ManaKind, intent: ManaIntent, amount: f32) -> Self {
+        Self::new(
+            ItemType::Mana(ManaItem {
+                kind,
+                subkind: 0,
+                intent,
+            }),
+            amount,
+        )
+    }
+
     pub fn solid(substance: Substance, shape: BulkShape, amount: f32) -> Self {
         Self::bulk(
             BulkStructure::Solid,
@@ -218,6 +318,16 @@ impl Item {
         )
     }
 
+    pub fn gas(substance: Substance, amount: f32) -> Self {
+        Self::bulk(
+            BulkStructure::Gas,
+            substance,
+            Processing::Refined,
+            BulkShape::Lump,
+            amount,
+        )
+    }
+
     pub fn fruit(species: Species, amount: f32) -> Self {
         Self::new(
             ItemType::Physical(PhysicalItem::Discrete(DiscreteItem {
@@ -228,11 +338,7 @@ impl Item {
         )
     }
 
-    pub fn organism(
-        species: Species,
-        stage: LifeStage,
-        amount: f32,
-    ) -> Self {
+    pub fn organism(species: Species, stage: LifeStage, amount: f32) -> Self {
         Self::new(
             ItemType::Physical(PhysicalItem::Discrete(DiscreteItem {
                 species,
@@ -269,7 +375,7 @@ impl Item {
     }
 
     pub fn name(&self) -> String {
-        self.identifier().adjective
+        self.identifier().name()
     }
 
     pub fn asset(&self) -> String {
@@ -306,8 +412,8 @@ impl Item {
         }
     }
 
-    pub fn draw(&self, rand: &mut WyRand) -> Image {
-        self.r#type.draw(rand)
+    pub fn draw(&self, rand: &mut Random, size: u32) -> Image {
+        self.r#type.draw(rand, size)
     }
 
     fn identifier(&self) -> ItemIdentifier {
@@ -324,6 +430,11 @@ pub enum ItemType {
     Minigame(MinigameItem),
 }
 
+// Every variant of a contiguous `IntEnum` starting at zero, in order.
+fn every<T: TryFrom<u8>>() -> impl Iterator<Item = T> {
+    (0..=u8::MAX).map_while(|v| T::try_from(v).ok())
+}
+
 impl ItemType {
     pub fn to_item(self, amount: f32) -> Item {
         Item::new(self, amount)
@@ -334,7 +445,7 @@ impl ItemType {
     }
 
     pub fn name(&self) -> String {
-        self.identifier().adjective
+        self.identifier().name()
     }
 
     pub fn identifier(&self) -> ItemIdentifier {
@@ -347,13 +458,112 @@ impl ItemType {
         }
     }
 
-    pub fn draw(&self, rand: &mut WyRand) -> Image {
+    // Every item type the game knows how to construct (and name), in a stable
+    // order. Mana and minigame items are left out: they can't be named yet.
+    pub fn catalog() -> Vec<ItemType> {
+        let mut types = vec![];
+        for substance in every::<Substance>() {
+            for shape in [
+                BulkShape::Lump,
+                BulkShape::Block,
+                BulkShape::Ball,
+                BulkShape::Gravel,
+            ] {
+                types.push(Item::solid(substance, shape, 0.0).r#type);
+            }
+            types.push(Item::ore(substance, 0.0).r#type);
+            types.push(Item::liquid(substance, 0.0).r#type);
+            types.push(Item::powder(substance, 0.0).r#type);
+        }
+        for species in every::<Species>() {
+            if species.class() == DiscreteClass::Fruit {
+                types.push(Item::fruit(species, 0.0).r#type);
+                continue;
+            }
+            for stage in every::<LifeStage>() {
+                types.push(Item::organism(species, stage, 0.0).r#type);
+            }
+        }
+        for variant in 0..2 {
+            types.push(
+                Item::new_abstract(AbstractKind::Click, variant, 0.0).r#type,
+            );
+        }
+        types.push(Item::new_abstract(AbstractKind::XP, 0, 0.0).r#type);
+        for rune in every::<rune::Rune>() {
+            types.push(
+                Item::new_abstract(AbstractKind::Rune, rune as u8, 0.0).r#type,
+            );
+        }
+        for kind in [
+            EnergyKind::Kinetic,
+            EnergyKind::Potential,
+            EnergyKind::Thermal,
+            EnergyKind::Electric,
+            EnergyKind::Magnetic,
+            EnergyKind::Radiant,
+        ] {
+            types.push(ItemType::Energy(EnergyItem { kind }));
+        }
+        types
+    }
+
+    // Inverse of `uid()` over the catalog. Case and spaces are ignored, so
+    // "physical/liquid/saltwater" finds "physical/Liquid/Salt Water". Where
+    // two types share a uid (a refined gravel and an ore), the first wins.
+    pub fn from_uid(uid: &str) -> Option<ItemType> {
+        let normalize = |s: &str| s.replace(' ', "").to_lowercase();
+        let wanted = normalize(uid.trim_end_matches('/'));
+        Self::catalog()
+            .into_iter()
+            .find(|t| normalize(t.uid().trim_end_matches('/')) == wanted)
+    }
+
+    // The texture for drawing this type `radius` pixels around, drawn and
+    // cached the first time each size is asked for.
+    pub fn texture(
+        &self,
+        images: &mut Assets<Image>,
+        generated_image_assets: &mut image_gen::GeneratedImageAssets,
+        radius: f32,
+    ) -> Handle<Image> {
+        let size = texture_size(radius);
+        let key = format!("{}@{}", self.uid(), size);
+        generated_image_assets.get(&key).unwrap_or_else(|| {
+            let texture = self
+                .picture()
+                .and_then(|path| generated_image_assets.load_picture(&path))
+                .unwrap_or_else(|| {
+                    let _span =
+                        info_span!("draw_item_texture", uid = %self.uid(), size)
+                            .entered();
+                    let started = Instant::now();
+                    let image = self.draw(&mut Random::for_drawing(), size);
+                    generated_image_assets.add_drawing(started.elapsed());
+                    images.add(image)
+                });
+            generated_image_assets.insert(key, &texture);
+            texture
+        })
+    }
+
+    // The PNG under assets/ this type is drawn from, if it isn't generated.
+    pub fn picture(&self) -> Option<String> {
+        match self {
+            ItemType::Abstract(a) => a.picture(),
+            ItemType::Physical(a) => a.picture(),
+            ItemType::Energy(a) => a.picture(),
+            ItemType::Mana(_) | ItemType::Minigame(_) => None,
+        }
+    }
+
+    pub fn draw(&self, rand: &mut Random, size: u32) -> Image {
         match self {
-            ItemType::Abstract(a) => a.draw(rand),
-            ItemType::Physical(a) => a.draw(rand),
-            ItemType::Mana(a) => a.draw(rand),
-            ItemType::Energy(a) => a.draw(rand),
-            ItemType::Minigame(a) => a.draw(rand),
+            ItemType::Abstract(a) => a.draw(rand, size),
+            ItemType::Physical(a) => a.draw(rand, size),
+            ItemType::Mana(a) => a.draw(rand, size),
+            ItemType::Energy(a) => a.draw(rand, size),
+            ItemType::Minigame(a) => a.draw(rand, size),
         }
     }
 
@@ -372,6 +582,16 @@ impl ItemType {
         }
     }
 
+    pub fn is_xp(&self) -> bool {
+        matches!(
+            self,
+            ItemType::Abstract(AbstractItem {
+                kind: AbstractKind::XP,
+                ..
+            })
+        )
+    }
+
     pub fn is_fruit(&self) -> bool {
         match self {
             ItemType::Physical(PhysicalItem::Discrete(d)) => {
@@ -408,10 +628,14 @@ impl ItemType {
     pub fn unpack(packed: u64) -> Option<ItemType> {
         let domain = packed >> 61;
         match domain {
-            DOMAIN_PHYSICAL => PhysicalItem::unpack(packed).map(ItemType::Physical),
+            DOMAIN_PHYSICAL => {
+                PhysicalItem::unpack(packed).map(ItemType::Physical)
+            }
             DOMAIN_MANA => ManaItem::unpack(packed).map(ItemType::Mana),
             DOMAIN_ENERGY => EnergyItem::unpack(packed).map(ItemType::Energy),
-            DOMAIN_ABSTRACT => AbstractItem::unpack(packed).map(ItemType::Abstract),
+            DOMAIN_ABSTRACT => {
+                AbstractItem::unpack(packed).map(ItemType::Abstract)
+            }
             DOMAIN_MINIGAME => {
                 MinigameItem::unpack(packed).map(ItemType::Minigame)
             }
@@ -442,12 +666,16 @@ pub struct ItemIdentifier {
 }
 
 impl ItemIdentifier {
+    // For the player, in their locale. Words without a translation are shown
+    // as they are.
     pub fn name(&self) -> String {
+        let noun = tr_or(&format!("word.{}", self.noun), &self.noun);
         if self.adjective.is_empty() {
-            self.noun.clone()
-        } else {
-            format!("{} {}", self.adjective, self.noun)
+            return noun;
         }
+        let adjective =
+            tr_or(&format!("word.{}", self.adjective), &self.adjective);
+        tr_fmt("item.name", &[("adjective", &adjective), ("noun", &noun)])
     }
 
     pub fn uid(&self) -> String {
@@ -514,17 +742,34 @@ impl AbstractItem {
         Some(AbstractItem { kind, variant })
     }
 
-    pub fn draw(&self, _rand: &mut WyRand) -> Image {
+    pub fn picture(&self) -> Option<String> {
         match self.kind {
             AbstractKind::Click => {
-                let path = format!("assets/abstract/{}.png", self.object());
-                load_image(&path)
+                Some(format!("abstract/{}.png", self.object()))
             }
+            AbstractKind::XP | AbstractKind::Rune => None,
+        }
+    }
+
+    pub fn draw(&self, rand: &mut Random, size: u32) -> Image {
+        match self.kind {
+            AbstractKind::Click => draw_picture(self.picture(), size),
             AbstractKind::Rune => match rune::Rune::try_from(self.variant) {
                 Ok(rune) => image_gen::draw_rune(rune),
                 Err(_) => panic!("Invalid rune variant {}", self.variant),
             },
-            _ => panic!("Invalid abstract item kind {:?}", self.kind),
+            // a glowing golden orb
+            AbstractKind::XP => {
+                let mut palette = image_gen::ColorPalette::new();
+                palette
+                    .add_colorant(image_gen::Colorant::new_tight(
+                        255, 215, 0, 3,
+                    ))
+                    .add_colorant(image_gen::Colorant::new_tight(
+                        255, 245, 170, 1,
+                    ));
+                palette.draw_ball(rand, size)
+            }
         }
     }
 
@@ -829,7 +1074,31 @@ pub enum AbstractKind {
     Rune,
 }
 
-const ITEM_SIZE: u32 = 256; // pixels
+// Item textures are drawn at a power of two between these, in pixels, picked
+// by how big the item shows on screen. Small items get small textures, and an
+// item only gets a new one when combining grows it into the next size up.
+const MIN_TEXTURE_SIZE: u32 = 16;
+const MAX_TEXTURE_SIZE: u32 = 256;
+// Texture pixels per screen pixel across, so zooming in stays sharp.
+const TEXTURE_OVERSAMPLE: f32 = 4.0;
+
+// Texture size for something drawn `radius` pixels around.
+pub fn texture_size(radius: f32) -> u32 {
+    ((radius * 2.0 * TEXTURE_OVERSAMPLE).ceil() as u32)
+        .next_power_of_two()
+        .clamp(MIN_TEXTURE_SIZE, MAX_TEXTURE_SIZE)
+}
+
+// A type's picture read straight from disk, for when there's no asset server
+// to load it (see `ItemType::texture`).
+fn draw_picture(picture: Option<String>, size: u32) -> Image {
+    match picture {
+        Some(path) => {
+            load_image_or_placeholder(&format!("assets/{}", path), size)
+        }
+        None => placeholder_image(),
+    }
+}
 
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq)]
 pub enum PhysicalItem {
@@ -868,7 +1137,7 @@ pub enum BulkStructure {
     Solid = 3,
 }
 
-#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, IntEnum)]
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, IntEnum, Deserialize)]
 #[repr(u8)]
 pub enum Substance {
     Mud = 0,
@@ -889,6 +1158,7 @@ pub enum Substance {
     Unobtainium = 15,
     SaltWater = 16,
     FreshWater = 17,
+    Salt = 18,
 }
 
 #[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, IntEnum)]
@@ -978,7 +1248,8 @@ impl Substance {
             | Substance::Granite
             | Substance::Marble
             | Substance::Obsidian
-            | Substance::Moss => SubstanceClass::Earthen,
+            | Substance::Moss
+            | Substance::Salt => SubstanceClass::Earthen,
             Substance::Copper
             | Substance::Tin
             | Substance::Bronze
@@ -1025,57 +1296,13 @@ impl Substance {
             Substance::Unobtainium => "Unobtainium",
             Substance::SaltWater => "Salt Water",
             Substance::FreshWater => "Fresh Water",
+            Substance::Salt => "Salt",
         }
     }
 
+    // Kept in assets/palettes.ron under the substance's name.
     pub fn palette(&self) -> image_gen::ColorPalette {
-        match self {
-            Substance::Mud => Self::mud_palette(),
-            Substance::Dirt => Self::dirt_palette(),
-            Substance::Sandstone => Self::sandstone_palette(),
-            Substance::SaltWater => Self::salt_water_palette(),
-            Substance::FreshWater => Self::fresh_water_palette(),
-            _ => panic!("palette not implemented for {:?}", self),
-        }
-    }
-
-    fn mud_palette() -> image_gen::ColorPalette {
-        let mut palette = image_gen::ColorPalette::new();
-        palette.add_colorant(image_gen::Colorant::new_loose(87, 39, 12, 10, 1));
-        palette
-    }
-
-    fn dirt_palette() -> image_gen::ColorPalette {
-        let mut palette = image_gen::ColorPalette::new();
-        palette.add_colorant(image_gen::Colorant::new_loose(70, 60, 40, 10, 1));
-        palette
-    }
-
-    fn sandstone_palette() -> image_gen::ColorPalette {
-        let mut palette = image_gen::ColorPalette::new();
-        palette
-            .add_colorant(image_gen::Colorant::new_loose(255, 174, 76, 15, 2));
-        palette
-            .add_colorant(image_gen::Colorant::new_loose(220, 114, 41, 15, 3));
-        palette
-    }
-
-    fn salt_water_palette() -> image_gen::ColorPalette {
-        let mut palette = image_gen::ColorPalette::new();
-        palette.add_colorant(image_gen::Colorant::new_loose(0, 21, 125, 2, 5));
-        palette
-            .add_colorant(image_gen::Colorant::new_loose(52, 71, 180, 2, 10));
-        palette
-            .add_colorant(image_gen::Colorant::new_loose(152, 162, 200, 4, 2));
-        palette
-    }
-
-    fn fresh_water_palette() -> image_gen::ColorPalette {
-        let mut palette = image_gen::ColorPalette::new();
-        palette.add_colorant(image_gen::Colorant::new_loose(0, 21, 125, 2, 5));
-        palette
-            .add_colorant(image_gen::Colorant::new_loose(52, 71, 180, 2, 10));
-        palette
+        palettes::palette(self.name())
     }
 }
 
@@ -1121,18 +1348,24 @@ impl Species {
         }
     }
 
+    // Kept in assets/palettes.ron, by what's drawn rather than by species.
     fn archaea_palette() -> image_gen::ColorPalette {
-        let mut palette = image_gen::ColorPalette::new();
-        palette.add_colorant(image_gen::Colorant {
-            red: 0,
-            green: 10,
-            blue: 0,
-            alpha: 200,
-            weight: 1,
-            looseness: 10,
-            alpha_looseness: 10,
-        });
-        palette
+        palettes::palette("Archaea")
+    }
+
+    fn algae_palette() -> image_gen::ColorPalette {
+        palettes::palette("Algae")
+    }
+
+    fn animal_palette(&self) -> image_gen::ColorPalette {
+        match self {
+            Species::Amphibian => palettes::palette("Amphibian"),
+            _ => palettes::palette("Animal"),
+        }
+    }
+
+    fn corpse_palette() -> image_gen::ColorPalette {
+        palettes::palette("Corpse")
     }
 }
 
@@ -1192,7 +1425,9 @@ impl PhysicalItem {
                 }
                 if matches!(
                     a.structure,
-                    BulkStructure::Gas | BulkStructure::Liquid | BulkStructure::Powder
+                    BulkStructure::Gas
+                        | BulkStructure::Liquid
+                        | BulkStructure::Powder
                 ) {
                     Some((*self, self_amount + other_amount))
                 } else {
@@ -1203,34 +1438,55 @@ impl PhysicalItem {
         }
     }
 
-    pub fn draw(&self, rand: &mut WyRand) -> Image {
+    pub fn picture(&self) -> Option<String> {
+        match self {
+            PhysicalItem::Discrete(d) if d.species == Species::Apple => {
+                Some(format!("physical/{}.png", d.species.name()))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn draw(&self, rand: &mut Random, size: u32) -> Image {
         match self {
             PhysicalItem::Bulk(b) => {
                 let palette = b.substance.palette();
                 match b.structure {
                     BulkStructure::Gas => palette
                         .adjust_alpha_looseness(128)
-                        .draw_ball(rand, ITEM_SIZE),
-                    BulkStructure::Liquid => palette
-                        .adjust_alpha_looseness(32)
-                        .draw_ball(rand, ITEM_SIZE),
-                    BulkStructure::Powder => palette.draw_powder(rand, ITEM_SIZE),
+                        .draw_ball(rand, size),
+                    BulkStructure::Liquid => {
+                        palette.adjust_alpha_looseness(32).draw_ball(rand, size)
+                    }
+                    BulkStructure::Powder => palette.draw_powder(rand, size),
                     BulkStructure::Solid => match b.shape {
-                        BulkShape::Lump => palette.draw_lump(rand, ITEM_SIZE),
-                        BulkShape::Block => palette.draw_block(rand, ITEM_SIZE),
-                        BulkShape::Ball => palette.draw_ball(rand, ITEM_SIZE),
-                        BulkShape::Gravel => palette.draw_powder(rand, ITEM_SIZE),
+                        BulkShape::Lump => palette.draw_lump(rand, size),
+                        BulkShape::Block => palette.draw_block(rand, size),
+                        BulkShape::Ball => palette.draw_ball(rand, size),
+                        BulkShape::Gravel => palette.draw_powder(rand, size),
                     },
                 }
             }
             PhysicalItem::Discrete(d) => match d.species.class() {
-                DiscreteClass::Fruit => load_image(&format!(
-                    "assets/physical/{}.png",
-                    d.species.name()
-                )),
+                // apples have a picture; the rest are drawn
+                DiscreteClass::Fruit if d.species == Species::Apple => {
+                    draw_picture(self.picture(), size)
+                }
+                DiscreteClass::Fruit => {
+                    palettes::palette(d.species.name()).draw_ball(rand, size)
+                }
+                _ if d.state == State::Stage(LifeStage::Corpse) => {
+                    Species::corpse_palette().draw_lump(rand, size)
+                }
                 _ => match d.species {
                     Species::Archaea => {
-                        Species::archaea_palette().draw_lump(rand, ITEM_SIZE)
+                        Species::archaea_palette().draw_lump(rand, size)
+                    }
+                    Species::Algae => {
+                        Species::algae_palette().draw_powder(rand, size)
+                    }
+                    Species::Fish | Species::Amphibian => {
+                        d.species.animal_palette().draw_ball(rand, size)
                     }
                     _ => panic!("Invalid species {:?}", d.species),
                 },
@@ -1382,12 +1638,42 @@ impl ManaItem {
         }
     }
 
-    pub fn draw(&self, _rand: &mut WyRand) -> Image {
-        panic!("ManaItem::draw not implemented");
+    // no art for it yet: a glowing orb in the element's colour
+    pub fn draw(&self, rand: &mut Random, size: u32) -> Image {
+        let (r, g, b) = match self.kind {
+            ManaKind::Fire => (240, 80, 30),
+            ManaKind::Water => (40, 120, 240),
+            ManaKind::Earth => (120, 90, 40),
+            ManaKind::Air => (200, 230, 240),
+            ManaKind::Light => (255, 250, 190),
+            ManaKind::Dark => (60, 20, 90),
+        };
+        let mut palette = image_gen::ColorPalette::new();
+        palette
+            .add_colorant(image_gen::Colorant::new_tight(r, g, b, 3))
+            .add_colorant(image_gen::Colorant::new_tight(255, 255, 255, 1));
+        palette.draw_ball(rand, size)
     }
 
     pub fn identifier(&self) -> ItemIdentifier {
-        panic!("ManaItem::identifier not implemented");
+        let noun = match self.kind {
+            ManaKind::Fire => "fire",
+            ManaKind::Water => "water",
+            ManaKind::Earth => "earth",
+            ManaKind::Air => "air",
+            ManaKind::Light => "light",
+            ManaKind::Dark => "dark",
+        };
+        let adjective = match self.intent {
+            ManaIntent::Attack => "attack",
+            ManaIntent::Defense => "defense",
+            ManaIntent::Support => "support",
+        };
+        ItemIdentifier {
+            domain: "mana".to_string(),
+            noun: noun.to_string(),
+            adjective: adjective.to_string(),
+        }
     }
 
     fn pack(&self) -> u64 {
@@ -1475,8 +1761,29 @@ impl EnergyItem {
         }
     }
 
-    pub fn draw(&self, _rand: &mut WyRand) -> Image {
-        load_image(&format!("assets/energy/{}.png", self.identifier().noun))
+    pub fn picture(&self) -> Option<String> {
+        match self.kind {
+            EnergyKind::Radiant => None,
+            _ => Some(format!("energy/{}.png", self.identifier().noun)),
+        }
+    }
+
+    pub fn draw(&self, rand: &mut Random, size: u32) -> Image {
+        match self.kind {
+            // no art for it yet: a pale sunny glow
+            EnergyKind::Radiant => {
+                let mut palette = image_gen::ColorPalette::new();
+                palette
+                    .add_colorant(image_gen::Colorant::new_tight(
+                        255, 250, 200, 3,
+                    ))
+                    .add_colorant(image_gen::Colorant::new_tight(
+                        255, 200, 60, 1,
+                    ));
+                palette.draw_ball(rand, size)
+            }
+            _ => draw_picture(self.picture(), size),
+        }
     }
 
     pub fn identifier(&self) -> ItemIdentifier {
@@ -1559,12 +1866,28 @@ impl MinigameItem {
         None
     }
 
-    pub fn draw(&self, _rand: &mut WyRand) -> Image {
-        panic!("MinigameItem::draw not implemented");
+    // no art for them yet: a pale block, like a crate with a game in it
+    pub fn draw(&self, rand: &mut Random, size: u32) -> Image {
+        let mut palette = image_gen::ColorPalette::new();
+        palette
+            .add_colorant(image_gen::Colorant::new_tight(210, 200, 240, 3))
+            .add_colorant(image_gen::Colorant::new_tight(120, 90, 200, 1));
+        palette.draw_block(rand, size)
     }
 
     pub fn identifier(&self) -> ItemIdentifier {
-        panic!("MinigameItem::identifier not implemented");
+        let noun = match self.kind {
+            MinigameItemKind::Button => "button",
+            MinigameItemKind::PrimordialOcean => "primordial ocean",
+            MinigameItemKind::Draw => "draw",
+            MinigameItemKind::BlockBreaker => "block breaker",
+            MinigameItemKind::Tree => "tree",
+        };
+        ItemIdentifier {
+            domain: "minigame".to_string(),
+            noun: noun.to_string(),
+            adjective: "".to_string(),
+        }
     }
 
     fn pack(&self) -> u64 {
@@ -1613,8 +1936,12 @@ pub struct Stuck {
 #[derive(Debug, Default, Copy, Clone, Component)]
 pub struct Sticky;
 
+// Only items that moved can have strayed.
 pub fn teleport_distant_loose_items(
-    mut query: Query<&mut Transform, (With<Item>, Without<Stuck>)>,
+    mut query: Query<
+        &mut Transform,
+        (With<Item>, Without<Stuck>, Changed<Transform>),
+    >,
 ) {
     for mut transform in query.iter_mut() {
         if transform.translation.length() > MAX_ITEM_DISTANCE {
@@ -1623,60 +1950,142 @@ pub fn teleport_distant_loose_items(
     }
 }
 
+// Combining happens in two phases so that no item can take part in more than
+// one merge per frame. First every combinable pair that started touching is
+// collected, then a disjoint set of them is picked (first come, first served),
+// and only then are the items despawned and their merged stacks spawned. An
+// item left out this frame is still touching its partner's replacement, so it
+// gets its own collision event and merges next frame.
+// Runs after `ingest_item`, so an item a minigame just swallowed is already
+// gone from the query instead of also being merged into a new stack.
 pub fn combine_loose_items(
     mut commands: Commands,
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
     loose_item_query: Query<(&Item, &Transform, &Velocity)>,
     stuck_query: Query<&Stuck>,
-    mut collision_events: MessageReader<CollisionEvent>,
+    charge_query: Query<&electric::Charge>,
+    dropping_query: Query<(), With<Dropping>>,
+    mut contacts: MessageReader<contact::ItemItem>,
+    mut timings: ResMut<FrameTimings>,
 ) {
-    let mut eliminated: HashSet<Entity> = HashSet::new();
-    for collision_event in collision_events.read() {
-        if let CollisionEvent::Started(entity1, entity2, _) = collision_event {
-            // already handled
-            if eliminated.contains(entity1) || eliminated.contains(entity2) {
-                continue;
-            }
-            // only loose items handled
-            let Ok(items) = loose_item_query.get_many([*entity1, *entity2])
-            else {
-                continue;
-            };
-            let (item1, transform1, velocity1) = items[0];
-            let (item2, transform2, velocity2) = items[1];
+    let started = Instant::now();
+    let span = info_span!("combine_loose_items", pairs = field::Empty);
+    let _entered = span.enter();
+    let mut pairs = 0;
+    // collect
+    let candidates = contacts.read().filter_map(|&contact::ItemItem(a, b)| {
+        // not until they're out of the minigame that dropped them
+        if dropping_query.contains(a) || dropping_query.contains(b) {
+            return None;
+        }
+        let [(item1, _, _), (item2, _, _)] =
+            loose_item_query.get_many([a, b]).ok()?;
+        item1.combine(item2).map(|_| (a, b))
+    });
+
+    // resolve, then apply
+    for (entity1, entity2) in disjoint_pairs(candidates) {
+        let [(item1, transform1, velocity1), (item2, transform2, velocity2)] =
+            loose_item_query.get_many([entity1, entity2]).unwrap();
+        let Some(combined) = item1.combine(item2) else {
+            continue;
+        };
+        pairs += 1;
 
-            // combine if possible
-            let Some(combined) = item1.combine(item2) else {
-                continue;
-            };
+        // prefer the transform of the stuck item, if any
+        let transform = if stuck_query.get(entity1).is_ok() {
+            transform1
+        } else {
+            transform2
+        };
 
-            // prefer the transform of the stuck item, if any
-            let transform = if stuck_query.get(*entity1).is_ok() {
-                transform1
-            } else {
-                transform2
-            };
+        // despawn both and add a new one, with any charge they held
+        commands.entity(entity1).despawn();
+        commands.entity(entity2).despawn();
+        let mut merged = commands.spawn(ItemBundle::new(
+            &mut images,
+            &mut generated_image_assets,
+            combined,
+            *transform,
+            Velocity {
+                linear: velocity1.linear + velocity2.linear,
+                angular: velocity1.angular + velocity2.angular,
+            },
+        ));
+        let charge: f32 = charge_query
+            .iter_many([entity1, entity2])
+            .map(|charge| charge.0)
+            .sum();
+        if charge > 0.0 {
+            merged.insert(electric::Charge(charge));
+        }
+    }
+    span.record("pairs", pairs);
+    timings.add(Timed::Combine, started.elapsed());
+}
 
-            // despawn both and add a new one
-            commands.entity(*entity1).despawn();
-            commands.entity(*entity2).despawn();
-            eliminated.insert(*entity1);
-            eliminated.insert(*entity2);
-            commands.spawn(ItemBundle::new(
-                &mut images,
-                &mut generated_image_assets,
-                combined,
-                *transform,
-                Velocity {
-                    linear: velocity1.linear + velocity2.linear,
-                    angular: velocity1.angular + velocity2.angular,
-                },
-            ));
+// Brings items whose `Item` changed in place back in line with it, as
+// `ItemBundle::new` would have built them: their area, collider, mass and
+// sprite size, and their texture when it's drawn at another size (or the type
+// changed). They keep their place, motion, joints and children.
+pub fn resize_changed_items(
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut item_query: Query<
+        (
+            &Item,
+            &mut CircularArea,
+            &mut Collider,
+            &mut ColliderMassProperties,
+            &mut Sprite,
+        ),
+        Changed<Item>,
+    >,
+) {
+    for (item, mut area, mut collider, mut mass, mut sprite) in
+        item_query.iter_mut()
+    {
+        let density = ColliderMassProperties::Density(item.density());
+        if *mass != density {
+            *mass = density;
+        }
+        let radius = item.size();
+        if area.radius != radius {
+            area.radius = radius;
+            *collider = (*area).into();
+            sprite.custom_size = Some((*area).into());
+        }
+        // a lookup, unless this type hasn't been drawn at this size yet
+        let texture = item.r#type.texture(
+            &mut images,
+            &mut generated_image_assets,
+            area.radius,
+        );
+        if sprite.image != texture {
+            sprite.image = texture;
         }
     }
 }
 
+// Keeps each pair only if neither entity is already in an earlier kept pair.
+pub fn disjoint_pairs(
+    candidates: impl IntoIterator<Item = (Entity, Entity)>,
+) -> Vec<(Entity, Entity)> {
+    let mut matched: HashSet<Entity> = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|&(entity1, entity2)| {
+            // insert reports whether the entity was new
+            entity1 != entity2
+                && !matched.contains(&entity1)
+                && !matched.contains(&entity2)
+                && matched.insert(entity1)
+                && matched.insert(entity2)
+        })
+        .collect()
+}
+
 pub fn grab_items(
     mut commands: Commands,
     read_rapier_context: ReadRapierContext,
@@ -1685,7 +2094,7 @@ pub fn grab_items(
         (&CircularArea, &mut Velocity),
         (With<Item>, Without<Stuck>),
     >,
-    mut collision_events: MessageReader<CollisionEvent>,
+    mut contacts: MessageReader<contact::PlayerItem>,
 ) {
     let Ok((player_entity, player_area)) = player_query.single() else {
         return;
@@ -1694,47 +2103,42 @@ pub fn grab_items(
         return;
     };
 
-    for collision_event in collision_events.read() {
-        if let CollisionEvent::Started(entity1, entity2, _) = collision_event {
-            let (other, player_is_first) = if *entity1 == player_entity {
-                (*entity2, true)
-            } else if *entity2 == player_entity {
-                (*entity1, false)
-            } else {
-                continue;
-            };
+    for contact in contacts.read() {
+        if contact.player != player_entity {
+            continue;
+        }
+        let other = contact.item;
 
-            let Ok((item_area, mut item_velocity)) =
-                loose_item_query.get_mut(other)
-            else {
-                continue;
-            };
+        let Ok((item_area, mut item_velocity)) =
+            loose_item_query.get_mut(other)
+        else {
+            continue;
+        };
 
-            let Some(contact_pair) =
-                rapier_context.contact_pair(player_entity, other)
-            else {
-                continue;
-            };
-            let Some(manifold) = contact_pair.manifold(0) else {
-                continue;
-            };
-            let direction = (if player_is_first {
-                manifold.local_n1()
-            } else {
-                manifold.local_n2()
-            })
-            .normalize();
-
-            stick(
-                &mut commands,
-                player_entity,
-                *player_area,
-                other,
-                *item_area,
-                &mut item_velocity,
-                direction,
-            );
-        }
+        let Some(contact_pair) =
+            rapier_context.contact_pair(player_entity, other)
+        else {
+            continue;
+        };
+        let Some(manifold) = contact_pair.manifold(0) else {
+            continue;
+        };
+        let direction = (if contact.player_first {
+            manifold.local_n1()
+        } else {
+            manifold.local_n2()
+        })
+        .normalize();
+
+        stick(
+            &mut commands,
+            player_entity,
+            *player_area,
+            other,
+            *item_area,
+            &mut item_velocity,
+            direction,
+        );
     }
 }
 
@@ -1775,10 +2179,78 @@ pub fn release_items(
     }
 }
 
+// Loose items: the player grabbing and letting go of them, keeping them tidy
+// between ticks, and labeling their amounts.
+pub struct ItemsPlugin;
+
+impl Plugin for ItemsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (grab_items.in_set(WorkSet::Collisions), release_items).chain(),
+                (
+                    item_labels::toggle_item_labels
+                        .run_if(console::console_closed)
+                        .run_if(photo_mode::photo_mode_off),
+                    item_labels::update_item_labels,
+                    item_labels::keep_labels_upright,
+                )
+                    .chain(),
+                electric::fade_bolts,
+                ejection::grow_ejected_items,
+            )
+                .in_set(GameSet::Items),
+        )
+        .add_systems(
+            FixedUpdate,
+            // GameSet::Items comes after GameSet::Minigames, which holds
+            // `ingest_item`
+            (
+                teleport_distant_loose_items,
+                puddle::slow_items_on_puddles,
+                // once routing has had its pick of what was put out
+                ejection::eject_items.after(routing::catch_routed_items),
+                // one after another, so no item is merged twice
+                (
+                    gas::dissipate_gases,
+                    puddle::drink_puddles,
+                    reaction::react_on_contact.in_set(WorkSet::Collisions),
+                    electric::arc_to_metals,
+                    electric::deposit_charges.in_set(WorkSet::Collisions),
+                    combine_loose_items.in_set(WorkSet::Collisions),
+                    overflow::merge_crowded_items.run_if(overflow::crowded),
+                    overflow::deposit_oldest_items
+                        .run_if(overflow::overcrowded),
+                    // once nothing else this step changes an amount
+                    resize_changed_items,
+                    puddle::shape_puddles,
+                )
+                    .chain(),
+            )
+                .in_set(GameSet::Items),
+        )
+        .init_resource::<ItemGrid>()
+        .init_resource::<ejection::Ejections>()
+        .init_resource::<item_labels::ItemLabels>()
+        .add_systems(PreUpdate, index_loose_items);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn disjoint_pairs_uses_each_entity_once() {
+        let [a, b, c, d] =
+            [1, 2, 3, 4].map(|i| Entity::from_raw_u32(i).unwrap());
+        // a triangle of contacts plus a repeat: only the first survives,
+        // and d is free to pair with the leftover c
+        let pairs = disjoint_pairs([(a, b), (b, c), (a, c), (b, a), (c, d)]);
+        assert_eq!(pairs, vec![(a, b), (c, d)]);
+    }
+
     // The tree minigame produces Apple fruit. Before this fix, identifier()
     // panicked on the Apple form (crashing on fruit spawn), and asset()/draw()
     // pointed at the wrong filename. Guards all three.
@@ -1794,6 +2266,61 @@ mod tests {
         assert_eq!(apple.r#type.uid(), "physical/Apple/Fruit");
     }
 
+    #[test]
+    fn only_some_types_have_pictures() {
+        let apple = Item::fruit(Species::Apple, 1.0).r#type.picture();
+        assert_eq!(apple.as_deref(), Some("physical/Apple.png"));
+        assert!(std::path::Path::new("assets/physical/Apple.png").exists());
+        assert_eq!(Item::fruit(Species::Lemon, 1.0).r#type.picture(), None);
+        let gold = Item::solid(Substance::Gold, BulkShape::Lump, 1.0);
+        assert_eq!(gold.r#type.picture(), None);
+    }
+
+    #[test]
+    fn from_uid_finds_catalog_types() {
+        assert_eq!(
+            ItemType::from_uid("physical/Lump/Gold"),
+            Some(Item::solid(Substance::Gold, BulkShape::Lump, 1.0).r#type)
+        );
+        assert_eq!(
+            ItemType::from_uid("physical/liquid/saltwater"),
+            Some(Item::liquid(Substance::SaltWater, 1.0).r#type)
+        );
+        assert_eq!(
+            ItemType::from_uid("energy/thermal"),
+            Some(ItemType::Energy(EnergyItem {
+                kind: EnergyKind::Thermal
+            }))
+        );
+        assert_eq!(ItemType::from_uid("physical/Lump/Cheese"), None);
+        // every catalog entry can be named, and its name finds it (or an
+        // earlier entry with the same name)
+        for t in ItemType::catalog() {
+            let found = ItemType::from_uid(&t.uid()).unwrap();
+            assert_eq!(found.uid(), t.uid());
+        }
+    }
+
+    #[test]
+    fn item_names_read_naturally() {
+        let gold = Item::solid(Substance::Gold, BulkShape::Lump, 1.0);
+        assert_eq!(gold.name(), "Gold Lump");
+        assert_eq!(Item::xp(1.0).name(), "XP");
+    }
+
+    #[test]
+    fn every_substance_and_species_has_a_palette() {
+        for substance in every::<Substance>() {
+            assert!(substance.palette().total_weight > 0);
+        }
+        for species in every::<Species>() {
+            species.animal_palette();
+        }
+        Species::archaea_palette();
+        Species::algae_palette();
+        Species::corpse_palette();
+    }
+
     fn roundtrip(t: ItemType) {
         let packed = t.pack();
         let unpacked = ItemType::unpack(packed)
@@ -1828,9 +2355,7 @@ mod tests {
         // a few others
         roundtrip(Item::liquid(Substance::SaltWater, 1.0).r#type);
         roundtrip(Item::powder(Substance::Gold, 1.0).r#type);
-        roundtrip(
-            Item::organism(Species::Tree, LifeStage::Adult, 1.0).r#type,
-        );
+        roundtrip(Item::organism(Species::Tree, LifeStage::Adult, 1.0).r#type);
         roundtrip(ItemType::Energy(EnergyItem {
             kind: EnergyKind::Thermal,
         }));
@@ -1851,6 +2376,20 @@ mod tests {
         assert_ne!(fresh.pack(), spoiled.pack());
     }
 
+    #[test]
+    fn textures_are_sized_to_the_item() {
+        let dust = Item::xp(0.1);
+        let boulder = Item::xp(1_000_000.0);
+        assert_eq!(texture_size(dust.size()), 32);
+        assert_eq!(texture_size(boulder.size()), MAX_TEXTURE_SIZE);
+        assert_eq!(texture_size(0.0), MIN_TEXTURE_SIZE);
+        // a small merge stays on the same texture
+        assert_eq!(
+            texture_size(Item::xp(2.0).size()),
+            texture_size(Item::xp(3.0).size())
+        );
+    }
+
     #[test]
     fn ore_and_block_have_distinct_ids() {
         let ore = Item::ore(Substance::Iron, 1.0).r#type;
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Amount labels: small text over each item holding more than
+// `labels.min_amount` (assets/balance.ron), so a big pile can be told from a
+// small one of the same kind. L switches them on and off; they start off.
+// Each item has at most one label, a child that's kept upright as the item
+// rolls. Its text is only rewritten when the item's amount changes, or when
+// the labels are switched or retuned.
+
+const LABEL_FONT_SIZE: f32 = 9.0;
+const LABEL_GAP: f32 = 2.0;
+const LABEL_COLOR: Color = Color::srgb(0.1, 0.1, 0.1);
+
+#[derive(Debug, Default, Resource)]
+pub struct ItemLabels {
+    pub on: bool,
+}
+
+#[derive(Debug, Component)]
+pub struct AmountLabel;
+
+// Above the item's center, in the item's own frame, so it reads upright
+// whichever way the item has turned.
+fn upright(item_transform: &Transform, item_area: &CircularArea) -> Transform {
+    let unturn = item_transform.rotation.inverse();
+    Transform::from_translation(
+        unturn * Vec3::new(0.0, item_area.radius + LABEL_GAP, 1.0),
+    )
+    .with_rotation(unturn)
+}
+
+pub fn toggle_item_labels(
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut labels: ResMut<ItemLabels>,
+) {
+    if kb_input.just_pressed(KeyCode::KeyL) {
+        labels.on = !labels.on;
+    }
+}
+
+// Adds, rewrites or removes the labels of items whose amount changed, or of
+// every item when the labels were switched or retuned, or the notation
+// changed.
+pub fn update_item_labels(
+    mut commands: Commands,
+    labels: Res<ItemLabels>,
+    balance: Res<BalanceConfig>,
+    settings: Res<Accessibility>,
+    item_query: Query<(
+        Entity,
+        Ref<Item>,
+        &Transform,
+        &CircularArea,
+        Option<&Children>,
+    )>,
+    mut label_query: Query<&mut Text2d, With<AmountLabel>>,
+) {
+    let everything =
+        labels.is_changed() || balance.is_changed() || settings.is_changed();
+    if !everything && !labels.on {
+        return;
+    }
+    for (item_entity, item, transform, area, children) in item_query.iter() {
+        if !everything && !item.is_changed() {
+            continue;
+        }
+        let label = children.and_then(|children| {
+            children.iter().find(|child| label_query.contains(*child))
+        });
+        let wanted = labels.on && item.amount > balance.labels.min_amount;
+        match (label, wanted) {
+            (Some(label), true) => {
+                let text = format_amount(item.amount);
+                if let Ok(mut label_text) = label_query.get_mut(label) {
+                    if label_text.0 != text {
+                        label_text.0 = text;
+                    }
+                }
+            }
+            (None, true) => {
+                commands.entity(item_entity).with_child((
+                    AmountLabel,
+                    Text2d::new(format_amount(item.amount)),
+                    TextFont {
+                        font_size: LABEL_FONT_SIZE,
+                        ..default()
+                    },
+                    TextColor(LABEL_COLOR),
+                    upright(transform, area),
+                ));
+            }
+            (Some(label), false) => {
+                commands.entity(label).despawn();
+            }
+            (None, false) => {}
+        }
+    }
+}
+
+// Turns labels back upright over items that moved.
+pub fn keep_labels_upright(
+    item_query: Query<
+        (&Transform, &CircularArea, &Children),
+        (With<Item>, Changed<Transform>),
+    >,
+    mut label_query: Query<&mut Transform, (With<AmountLabel>, Without<Item>)>,
+) {
+    for (item_transform, item_area, children) in item_query.iter() {
+        let mut labels = label_query.iter_many_mut(children);
+        while let Some(mut label_transform) = labels.fetch_next() {
+            *label_transform = upright(item_transform, item_area);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels_stay_upright_over_the_item() {
+        let area = CircularArea { radius: 10.0 };
+        let turned = Transform::from_rotation(Quat::from_rotation_z(1.0));
+        let label = upright(&turned, &area);
+        // in the world, the turns cancel out
+        let world = turned.mul_transform(label);
+        assert!(world.rotation.angle_between(Quat::IDENTITY) < 1e-5);
+        assert!((world.translation.y - 12.0).abs() < 1e-4);
+        assert!(world.translation.x.abs() < 1e-4);
+    }
+}
@@ -0,0 +1,247 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// The player's magnet: an upgrade that keeps pulling loose items toward the
+// player, a gentler and continuous alternative to the sweep. Minigame levels
+// unlock it, first for metals and later for everything. M switches it off and
+// on again, and a ring around the player shows how far it reaches. Reach and
+// pull are in assets/balance.ron.
+
+const FIELD_WIDTH: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MagnetTier {
+    Metals,
+    Everything,
+}
+
+// Each tier with the minigame level that unlocks it, lowest tier first.
+pub const MAGNET_TIERS: [(MagnetTier, &str, u8); 2] = [
+    (MagnetTier::Metals, minigames::foundry::ID, 3),
+    (MagnetTier::Everything, minigames::battery::ID, 3),
+];
+
+impl MagnetTier {
+    // The best tier the minigames' levels have unlocked, if any.
+    pub fn unlocked(minigames: &MinigamesResource) -> Option<Self> {
+        MAGNET_TIERS
+            .iter()
+            .rev()
+            .find(|(_, id, level)| minigames.level(id) >= *level)
+            .map(|(tier, _, _)| *tier)
+    }
+
+    pub fn attracts(&self, item: &Item) -> bool {
+        match self {
+            MagnetTier::Metals => match item.r#type {
+                ItemType::Physical(PhysicalItem::Bulk(bulk)) => {
+                    bulk.substance.is_metal()
+                }
+                _ => false,
+            },
+            MagnetTier::Everything => true,
+        }
+    }
+
+    pub fn name(&self) -> String {
+        tr(match self {
+            MagnetTier::Metals => "magnet.metals",
+            MagnetTier::Everything => "magnet.everything",
+        })
+    }
+
+    fn field_color(&self) -> Color {
+        match self {
+            MagnetTier::Metals => Color::srgba(0.8, 0.5, 0.2, 0.4),
+            MagnetTier::Everything => Color::srgba(0.4, 0.6, 1.0, 0.4),
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct Magnet {
+    pub tier: MagnetTier,
+    pub on: bool,
+}
+
+// The ring drawn around the player at the magnet's reach.
+#[derive(Debug, Component)]
+pub struct MagnetField;
+
+// The pull, as an acceleration, on something at `offset` from the player: the
+// full `acceleration` up close, fading to nothing at the edge of the field.
+pub fn magnet_pull(offset: Vec2, magnet: &MagnetBalance) -> Vec2 {
+    let distance = offset.length();
+    if distance == 0.0 || distance >= magnet.radius {
+        return Vec2::ZERO;
+    }
+    -offset / distance * magnet.acceleration * (1.0 - distance / magnet.radius)
+}
+
+// Give the player the magnet, or a better one, once a minigame reaches the
+// level for it.
+pub fn unlock_magnet(
+    mut commands: Commands,
+    minigames: Res<MinigamesResource>,
+    mut game_events: MessageWriter<GameEvent>,
+    mut player_query: Query<(Entity, Option<&mut Magnet>), With<Player>>,
+) {
+    if !minigames.is_changed() {
+        return;
+    }
+    let Some(tier) = MagnetTier::unlocked(&minigames) else {
+        return;
+    };
+    let Ok((player_entity, magnet)) = player_query.single_mut() else {
+        return;
+    };
+    match magnet {
+        Some(magnet) if magnet.tier >= tier => return,
+        Some(mut magnet) => magnet.tier = tier,
+        None => {
+            commands
+                .entity(player_entity)
+                .insert(Magnet { tier, on: true });
+        }
+    }
+    game_events.write(GameEvent::Upgraded {
+        upgrade: tier.name(),
+    });
+}
+
+pub fn toggle_magnet(
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut magnet_query: Query<&mut Magnet, With<Player>>,
+) {
+    if !kb_input.just_pressed(KeyCode::KeyM) {
+        return;
+    }
+    for mut magnet in magnet_query.iter_mut() {
+        magnet.on = !magnet.on;
+    }
+}
+
+// Pulls on every loose item in reach the magnet attracts. Runs each frame,
+// ahead of the physics step, on the game clock, so it scales with the
+// simulation speed like physics does.
+pub fn attract_items(
+    time: Res<Time>,
+    balance: Res<BalanceConfig>,
+    item_grid: Res<ItemGrid>,
+    player_query: Query<(&Transform, &Magnet), With<Player>>,
+    mut item_query: Query<
+        (&Item, &Transform, &mut Velocity),
+        (Without<Stuck>, Without<Player>, Without<ejection::Ejecting>),
+    >,
+) {
+    let Ok((player_transform, magnet)) = player_query.single() else {
+        return;
+    };
+    if !magnet.on {
+        return;
+    }
+    let player = player_transform.translation.truncate();
+    let delta = time.delta_secs();
+    let nearby =
+        item_grid.near(player, balance.magnet.radius + Item::MAX_RADIUS);
+    let mut nearby_items = item_query.iter_many_mut(nearby);
+    while let Some((item, transform, mut velocity)) = nearby_items.fetch_next()
+    {
+        if !magnet.tier.attracts(item) {
+            continue;
+        }
+        let offset = transform.translation.truncate() - player;
+        let pull = magnet_pull(offset, &balance.magnet);
+        if pull != Vec2::ZERO {
+            velocity.linear += pull * delta;
+        }
+    }
+}
+
+// Redraw the ring when the magnet changes or its reach is retuned.
+pub fn render_magnet_field(
+    mut commands: Commands,
+    balance: Res<BalanceConfig>,
+    player_query: Query<(Entity, Ref<Magnet>), With<Player>>,
+    field_query: Query<Entity, With<MagnetField>>,
+) {
+    let Ok((player_entity, magnet)) = player_query.single() else {
+        return;
+    };
+    if !magnet.is_changed() && !balance.is_changed() {
+        return;
+    }
+    for field_entity in field_query.iter() {
+        commands.entity(field_entity).despawn();
+    }
+    if !magnet.on {
+        return;
+    }
+    commands.entity(player_entity).with_child((
+        MagnetField,
+        ShapeBuilder::with(&shapes::Circle {
+            radius: balance.magnet.radius,
+            ..default()
+        })
+        .stroke(Stroke::new(magnet.tier.field_color(), FIELD_WIDTH))
+        .build(),
+        // under the player and the items
+        Transform::from_xyz(0.0, 0.0, -1.0),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiers_unlock_with_levels() {
+        let mut minigames = MinigamesResource::default();
+        minigames.insert(minigames::foundry::ID, vec![]);
+        minigames.insert(minigames::battery::ID, vec![]);
+        assert_eq!(MagnetTier::unlocked(&minigames), None);
+
+        let foundry = Minigame::from_id(minigames::foundry::ID).unwrap();
+        for _ in 0..3 {
+            minigames.set_level(&foundry);
+        }
+        assert_eq!(MagnetTier::unlocked(&minigames), Some(MagnetTier::Metals));
+
+        let battery = Minigame::from_id(minigames::battery::ID).unwrap();
+        for _ in 0..3 {
+            minigames.set_level(&battery);
+        }
+        assert_eq!(
+            MagnetTier::unlocked(&minigames),
+            Some(MagnetTier::Everything)
+        );
+    }
+
+    #[test]
+    fn metals_tier_only_attracts_metal() {
+        let iron = Item::solid(Substance::Iron, BulkShape::Lump, 1.0);
+        let iron_powder = Item::powder(Substance::Iron, 1.0);
+        let granite = Item::solid(Substance::Granite, BulkShape::Lump, 1.0);
+        assert!(MagnetTier::Metals.attracts(&iron));
+        assert!(MagnetTier::Metals.attracts(&iron_powder));
+        assert!(!MagnetTier::Metals.attracts(&granite));
+        assert!(!MagnetTier::Metals.attracts(&Item::xp(1.0)));
+        assert!(MagnetTier::Everything.attracts(&granite));
+    }
+
+    #[test]
+    fn pull_fades_to_the_edge_of_the_field() {
+        let magnet = MagnetBalance {
+            radius: 100.0,
+            acceleration: 50.0,
+        };
+        assert_eq!(magnet_pull(Vec2::new(10.0, 0.0), &magnet).x, -45.0);
+        assert_eq!(magnet_pull(Vec2::new(0.0, -50.0), &magnet).y, 25.0);
+        assert_eq!(magnet_pull(Vec2::new(100.0, 0.0), &magnet), Vec2::ZERO);
+        assert_eq!(magnet_pull(Vec2::ZERO, &magnet), Vec2::ZERO);
+    }
+}
@@ -1,11 +1,16 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
+use bevy::ecs::system::SystemParam;
+use bevy::platform::time::Instant;
 use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::entities::item::{Item, ItemBundle, ItemType, Stuck};
+use crate::entities::item::{Item, ItemType, Stuck};
 use crate::entities::player::Player;
+use crate::entities::{
+    aura_highlight, batching, contact, heat, pins, puddle, routing, throughput,
+};
 use crate::libs::*;
 use crate::minigames::*;
 
@@ -41,6 +46,17 @@ pub enum Minigame {
     Land(land::LandMinigame),
     Life(life::LifeMinigame),
     Tree(tree::TreeMinigame),
+    Solar(solar::SolarMinigame),
+    Turbine(turbine::TurbineMinigame),
+    Quarry(quarry::QuarryMinigame),
+    Aquarium(aquarium::AquariumMinigame),
+    Composter(composter::ComposterMinigame),
+    Slots(slots::SlotsMinigame),
+    Rhythm(rhythm::RhythmMinigame),
+    Contracts(contracts::ContractsMinigame),
+    Flask(flask::FlaskMinigame),
+    WeatherStation(weather_station::WeatherStationMinigame),
+    Galaxia(galaxia::GalaxiaMinigame),
 }
 
 impl Minigame {
@@ -66,6 +82,33 @@ impl Minigame {
             land::ID => Some(Minigame::Land(land::LandMinigame::default())),
             life::ID => Some(Minigame::Life(life::LifeMinigame::default())),
             tree::ID => Some(Minigame::Tree(tree::TreeMinigame::default())),
+            solar::ID => Some(Minigame::Solar(solar::SolarMinigame::default())),
+            turbine::ID => {
+                Some(Minigame::Turbine(turbine::TurbineMinigame::default()))
+            }
+            quarry::ID => {
+                Some(Minigame::Quarry(quarry::QuarryMinigame::default()))
+            }
+            aquarium::ID => {
+                Some(Minigame::Aquarium(aquarium::AquariumMinigame::default()))
+            }
+            composter::ID => Some(Minigame::Composter(
+                composter::ComposterMinigame::default(),
+            )),
+            slots::ID => Some(Minigame::Slots(slots::SlotsMinigame::default())),
+            rhythm::ID => {
+                Some(Minigame::Rhythm(rhythm::RhythmMinigame::default()))
+            }
+            contracts::ID => Some(Minigame::Contracts(
+                contracts::ContractsMinigame::default(),
+            )),
+            flask::ID => Some(Minigame::Flask(flask::FlaskMinigame::default())),
+            weather_station::ID => Some(Minigame::WeatherStation(
+                weather_station::WeatherStationMinigame::default(),
+            )),
+            galaxia::ID => {
+                Some(Minigame::Galaxia(galaxia::GalaxiaMinigame::default()))
+            }
             _ => None,
         }
     }
@@ -82,11 +125,23 @@ impl Minigame {
             Minigame::Land(_) => land::ID,
             Minigame::Life(_) => life::ID,
             Minigame::Tree(_) => tree::ID,
+            Minigame::Solar(_) => solar::ID,
+            Minigame::Turbine(_) => turbine::ID,
+            Minigame::Quarry(_) => quarry::ID,
+            Minigame::Aquarium(_) => aquarium::ID,
+            Minigame::Composter(_) => composter::ID,
+            Minigame::Slots(_) => slots::ID,
+            Minigame::Rhythm(_) => rhythm::ID,
+            Minigame::Contracts(_) => contracts::ID,
+            Minigame::Flask(_) => flask::ID,
+            Minigame::WeatherStation(_) => weather_station::ID,
+            Minigame::Galaxia(_) => galaxia::ID,
         }
     }
 
-    pub fn name(&self) -> &str {
-        match self {
+    // Each minigame's `name` and `description` are locale keys.
+    pub fn name(&self) -> String {
+        tr(match self {
             Minigame::Button(m) => m.name(),
             Minigame::PrimordialOcean(m) => m.name(),
             Minigame::Rune(m) => m.name(),
@@ -97,11 +152,22 @@ impl Minigame {
             Minigame::Land(m) => m.name(),
             Minigame::Life(m) => m.name(),
             Minigame::Tree(m) => m.name(),
-        }
+            Minigame::Solar(m) => m.name(),
+            Minigame::Turbine(m) => m.name(),
+            Minigame::Quarry(m) => m.name(),
+            Minigame::Aquarium(m) => m.name(),
+            Minigame::Composter(m) => m.name(),
+            Minigame::Slots(m) => m.name(),
+            Minigame::Rhythm(m) => m.name(),
+            Minigame::Contracts(m) => m.name(),
+            Minigame::Flask(m) => m.name(),
+            Minigame::WeatherStation(m) => m.name(),
+            Minigame::Galaxia(m) => m.name(),
+        })
     }
 
-    pub fn description(&self) -> &str {
-        match self {
+    pub fn description(&self) -> String {
+        tr(match self {
             Minigame::Button(m) => m.description(),
             Minigame::PrimordialOcean(m) => m.description(),
             Minigame::Rune(m) => m.description(),
@@ -112,7 +178,18 @@ impl Minigame {
             Minigame::Land(m) => m.description(),
             Minigame::Life(m) => m.description(),
             Minigame::Tree(m) => m.description(),
-        }
+            Minigame::Solar(m) => m.description(),
+            Minigame::Turbine(m) => m.description(),
+            Minigame::Quarry(m) => m.description(),
+            Minigame::Aquarium(m) => m.description(),
+            Minigame::Composter(m) => m.description(),
+            Minigame::Slots(m) => m.description(),
+            Minigame::Rhythm(m) => m.description(),
+            Minigame::Contracts(m) => m.description(),
+            Minigame::Flask(m) => m.description(),
+            Minigame::WeatherStation(m) => m.description(),
+            Minigame::Galaxia(m) => m.description(),
+        })
     }
 
     pub fn position(&self) -> Vec2 {
@@ -127,6 +204,17 @@ impl Minigame {
             Minigame::Land(_) => land::POSITION,
             Minigame::Life(_) => life::POSITION,
             Minigame::Tree(_) => tree::POSITION,
+            Minigame::Solar(_) => solar::POSITION,
+            Minigame::Turbine(_) => turbine::POSITION,
+            Minigame::Quarry(_) => quarry::POSITION,
+            Minigame::Aquarium(_) => aquarium::POSITION,
+            Minigame::Composter(_) => composter::POSITION,
+            Minigame::Slots(_) => slots::POSITION,
+            Minigame::Rhythm(_) => rhythm::POSITION,
+            Minigame::Contracts(_) => contracts::POSITION,
+            Minigame::Flask(_) => flask::POSITION,
+            Minigame::WeatherStation(_) => weather_station::POSITION,
+            Minigame::Galaxia(_) => galaxia::POSITION,
         }
     }
 
@@ -142,6 +230,17 @@ impl Minigame {
             Minigame::Land(m) => m.area(),
             Minigame::Life(m) => m.area(),
             Minigame::Tree(m) => m.area(),
+            Minigame::Solar(m) => m.area(),
+            Minigame::Turbine(m) => m.area(),
+            Minigame::Quarry(m) => m.area(),
+            Minigame::Aquarium(m) => m.area(),
+            Minigame::Composter(m) => m.area(),
+            Minigame::Slots(m) => m.area(),
+            Minigame::Rhythm(m) => m.area(),
+            Minigame::Contracts(m) => m.area(),
+            Minigame::Flask(m) => m.area(),
+            Minigame::WeatherStation(m) => m.area(),
+            Minigame::Galaxia(m) => m.area(),
         }
     }
 
@@ -154,13 +253,15 @@ impl Minigame {
         }
     }
 
-    // The item store, for the minigames that hold one (chest, battery). This
-    // is the single source of truth for what's stored; the inventory UI reads
-    // it through the owning minigame entity rather than keeping its own copy.
+    // The item store, for the minigames that hold one (chest, battery, flask).
+    // This is the single source of truth for what's stored; the inventory UI
+    // reads it through the owning minigame entity rather than keeping its own
+    // copy.
     pub fn items(&self) -> Option<&HashMap<ItemType, f32>> {
         match self {
             Minigame::Chest(m) => Some(&m.items),
             Minigame::Battery(m) => Some(&m.items),
+            Minigame::Flask(m) => Some(&m.items),
             _ => None,
         }
     }
@@ -169,10 +270,187 @@ impl Minigame {
         match self {
             Minigame::Chest(m) => Some(&mut m.items),
             Minigame::Battery(m) => Some(&mut m.items),
+            Minigame::Flask(m) => Some(&mut m.items),
             _ => None,
         }
     }
 
+    // Total amount the minigame is holding on to and could still give back
+    // or use up: stored items, balls, queued cooking, banked energy. Reported
+    // by the amount audit.
+    pub fn held_amount(&self) -> f32 {
+        match self {
+            Minigame::Chest(m) => total_stored(&m.items),
+            Minigame::Battery(m) => total_stored(&m.items),
+            Minigame::Flask(m) => total_stored(&m.items),
+            Minigame::BallBreaker(m) => m.balls.values().sum::<u32>() as f32,
+            Minigame::Foundry(m) => {
+                let queued = m.cooking.iter().chain(&m.special_cooking);
+                m.heat() + queued.map(|item| item.amount).sum::<f32>()
+            }
+            Minigame::Land(m) => m.energy,
+            Minigame::Life(m) => m.energy,
+            Minigame::Quarry(m) => m.energy,
+            Minigame::Aquarium(m) => m.water + m.food,
+            Minigame::Composter(m) => m.matter,
+            Minigame::Slots(m) => m.credits,
+            Minigame::WeatherStation(m) => m.water + m.light,
+            Minigame::Button(_)
+            | Minigame::PrimordialOcean(_)
+            | Minigame::Rune(_)
+            | Minigame::Tree(_)
+            | Minigame::Solar(_)
+            | Minigame::Rhythm(_)
+            | Minigame::Contracts(_)
+            | Minigame::Galaxia(_)
+            | Minigame::Turbine(_) => 0.0,
+        }
+    }
+
+    // Whether what it puts out falls out through a gate in its bottom wall
+    // (see `OneWayGate`), rather than coming out at the emit point.
+    pub fn drops_out(&self) -> bool {
+        matches!(self, Minigame::BallBreaker(_))
+    }
+
+    // Whether it sips from puddles lying over its aura (see puddle.rs) rather
+    // than ingesting them whole.
+    pub fn drinks(&self) -> bool {
+        matches!(self, Minigame::Land(_) | Minigame::Tree(_))
+    }
+
+    // Takes a sip of a puddle, `item`. Returns how much of it was drunk.
+    pub fn drink(
+        &mut self,
+        commands: &mut Commands,
+        rand: &mut Random,
+        images: &mut Assets<Image>,
+        generated_image_assets: &mut image_gen::GeneratedImageAssets,
+        minigame_entity: Entity,
+        minigame_transform: &GlobalTransform,
+        minigame_area: &RectangularArea,
+        item: &Item,
+    ) -> f32 {
+        match self {
+            // stored like any other bulk
+            Minigame::Land(_) => self.ingest_item(
+                commands,
+                rand,
+                images,
+                generated_image_assets,
+                minigame_entity,
+                minigame_transform,
+                minigame_area,
+                item,
+            ),
+            Minigame::Tree(m) => m.drink(item),
+            _ => 0.0,
+        }
+    }
+
+    // Notes that `amount` of `item`, just ingested, came in softened by heat
+    // (see heat.rs). Only the foundry cares: it smelts it for less.
+    pub fn ingested_softened(&mut self, item: &Item, amount: f32) {
+        if let Minigame::Foundry(m) = self {
+            m.add_softened(item, amount);
+        }
+    }
+
+    // Feed XP into the minigame; it counts as progress on whatever the
+    // minigame levels by. Returns whether it should now level up, or None if
+    // it doesn't take XP: the stores (chest, battery) keep XP as an item, the
+    // flask keeps only gases, and rune and land level on what's drawn or grown
+    // rather than on amounts.
+    pub fn add_xp(&mut self, amount: f32) -> Option<bool> {
+        match self {
+            Minigame::Button(m) => Some(m.add_xp(amount)),
+            Minigame::PrimordialOcean(m) => Some(m.add_xp(amount)),
+            Minigame::Foundry(m) => Some(m.add_xp(amount)),
+            Minigame::BallBreaker(m) => Some(m.add_xp(amount)),
+            Minigame::Life(m) => Some(m.add_xp(amount)),
+            Minigame::Tree(m) => Some(m.add_xp(amount)),
+            Minigame::Solar(m) => Some(m.add_xp(amount)),
+            Minigame::Turbine(m) => Some(m.add_xp(amount)),
+            Minigame::Quarry(m) => Some(m.add_xp(amount)),
+            Minigame::Aquarium(m) => Some(m.add_xp(amount)),
+            Minigame::Composter(m) => Some(m.add_xp(amount)),
+            Minigame::Slots(m) => Some(m.add_xp(amount)),
+            Minigame::Rhythm(m) => Some(m.add_xp(amount)),
+            Minigame::Contracts(m) => Some(m.add_xp(amount)),
+            Minigame::WeatherStation(m) => Some(m.add_xp(amount)),
+            Minigame::Rune(_)
+            | Minigame::Chest(_)
+            | Minigame::Battery(_)
+            | Minigame::Flask(_)
+            | Minigame::Galaxia(_)
+            | Minigame::Land(_) => None,
+        }
+    }
+
+    // How much of `item` `ingest_item` would take right now, without taking
+    // it. A minigame that's `LevelingUp` takes nothing either way.
+    pub fn accepted_amount(&self, item: &Item) -> f32 {
+        if item.r#type.is_xp() && self.takes_xp() {
+            return item.amount;
+        }
+        let whole = |accepted: bool| if accepted { item.amount } else { 0.0 };
+        match self {
+            Minigame::PrimordialOcean(_) => {
+                primordial_ocean::PrimordialOceanMinigame::accepted_amount(item)
+            }
+            Minigame::Chest(m) => whole(m.can_accept(item)),
+            Minigame::Battery(m) => whole(m.can_accept(item)),
+            Minigame::Flask(_) => whole(flask::FlaskMinigame::can_accept(item)),
+            Minigame::Foundry(_) => {
+                foundry::FoundryMinigame::accepted_amount(item)
+            }
+            Minigame::BallBreaker(_) => {
+                ball_breaker::BallBreakerMinigame::accepted_amount(item)
+            }
+            Minigame::Land(m) => m.accepted_amount(item),
+            Minigame::Life(m) => m.accepted_amount(item),
+            Minigame::Tree(m) => m.accepted_amount(item),
+            Minigame::Quarry(_) => {
+                quarry::QuarryMinigame::accepted_amount(item)
+            }
+            Minigame::Aquarium(_) => {
+                aquarium::AquariumMinigame::accepted_amount(item)
+            }
+            Minigame::Composter(_) => {
+                whole(composter::ComposterMinigame::is_compostable(item))
+            }
+            Minigame::Slots(_) => slots::SlotsMinigame::accepted_amount(item),
+            Minigame::Contracts(m) => m.accepted_amount(item),
+            Minigame::WeatherStation(_) => {
+                weather_station::WeatherStationMinigame::accepted_amount(item)
+            }
+            Minigame::Galaxia(m) => m.accepted_amount(item),
+            Minigame::Button(_)
+            | Minigame::Rune(_)
+            | Minigame::Solar(_)
+            | Minigame::Turbine(_)
+            | Minigame::Rhythm(_) => 0.0,
+        }
+    }
+
+    // Whether `ingest_item` would take any of `item` right now.
+    pub fn can_ingest(&self, item: &Item) -> bool {
+        self.accepted_amount(item) > 0.0
+    }
+
+    // Whether XP levels it up; see `add_xp`.
+    fn takes_xp(&self) -> bool {
+        !matches!(
+            self,
+            Minigame::Rune(_)
+                | Minigame::Chest(_)
+                | Minigame::Battery(_)
+                | Minigame::Flask(_)
+                | Minigame::Galaxia(_)
+                | Minigame::Land(_)
+        )
+    }
+
     // The level the minigame currently has.
     pub fn level(&self) -> u8 {
         match self {
@@ -186,24 +464,158 @@ impl Minigame {
             Minigame::Land(m) => m.level(),
             Minigame::Life(m) => m.level(),
             Minigame::Tree(m) => m.level(),
+            Minigame::Solar(m) => m.level(),
+            Minigame::Turbine(m) => m.level(),
+            Minigame::Quarry(m) => m.level(),
+            Minigame::Aquarium(m) => m.level(),
+            Minigame::Composter(m) => m.level(),
+            Minigame::Slots(m) => m.level(),
+            Minigame::Rhythm(m) => m.level(),
+            Minigame::Contracts(m) => m.level(),
+            Minigame::Flask(m) => m.level(),
+            Minigame::WeatherStation(m) => m.level(),
+            Minigame::Galaxia(m) => m.level(),
+        }
+    }
+
+    // How far along the minigame is toward its next level, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        match self {
+            Minigame::Button(m) => m.progress(),
+            Minigame::PrimordialOcean(m) => m.progress(),
+            Minigame::Rune(m) => m.progress(),
+            Minigame::Chest(m) => m.progress(),
+            Minigame::Battery(m) => m.progress(),
+            Minigame::Foundry(m) => m.progress(),
+            Minigame::BallBreaker(m) => m.progress(),
+            Minigame::Land(m) => m.progress(),
+            Minigame::Life(m) => m.progress(),
+            Minigame::Tree(m) => m.progress(),
+            Minigame::Solar(m) => m.progress(),
+            Minigame::Turbine(m) => m.progress(),
+            Minigame::Quarry(m) => m.progress(),
+            Minigame::Aquarium(m) => m.progress(),
+            Minigame::Composter(m) => m.progress(),
+            Minigame::Slots(m) => m.progress(),
+            Minigame::Rhythm(m) => m.progress(),
+            Minigame::Contracts(m) => m.progress(),
+            Minigame::Flask(m) => m.progress(),
+            Minigame::WeatherStation(m) => m.progress(),
+            Minigame::Galaxia(m) => m.progress(),
         }
     }
 
-    // Recreate minigame with correct new level, by its internal logic.
+    // Recreate minigame with correct new level, by its internal logic, then
+    // move everything the player built up over to it. Each variant's `levelup`
+    // only decides what the next level looks like; `carry_over` is the one
+    // place that says what survives the respawn.
     pub fn levelup(&self) -> Self {
         match self {
-            Minigame::Button(m) => Minigame::Button(m.levelup()),
+            Minigame::Button(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Button(next)
+            }
             Minigame::PrimordialOcean(m) => {
-                Minigame::PrimordialOcean(m.levelup())
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::PrimordialOcean(next)
+            }
+            Minigame::Rune(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Rune(next)
+            }
+            Minigame::Chest(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Chest(next)
+            }
+            Minigame::Battery(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Battery(next)
+            }
+            Minigame::Foundry(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Foundry(next)
+            }
+            Minigame::BallBreaker(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::BallBreaker(next)
+            }
+            Minigame::Land(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Land(next)
+            }
+            Minigame::Life(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Life(next)
+            }
+            Minigame::Tree(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Tree(next)
+            }
+            Minigame::Solar(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Solar(next)
+            }
+            Minigame::Turbine(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Turbine(next)
+            }
+            Minigame::Quarry(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Quarry(next)
+            }
+            Minigame::Aquarium(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Aquarium(next)
+            }
+            Minigame::Composter(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Composter(next)
+            }
+            Minigame::Slots(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Slots(next)
+            }
+            Minigame::Rhythm(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Rhythm(next)
+            }
+            Minigame::Contracts(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Contracts(next)
+            }
+            Minigame::Flask(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Flask(next)
+            }
+            Minigame::WeatherStation(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::WeatherStation(next)
+            }
+            Minigame::Galaxia(m) => {
+                let mut next = m.levelup();
+                m.carry_over(&mut next);
+                Minigame::Galaxia(next)
             }
-            Minigame::Rune(m) => Minigame::Rune(m.levelup()),
-            Minigame::Chest(m) => Minigame::Chest(m.levelup()),
-            Minigame::Battery(m) => Minigame::Battery(m.levelup()),
-            Minigame::Foundry(m) => Minigame::Foundry(m.levelup()),
-            Minigame::BallBreaker(m) => Minigame::BallBreaker(m.levelup()),
-            Minigame::Land(m) => Minigame::Land(m.levelup()),
-            Minigame::Life(m) => Minigame::Life(m.levelup()),
-            Minigame::Tree(m) => Minigame::Tree(m.levelup()),
         }
     }
 
@@ -213,20 +625,28 @@ impl Minigame {
         transform: Transform,
         random: &mut Random,
         asset_server: &AssetServer,
-        _images: &mut Assets<Image>,
-        _generated_image_assets: &mut image_gen::GeneratedImageAssets,
+        images: &mut Assets<Image>,
+        generated_image_assets: &mut image_gen::GeneratedImageAssets,
+        item_grid: &ItemGrid,
         item_query: &Query<
             (&Transform, &CircularArea, Entity),
             (With<Item>, Without<Stuck>),
         >,
         player_query: &Query<(&Transform, &CircularArea, Entity), With<Player>>,
     ) -> Entity {
-        self.clear_clutter(commands, &transform, item_query, player_query);
+        self.clear_clutter(
+            commands,
+            &transform,
+            item_grid,
+            item_query,
+            player_query,
+        );
 
         let area = self.area();
         let name = self.name();
         let description = self.description();
         let level = self.level();
+        let progress = self.progress();
         let mut new_minigame = self.clone();
         let entity = commands
             // Give the entity its spatial components up front, before spawning
@@ -239,14 +659,17 @@ impl Minigame {
                 spawn_minigame_container(
                     parent,
                     area,
-                    name,
-                    description,
+                    &name,
+                    &description,
                     level,
+                    progress,
+                    self.drops_out(),
                 );
                 parent.spawn(MinigameAuraBundle::new(
                     parent.target_entity(),
                     area,
                 ));
+                routing::spawn_ports(parent, self.id(), area);
                 match &mut new_minigame {
                     Minigame::Button(m) => m.spawn(parent),
                     Minigame::Rune(m) => m.spawn(parent),
@@ -254,12 +677,33 @@ impl Minigame {
                     Minigame::Chest(m) => m.spawn(parent, asset_server),
                     Minigame::Battery(m) => m.spawn(parent, asset_server),
                     Minigame::Foundry(m) => m.spawn(parent),
-                    Minigame::BallBreaker(m) => {
-                        m.spawn(parent, random, asset_server)
-                    }
+                    Minigame::BallBreaker(m) => m.spawn(
+                        parent,
+                        random,
+                        asset_server,
+                        images,
+                        generated_image_assets,
+                    ),
                     Minigame::Land(m) => m.spawn(parent),
                     Minigame::Life(m) => m.spawn(parent),
-                    Minigame::Tree(m) => m.spawn(parent, asset_server),
+                    Minigame::Tree(m) => m.spawn(
+                        parent,
+                        random,
+                        asset_server,
+                        images,
+                        generated_image_assets,
+                    ),
+                    Minigame::Solar(m) => m.spawn(parent),
+                    Minigame::Turbine(m) => m.spawn(parent),
+                    Minigame::Quarry(m) => m.spawn(parent),
+                    Minigame::Aquarium(m) => m.spawn(parent, random),
+                    Minigame::Composter(m) => m.spawn(parent),
+                    Minigame::Slots(m) => m.spawn(parent),
+                    Minigame::Rhythm(m) => m.spawn(parent),
+                    Minigame::Contracts(m) => m.spawn(parent),
+                    Minigame::Flask(m) => m.spawn(parent),
+                    Minigame::WeatherStation(m) => m.spawn(parent),
+                    Minigame::Galaxia(m) => m.spawn(parent),
                 };
             })
             .id();
@@ -282,6 +726,14 @@ impl Minigame {
         minigame_area: &RectangularArea,
         item: &Item,
     ) -> f32 {
+        if item.r#type.is_xp() {
+            if let Some(level_up) = self.add_xp(item.amount) {
+                if level_up {
+                    commands.entity(minigame_entity).insert(LevelingUp);
+                }
+                return item.amount;
+            }
+        }
         match self {
             Minigame::Button(m) => m.ingest_item(),
             Minigame::PrimordialOcean(m) => {
@@ -312,26 +764,48 @@ impl Minigame {
                 item,
             ),
             Minigame::Life(m) => m.ingest_item(rand, item),
-            Minigame::Tree(m) => m.ingest_item(),
+            Minigame::Tree(m) => m.ingest_item(commands, minigame_entity, item),
+            Minigame::Solar(m) => m.ingest_item(),
+            Minigame::Turbine(m) => m.ingest_item(),
+            Minigame::Quarry(m) => m.ingest_item(item),
+            Minigame::Aquarium(m) => m.ingest_item(item),
+            Minigame::Composter(m) => m.ingest_item(item),
+            Minigame::Slots(m) => m.ingest_item(item),
+            Minigame::Rhythm(m) => m.ingest_item(),
+            Minigame::Contracts(m) => m.ingest_item(item),
+            Minigame::Flask(m) => {
+                m.ingest_item(commands, minigame_entity, item)
+            }
+            Minigame::WeatherStation(m) => m.ingest_item(item),
+            Minigame::Galaxia(m) => m.ingest_item(item),
         }
     }
 
-    // Clear items and players from the minigame area.
+    // Clear items and players from the minigame area. Only the items
+    // `item_grid` has near the area are looked at.
     pub fn clear_clutter(
         &self,
         commands: &mut Commands,
         minigame_transform: &Transform,
+        item_grid: &ItemGrid,
         item_query: &Query<
             (&Transform, &CircularArea, Entity),
             (With<Item>, Without<Stuck>),
         >,
         player_query: &Query<(&Transform, &CircularArea, Entity), With<Player>>,
     ) {
-        let minigame_area = PositionedArea {
-            position: minigame_transform.translation.truncate(),
-            area: Area::Rectangular(self.area_with_header()),
-        };
-        for (&item_transform, &item_area, item_entity) in item_query.iter() {
+        let minigame_area = self.clutter_area(minigame_transform);
+        let region = Rect::from_center_size(
+            minigame_area.position,
+            minigame_area.dimensions(),
+        )
+        .inflate(Item::MAX_RADIUS * 2.0);
+        for item_entity in item_grid.in_rect(region) {
+            let Ok((&item_transform, &item_area, _)) =
+                item_query.get(item_entity)
+            else {
+                continue;
+            };
             Self::clear_one_clutter(
                 commands,
                 &minigame_area,
@@ -354,13 +828,41 @@ impl Minigame {
                     position: player_transform.translation.truncate(),
                     area: Area::Circular(player_area),
                 },
-                // Double max item radius to account for holding items on both sides
-                player_area.radius + (Item::MAX_RADIUS * 2.0),
+                Self::player_clutter_buffer(&player_area),
                 player_entity,
             );
         }
     }
 
+    // The area, header included, kept clear of items and players.
+    pub fn clutter_area(
+        &self,
+        minigame_transform: &Transform,
+    ) -> PositionedArea {
+        PositionedArea {
+            position: minigame_transform.translation.truncate(),
+            area: Area::Rectangular(self.area_with_header()),
+        }
+    }
+
+    // Whether this minigame at `position` would keep clear of every area in
+    // `placed` (the others' `clutter_area`s), headers and all. Moving one
+    // (placement.rs) only puts it down where this holds.
+    pub fn placement_clear(
+        &self,
+        position: Vec2,
+        placed: &[PositionedArea],
+    ) -> bool {
+        let area = self
+            .clutter_area(&Transform::from_translation(position.extend(0.0)));
+        placed.iter().all(|other| !area.overlaps(other))
+    }
+
+    // Double max item radius to account for holding items on both sides
+    pub fn player_clutter_buffer(player_area: &CircularArea) -> f32 {
+        player_area.radius + (Item::MAX_RADIUS * 2.0)
+    }
+
     // Clears one entity from the area, moving it to the nearest edge.
     // Buffer is the radius of the entity, so it does not overlap the edge.
     fn clear_one_clutter(
@@ -370,46 +872,81 @@ impl Minigame {
         buffer: f32,
         entity: Entity,
     ) {
-        if minigame_area.overlaps(area) {
-            commands.entity(entity).insert(Transform::from_translation(
-                minigame_area
-                    .grow(buffer + 1.0) // +1.0 to ensure it is outside
-                    .nearest_edge(area.position)
-                    .extend(0.0),
-            ));
+        if let Some(position) =
+            Self::cleared_position(minigame_area, area, buffer)
+        {
+            commands
+                .entity(entity)
+                .insert(Transform::from_translation(position.extend(0.0)));
         }
     }
+
+    // Where `clear_one_clutter` would move something at `area`, if it's in
+    // the way.
+    pub fn cleared_position(
+        minigame_area: &PositionedArea,
+        area: &PositionedArea,
+        buffer: f32,
+    ) -> Option<Vec2> {
+        minigame_area.overlaps(area).then(|| {
+            minigame_area
+                .grow(buffer + 1.0) // +1.0 to ensure it is outside
+                .nearest_edge(area.position)
+        })
+    }
 }
 
-// Respawn leveled-up minigames.
+// Respawn leveled-up minigames, once their levelup animation has played.
 // Spawn unlocked minigames.
+// The first tick a minigame is `LevelingUp` it's armed with a
+// `DelayedTrigger<LevelingUp>`: it pulses in size (`animate_levelup`) for
+// `LEVELUP_SECONDS` while every input system skips it (they all check for
+// `LevelingUp`), then the trigger fires and it's replaced by the next level.
 pub fn levelup(
     mut commands: Commands,
+    mut fired: MessageReader<TriggerFired<LevelingUp>>,
     mut random: ResMut<Random>,
     asset_server: Res<AssetServer>,
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
     mut minigames: ResMut<MinigamesResource>,
-    mut query: Query<
+    mut game_events: MessageWriter<GameEvent>,
+    query: Query<
         (
-            &mut Minigame,
+            &Minigame,
             &Transform,
-            &GlobalTransform,
-            &RectangularArea,
+            Has<DelayedTrigger<LevelingUp>>,
             Entity,
         ),
         With<LevelingUp>,
     >,
+    item_grid: Res<ItemGrid>,
     item_query: Query<
         (&Transform, &CircularArea, Entity),
         (With<Item>, Without<Stuck>),
     >,
     player_query: Query<(&Transform, &CircularArea, Entity), With<Player>>,
+    mut timings: ResMut<FrameTimings>,
 ) {
-    for (minigame, transform, _minigame_global_transform, _area, entity) in
-        query.iter_mut()
-    {
+    let started = Instant::now();
+    let fired: HashSet<Entity> = fired.read().map(|f| f.entity).collect();
+    for (minigame, transform, armed, entity) in query.iter() {
+        if !fired.contains(&entity) {
+            if !armed {
+                commands
+                    .entity(entity)
+                    .insert(DelayedTrigger::<LevelingUp>::new(LEVELUP_SECONDS));
+            }
+            continue;
+        }
+
         let new_minigame = minigame.levelup();
+        let _span = info_span!(
+            "levelup",
+            minigame = new_minigame.id(),
+            level = new_minigame.level()
+        )
+        .entered();
 
         // Despawn the old minigame
         commands.entity(entity).despawn();
@@ -419,44 +956,142 @@ pub fn levelup(
         // entity instead of the despawned one.
         let new_entity = new_minigame.spawn(
             &mut commands,
-            *transform,
+            transform.with_scale(Vec3::ONE),
             &mut random,
             &asset_server,
             &mut images,
             &mut generated_image_assets,
+            &item_grid,
             &item_query,
             &player_query,
         );
         minigames.set_entity(new_minigame.id(), new_entity);
         // Update minigame level
         minigames.set_level(&new_minigame);
+        game_events.write(GameEvent::LeveledUp {
+            minigame: new_minigame.name(),
+            level: new_minigame.level(),
+        });
         // Unlock minigames
         for id in minigames.to_unlock(minigame.id()) {
-            if let Some(unlocked_minigame) = Minigame::from_id(&id) {
-                let pos = unlocked_minigame.position();
-                let entity = unlocked_minigame.spawn(
-                    &mut commands,
-                    Transform::from_translation(pos.extend(0.0)),
-                    &mut random,
-                    &asset_server,
-                    &mut images,
-                    &mut generated_image_assets,
-                    &item_query,
-                    &player_query,
-                );
-                minigames.set_entity(&id, entity);
+            if let Some(unlocked) = Minigame::from_id(&id) {
+                game_events.write(GameEvent::Unlocked {
+                    minigame: unlocked.name(),
+                });
             }
+            spawn_unlocked(
+                &id,
+                &mut commands,
+                &mut random,
+                &asset_server,
+                &mut images,
+                &mut generated_image_assets,
+                &mut minigames,
+                &item_grid,
+                &item_query,
+                &player_query,
+            );
         }
     }
+    timings.add(Timed::Levelup, started.elapsed());
+}
+
+pub fn animate_levelup(
+    mut query: Query<
+        (&mut Transform, &DelayedTrigger<LevelingUp>),
+        With<Minigame>,
+    >,
+) {
+    for (mut transform, trigger) in query.iter_mut() {
+        transform.scale = Vec3::splat(levelup_scale(trigger.fraction()));
+    }
+}
+
+// Spawns a freshly unlocked minigame at its home position, or where it was
+// moved to, and records its entity. Returns None for an unknown id.
+pub fn spawn_unlocked(
+    id: &str,
+    commands: &mut Commands,
+    random: &mut Random,
+    asset_server: &AssetServer,
+    images: &mut Assets<Image>,
+    generated_image_assets: &mut image_gen::GeneratedImageAssets,
+    minigames: &mut MinigamesResource,
+    item_grid: &ItemGrid,
+    item_query: &Query<
+        (&Transform, &CircularArea, Entity),
+        (With<Item>, Without<Stuck>),
+    >,
+    player_query: &Query<(&Transform, &CircularArea, Entity), With<Player>>,
+) -> Option<Entity> {
+    let minigame = Minigame::from_id(id)?;
+    let pos = minigames.moved(id).unwrap_or(minigame.position());
+    let entity = minigame.spawn(
+        commands,
+        Transform::from_translation(pos.extend(0.0)),
+        random,
+        asset_server,
+        images,
+        generated_image_assets,
+        item_grid,
+        item_query,
+        player_query,
+    );
+    minigames.set_entity(id, entity);
+    Some(entity)
 }
 
 #[derive(Debug, Copy, Clone, Component)]
 pub struct LevelingUp;
 
-const META_HEIGHT: f32 = 25.0;
-const BUTTON_WIDTH: f32 = 25.0;
-const BUTTON_COUNT: f32 = 1.0;
+// XP that takes a minigame without an amount-based level (ball breaker, tree)
+// from `level` to the next. Grows by `xp_growth` in assets/balance.ron each
+// level; at 2 it doubles, like the log2 curves the others level on.
+pub fn xp_for_levelup(level: u8) -> f32 {
+    balance().levelup.xp_growth.powi(level as i32)
+}
+
+// How far `amount` has come from the current level to the next, on the log2
+// curves where level n is reached at 2^(n-1).
+pub fn log2_level_progress(amount: f32, level: u8) -> f32 {
+    let from = if level == 0 {
+        0.0
+    } else {
+        2f32.powi(level as i32 - 1)
+    };
+    let to = 2f32.powi(level as i32);
+    ((amount - from) / (to - from)).clamp(0.0, 1.0)
+}
+
+// The XP left over once a levelup has spent what it needed, if it could.
+pub fn xp_left_after_levelup(level: u8, xp: f32) -> f32 {
+    let needed = xp_for_levelup(level);
+    if xp >= needed {
+        xp - needed
+    } else {
+        xp
+    }
+}
+
+pub const LEVELUP_SECONDS: f32 = 0.5;
+// How much bigger a minigame gets at the peak of its levelup pulse.
+const LEVELUP_GROWTH: f32 = 0.15;
+
+// A `LevelingUp` minigame's size `t` of the way through its animation; it
+// swells up and back down. See `levelup`.
+pub fn levelup_scale(t: f32) -> f32 {
+    1.0 + LEVELUP_GROWTH * (t * std::f32::consts::PI).sin()
+}
+
+pub const META_HEIGHT: f32 = 25.0;
+pub const BUTTON_WIDTH: f32 = 25.0;
+const BUTTON_COUNT: f32 = 2.0;
 const WALL_THICKNESS: f32 = 1.0;
+const GATE_THICKNESS: f32 = 3.0;
+const GATE_COLOR: Color = Color::srgb(0.3, 0.6, 1.0);
+const PROGRESS_BAR_HEIGHT: f32 = 3.0;
+const PROGRESS_BAR_COLOR: Color = Color::srgb(0.2, 0.7, 0.3);
+const PROGRESS_BAR_BACKGROUND: Color = Color::srgb(0.85, 0.85, 0.85);
 
 #[derive(Debug, Bundle)]
 pub struct MinigameAuraBundle {
@@ -498,29 +1133,32 @@ pub fn spawn_minigame_container(
     name: &str,
     description: &str,
     level: u8,
+    progress: f32,
+    bottom_gate: bool,
 ) {
     let minigame = parent.target_entity();
-    spawn_minigame_bounds(parent, area);
+    spawn_minigame_bounds(parent, minigame, area, bottom_gate);
     let meta_area = RectangularArea {
         width: area.width,
         height: META_HEIGHT,
     };
     // Prevents player and resources from directly entering the minigame.
     // Necessary because resource speed can allow tunneling.
-    parent.spawn((
+    let mut blocker = parent.spawn((
+        EtherBlocker { minigame },
         Collider::from(area.grow(0.0, META_HEIGHT)),
         CollisionGroups::new(ETHER_GROUP, ether_filter()),
         Transform::from_xyz(0.0, META_HEIGHT / 2.0, 0.0),
         Visibility::default(),
     ));
+    if bottom_gate {
+        // so what it drops can get out
+        blocker.insert(ActiveHooks::MODIFY_SOLVER_CONTACTS);
+    }
     // Spawn the rest
     parent
         .spawn((
-            Transform::from_xyz(
-                0.0,
-                area.top() + META_HEIGHT / 2.0,
-                0.0,
-            ),
+            Transform::from_xyz(0.0, area.top() + META_HEIGHT / 2.0, 0.0),
             Visibility::default(),
         ))
         .with_children(|parent| {
@@ -537,6 +1175,8 @@ pub fn spawn_minigame_container(
                 ),
             ));
             spawn_minigame_name(parent, name, &area);
+            spawn_progress_bar(parent, meta_area, minigame, progress);
+            throughput::spawn_gauge(parent, meta_area, minigame);
             spawn_minigame_buttons(
                 parent,
                 meta_area,
@@ -553,7 +1193,8 @@ pub fn spawn_minigame_name(
     area: &RectangularArea,
 ) {
     // set font size so it fits in the space
-    let font_size = (area.width / name.len() as f32).clamp(10.0, 24.0);
+    let font_size =
+        (area.width / name.chars().count() as f32).clamp(10.0, 24.0);
     parent.spawn((
         Text2d::new(name),
         TextFont {
@@ -573,6 +1214,67 @@ pub fn spawn_minigame_name(
     ));
 }
 
+// The fill of the bar along the bottom of the meta header. `width` is the bar
+// when full.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct ProgressBar {
+    pub minigame: Entity,
+    pub width: f32,
+}
+
+impl ProgressBar {
+    // Grows from the left edge.
+    pub fn fill(&self, progress: f32) -> (Vec2, f32) {
+        let width = self.width * progress;
+        let left = -(BUTTON_WIDTH * BUTTON_COUNT + self.width) / 2.0;
+        (Vec2::new(width, PROGRESS_BAR_HEIGHT), left + width / 2.0)
+    }
+}
+
+pub fn spawn_progress_bar(
+    parent: &mut ChildSpawnerCommands,
+    area: RectangularArea,
+    minigame: Entity,
+    progress: f32,
+) {
+    // leaves room for the buttons on the right
+    let width = area.width - BUTTON_WIDTH * BUTTON_COUNT;
+    let y = -area.height / 2.0 + PROGRESS_BAR_HEIGHT / 2.0;
+    let bar = ProgressBar { minigame, width };
+    let (size, x) = bar.fill(progress);
+    parent.spawn((
+        Sprite::from_color(
+            PROGRESS_BAR_BACKGROUND,
+            Vec2::new(width, PROGRESS_BAR_HEIGHT),
+        ),
+        Transform::from_xyz(-(BUTTON_WIDTH * BUTTON_COUNT) / 2.0, y, -0.5),
+    ));
+    parent.spawn((
+        bar,
+        Sprite::from_color(PROGRESS_BAR_COLOR, size),
+        Transform::from_xyz(x, y, -0.4),
+    ));
+}
+
+// Only minigames that changed since last frame; the bar is rebuilt with the
+// header on levelup anyway.
+pub fn update_progress_bars(
+    minigame_query: Query<&Minigame, Changed<Minigame>>,
+    mut bar_query: Query<(&ProgressBar, &mut Sprite, &mut Transform)>,
+) {
+    if minigame_query.is_empty() {
+        return;
+    }
+    for (bar, mut sprite, mut transform) in bar_query.iter_mut() {
+        let Ok(minigame) = minigame_query.get(bar.minigame) else {
+            continue;
+        };
+        let (size, x) = bar.fill(minigame.progress());
+        sprite.custom_size = Some(size);
+        transform.translation.x = x;
+    }
+}
+
 pub fn spawn_minigame_buttons(
     parent: &mut ChildSpawnerCommands,
     area: RectangularArea,
@@ -581,11 +1283,16 @@ pub fn spawn_minigame_buttons(
     description: &str,
 ) {
     spawn_minigame_engage_button(parent, area, minigame, level, description);
+    pins::spawn_pin_button(parent, minigame, area.right() - BUTTON_WIDTH * 1.5);
 }
 
+// A BTreeMap so iteration (and so the order unlocked minigames spawn in) is the
+// same on every run, which replays depend on.
 #[derive(Debug, Clone, Default, Resource)]
 pub struct MinigamesResource(
-    HashMap<String, (Option<Entity>, u8, Vec<Prerequisite>)>,
+    BTreeMap<String, (Option<Entity>, u8, Vec<Prerequisite>)>,
+    // where minigames were moved to (placement.rs), by id
+    BTreeMap<String, Vec2>,
 );
 
 impl MinigamesResource {
@@ -616,6 +1323,29 @@ impl MinigamesResource {
         self.0.get(minigame).and_then(|(entity, _, _)| *entity)
     }
 
+    // Records that `minigame` was moved to `position`, where it's spawned
+    // from then on. `placement::move_to_placements` moves it there if it's
+    // somewhere else.
+    pub fn set_moved(&mut self, minigame: &str, position: Vec2) {
+        if self.0.contains_key(minigame) {
+            self.1.insert(minigame.into(), position);
+        }
+    }
+
+    pub fn moved(&self, minigame: &str) -> Option<Vec2> {
+        self.1.get(minigame).copied()
+    }
+
+    // Every moved minigame's position, by id.
+    pub fn moved_positions(&self) -> &BTreeMap<String, Vec2> {
+        &self.1
+    }
+
+    // Every registered minigame id, in order.
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
     pub fn is_unlocked(&self, minigame: &str) -> bool {
         self.entity(minigame).is_some()
     }
@@ -642,8 +1372,7 @@ impl MinigamesResource {
             return false;
         }
         self.prerequisites(minigame).iter().all(|prerequisite| {
-            self.is_unlocked(&prerequisite.minigame)
-                || self.level(&prerequisite.minigame) >= prerequisite.level
+            self.level(&prerequisite.minigame) >= prerequisite.level
         })
     }
 
@@ -671,6 +1400,252 @@ pub struct Prerequisite {
     pub level: u8,
 }
 
+fn setup_board(
+    mut commands: Commands,
+    mut minigames: ResMut<MinigamesResource>,
+    asset_server: Res<AssetServer>,
+    mut random: ResMut<Random>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    item_grid: Res<ItemGrid>,
+    item_query: Query<
+        (&Transform, &CircularArea, Entity),
+        (With<Item>, Without<Stuck>),
+    >,
+    player_query: Query<(&Transform, &CircularArea, Entity), With<Player>>,
+) {
+    let mut spawn = |minigame: Minigame, transform: Transform| -> Entity {
+        minigame.spawn(
+            &mut commands,
+            transform,
+            &mut random,
+            &asset_server,
+            &mut images,
+            &mut generated_image_assets,
+            &item_grid,
+            &item_query,
+            &player_query,
+        )
+    };
+
+    minigames.set_entity(
+        button::ID,
+        spawn(
+            Minigame::Button(button::ButtonMinigame { ..default() }),
+            Transform::from_xyz(0.0, 200.0, 0.0),
+        ),
+    );
+    minigames.set_entity(
+        primordial_ocean::ID,
+        spawn(
+            Minigame::PrimordialOcean(
+                primordial_ocean::PrimordialOceanMinigame::new(0.0),
+            ),
+            Transform::from_xyz(200.0, -200.0, 0.0),
+        ),
+    );
+    minigames.set_entity(
+        rune::ID,
+        spawn(
+            Minigame::Rune(rune::RuneMinigame::new(0)),
+            Transform::from_xyz(-200.0, -200.0, 0.0),
+        ),
+    );
+}
+
+// Every minigame: the unlock graph, the starting board, levelups, and each
+// minigame's own systems.
+pub struct MinigamesPlugin;
+
+impl Plugin for MinigamesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Engaged { game: None })
+            .init_resource::<MinigameStack>()
+            .init_resource::<MinigamesResource>()
+            .add_message::<TriggerFired<LevelingUp>>()
+            .add_message::<TriggerFired<rune::RuneReady>>()
+            .init_resource::<world_clock::WorldClock>()
+            .init_resource::<routing::Routes>()
+            .init_resource::<throughput::Throughput>()
+            .init_resource::<batching::IdleBatches>()
+            .init_resource::<pins::Pinned>()
+            .init_resource::<LevelTargets>()
+            .init_resource::<IngestRetries>()
+            .add_systems(
+                PostUpdate,
+                offer_remainders_again.after(PhysicsSet::Writeback),
+            )
+            .add_systems(
+                Startup,
+                (
+                    // Chained: setup_minigame_unlocks must register the
+                    // minigame ids before setup_board's set_entity calls can
+                    // record their entities (set_entity no-ops on an unknown
+                    // id).
+                    setup_minigame_unlocks,
+                    setup_board,
+                )
+                    .chain()
+                    .in_set(GameSet::Minigames),
+            )
+            .add_systems(Startup, pins::setup_pinned_list)
+            .add_systems(
+                Update,
+                (
+                    constant_velocity_system,
+                    (update_engage_button_appearance, update_progress_bars),
+                    (bring_engaged_to_front, stack_minigames).chain(),
+                    button::update,
+                    rune::pixel_update,
+                    (
+                        rune::hint_button_update,
+                        rune::update_hint_stencil,
+                        rune::grow_pixels,
+                        rune::undo_input
+                            .run_if(console::console_closed)
+                            .run_if(photo_mode::photo_mode_off),
+                    ),
+                    (
+                        tree::update,
+                        aquarium::harvest_update,
+                        rhythm::strike_update,
+                    ),
+                    life::cell_update,
+                    (
+                        (land::brush_input, land::undo_input)
+                            .run_if(console::console_closed)
+                            .run_if(photo_mode::photo_mode_off),
+                        land::cell_update,
+                        land::paint_cells,
+                        land::update_brush_text,
+                        land::update_biome_text,
+                    )
+                        .chain(),
+                    (
+                        ball_breaker::unselected_paddle_update,
+                        ball_breaker::keyboard_paddle_update
+                            .run_if(console::console_closed)
+                            .run_if(photo_mode::photo_mode_off),
+                        ball_breaker::update_score_text,
+                        ball_breaker::apply_ball_speed,
+                        ball_breaker::waiting_ball_update,
+                        ball_breaker::launch_ball_update
+                            .run_if(console::console_closed)
+                            .run_if(photo_mode::photo_mode_off),
+                    ),
+                    (
+                        primordial_ocean::update,
+                        primordial_ocean::update_salinity_text,
+                        chest::deposit_all_update,
+                        chest::animate_deposit_flights,
+                        (chest::tab_click_update, chest::update_tabs).chain(),
+                        solar::panel_click_update,
+                        turbine::spin_update,
+                        quarry::update_depth_meter,
+                        composter::update_heap,
+                        foundry::update_temperature_gauge,
+                        slots::update_reel_text,
+                        rhythm::update_notes,
+                        contracts::update_contract_text,
+                        weather_station::control_click_update,
+                        weather_station::update_forecast,
+                        galaxia::update_stars,
+                    ),
+                )
+                    .chain()
+                    .in_set(GameSet::Minigames),
+            )
+            .add_systems(
+                FixedUpdate,
+                world_clock::advance_world_clock.in_set(GameSet::Clock),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    save::pursue_level_targets.before(WorkSet::Levelup),
+                    (
+                        tick_delayed_triggers::<LevelingUp>,
+                        levelup,
+                        animate_levelup,
+                    )
+                        .chain()
+                        .in_set(WorkSet::Levelup),
+                    ingest_item.in_set(WorkSet::Collisions),
+                    (
+                        tick_delayed_triggers::<rune::RuneReady>,
+                        rune::fixed_update,
+                    )
+                        .chain(),
+                    tree::fixed_update,
+                    (
+                        ball_breaker::hit_block_fixed_update
+                            .in_set(WorkSet::Collisions),
+                        finish_dropping,
+                    ),
+                    foundry::cook_fixed_update,
+                    solar::fixed_update,
+                    primordial_ocean::evaporate_fixed_update,
+                    turbine::catch_items_fixed_update
+                        .in_set(WorkSet::Collisions),
+                    quarry::dig_fixed_update,
+                    aquarium::fixed_update,
+                    composter::fixed_update,
+                    slots::fixed_update,
+                    rhythm::fixed_update,
+                    // grouped: a tuple takes twenty systems at most
+                    (
+                        contracts::fixed_update,
+                        weather_station::fixed_update,
+                        galaxia::fixed_update,
+                    ),
+                    (life::evolve_fixed_update, life::render_cells).chain(),
+                    (
+                        land::evolve_fixed_update
+                            .run_if(catch_up::not_catching_up),
+                        land::render_cells,
+                    )
+                        .chain(),
+                    land::biome_fixed_update,
+                    routing::deliver_routed_items,
+                )
+                    .in_set(GameSet::Minigames),
+            )
+            .add_systems(
+                Update,
+                (
+                    routing::port_click_update,
+                    (routing::update_port_colors, routing::update_route_lines),
+                    (
+                        aura_highlight::highlight_accepting_auras,
+                        aura_highlight::pulse_highlights,
+                    ),
+                    (
+                        pins::update_pin_button_appearance,
+                        pins::pinned_entry_input,
+                        pins::render_pinned_list,
+                    ),
+                    (
+                        routing::update_route_line_widths,
+                        throughput::update_gauges,
+                    ),
+                )
+                    .chain()
+                    .in_set(GameSet::Minigames),
+            )
+            .add_systems(
+                FixedUpdate,
+                batching::put_out_near_batches.in_set(GameSet::Minigames),
+            )
+            // after the minigames put things out, before the audit counts them
+            .add_systems(
+                FixedUpdate,
+                (throughput::meter_output, routing::catch_routed_items)
+                    .chain()
+                    .in_set(GameSet::Items),
+            );
+    }
+}
+
 pub fn setup_minigame_unlocks(mut unlocks: ResMut<MinigamesResource>) {
     unlocks.insert(button::ID, Vec::new());
     unlocks.insert(primordial_ocean::ID, Vec::new());
@@ -738,6 +1713,93 @@ pub fn setup_minigame_unlocks(mut unlocks: ResMut<MinigamesResource>) {
             level: 1,
         }],
     );
+    unlocks.insert(
+        turbine::ID,
+        vec![Prerequisite {
+            minigame: chest::ID.into(),
+            level: 1,
+        }],
+    );
+    unlocks.insert(
+        quarry::ID,
+        vec![Prerequisite {
+            minigame: turbine::ID.into(),
+            level: 1,
+        }],
+    );
+    unlocks.insert(
+        aquarium::ID,
+        vec![Prerequisite {
+            minigame: life::ID.into(),
+            level: 1,
+        }],
+    );
+    unlocks.insert(
+        composter::ID,
+        vec![Prerequisite {
+            minigame: aquarium::ID.into(),
+            level: 1,
+        }],
+    );
+    unlocks.insert(
+        slots::ID,
+        vec![Prerequisite {
+            minigame: button::ID.into(),
+            level: 3,
+        }],
+    );
+    unlocks.insert(
+        rhythm::ID,
+        vec![Prerequisite {
+            minigame: rune::ID.into(),
+            level: 2,
+        }],
+    );
+    unlocks.insert(
+        contracts::ID,
+        vec![
+            Prerequisite {
+                minigame: chest::ID.into(),
+                level: 1,
+            },
+            Prerequisite {
+                minigame: foundry::ID.into(),
+                level: 1,
+            },
+        ],
+    );
+    unlocks.insert(
+        flask::ID,
+        vec![Prerequisite {
+            minigame: foundry::ID.into(),
+            level: 2,
+        }],
+    );
+    unlocks.insert(
+        solar::ID,
+        vec![Prerequisite {
+            minigame: battery::ID.into(),
+            level: 1,
+        }],
+    );
+    unlocks.insert(
+        weather_station::ID,
+        vec![Prerequisite {
+            minigame: solar::ID.into(),
+            level: 2,
+        }],
+    );
+
+    // The end of the graph: everything above, at the goal level. Keep it
+    // last, so it takes in every minigame.
+    let everything = unlocks
+        .ids()
+        .map(|id| Prerequisite {
+            minigame: id.into(),
+            level: galaxia::GOAL_LEVEL,
+        })
+        .collect();
+    unlocks.insert(galaxia::ID, everything);
 }
 
 #[derive(Debug, Copy, Clone, Component)]
@@ -750,6 +1812,63 @@ pub struct Engaged {
     pub game: Option<&'static str>,
 }
 
+// How far apart the stacked minigames are drawn. More than the spread of their
+// children's z, so the whole of one draws over the whole of another.
+pub const STACK_STEP: f32 = 4.0;
+
+// Which minigames draw over which, where they overlap: those engaged so far,
+// by id, the latest on top. The rest lie at the bottom, at z 0. Clicks follow
+// (see picking.rs).
+#[derive(Debug, Default, Resource)]
+pub struct MinigameStack {
+    order: Vec<&'static str>,
+}
+
+impl MinigameStack {
+    pub fn bring_to_front(&mut self, id: &'static str) {
+        if self.order.last() == Some(&id) {
+            return;
+        }
+        self.order.retain(|stacked| *stacked != id);
+        self.order.push(id);
+    }
+
+    // The z of the minigame's root.
+    pub fn z(&self, id: &str) -> f32 {
+        self.order
+            .iter()
+            .position(|stacked| *stacked == id)
+            .map_or(0.0, |place| (place + 1) as f32 * STACK_STEP)
+    }
+}
+
+// Whatever engaged it, the engaged minigame comes to the front.
+pub fn bring_engaged_to_front(
+    engaged: Res<Engaged>,
+    mut stack: ResMut<MinigameStack>,
+) {
+    if !engaged.is_changed() {
+        return;
+    }
+    if let Some(id) = engaged.game {
+        stack.bring_to_front(id);
+    }
+}
+
+// Sets each minigame's z from the stack. Its children are placed relative to
+// it, so they all move with it. Covers minigames respawned on levelup too.
+pub fn stack_minigames(
+    stack: Res<MinigameStack>,
+    mut minigame_query: Query<(&Minigame, &mut Transform)>,
+) {
+    for (minigame, mut transform) in minigame_query.iter_mut() {
+        let z = stack.z(minigame.id());
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+    }
+}
+
 pub fn spawn_minigame_engage_button(
     parent: &mut ChildSpawnerCommands,
     area: RectangularArea,
@@ -761,6 +1880,8 @@ pub fn spawn_minigame_engage_button(
         .spawn((
             MinigameEngageButton { minigame },
             CircularArea { radius: 90.0 },
+            Clickable::new(CLICK_PRIORITY_HEADER),
+            Hoverable,
             HoverText::new(description.into()),
             ShapeBuilder::with(&shapes::Rectangle {
                 extents: Vec2::new(BUTTON_WIDTH, META_HEIGHT),
@@ -769,16 +1890,13 @@ pub fn spawn_minigame_engage_button(
             .fill(Fill::color(Color::srgba(0.2, 0.8, 0.8, 1.0)))
             .stroke(Stroke::new(Color::BLACK, 1.0))
             .build(),
-            Transform::from_xyz(
-                area.right() - BUTTON_WIDTH / 2.0,
-                0.0,
-                0.0,
-            ),
+            Transform::from_xyz(area.right() - BUTTON_WIDTH / 2.0, 0.0, 0.0),
             RectangularArea {
                 width: BUTTON_WIDTH,
                 height: META_HEIGHT,
             },
         ))
+        .observe(engage_button_clicked)
         .with_children(|parent| {
             parent.spawn((
                 Text2d::new(level.to_string()),
@@ -793,43 +1911,27 @@ pub fn spawn_minigame_engage_button(
         });
 }
 
-pub fn engage_button_update(
-    button_query: Query<(
-        &MinigameEngageButton,
-        &GlobalTransform,
-        &RectangularArea,
-    )>,
+// Toggles camera focus on the button's minigame by id, so it survives the
+// minigame's despawn/respawn on levelup.
+pub fn engage_button_clicked(
+    clicked: On<Clicked>,
+    button_query: Query<&MinigameEngageButton>,
     minigame_query: Query<&Minigame>,
-    camera_query: Query<(&Camera, &GlobalTransform)>,
-    window_query: Query<&Window>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
     mut engaged: ResMut<Engaged>,
 ) {
-    let Some(click_position) = get_click_release_position(
-        camera_query,
-        window_query,
-        mouse_button_input,
-    ) else {
+    if !clicked.click.is_release() {
+        return;
+    }
+    let Ok(engage_button) = button_query.get(clicked.entity) else {
         return;
     };
-
-    for (engage_button, global_transform, area) in button_query.iter() {
-        if area.is_within(
-            click_position,
-            global_transform.translation().truncate(),
-        ) {
-            let Ok(minigame) = minigame_query.get(engage_button.minigame)
-            else {
-                continue;
-            };
-            // Toggle camera focus on this minigame by id, so it survives the
-            // minigame's despawn/respawn on levelup.
-            if engaged.game == Some(minigame.id()) {
-                engaged.game = None;
-            } else {
-                engaged.game = Some(minigame.id());
-            }
-        }
+    let Ok(minigame) = minigame_query.get(engage_button.minigame) else {
+        return;
+    };
+    if engaged.game == Some(minigame.id()) {
+        engaged.game = None;
+    } else {
+        engaged.game = Some(minigame.id());
     }
 }
 
@@ -850,8 +1952,13 @@ pub fn update_engage_button_appearance(
         } else {
             1.0
         };
-        if let Some(fill) = shape.fill.as_mut() {
+        // writing the shape re-tessellates it, so only when it changes
+        let Some(mut fill) = shape.fill else {
+            continue;
+        };
+        if fill.color.alpha() != alpha {
             fill.color.set_alpha(alpha);
+            shape.fill = Some(fill);
         }
     }
 }
@@ -886,9 +1993,7 @@ impl MinigameBoundBundle {
 
     fn build(x_offset: f32, y_offset: f32, width: f32, height: f32) -> Self {
         Self {
-            transform: Transform::from_xyz(
-                x_offset, y_offset, 0.0,
-            ),
+            transform: Transform::from_xyz(x_offset, y_offset, 0.0),
             collider: Collider::cuboid(width / 2.0, height / 2.0),
             collision_groups: CollisionGroups::new(
                 BORDER_GROUP,
@@ -900,24 +2005,130 @@ impl MinigameBoundBundle {
     }
 }
 
+// A wall of a minigame's bounds that items it drops (`Dropping`) pass out
+// through, and nothing passes in through. Only items: the minigame's own
+// contents, like ball breaker's balls, bounce off it as off any wall.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct OneWayGate {
+    pub minigame: Entity,
+    // out of the minigame
+    pub outward: Vec2,
+}
+
+// The collider that keeps loose items out of a minigame's area, against
+// tunneling through its thin walls.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct EtherBlocker {
+    pub minigame: Entity,
+}
+
+// An item a minigame put out inside its area, on its way out through its
+// gate. It passes through the minigame's `EtherBlocker` and out through its
+// `OneWayGate`; other walls still stop it. Until it's out, the minigame
+// doesn't ingest it and it doesn't combine.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct Dropping {
+    pub minigame: Entity,
+}
+
+// Rapier's contact hooks for minigame bounds: they drop the contacts between
+// a `Dropping` item and its minigame's blocker, and its gate while the item
+// is still on the inside of it. Rapier takes one set of hooks, so they also
+// drop the contacts between a puddle and the items rolling over it.
+#[derive(SystemParam)]
+pub struct BoundsHooks<'w, 's> {
+    gate_query: Query<'w, 's, &'static OneWayGate>,
+    blocker_query: Query<'w, 's, &'static EtherBlocker>,
+    dropping_query: Query<'w, 's, &'static Dropping>,
+    item_query: Query<'w, 's, Has<puddle::Puddle>, With<Item>>,
+}
+
+impl BevyPhysicsHooks for BoundsHooks<'_, '_> {
+    fn modify_solver_contacts(&self, context: ContactModificationContextView) {
+        if let Ok([puddle1, puddle2]) = self
+            .item_query
+            .get_many([context.collider1(), context.collider2()])
+        {
+            if puddle1 != puddle2 {
+                context.raw.solver_contacts.clear();
+            }
+            return;
+        }
+        let raw = &context.raw;
+        let (bound, item, bound_handle, item_handle) =
+            if self.dropping_query.contains(context.collider2()) {
+                (
+                    context.collider1(),
+                    context.collider2(),
+                    raw.collider1,
+                    raw.collider2,
+                )
+            } else {
+                (
+                    context.collider2(),
+                    context.collider1(),
+                    raw.collider2,
+                    raw.collider1,
+                )
+            };
+        let Ok(dropping) = self.dropping_query.get(item) else {
+            return;
+        };
+        let passes = if let Ok(gate) = self.gate_query.get(bound) {
+            let inward = raw.colliders[item_handle].translation()
+                - raw.colliders[bound_handle].translation();
+            gate.minigame == dropping.minigame
+                && Vec2::new(inward.x, inward.y).dot(gate.outward) < 0.0
+        } else if let Ok(blocker) = self.blocker_query.get(bound) {
+            blocker.minigame == dropping.minigame
+        } else {
+            false
+        };
+        if passes {
+            context.raw.solver_contacts.clear();
+        }
+    }
+}
+
+// A dropped item is an ordinary loose item again once it's out of its
+// minigame's area, or the minigame is gone.
+pub fn finish_dropping(
+    mut commands: Commands,
+    dropping_query: Query<(Entity, &Dropping, &Transform)>,
+    minigame_query: Query<(&Minigame, &Transform)>,
+) {
+    for (entity, dropping, transform) in dropping_query.iter() {
+        let inside = minigame_query.get(dropping.minigame).is_ok_and(
+            |(minigame, minigame_transform)| {
+                minigame
+                    .clutter_area(minigame_transform)
+                    .is_within(transform.translation.truncate())
+            },
+        );
+        if !inside {
+            commands.entity(entity).remove::<Dropping>();
+        }
+    }
+}
+
 pub fn spawn_minigame_bounds(
     parent: &mut ChildSpawnerCommands,
+    minigame: Entity,
     area: RectangularArea,
+    bottom_gate: bool,
 ) {
     parent
-        .spawn((
-            ShapeBuilder::with(&shapes::Rectangle {
-                extents: Vec2::new(area.width, area.height + META_HEIGHT),
-                origin: RectangleOrigin::CustomCenter(Vec2::new(
-                    0.0,
-                    META_HEIGHT / 2.0,
-                )),
-                ..default()
-            })
-            .fill(Fill::color(Color::NONE))
-            .stroke(Stroke::new(Color::BLACK, WALL_THICKNESS))
-            .build(),
-        ))
+        .spawn((ShapeBuilder::with(&shapes::Rectangle {
+            extents: Vec2::new(area.width, area.height + META_HEIGHT),
+            origin: RectangleOrigin::CustomCenter(Vec2::new(
+                0.0,
+                META_HEIGHT / 2.0,
+            )),
+            ..default()
+        })
+        .fill(Fill::color(Color::NONE))
+        .stroke(Stroke::new(Color::BLACK, WALL_THICKNESS))
+        .build(),))
         .with_children(|parent| {
             // top wall
             parent.spawn(MinigameBoundBundle::horizontal(
@@ -934,12 +2145,31 @@ pub fn spawn_minigame_bounds(
                 WALL_THICKNESS,
             ));
             // bottom wall
-            parent.spawn(MinigameBoundBundle::horizontal(
+            let mut bottom = parent.spawn(MinigameBoundBundle::horizontal(
                 0.0,
                 -area.height / 2.0,
                 area.width,
                 WALL_THICKNESS,
             ));
+            if bottom_gate {
+                bottom.insert((
+                    OneWayGate {
+                        minigame,
+                        outward: Vec2::NEG_Y,
+                    },
+                    ActiveHooks::MODIFY_SOLVER_CONTACTS,
+                ));
+                // drawn over the wall's own line
+                parent.spawn((
+                    ShapeBuilder::with(&shapes::Line(
+                        Vec2::new(area.left(), area.bottom()),
+                        Vec2::new(area.right(), area.bottom()),
+                    ))
+                    .stroke(Stroke::new(GATE_COLOR, GATE_THICKNESS))
+                    .build(),
+                    Transform::from_xyz(0.0, 0.0, 0.5),
+                ));
+            }
             // left wall
             parent.spawn(MinigameBoundBundle::vertical(
                 -area.width / 2.0,
@@ -957,44 +2187,87 @@ pub fn spawn_minigame_bounds(
         });
 }
 
+// Contacts to offer `ingest_item` again: an item the minigame took only part
+// of, or missed this time, gets no new contact while it stays in the aura.
+#[derive(Debug, Default, Resource)]
+pub struct IngestRetries(pub Vec<contact::ItemAura>);
+
+impl IngestRetries {
+    fn retry(&mut self, contact: contact::ItemAura) {
+        if !self.0.contains(&contact) {
+            self.0.push(contact);
+        }
+    }
+}
+
+// Offers the retried contacts again, as long as the item still touches the
+// aura. Rapier's intersections are the latest here, after its writeback.
+pub fn offer_remainders_again(
+    mut retries: ResMut<IngestRetries>,
+    read_rapier_context: ReadRapierContext,
+    mut item_aura: MessageWriter<contact::ItemAura>,
+) {
+    if retries.0.is_empty() {
+        return;
+    }
+    let Ok(rapier_context) = read_rapier_context.single() else {
+        return;
+    };
+    for contact in retries.0.drain(..) {
+        if rapier_context.intersection_pair(contact.item, contact.aura)
+            == Some(true)
+        {
+            item_aura.write(contact);
+        }
+    }
+}
+
 pub fn ingest_item(
     mut commands: Commands,
     mut random: ResMut<Random>,
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
-    mut collision_events: MessageReader<CollisionEvent>,
+    mut contacts: MessageReader<contact::ItemAura>,
     mut minigame_query: Query<(
         &mut Minigame,
         &GlobalTransform,
         &RectangularArea,
     )>,
     aura_query: Query<&MinigameAura>,
-    item_query: Query<(&Item, &Transform, &Velocity)>,
+    mut item_query: Query<&mut Item>,
+    dropping_query: Query<&Dropping>,
+    // (puddle, softened)
+    state_query: Query<(Has<puddle::Puddle>, Has<heat::Softened>)>,
     leveling_up_query: Query<&LevelingUp>,
+    mut audit: ResMut<AmountAudit>,
+    time: Res<Time>,
+    mut throughput: ResMut<throughput::Throughput>,
+    mut timings: ResMut<FrameTimings>,
+    mut retries: ResMut<IngestRetries>,
 ) {
+    let started = Instant::now();
     let mut ingested: HashSet<Entity> = HashSet::new();
-    for event in collision_events.read() {
-        let CollisionEvent::Started(e1, e2, _) = event else {
+    for contact in contacts.read() {
+        let item_entity = contact.item;
+        let Ok(item) = item_query.get(item_entity).copied() else {
             continue;
         };
-        let (item_entity, aura_entity, item, item_transform, item_velocity) =
-            if let Ok((item, transform, velocity)) = item_query.get(*e1) {
-                (*e1, *e2, item, transform, velocity)
-            } else if let Ok((item, transform, velocity)) = item_query.get(*e2)
-            {
-                (*e2, *e1, item, transform, velocity)
-            } else {
-                continue;
-            };
 
         if ingested.contains(&item_entity) {
             continue;
         }
 
         // Get the minigame
-        let Ok(aura) = aura_query.get(aura_entity) else {
+        let Ok(aura) = aura_query.get(contact.aura) else {
             continue;
         };
+        // on its way out of this one
+        if dropping_query
+            .get(item_entity)
+            .is_ok_and(|dropping| dropping.minigame == aura.minigame)
+        {
+            continue;
+        }
         let Ok((minigame, minigame_transform, minigame_area)) =
             minigame_query.get_mut(aura.minigame)
         else {
@@ -1006,6 +2279,16 @@ pub fn ingest_item(
         if leveling_up_query.get(aura.minigame).is_ok() {
             continue;
         }
+        // sipped a little at a time instead, in `puddle::drink_puddles`
+        let (puddle, softened) =
+            state_query.get(item_entity).unwrap_or_default();
+        if minigame.drinks() && puddle {
+            continue;
+        }
+        // Leave what it won't take where it is, untouched
+        if !minigame.can_ingest(&item) {
+            continue;
+        }
 
         let ingested_amount = minigame.ingest_item(
             &mut commands,
@@ -1015,33 +2298,41 @@ pub fn ingest_item(
             aura.minigame,
             minigame_transform,
             minigame_area,
-            item,
+            &item,
         );
 
         if ingested_amount == 0.0 {
+            // a miss, like land's random cell; it can try again
+            retries.retry(*contact);
             continue;
         }
         ingested.insert(item_entity);
-        // Always despawn - respawn later if needed
-        commands.entity(item_entity).despawn();
+        if softened {
+            minigame.ingested_softened(&item, ingested_amount);
+        }
+        audit.ingested += ingested_amount;
+        throughput.record_in(minigame.id(), time.elapsed_secs());
 
         let remainder = item.amount - ingested_amount;
-        if remainder == 0.0 {
-            continue; // nothing more to do
+        if remainder > 0.0 {
+            // What's left stays where it is, still moving as it was;
+            // `resize_changed_items` shrinks it, and it's offered again
+            // while it stays in the aura
+            if let Ok(mut item) = item_query.get_mut(item_entity) {
+                item.amount = remainder;
+            }
+            retries.retry(*contact);
+            continue;
         } else if remainder < 0.0 {
-            println!("Error: Ingested more than item amount for minigame={}, item={}", minigame.name(), item.name());
+            error!(
+                minigame = minigame.id(),
+                item = %item.r#type.uid(),
+                amount = item.amount,
+                ingested = ingested_amount,
+                "ingested more than the item held"
+            );
         }
-
-        // Spawn a new item with the remainder
-        commands.spawn(ItemBundle::new(
-            &mut images,
-            &mut generated_image_assets,
-            Item {
-                amount: remainder,
-                ..*item
-            },
-            *item_transform,
-            *item_velocity,
-        ));
+        commands.entity(item_entity).despawn();
     }
+    timings.add(Timed::Ingest, started.elapsed());
 }
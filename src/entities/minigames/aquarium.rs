@@ -0,0 +1,476 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// A fish tank. Fresh water makes room for fish and algae feeds them; fed fish
+// grow from babies into adults, and clicking one nets it. Too much food fouls
+// the water and a fish dies, floating out as a corpse. Algae also grows back
+// slowly on the glass, so a tank with water never quite starves.
+
+pub const ID: &str = "aquarium";
+pub const POSITION: Vec2 = Vec2::new(650.0, -200.0);
+
+pub const NAME: &str = "aquarium.name";
+pub const DESCRIPTION: &str = "aquarium.description";
+const AREA: RectangularArea = RectangularArea {
+    width: 180.0,
+    height: 120.0,
+};
+
+// Water each fish needs to have room.
+const WATER_PER_FISH: f32 = 1.0;
+// Seconds between births while there's room and food.
+const SPAWN_SECONDS: f32 = 8.0;
+// Seconds of feeding from one life stage to the next.
+const GROW_SECONDS: f32 = 10.0;
+// Food each fish eats per second while growing.
+const FOOD_PER_SECOND: f32 = 0.05;
+// Food the tank can hold per fish (or for an empty tank) before it fouls.
+const FOOD_PER_FISH_LIMIT: f32 = 2.0;
+// Algae the glass grows per second per unit of water.
+const ALGAE_PER_WATER_SECOND: f32 = 0.002;
+const FISH_RADIUS: f32 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fish {
+    pub id: u32,
+    pub species: Species,
+    pub stage: LifeStage,
+    // seconds fed in the current stage
+    pub growth: f32,
+}
+
+#[derive(Debug, Clone, Default, Component)]
+pub struct AquariumMinigame {
+    pub level: u8,
+    pub water: f32,
+    pub food: f32,
+    pub fish: Vec<Fish>,
+    pub next_id: u32,
+    pub since_spawn: f32,
+    // adults netted so far
+    pub harvested: f32,
+    // fed in as items, counts as extra adults netted
+    pub xp: f32,
+}
+
+impl AquariumMinigame {
+    pub fn new(harvested: f32) -> Self {
+        Self {
+            level: Self::level_by_harvested(harvested),
+            harvested,
+            ..default()
+        }
+    }
+
+    //
+    // COMMON
+    //
+
+    pub fn name(&self) -> &str {
+        NAME
+    }
+
+    pub fn description(&self) -> &str {
+        DESCRIPTION
+    }
+
+    pub fn area(&self) -> RectangularArea {
+        AREA
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn levelup(&self) -> Self {
+        Self::new(self.level_basis())
+    }
+
+    // The fish move tanks along with their water and food.
+    pub fn carry_over(&self, next: &mut Self) {
+        next.water = self.water;
+        next.food = self.food;
+        next.fish = self.fish.clone();
+        next.next_id = self.next_id;
+        next.since_spawn = self.since_spawn;
+        next.harvested = self.harvested;
+        next.xp = self.xp;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.level_basis(), self.level)
+    }
+
+    pub fn spawn(
+        &self,
+        parent: &mut ChildSpawnerCommands,
+        random: &mut Random,
+    ) {
+        parent.spawn((
+            Sprite {
+                color: Color::srgb(0.6, 0.8, 0.95),
+                custom_size: Some(AREA.into()),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, -1.0),
+        ));
+        let minigame = parent.target_entity();
+        for fish in &self.fish {
+            parent.spawn(SwimmerBundle::new(
+                minigame,
+                fish,
+                random_tank_position(random),
+            ));
+        }
+    }
+
+    pub fn ingest_item(&mut self, item: &Item) -> f32 {
+        match item.r#type {
+            ItemType::Physical(PhysicalItem::Bulk(bulk))
+                if bulk.structure == BulkStructure::Liquid
+                    && bulk.substance == Substance::FreshWater =>
+            {
+                self.water += item.amount;
+                item.amount
+            }
+            ItemType::Physical(PhysicalItem::Discrete(d))
+                if d.species == Species::Algae =>
+            {
+                self.food += item.amount;
+                item.amount
+            }
+            _ => 0.0,
+        }
+    }
+
+    // How much of `item` `ingest_item` would take.
+    pub fn accepted_amount(item: &Item) -> f32 {
+        let wanted = match item.r#type {
+            ItemType::Physical(PhysicalItem::Bulk(bulk)) => {
+                bulk.structure == BulkStructure::Liquid
+                    && bulk.substance == Substance::FreshWater
+            }
+            ItemType::Physical(PhysicalItem::Discrete(d)) => {
+                d.species == Species::Algae
+            }
+            _ => false,
+        };
+        if wanted {
+            item.amount
+        } else {
+            0.0
+        }
+    }
+
+    //
+    // SPECIFIC
+    //
+
+    // Adults netted plus XP: what the level is computed from.
+    pub fn level_basis(&self) -> f32 {
+        self.harvested + self.xp
+    }
+
+    pub fn should_level_up(&self) -> bool {
+        Self::level_by_harvested(self.level_basis()) > self.level
+    }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.should_level_up()
+    }
+
+    pub fn level_by_harvested(harvested: f32) -> u8 {
+        if harvested <= 0.0 {
+            0
+        } else {
+            ((harvested.log2() + 1.0) as u8).min(99)
+        }
+    }
+
+    // Fish the tank can hold: its water, up to a size set by level.
+    pub fn room(&self) -> usize {
+        let by_water = (self.water / WATER_PER_FISH) as usize;
+        by_water.min(2 + self.level as usize)
+    }
+
+    pub fn food_limit(&self) -> f32 {
+        FOOD_PER_FISH_LIMIT * self.fish.len().max(1) as f32
+    }
+
+    pub fn is_overfed(&self) -> bool {
+        !self.fish.is_empty() && self.food > self.food_limit()
+    }
+
+    // Frogs turn up once the tank is big enough for them.
+    pub fn random_species(level: u8, random: &mut Random) -> Species {
+        if level >= 3 && random.next().is_multiple_of(3) {
+            Species::Amphibian
+        } else {
+            Species::Fish
+        }
+    }
+
+    // A baby fish, if there's room and food and it's been long enough.
+    pub fn try_spawn_fish(
+        &mut self,
+        seconds: f32,
+        random: &mut Random,
+    ) -> Option<Fish> {
+        self.since_spawn += seconds;
+        if self.since_spawn < SPAWN_SECONDS
+            || self.fish.len() >= self.room()
+            || self.food <= 0.0
+        {
+            return None;
+        }
+        self.since_spawn = 0.0;
+        let fish = Fish {
+            id: self.next_id,
+            species: Self::random_species(self.level, random),
+            stage: LifeStage::Baby,
+            growth: 0.0,
+        };
+        self.next_id += 1;
+        self.fish.push(fish);
+        Some(fish)
+    }
+
+    // Feed and grow every fish while the food lasts, and the glass grows
+    // a little algae back.
+    pub fn grow(&mut self, seconds: f32) {
+        self.food += self.water * ALGAE_PER_WATER_SECOND * seconds;
+        for fish in self.fish.iter_mut() {
+            let meal = FOOD_PER_SECOND * seconds;
+            if self.food < meal || fish.stage == LifeStage::Adult {
+                continue;
+            }
+            self.food -= meal;
+            fish.growth += seconds;
+            if fish.growth >= GROW_SECONDS {
+                fish.growth = 0.0;
+                fish.stage = match fish.stage {
+                    LifeStage::Baby => LifeStage::Youth,
+                    _ => LifeStage::Adult,
+                };
+            }
+        }
+    }
+
+    // Overfeeding kills the oldest fish; the rot uses up the excess food.
+    pub fn kill_overfed(&mut self) -> Option<Fish> {
+        if !self.is_overfed() {
+            return None;
+        }
+        let dead = self.fish.remove(0);
+        self.food = self.food_limit();
+        Some(dead)
+    }
+
+    pub fn harvest(&mut self, id: u32) -> Option<Fish> {
+        let index = self.fish.iter().position(|fish| fish.id == id)?;
+        let fish = self.fish.remove(index);
+        if fish.stage == LifeStage::Adult {
+            self.harvested += 1.0;
+        }
+        Some(fish)
+    }
+}
+
+fn random_tank_position(random: &mut Random) -> Vec2 {
+    let mut coordinate = |extent: f32| {
+        let span = extent - 2.0 * FISH_RADIUS;
+        random.next_f32() * span - span / 2.0
+    };
+    Vec2::new(coordinate(AREA.width), coordinate(AREA.height))
+}
+
+fn fish_scale(stage: LifeStage) -> f32 {
+    match stage {
+        LifeStage::Baby => 0.5,
+        LifeStage::Youth => 0.75,
+        _ => 1.0,
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct Swimmer {
+    pub minigame: Entity,
+    pub id: u32,
+}
+
+#[derive(Bundle)]
+pub struct SwimmerBundle {
+    pub swimmer: Swimmer,
+    pub area: CircularArea,
+    pub clickable: Clickable,
+    pub hoverable: Hoverable,
+    pub shape: Shape,
+    pub transform: Transform,
+}
+
+impl SwimmerBundle {
+    pub fn new(minigame: Entity, fish: &Fish, position: Vec2) -> Self {
+        let color = match fish.species {
+            Species::Amphibian => Color::srgb(0.3, 0.6, 0.2),
+            _ => Color::srgb(0.95, 0.55, 0.1),
+        };
+        Self {
+            swimmer: Swimmer {
+                minigame,
+                id: fish.id,
+            },
+            area: CircularArea {
+                radius: FISH_RADIUS,
+            },
+            clickable: Clickable::new(CLICK_PRIORITY_CONTENT),
+            hoverable: Hoverable,
+            shape: ShapeBuilder::with(&shapes::Circle {
+                radius: FISH_RADIUS,
+                ..default()
+            })
+            .fill(Fill::color(color))
+            .build(),
+            transform: Transform::from_translation(position.extend(1.0))
+                .with_scale(Vec3::splat(fish_scale(fish.stage))),
+        }
+    }
+}
+
+pub fn fixed_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut random: ResMut<Random>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut minigame_query: Query<
+        (Entity, &mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+    mut swimmer_query: Query<(Entity, &Swimmer, &mut Transform)>,
+) {
+    let seconds = time.delta_secs();
+    for (entity, minigame, global_transform, area) in minigame_query.iter_mut()
+    {
+        let Minigame::Aquarium(aquarium) = minigame.into_inner() else {
+            continue;
+        };
+        aquarium.grow(seconds);
+        if let Some(fish) = aquarium.try_spawn_fish(seconds, &mut random) {
+            let position = random_tank_position(&mut random);
+            commands.entity(entity).with_children(|parent| {
+                parent.spawn(SwimmerBundle::new(entity, &fish, position));
+            });
+        }
+        if let Some(dead) = aquarium.kill_overfed() {
+            for (swimmer_entity, swimmer, _) in swimmer_query.iter() {
+                if swimmer.minigame == entity && swimmer.id == dead.id {
+                    commands.entity(swimmer_entity).despawn();
+                }
+            }
+            commands.spawn(ItemBundle::new_from_minigame(
+                &mut images,
+                &mut generated_image_assets,
+                Item::organism(dead.species, LifeStage::Corpse, 1.0),
+                global_transform,
+                area,
+            ));
+        }
+        for (_, swimmer, mut transform) in swimmer_query.iter_mut() {
+            if swimmer.minigame != entity {
+                continue;
+            }
+            let Some(fish) = aquarium.fish.iter().find(|f| f.id == swimmer.id)
+            else {
+                continue;
+            };
+            transform.scale = Vec3::splat(fish_scale(fish.stage));
+        }
+    }
+}
+
+// Clicking a fish nets it.
+pub fn harvest_update(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut click_events: MessageReader<ClickEvent>,
+    swimmer_query: Query<&Swimmer>,
+    mut minigame_query: Query<
+        (&mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+) {
+    for click in click_events.read() {
+        if !click.is_release() {
+            continue;
+        }
+        let Some(entity) = click.target else {
+            continue;
+        };
+        let Ok(swimmer) = swimmer_query.get(entity) else {
+            continue;
+        };
+        let Ok((minigame, global_transform, area)) =
+            minigame_query.get_mut(swimmer.minigame)
+        else {
+            continue;
+        };
+        let Minigame::Aquarium(aquarium) = minigame.into_inner() else {
+            continue;
+        };
+        let Some(fish) = aquarium.harvest(swimmer.id) else {
+            continue;
+        };
+        commands.entity(entity).despawn();
+        commands.spawn(ItemBundle::new_from_minigame(
+            &mut images,
+            &mut generated_image_assets,
+            Item::organism(fish.species, fish.stage, 1.0),
+            global_transform,
+            area,
+        ));
+        if aquarium.should_level_up() {
+            commands.entity(swimmer.minigame).insert(LevelingUp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stocked() -> AquariumMinigame {
+        let mut aquarium = AquariumMinigame {
+            water: 2.0,
+            food: 1.0,
+            ..default()
+        };
+        let mut random = Random::new(1);
+        aquarium.try_spawn_fish(SPAWN_SECONDS, &mut random).unwrap();
+        aquarium
+    }
+
+    #[test]
+    fn fed_fish_grow_up() {
+        let mut aquarium = stocked();
+        aquarium.grow(GROW_SECONDS);
+        aquarium.grow(GROW_SECONDS);
+        assert_eq!(aquarium.fish[0].stage, LifeStage::Adult);
+        let id = aquarium.fish[0].id;
+        assert!(aquarium.harvest(id).is_some());
+        assert_eq!(aquarium.harvested, 1.0);
+    }
+
+    #[test]
+    fn overfeeding_kills() {
+        let mut aquarium = stocked();
+        assert!(aquarium.kill_overfed().is_none());
+        aquarium.food = 10.0;
+        assert!(aquarium.kill_overfed().is_some());
+        assert!(aquarium.fish.is_empty());
+    }
+}
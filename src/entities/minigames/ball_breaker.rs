@@ -1,32 +1,60 @@
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
+use std::f32::consts::TAU;
 
 use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use bevy_prototype_lyon::prelude::*;
 use bevy_rapier2d::prelude::*;
-use wyrand::WyRand;
 
 use crate::entities::*;
 use crate::libs::*;
 
+// bevy_rapier2d's prelude exports a `Real` too; the clock's is Bevy's.
+use bevy::time::Real;
+
 // Grid of blocks or empty spaces. The bottom has a paddle that can move left
 // and right. The player inserts a ball which bounces off of or breaks the
 // blocks, depending on which is harder. The ball also bounces off of the
 // paddle - if the ball hits the bottom, it is lost.
+// A ball fed in waits on the paddle, riding along with it, while an arrow
+// sweeps from side to side; a click in the field while ball breaker is
+// engaged launches it the way the arrow points (see `WaitingBall`).
 // When all blocks are broken, the player wins. This gives them a copy of the
 // minigame to use or deploy.
+// Each board's blocks are drawn with an eye on recent play (`Performance`):
+// players losing balls get softer boards, players breaking blocks with ease
+// get tougher ones and the odd block of unobtainium.
 
 pub const ID: &str = "ball_breaker";
 pub const POSITION: Vec2 = Vec2::new(0.0, 900.0);
 
-pub const NAME: &str = "ball breaker";
-pub const DESCRIPTION: &str = "Throw balls to break blocks!";
+pub const NAME: &str = "ball_breaker.name";
+pub const DESCRIPTION: &str = "ball_breaker.description";
+
+const AIM_LENGTH: f32 = 40.0;
+const AIM_THICKNESS: f32 = 2.0;
+const AIM_COLOR: Color = Color::srgb(0.9, 0.2, 0.2);
+
+// Block size, ball and paddle speeds, toughness and damage and the rest of the
+// tuning are in assets/balance.ron (see `BallBreakerBalance`).
 
-pub const BLOCK_SIZE: f32 = 20.0;
+pub fn block_size() -> f32 {
+    balance().ball_breaker.block_size
+}
 
 #[derive(Debug, Clone, Default, Component)]
 pub struct BallBreakerMinigame {
     pub level: u8,
     pub balls: HashMap<Substance, u32>,
+    // fed in as items; `xp_for_levelup` of it skips a board
+    pub xp: f32,
+    // on the current board
+    pub blocks_broken: u32,
+    pub score: u32,
+    // blocks broken since a ball last touched the paddle
+    pub combo: u32,
+    pub performance: Performance,
 }
 
 impl BallBreakerMinigame {
@@ -34,6 +62,11 @@ impl BallBreakerMinigame {
         Self {
             level,
             balls: HashMap::new(),
+            xp: 0.0,
+            blocks_broken: 0,
+            score: 0,
+            combo: 0,
+            performance: Performance::default(),
         }
     }
 
@@ -50,9 +83,10 @@ impl BallBreakerMinigame {
     }
 
     pub fn area(&self) -> RectangularArea {
+        let block_size = block_size();
         RectangularArea {
-            width: self.blocks_per_row() as f32 * BLOCK_SIZE,
-            height: (3 + self.blocks_per_column()) as f32 * BLOCK_SIZE,
+            width: self.blocks_per_row() as f32 * block_size,
+            height: (3 + self.blocks_per_column()) as f32 * block_size,
         }
     }
 
@@ -64,18 +98,31 @@ impl BallBreakerMinigame {
         Self::new(self.level + 1)
     }
 
+    // Balls in play come back on the new board (see `spawn`).
+    pub fn carry_over(&self, next: &mut Self) {
+        next.balls = self.balls.clone();
+        next.performance = self.performance;
+        next.xp = xp_left_after_levelup(self.level, self.xp);
+    }
+
+    // Clearing the board or feeding XP, whichever is further along.
+    pub fn progress(&self) -> f32 {
+        let blocks = self.blocks_per_row() * self.blocks_per_column();
+        let cleared = self.blocks_broken as f32 / blocks as f32;
+        let xp = self.xp / xp_for_levelup(self.level);
+        cleared.max(xp).clamp(0.0, 1.0)
+    }
+
     pub fn spawn(
         &self,
         parent: &mut ChildSpawnerCommands,
         random: &mut Random,
         asset_server: &AssetServer,
+        images: &mut Assets<Image>,
+        generated_image_assets: &mut image_gen::GeneratedImageAssets,
     ) {
-        let (area, blocks_per_column, blocks_per_row, level) = (
-            self.area(),
-            self.blocks_per_column(),
-            self.blocks_per_row(),
-            self.level,
-        );
+        let (area, blocks_per_column, blocks_per_row) =
+            (self.area(), self.blocks_per_column(), self.blocks_per_row());
         let _background = parent.spawn((
             Sprite {
                 color: Color::srgb(1.0, 1.0, 1.0),
@@ -89,7 +136,7 @@ impl BallBreakerMinigame {
             for x in 0..blocks_per_row {
                 parent.spawn(BlockBundle::new(
                     asset_server,
-                    BallBreakerMinigame::random_substance(level, random),
+                    self.block_substance(random),
                     blocks_per_column,
                     blocks_per_row,
                     x,
@@ -97,13 +144,26 @@ impl BallBreakerMinigame {
                 ));
             }
         }
+        spawn_score_text(parent, area, self.score);
         parent.spawn(PaddleBundle::new(
             asset_server,
             parent.target_entity(),
             blocks_per_column,
         ));
 
-        // TODO empty out balls as loose items
+        // balls carried over from the previous level
+        for (&substance, &count) in &self.balls {
+            for _ in 0..count {
+                parent.spawn(BallBundle::new(
+                    images,
+                    generated_image_assets,
+                    substance,
+                    parent.target_entity(),
+                    blocks_per_column,
+                    blocks_per_row,
+                ));
+            }
+        }
     }
 
     pub fn ingest_item(
@@ -114,8 +174,8 @@ impl BallBreakerMinigame {
         minigame_entity: Entity,
         item: &Item,
     ) -> f32 {
-        // Need at least 1.0 to form a ball
-        if item.amount < 1.0 {
+        let ball_cost = balance().ball_breaker.ball_cost;
+        if item.amount < ball_cost {
             return 0.0;
         }
 
@@ -126,17 +186,32 @@ impl BallBreakerMinigame {
         self.add_ball(substance);
         // TODO verify this works since its parent is minigame instead of aura
         commands.entity(minigame_entity).with_children(|parent| {
-            parent.spawn(BallBundle::new(
-                images,
-                generated_image_assets,
-                substance,
-                minigame_entity,
-                self.blocks_per_column(),
-                self.blocks_per_row(),
-            ));
+            parent
+                .spawn(
+                    BallBundle::new(
+                        images,
+                        generated_image_assets,
+                        substance,
+                        minigame_entity,
+                        self.blocks_per_column(),
+                        self.blocks_per_row(),
+                    )
+                    .waiting(self.blocks_per_column()),
+                )
+                .with_child(AimBundle::default());
         });
 
-        1.0 // Ball uses 1.0 of the item
+        ball_cost
+    }
+
+    // How much of `item` `ingest_item` would take: one ball's worth.
+    pub fn accepted_amount(item: &Item) -> f32 {
+        let ball_cost = balance().ball_breaker.ball_cost;
+        if item.amount >= ball_cost && Self::item_is_valid(item).is_some() {
+            ball_cost
+        } else {
+            0.0
+        }
     }
 
     //
@@ -163,6 +238,10 @@ impl BallBreakerMinigame {
         let ItemType::Physical(PhysicalItem::Bulk(bulk)) = item.r#type else {
             return None;
         };
+        // ore has to go through the foundry first
+        if bulk.processing == Processing::Raw {
+            return None;
+        }
 
         let valid = matches!(
             bulk.substance,
@@ -189,7 +268,7 @@ impl BallBreakerMinigame {
         let r: u64 = if level == 0 {
             0
         } else {
-            1 + random.next() % (level as u64)
+            1 + random.next_range(level as u64)
         };
 
         match r {
@@ -212,47 +291,78 @@ impl BallBreakerMinigame {
         }
     }
 
-    pub fn material_toughness(substance: Substance) -> u32 {
-        match substance {
-            Substance::Mud => 1,
-            Substance::Dirt => 2,
-            Substance::Sandstone => 3,
-            Substance::Granite => 4,
-            Substance::Marble => 4,
-            Substance::Obsidian => 2,
-            Substance::Copper => 4,
-            Substance::Tin => 4,
-            Substance::Iron => 8,
-            Substance::Silver => 4,
-            Substance::Gold => 3,
-            Substance::Diamond => 6,
-            Substance::Amethyst => 6,
-            Substance::FreshWater => 0,
-            Substance::Moss => 1,
-            _ => 16,
+    // A block for the next board: the level's draw, bent toward softer or
+    // tougher blocks by how the player has been doing.
+    pub fn block_substance(&self, random: &mut Random) -> Substance {
+        let difficulty = balance().ball_breaker.difficulty.clone();
+        let skill = self.performance.skill(difficulty.loss_weight);
+        if self.level > 0
+            && skill >= difficulty.unobtainium_above
+            && random
+                .next()
+                .is_multiple_of(difficulty.unobtainium_odds.max(1))
+        {
+            return Substance::Unobtainium;
+        }
+        let rerolls =
+            (skill.abs() * difficulty.max_rerolls as f32).round() as u32;
+        let mut substance = Self::random_substance(self.level, random);
+        for _ in 0..rerolls {
+            let other = Self::random_substance(self.level, random);
+            let toughness = Self::material_toughness(other);
+            let tougher = toughness > Self::material_toughness(substance);
+            let softer = toughness < Self::material_toughness(substance);
+            if (skill > 0.0 && tougher) || (skill < 0.0 && softer) {
+                substance = other;
+            }
         }
+        substance
+    }
+
+    pub fn material_toughness(substance: Substance) -> u32 {
+        let balance = balance();
+        let balance = &balance.ball_breaker;
+        balance
+            .toughness
+            .get(&substance)
+            .copied()
+            .unwrap_or(balance.unlisted_toughness)
     }
 
     pub fn material_damage(substance: Substance) -> u32 {
-        match substance {
-            Substance::Mud => 2,
-            Substance::Dirt => 3,
-            Substance::Sandstone => 4,
-            Substance::Granite => 4,
-            Substance::Marble => 4,
-            Substance::Obsidian => 6,
-            Substance::Copper => 7,
-            Substance::Tin => 7,
-            Substance::Bronze => 8, // must be forged from copper and tin
-            Substance::Iron => 10,
-            Substance::Silver => 4,
-            Substance::Gold => 3,
-            Substance::Diamond => 11,
-            Substance::Amethyst => 4,
-            Substance::FreshWater => 1,
-            Substance::Moss => 0,
-            _ => 16,
-        }
+        let balance = balance();
+        let balance = &balance.ball_breaker;
+        balance
+            .damage
+            .get(&substance)
+            .copied()
+            .unwrap_or(balance.unlisted_damage)
+    }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.xp >= xp_for_levelup(self.level)
+    }
+
+    // Tougher blocks are worth more; even water is worth something.
+    pub fn block_points(substance: Substance) -> u32 {
+        Self::material_toughness(substance) + 1
+    }
+
+    // Each break in a row without touching the paddle multiplies by one more.
+    pub fn score_break(&mut self, block_substance: Substance) {
+        self.performance.record(true);
+        self.combo += 1;
+        self.score += Self::block_points(block_substance) * self.combo;
+    }
+
+    pub fn touch_paddle(&mut self) {
+        self.combo = 0;
+    }
+
+    // What the board's score pays out once it's cleared.
+    pub fn bonus_powder(&self) -> f32 {
+        (self.score / balance().ball_breaker.points_per_bonus_powder) as f32
     }
 
     // counts ball substance
@@ -260,8 +370,10 @@ impl BallBreakerMinigame {
         *self.balls.entry(substance).or_insert(0) += 1;
     }
 
-    // decrements ball substance
+    // decrements ball substance; a ball only goes when a block breaks it, so
+    // this is also a lost ball
     pub fn remove_ball(&mut self, substance: Substance) {
+        self.performance.record(false);
         if let Entry::Occupied(mut entry) = self.balls.entry(substance) {
             let count = entry.get_mut();
             if *count > 0 {
@@ -271,6 +383,39 @@ impl BallBreakerMinigame {
     }
 }
 
+// Recent play, for judging how tough to make the next board. Every block broken
+// and every ball lost counts one, after shrinking what's already counted by
+// `difficulty.memory` in assets/balance.ron, so old play fades out.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Performance {
+    pub breaks: f32,
+    pub losses: f32,
+}
+
+impl Performance {
+    pub fn record(&mut self, broke_block: bool) {
+        let memory = balance().ball_breaker.difficulty.memory;
+        self.breaks *= memory;
+        self.losses *= memory;
+        if broke_block {
+            self.breaks += 1.0;
+        } else {
+            self.losses += 1.0;
+        }
+    }
+
+    // From -1, only losing balls, to 1, only breaking blocks; 0 without any
+    // play yet. A lost ball weighs as much as `loss_weight` breaks.
+    pub fn skill(&self, loss_weight: f32) -> f32 {
+        let losses = self.losses * loss_weight;
+        let total = self.breaks + losses;
+        if total <= 0.0 {
+            return 0.0;
+        }
+        (self.breaks - losses) / total
+    }
+}
+
 #[derive(Debug, Clone, Bundle)]
 pub struct BlockBundle {
     pub block: Block,
@@ -290,13 +435,14 @@ impl BlockBundle {
         x: u32,
         y: u32,
     ) -> Self {
+        let block_size = block_size();
         let area = RectangularArea {
-            width: BLOCK_SIZE,
-            height: BLOCK_SIZE,
+            width: block_size,
+            height: block_size,
         };
-        let x = BLOCK_SIZE
+        let x = block_size
             * ((x as f32) - (blocks_per_row as f32 / 2.0) + 1.0 / 2.0);
-        let y = BLOCK_SIZE
+        let y = block_size
             * ((y as f32) - ((blocks_per_column + 3) as f32 / 2.0) + 1.0 / 2.0);
         Self {
             block: Block { substance },
@@ -304,7 +450,7 @@ impl BlockBundle {
                 image: asset_server.load(
                     Item::solid(substance, BulkShape::Block, 1.0).asset(),
                 ),
-                custom_size: Some(Vec2::new(BLOCK_SIZE, BLOCK_SIZE)),
+                custom_size: Some(area.into()),
                 ..default()
             },
             transform: Transform::from_xyz(x, y, 0.0),
@@ -350,22 +496,17 @@ impl BallBundle {
         blocks_per_column: u32,
         blocks_per_row: u32,
     ) -> Self {
-        let x = BLOCK_SIZE * ((blocks_per_row / 2) as f32 - 2.0);
-        let y = -BLOCK_SIZE * (((blocks_per_column + 3) / 2) as f32 - 1.0);
+        let balance = balance();
+        let block_size = balance.ball_breaker.block_size;
+        let x = block_size * ((blocks_per_row / 2) as f32 - 2.0);
+        let y = -block_size * (((blocks_per_column + 3) / 2) as f32 - 1.0);
         let area = CircularArea {
-            radius: BLOCK_SIZE / 2.0,
+            radius: block_size / 2.0,
         };
         let item = Item::solid(substance, BulkShape::Ball, 1.0);
-        let texture: Handle<Image> =
-            match generated_image_assets.get(&item.uid()) {
-                Some(image) => image,
-                None => {
-                    let image = item.draw(&mut WyRand::new(SEED));
-                    let handle = images.add(image.clone());
-                    generated_image_assets.insert(item.uid(), &handle);
-                    handle
-                }
-            };
+        let texture =
+            item.r#type
+                .texture(images, generated_image_assets, area.radius);
         Self {
             ball: Ball {
                 substance,
@@ -386,7 +527,9 @@ impl BallBundle {
             rigid_body: RigidBody::Dynamic {},
             velocity: Velocity::linear(Vec2::new(-1.0, 1.0)),
             locked_axes: LockedAxes::ROTATION_LOCKED,
-            constant_speed: ConstantSpeed { speed: 200.0 },
+            constant_speed: ConstantSpeed {
+                speed: balance.ball_breaker.ball_speed,
+            },
             friction: Friction {
                 coefficient: 0.0,
                 combine_rule: CoefficientCombineRule::Min,
@@ -404,18 +547,82 @@ impl BallBundle {
     }
 }
 
+impl BallBundle {
+    // Waiting on the paddle to be launched: moved by hand rather than by
+    // physics, so it neither falls nor breaks anything, and still.
+    pub fn waiting(self, blocks_per_column: u32) -> (Self, WaitingBall) {
+        let block_size = block_size();
+        let y = -block_size * (((blocks_per_column + 3) as f32 / 2.0) - 1.5);
+        let bundle = Self {
+            transform: Transform::from_xyz(0.0, y, 0.0),
+            rigid_body: RigidBody::KinematicPositionBased,
+            velocity: Velocity::zero(),
+            // zero holds it still, where any other speed would need a
+            // direction
+            constant_speed: ConstantSpeed { speed: 0.0 },
+            ..self
+        };
+        (bundle, WaitingBall::default())
+    }
+}
+
 #[derive(Debug, Clone, Component)]
 pub struct Ball {
     pub substance: Substance,
     pub minigame: Entity,
 }
 
+// A ball waiting on its paddle to be launched.
+#[derive(Debug, Clone, Copy, Default, Component)]
+pub struct WaitingBall {
+    // seconds so far, which set where the sweep is
+    pub waited: f32,
+}
+
+impl WaitingBall {
+    // Where it would go now: straight up, swung `spread_degrees` to either
+    // side and back once every `sweep_seconds`.
+    pub fn aim(&self, spread_degrees: f32, sweep_seconds: f32) -> Vec2 {
+        let phase = self.waited / sweep_seconds.max(f32::EPSILON) * TAU;
+        let angle = spread_degrees.to_radians() * phase.sin();
+        Vec2::from_angle(angle).rotate(Vec2::Y)
+    }
+}
+
+// The arrow over a waiting ball, pointing where it would go.
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Aim;
+
+#[derive(Bundle)]
+pub struct AimBundle {
+    pub aim: Aim,
+    pub shape: Shape,
+    pub transform: Transform,
+}
+
+impl Default for AimBundle {
+    fn default() -> Self {
+        Self {
+            aim: Aim,
+            shape: ShapeBuilder::with(&shapes::Line(
+                Vec2::ZERO,
+                Vec2::Y * AIM_LENGTH,
+            ))
+            .stroke(Stroke::new(AIM_COLOR, AIM_THICKNESS))
+            .build(),
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Bundle)]
 pub struct PaddleBundle {
     pub paddle: Paddle,
     pub sprite: Sprite,
     pub transform: Transform,
     pub area: RectangularArea,
+    pub clickable: Clickable,
+    pub hoverable: Hoverable,
     pub collider: Collider,
     pub collision_groups: CollisionGroups,
 }
@@ -426,11 +633,12 @@ impl PaddleBundle {
         minigame: Entity,
         blocks_per_column: u32,
     ) -> Self {
+        let block_size = block_size();
         let x = 0.0;
-        let y = -BLOCK_SIZE * (((blocks_per_column + 3) as f32 / 2.0) - 0.5);
+        let y = -block_size * (((blocks_per_column + 3) as f32 / 2.0) - 0.5);
         let area = RectangularArea {
-            width: BLOCK_SIZE * 3.0,
-            height: BLOCK_SIZE,
+            width: block_size * 3.0,
+            height: block_size,
         };
         Self {
             paddle: Paddle { minigame },
@@ -441,6 +649,8 @@ impl PaddleBundle {
             },
             transform: Transform::from_xyz(x, y, 0.0),
             area,
+            clickable: Clickable::new(CLICK_PRIORITY_CONTENT),
+            hoverable: Hoverable,
             collider: Collider::from(area),
             collision_groups: CollisionGroups::new(
                 MINIGAME_CONTENTS_GROUP,
@@ -455,32 +665,72 @@ pub struct Paddle {
     pub minigame: Entity,
 }
 
+// Score readout, right-aligned in the header next to the meta buttons.
+#[derive(Debug, Clone, Component)]
+pub struct ScoreText {
+    pub minigame: Entity,
+}
+
+fn spawn_score_text(
+    parent: &mut ChildSpawnerCommands,
+    area: RectangularArea,
+    score: u32,
+) {
+    parent.spawn((
+        ScoreText {
+            minigame: parent.target_entity(),
+        },
+        Text2d::new(score.to_string()),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::BLACK),
+        Anchor::CENTER_RIGHT,
+        Transform::from_xyz(
+            area.width / 2.0 - BUTTON_WIDTH - 4.0,
+            area.top() + META_HEIGHT / 2.0,
+            1.0,
+        ),
+    ));
+}
+
+pub fn update_score_text(
+    minigame_query: Query<&Minigame, Changed<Minigame>>,
+    mut text_query: Query<(&ScoreText, &mut Text2d)>,
+) {
+    for (score_text, mut text) in text_query.iter_mut() {
+        let Ok(Minigame::BallBreaker(minigame)) =
+            minigame_query.get(score_text.minigame)
+        else {
+            continue;
+        };
+        text.0 = minigame.score.to_string();
+    }
+}
+
 pub fn unselected_paddle_update(
     mut commands: Commands,
-    mut paddle_query: Query<
-        (Entity, &Paddle, &GlobalTransform, &RectangularArea),
+    paddle_query: Query<
+        (&Paddle, &GlobalTransform, &RectangularArea),
         Without<FollowsMouse>,
     >,
     minigame_query: Query<(&RectangularArea, &GlobalTransform), With<Minigame>>,
-    camera_query: Query<(&Camera, &GlobalTransform)>,
-    window_query: Query<&Window>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    mut click_events: MessageReader<ClickEvent>,
 ) {
-    let Some(click_position) = get_click_press_position(
-        camera_query,
-        window_query,
-        mouse_button_input,
-    ) else {
-        return;
-    };
-
-    for (paddle_entity, paddle, paddle_global_transform, paddle_area) in
-        paddle_query.iter_mut()
-    {
-        let paddle_position = paddle_global_transform.translation().truncate();
-        if !paddle_area.is_within(click_position, paddle_position) {
+    for click in click_events.read() {
+        if !click.is_press() {
             continue;
         }
+        let Some(paddle_entity) = click.target else {
+            continue;
+        };
+        let Ok((paddle, paddle_global_transform, paddle_area)) =
+            paddle_query.get(paddle_entity)
+        else {
+            continue;
+        };
+        let paddle_position = paddle_global_transform.translation().truncate();
 
         let (minigame_area, minigame_global_transform) =
             minigame_query.get(paddle.minigame).unwrap();
@@ -495,46 +745,202 @@ pub fn unselected_paddle_update(
                 paddle_position.y,
             ),
             *paddle_area,
-            click_position - paddle_position,
+            click.position - paddle_position,
             true,
         ));
     }
 }
 
+// While the ball breaker is engaged, the paddle can also be steered with the
+// arrow keys (or A/D). This leaves the mouse free for the player ball. A paddle
+// currently being dragged ignores the keyboard so the two don't fight.
+pub fn keyboard_paddle_update(
+    time: Res<Time<Real>>,
+    balance: Res<BalanceConfig>,
+    engaged: Res<Engaged>,
+    minigames: Res<MinigamesResource>,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut paddle_query: Query<
+        (&Paddle, &mut Transform, &RectangularArea),
+        Without<FollowsMouse>,
+    >,
+    minigame_query: Query<&RectangularArea, With<Minigame>>,
+) {
+    if engaged.game != Some(ID) {
+        return;
+    }
+    let Some(engaged_entity) = minigames.entity(ID) else {
+        return;
+    };
+
+    let mut direction = 0.0;
+    if kb_input.any_pressed([KeyCode::ArrowLeft, KeyCode::KeyA]) {
+        direction -= 1.0;
+    }
+    if kb_input.any_pressed([KeyCode::ArrowRight, KeyCode::KeyD]) {
+        direction += 1.0;
+    }
+    if direction == 0.0 {
+        return;
+    }
+
+    for (paddle, mut transform, paddle_area) in paddle_query.iter_mut() {
+        if paddle.minigame != engaged_entity {
+            continue;
+        }
+        let Ok(minigame_area) = minigame_query.get(paddle.minigame) else {
+            continue;
+        };
+        // paddle is a child of the minigame, so its x is relative to center
+        let max_x = (minigame_area.width - paddle_area.width) / 2.0;
+        transform.translation.x = (transform.translation.x
+            + direction
+                * balance.ball_breaker.paddle_speed
+                * time.delta_secs())
+        .clamp(-max_x, max_x);
+    }
+}
+
+// Waiting balls ride on their paddle, and their aim sweeps.
+pub fn waiting_ball_update(
+    time: Res<Time<Real>>,
+    balance: Res<BalanceConfig>,
+    mut ball_query: Query<
+        (&Ball, &mut WaitingBall, &mut Transform, &Children),
+        Without<Paddle>,
+    >,
+    paddle_query: Query<(&Paddle, &Transform), Without<Aim>>,
+    mut aim_query: Query<&mut Transform, (With<Aim>, Without<Ball>)>,
+) {
+    let balance = &balance.ball_breaker;
+    for (ball, mut waiting, mut transform, children) in ball_query.iter_mut() {
+        waiting.waited += time.delta_secs();
+        if let Some((_, paddle_transform)) = paddle_query
+            .iter()
+            .find(|(paddle, _)| paddle.minigame == ball.minigame)
+        {
+            transform.translation.x = paddle_transform.translation.x;
+        }
+        let aim =
+            waiting.aim(balance.aim_spread_degrees, balance.aim_sweep_seconds);
+        for child in children.iter() {
+            if let Ok(mut aim_transform) = aim_query.get_mut(child) {
+                aim_transform.rotation =
+                    Quat::from_rotation_arc_2d(Vec2::Y, aim);
+            }
+        }
+    }
+}
+
+// A click in the field while ball breaker is engaged launches the ball that's
+// waited longest, along its aim.
+pub fn launch_ball_update(
+    mut commands: Commands,
+    engaged: Res<Engaged>,
+    minigames: Res<MinigamesResource>,
+    balance: Res<BalanceConfig>,
+    mut click_events: MessageReader<ClickEvent>,
+    minigame_query: Query<(&GlobalTransform, &RectangularArea), With<Minigame>>,
+    ball_query: Query<(Entity, &Ball, &WaitingBall)>,
+) {
+    let clicks: Vec<Vec2> = click_events
+        .read()
+        .filter(|click| click.kind == ClickKind::Short)
+        .map(|click| click.position)
+        .collect();
+    if engaged.game != Some(ID) {
+        return;
+    }
+    let Some(minigame) = minigames.entity(ID) else {
+        return;
+    };
+    let Ok((minigame_transform, minigame_area)) = minigame_query.get(minigame)
+    else {
+        return;
+    };
+    let center = minigame_transform.translation().truncate();
+    let mut waiting: Vec<_> = ball_query
+        .iter()
+        .filter(|(_, ball, _)| ball.minigame == minigame)
+        .collect();
+    waiting.sort_by(|(_, _, a), (_, _, b)| b.waited.total_cmp(&a.waited));
+    let launches = clicks
+        .iter()
+        .filter(|position| minigame_area.is_within(**position, center))
+        .count();
+    let balance = &balance.ball_breaker;
+    for (entity, _, ball) in waiting.into_iter().take(launches) {
+        let aim =
+            ball.aim(balance.aim_spread_degrees, balance.aim_sweep_seconds);
+        commands
+            .entity(entity)
+            .remove::<WaitingBall>()
+            .despawn_related::<Children>()
+            .insert((
+                RigidBody::Dynamic,
+                Velocity::linear(aim * balance.ball_speed),
+                ConstantSpeed {
+                    speed: balance.ball_speed,
+                },
+            ));
+    }
+}
+
+// Balls already in play pick up a new speed when the balance file changes.
+pub fn apply_ball_speed(
+    balance: Res<BalanceConfig>,
+    mut ball_query: Query<
+        &mut ConstantSpeed,
+        (With<Ball>, Without<WaitingBall>),
+    >,
+) {
+    if !balance.is_changed() {
+        return;
+    }
+    for mut constant_speed in ball_query.iter_mut() {
+        constant_speed.speed = balance.ball_breaker.ball_speed;
+    }
+}
+
 pub fn hit_block_fixed_update(
     mut commands: Commands,
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
-    mut collision_events: MessageReader<CollisionEvent>,
+    mut contacts: MessageReader<contact::BallBlock>,
     mut minigame_query: Query<(
         &mut Minigame,
         &GlobalTransform,
         &RectangularArea,
     )>,
-    ball_query: Query<&Ball>,
-    block_query: Query<&Block>,
+    ball_query: Query<(&Ball, &GlobalTransform)>,
+    block_query: Query<(&Block, &GlobalTransform)>,
+    paddle_query: Query<&Paddle>,
 ) {
     let mut broken: HashSet<Entity> = HashSet::new();
 
-    for event in collision_events.read() {
-        // only care about collision start
-        let CollisionEvent::Started(a, b, _flags) = event else {
+    for contact in contacts.read() {
+        let (ball_entity, block_entity) = (contact.ball, contact.block);
+        let Ok((ball, ball_global_transform)) = ball_query.get(ball_entity)
+        else {
             continue;
         };
-
-        // only care about collisions between balls and blocks
-        let (ball_entity, block_entity, ball) =
-            if let Ok(ball) = ball_query.get(*a) {
-                (*a, *b, ball)
-            } else if let Ok(ball) = ball_query.get(*b) {
-                (*b, *a, ball)
-            } else {
-                continue;
-            };
         let ball_substance = ball.substance;
         let minigame_entity = ball.minigame;
 
-        let Ok(block) = block_query.get(block_entity) else {
+        // a paddle bounce ends the combo
+        if paddle_query.contains(block_entity) {
+            if let Ok((mut minigame, _, _)) =
+                minigame_query.get_mut(minigame_entity)
+            {
+                if let Minigame::BallBreaker(minigame) = minigame.as_mut() {
+                    minigame.touch_paddle();
+                }
+            }
+            continue;
+        }
+
+        let Ok((block, block_global_transform)) = block_query.get(block_entity)
+        else {
             continue;
         };
         let block_substance = block.substance;
@@ -562,10 +968,14 @@ pub fn hit_block_fixed_update(
             // reference that the levelup despawn_recursive later hits (B0003).
             commands.entity(block_entity).despawn();
             broken.insert(block_entity);
-            commands.spawn(ItemBundle::new_from_minigame(
+            minigame.blocks_broken += 1;
+            minigame.score_break(block_substance);
+            commands.spawn(ItemBundle::drop_from_minigame(
                 &mut images,
                 &mut generated_image_assets,
                 Item::powder(block_substance, 1.0),
+                block_global_transform.translation().truncate(),
+                minigame_entity,
                 minigame_global_transform,
                 minigame_area,
             ));
@@ -573,6 +983,27 @@ pub fn hit_block_fixed_update(
             // this was the last block, so reset and level up!
             if block_query.iter().count() == 1 {
                 commands.entity(minigame_entity).insert(LevelingUp);
+                commands.spawn(ItemBundle::drop_from_minigame(
+                    &mut images,
+                    &mut generated_image_assets,
+                    Item::xp(minigame.level as f32 + 1.0),
+                    block_global_transform.translation().truncate(),
+                    minigame_entity,
+                    minigame_global_transform,
+                    minigame_area,
+                ));
+                let bonus = minigame.bonus_powder();
+                if bonus > 0.0 {
+                    commands.spawn(ItemBundle::drop_from_minigame(
+                        &mut images,
+                        &mut generated_image_assets,
+                        Item::powder(block_substance, bonus),
+                        block_global_transform.translation().truncate(),
+                        minigame_entity,
+                        minigame_global_transform,
+                        minigame_area,
+                    ));
+                }
             }
         }
         if BallBreakerMinigame::material_damage(block_substance)
@@ -584,13 +1015,100 @@ pub fn hit_block_fixed_update(
             commands.entity(ball_entity).despawn();
             broken.insert(ball_entity);
             minigame.remove_ball(ball_substance);
-            commands.spawn(ItemBundle::new_from_minigame(
+            commands.spawn(ItemBundle::drop_from_minigame(
                 &mut images,
                 &mut generated_image_assets,
                 Item::powder(ball_substance, 1.0),
+                ball_global_transform.translation().truncate(),
+                minigame_entity,
                 minigame_global_transform,
                 minigame_area,
             ));
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ore_makes_no_balls() {
+        let ore = Item::ore(Substance::Iron, 1.0);
+        assert_eq!(BallBreakerMinigame::item_is_valid(&ore), None);
+        let lump = Item::solid(Substance::Iron, BulkShape::Lump, 1.0);
+        assert_eq!(
+            BallBreakerMinigame::item_is_valid(&lump),
+            Some(Substance::Iron)
+        );
+    }
+
+    #[test]
+    fn aim_sweeps_either_side_of_up() {
+        let aim = |waited| WaitingBall { waited }.aim(60.0, 2.0);
+        assert!(aim(0.0).abs_diff_eq(Vec2::Y, 1e-6));
+        // a quarter of the way through, all the way to one side
+        let side = aim(0.5);
+        assert!(
+            (side.angle_to(Vec2::Y).abs().to_degrees() - 60.0).abs() < 1e-3
+        );
+        assert!(aim(1.5).abs_diff_eq(Vec2::new(-side.x, side.y), 1e-5));
+        for i in 0..40 {
+            let aim = aim(i as f32 * 0.1);
+            assert!((aim.length() - 1.0).abs() < 1e-5);
+            assert!(aim.y >= 0.5 - 1e-5, "{:?} below the spread", aim);
+        }
+    }
+
+    #[test]
+    fn combo_multiplies_until_the_paddle() {
+        let mut minigame = BallBreakerMinigame::new(0);
+        let mud = BallBreakerMinigame::block_points(Substance::Mud);
+        minigame.score_break(Substance::Mud);
+        minigame.score_break(Substance::Mud);
+        assert_eq!(minigame.score, mud + 2 * mud);
+        minigame.touch_paddle();
+        minigame.score_break(Substance::Mud);
+        assert_eq!(minigame.score, 4 * mud);
+    }
+
+    #[test]
+    fn recent_play_shifts_the_blocks() {
+        let toughness = |minigame: &BallBreakerMinigame| {
+            let mut random = Random::new(1);
+            (0..200)
+                .map(|_| {
+                    BallBreakerMinigame::material_toughness(
+                        minigame.block_substance(&mut random),
+                    )
+                })
+                .sum::<u32>()
+        };
+        let even = BallBreakerMinigame::new(12);
+        let mut struggling = BallBreakerMinigame::new(12);
+        let mut dominating = BallBreakerMinigame::new(12);
+        for _ in 0..20 {
+            struggling.performance.record(true);
+            struggling.performance.record(false);
+            dominating.performance.record(true);
+        }
+        let weight = balance().ball_breaker.difficulty.loss_weight;
+        assert!(struggling.performance.skill(weight) < -0.5);
+        assert_eq!(dominating.performance.skill(weight), 1.0);
+        assert!(toughness(&struggling) < toughness(&even));
+        assert!(toughness(&dominating) > toughness(&even));
+
+        let mut random = Random::new(1);
+        let unobtainium = (0..200)
+            .filter(|_| {
+                dominating.block_substance(&mut random)
+                    == Substance::Unobtainium
+            })
+            .count();
+        assert!(unobtainium > 0);
+        let mut random = Random::new(1);
+        assert!((0..200).all(|_| {
+            even.block_substance(&mut random) != Substance::Unobtainium
+        }));
+    }
+}
@@ -8,11 +8,11 @@ use crate::libs::*;
 pub const ID: &str = "battery";
 pub const POSITION: Vec2 = Vec2::new(0.0, -300.0);
 
-pub const NAME_FIRST: &str = "spring";
-pub const NAME_SECOND: &str = "spring and battery";
-pub const NAME_THIRD: &str = "spring, battery, heat stone";
-pub const NAME_FOURTH: &str = "tesseract";
-pub const DESCRIPTION: &str = "Store your energy!";
+pub const NAME_FIRST: &str = "battery.name_first";
+pub const NAME_SECOND: &str = "battery.name_second";
+pub const NAME_THIRD: &str = "battery.name_third";
+pub const NAME_FOURTH: &str = "battery.name_fourth";
+pub const DESCRIPTION: &str = "battery.description";
 
 const STORAGE_SIZE: f32 = 50.0;
 const ITEMS_PER_ROW: u32 = 3;
@@ -57,10 +57,19 @@ impl BatteryMinigame {
     pub fn levelup(&self) -> Self {
         Self {
             level: self.level + 1,
-            ..self.clone()
+            ..default()
         }
     }
 
+    pub fn carry_over(&self, next: &mut Self) {
+        next.items = self.items.clone();
+    }
+
+    // Levels up on overflowing its capacity.
+    pub fn progress(&self) -> f32 {
+        (total_stored(&self.items) / self.capacity()).clamp(0.0, 1.0)
+    }
+
     pub fn spawn(
         &mut self,
         parent: &mut ChildSpawnerCommands,
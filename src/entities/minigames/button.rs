@@ -7,8 +7,8 @@ use crate::libs::*;
 pub const ID: &str = "button";
 pub const POSITION: Vec2 = Vec2::new(0.0, -200.0);
 
-pub const NAME: &str = "Button";
-pub const DESCRIPTION: &str = "Click the button, get clicks!";
+pub const NAME: &str = "button.name";
+pub const DESCRIPTION: &str = "button.description";
 const AREA: RectangularArea = RectangularArea {
     width: 120.0,
     height: 140.0,
@@ -18,6 +18,8 @@ const AREA: RectangularArea = RectangularArea {
 pub struct ButtonMinigame {
     pub count: u64,
     pub level: u8,
+    // fed in as items, counts as extra clicks
+    pub xp: f32,
 }
 
 impl ButtonMinigame {
@@ -25,6 +27,7 @@ impl ButtonMinigame {
         Self {
             count: clicks,
             level: Self::level_by_clicks(clicks),
+            xp: 0.0,
         }
     }
 
@@ -49,7 +52,16 @@ impl ButtonMinigame {
     }
 
     pub fn levelup(&self) -> Self {
-        Self::new(self.count)
+        Self::new(self.level_basis())
+    }
+
+    pub fn carry_over(&self, next: &mut Self) {
+        next.count = self.count;
+        next.xp = self.xp;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.level_basis() as f32, self.level)
     }
 
     pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
@@ -74,13 +86,23 @@ impl ButtonMinigame {
         }
     }
 
+    // Clicks plus XP: what the level is computed from.
+    pub fn level_basis(&self) -> u64 {
+        self.count + self.xp as u64
+    }
+
     pub fn should_level_up(&self) -> bool {
-        if self.count == 0 {
+        if self.level_basis() == 0 {
             false
         } else {
-            Self::level_by_clicks(self.count) > self.level
+            Self::level_by_clicks(self.level_basis()) > self.level
         }
     }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.should_level_up()
+    }
 }
 
 fn spawn_background(parent: &mut ChildSpawnerCommands) {
@@ -94,10 +116,16 @@ fn spawn_background(parent: &mut ChildSpawnerCommands) {
     ));
 }
 
-fn spawn_text(parent: &mut ChildSpawnerCommands, initial_clicks: u64) -> Entity {
+fn spawn_text(
+    parent: &mut ChildSpawnerCommands,
+    initial_clicks: u64,
+) -> Entity {
     parent
         .spawn((
-            Text2d::new(format!("Clicks: {}", initial_clicks)),
+            Text2d::new(tr_fmt(
+                "button.clicks",
+                &[("count", &format_amount(initial_clicks as f32))],
+            )),
             TextFont {
                 font_size: 20.0,
                 ..default()
@@ -116,6 +144,8 @@ fn spawn_button(parent: &mut ChildSpawnerCommands, text: Entity) {
             text,
         },
         CircularArea { radius },
+        Clickable::new(CLICK_PRIORITY_CONTENT),
+        Hoverable,
         ShapeBuilder::with(&shapes::Circle {
             radius,
             ..default()
@@ -137,8 +167,8 @@ pub fn update(
     mut commands: Commands,
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
-    clickable_query: Query<(&ClickMeButton, &GlobalTransform, &CircularArea)>,
-    mouse_state: Res<MouseState>,
+    clickable_query: Query<&ClickMeButton>,
+    mut click_events: MessageReader<ClickEvent>,
     mut minigame_query: Query<(
         &mut Minigame,
         &GlobalTransform,
@@ -147,54 +177,48 @@ pub fn update(
     mut text_query: Query<&mut Text2d>,
     leveling_up_query: Query<&LevelingUp>,
 ) {
-    if !mouse_state.just_released {
-        return;
-    }
-    let click_position = mouse_state.current_position;
-
-    for (button, global_transform, area) in clickable_query.iter() {
-        if area.is_within(
-            click_position,
-            global_transform.translation().truncate(),
-        ) {
-            // Skip if already leveling up
-            if leveling_up_query.get(button.game).is_ok() {
-                continue;
-            }
-
-            let Ok((minigame, minigame_transform, minigame_area)) =
-                minigame_query.get_mut(button.game)
-            else {
-                continue;
-            };
-            let Minigame::Button(minigame) = minigame.into_inner() else {
-                continue;
-            };
-            minigame.count += 1;
-            let mut text = text_query.get_mut(button.text).unwrap();
-            text.0 = format!("Clicks: {}", minigame.count);
-
-            // Check for level up condition
-            if minigame.should_level_up() {
-                commands.entity(button.game).insert(LevelingUp);
-            }
-
-            let click_type = mouse_state.get_click_type();
-            let variant = match click_type {
-                ClickType::Short => 0,
-                ClickType::Long => 1,
-                ClickType::Invalid => {
-                    println!("unexpected: invalid click type");
-                    continue;
-                }
-            };
-            commands.spawn(ItemBundle::new_from_minigame(
-                &mut images,
-                &mut generated_image_assets,
-                Item::new_abstract(AbstractKind::Click, variant, 1.0),
-                minigame_transform,
-                minigame_area,
-            ));
+    for click in click_events.read() {
+        let variant = match click.kind {
+            ClickKind::Short => 0,
+            ClickKind::Long => 1,
+            ClickKind::Press => continue,
+        };
+        let Some(Ok(button)) = click.target.map(|e| clickable_query.get(e))
+        else {
+            continue;
+        };
+
+        // Skip if already leveling up
+        if leveling_up_query.get(button.game).is_ok() {
+            continue;
         }
+
+        let Ok((minigame, minigame_transform, minigame_area)) =
+            minigame_query.get_mut(button.game)
+        else {
+            continue;
+        };
+        let Minigame::Button(minigame) = minigame.into_inner() else {
+            continue;
+        };
+        minigame.count += 1;
+        let mut text = text_query.get_mut(button.text).unwrap();
+        text.0 = tr_fmt(
+            "button.clicks",
+            &[("count", &format_amount(minigame.count as f32))],
+        );
+
+        // Check for level up condition
+        if minigame.should_level_up() {
+            commands.entity(button.game).insert(LevelingUp);
+        }
+
+        commands.spawn(ItemBundle::new_from_minigame(
+            &mut images,
+            &mut generated_image_assets,
+            Item::new_abstract(AbstractKind::Click, variant, 1.0),
+            minigame_transform,
+            minigame_area,
+        ));
     }
 }
@@ -8,16 +8,35 @@ use crate::libs::*;
 pub const ID: &str = "chest";
 pub const POSITION: Vec2 = Vec2::new(300.0, 150.0);
 
-pub const NAME: &str = "chest";
-pub const NAME_WITH_BAGS: &str = "chest with bags";
-pub const NAME_WITH_BARRELS: &str = "barrels and chest with bags";
-pub const NAME_WITH_TANKS: &str = "tanks, barrels, and chest with bags";
-pub const DESCRIPTION: &str = "Store your items!";
+pub const NAME: &str = "chest.name";
+pub const NAME_WITH_BAGS: &str = "chest.name_with_bags";
+pub const NAME_WITH_BARRELS: &str = "chest.name_with_barrels";
+pub const NAME_WITH_TANKS: &str = "chest.name_with_tanks";
+pub const DESCRIPTION: &str = "chest.description";
 
 const STORAGE_SIZE: f32 = 50.0;
 const ITEMS_PER_ROW: u32 = 5;
 const VISIBLE_ROWS: u32 = 3;
 
+const DEPOSIT_BUTTON_SIZE: Vec2 = Vec2::new(70.0, 20.0);
+// the row of domain tabs over the grid
+const TAB_HEIGHT: f32 = 24.0;
+const TAB_FONT_SIZE: f32 = 8.0;
+const TAB_COLOR: Color = Color::srgb(0.8, 0.8, 0.8);
+const ACTIVE_TAB_COLOR: Color = Color::srgb(1.0, 0.85, 0.4);
+// Each tab's filter and the locale key of its name. A domain's filter is the
+// start of its uids; everything's is empty.
+pub const TABS: [(&str, &str); 6] = [
+    ("", "chest.tab.all"),
+    ("physical/", "chest.tab.physical"),
+    ("abstract/", "chest.tab.abstract"),
+    ("energy/", "chest.tab.energy"),
+    ("mana/", "chest.tab.mana"),
+    ("minigame/", "chest.tab.minigame"),
+];
+// How long a deposited item takes to fly from the player into the chest.
+const FLIGHT_SECONDS: f32 = 0.4;
+
 #[derive(Debug, Clone, Default, Component)]
 pub struct ChestMinigame {
     pub level: u8,
@@ -46,7 +65,7 @@ impl ChestMinigame {
     pub fn area(&self) -> RectangularArea {
         RectangularArea {
             width: STORAGE_SIZE * ITEMS_PER_ROW as f32,
-            height: STORAGE_SIZE * VISIBLE_ROWS as f32,
+            height: STORAGE_SIZE * VISIBLE_ROWS as f32 + TAB_HEIGHT,
         }
     }
 
@@ -57,16 +76,48 @@ impl ChestMinigame {
     pub fn levelup(&self) -> Self {
         Self {
             level: self.level + 1,
-            ..self.clone()
+            ..default()
         }
     }
 
+    pub fn carry_over(&self, next: &mut Self) {
+        next.items = self.items.clone();
+    }
+
+    // Levels up on overflowing its capacity.
+    pub fn progress(&self) -> f32 {
+        (total_stored(&self.items) / self.capacity()).clamp(0.0, 1.0)
+    }
+
     pub fn spawn(
         &mut self,
         parent: &mut ChildSpawnerCommands,
         _asset_server: &AssetServer,
     ) {
         // TODO draw background chest, barrels, etc
+        let area = self.area();
+        // under the grid, right of the paging buttons
+        parent
+            .spawn(DepositAllButtonBundle::new(
+                parent.target_entity(),
+                Vec2::new(
+                    area.width / 2.0 - DEPOSIT_BUTTON_SIZE.x / 2.0,
+                    area.bottom() - DEPOSIT_BUTTON_SIZE.y,
+                ),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text2d::new(tr("chest.deposit_all")),
+                    TextFont {
+                        font_size: 10.0,
+                        ..default()
+                    },
+                    TextColor(Color::BLACK),
+                    Transform::from_xyz(0.0, 0.0, 1.0),
+                ));
+            });
+        // the grid fills what the tabs leave
+        let grid_size = Vec2::new(area.width, area.height - TAB_HEIGHT);
         let inventory = InventoryBundle::spawn(
             parent,
             Inventory::new(
@@ -75,10 +126,33 @@ impl ChestMinigame {
                 (ITEMS_PER_ROW, VISIBLE_ROWS),
             ),
             &self.items,
-            Vec2::ZERO,
-            self.area().into(),
+            Vec2::new(0.0, -TAB_HEIGHT / 2.0),
+            grid_size,
         );
         self.inventory = Some(inventory);
+        let tab_width = area.width / TABS.len() as f32;
+        for (i, (filter, _)) in TABS.iter().enumerate() {
+            parent
+                .spawn(TabBundle::new(
+                    inventory,
+                    filter,
+                    Vec2::new(
+                        area.left() + tab_width * (i as f32 + 0.5),
+                        area.top() - TAB_HEIGHT / 2.0,
+                    ),
+                    Vec2::new(tab_width, TAB_HEIGHT),
+                ))
+                .with_child((
+                    Text2d::new(""),
+                    TextFont {
+                        font_size: TAB_FONT_SIZE,
+                        ..default()
+                    },
+                    TextColor(Color::BLACK),
+                    TextLayout::new_with_justify(Justify::Center),
+                    Transform::from_xyz(0.0, 0.0, 1.0),
+                ));
+        }
     }
 
     pub fn ingest_item(
@@ -145,6 +219,297 @@ impl ChestMinigame {
             _ => true, // all forms allowed
         }
     }
+
+    // Whether "deposit all" takes `item`: it's of a type the chest already
+    // holds, or matches the chest's search filter, and the chest accepts it.
+    pub fn wants(&self, item: &Item, filter: &str) -> bool {
+        let matches_filter = !filter.is_empty()
+            && item
+                .r#type
+                .uid()
+                .to_lowercase()
+                .contains(&filter.to_lowercase());
+        (self.items.contains_key(&item.r#type) || matches_filter)
+            && self.can_accept(item)
+    }
+}
+
+// A tab over the grid, showing only one domain's items, or all of them.
+#[derive(Bundle)]
+pub struct TabBundle {
+    pub tab: ChestTab,
+    pub area: RectangularArea,
+    pub clickable: Clickable,
+    pub hoverable: Hoverable,
+    pub sprite: Sprite,
+    pub transform: Transform,
+}
+
+impl TabBundle {
+    pub fn new(
+        inventory: Entity,
+        filter: &'static str,
+        position: Vec2,
+        size: Vec2,
+    ) -> Self {
+        Self {
+            tab: ChestTab { inventory, filter },
+            area: RectangularArea::new(size.x, size.y),
+            clickable: Clickable::new(CLICK_PRIORITY_INVENTORY),
+            hoverable: Hoverable,
+            sprite: Sprite {
+                color: TAB_COLOR,
+                // a gap between neighbours
+                custom_size: Some(size - Vec2::X * 2.0),
+                ..default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct ChestTab {
+    pub inventory: Entity,
+    // what it sets `Inventory::filter` to
+    pub filter: &'static str,
+}
+
+// A tab's name and how many kinds of item the chest holds under it.
+pub fn tab_label(filter: &str, items: &HashMap<ItemType, f32>) -> String {
+    let key = TABS
+        .iter()
+        .find(|(tab, _)| *tab == filter)
+        .map_or("chest.tab.all", |(_, key)| key);
+    tr_fmt(
+        "chest.tab",
+        &[
+            ("name", &tr(key)),
+            ("count", &count_filtered_items(items, filter)),
+        ],
+    )
+}
+
+// Clicking a tab filters the grid to its domain, from the first page.
+pub fn tab_click_update(
+    mut click_events: MessageReader<ClickEvent>,
+    tab_query: Query<&ChestTab>,
+    mut inventory_query: Query<&mut Inventory>,
+) {
+    for click in click_events.read() {
+        if !click.is_release() {
+            continue;
+        }
+        let Some(Ok(tab)) = click.target.map(|e| tab_query.get(e)) else {
+            continue;
+        };
+        let Ok(mut inventory) = inventory_query.get_mut(tab.inventory) else {
+            continue;
+        };
+        inventory.filter = tab.filter.to_string();
+        inventory.page = 0;
+    }
+}
+
+// Keeps each tab's count current, and picks out the one in use.
+pub fn update_tabs(
+    mut tab_query: Query<(&ChestTab, &Children, &mut Sprite)>,
+    inventory_query: Query<&Inventory>,
+    minigame_query: Query<&Minigame>,
+    mut text_query: Query<&mut Text2d>,
+) {
+    for (tab, children, mut sprite) in tab_query.iter_mut() {
+        let Ok(inventory) = inventory_query.get(tab.inventory) else {
+            continue;
+        };
+        let Some(items) = minigame_query
+            .get(inventory.owner)
+            .ok()
+            .and_then(Minigame::items)
+        else {
+            continue;
+        };
+        let color = if inventory.filter == tab.filter {
+            ACTIVE_TAB_COLOR
+        } else {
+            TAB_COLOR
+        };
+        if sprite.color != color {
+            sprite.color = color;
+        }
+        let wanted = tab_label(tab.filter, items);
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            if text.0 != wanted {
+                text.0 = wanted.clone();
+            }
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct DepositAllButtonBundle {
+    pub button: DepositAllButton,
+    pub area: RectangularArea,
+    pub clickable: Clickable,
+    pub hoverable: Hoverable,
+    pub sprite: Sprite,
+    pub transform: Transform,
+}
+
+impl DepositAllButtonBundle {
+    pub fn new(minigame: Entity, position: Vec2) -> Self {
+        Self {
+            button: DepositAllButton { minigame },
+            area: RectangularArea::new(
+                DEPOSIT_BUTTON_SIZE.x,
+                DEPOSIT_BUTTON_SIZE.y,
+            ),
+            clickable: Clickable::new(CLICK_PRIORITY_INVENTORY),
+            hoverable: Hoverable,
+            sprite: Sprite {
+                color: Color::srgb(0.8, 0.8, 0.8),
+                custom_size: Some(DEPOSIT_BUTTON_SIZE),
+                ..default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct DepositAllButton {
+    pub minigame: Entity,
+}
+
+// A deposited item's picture on its way into the chest. The item itself is
+// already stored; this is only for show.
+#[derive(Debug, Component)]
+pub struct DepositFlight {
+    pub from: Vec3,
+    pub to: Vec3,
+    pub elapsed: f32,
+}
+
+// The deposit-all button: every item stuck to the player that the chest
+// wants goes in, and flies there.
+pub fn deposit_all_update(
+    mut commands: Commands,
+    mut audit: ResMut<AmountAudit>,
+    mut click_events: MessageReader<ClickEvent>,
+    button_query: Query<&DepositAllButton>,
+    mut minigame_query: Query<
+        (&mut Minigame, &GlobalTransform),
+        Without<LevelingUp>,
+    >,
+    inventory_query: Query<&Inventory>,
+    stuck_query: Query<(Entity, &Item, &GlobalTransform, &Sprite), With<Stuck>>,
+) {
+    for click in click_events.read() {
+        if !click.is_release() {
+            continue;
+        }
+        let Some(Ok(button)) = click.target.map(|e| button_query.get(e)) else {
+            continue;
+        };
+        let Ok((mut minigame, chest_transform)) =
+            minigame_query.get_mut(button.minigame)
+        else {
+            continue;
+        };
+        let Minigame::Chest(chest) = &mut *minigame else {
+            continue;
+        };
+        let filter = chest
+            .inventory
+            .and_then(|inventory| inventory_query.get(inventory).ok())
+            .map(|inventory| inventory.filter.clone())
+            .unwrap_or_default();
+        for (item_entity, item, item_transform, sprite) in stuck_query.iter() {
+            if !chest.wants(item, &filter) {
+                continue;
+            }
+            let ingested =
+                chest.ingest_item(&mut commands, button.minigame, item);
+            if ingested == 0.0 {
+                continue;
+            }
+            audit.ingested += ingested;
+            commands.entity(item_entity).despawn();
+            commands.spawn((
+                DepositFlight {
+                    from: item_transform.translation(),
+                    to: chest_transform.translation(),
+                    elapsed: 0.0,
+                },
+                sprite.clone(),
+                Transform::from_translation(item_transform.translation()),
+            ));
+        }
+    }
+}
+
+// Moves deposited items' pictures into their chest, shrinking as they go.
+pub fn animate_deposit_flights(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flight_query: Query<(Entity, &mut DepositFlight, &mut Transform)>,
+) {
+    for (entity, mut flight, mut transform) in flight_query.iter_mut() {
+        flight.elapsed += time.delta_secs();
+        let t = flight.elapsed / FLIGHT_SECONDS;
+        if t >= 1.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        // eases in, like being pulled
+        transform.translation = flight.from.lerp(flight.to, t * t);
+        transform.scale = Vec3::splat(1.0 - t / 2.0);
+    }
+}
+
+// Puts `item` into the chest nearest `position` that accepts it. Returns how
+// much went in: all of it, or nothing when no chest takes it.
+pub fn deposit_in_nearest(
+    commands: &mut Commands,
+    random: &mut Random,
+    images: &mut Assets<Image>,
+    generated_image_assets: &mut image_gen::GeneratedImageAssets,
+    minigame_query: &mut Query<
+        (Entity, &mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+    position: Vec2,
+    item: &Item,
+) -> f32 {
+    let chest = minigame_query
+        .iter_mut()
+        .filter(|(_, minigame, _, _)| {
+            matches!(
+                &**minigame,
+                Minigame::Chest(chest) if chest.can_accept(item)
+            )
+        })
+        .min_by(|(_, _, a, _), (_, _, b, _)| {
+            let distance = |transform: &GlobalTransform| {
+                transform.translation().truncate().distance(position)
+            };
+            distance(a).total_cmp(&distance(b))
+        });
+    let Some((chest_entity, mut chest, chest_transform, chest_area)) = chest
+    else {
+        return 0.0;
+    };
+    chest.ingest_item(
+        commands,
+        random,
+        images,
+        generated_image_assets,
+        chest_entity,
+        chest_transform,
+        chest_area,
+        item,
+    )
 }
 
 #[cfg(test)]
@@ -160,12 +525,43 @@ mod tests {
         let stored = Item::solid(Substance::Iron, BulkShape::Block, 4.0);
         add_item(&mut chest.items, stored.r#type, stored.amount);
 
-        let leveled = chest.levelup();
+        let Minigame::Chest(leveled) = Minigame::Chest(chest).levelup() else {
+            unreachable!();
+        };
 
         assert_eq!(leveled.level, 1);
         assert_eq!(total_stored(&leveled.items), 4.0);
     }
 
+    #[test]
+    fn deposit_all_wants_held_types_and_filter_matches() {
+        let mut chest = ChestMinigame::default();
+        let iron = Item::solid(Substance::Iron, BulkShape::Block, 1.0);
+        let granite = Item::solid(Substance::Granite, BulkShape::Lump, 1.0);
+        let water = Item::liquid(Substance::FreshWater, 1.0);
+        assert!(!chest.wants(&iron, ""));
+        add_item(&mut chest.items, iron.r#type, 1.0);
+        assert!(chest.wants(&iron, ""));
+        assert!(!chest.wants(&granite, ""));
+        assert!(chest.wants(&granite, "GRAN"));
+        // matching isn't enough if the chest can't hold it yet
+        assert!(!chest.wants(&water, "water"));
+    }
+
+    #[test]
+    fn tabs_count_the_kinds_in_their_domain() {
+        let mut items = HashMap::new();
+        add_item(&mut items, Item::fruit(Species::Apple, 1.0).r#type, 1.0);
+        let iron = Item::solid(Substance::Iron, BulkShape::Block, 1.0);
+        add_item(&mut items, iron.r#type, 5.0);
+        let clicks = Item::new_abstract(AbstractKind::Click, 0, 1.0);
+        add_item(&mut items, clicks.r#type, 3.0);
+        assert_eq!(tab_label("", &items), "All\n3");
+        assert_eq!(tab_label("physical/", &items), "Physical\n2");
+        assert_eq!(tab_label("abstract/", &items), "Abstract\n1");
+        assert_eq!(tab_label("mana/", &items), "Mana\n0");
+    }
+
     // Tree fruit (Apple) must be storable even in a level-0 chest, which
     // otherwise only accepts solid lumps/blocks/balls.
     #[test]
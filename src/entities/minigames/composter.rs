@@ -0,0 +1,284 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// A compost heap. Corpses and fruit thrown in slowly rot down into soil that
+// spills out as loose items: dirt most of the time, mud when it's raining, and
+// now and then a clump of moss. Higher levels rot faster and grow more moss.
+
+pub const ID: &str = "composter";
+pub const POSITION: Vec2 = Vec2::new(350.0, -450.0);
+
+pub const NAME: &str = "composter.name";
+pub const DESCRIPTION: &str = "composter.description";
+const AREA: RectangularArea = RectangularArea {
+    width: 120.0,
+    height: 120.0,
+};
+
+// Matter rotted per second at level 0.
+const ROT_PER_SECOND: f32 = 0.1;
+// Chance of moss per unit of soil at level 0, as 1 in this many.
+const MOSS_ODDS: u64 = 10;
+const HEAP_WIDTH: f32 = 90.0;
+// The heap is drawn full at this much matter.
+const HEAP_FULL: f32 = 10.0;
+
+#[derive(Debug, Clone, Default, Component)]
+pub struct ComposterMinigame {
+    pub level: u8,
+    // corpses and fruit waiting to rot
+    pub matter: f32,
+    // toward the next unit of soil
+    pub rot: f32,
+    // soil given off so far
+    pub composted: f32,
+    // fed in as items, counts as extra soil composted
+    pub xp: f32,
+}
+
+impl ComposterMinigame {
+    pub fn new(composted: f32) -> Self {
+        Self {
+            level: Self::level_by_composted(composted),
+            composted,
+            ..default()
+        }
+    }
+
+    //
+    // COMMON
+    //
+
+    pub fn name(&self) -> &str {
+        NAME
+    }
+
+    pub fn description(&self) -> &str {
+        DESCRIPTION
+    }
+
+    pub fn area(&self) -> RectangularArea {
+        AREA
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn levelup(&self) -> Self {
+        Self::new(self.level_basis())
+    }
+
+    pub fn carry_over(&self, next: &mut Self) {
+        next.matter = self.matter;
+        next.rot = self.rot;
+        next.composted = self.composted;
+        next.xp = self.xp;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.level_basis(), self.level)
+    }
+
+    pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
+        let minigame = parent.target_entity();
+        parent.spawn((
+            Sprite {
+                color: Color::srgb(0.45, 0.35, 0.2),
+                custom_size: Some(AREA.into()),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, -1.0),
+        ));
+        let (size, y) = heap_fill(self.matter);
+        parent.spawn((
+            Heap { minigame },
+            Sprite::from_color(Color::srgb(0.25, 0.18, 0.1), size),
+            Transform::from_xyz(0.0, y, 0.0),
+        ));
+    }
+
+    pub fn ingest_item(&mut self, item: &Item) -> f32 {
+        if !Self::is_compostable(item) {
+            return 0.0;
+        }
+        self.matter += item.amount;
+        item.amount
+    }
+
+    //
+    // SPECIFIC
+    //
+
+    // Anything dead: corpses of every kind, and fruit.
+    pub fn is_compostable(item: &Item) -> bool {
+        let ItemType::Physical(PhysicalItem::Discrete(d)) = item.r#type else {
+            return false;
+        };
+        d.state == item::State::Stage(LifeStage::Corpse)
+            || d.species.class() == DiscreteClass::Fruit
+    }
+
+    // Soil composted plus XP: what the level is computed from.
+    pub fn level_basis(&self) -> f32 {
+        self.composted + self.xp
+    }
+
+    pub fn should_level_up(&self) -> bool {
+        Self::level_by_composted(self.level_basis()) > self.level
+    }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.should_level_up()
+    }
+
+    pub fn level_by_composted(composted: f32) -> u8 {
+        if composted <= 0.0 {
+            0
+        } else {
+            ((composted.log2() + 1.0) as u8).min(99)
+        }
+    }
+
+    pub fn rot_rate(&self) -> f32 {
+        ROT_PER_SECOND * (1.0 + self.level as f32 * 0.25)
+    }
+
+    // Rot for `seconds`. Returns how many whole units of soil are ready.
+    pub fn decay(&mut self, seconds: f32) -> u32 {
+        let rotted = self.matter.min(self.rot_rate() * seconds);
+        if rotted <= 0.0 {
+            return 0;
+        }
+        self.matter -= rotted;
+        self.rot += rotted;
+        let soil = self.rot as u32;
+        self.rot -= soil as f32;
+        self.composted += soil as f32;
+        soil
+    }
+
+    // What one unit of soil comes out as.
+    pub fn random_soil(&self, raining: bool, random: &mut Random) -> Item {
+        let moss_odds = MOSS_ODDS - (self.level as u64).min(MOSS_ODDS - 2);
+        if random.next().is_multiple_of(moss_odds) {
+            Item::solid(Substance::Moss, BulkShape::Lump, 1.0)
+        } else if raining {
+            Item::liquid(Substance::Mud, 1.0)
+        } else {
+            Item::powder(Substance::Dirt, 1.0)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct Heap {
+    pub minigame: Entity,
+}
+
+// Size and y of the heap: it rises from the bottom of the bin.
+fn heap_fill(matter: f32) -> (Vec2, f32) {
+    let bin = AREA.height - 10.0;
+    let height = bin * (matter / HEAP_FULL).min(1.0);
+    (Vec2::new(HEAP_WIDTH, height), -bin / 2.0 + height / 2.0)
+}
+
+pub fn fixed_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    clock: Res<WorldClock>,
+    mut random: ResMut<Random>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut batches: ResMut<batching::IdleBatches>,
+    mut minigame_query: Query<
+        (Entity, &mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+) {
+    for (entity, minigame, global_transform, area) in minigame_query.iter_mut()
+    {
+        // an empty heap is left alone so it isn't marked changed
+        let Minigame::Composter(composter) = minigame.as_ref() else {
+            continue;
+        };
+        if composter.matter <= 0.0 {
+            continue;
+        }
+        let Minigame::Composter(composter) = minigame.into_inner() else {
+            continue;
+        };
+        for _ in 0..composter.decay(time.delta_secs()) {
+            let soil = composter.random_soil(clock.is_raining(), &mut random);
+            let Some(soil) = batches.batch(ID, soil) else {
+                continue;
+            };
+            commands.spawn(ItemBundle::new_from_minigame(
+                &mut images,
+                &mut generated_image_assets,
+                soil,
+                global_transform,
+                area,
+            ));
+        }
+        if composter.should_level_up() {
+            commands.entity(entity).insert(LevelingUp);
+        }
+    }
+}
+
+pub fn update_heap(
+    minigame_query: Query<&Minigame, Changed<Minigame>>,
+    mut heap_query: Query<(&Heap, &mut Sprite, &mut Transform)>,
+) {
+    for (heap, mut sprite, mut transform) in heap_query.iter_mut() {
+        let Ok(Minigame::Composter(composter)) =
+            minigame_query.get(heap.minigame)
+        else {
+            continue;
+        };
+        let (size, y) = heap_fill(composter.matter);
+        sprite.custom_size = Some(size);
+        transform.translation.y = y;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn corpses_and_fruit_rot_into_soil() {
+        let mut composter = ComposterMinigame::default();
+        let corpse = Item::organism(Species::Fish, LifeStage::Corpse, 2.0);
+        assert_eq!(composter.ingest_item(&corpse), 2.0);
+        assert_eq!(
+            composter.ingest_item(&Item::fruit(Species::Apple, 1.0)),
+            1.0
+        );
+        let fish = Item::organism(Species::Fish, LifeStage::Adult, 1.0);
+        assert_eq!(composter.ingest_item(&fish), 0.0);
+
+        assert_eq!(composter.decay(5.0), 0);
+        assert_eq!(composter.decay(100.0), 3);
+        assert_eq!(composter.matter, 0.0);
+        assert_eq!(composter.composted, 3.0);
+    }
+
+    #[test]
+    fn rain_makes_mud() {
+        let composter = ComposterMinigame::default();
+        let mut random = Random::new(1);
+        for _ in 0..20 {
+            let soil = composter.random_soil(true, &mut random);
+            let ItemType::Physical(PhysicalItem::Bulk(bulk)) = soil.r#type
+            else {
+                panic!("soil should be bulk");
+            };
+            assert_ne!(bulk.substance, Substance::Dirt);
+        }
+    }
+}
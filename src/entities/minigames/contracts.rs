@@ -0,0 +1,501 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// A board of delivery contracts, for something to aim at between levelups.
+// Every so often it posts a contract: a few kinds of item, how much of each,
+// a deadline, and what it pays. Items fed to the board count toward the
+// posted contract. Filling it in time pays the reward, XP, a rare material or
+// a minigame item; letting it lapse forfeits whatever was delivered. Either
+// way the next contract goes up after a short wait. It levels by contracts
+// filled.
+
+pub const ID: &str = "contracts";
+pub const POSITION: Vec2 = Vec2::new(-700.0, 550.0);
+
+pub const NAME: &str = "contracts.name";
+pub const DESCRIPTION: &str = "contracts.description";
+const AREA: RectangularArea = RectangularArea {
+    width: 160.0,
+    height: 120.0,
+};
+
+// Seconds between one contract closing and the next going up.
+const POST_SECONDS: f32 = 30.0;
+const DEADLINE_SECONDS: f32 = 600.0;
+// Kinds of item asked for in one contract, at most.
+const MAX_WANTS: usize = 2;
+
+// What contracts can ask for, and how much of it at level 0. Each level asks
+// for half as much again.
+fn requests() -> [(ItemType, f32); 8] {
+    [
+        (Item::liquid(Substance::FreshWater, 0.0).r#type, 5.0),
+        (Item::liquid(Substance::SaltWater, 0.0).r#type, 10.0),
+        (Item::liquid(Substance::Mud, 0.0).r#type, 5.0),
+        (Item::powder(Substance::Dirt, 0.0).r#type, 5.0),
+        (
+            Item::solid(Substance::Copper, BulkShape::Lump, 0.0).r#type,
+            4.0,
+        ),
+        (
+            Item::solid(Substance::Iron, BulkShape::Lump, 0.0).r#type,
+            4.0,
+        ),
+        (Item::fruit(Species::Apple, 0.0).r#type, 3.0),
+        (
+            Item::organism(Species::Fish, LifeStage::Adult, 0.0).r#type,
+            2.0,
+        ),
+    ]
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reward {
+    Xp,
+    Rare,
+    Minigame,
+}
+
+impl Reward {
+    pub fn label(&self) -> String {
+        tr(match self {
+            Reward::Xp => "contracts.reward.xp",
+            Reward::Rare => "contracts.reward.rare",
+            Reward::Minigame => "contracts.reward.minigame",
+        })
+    }
+
+    // What the reward pays for a contract of `size` items at `level`.
+    pub fn items(&self, size: f32, level: u8, random: &mut Random) -> Item {
+        match self {
+            Reward::Xp => Item::xp(size),
+            Reward::Rare => {
+                let substances = [
+                    Substance::Silver,
+                    Substance::Gold,
+                    Substance::Amethyst,
+                    Substance::Diamond,
+                ];
+                let substance = *random.pick(&substances).unwrap();
+                Item::solid(
+                    substance,
+                    BulkShape::Lump,
+                    1.0 + (level / 2) as f32,
+                )
+            }
+            Reward::Minigame => {
+                let kinds = [
+                    MinigameItemKind::Button,
+                    MinigameItemKind::PrimordialOcean,
+                    MinigameItemKind::Draw,
+                    MinigameItemKind::BlockBreaker,
+                    MinigameItemKind::Tree,
+                ];
+                let kind = *random.pick(&kinds).unwrap();
+                Item::new(
+                    ItemType::Minigame(MinigameItem { kind, variant: 0 }),
+                    1.0,
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contract {
+    // each kind asked for, with how much of it is still owed
+    pub wants: Vec<(ItemType, f32)>,
+    // everything asked for, which sizes the reward
+    pub size: f32,
+    pub seconds_left: f32,
+    pub reward: Reward,
+}
+
+impl Contract {
+    pub fn new(level: u8, random: &mut Random) -> Self {
+        let mut requests = requests().to_vec();
+        let count = 1 + random.next_range(MAX_WANTS as u64) as usize;
+        let scale = 1.0 + level as f32 / 2.0;
+        let mut wants = vec![];
+        for _ in 0..count {
+            let index = random.next_range(requests.len() as u64) as usize;
+            let (r#type, base) = requests.swap_remove(index);
+            wants.push((r#type, (base * scale).round()));
+        }
+        // the better rewards come up more often at higher levels
+        let roll = random.next_range(10 + level as u64);
+        let reward = match roll {
+            0..=5 => Reward::Xp,
+            6..=8 => Reward::Rare,
+            _ => Reward::Minigame,
+        };
+        Self {
+            size: wants.iter().map(|(_, amount)| amount).sum(),
+            wants,
+            seconds_left: DEADLINE_SECONDS,
+            reward,
+        }
+    }
+
+    // Takes as much of `item` as the contract still owes. Bulk quality and
+    // fruit freshness don't matter.
+    // Whether some of `item` is still owed.
+    // How much more of `item`'s kind the contract wants.
+    pub fn owed(&self, item: &Item) -> f32 {
+        let uid = item.uid();
+        self.wants
+            .iter()
+            .find(|(r#type, owed)| *owed > 0.0 && r#type.uid() == uid)
+            .map_or(0.0, |(_, owed)| *owed)
+    }
+
+    pub fn deliver(&mut self, item: &Item) -> f32 {
+        let uid = item.uid();
+        let Some((_, owed)) = self
+            .wants
+            .iter_mut()
+            .find(|(r#type, owed)| *owed > 0.0 && r#type.uid() == uid)
+        else {
+            return 0.0;
+        };
+        let taken = item.amount.min(*owed);
+        *owed -= taken;
+        taken
+    }
+
+    pub fn is_filled(&self) -> bool {
+        self.wants.iter().all(|(_, owed)| *owed <= 0.0)
+    }
+
+    pub fn label(&self) -> String {
+        let wants = self
+            .wants
+            .iter()
+            .map(|(r#type, owed)| {
+                tr_fmt(
+                    "contracts.want",
+                    &[
+                        ("amount", &format_amount(owed.ceil())),
+                        ("item", &r#type.name()),
+                    ],
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let seconds = self.seconds_left.ceil() as u32;
+        tr_fmt(
+            "contracts.contract",
+            &[
+                ("wants", &wants),
+                ("time", &format!("{}:{:02}", seconds / 60, seconds % 60)),
+                ("reward", &self.reward.label()),
+            ],
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Component)]
+pub struct ContractsMinigame {
+    pub level: u8,
+    // the posted contract, if any
+    pub contract: Option<Contract>,
+    // seconds until the next contract goes up
+    pub cooldown: f32,
+    // contracts filled so far
+    pub filled: f32,
+    // fed in as items, counts as extra contracts
+    pub xp: f32,
+}
+
+impl ContractsMinigame {
+    pub fn new(filled: f32) -> Self {
+        Self {
+            level: Self::level_by_filled(filled),
+            filled,
+            ..default()
+        }
+    }
+
+    //
+    // COMMON
+    //
+
+    pub fn name(&self) -> &str {
+        NAME
+    }
+
+    pub fn description(&self) -> &str {
+        DESCRIPTION
+    }
+
+    pub fn area(&self) -> RectangularArea {
+        AREA
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn levelup(&self) -> Self {
+        Self::new(self.level_basis())
+    }
+
+    pub fn carry_over(&self, next: &mut Self) {
+        next.contract = self.contract.clone();
+        next.cooldown = self.cooldown;
+        next.filled = self.filled;
+        next.xp = self.xp;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.level_basis(), self.level)
+    }
+
+    pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
+        let minigame = parent.target_entity();
+        parent.spawn((
+            Sprite {
+                color: Color::srgb(0.55, 0.4, 0.25),
+                custom_size: Some(AREA.into()),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, -1.0),
+        ));
+        parent.spawn((
+            Sprite::from_color(
+                Color::srgb(0.95, 0.92, 0.8),
+                Vec2::new(AREA.width - 16.0, AREA.height - 16.0),
+            ),
+            Transform::from_xyz(0.0, 0.0, 0.0),
+        ));
+        parent.spawn((
+            ContractText { minigame },
+            Text2d::new(self.board_label()),
+            TextFont {
+                font_size: 11.0,
+                ..default()
+            },
+            TextColor(Color::BLACK),
+            TextLayout::new_with_justify(Justify::Center),
+            Transform::from_xyz(0.0, 0.0, 0.1),
+        ));
+    }
+
+    pub fn ingest_item(&mut self, item: &Item) -> f32 {
+        match &mut self.contract {
+            Some(contract) => contract.deliver(item),
+            None => 0.0,
+        }
+    }
+
+    // How much of `item` `ingest_item` would take: what's still owed.
+    pub fn accepted_amount(&self, item: &Item) -> f32 {
+        self.contract
+            .as_ref()
+            .map_or(0.0, |contract| item.amount.min(contract.owed(item)))
+    }
+
+    //
+    // SPECIFIC
+    //
+
+    // Contracts filled plus XP: what the level is computed from.
+    pub fn level_basis(&self) -> f32 {
+        self.filled + self.xp
+    }
+
+    pub fn should_level_up(&self) -> bool {
+        Self::level_by_filled(self.level_basis()) > self.level
+    }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.should_level_up()
+    }
+
+    pub fn level_by_filled(filled: f32) -> u8 {
+        if filled <= 0.0 {
+            0
+        } else {
+            ((filled.log2() + 1.0) as u8).min(99)
+        }
+    }
+
+    // Moves the board on by `seconds`: posts a contract once the wait is
+    // over, and closes the posted one when it's filled or runs out of time.
+    // Returns the reward for a contract filled this step.
+    pub fn advance(
+        &mut self,
+        seconds: f32,
+        random: &mut Random,
+    ) -> Option<Item> {
+        let Some(contract) = &mut self.contract else {
+            self.cooldown -= seconds;
+            if self.cooldown <= 0.0 {
+                self.cooldown = 0.0;
+                self.contract = Some(Contract::new(self.level, random));
+            }
+            return None;
+        };
+        if contract.is_filled() {
+            let reward =
+                contract.reward.items(contract.size, self.level, random);
+            self.contract = None;
+            self.cooldown = POST_SECONDS;
+            self.filled += 1.0;
+            return Some(reward);
+        }
+        contract.seconds_left -= seconds;
+        if contract.seconds_left <= 0.0 {
+            self.contract = None;
+            self.cooldown = POST_SECONDS;
+        }
+        None
+    }
+
+    pub fn board_label(&self) -> String {
+        match &self.contract {
+            Some(contract) => contract.label(),
+            None => tr_fmt(
+                "contracts.waiting",
+                &[("seconds", &(self.cooldown.ceil() as u32))],
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct ContractText {
+    pub minigame: Entity,
+}
+
+// The board is only marked changed when what it shows does, so the text
+// isn't rebuilt every tick of the countdown.
+pub fn fixed_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut random: ResMut<Random>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut minigame_query: Query<
+        (Entity, &mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+) {
+    for (entity, mut minigame, global_transform, area) in
+        minigame_query.iter_mut()
+    {
+        let Minigame::Contracts(contracts) = minigame.bypass_change_detection()
+        else {
+            continue;
+        };
+        let before = contracts.board_label();
+        let reward = contracts.advance(time.delta_secs(), &mut random);
+        let should_level_up = contracts.should_level_up();
+        if contracts.board_label() != before {
+            minigame.set_changed();
+        }
+        let Some(reward) = reward else {
+            continue;
+        };
+        commands.spawn(ItemBundle::new_from_minigame(
+            &mut images,
+            &mut generated_image_assets,
+            reward,
+            global_transform,
+            area,
+        ));
+        if should_level_up {
+            commands.entity(entity).insert(LevelingUp);
+        }
+    }
+}
+
+pub fn update_contract_text(
+    minigame_query: Query<&Minigame, Changed<Minigame>>,
+    mut text_query: Query<(&ContractText, &mut Text2d)>,
+) {
+    for (contract_text, mut text) in text_query.iter_mut() {
+        let Ok(Minigame::Contracts(contracts)) =
+            minigame_query.get(contract_text.minigame)
+        else {
+            continue;
+        };
+        text.0 = contracts.board_label();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contract(wants: Vec<(Item, f32)>) -> Contract {
+        Contract {
+            size: wants.iter().map(|(_, amount)| amount).sum(),
+            wants: wants
+                .into_iter()
+                .map(|(item, amount)| (item.r#type, amount))
+                .collect(),
+            seconds_left: DEADLINE_SECONDS,
+            reward: Reward::Xp,
+        }
+    }
+
+    #[test]
+    fn deliveries_take_only_what_is_owed() {
+        let iron = Item::solid(Substance::Iron, BulkShape::Lump, 3.0);
+        let water = Item::liquid(Substance::FreshWater, 1.0);
+        let mut c = contract(vec![(iron, 5.0), (water, 1.0)]);
+        assert_eq!(c.deliver(&iron), 3.0);
+        assert_eq!(c.deliver(&iron), 2.0);
+        assert_eq!(c.deliver(&iron), 0.0);
+        assert!(!c.is_filled());
+        // quality doesn't matter
+        let mut good_water = water;
+        if let ItemType::Physical(PhysicalItem::Bulk(bulk)) =
+            &mut good_water.r#type
+        {
+            bulk.quality = 7;
+        }
+        assert_eq!(c.deliver(&Item::liquid(Substance::Mud, 1.0)), 0.0);
+        assert_eq!(c.deliver(&good_water), 1.0);
+        assert!(c.is_filled());
+    }
+
+    #[test]
+    fn only_what_is_owed_is_accepted() {
+        let iron = Item::solid(Substance::Iron, BulkShape::Lump, 3.0);
+        let mut board = ContractsMinigame::default();
+        assert_eq!(board.accepted_amount(&iron), 0.0);
+        board.contract = Some(contract(vec![(iron, 2.0)]));
+        assert_eq!(board.accepted_amount(&iron), 2.0);
+        assert_eq!(board.ingest_item(&iron), 2.0);
+        assert_eq!(board.accepted_amount(&iron), 0.0);
+    }
+
+    #[test]
+    fn filled_contracts_pay_and_lapsed_ones_do_not() {
+        let mut random = Random::new(1);
+        let mut board = ContractsMinigame::default();
+        assert!(board.advance(0.1, &mut random).is_none());
+        let posted = board.contract.clone().unwrap();
+        assert!(!posted.wants.is_empty() && posted.wants.len() <= MAX_WANTS);
+
+        // left alone, it lapses
+        assert!(board.advance(DEADLINE_SECONDS, &mut random).is_none());
+        assert!(board.contract.is_none());
+        assert_eq!(board.filled, 0.0);
+
+        board.advance(POST_SECONDS, &mut random);
+        let wants = board.contract.clone().unwrap().wants;
+        for (r#type, amount) in wants {
+            assert_eq!(board.ingest_item(&r#type.to_item(amount)), amount);
+        }
+        assert!(board.advance(0.1, &mut random).is_some());
+        assert!(board.contract.is_none());
+        assert_eq!(board.filled, 1.0);
+        assert!(board.should_level_up());
+    }
+}
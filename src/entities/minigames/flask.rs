@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// A flask. Loose gases thin out into the air (see gas.rs); one that reaches
+// the flask's aura is caught and kept here instead, for good. It stores gases
+// and nothing else, and levels up on overflowing its capacity, like the
+// battery.
+
+pub const ID: &str = "flask";
+pub const POSITION: Vec2 = Vec2::new(-300.0, -500.0);
+
+pub const NAME: &str = "flask.name";
+pub const DESCRIPTION: &str = "flask.description";
+
+const STORAGE_SIZE: f32 = 50.0;
+const ITEMS_PER_ROW: u32 = 2;
+const VISIBLE_ROWS: u32 = 3;
+// capacity at level 0, doubling each level
+const BASE_CAPACITY: f32 = 10.0;
+
+#[derive(Debug, Clone, Default, Component)]
+pub struct FlaskMinigame {
+    pub level: u8,
+    pub items: HashMap<ItemType, f32>,
+    pub inventory: Option<Entity>,
+}
+
+impl FlaskMinigame {
+    //
+    // COMMON
+    //
+
+    pub fn name(&self) -> &str {
+        NAME
+    }
+
+    pub fn description(&self) -> &str {
+        DESCRIPTION
+    }
+
+    pub fn area(&self) -> RectangularArea {
+        RectangularArea {
+            width: STORAGE_SIZE * ITEMS_PER_ROW as f32,
+            height: STORAGE_SIZE * VISIBLE_ROWS as f32,
+        }
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn levelup(&self) -> Self {
+        Self {
+            level: self.level + 1,
+            ..default()
+        }
+    }
+
+    pub fn carry_over(&self, next: &mut Self) {
+        next.items = self.items.clone();
+    }
+
+    // Levels up on overflowing its capacity.
+    pub fn progress(&self) -> f32 {
+        (total_stored(&self.items) / self.capacity()).clamp(0.0, 1.0)
+    }
+
+    pub fn spawn(&mut self, parent: &mut ChildSpawnerCommands) {
+        let inventory = InventoryBundle::spawn(
+            parent,
+            Inventory::new(
+                parent.target_entity(),
+                Vec::new(),
+                (ITEMS_PER_ROW, VISIBLE_ROWS),
+            ),
+            &self.items,
+            Vec2::ZERO,
+            self.area().into(),
+        );
+        self.inventory = Some(inventory);
+    }
+
+    pub fn ingest_item(
+        &mut self,
+        commands: &mut Commands,
+        minigame_entity: Entity,
+        item: &Item,
+    ) -> f32 {
+        if !Self::can_accept(item) {
+            return 0.0;
+        }
+        add_item(&mut self.items, item.r#type, item.amount);
+
+        // Poke Inventory so it redraws
+        mark_component_changed::<Inventory>(commands, self.inventory.unwrap());
+
+        if total_stored(&self.items) > self.capacity() {
+            commands.entity(minigame_entity).insert(LevelingUp);
+        }
+
+        item.amount
+    }
+
+    //
+    // SPECIFIC
+    //
+
+    pub fn capacity(&self) -> f32 {
+        BASE_CAPACITY * 2.0f32.powi(self.level as i32)
+    }
+
+    pub fn can_accept(item: &Item) -> bool {
+        gas::is_gas(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_gases() {
+        let steam = Item::gas(Substance::FreshWater, 1.0);
+        let water = Item::liquid(Substance::FreshWater, 1.0);
+        let apple = Item::fruit(Species::Apple, 1.0);
+        assert!(FlaskMinigame::can_accept(&steam));
+        assert!(!FlaskMinigame::can_accept(&water));
+        assert!(!FlaskMinigame::can_accept(&apple));
+    }
+
+    #[test]
+    fn capacity_doubles_each_level() {
+        let flask = FlaskMinigame::default();
+        assert_eq!(flask.capacity(), BASE_CAPACITY);
+        assert_eq!(flask.levelup().levelup().capacity(), BASE_CAPACITY * 4.0);
+    }
+}
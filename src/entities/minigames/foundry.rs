@@ -8,43 +8,74 @@ use crate::libs::*;
 // Changes items under the vague notion of transmutation through heating.
 // This works for physical items like metals but also abstract items.
 // Collects Heat Energy for physical transmutation but creates Heat Energy
-// when fed Clicks.
+// when fed Clicks. Ore smelts down into refined lumps, burning heat to do it.
 // Levels up as more items are transmuted.
+//
+// Heat is kept as a temperature, in degrees over the surroundings: Thermal
+// energy fed in raises it and it cools on its own. Each ore smelts within a
+// range of temperatures (`smelting_range`). Below it the ore waits for the
+// heat; above it the ore boils off as gas and nothing is cooked. A gauge on
+// the right shows the temperature and the range the next ore needs.
 
 pub const ID: &str = "foundry";
 pub const POSITION: Vec2 = Vec2::new(0.0, 500.0);
 
-pub const NAME: &str = "Foundry";
-pub const DESCRIPTION: &str = "Transmute items through heat.";
+pub const NAME: &str = "foundry.name";
+pub const DESCRIPTION: &str = "foundry.description";
 const AREA: RectangularArea = RectangularArea {
     width: 150.0,
     height: 150.0,
 };
 
+// Heat burned per unit of ore smelted.
+pub const HEAT_PER_ORE: f32 = 0.5;
+// Lump per unit of ore: the slag is lost. Boiled off ore gives as much gas.
+pub const SMELT_YIELD: f32 = 0.5;
+// Share of the heat it saves smelting ore that came in softened (see heat.rs).
+pub const SOFTENED_HEAT_SAVED: f32 = 0.5;
+// Degrees one unit of Thermal energy heats the foundry.
+pub const DEGREES_PER_HEAT: f32 = 100.0;
+// Fraction of its temperature the foundry loses per second.
+pub const COOLING_PER_SECOND: f32 = 0.05;
+// Below this it's as cold as its surroundings.
+const COLD: f32 = 0.5;
+// The gauge reads up to this temperature.
+const GAUGE_MAX: f32 = 1200.0;
+const GAUGE_WIDTH: f32 = 12.0;
+const GAUGE_COLD: Color = Color::srgb(0.2, 0.3, 0.9);
+const GAUGE_HOT: Color = Color::srgb(1.0, 0.25, 0.0);
+
 #[derive(Debug, Clone, Default, Component)]
 pub struct FoundryMinigame {
     pub level: u8,
-    pub heat: f32,
+    // degrees over the surroundings
+    pub temperature: f32,
     pub cooking: VecDeque<Item>,
     pub special_cooking: VecDeque<Item>, // clicks
+    // units of ore fed in softened, smelted first for less heat
+    pub softened: f32,
     pub last_cook: f32,
     pub total_cooked: f32,
+    // fed in as items, counts as extra cooking
+    pub xp: f32,
 }
 
 impl FoundryMinigame {
     pub fn new(
         total_cooked: f32,
-        heat: f32,
+        temperature: f32,
         cooking: VecDeque<Item>,
         special_cooking: VecDeque<Item>,
     ) -> Self {
         Self {
             level: Self::level_by_total_cooked(total_cooked),
-            heat,
+            temperature,
             cooking,
             special_cooking,
+            softened: 0.0,
             last_cook: 0.0,
             total_cooked,
+            xp: 0.0,
         }
     }
 
@@ -69,18 +100,48 @@ impl FoundryMinigame {
     }
 
     pub fn levelup(&self) -> Self {
-        Self::new(
-            self.total_cooked,
-            self.heat,
-            self.cooking.clone(),
-            self.special_cooking.clone(),
-        )
+        Self::new(self.level_basis(), 0.0, VecDeque::new(), VecDeque::new())
+    }
+
+    pub fn carry_over(&self, next: &mut Self) {
+        next.total_cooked = self.total_cooked;
+        next.xp = self.xp;
+        next.temperature = self.temperature;
+        next.cooking = self.cooking.clone();
+        next.special_cooking = self.special_cooking.clone();
+        next.softened = self.softened;
+        next.last_cook = self.last_cook;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.level_basis(), self.level)
     }
 
-    pub fn spawn(&self, _parent: &mut ChildSpawnerCommands) {
+    pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
         // TODO background
-        // TODO heat meter
         // TODO transmutation timer
+        let minigame = parent.target_entity();
+        let gauge_x = AREA.width / 2.0 - GAUGE_WIDTH;
+        parent.spawn((
+            Sprite::from_color(
+                Color::srgb(0.15, 0.15, 0.15),
+                Vec2::new(GAUGE_WIDTH, AREA.height - 10.0),
+            ),
+            Transform::from_xyz(gauge_x, 0.0, 0.0),
+        ));
+        let (size, y) = gauge_span(0.0, self.temperature);
+        parent.spawn((
+            TemperatureGauge { minigame },
+            Sprite::from_color(gauge_color(self.temperature), size),
+            Transform::from_xyz(gauge_x, y, 0.1),
+        ));
+        let (size, y) = gauge_span(0.0, 0.0);
+        parent.spawn((
+            SmeltingBand { minigame },
+            Sprite::from_color(Color::srgba(0.3, 1.0, 0.3, 0.4), size),
+            Transform::from_xyz(gauge_x, y, 0.2),
+            Visibility::Hidden,
+        ));
     }
 
     pub fn ingest_item(&mut self, item: &Item) -> f32 {
@@ -88,7 +149,7 @@ impl FoundryMinigame {
             // Keep heat
             ItemType::Energy(energy) => match energy.kind {
                 EnergyKind::Thermal => {
-                    self.heat += item.amount;
+                    self.temperature += item.amount * DEGREES_PER_HEAT;
                     item.amount
                 }
                 _ => 0.0,
@@ -112,10 +173,43 @@ impl FoundryMinigame {
         }
     }
 
+    // How much of `item` `ingest_item` would take.
+    pub fn accepted_amount(item: &Item) -> f32 {
+        let wanted = match item.r#type {
+            ItemType::Energy(energy) => energy.kind == EnergyKind::Thermal,
+            ItemType::Abstract(abstraction) => {
+                abstraction.kind == AbstractKind::Click
+            }
+            ItemType::Physical(PhysicalItem::Bulk(bulk)) => {
+                bulk.processing == Processing::Raw
+            }
+            _ => false,
+        };
+        if wanted {
+            item.amount
+        } else {
+            0.0
+        }
+    }
+
     //
     // SPECIFIC
     //
 
+    // Amount cooked plus XP: what the level is computed from.
+    pub fn level_basis(&self) -> f32 {
+        self.total_cooked + self.xp
+    }
+
+    pub fn should_level_up(&self) -> bool {
+        Self::level_by_total_cooked(self.level_basis()) > self.level
+    }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.should_level_up()
+    }
+
     pub fn level_by_total_cooked(total_cooked: f32) -> u8 {
         if total_cooked <= 0.0 {
             0
@@ -137,11 +231,11 @@ impl FoundryMinigame {
                 }
                 _ => item_type,
             },
-            // Smelting an ore (Raw bulk solid) yields a liquid of the same
-            // substance.
+            // Smelting an ore (Raw bulk solid) yields a refined lump of the
+            // same substance.
             ItemType::Physical(PhysicalItem::Bulk(bulk)) => {
                 if bulk.processing == Processing::Raw {
-                    Item::liquid(bulk.substance, 1.0).r#type
+                    Item::solid(bulk.substance, BulkShape::Lump, 1.0).r#type
                 } else {
                     item_type
                 }
@@ -149,6 +243,147 @@ impl FoundryMinigame {
             _ => item_type,
         }
     }
+
+    pub fn is_ore(item_type: ItemType) -> bool {
+        matches!(
+            item_type,
+            ItemType::Physical(PhysicalItem::Bulk(bulk))
+                if bulk.processing == Processing::Raw
+        )
+    }
+
+    // Notes that `amount` of `item`, just ingested, came in softened.
+    pub fn add_softened(&mut self, item: &Item, amount: f32) {
+        if Self::is_ore(item.r#type) {
+            self.softened += amount;
+        }
+    }
+
+    // Heat it takes to cook `item`; only smelting needs any.
+    // Smelting burns it, cooling the foundry by as many degrees as it's worth.
+    pub fn heat_needed(item: &Item) -> f32 {
+        if Self::is_ore(item.r#type) {
+            item.amount * HEAT_PER_ORE
+        } else {
+            0.0
+        }
+    }
+
+    // The heat held, as Thermal energy it'd take to get back to this
+    // temperature.
+    pub fn heat(&self) -> f32 {
+        self.temperature / DEGREES_PER_HEAT
+    }
+
+    // Loses a share of the temperature, so it falls fast when hot and then
+    // settles to nothing.
+    pub fn cool(&mut self, seconds: f32) {
+        self.temperature -= self.temperature * COOLING_PER_SECOND * seconds;
+        if self.temperature < COLD {
+            self.temperature = 0.0;
+        }
+    }
+
+    // The temperatures an ore of `substance` smelts between: below, it waits
+    // for the heat; above, it boils off. Metals each have their own, the rest
+    // go by class.
+    pub fn smelting_range(substance: Substance) -> (f32, f32) {
+        match substance {
+            Substance::Tin => (100.0, 400.0),
+            Substance::Copper | Substance::Bronze => (200.0, 600.0),
+            Substance::Silver | Substance::Gold => (250.0, 650.0),
+            Substance::Iron => (400.0, 900.0),
+            _ => match substance.class() {
+                SubstanceClass::Gem | SubstanceClass::Exotic => (600.0, 1100.0),
+                SubstanceClass::Organic | SubstanceClass::Water => {
+                    (50.0, 200.0)
+                }
+                _ => (150.0, 500.0),
+            },
+        }
+    }
+
+    // The smelting range of the ore next in line, if any is waiting.
+    pub fn next_smelting_range(&self) -> Option<(f32, f32)> {
+        match self.cooking.front()?.r#type {
+            ItemType::Physical(PhysicalItem::Bulk(bulk)) => {
+                Some(Self::smelting_range(bulk.substance))
+            }
+            _ => None,
+        }
+    }
+
+    // Smelts the ore at the front of the line into lumps if the temperature
+    // is in its range, or boils it off into gas if it's over. Returns what
+    // comes out; nothing while it waits for the heat.
+    pub fn cook_ore(&mut self) -> Option<Item> {
+        let raw = *self.cooking.front()?;
+        let ItemType::Physical(PhysicalItem::Bulk(bulk)) = raw.r#type else {
+            return None;
+        };
+        let (min, max) = Self::smelting_range(bulk.substance);
+        if self.temperature > max {
+            self.cooking.pop_front();
+            self.take_softened(raw.amount);
+            return Some(Item::gas(bulk.substance, raw.amount * SMELT_YIELD));
+        }
+        if self.temperature < min {
+            return None;
+        }
+        self.cooking.pop_front();
+        let saved =
+            self.take_softened(raw.amount) * HEAT_PER_ORE * SOFTENED_HEAT_SAVED;
+        self.temperature = (self.temperature
+            - (Self::heat_needed(&raw) - saved) * DEGREES_PER_HEAT)
+            .max(0.0);
+        self.total_cooked += raw.amount;
+        Some(Self::cooked(&raw))
+    }
+
+    // Uses up to `amount` of the softened ore, returning how much it had.
+    fn take_softened(&mut self, amount: f32) -> f32 {
+        let taken = self.softened.min(amount);
+        self.softened -= taken;
+        taken
+    }
+
+    // What comes out of cooking `item`.
+    pub fn cooked(item: &Item) -> Item {
+        let amount = if Self::is_ore(item.r#type) {
+            item.amount * SMELT_YIELD
+        } else {
+            item.amount
+        };
+        Self::transmute(item.r#type).to_item(amount)
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct TemperatureGauge {
+    pub minigame: Entity,
+}
+
+// The range the next ore smelts in, over the gauge.
+#[derive(Debug, Clone, Component)]
+pub struct SmeltingBand {
+    pub minigame: Entity,
+}
+
+// Size and y of the stretch of the gauge from `low` to `high` degrees: it
+// reads up from the bottom of its well.
+fn gauge_span(low: f32, high: f32) -> (Vec2, f32) {
+    let well = AREA.height - 10.0;
+    let bottom = well * (low / GAUGE_MAX).clamp(0.0, 1.0);
+    let top = well * (high / GAUGE_MAX).clamp(0.0, 1.0);
+    (
+        Vec2::new(GAUGE_WIDTH, top - bottom),
+        -well / 2.0 + (bottom + top) / 2.0,
+    )
+}
+
+// Blue when cold, red at the top of the gauge.
+fn gauge_color(temperature: f32) -> Color {
+    GAUGE_COLD.mix(&GAUGE_HOT, (temperature / GAUGE_MAX).clamp(0.0, 1.0))
 }
 
 const COOK_PERIOD_SECONDS: f32 = 1.0;
@@ -171,6 +406,7 @@ pub fn cook_fixed_update(
         let Minigame::Foundry(minigame) = minigame.into_inner() else {
             continue;
         };
+        minigame.cool(time.delta_secs());
         if minigame.last_cook == 0.0 {
             minigame.last_cook = time.elapsed_secs();
         } else if minigame.last_cook + time.elapsed_secs()
@@ -191,8 +427,9 @@ pub fn cook_fixed_update(
                 return;
             }
 
-            // remove first item in cooking, map, emit
-            let Some(raw) = minigame.cooking.pop_front() else {
+            // smelt or boil off the first item in cooking; it waits in
+            // line until it's hot enough
+            let Some(cooked) = minigame.cook_ore() else {
                 continue;
             };
             minigame.last_cook = time.elapsed_secs();
@@ -200,19 +437,140 @@ pub fn cook_fixed_update(
             commands.spawn(ItemBundle::new_from_minigame(
                 &mut images,
                 &mut generated_image_assets,
-                FoundryMinigame::transmute(raw.r#type).to_item(raw.amount),
+                cooked,
                 minigame_transform,
                 minigame_area,
             ));
 
-            // update total cooked
-            minigame.total_cooked += raw.amount;
             // level up
-            let level =
-                FoundryMinigame::level_by_total_cooked(minigame.total_cooked);
-            if level > minigame.level {
+            if minigame.should_level_up() {
                 commands.entity(minigame_entity).insert(LevelingUp);
             }
         }
     }
 }
+
+pub fn update_temperature_gauge(
+    minigame_query: Query<&Minigame, Changed<Minigame>>,
+    mut gauge_query: Query<(&TemperatureGauge, &mut Sprite, &mut Transform)>,
+    mut band_query: Query<
+        (&SmeltingBand, &mut Sprite, &mut Transform, &mut Visibility),
+        Without<TemperatureGauge>,
+    >,
+) {
+    for (gauge, mut sprite, mut transform) in gauge_query.iter_mut() {
+        let Ok(Minigame::Foundry(foundry)) = minigame_query.get(gauge.minigame)
+        else {
+            continue;
+        };
+        let (size, y) = gauge_span(0.0, foundry.temperature);
+        sprite.custom_size = Some(size);
+        sprite.color = gauge_color(foundry.temperature);
+        transform.translation.y = y;
+    }
+    for (band, mut sprite, mut transform, mut visibility) in
+        band_query.iter_mut()
+    {
+        let Ok(Minigame::Foundry(foundry)) = minigame_query.get(band.minigame)
+        else {
+            continue;
+        };
+        let Some((min, max)) = foundry.next_smelting_range() else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        let (size, y) = gauge_span(min, max);
+        sprite.custom_size = Some(size);
+        transform.translation.y = y;
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ore_smelts_to_fewer_refined_lumps() {
+        let ore = Item::ore(Substance::Copper, 4.0);
+        assert_eq!(FoundryMinigame::heat_needed(&ore), 4.0 * HEAT_PER_ORE);
+        let lump = FoundryMinigame::cooked(&ore);
+        assert_eq!(
+            lump.r#type,
+            Item::solid(Substance::Copper, BulkShape::Lump, 1.0).r#type
+        );
+        assert_eq!(lump.amount, 4.0 * SMELT_YIELD);
+    }
+
+    #[test]
+    fn ore_smelts_only_in_its_range() {
+        let mut foundry = FoundryMinigame::default();
+        let tin = Item::ore(Substance::Tin, 2.0);
+        let (min, max) = FoundryMinigame::smelting_range(Substance::Tin);
+        foundry.ingest_item(&tin);
+        assert_eq!(foundry.next_smelting_range(), Some((min, max)));
+
+        // too cold: it waits
+        assert!(foundry.cook_ore().is_none());
+        assert_eq!(foundry.cooking.len(), 1);
+
+        // in range: lumps, and smelting cools the foundry
+        let thermal = Item::energy(EnergyKind::Thermal, 3.0);
+        foundry.ingest_item(&thermal);
+        assert_eq!(foundry.temperature, 3.0 * DEGREES_PER_HEAT);
+        assert!((min..=max).contains(&foundry.temperature));
+        let lump = foundry.cook_ore().unwrap();
+        let cooked = FoundryMinigame::cooked(&tin);
+        assert_eq!((lump.r#type, lump.amount), (cooked.r#type, cooked.amount));
+        assert_eq!(
+            foundry.temperature,
+            (3.0 - FoundryMinigame::heat_needed(&tin)) * DEGREES_PER_HEAT
+        );
+        assert_eq!(foundry.total_cooked, 2.0);
+
+        // too hot: it boils off as gas, and nothing is cooked
+        foundry.ingest_item(&tin);
+        foundry.ingest_item(&Item::energy(EnergyKind::Thermal, 5.0));
+        assert!(foundry.temperature > max);
+        let gas = foundry.cook_ore().unwrap();
+        let boiled = Item::gas(Substance::Tin, 2.0 * SMELT_YIELD);
+        assert_eq!((gas.r#type, gas.amount), (boiled.r#type, boiled.amount));
+        assert!(foundry.cooking.is_empty());
+        assert_eq!(foundry.total_cooked, 2.0);
+        assert_eq!(foundry.next_smelting_range(), None);
+    }
+
+    #[test]
+    fn softened_ore_smelts_for_less_heat() {
+        let mut foundry = FoundryMinigame::default();
+        let iron = Item::ore(Substance::Iron, 2.0);
+        foundry.ingest_item(&iron);
+        foundry.add_softened(&iron, 2.0);
+        // only ore is smelted softened
+        foundry.add_softened(&Item::energy(EnergyKind::Thermal, 1.0), 1.0);
+        assert_eq!(foundry.softened, 2.0);
+
+        let (min, _) = FoundryMinigame::smelting_range(Substance::Iron);
+        foundry.temperature = min + 100.0;
+        foundry.cook_ore().unwrap();
+        let spent = FoundryMinigame::heat_needed(&iron)
+            * (1.0 - SOFTENED_HEAT_SAVED)
+            * DEGREES_PER_HEAT;
+        assert_eq!(foundry.temperature, min + 100.0 - spent);
+        assert_eq!(foundry.softened, 0.0);
+    }
+
+    #[test]
+    fn the_foundry_cools_down() {
+        let mut foundry = FoundryMinigame {
+            temperature: 500.0,
+            ..default()
+        };
+        foundry.cool(1.0);
+        assert_eq!(foundry.temperature, 500.0 * (1.0 - COOLING_PER_SECOND));
+        for _ in 0..200 {
+            foundry.cool(1.0);
+        }
+        assert_eq!(foundry.temperature, 0.0);
+    }
+}
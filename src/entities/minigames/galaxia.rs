@@ -0,0 +1,389 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Galaxia, the goal at the end of the unlock graph. It opens once every other
+// minigame has reached `GOAL_LEVEL`, and holds a constellation of `STARS`,
+// each lit by one unit of a rare item: Unobtainium, an Exclusive Other rune
+// and each kind of mana. With every star lit the finale plays for
+// `FINALE_SECONDS`, and then it levels up, which is a prestige: the stars go
+// dark to be assembled again, and each prestige makes the constellation glow
+// out `XP_PER_PRESTIGE_MINUTE` XP a minute more, for good. Its level is its
+// prestige, so saves keep it with the other levelups.
+
+pub const ID: &str = "galaxia";
+pub const POSITION: Vec2 = Vec2::new(0.0, -1000.0);
+
+pub const NAME: &str = "galaxia.name";
+pub const DESCRIPTION: &str = "galaxia.description";
+const AREA: RectangularArea = RectangularArea {
+    width: 300.0,
+    height: 220.0,
+};
+
+// What every other minigame has to reach first.
+pub const GOAL_LEVEL: u8 = 5;
+const FINALE_SECONDS: f32 = 5.0;
+const XP_PER_PRESTIGE_MINUTE: f32 = 1.0;
+
+const STAR_RADIUS: f32 = 12.0;
+const DARK_STAR: Color = Color::srgb(0.2, 0.2, 0.35);
+const LIT_STAR: Color = Color::srgb(1.0, 0.95, 0.6);
+const LINE_COLOR: Color = Color::srgba(0.6, 0.6, 0.9, 0.5);
+// how much bigger stars swell at the height of the finale
+const FINALE_SWELL: f32 = 0.5;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Star {
+    Unobtainium,
+    ExclusiveOther,
+    Mana(ManaKind),
+}
+
+// In the order the lines join them.
+pub const STARS: [(Star, Vec2); 8] = [
+    (Star::Unobtainium, Vec2::new(0.0, 10.0)),
+    (Star::Mana(ManaKind::Light), Vec2::new(50.0, 45.0)),
+    (Star::Mana(ManaKind::Fire), Vec2::new(105.0, 20.0)),
+    (Star::Mana(ManaKind::Air), Vec2::new(90.0, -50.0)),
+    (Star::ExclusiveOther, Vec2::new(20.0, -75.0)),
+    (Star::Mana(ManaKind::Earth), Vec2::new(-55.0, -40.0)),
+    (Star::Mana(ManaKind::Water), Vec2::new(-80.0, 35.0)),
+    (Star::Mana(ManaKind::Dark), Vec2::new(-30.0, 80.0)),
+];
+
+impl Star {
+    pub fn takes(&self, item: &Item) -> bool {
+        match (self, item.r#type) {
+            (
+                Star::Unobtainium,
+                ItemType::Physical(PhysicalItem::Bulk(bulk)),
+            ) => bulk.substance == Substance::Unobtainium,
+            (
+                Star::ExclusiveOther,
+                ItemType::Abstract(AbstractItem {
+                    kind: AbstractKind::Rune,
+                    variant,
+                }),
+            ) => variant == item::rune::Rune::ExclusiveOther as u8,
+            (Star::Mana(kind), ItemType::Mana(mana)) => mana.kind == *kind,
+            _ => false,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        tr(match self {
+            Star::Unobtainium => "galaxia.star.unobtainium",
+            Star::ExclusiveOther => "galaxia.star.exclusive_other",
+            Star::Mana(ManaKind::Fire) => "galaxia.star.fire",
+            Star::Mana(ManaKind::Water) => "galaxia.star.water",
+            Star::Mana(ManaKind::Earth) => "galaxia.star.earth",
+            Star::Mana(ManaKind::Air) => "galaxia.star.air",
+            Star::Mana(ManaKind::Light) => "galaxia.star.light",
+            Star::Mana(ManaKind::Dark) => "galaxia.star.dark",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default, Component)]
+pub struct GalaxiaMinigame {
+    pub level: u8,
+    // which of `STARS` are lit
+    pub lit: [bool; STARS.len()],
+    // seconds of finale left; 0 when not playing
+    pub finale_left: f32,
+    // XP glowed out but not yet a whole item
+    pub glow: f32,
+}
+
+impl GalaxiaMinigame {
+    //
+    // COMMON
+    //
+
+    pub fn name(&self) -> &str {
+        NAME
+    }
+
+    pub fn description(&self) -> &str {
+        DESCRIPTION
+    }
+
+    pub fn area(&self) -> RectangularArea {
+        AREA
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn levelup(&self) -> Self {
+        Self {
+            level: self.level + 1,
+            ..default()
+        }
+    }
+
+    pub fn carry_over(&self, next: &mut Self) {
+        next.glow = self.glow;
+    }
+
+    pub fn progress(&self) -> f32 {
+        self.lit_count() as f32 / STARS.len() as f32
+    }
+
+    pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
+        let minigame = parent.target_entity();
+        parent.spawn((
+            Sprite {
+                color: Color::srgb(0.03, 0.03, 0.1),
+                custom_size: Some(AREA.into()),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, -1.0),
+        ));
+        parent.spawn((
+            ShapeBuilder::with(&shapes::Polygon {
+                points: STARS.iter().map(|(_, at)| *at).collect(),
+                closed: false,
+            })
+            .stroke(Stroke::new(LINE_COLOR, 1.5))
+            .build(),
+            Transform::from_xyz(0.0, 0.0, -0.5),
+        ));
+        for (index, (star, at)) in STARS.iter().enumerate() {
+            parent.spawn((
+                StarSlot { minigame, index },
+                CircularArea {
+                    radius: STAR_RADIUS,
+                },
+                Hoverable,
+                HoverText::new(star.label()),
+                ShapeBuilder::with(&shapes::Circle {
+                    radius: STAR_RADIUS,
+                    ..default()
+                })
+                .fill(Fill::color(star_color(self.lit[index])))
+                .stroke(Stroke::new(LIT_STAR, 1.0))
+                .build(),
+                Transform::from_translation(at.extend(0.0)),
+            ));
+        }
+        parent.spawn((
+            FinaleText { minigame },
+            Text2d::new(tr("galaxia.finale")),
+            TextFont {
+                font_size: 24.0,
+                ..default()
+            },
+            TextColor(LIT_STAR),
+            Transform::from_xyz(0.0, AREA.height / 2.0 - 20.0, 1.0),
+            Visibility::Hidden,
+        ));
+    }
+
+    pub fn ingest_item(&mut self, item: &Item) -> f32 {
+        let Some(index) = self.star_for(item) else {
+            return 0.0;
+        };
+        self.lit[index] = true;
+        if self.is_assembled() {
+            self.finale_left = FINALE_SECONDS;
+        }
+        1.0
+    }
+
+    //
+    // SPECIFIC
+    //
+
+    // The first dark star `item` would light, if it holds a whole unit.
+    pub fn star_for(&self, item: &Item) -> Option<usize> {
+        if item.amount < 1.0 || self.finale_left > 0.0 {
+            return None;
+        }
+        (0..STARS.len())
+            .find(|&index| !self.lit[index] && STARS[index].0.takes(item))
+    }
+
+    pub fn accepted_amount(&self, item: &Item) -> f32 {
+        if self.star_for(item).is_some() {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    pub fn lit_count(&self) -> usize {
+        self.lit.iter().filter(|lit| **lit).count()
+    }
+
+    pub fn is_assembled(&self) -> bool {
+        self.lit_count() == STARS.len()
+    }
+
+    // XP a second the constellation gives off, for prestiges so far.
+    pub fn glow_rate(&self) -> f32 {
+        self.level as f32 * XP_PER_PRESTIGE_MINUTE / 60.0
+    }
+
+    // Play the finale on for `seconds`. Returns whether it just ended.
+    pub fn play_finale(&mut self, seconds: f32) -> bool {
+        if self.finale_left <= 0.0 {
+            return false;
+        }
+        self.finale_left = (self.finale_left - seconds).max(0.0);
+        self.finale_left == 0.0
+    }
+}
+
+fn star_color(lit: bool) -> Color {
+    if lit {
+        LIT_STAR
+    } else {
+        DARK_STAR
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct StarSlot {
+    pub minigame: Entity,
+    pub index: usize,
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct FinaleText {
+    pub minigame: Entity,
+}
+
+// Plays the finale into a prestige, and glows out XP.
+pub fn fixed_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut minigame_query: Query<
+        (Entity, &mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+) {
+    for (entity, minigame, global_transform, area) in minigame_query.iter_mut()
+    {
+        let Minigame::Galaxia(minigame) = minigame.into_inner() else {
+            continue;
+        };
+        if minigame.play_finale(time.delta_secs()) {
+            commands.entity(entity).insert(LevelingUp);
+        }
+        minigame.glow += minigame.glow_rate() * time.delta_secs();
+        if minigame.glow < 1.0 {
+            continue;
+        }
+        let amount = minigame.glow.floor();
+        minigame.glow -= amount;
+        commands.spawn(ItemBundle::new_from_minigame(
+            &mut images,
+            &mut generated_image_assets,
+            Item::xp(amount),
+            global_transform,
+            area,
+        ));
+    }
+}
+
+// Lights the stars, and swells them through the finale.
+pub fn update_stars(
+    minigame_query: Query<&Minigame>,
+    mut star_query: Query<(&StarSlot, &mut Shape, &mut Transform)>,
+    mut finale_query: Query<(&FinaleText, &mut Visibility)>,
+) {
+    for (slot, mut shape, mut transform) in star_query.iter_mut() {
+        let Ok(Minigame::Galaxia(galaxia)) = minigame_query.get(slot.minigame)
+        else {
+            continue;
+        };
+        let color = star_color(galaxia.lit[slot.index]);
+        if let Some(mut fill) = shape.fill.filter(|fill| fill.color != color) {
+            fill.color = color;
+            shape.fill = Some(fill);
+        }
+        let finale = galaxia.finale_left / FINALE_SECONDS;
+        let swell = 1.0 + FINALE_SWELL * (finale * std::f32::consts::PI).sin();
+        transform.scale = Vec3::splat(swell);
+    }
+    for (text, mut visibility) in finale_query.iter_mut() {
+        let Ok(Minigame::Galaxia(galaxia)) = minigame_query.get(text.minigame)
+        else {
+            continue;
+        };
+        *visibility = if galaxia.finale_left > 0.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mana(kind: ManaKind) -> Item {
+        Item::mana(kind, ManaIntent::Attack, 1.0)
+    }
+
+    fn everything() -> Vec<Item> {
+        let rune = item::rune::Rune::ExclusiveOther as u8;
+        let mut items = vec![
+            Item::ore(Substance::Unobtainium, 1.0),
+            Item::new_abstract(AbstractKind::Rune, rune, 1.0),
+        ];
+        items.extend(
+            [
+                ManaKind::Fire,
+                ManaKind::Water,
+                ManaKind::Earth,
+                ManaKind::Air,
+                ManaKind::Light,
+                ManaKind::Dark,
+            ]
+            .map(mana),
+        );
+        items
+    }
+
+    #[test]
+    fn each_star_takes_one_whole_unit_of_its_item() {
+        let mut galaxia = GalaxiaMinigame::default();
+        let fire = Item::mana(ManaKind::Fire, ManaIntent::Support, 3.0);
+        assert_eq!(galaxia.ingest_item(&fire), 1.0);
+        assert_eq!(galaxia.accepted_amount(&fire), 0.0, "already lit");
+        let half = Item::ore(Substance::Unobtainium, 0.5);
+        assert_eq!(galaxia.accepted_amount(&half), 0.0);
+        let force = Item::new_abstract(
+            AbstractKind::Rune,
+            item::rune::Rune::Force as u8,
+            1.0,
+        );
+        assert_eq!(galaxia.accepted_amount(&force), 0.0);
+        assert_eq!(galaxia.lit_count(), 1);
+    }
+
+    #[test]
+    fn assembling_every_star_plays_the_finale_into_a_prestige() {
+        let mut galaxia = GalaxiaMinigame::default();
+        for item in everything() {
+            assert!(!galaxia.is_assembled());
+            assert_eq!(galaxia.ingest_item(&item), 1.0);
+        }
+        assert!(galaxia.is_assembled());
+        assert_eq!(galaxia.progress(), 1.0);
+        assert!(!galaxia.play_finale(FINALE_SECONDS / 2.0));
+        assert!(galaxia.play_finale(FINALE_SECONDS));
+        let next = galaxia.levelup();
+        assert_eq!(next.lit_count(), 0, "dark again");
+        assert!(next.glow_rate() > galaxia.glow_rate());
+    }
+}
@@ -1,5 +1,6 @@
+use std::collections::HashMap;
+
 use bevy::prelude::*;
-use wyrand::WyRand;
 
 use crate::entities::*;
 use crate::libs::*;
@@ -7,16 +8,12 @@ use crate::libs::*;
 pub const ID: &str = "land";
 pub const POSITION: Vec2 = Vec2::new(600.0, -600.0);
 
-pub const NAME: &str = "Land";
-pub const DESCRIPTION: &str = "Evolve life";
+pub const NAME: &str = "land.name";
+pub const DESCRIPTION: &str = "land.description";
 
 const MIN_WIDTH: f32 = 100.0;
 const MIN_HEIGHT: f32 = 100.0;
 const CELL_SIZE: f32 = 25.0;
-const CELL_AREA: RectangularArea = RectangularArea {
-    width: CELL_SIZE,
-    height: CELL_SIZE,
-};
 
 // Fixed-update ticks between evolution steps (FixedUpdate is 20 Hz → ~1s), so
 // the simulation is watchable rather than an instant blur. A tick countdown
@@ -27,6 +24,9 @@ const EVOLVE_TICKS: u32 = 20;
 // is capped low until the food web and species pyramid arrive (see design).
 const MAX_LEVEL: u8 = 6;
 
+// How many painted strokes Ctrl+Z can take back.
+const UNDO_STROKES: usize = 20;
+
 // A single cell: a stack of coexisting layers, one occupant per layer. Terrain
 // is always present (default Mud); the rest are optional. The layers mirror the
 // item-model taxonomy classes so insertion routes by class.
@@ -69,6 +69,16 @@ pub struct LandMinigame {
     pub cells: Vec<Vec<LandCell>>,
     // Fixed-update ticks until the next evolution step.
     pub evolve_cooldown: u32,
+    // Ingested terrain materials, waiting to be painted onto cells.
+    pub terrain_store: HashMap<ItemType, f32>,
+    // The material dragging across cells paints, while Land is engaged. None
+    // leaves clicks to extract instead.
+    pub brush: Option<ItemType>,
+    // Fixed-update ticks until the biomes next yield their bonus.
+    pub biome_cooldown: u32,
+    // Cells each painted stroke changed, oldest stroke first, with the
+    // terrain they had before. A stroke runs from a press to its release.
+    pub strokes: Vec<Vec<(u8, u8, ItemType)>>,
 }
 
 impl Default for LandMinigame {
@@ -93,6 +103,10 @@ impl LandMinigame {
             energy,
             cells,
             evolve_cooldown: EVOLVE_TICKS,
+            terrain_store: HashMap::new(),
+            brush: None,
+            biome_cooldown: balance().land.bonus_ticks,
+            strokes: Vec::new(),
         }
     }
 
@@ -114,12 +128,10 @@ impl LandMinigame {
 
     pub fn area(&self) -> RectangularArea {
         const BUFFER: f32 = 20.0;
-        let blocks_per_row = self.blocks_per_row();
-        let blocks_per_column = self.blocks_per_column();
+        let grid = self.grid().size();
         RectangularArea {
-            width: BUFFER + MIN_WIDTH.max(CELL_SIZE * blocks_per_row as f32),
-            height: BUFFER
-                + MIN_HEIGHT.max(CELL_SIZE * blocks_per_column as f32),
+            width: BUFFER + MIN_WIDTH.max(grid.x),
+            height: BUFFER + MIN_HEIGHT.max(grid.y),
         }
     }
 
@@ -128,8 +140,11 @@ impl LandMinigame {
     }
 
     pub fn levelup(&self) -> Self {
-        // Preserve the existing cells into the (larger) new grid.
-        let mut next = Self::new(self.max_achieved_complexity, self.energy);
+        Self::new(self.max_achieved_complexity, self.energy)
+    }
+
+    // Preserve the existing cells into the (larger) new grid.
+    pub fn carry_over(&self, next: &mut Self) {
         for (y, row) in self.cells.iter().enumerate() {
             for (x, cell) in row.iter().enumerate() {
                 if y < next.cells.len() && x < next.cells[y].len() {
@@ -137,37 +152,73 @@ impl LandMinigame {
                 }
             }
         }
-        next
+        next.evolve_cooldown = self.evolve_cooldown;
+        next.terrain_store = self.terrain_store.clone();
+        next.brush = self.brush;
+        next.biome_cooldown = self.biome_cooldown;
+    }
+
+    pub fn progress(&self) -> f32 {
+        0.0 // levels all at once, on evolving a more complex species
     }
 
     pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
-        let (area, blocks_per_row, blocks_per_column) =
-            (self.area(), self.blocks_per_row(), self.blocks_per_column());
+        let minigame = parent.target_entity();
+        let (area, grid) = (self.area(), self.grid());
 
         let _background = parent.spawn((
+            GridBackground,
             Sprite {
-                color: Color::srgb(0.5, 0.5, 0.5),
+                color: GRID_BACKGROUND_COLOR,
                 custom_size: Some(area.into()),
                 ..default()
             },
             Transform::from_xyz(0.0, 0.0, -1.0),
         ));
 
-        for y in 0..blocks_per_column {
-            for x in 0..blocks_per_row {
-                parent.spawn(CellBundle::new(
-                    x,
-                    y,
-                    blocks_per_row,
-                    blocks_per_column,
-                ));
-            }
-        }
+        grid.spawn(parent, |_, _| {
+            (
+                Cell,
+                Sprite {
+                    // Slightly smaller than the cell pitch so the grid reads
+                    // as distinct squares.
+                    custom_size: Some(grid.cell_area().dimensions() * 0.9),
+                    ..default()
+                },
+            )
+        });
+
+        // shown while engaged; see update_brush_text
+        parent.spawn((
+            BrushText { minigame },
+            Text2d::new(""),
+            TextFont {
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Transform::from_xyz(0.0, area.bottom() + 5.0, 1.0),
+            Visibility::Hidden,
+        ));
+
+        // see update_biome_text
+        parent.spawn((
+            BiomeText { minigame },
+            Text2d::new(""),
+            TextFont {
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Transform::from_xyz(0.0, area.top() - 5.0, 1.0),
+        ));
     }
 
-    // Route an ingested item onto a random cell, placing one unit and ejecting
-    // the remainder. Energy goes into the pool; bulk into terrain; organisms
-    // into their class layer; everything else into `other`.
+    // Route an ingested item. Energy goes into the pool and bulk into the
+    // terrain store, for painting, salt water dried out (see `store_bulk`).
+    // Organisms go onto a random cell's class
+    // layer and everything else onto its `other` layer, one unit placed and
+    // the remainder left to the caller.
     #[allow(clippy::too_many_arguments)]
     pub fn ingest_item(
         &mut self,
@@ -185,26 +236,39 @@ impl LandMinigame {
                 self.energy += item.amount;
                 item.amount
             }
-            // Bulk substances replace the cell's terrain layer.
+            // Bulk substances wait in the store until painted.
             ItemType::Physical(PhysicalItem::Bulk(_)) => {
-                self.place(commands, rand, images, generated_image_assets,
-                    minigame_transform, minigame_area, item, Layer::Terrain)
+                if let Some(salt) = self.store_bulk(item) {
+                    commands.spawn(ItemBundle::new_from_minigame(
+                        images,
+                        generated_image_assets,
+                        salt,
+                        minigame_transform,
+                        minigame_area,
+                    ));
+                }
+                item.amount
             }
-            // Organisms route to their taxonomic class layer.
-            ItemType::Physical(PhysicalItem::Discrete(d)) => {
-                let layer = match d.species.class() {
-                    DiscreteClass::Microbe => Layer::Micro,
-                    DiscreteClass::Plant => Layer::Plant,
-                    DiscreteClass::Animal => Layer::Animal,
-                    // Fruit/Tool/Weapon are not organisms here — stash them.
-                    _ => Layer::Other,
-                };
-                self.place(commands, rand, images, generated_image_assets,
-                    minigame_transform, minigame_area, item, layer)
+            _ => self.place(rand, item, Layer::of(item.r#type)),
+        }
+    }
+
+    // How much of `item` `ingest_item` would take: all the energy and bulk,
+    // and one of anything else while some cell has its layer free. Placing
+    // picks a random cell, so that one can still miss.
+    pub fn accepted_amount(&self, item: &Item) -> f32 {
+        match item.r#type {
+            ItemType::Energy(_) | ItemType::Physical(PhysicalItem::Bulk(_)) => {
+                item.amount
+            }
+            _ => {
+                let layer = Layer::of(item.r#type);
+                if self.cells.iter().flatten().any(|cell| layer.is_free(cell)) {
+                    1.0
+                } else {
+                    0.0
+                }
             }
-            // Mana, abstract, fruit, etc. go in the catch-all `other` layer.
-            _ => self.place(commands, rand, images, generated_image_assets,
-                minigame_transform, minigame_area, item, Layer::Other),
         }
     }
 
@@ -220,6 +284,14 @@ impl LandMinigame {
         Self::_blocks_per_column(self.level)
     }
 
+    pub fn grid(&self) -> GridWidget {
+        GridWidget::new(
+            self.blocks_per_row(),
+            self.blocks_per_column(),
+            CELL_SIZE,
+        )
+    }
+
     // Shared gentle growth, same as life: 1×1, 2×1, 2×2, 3×2, 3×3, …
     // level -> blocks_per_row: 0->1, 1->1, 2->2
     fn _blocks_per_row(level: u8) -> u8 {
@@ -235,40 +307,47 @@ impl LandMinigame {
         1 + level / 2
     }
 
+    // Puts a bulk item in the terrain store. Salt water from the ocean dries
+    // out on land: its water goes in fresh, and the salt it leaves is
+    // returned, to be ejected.
+    fn store_bulk(&mut self, item: &Item) -> Option<Item> {
+        let ItemType::Physical(PhysicalItem::Bulk(bulk)) = item.r#type else {
+            return None;
+        };
+        if bulk.substance != Substance::SaltWater {
+            add_item(&mut self.terrain_store, item.r#type, item.amount);
+            return None;
+        }
+        let fresh_water = ItemType::Physical(PhysicalItem::Bulk(BulkItem {
+            substance: Substance::FreshWater,
+            ..bulk
+        }));
+        add_item(&mut self.terrain_store, fresh_water, item.amount);
+        Some(Item::powder(
+            Substance::Salt,
+            item.amount * minigames::primordial_ocean::SALT_PER_SALT_WATER,
+        ))
+    }
+
     fn dimensions(&self) -> (usize, usize) {
         let height = self.cells.len();
         let width = if height == 0 { 0 } else { self.cells[0].len() };
         (width, height)
     }
 
-    // Place one unit of `item` onto a random cell's `layer`, ejecting the
-    // remainder as a loose item. Terrain is always replaced; other layers only
-    // accept if empty (else the whole item is rejected). Returns amount ingested.
-    #[allow(clippy::too_many_arguments)]
-    fn place(
-        &mut self,
-        commands: &mut Commands,
-        rand: &mut Random,
-        images: &mut Assets<Image>,
-        generated_image_assets: &mut image_gen::GeneratedImageAssets,
-        minigame_transform: &GlobalTransform,
-        minigame_area: &RectangularArea,
-        item: &Item,
-        layer: Layer,
-    ) -> f32 {
+    // Place one unit of `item` onto a random cell's `layer`. The layer only
+    // accepts if empty (else the whole item is rejected). Returns amount
+    // ingested.
+    fn place(&mut self, rand: &mut Random, item: &Item, layer: Layer) -> f32 {
         let (width, height) = self.dimensions();
         if width == 0 || height == 0 {
             return 0.0;
         }
-        let x = (rand.next() as usize) % width;
-        let y = (rand.next() as usize) % height;
+        let x = rand.next_range(width as u64) as usize;
+        let y = rand.next_range(height as u64) as usize;
         let cell = &mut self.cells[y][x];
 
         let placed = match layer {
-            Layer::Terrain => {
-                cell.terrain = item.r#type;
-                true
-            }
             Layer::Micro if cell.micro.is_none() => {
                 cell.micro = Some(item.r#type);
                 true
@@ -289,21 +368,11 @@ impl LandMinigame {
             _ => false,
         };
 
-        if !placed {
-            return 0.0;
-        }
-
-        // Eject the remainder.
-        if item.amount > 1.0 {
-            commands.spawn(ItemBundle::new_from_minigame(
-                images,
-                generated_image_assets,
-                Item::new(item.r#type, item.amount - 1.0),
-                minigame_transform,
-                minigame_area,
-            ));
+        if placed {
+            1.0
+        } else {
+            0.0
         }
-        1.0
     }
 
     // A terrain cell counts as water if it is a bulk substance in the Water
@@ -324,6 +393,22 @@ impl LandMinigame {
         )
     }
 
+    // A tree past the seed and not yet dead.
+    fn is_tree(item: ItemType) -> bool {
+        matches!(
+            item,
+            ItemType::Physical(PhysicalItem::Discrete(DiscreteItem {
+                species: Species::Tree,
+                state: item::State::Stage(
+                    LifeStage::Baby
+                        | LifeStage::Youth
+                        | LifeStage::Adult
+                        | LifeStage::Elder
+                ),
+            }))
+        )
+    }
+
     fn archaea() -> ItemType {
         Item::organism(Species::Archaea, LifeStage::Adult, 1.0).r#type
     }
@@ -344,9 +429,7 @@ impl LandMinigame {
             .enumerate()
             .flat_map(|(y, row)| {
                 row.iter().enumerate().filter_map(move |(x, cell)| {
-                    cell.micro
-                        .is_some_and(Self::is_archaea)
-                        .then_some((x, y))
+                    cell.micro.is_some_and(Self::is_archaea).then_some((x, y))
                 })
             })
             .collect();
@@ -361,15 +444,16 @@ impl LandMinigame {
             //    water and empty of micro.
             let (nx, ny) = self.random_neighbor(rand, (x, y));
             let neighbor = &self.cells[ny][nx];
-            if Self::terrain_is_water(neighbor.terrain) && neighbor.micro.is_none()
+            if Self::terrain_is_water(neighbor.terrain)
+                && neighbor.micro.is_none()
             {
                 self.cells[ny][nx].micro = Some(Self::archaea());
             }
         }
     }
 
-    // A random neighbor of the given cell (may return the cell itself), clamped
-    // to grid bounds.
+    // A random neighbor of the given cell, or the cell itself, each in-bounds
+    // choice as likely as the others.
     fn random_neighbor(
         &self,
         rand: &mut Random,
@@ -382,13 +466,15 @@ impl LandMinigame {
         )
     }
 
+    // `here` or a step either way, within `0..bound`. An empty axis has
+    // nowhere to go, so it stays put.
     fn random_1d(rand: &mut Random, here: usize, bound: usize) -> usize {
-        let mut v = here + (rand.next() as usize % 3);
-        v = v.saturating_sub(1); // only go negative if possible
-        if v >= bound {
-            v = bound - 1;
-        }
-        v
+        let Some(last) = bound.checked_sub(1) else {
+            return here;
+        };
+        let low = here.saturating_sub(1).min(last);
+        let high = (here + 1).min(last);
+        low + rand.next_range((high - low + 1) as u64) as usize
     }
 
     // The number of distinct ItemTypes present across all layers of all cells
@@ -432,75 +518,310 @@ impl LandMinigame {
         }
     }
 
+    // The stored materials there's at least a cell's worth of, in a stable
+    // order.
+    fn paintable_materials(&self) -> Vec<ItemType> {
+        let mut materials: Vec<ItemType> = self
+            .terrain_store
+            .iter()
+            .filter(|(_, amount)| **amount >= 1.0)
+            .map(|(material, _)| *material)
+            .collect();
+        materials.sort_by_key(|material| material.uid());
+        materials
+    }
+
+    // Moves the brush `steps` through the paintable materials, forward or
+    // back, passing through "no brush" between the last and the first.
+    pub fn cycle_brush(&mut self, steps: i32) {
+        let materials = self.paintable_materials();
+        let current = self
+            .brush
+            .and_then(|brush| materials.iter().position(|m| *m == brush))
+            .map_or(0, |i| i as i32 + 1);
+        let next = (current + steps).rem_euclid(materials.len() as i32 + 1);
+        self.brush = if next == 0 {
+            None
+        } else {
+            Some(materials[next as usize - 1])
+        };
+    }
+
+    // Paints the brush's material onto a cell's terrain, using up one unit of
+    // it. The brush goes down once there isn't a unit left. Returns whether
+    // the cell changed.
+    pub fn paint(&mut self, x: u8, y: u8) -> bool {
+        let Some(material) = self.brush else {
+            return false;
+        };
+        let (x, y) = (x as usize, y as usize);
+        let Some(cell) = self.cells.get_mut(y).and_then(|row| row.get_mut(x))
+        else {
+            return false;
+        };
+        if cell.terrain == material {
+            return false;
+        }
+        if self.terrain_store.get(&material).copied().unwrap_or(0.0) < 1.0 {
+            self.brush = None;
+            return false;
+        }
+        let was = std::mem::replace(&mut cell.terrain, material);
+        if self.strokes.is_empty() {
+            self.strokes.push(Vec::new());
+        }
+        if let Some(stroke) = self.strokes.last_mut() {
+            stroke.push((x as u8, y as u8, was));
+        }
+        let (_, remaining) =
+            remove_item(&mut self.terrain_store, material, 1.0);
+        if remaining < 1.0 {
+            self.brush = None;
+        }
+        true
+    }
+
+    pub fn begin_stroke(&mut self) {
+        if self.strokes.last().is_some_and(|stroke| stroke.is_empty()) {
+            return;
+        }
+        if self.strokes.len() >= UNDO_STROKES {
+            self.strokes.remove(0);
+        }
+        self.strokes.push(Vec::new());
+    }
+
+    // Unpaints the last stroke that painted anything, returning its material
+    // to the store. Cells whose terrain changed since are left alone. Returns
+    // false if there's no stroke to take back.
+    pub fn undo_stroke(&mut self) -> bool {
+        while let Some(stroke) = self.strokes.pop() {
+            if stroke.is_empty() {
+                continue;
+            }
+            for (x, y, was) in stroke.into_iter().rev() {
+                let (x, y) = (x as usize, y as usize);
+                let Some(cell) =
+                    self.cells.get_mut(y).and_then(|row| row.get_mut(x))
+                else {
+                    continue;
+                };
+                let painted = cell.terrain;
+                if painted == was {
+                    continue;
+                }
+                cell.terrain = was;
+                add_item(&mut self.terrain_store, painted, 1.0);
+            }
+            return true;
+        }
+        false
+    }
+
+    fn brush_label(&self) -> String {
+        match self.brush {
+            Some(material) => tr_fmt(
+                "land.brush",
+                &[
+                    ("material", &material.name()),
+                    (
+                        "amount",
+                        &self
+                            .terrain_store
+                            .get(&material)
+                            .copied()
+                            .unwrap_or(0.0)
+                            .floor(),
+                    ),
+                ],
+            ),
+            None => tr("land.no_brush"),
+        }
+    }
+
+    // Every region of cells big enough to count as a biome, with its size in
+    // cells. Cells only join a region through an edge, not a corner.
+    pub fn biomes(&self, balance: &LandBalance) -> Vec<(Biome, usize)> {
+        let (width, height) = self.dimensions();
+        let mut found = vec![];
+        for biome in Biome::ALL {
+            let mut seen = vec![vec![false; width]; height];
+            for (y, row) in self.cells.iter().enumerate() {
+                for (x, cell) in row.iter().enumerate() {
+                    if seen[y][x] || !biome.contains(cell) {
+                        continue;
+                    }
+                    seen[y][x] = true;
+                    let mut size = 0;
+                    let mut stack = vec![(x, y)];
+                    while let Some((x, y)) = stack.pop() {
+                        size += 1;
+                        // wrapping, so a step off the near edge lands far
+                        // out of bounds
+                        let neighbors = [
+                            (x.wrapping_sub(1), y),
+                            (x + 1, y),
+                            (x, y.wrapping_sub(1)),
+                            (x, y + 1),
+                        ];
+                        for (nx, ny) in neighbors {
+                            if nx < width
+                                && ny < height
+                                && !seen[ny][nx]
+                                && biome.contains(&self.cells[ny][nx])
+                            {
+                                seen[ny][nx] = true;
+                                stack.push((nx, ny));
+                            }
+                        }
+                    }
+                    if size >= biome.min_cells(balance) {
+                        found.push((biome, size));
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    // What the biomes yield each time their countdown runs out: each biome's
+    // bonus once for every multiple of the smallest size it comes in.
+    pub fn biome_yields(&self, balance: &LandBalance) -> Vec<Item> {
+        self.biomes(balance)
+            .into_iter()
+            .map(|(biome, size)| {
+                let mut bonus = biome.bonus();
+                bonus.amount *= (size / biome.min_cells(balance)) as f32;
+                bonus
+            })
+            .collect()
+    }
+
+    // The names of the biomes there are, each once.
+    fn biome_label(&self, balance: &LandBalance) -> String {
+        let mut names: Vec<String> = self
+            .biomes(balance)
+            .iter()
+            .map(|(biome, _)| biome.name())
+            .collect();
+        names.dedup();
+        names.join(", ")
+    }
+
     pub fn get_cell(&self, x: u8, y: u8) -> Option<&LandCell> {
         let (x, y) = (x as usize, y as usize);
         self.cells.get(y).and_then(|row| row.get(x))
     }
 }
 
-// Which layer of a cell an ingested item routes to.
+// A pattern of cells that yields bonus items on its own, rewarding land laid
+// out on purpose. The sizes it takes are in `land` in assets/balance.ron.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Biome {
+    // contiguous water terrain
+    Lake,
+    // contiguous cells growing a tree
+    Forest,
+}
+
+impl Biome {
+    const ALL: [Biome; 2] = [Biome::Lake, Biome::Forest];
+
+    fn contains(&self, cell: &LandCell) -> bool {
+        match self {
+            Biome::Lake => LandMinigame::terrain_is_water(cell.terrain),
+            Biome::Forest => cell.plant.is_some_and(LandMinigame::is_tree),
+        }
+    }
+
+    fn min_cells(&self, balance: &LandBalance) -> usize {
+        match self {
+            Biome::Lake => balance.lake_cells,
+            Biome::Forest => balance.forest_cells,
+        }
+        .max(1)
+    }
+
+    // What a biome of the smallest size yields each time.
+    fn bonus(&self) -> Item {
+        match self {
+            Biome::Lake => Item::organism(Species::Fish, LifeStage::Adult, 1.0),
+            Biome::Forest => Item::fruit(Species::Apple, 1.0),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Biome::Lake => tr("land.biome.lake"),
+            Biome::Forest => tr("land.biome.forest"),
+        }
+    }
+}
+
+// Which layer of a cell an ingested item routes to. Terrain is painted
+// instead.
 #[derive(Debug, Clone, Copy)]
 enum Layer {
-    Terrain,
     Micro,
     Plant,
     Animal,
     Other,
 }
 
-#[derive(Bundle)]
-pub struct CellBundle {
-    pub cell: Cell,
-    pub toggleable: Toggleable,
-    pub sprite: Sprite,
-    pub transform: Transform,
-}
-
-impl CellBundle {
-    pub fn new(x: u8, y: u8, cols: u8, rows: u8) -> Self {
-        let t_y = rows - y; // top to bottom
-        let dx = -CELL_SIZE * ((cols - 1) as f32 / 2.0);
-        let dy = -CELL_SIZE * ((rows + 1) as f32 / 2.0);
-        Self {
-            cell: Cell { x, y },
-            toggleable: Toggleable::new(),
-            sprite: Sprite {
-                // Slightly smaller than the cell pitch so the grid reads as
-                // distinct squares.
-                custom_size: Some(CELL_AREA.dimensions() * 0.9),
-                ..default()
-            },
-            transform: Transform::from_xyz(
-                x as f32 * CELL_SIZE + dx,
-                t_y as f32 * CELL_SIZE + dy,
-                0.0,
-            ),
+impl Layer {
+    // Organisms go on their taxonomic class's layer. Mana, abstract, fruit,
+    // tools and so on go on the catch-all `other` layer.
+    fn of(r#type: ItemType) -> Self {
+        match r#type {
+            ItemType::Physical(PhysicalItem::Discrete(d)) => {
+                match d.species.class() {
+                    DiscreteClass::Microbe => Layer::Micro,
+                    DiscreteClass::Plant => Layer::Plant,
+                    DiscreteClass::Animal => Layer::Animal,
+                    _ => Layer::Other,
+                }
+            }
+            _ => Layer::Other,
         }
     }
 
-    pub fn paint(
-        entity: Entity,
-        query: &mut Query<&mut Sprite, With<Cell>>,
-        new_handle: Handle<Image>,
-    ) {
-        if let Ok(mut sprite) = query.get_mut(entity) {
-            sprite.image = new_handle;
-            sprite.color = Color::WHITE; // no tint — show the texture as-is
+    fn is_free(self, cell: &LandCell) -> bool {
+        match self {
+            Layer::Micro => cell.micro.is_none(),
+            Layer::Plant => cell.plant.is_none(),
+            Layer::Animal => cell.animal.is_none(),
+            Layer::Other => cell.other.is_none(),
         }
     }
 }
 
+// Marks the land grid's cells.
 #[derive(Debug, Clone, Component)]
-pub struct Cell {
-    pub x: u8,
-    pub y: u8,
+pub struct Cell;
+
+// The brush's material and how much of it is left, under the grid.
+#[derive(Debug, Component)]
+pub struct BrushText {
+    pub minigame: Entity,
+}
+
+// The biomes the grid has, over it.
+#[derive(Debug, Component)]
+pub struct BiomeText {
+    pub minigame: Entity,
+}
+
+// Whether dragging across `land` paints rather than clicking extracting.
+fn painting(engaged: &Engaged, land: &LandMinigame) -> bool {
+    engaged.game == Some(ID) && land.brush.is_some()
 }
 
 // Cell was clicked: extract the topmost occupied non-terrain layer and eject it
-// as a loose item. Terrain stays.
+// as a loose item. Terrain stays. Not while painting.
 pub fn cell_update(
     mut commands: Commands,
-    mouse_state: Res<MouseState>,
+    engaged: Res<Engaged>,
+    mut click_events: MessageReader<ClickEvent>,
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
     mut minigame_query: Query<(
@@ -509,43 +830,183 @@ pub fn cell_update(
         &RectangularArea,
     )>,
     leveling_up_query: Query<&LevelingUp, With<Minigame>>,
-    cell_query: Query<(&Cell, &ChildOf, &GlobalTransform)>,
+    cell_query: Query<(&GridCell, &ChildOf), With<Cell>>,
+) {
+    for click in click_events.read() {
+        if !click.is_press() {
+            continue;
+        }
+        let Some((minigame_entity, cell)) =
+            GridCell::hit(click.target, &cell_query)
+        else {
+            continue;
+        };
+        if leveling_up_query.get(minigame_entity).is_ok() {
+            continue;
+        }
+        let Ok((minigame, minigame_transform, minigame_area)) =
+            minigame_query.get_mut(minigame_entity)
+        else {
+            continue;
+        };
+        let Minigame::Land(land) = &*minigame else {
+            continue;
+        };
+        if painting(&engaged, land) {
+            continue;
+        }
+        let Minigame::Land(minigame) = minigame.into_inner() else {
+            continue;
+        };
+
+        // Remove the top non-terrain occupant, if any.
+        let Some(item_type) = minigame.extract_top(cell.x, cell.y) else {
+            continue;
+        };
+        commands.spawn(ItemBundle::new_from_minigame(
+            &mut images,
+            &mut generated_image_assets,
+            item_type.to_item(1.0),
+            minigame_transform,
+            minigame_area,
+        ));
+    }
+}
+
+// While Land is engaged, `,` and `.` step the brush back and forth through
+// the stored terrain materials.
+pub fn brush_input(
+    engaged: Res<Engaged>,
+    minigames: Res<MinigamesResource>,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut minigame_query: Query<&mut Minigame, Without<LevelingUp>>,
 ) {
-    if !mouse_state.just_pressed {
+    if engaged.game != Some(ID) {
         return;
     }
+    let mut steps = 0;
+    if kb_input.just_pressed(KeyCode::Comma) {
+        steps -= 1;
+    }
+    if kb_input.just_pressed(KeyCode::Period) {
+        steps += 1;
+    }
+    if steps == 0 {
+        return;
+    }
+    let Some(entity) = minigames.entity(ID) else {
+        return;
+    };
+    let Ok(mut minigame) = minigame_query.get_mut(entity) else {
+        return;
+    };
+    if let Minigame::Land(land) = &mut *minigame {
+        land.cycle_brush(steps);
+    }
+}
 
-    let mouse_position = mouse_state.current_position;
-    for (cell, cell_parent, cell_global_transform) in cell_query.iter() {
-        let minigame_entity = cell_parent.parent();
-        if leveling_up_query.get(minigame_entity).is_ok() {
+// Paint the cell under a held mouse button with the brush's material, so
+// dragging paints a stroke.
+pub fn paint_cells(
+    engaged: Res<Engaged>,
+    mouse_state: Res<MouseState>,
+    hovered: Res<Hovered>,
+    cell_query: Query<(&GridCell, &ChildOf), With<Cell>>,
+    mut minigame_query: Query<&mut Minigame, Without<LevelingUp>>,
+) {
+    if !mouse_state.dragging() {
+        return;
+    }
+    let Some((minigame_entity, cell)) =
+        GridCell::hit(hovered.entity, &cell_query)
+    else {
+        return;
+    };
+    let Ok(mut minigame) = minigame_query.get_mut(minigame_entity) else {
+        return;
+    };
+    // peek first, so a hover that paints nothing doesn't mark it changed
+    let Minigame::Land(land) = &*minigame else {
+        return;
+    };
+    if !painting(&engaged, land) {
+        return;
+    }
+    if let Minigame::Land(land) = minigame.bypass_change_detection() {
+        if mouse_state.just_pressed {
+            land.begin_stroke();
+        }
+        if !land.paint(cell.x, cell.y) {
+            return;
+        }
+    }
+    minigame.set_changed();
+}
+
+// Ctrl+Z unpaints the last stroke while Land is engaged.
+pub fn undo_input(
+    engaged: Res<Engaged>,
+    minigames: Res<MinigamesResource>,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut minigame_query: Query<&mut Minigame, Without<LevelingUp>>,
+) {
+    if engaged.game != Some(ID) || !undo_pressed(&kb_input) {
+        return;
+    }
+    let Some(entity) = minigames.entity(ID) else {
+        return;
+    };
+    let Ok(mut minigame) = minigame_query.get_mut(entity) else {
+        return;
+    };
+    if let Minigame::Land(land) = minigame.bypass_change_detection() {
+        if !land.undo_stroke() {
+            return;
+        }
+    }
+    minigame.set_changed();
+}
+
+pub fn update_brush_text(
+    engaged: Res<Engaged>,
+    minigame_query: Query<Ref<Minigame>>,
+    mut text_query: Query<(&BrushText, &mut Text2d, &mut Visibility)>,
+) {
+    for (brush_text, mut text, mut visibility) in text_query.iter_mut() {
+        let Ok(minigame) = minigame_query.get(brush_text.minigame) else {
+            continue;
+        };
+        if !minigame.is_changed() && !engaged.is_changed() {
             continue;
         }
-        if CELL_AREA.is_within(
-            mouse_position,
-            cell_global_transform.translation().truncate(),
-        ) {
-            let Ok((minigame, minigame_transform, minigame_area)) =
-                minigame_query.get_mut(minigame_entity)
-            else {
-                continue;
-            };
-            let Minigame::Land(minigame) = minigame.into_inner() else {
-                continue;
-            };
+        let Minigame::Land(land) = &*minigame else {
+            continue;
+        };
+        *visibility = if engaged.game == Some(ID) {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        text.0 = land.brush_label();
+    }
+}
 
-            // Remove the top non-terrain occupant, if any.
-            let Some(item_type) = minigame.extract_top(cell.x, cell.y) else {
-                continue;
-            };
-            commands.spawn(ItemBundle::new_from_minigame(
-                &mut images,
-                &mut generated_image_assets,
-                item_type.to_item(1.0),
-                minigame_transform,
-                minigame_area,
-            ));
+pub fn update_biome_text(
+    balance: Res<BalanceConfig>,
+    minigame_query: Query<Ref<Minigame>>,
+    mut text_query: Query<(&BiomeText, &mut Text2d)>,
+) {
+    for (biome_text, mut text) in text_query.iter_mut() {
+        let Ok(minigame) = minigame_query.get(biome_text.minigame) else {
+            continue;
+        };
+        if !minigame.is_changed() && !balance.is_changed() {
+            continue;
         }
+        let Minigame::Land(land) = &*minigame else {
+            continue;
+        };
+        text.0 = land.biome_label(&balance.land);
     }
 }
 
@@ -593,6 +1054,42 @@ pub fn evolve_fixed_update(
     }
 }
 
+// Eject the biomes' bonus yields every `land.bonus_ticks` fixed ticks. Unlike
+// evolution, this takes no energy.
+pub fn biome_fixed_update(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    balance: Res<BalanceConfig>,
+    mut minigame_query: Query<
+        (&mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+) {
+    for (mut minigame, minigame_transform, minigame_area) in
+        minigame_query.iter_mut()
+    {
+        // the countdown isn't shown, so ticking it doesn't count as a change
+        let Minigame::Land(land) = minigame.bypass_change_detection() else {
+            continue;
+        };
+        if land.biome_cooldown > 0 {
+            land.biome_cooldown -= 1;
+            continue;
+        }
+        land.biome_cooldown = balance.land.bonus_ticks;
+        for item in land.biome_yields(&balance.land) {
+            commands.spawn(ItemBundle::new_from_minigame(
+                &mut images,
+                &mut generated_image_assets,
+                item,
+                minigame_transform,
+                minigame_area,
+            ));
+        }
+    }
+}
+
 // Repaint each cell every FixedUpdate to its topmost occupied layer's texture
 // (other > animal > plant > micro), else the terrain's texture. Cells always
 // show at least terrain. Cheap: Land grids are small.
@@ -600,27 +1097,23 @@ pub fn render_cells(
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
     minigame_query: Query<(Entity, &Minigame)>,
-    cell_query: Query<(Entity, &Cell, &ChildOf)>,
-    mut cell_draw_query: Query<&mut Sprite, With<Cell>>,
+    mut cell_query: Query<(&GridCell, &ChildOf, &mut Sprite), With<Cell>>,
 ) {
     for (minigame_entity, minigame) in minigame_query.iter() {
         let Minigame::Land(land) = minigame else {
             continue;
         };
-        for (cell_entity, cell, cell_parent) in cell_query.iter() {
-            if cell_parent.parent() != minigame_entity {
-                continue;
-            }
-            let Some(land_cell) = land.get_cell(cell.x, cell.y) else {
-                continue;
+        paint_grid(minigame_entity, &mut cell_query, |x, y, sprite| {
+            let Some(land_cell) = land.get_cell(x, y) else {
+                return;
             };
-            let texture = cell_texture(
+            sprite.image = cell_texture(
                 land_cell.top(),
                 &mut images,
                 &mut generated_image_assets,
             );
-            CellBundle::paint(cell_entity, &mut cell_draw_query, texture);
-        }
+            sprite.color = Color::WHITE; // no tint — show the texture as-is
+        });
     }
 }
 
@@ -629,13 +1122,7 @@ fn cell_texture(
     images: &mut Assets<Image>,
     generated_image_assets: &mut image_gen::GeneratedImageAssets,
 ) -> Handle<Image> {
-    let uid = item_type.uid();
-    generated_image_assets.get(&uid).unwrap_or_else(|| {
-        let image = item_type.draw(&mut WyRand::new(SEED));
-        let handle = images.add(image);
-        generated_image_assets.insert(uid, &handle);
-        handle
-    })
+    item_type.texture(images, generated_image_assets, CELL_SIZE / 2.0)
 }
 
 #[cfg(test)]
@@ -651,6 +1138,10 @@ mod tests {
             energy: 0.0,
             cells: vec![vec![LandCell::new(mud); width]; height],
             evolve_cooldown: EVOLVE_TICKS,
+            terrain_store: HashMap::new(),
+            brush: None,
+            biome_cooldown: 0,
+            strokes: Vec::new(),
         }
     }
 
@@ -671,23 +1162,17 @@ mod tests {
                     self.energy += item.amount;
                     return item.amount;
                 }
-                ItemType::Physical(PhysicalItem::Bulk(_)) => Layer::Terrain,
-                ItemType::Physical(PhysicalItem::Discrete(d)) => {
-                    match d.species.class() {
-                        DiscreteClass::Microbe => Layer::Micro,
-                        DiscreteClass::Plant => Layer::Plant,
-                        DiscreteClass::Animal => Layer::Animal,
-                        _ => Layer::Other,
-                    }
+                ItemType::Physical(PhysicalItem::Bulk(_)) => {
+                    self.store_bulk(item);
+                    return item.amount;
                 }
-                _ => Layer::Other,
+                _ => Layer::of(item.r#type),
             };
             let (width, height) = self.dimensions();
-            let x = (rand.next() as usize) % width;
-            let y = (rand.next() as usize) % height;
+            let x = rand.next_range(width as u64) as usize;
+            let y = rand.next_range(height as u64) as usize;
             let cell = &mut self.cells[y][x];
             match layer {
-                Layer::Terrain => cell.terrain = item.r#type,
                 Layer::Micro => cell.micro = Some(item.r#type),
                 Layer::Plant => cell.plant = Some(item.r#type),
                 Layer::Animal => cell.animal = Some(item.r#type),
@@ -698,11 +1183,158 @@ mod tests {
     }
 
     #[test]
-    fn bulk_routes_to_terrain() {
+    fn bulk_waits_in_the_terrain_store() {
         let mut l = land(1, 1);
-        ingest(&mut l, &Item::liquid(Substance::FreshWater, 1.0));
+        ingest(&mut l, &Item::liquid(Substance::FreshWater, 2.5));
+        assert_eq!(l.terrain_store.get(&water()), Some(&2.5));
+        assert_eq!(l.cells[0][0].terrain, LandMinigame::default_terrain());
+    }
+
+    #[test]
+    fn salt_water_dries_out_into_fresh_water_and_salt() {
+        let mut l = land(1, 1);
+        let salt = l.store_bulk(&Item::liquid(Substance::SaltWater, 4.0));
+        assert_eq!(l.terrain_store.get(&water()), Some(&4.0));
+        assert_eq!(l.terrain_store.len(), 1);
+        let salt = salt.expect("salt left over");
+        assert_eq!(salt.r#type, Item::powder(Substance::Salt, 1.0).r#type);
+        assert_eq!(salt.amount, 1.0);
+    }
+
+    #[test]
+    fn brush_cycles_through_paintable_materials() {
+        let mut l = land(1, 1);
+        let stone = Item::solid(Substance::Granite, BulkShape::Block, 1.0);
+        add_item(&mut l.terrain_store, water(), 3.0);
+        add_item(&mut l.terrain_store, stone.r#type, 2.0);
+        // not a cell's worth
+        add_item(
+            &mut l.terrain_store,
+            Item::powder(Substance::Dirt, 1.0).r#type,
+            0.5,
+        );
+
+        let mut seen = vec![];
+        for _ in 0..3 {
+            l.cycle_brush(1);
+            seen.push(l.brush);
+        }
+        assert!(seen[..2].contains(&Some(water())));
+        assert!(seen[..2].contains(&Some(stone.r#type)));
+        assert_eq!(seen[2], None);
+        l.cycle_brush(-1);
+        assert_eq!(l.brush, seen[1]);
+    }
+
+    #[test]
+    fn painting_uses_up_the_store() {
+        let mut l = land(3, 1);
+        add_item(&mut l.terrain_store, water(), 2.0);
+        assert!(!l.paint(0, 0), "no brush, no paint");
+        l.brush = Some(water());
+
+        assert!(l.paint(0, 0));
+        assert!(!l.paint(0, 0), "already water");
+        assert!(l.paint(1, 0));
         assert_eq!(l.cells[0][0].terrain, water());
-        assert!(l.cells[0][0].micro.is_none());
+        assert_eq!(l.cells[0][1].terrain, water());
+        // the store ran dry, so the brush went down
+        assert!(l.terrain_store.is_empty());
+        assert_eq!(l.brush, None);
+        assert!(!l.paint(2, 0));
+    }
+
+    #[test]
+    fn undo_unpaints_the_last_stroke() {
+        let mud = LandMinigame::default_terrain();
+        let mut l = land(3, 1);
+        add_item(&mut l.terrain_store, water(), 5.0);
+        l.brush = Some(water());
+        l.begin_stroke();
+        assert!(l.paint(0, 0));
+        l.begin_stroke();
+        assert!(l.paint(1, 0));
+        assert!(l.paint(2, 0));
+        // something else turned a painted cell back meanwhile
+        l.cells[0][2].terrain = mud;
+
+        assert!(l.undo_stroke());
+        assert_eq!(l.cells[0][0].terrain, water());
+        assert_eq!(l.cells[0][1].terrain, mud);
+        assert_eq!(l.terrain_store[&water()], 3.0);
+        assert!(l.undo_stroke());
+        assert_eq!(l.cells[0][0].terrain, mud);
+        assert_eq!(l.terrain_store[&water()], 4.0);
+        assert!(!l.undo_stroke());
+    }
+
+    fn tree() -> ItemType {
+        Item::organism(Species::Tree, LifeStage::Adult, 1.0).r#type
+    }
+
+    fn biome_balance() -> LandBalance {
+        LandBalance {
+            lake_cells: 3,
+            forest_cells: 2,
+            bonus_ticks: 10,
+        }
+    }
+
+    fn yields(l: &LandMinigame, balance: &LandBalance) -> Vec<(ItemType, f32)> {
+        l.biome_yields(balance)
+            .iter()
+            .map(|item| (item.r#type, item.amount))
+            .collect()
+    }
+
+    #[test]
+    fn lakes_are_enough_water_joined_by_edges() {
+        let balance = biome_balance();
+        let mut l = land(3, 3);
+        // a diagonal doesn't join
+        l.cells[0][0].terrain = water();
+        l.cells[1][1].terrain = water();
+        l.cells[2][2].terrain = water();
+        assert!(l.biomes(&balance).is_empty());
+
+        l.cells[0][1].terrain = water();
+        l.cells[1][2].terrain = water();
+        assert_eq!(l.biomes(&balance), vec![(Biome::Lake, 5)]);
+    }
+
+    #[test]
+    fn forests_need_grown_trees() {
+        let balance = biome_balance();
+        let mut l = land(3, 1);
+        l.cells[0][0].plant = Some(tree());
+        l.cells[0][1].plant =
+            Some(Item::organism(Species::Tree, LifeStage::Seed, 1.0).r#type);
+        l.cells[0][2].plant = Some(tree());
+        assert!(l.biomes(&balance).is_empty());
+
+        l.cells[0][1].plant = Some(tree());
+        assert_eq!(l.biomes(&balance), vec![(Biome::Forest, 3)]);
+    }
+
+    #[test]
+    fn bigger_biomes_yield_more() {
+        let balance = biome_balance();
+        let mut l = land(4, 2);
+        for x in 0..4 {
+            l.cells[0][x].terrain = water();
+        }
+        l.cells[1][0].plant = Some(tree());
+        l.cells[1][1].plant = Some(tree());
+        let fish = Item::organism(Species::Fish, LifeStage::Adult, 1.0).r#type;
+        let apple = Item::fruit(Species::Apple, 1.0).r#type;
+        assert_eq!(yields(&l, &balance), vec![(fish, 1.0), (apple, 1.0)]);
+
+        for x in 0..4 {
+            l.cells[1][x].terrain = water();
+            l.cells[1][x].plant = None;
+        }
+        // 8 water cells are two lakes' worth
+        assert_eq!(yields(&l, &balance), vec![(fish, 2.0)]);
     }
 
     #[test]
@@ -726,6 +1358,23 @@ mod tests {
         assert!(cell.micro.is_none() && cell.plant.is_none());
     }
 
+    #[test]
+    fn full_layers_take_nothing_more() {
+        let mut l = land(1, 1);
+        let apples = Item::fruit(Species::Apple, 3.0);
+        let archaea = Item::organism(Species::Archaea, LifeStage::Adult, 1.0);
+        assert_eq!(l.accepted_amount(&apples), 1.0);
+        ingest(&mut l, &apples);
+        assert_eq!(l.accepted_amount(&apples), 0.0);
+        // other layers, energy and bulk still go in
+        assert_eq!(l.accepted_amount(&archaea), 1.0);
+        assert_eq!(
+            l.accepted_amount(&Item::energy(EnergyKind::Kinetic, 4.0)),
+            4.0
+        );
+        assert_eq!(l.accepted_amount(&Item::liquid(Substance::Mud, 2.0)), 2.0);
+    }
+
     #[test]
     fn archaea_on_non_water_dies() {
         // Single mud cell with an archaea: it should die (mud is not water).
@@ -736,6 +1385,24 @@ mod tests {
         assert!(l.cells[0][0].micro.is_none());
     }
 
+    // Neighbors are even odds among those on the grid, even at its edges,
+    // and an empty axis doesn't underflow.
+    #[test]
+    fn neighbors_stay_on_the_grid_evenly() {
+        let mut rand = Random::new(5);
+        let mut counts = [0; 2];
+        for _ in 0..2_000 {
+            let v = LandMinigame::random_1d(&mut rand, 0, 5);
+            counts[v] += 1;
+        }
+        assert!(counts.iter().all(|count| (900..1_100).contains(count)));
+        for _ in 0..100 {
+            assert!((3..5).contains(&LandMinigame::random_1d(&mut rand, 4, 5)));
+            assert_eq!(LandMinigame::random_1d(&mut rand, 0, 1), 0);
+            assert_eq!(LandMinigame::random_1d(&mut rand, 0, 0), 0);
+        }
+    }
+
     #[test]
     fn archaea_on_water_spreads_to_empty_water_neighbor() {
         // Two water cells in a row; archaea in the left one. It survives and
@@ -812,7 +1479,8 @@ mod tests {
             .map(|(x, y)| {
                 world
                     .spawn((
-                        Cell { x, y },
+                        Cell,
+                        GridCell { x, y },
                         Sprite::default(),
                         GlobalTransform::default(),
                     ))
@@ -860,6 +1528,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn biomes_yield_through_the_ecs() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        world.insert_resource(Assets::<Image>::default());
+        world.insert_resource(image_gen::GeneratedImageAssets::default());
+        let balance = BalanceConfig::default();
+        let bonus_ticks = balance.land.bonus_ticks;
+        world.insert_resource(balance);
+
+        let mut lm = land(2, 2);
+        for row in lm.cells.iter_mut() {
+            for cell in row.iter_mut() {
+                cell.terrain = water();
+            }
+        }
+        lm.biome_cooldown = bonus_ticks;
+        spawn_land(&mut world, lm, 2, 2);
+
+        let mut items = world.query::<&Item>();
+        for _ in 0..bonus_ticks {
+            world.run_system_once(biome_fixed_update).unwrap();
+        }
+        assert_eq!(items.iter(&world).count(), 0, "not yet");
+        world.run_system_once(biome_fixed_update).unwrap();
+        let yielded: Vec<_> =
+            items.iter(&world).map(|i| (i.r#type, i.amount)).collect();
+        let fish = Item::organism(Species::Fish, LifeStage::Adult, 1.0).r#type;
+        assert_eq!(yielded, vec![(fish, 1.0)]);
+    }
+
     #[test]
     fn click_extracts_top_layer_through_the_ecs() {
         use bevy::ecs::system::RunSystemOnce;
@@ -868,15 +1568,24 @@ mod tests {
         world.insert_resource(Random::new(1));
         world.insert_resource(Assets::<Image>::default());
         world.insert_resource(image_gen::GeneratedImageAssets::default());
-        let mut mouse = MouseState::new(1.0);
-        mouse.just_pressed = true;
-        mouse.current_position = Vec2::ZERO; // over the cell at the origin
-        world.insert_resource(mouse);
+        world.init_resource::<Messages<ClickEvent>>();
+        world.insert_resource(Engaged { game: None });
 
         let mut lm = land(1, 1);
         lm.cells[0][0].other = Some(Item::fruit(Species::Apple, 1.0).r#type);
         let mg = spawn_land(&mut world, lm, 1, 1);
 
+        // a press targeted at the only cell
+        let cell = world
+            .query_filtered::<Entity, With<Cell>>()
+            .single(&world)
+            .unwrap();
+        world.write_message(ClickEvent::targeted(
+            Vec2::ZERO,
+            ClickKind::Press,
+            Some(cell),
+        ));
+
         world.run_system_once(cell_update).unwrap();
 
         {
@@ -895,4 +1604,48 @@ mod tests {
             "extraction should eject one loose item"
         );
     }
+
+    #[test]
+    fn dragging_paints_instead_of_extracting_through_the_ecs() {
+        use bevy::ecs::system::RunSystemOnce;
+
+        let mut world = World::new();
+        world.insert_resource(Assets::<Image>::default());
+        world.insert_resource(image_gen::GeneratedImageAssets::default());
+        world.init_resource::<Messages<ClickEvent>>();
+        world.insert_resource(Engaged { game: Some(ID) });
+
+        let mut lm = land(1, 1);
+        lm.cells[0][0].other = Some(Item::fruit(Species::Apple, 1.0).r#type);
+        add_item(&mut lm.terrain_store, water(), 5.0);
+        lm.brush = Some(water());
+        let mg = spawn_land(&mut world, lm, 1, 1);
+        let cell = world
+            .query_filtered::<Entity, With<Cell>>()
+            .single(&world)
+            .unwrap();
+
+        // the button goes down over the cell
+        let mut mouse_state = MouseState::new(0.5);
+        mouse_state.start_press(0.0);
+        world.insert_resource(mouse_state);
+        world.insert_resource(Hovered { entity: Some(cell) });
+        world.write_message(ClickEvent::targeted(
+            Vec2::ZERO,
+            ClickKind::Press,
+            Some(cell),
+        ));
+        world.run_system_once(cell_update).unwrap();
+        world.run_system_once(paint_cells).unwrap();
+
+        let Some(Minigame::Land(land)) = world.get::<Minigame>(mg) else {
+            panic!("land minigame missing");
+        };
+        assert_eq!(land.cells[0][0].terrain, water());
+        assert_eq!(land.terrain_store.get(&water()), Some(&4.0));
+        assert!(
+            land.cells[0][0].other.is_some(),
+            "painting shouldn't extract"
+        );
+    }
 }
@@ -1,5 +1,4 @@
 use bevy::prelude::*;
-use wyrand::WyRand;
 
 use crate::entities::*;
 use crate::libs::*;
@@ -7,8 +6,8 @@ use crate::libs::*;
 pub const ID: &str = "life";
 pub const POSITION: Vec2 = Vec2::new(-600.0, -600.0);
 
-pub const NAME: &str = "Life";
-pub const DESCRIPTION: &str = "Conway's Game of Life";
+pub const NAME: &str = "life.name";
+pub const DESCRIPTION: &str = "life.description";
 
 const MIN_WIDTH: f32 = 100.0;
 const MIN_HEIGHT: f32 = 100.0;
@@ -26,6 +25,9 @@ const EVOLVE_TICKS: u32 = 20;
 // Empty cells are drawn faintly so the grid is always visible (rather than
 // invisible until life appears). Live cells override this with their texture.
 const EMPTY_CELL_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.35);
+// In high-contrast mode they're solid and dark, so live cells stand out by
+// brightness against the black background.
+const HIGH_CONTRAST_EMPTY_CELL_COLOR: Color = Color::srgb(0.2, 0.2, 0.2);
 
 #[derive(Debug, Clone, Component)]
 pub struct LifeMinigame {
@@ -87,8 +89,11 @@ impl LifeMinigame {
     }
 
     pub fn levelup(&self) -> Self {
-        // Preserve the colony into the (larger) new grid rather than wiping it.
-        let mut next = Self::new(self.xp, self.energy);
+        Self::new(self.xp, self.energy)
+    }
+
+    // Preserve the colony into the (larger) new grid rather than wiping it.
+    pub fn carry_over(&self, next: &mut Self) {
         for (y, row) in self.cells.iter().enumerate() {
             for (x, cell) in row.iter().enumerate() {
                 if y < next.cells.len() && x < next.cells[y].len() {
@@ -96,7 +101,11 @@ impl LifeMinigame {
                 }
             }
         }
-        next
+        next.evolve_cooldown = self.evolve_cooldown;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.xp, self.level)
     }
 
     pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
@@ -104,8 +113,9 @@ impl LifeMinigame {
             (self.area(), self.blocks_per_row(), self.blocks_per_column());
 
         let _background = parent.spawn((
+            GridBackground,
             Sprite {
-                color: Color::srgb(0.5, 0.5, 0.5),
+                color: GRID_BACKGROUND_COLOR,
                 custom_size: Some(area.into()),
                 ..default()
             },
@@ -142,6 +152,18 @@ impl LifeMinigame {
         }
     }
 
+    // How much of `item` `ingest_item` would take: all the energy, and one of
+    // anything else while a cell is free.
+    pub fn accepted_amount(&self, item: &Item) -> f32 {
+        if matches!(item.r#type, ItemType::Energy(_)) {
+            item.amount
+        } else if self.cells.iter().flatten().any(|cell| cell.is_none()) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
     //
     // SPECIFIC
     //
@@ -150,6 +172,12 @@ impl LifeMinigame {
     // XP >= 2^(N-1), so the first level comes from a single death, but high
     // levels demand a large net birth surplus (only achievable later with
     // rule-bending items — see the design note in logs/2026-06-28.md).
+    // XP items add straight to the XP births and deaths earn.
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        Self::level_by_xp(self.xp) > self.level
+    }
+
     pub fn level_by_xp(xp: f32) -> u8 {
         if xp <= 0.0 {
             0
@@ -292,10 +320,9 @@ impl LifeMinigame {
                 })
             })
             .collect();
-        if empty.is_empty() {
+        let Some(&(x, y)) = rand.pick(&empty) else {
             return false;
-        }
-        let (x, y) = empty[(rand.next() as usize) % empty.len()];
+        };
         self.cells[y][x] = Some(Self::life_form());
         true
     }
@@ -309,6 +336,9 @@ impl LifeMinigame {
 pub struct CellBundle {
     pub cell: Cell,
     pub toggleable: Toggleable,
+    pub area: RectangularArea,
+    pub clickable: Clickable,
+    pub hoverable: Hoverable,
     pub sprite: Sprite,
     pub transform: Transform,
 }
@@ -321,6 +351,9 @@ impl CellBundle {
         Self {
             cell: Cell { x, y },
             toggleable: Toggleable::new(),
+            area: CELL_AREA,
+            clickable: Clickable::new(CLICK_PRIORITY_CONTENT),
+            hoverable: Hoverable,
             sprite: Sprite {
                 // Slightly smaller than the cell pitch so the grid reads as
                 // distinct squares; faint so empty cells are always visible.
@@ -350,11 +383,16 @@ impl CellBundle {
     pub fn turn_off(
         entity: Entity,
         query: &mut Query<&mut Sprite, With<Cell>>,
+        accessibility: &Accessibility,
     ) {
         if let Ok(mut sprite) = query.get_mut(entity) {
-            // Back to a faint empty square: drop the texture and the tint.
+            // Back to an empty square: drop the texture and the tint.
             sprite.image = Handle::default();
-            sprite.color = EMPTY_CELL_COLOR;
+            sprite.color = if accessibility.high_contrast {
+                HIGH_CONTRAST_EMPTY_CELL_COLOR
+            } else {
+                EMPTY_CELL_COLOR
+            };
         }
     }
 }
@@ -368,7 +406,8 @@ pub struct Cell {
 // Cell was clicked.
 pub fn cell_update(
     mut commands: Commands,
-    mouse_state: Res<MouseState>,
+    mut click_events: MessageReader<ClickEvent>,
+    accessibility: Res<Accessibility>,
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
     mut minigame_query: Query<(
@@ -377,51 +416,48 @@ pub fn cell_update(
         &RectangularArea,
     )>,
     leveling_up_query: Query<&LevelingUp, With<Minigame>>,
-    cell_query: Query<(&Cell, Entity, &ChildOf, &GlobalTransform)>,
+    cell_query: Query<(&Cell, &ChildOf)>,
     mut cell_draw_query: Query<&mut Sprite, With<Cell>>,
 ) {
-    if !mouse_state.just_pressed {
-        return;
-    }
-
-    let mouse_position = mouse_state.current_position;
-    for (cell, cell_entity, cell_parent, cell_global_transform) in
-        cell_query.iter()
-    {
+    for click in click_events.read() {
+        if !click.is_press() {
+            continue;
+        }
+        let Some(cell_entity) = click.target else {
+            continue;
+        };
+        let Ok((cell, cell_parent)) = cell_query.get(cell_entity) else {
+            continue;
+        };
         let minigame_entity = cell_parent.parent();
         if leveling_up_query.get(minigame_entity).is_ok() {
             continue;
         }
-        if CELL_AREA.is_within(
-            mouse_position,
-            cell_global_transform.translation().truncate(),
-        ) {
-            let Ok((minigame, minigame_transform, minigame_area)) =
-                minigame_query.get_mut(minigame_entity)
-            else {
-                continue;
-            };
-            let Minigame::Life(minigame) = minigame.into_inner() else {
-                continue;
-            };
+        let Ok((minigame, minigame_transform, minigame_area)) =
+            minigame_query.get_mut(minigame_entity)
+        else {
+            continue;
+        };
+        let Minigame::Life(minigame) = minigame.into_inner() else {
+            continue;
+        };
 
-            // Only "on" cells do something when clicked
-            let Some(item_type) = minigame.get_cell(cell.x, cell.y) else {
-                continue;
-            };
+        // Only "on" cells do something when clicked
+        let Some(item_type) = minigame.get_cell(cell.x, cell.y) else {
+            continue;
+        };
 
-            // Clear cell
-            minigame.set_cell(cell.x, cell.y, None);
-            CellBundle::turn_off(cell_entity, &mut cell_draw_query);
-            // Emit item (harvesting is a payout, not XP — XP is births/deaths)
-            commands.spawn(ItemBundle::new_from_minigame(
-                &mut images,
-                &mut generated_image_assets,
-                item_type.to_item(1.0),
-                minigame_transform,
-                minigame_area,
-            ));
-        }
+        // Clear cell
+        minigame.set_cell(cell.x, cell.y, None);
+        CellBundle::turn_off(cell_entity, &mut cell_draw_query, &accessibility);
+        // Emit item (harvesting is a payout, not XP — XP is births/deaths)
+        commands.spawn(ItemBundle::new_from_minigame(
+            &mut images,
+            &mut generated_image_assets,
+            item_type.to_item(1.0),
+            minigame_transform,
+            minigame_area,
+        ));
     }
 }
 
@@ -468,6 +504,7 @@ pub fn evolve_fixed_update(
 // Alive cells show their life form; dead cells are transparent. Cheap: Life
 // grids are small.
 pub fn render_cells(
+    accessibility: Res<Accessibility>,
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
     minigame_query: Query<(Entity, &Minigame)>,
@@ -495,7 +532,11 @@ pub fn render_cells(
                         texture,
                     );
                 }
-                None => CellBundle::turn_off(cell_entity, &mut cell_draw_query),
+                None => CellBundle::turn_off(
+                    cell_entity,
+                    &mut cell_draw_query,
+                    &accessibility,
+                ),
             }
         }
     }
@@ -506,13 +547,7 @@ fn cell_texture(
     images: &mut Assets<Image>,
     generated_image_assets: &mut image_gen::GeneratedImageAssets,
 ) -> Handle<Image> {
-    let uid = item_type.uid();
-    generated_image_assets.get(&uid).unwrap_or_else(|| {
-        let image = item_type.draw(&mut WyRand::new(SEED));
-        let handle = images.add(image);
-        generated_image_assets.insert(uid, &handle);
-        handle
-    })
+    item_type.texture(images, generated_image_assets, CELL_SIZE / 2.0)
 }
 
 #[cfg(test)]
@@ -576,11 +611,13 @@ mod tests {
     fn seed_fills_an_empty_cell() {
         let mut life = life_with(grid(&[], 2, 2));
         let mut rand = Random::new(1);
-        assert_eq!(life.ingest_item(&mut rand, &Item::new_abstract(
-            AbstractKind::Click,
-            0,
-            1.0,
-        )), 1.0);
+        assert_eq!(
+            life.ingest_item(
+                &mut rand,
+                &Item::new_abstract(AbstractKind::Click, 0, 1.0,)
+            ),
+            1.0
+        );
         assert_eq!(alive_coords(&life).len(), 1);
     }
 
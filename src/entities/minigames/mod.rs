@@ -1,10 +1,21 @@
+pub mod aquarium;
 pub mod ball_breaker;
 pub mod battery;
 pub mod button;
 pub mod chest;
+pub mod composter;
+pub mod contracts;
+pub mod flask;
 pub mod foundry;
+pub mod galaxia;
 pub mod land;
 pub mod life;
 pub mod primordial_ocean;
+pub mod quarry;
+pub mod rhythm;
 pub mod rune;
+pub mod slots;
+pub mod solar;
 pub mod tree;
+pub mod turbine;
+pub mod weather_station;
@@ -7,17 +7,33 @@ use crate::libs::*;
 pub const ID: &str = "primordial_ocean";
 pub const POSITION: Vec2 = Vec2::new(250.0, -200.0);
 
-pub const NAME: &str = "Primordial Ocean";
-pub const DESCRIPTION: &str = "Infinitely deep, the source of water and mud.";
+pub const NAME: &str = "primordial_ocean.name";
+pub const DESCRIPTION: &str = "primordial_ocean.description";
 
 const BASE_SIZE: f32 = 60.0;
 const MAX_SIZE_MULTIPLIER: f32 = 2.0;
 
+// Salinity runs from 0 (fresh) to 1 (brine). Fresh water fed in dilutes the
+// ocean and sunlight evaporates it saltier. What clicks bring up follows: the
+// fresher it is, the more fresh water and archaea; the brinier, the more salt.
+const DEFAULT_SALINITY: f32 = 0.5;
+// per unit of fresh water ingested
+const DILUTION_PER_UNIT: f32 = 0.02;
+// per second of noon sun
+const EVAPORATION_PER_SECOND: f32 = 0.002;
+// 1 in this many fresh catches is archaea instead
+const ARCHAEA_ODDS: u64 = 4;
+// How much salt there is in a unit of salt water, for whatever dries it out.
+pub const SALT_PER_SALT_WATER: f32 = 0.25;
+
 #[derive(Debug, Clone, Component)]
 pub struct PrimordialOceanMinigame {
     pub radius: f32,
     pub level: u8,
     pub salt_water_collected: f32,
+    // fed in as items, counts as extra salt water
+    pub xp: f32,
+    pub salinity: f32,
 }
 
 impl Default for PrimordialOceanMinigame {
@@ -26,6 +42,8 @@ impl Default for PrimordialOceanMinigame {
             radius: BASE_SIZE,
             level: 0,
             salt_water_collected: 0.0,
+            xp: 0.0,
+            salinity: DEFAULT_SALINITY,
         }
     }
 }
@@ -39,6 +57,8 @@ impl PrimordialOceanMinigame {
             radius: BASE_SIZE * size_multiplier,
             level,
             salt_water_collected,
+            xp: 0.0,
+            salinity: DEFAULT_SALINITY,
         }
     }
 
@@ -63,19 +83,51 @@ impl PrimordialOceanMinigame {
     }
 
     pub fn levelup(&self) -> Self {
-        Self::new(self.salt_water_collected)
+        Self::new(self.level_basis())
+    }
+
+    pub fn carry_over(&self, next: &mut Self) {
+        next.salt_water_collected = self.salt_water_collected;
+        next.xp = self.xp;
+        next.salinity = self.salinity;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.level_basis(), self.level)
     }
 
     pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
-        parent.spawn(OceanBundle::new(parent.target_entity(), self.radius));
+        let minigame = parent.target_entity();
+        parent.spawn(OceanBundle::new(minigame, self.radius));
+        // over the header, which the name fills
+        parent.spawn((
+            SalinityText { minigame },
+            Text2d::new(self.salinity_label()),
+            TextFont {
+                font_size: 10.0,
+                ..default()
+            },
+            TextColor(Color::BLACK),
+            Transform::from_xyz(
+                0.0,
+                self.area().top() + META_HEIGHT + 6.0,
+                1.0,
+            ),
+        ));
     }
 
+    // Salt water fills the ocean toward its next level; fresh water only
+    // dilutes it.
     pub fn ingest_item(
         &mut self,
         commands: &mut Commands,
         minigame_entity: Entity,
         item: &Item,
     ) -> f32 {
+        if Self::is_fresh_water(item) {
+            self.dilute(item.amount);
+            return item.amount;
+        }
         if !Self::item_is_valid(item) {
             return 0.0;
         }
@@ -89,6 +141,15 @@ impl PrimordialOceanMinigame {
         item.amount
     }
 
+    // How much of `item` `ingest_item` would take.
+    pub fn accepted_amount(item: &Item) -> f32 {
+        if Self::is_fresh_water(item) || Self::item_is_valid(item) {
+            item.amount
+        } else {
+            0.0
+        }
+    }
+
     //
     // SPECIFIC
     //
@@ -101,15 +162,24 @@ impl PrimordialOceanMinigame {
         }
     }
 
+    // Salt water plus XP: what the level is computed from.
+    pub fn level_basis(&self) -> f32 {
+        self.salt_water_collected + self.xp
+    }
+
     pub fn should_level_up(&self) -> bool {
         if self.level == 99 {
             false
         } else {
-            Self::level_by_salt_water_collected(self.salt_water_collected)
-                > self.level
+            Self::level_by_salt_water_collected(self.level_basis()) > self.level
         }
     }
 
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.should_level_up()
+    }
+
     pub fn item_is_valid(item: &Item) -> bool {
         let ItemType::Physical(PhysicalItem::Bulk(bulk)) = item.r#type else {
             return false;
@@ -117,12 +187,66 @@ impl PrimordialOceanMinigame {
 
         bulk.substance == Substance::SaltWater
     }
+
+    fn is_fresh_water(item: &Item) -> bool {
+        matches!(
+            item.r#type,
+            ItemType::Physical(PhysicalItem::Bulk(bulk))
+                if bulk.substance == Substance::FreshWater
+        )
+    }
+
+    pub fn dilute(&mut self, fresh_water: f32) {
+        self.salinity =
+            (self.salinity - fresh_water * DILUTION_PER_UNIT).max(0.0);
+    }
+
+    pub fn evaporate(&mut self, seconds_of_noon_sun: f32) {
+        self.salinity = (self.salinity
+            + seconds_of_noon_sun * EVAPORATION_PER_SECOND)
+            .min(1.0);
+    }
+
+    // What a short click brings up, `amount` of it. Below the default
+    // salinity, fresh water is likelier the fresher the ocean; above it, salt
+    // is, up to half the catches at full brine. Salt water otherwise.
+    pub fn catch(&self, random: &mut Random, amount: f32) -> Item {
+        let roll = random.next_f32();
+        let freshness = (1.0 - self.salinity / DEFAULT_SALINITY).max(0.0);
+        let brininess = ((self.salinity - DEFAULT_SALINITY)
+            / (1.0 - DEFAULT_SALINITY))
+            .max(0.0);
+        if roll < freshness {
+            if random.next().is_multiple_of(ARCHAEA_ODDS) {
+                Item::organism(Species::Archaea, LifeStage::Adult, 1.0)
+            } else {
+                Item::liquid(Substance::FreshWater, amount)
+            }
+        } else if roll < brininess / 2.0 {
+            Item::powder(Substance::Salt, amount)
+        } else {
+            Item::liquid(Substance::SaltWater, amount)
+        }
+    }
+
+    fn salinity_percent(&self) -> u32 {
+        (self.salinity * 100.0).round() as u32
+    }
+
+    fn salinity_label(&self) -> String {
+        tr_fmt(
+            "primordial_ocean.salinity",
+            &[("percent", &self.salinity_percent())],
+        )
+    }
 }
 
 #[derive(Bundle)]
 pub struct OceanBundle {
     pub ocean: Ocean,
     pub area: CircularArea,
+    pub clickable: Clickable,
+    pub hoverable: Hoverable,
     pub shape: bevy_prototype_lyon::prelude::Shape,
 }
 
@@ -133,6 +257,8 @@ impl OceanBundle {
         Self {
             ocean: Ocean { minigame },
             area,
+            clickable: Clickable::new(CLICK_PRIORITY_CONTENT),
+            hoverable: Hoverable,
             shape: ShapeBuilder::with(&shapes::Circle {
                 radius,
                 ..default()
@@ -148,21 +274,29 @@ pub struct Ocean {
     pub minigame: Entity,
 }
 
+#[derive(Debug, Component)]
+pub struct SalinityText {
+    pub minigame: Entity,
+}
+
 pub fn update(
     mut commands: Commands,
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
-    mouse_state: Res<MouseState>,
-    minigame_query: Query<(&GlobalTransform, &RectangularArea), With<Minigame>>,
-    mut ocean_query: Query<(&Ocean, &GlobalTransform, &CircularArea)>,
+    mut random: ResMut<Random>,
+    mut click_events: MessageReader<ClickEvent>,
+    clock: Res<WorldClock>,
+    minigame_query: Query<(&Minigame, &GlobalTransform, &RectangularArea)>,
+    ocean_query: Query<&Ocean>,
     leveling_up_query: Query<&LevelingUp, With<Minigame>>,
 ) {
-    if !mouse_state.just_released {
-        return;
-    }
-    let click_position = mouse_state.current_position;
-
-    for (ocean, ocean_transform, ocean_area) in ocean_query.iter_mut() {
+    for click in click_events.read() {
+        if click.kind == ClickKind::Press {
+            continue;
+        }
+        let Some(Ok(ocean)) = click.target.map(|e| ocean_query.get(e)) else {
+            continue;
+        };
         let minigame_entity = ocean.minigame;
 
         // Skip if currently leveling up
@@ -170,31 +304,115 @@ pub fn update(
             continue;
         }
 
-        if ocean_area
-            .is_within(click_position, ocean_transform.translation().truncate())
-        {
-            let (minigame_transform, minigame_area) =
-                minigame_query.get(minigame_entity).unwrap();
-            let click_type = mouse_state.get_click_type();
-            let item = match click_type {
-                ClickType::Short => Item::liquid(Substance::SaltWater, 1.0),
-                ClickType::Long => Item::solid(
-                    Substance::Mud,
-                    crate::entities::item::BulkShape::Lump,
-                    1.0,
-                ),
-                ClickType::Invalid => {
-                    println!("unexpected: invalid click type");
-                    continue;
-                }
-            };
-            commands.spawn(ItemBundle::new_from_minigame(
-                &mut images,
-                &mut generated_image_assets,
-                item,
-                minigame_transform,
-                minigame_area,
-            ));
+        let (minigame, minigame_transform, minigame_area) =
+            minigame_query.get(minigame_entity).unwrap();
+        let Minigame::PrimordialOcean(ocean) = minigame else {
+            continue;
+        };
+        let item = match click.kind {
+            // the high tide at night brings in more
+            ClickKind::Short => ocean.catch(&mut random, clock.tide_yield()),
+            _ => Item::solid(
+                Substance::Mud,
+                crate::entities::item::BulkShape::Lump,
+                1.0,
+            ),
+        };
+        commands.spawn(ItemBundle::new_from_minigame(
+            &mut images,
+            &mut generated_image_assets,
+            item,
+            minigame_transform,
+            minigame_area,
+        ));
+    }
+}
+
+// Sunlight evaporates the ocean saltier. The label shows whole percents, so
+// the minigame only counts as changed when one goes by.
+pub fn evaporate_fixed_update(
+    time: Res<Time>,
+    clock: Res<WorldClock>,
+    mut minigame_query: Query<&mut Minigame>,
+) {
+    let seconds_of_noon_sun = clock.sunlight() * time.delta_secs();
+    if seconds_of_noon_sun == 0.0 {
+        return;
+    }
+    for mut minigame in minigame_query.iter_mut() {
+        let Minigame::PrimordialOcean(ocean) =
+            minigame.bypass_change_detection()
+        else {
+            continue;
+        };
+        let before = ocean.salinity_percent();
+        ocean.evaporate(seconds_of_noon_sun);
+        if ocean.salinity_percent() != before {
+            minigame.set_changed();
         }
     }
 }
+
+pub fn update_salinity_text(
+    minigame_query: Query<&Minigame, Changed<Minigame>>,
+    mut text_query: Query<(&SalinityText, &mut Text2d)>,
+) {
+    for (salinity_text, mut text) in text_query.iter_mut() {
+        let Ok(Minigame::PrimordialOcean(ocean)) =
+            minigame_query.get(salinity_text.minigame)
+        else {
+            continue;
+        };
+        text.0 = ocean.salinity_label();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catches(ocean: &PrimordialOceanMinigame) -> Vec<Item> {
+        let mut random = Random::new(5);
+        (0..1000).map(|_| ocean.catch(&mut random, 1.0)).collect()
+    }
+
+    fn count(catches: &[Item], item: Item) -> usize {
+        catches.iter().filter(|c| c.r#type == item.r#type).count()
+    }
+
+    #[test]
+    fn fresh_water_dilutes_and_sun_evaporates() {
+        let mut ocean = PrimordialOceanMinigame::default();
+        ocean.dilute(5.0);
+        assert!((ocean.salinity - 0.4).abs() < 1e-6);
+        ocean.dilute(100.0);
+        assert_eq!(ocean.salinity, 0.0);
+
+        ocean.evaporate(100.0);
+        assert!((ocean.salinity - 0.2).abs() < 1e-6);
+        ocean.evaporate(1000.0);
+        assert_eq!(ocean.salinity, 1.0);
+    }
+
+    #[test]
+    fn salinity_shifts_the_catch() {
+        let salt_water = Item::liquid(Substance::SaltWater, 1.0);
+        let fresh_water = Item::liquid(Substance::FreshWater, 1.0);
+        let archaea = Item::organism(Species::Archaea, LifeStage::Adult, 1.0);
+        let salt = Item::powder(Substance::Salt, 1.0);
+
+        let mut ocean = PrimordialOceanMinigame::default();
+        assert_eq!(count(&catches(&ocean), salt_water), 1000);
+
+        ocean.salinity = 0.0;
+        let fresh = catches(&ocean);
+        assert_eq!(count(&fresh, salt_water), 0);
+        assert!(count(&fresh, fresh_water) > count(&fresh, archaea));
+        assert!(count(&fresh, archaea) > 100);
+
+        ocean.salinity = 1.0;
+        let brine = catches(&ocean);
+        assert!((400..600).contains(&count(&brine, salt)));
+        assert_eq!(count(&brine, fresh_water), 0);
+    }
+}
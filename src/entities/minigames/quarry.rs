@@ -0,0 +1,330 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// A pit that digs itself when fed Kinetic or Thermal energy, slowly turning
+// up ore. Every layer down is harder going, so each unit of energy yields
+// less; levelling up opens a fresh, shallow pit with richer ores in it.
+
+pub const ID: &str = "quarry";
+pub const POSITION: Vec2 = Vec2::new(650.0, 150.0);
+
+pub const NAME: &str = "quarry.name";
+pub const DESCRIPTION: &str = "quarry.description";
+const AREA: RectangularArea = RectangularArea {
+    width: 140.0,
+    height: 160.0,
+};
+
+// Energy burned per second while there's any stored.
+const DIG_RATE: f32 = 0.5;
+// Layers dug per unit of energy burned.
+const LAYERS_PER_ENERGY: f32 = 0.25;
+// Digging progress one ore takes.
+const ORE_COST: f32 = 1.0;
+// The depth meter is full at this many layers.
+const METER_LAYERS: f32 = 20.0;
+const METER_WIDTH: f32 = 12.0;
+
+#[derive(Debug, Clone, Default, Component)]
+pub struct QuarryMinigame {
+    pub level: u8,
+    // ingested energy waiting to be burned
+    pub energy: f32,
+    // layers dug in this pit; fractional while partway through one
+    pub depth: f32,
+    // toward the next ore
+    pub dig_progress: f32,
+    // ore dug up so far, over every pit
+    pub mined: f32,
+    // fed in as items, counts as extra ore mined
+    pub xp: f32,
+}
+
+impl QuarryMinigame {
+    pub fn new(mined: f32) -> Self {
+        Self {
+            level: Self::level_by_mined(mined),
+            mined,
+            ..default()
+        }
+    }
+
+    //
+    // COMMON
+    //
+
+    pub fn name(&self) -> &str {
+        NAME
+    }
+
+    pub fn description(&self) -> &str {
+        DESCRIPTION
+    }
+
+    pub fn area(&self) -> RectangularArea {
+        AREA
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn levelup(&self) -> Self {
+        Self::new(self.level_basis())
+    }
+
+    // A new pit starts at the surface; the fuel and the tally come along.
+    pub fn carry_over(&self, next: &mut Self) {
+        next.energy = self.energy;
+        next.mined = self.mined;
+        next.xp = self.xp;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.level_basis(), self.level)
+    }
+
+    pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
+        let minigame = parent.target_entity();
+        parent.spawn((
+            Sprite {
+                color: Color::srgb(0.55, 0.45, 0.35),
+                custom_size: Some(AREA.into()),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, -1.0),
+        ));
+        // the meter's well, then the fill that grows down it
+        let meter_x = AREA.width / 2.0 - METER_WIDTH;
+        parent.spawn((
+            Sprite::from_color(
+                Color::srgb(0.3, 0.25, 0.2),
+                Vec2::new(METER_WIDTH, AREA.height - 10.0),
+            ),
+            Transform::from_xyz(meter_x, 0.0, 0.0),
+        ));
+        let (size, y) = depth_meter_fill(self.depth);
+        parent.spawn((
+            DepthMeter { minigame },
+            Sprite::from_color(Color::srgb(0.1, 0.1, 0.1), size),
+            Transform::from_xyz(meter_x, y, 0.1),
+        ));
+        parent.spawn((
+            DepthText { minigame },
+            Text2d::new(Self::depth_label(self.depth)),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Transform::from_xyz(-METER_WIDTH, 0.0, 0.1),
+        ));
+    }
+
+    pub fn ingest_item(&mut self, item: &Item) -> f32 {
+        let taken = Self::accepted_amount(item);
+        self.energy += taken;
+        taken
+    }
+
+    // How much of `item` `ingest_item` would take.
+    pub fn accepted_amount(item: &Item) -> f32 {
+        match item.r#type {
+            ItemType::Energy(energy)
+                if matches!(
+                    energy.kind,
+                    EnergyKind::Kinetic | EnergyKind::Thermal
+                ) =>
+            {
+                item.amount
+            }
+            _ => 0.0,
+        }
+    }
+
+    //
+    // SPECIFIC
+    //
+
+    // Ore mined plus XP: what the level is computed from.
+    pub fn level_basis(&self) -> f32 {
+        self.mined + self.xp
+    }
+
+    pub fn should_level_up(&self) -> bool {
+        Self::level_by_mined(self.level_basis()) > self.level
+    }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.should_level_up()
+    }
+
+    pub fn level_by_mined(mined: f32) -> u8 {
+        if mined <= 0.0 {
+            0
+        } else {
+            ((mined.log2() + 1.0) as u8).min(99)
+        }
+    }
+
+    pub fn layer(&self) -> u32 {
+        self.depth as u32
+    }
+
+    // Digging progress per unit of energy in the current layer.
+    pub fn dig_yield(&self) -> f32 {
+        1.0 / (1.0 + 0.25 * self.layer() as f32)
+    }
+
+    // Burn up to `seconds` worth of energy. Returns how many ores came up.
+    pub fn dig(&mut self, seconds: f32) -> u32 {
+        let burned = self.energy.min(DIG_RATE * seconds);
+        if burned <= 0.0 {
+            return 0;
+        }
+        self.energy -= burned;
+        self.dig_progress += burned * self.dig_yield();
+        self.depth += burned * LAYERS_PER_ENERGY;
+        let ores = (self.dig_progress / ORE_COST) as u32;
+        self.dig_progress -= ores as f32 * ORE_COST;
+        self.mined += ores as f32;
+        ores
+    }
+
+    // What the pit can turn up at this level.
+    pub fn ores(level: u8) -> Vec<Substance> {
+        let mut ores = vec![Substance::Copper, Substance::Tin, Substance::Iron];
+        if level >= 2 {
+            ores.push(Substance::Silver);
+        }
+        if level >= 4 {
+            ores.push(Substance::Gold);
+        }
+        if level >= 6 {
+            ores.push(Substance::Diamond);
+        }
+        ores
+    }
+
+    pub fn random_ore(level: u8, random: &mut Random) -> Substance {
+        let ores = Self::ores(level);
+        *random.pick(&ores).unwrap()
+    }
+
+    pub fn depth_label(depth: f32) -> String {
+        tr_fmt("quarry.layer", &[("layer", &(depth as u32))])
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct DepthMeter {
+    pub minigame: Entity,
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct DepthText {
+    pub minigame: Entity,
+}
+
+// Size and y of the meter's fill: it hangs from the top of its well.
+fn depth_meter_fill(depth: f32) -> (Vec2, f32) {
+    let well = AREA.height - 10.0;
+    let height = well * (depth / METER_LAYERS).min(1.0);
+    (Vec2::new(METER_WIDTH, height), well / 2.0 - height / 2.0)
+}
+
+pub fn dig_fixed_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut random: ResMut<Random>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut batches: ResMut<batching::IdleBatches>,
+    mut minigame_query: Query<
+        (Entity, &mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+) {
+    for (entity, minigame, global_transform, area) in minigame_query.iter_mut()
+    {
+        // only touch the component when there's digging to do, so the meter
+        // doesn't redraw every tick
+        let Minigame::Quarry(quarry) = minigame.as_ref() else {
+            continue;
+        };
+        if quarry.energy <= 0.0 {
+            continue;
+        }
+        let Minigame::Quarry(quarry) = minigame.into_inner() else {
+            continue;
+        };
+        for _ in 0..quarry.dig(time.delta_secs()) {
+            let substance =
+                QuarryMinigame::random_ore(quarry.level, &mut random);
+            let Some(ore) = batches.batch(ID, Item::ore(substance, 1.0)) else {
+                continue;
+            };
+            commands.spawn(ItemBundle::new_from_minigame(
+                &mut images,
+                &mut generated_image_assets,
+                ore,
+                global_transform,
+                area,
+            ));
+        }
+        if quarry.should_level_up() {
+            commands.entity(entity).insert(LevelingUp);
+        }
+    }
+}
+
+pub fn update_depth_meter(
+    minigame_query: Query<&Minigame, Changed<Minigame>>,
+    mut meter_query: Query<(&DepthMeter, &mut Sprite, &mut Transform)>,
+    mut text_query: Query<(&DepthText, &mut Text2d)>,
+) {
+    for (meter, mut sprite, mut transform) in meter_query.iter_mut() {
+        let Ok(Minigame::Quarry(quarry)) = minigame_query.get(meter.minigame)
+        else {
+            continue;
+        };
+        let (size, y) = depth_meter_fill(quarry.depth);
+        sprite.custom_size = Some(size);
+        transform.translation.y = y;
+    }
+    for (depth_text, mut text) in text_query.iter_mut() {
+        let Ok(Minigame::Quarry(quarry)) =
+            minigame_query.get(depth_text.minigame)
+        else {
+            continue;
+        };
+        text.0 = QuarryMinigame::depth_label(quarry.depth);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deeper_layers_yield_less() {
+        let mut quarry = QuarryMinigame {
+            energy: 100.0,
+            ..default()
+        };
+        let surface = quarry.dig_yield();
+        quarry.dig(40.0);
+        assert!(quarry.layer() > 0);
+        assert!(quarry.dig_yield() < surface);
+        assert_eq!(quarry.energy, 80.0);
+    }
+
+    #[test]
+    fn richer_ores_come_with_level() {
+        assert!(!QuarryMinigame::ores(0).contains(&Substance::Gold));
+        assert!(QuarryMinigame::ores(6).contains(&Substance::Diamond));
+    }
+}
@@ -0,0 +1,444 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// A rhythm game. Notes scroll in from the right toward a line on the left, and
+// the player clicks the lane as each one crosses it. Every few hits in a row
+// give off mana of the song's element; a miss, or a click with no note there,
+// breaks the streak. Each level unlocks another song, and the songs take turns.
+
+pub const ID: &str = "rhythm";
+pub const POSITION: Vec2 = Vec2::new(-650.0, -200.0);
+
+pub const NAME: &str = "rhythm.name";
+pub const DESCRIPTION: &str = "rhythm.description";
+const AREA: RectangularArea = RectangularArea {
+    width: 200.0,
+    height: 80.0,
+};
+
+const BEAT_SECONDS: f32 = 0.5;
+// How early or late a click can be and still hit.
+const HIT_WINDOW: f32 = 0.15;
+// Hits in a row per mana.
+const STREAK_FOR_MANA: u32 = 4;
+// Silence after a song's last note before the next one starts, in beats.
+const SONG_GAP_BEATS: f32 = 4.0;
+const HIT_X: f32 = -AREA.width / 2.0 + 20.0;
+// Pixels per second the notes travel.
+const SCROLL_SPEED: f32 = 80.0;
+const LANE_HEIGHT: f32 = 40.0;
+const NOTE_RADIUS: f32 = 8.0;
+// Note sprites reused for whatever notes are on screen.
+const NOTE_POOL: usize = 8;
+
+pub struct Song {
+    // a locale key
+    pub name: &'static str,
+    pub kind: ManaKind,
+    pub intent: ManaIntent,
+    // when each note lands, in beats
+    pub notes: &'static [f32],
+}
+
+pub static SONGS: [Song; 6] = [
+    Song {
+        name: "rhythm.song.ember_march",
+        kind: ManaKind::Fire,
+        intent: ManaIntent::Attack,
+        notes: &[0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0],
+    },
+    Song {
+        name: "rhythm.song.rain_waltz",
+        kind: ManaKind::Water,
+        intent: ManaIntent::Support,
+        notes: &[0.0, 1.0, 1.5, 3.0, 4.0, 4.5, 6.0, 7.0, 7.5],
+    },
+    Song {
+        name: "rhythm.song.stone_drum",
+        kind: ManaKind::Earth,
+        intent: ManaIntent::Defense,
+        notes: &[0.0, 2.0, 3.0, 4.0, 6.0, 7.0, 8.0, 10.0],
+    },
+    Song {
+        name: "rhythm.song.gale_reel",
+        kind: ManaKind::Air,
+        intent: ManaIntent::Support,
+        notes: &[0.0, 0.5, 1.0, 1.5, 2.0, 3.0, 3.5, 4.0, 5.0, 5.5],
+    },
+    Song {
+        name: "rhythm.song.dawn_hymn",
+        kind: ManaKind::Light,
+        intent: ManaIntent::Defense,
+        notes: &[0.0, 2.0, 4.0, 6.0, 7.0, 8.0, 10.0, 12.0],
+    },
+    Song {
+        name: "rhythm.song.night_pulse",
+        kind: ManaKind::Dark,
+        intent: ManaIntent::Attack,
+        notes: &[0.0, 0.5, 1.5, 2.0, 3.0, 3.5, 4.5, 5.0, 6.0],
+    },
+];
+
+#[derive(Debug, Clone, Default, Component)]
+pub struct RhythmMinigame {
+    pub level: u8,
+    // index into `SONGS`
+    pub song: usize,
+    // seconds into the song
+    pub time: f32,
+    // the first note not yet hit or missed
+    pub next_note: usize,
+    pub streak: u32,
+    // notes hit so far
+    pub hits: f32,
+    // fed in as items, counts as extra notes hit
+    pub xp: f32,
+}
+
+impl RhythmMinigame {
+    pub fn new(hits: f32) -> Self {
+        Self {
+            level: Self::level_by_hits(hits),
+            hits,
+            ..default()
+        }
+    }
+
+    //
+    // COMMON
+    //
+
+    pub fn name(&self) -> &str {
+        NAME
+    }
+
+    pub fn description(&self) -> &str {
+        DESCRIPTION
+    }
+
+    pub fn area(&self) -> RectangularArea {
+        AREA
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn levelup(&self) -> Self {
+        Self::new(self.level_basis())
+    }
+
+    // The song plays on through a levelup.
+    pub fn carry_over(&self, next: &mut Self) {
+        next.song = self.song;
+        next.time = self.time;
+        next.next_note = self.next_note;
+        next.streak = self.streak;
+        next.hits = self.hits;
+        next.xp = self.xp;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.level_basis(), self.level)
+    }
+
+    pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
+        let minigame = parent.target_entity();
+        parent.spawn((
+            Sprite {
+                color: Color::srgb(0.15, 0.1, 0.25),
+                custom_size: Some(AREA.into()),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, -1.0),
+        ));
+        let lane = RectangularArea {
+            width: AREA.width,
+            height: LANE_HEIGHT,
+        };
+        parent.spawn((
+            Lane { minigame },
+            lane,
+            Clickable::new(CLICK_PRIORITY_CONTENT),
+            Hoverable,
+            Sprite::from_color(Color::srgb(0.25, 0.2, 0.4), lane.into()),
+            Transform::from_xyz(0.0, -10.0, 0.0),
+        ));
+        parent.spawn((
+            Sprite::from_color(
+                Color::srgb(0.9, 0.9, 0.9),
+                Vec2::new(3.0, LANE_HEIGHT),
+            ),
+            Transform::from_xyz(HIT_X, -10.0, 0.1),
+        ));
+        for slot in 0..NOTE_POOL {
+            parent.spawn((
+                NoteSprite { minigame, slot },
+                ShapeBuilder::with(&shapes::Circle {
+                    radius: NOTE_RADIUS,
+                    ..default()
+                })
+                .fill(Fill::color(Color::srgb(1.0, 0.8, 0.3)))
+                .build(),
+                Transform::from_xyz(HIT_X, -10.0, 0.2),
+                Visibility::Hidden,
+            ));
+        }
+        parent.spawn((
+            SongText { minigame },
+            Text2d::new(tr(self.current_song().name)),
+            TextFont {
+                font_size: 12.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Transform::from_xyz(0.0, AREA.height / 2.0 - 12.0, 0.1),
+        ));
+    }
+
+    pub fn ingest_item(&self) -> f32 {
+        0.0 // nothing to feed it but XP
+    }
+
+    //
+    // SPECIFIC
+    //
+
+    // Notes hit plus XP: what the level is computed from.
+    pub fn level_basis(&self) -> f32 {
+        self.hits + self.xp
+    }
+
+    pub fn should_level_up(&self) -> bool {
+        Self::level_by_hits(self.level_basis()) > self.level
+    }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.should_level_up()
+    }
+
+    pub fn level_by_hits(hits: f32) -> u8 {
+        if hits <= 0.0 {
+            0
+        } else {
+            ((hits.log2() + 1.0) as u8).min(99)
+        }
+    }
+
+    // One song to start, and another with every level.
+    pub fn songs_unlocked(level: u8) -> usize {
+        (level as usize + 1).min(SONGS.len())
+    }
+
+    pub fn current_song(&self) -> &'static Song {
+        &SONGS[self.song]
+    }
+
+    pub fn note_seconds(&self, index: usize) -> Option<f32> {
+        let beat = self.current_song().notes.get(index)?;
+        Some(beat * BEAT_SECONDS)
+    }
+
+    fn song_seconds(&self) -> f32 {
+        let notes = self.current_song().notes;
+        let last = notes.last().copied().unwrap_or(0.0);
+        (last + SONG_GAP_BEATS) * BEAT_SECONDS
+    }
+
+    // Play on for `seconds`. Notes that slide past the window are misses, and
+    // once the song is over the next unlocked one starts.
+    pub fn advance(&mut self, seconds: f32) {
+        self.time += seconds;
+        while let Some(at) = self.note_seconds(self.next_note) {
+            if at + HIT_WINDOW >= self.time {
+                break;
+            }
+            self.next_note += 1;
+            self.streak = 0;
+        }
+        if self.time >= self.song_seconds() {
+            self.song = (self.song + 1) % Self::songs_unlocked(self.level);
+            self.time = 0.0;
+            self.next_note = 0;
+        }
+    }
+
+    // A click on the lane. Returns mana when it completes a streak.
+    pub fn strike(&mut self) -> Option<Item> {
+        let on_time = self
+            .note_seconds(self.next_note)
+            .is_some_and(|at| (at - self.time).abs() <= HIT_WINDOW);
+        if !on_time {
+            self.streak = 0;
+            return None;
+        }
+        self.next_note += 1;
+        self.streak += 1;
+        self.hits += 1.0;
+        if !self.streak.is_multiple_of(STREAK_FOR_MANA) {
+            return None;
+        }
+        let song = self.current_song();
+        Some(Item::mana(song.kind, song.intent, 1.0))
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct Lane {
+    pub minigame: Entity,
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct NoteSprite {
+    pub minigame: Entity,
+    // which upcoming note it shows: 0 is the next one
+    pub slot: usize,
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct SongText {
+    pub minigame: Entity,
+}
+
+pub fn fixed_update(
+    time: Res<Time>,
+    mut minigame_query: Query<&mut Minigame, Without<LevelingUp>>,
+) {
+    for minigame in minigame_query.iter_mut() {
+        let Minigame::Rhythm(rhythm) = minigame.into_inner() else {
+            continue;
+        };
+        rhythm.advance(time.delta_secs());
+    }
+}
+
+pub fn strike_update(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut click_events: MessageReader<ClickEvent>,
+    lane_query: Query<&Lane>,
+    mut minigame_query: Query<
+        (&mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+) {
+    for click in click_events.read() {
+        // the press is the beat; waiting for the release would be late
+        if !click.is_press() {
+            continue;
+        }
+        let Some(Ok(lane)) = click.target.map(|e| lane_query.get(e)) else {
+            continue;
+        };
+        let Ok((minigame, global_transform, area)) =
+            minigame_query.get_mut(lane.minigame)
+        else {
+            continue;
+        };
+        let Minigame::Rhythm(rhythm) = minigame.into_inner() else {
+            continue;
+        };
+        if let Some(mana) = rhythm.strike() {
+            commands.spawn(ItemBundle::new_from_minigame(
+                &mut images,
+                &mut generated_image_assets,
+                mana,
+                global_transform,
+                area,
+            ));
+        }
+        if rhythm.should_level_up() {
+            commands.entity(lane.minigame).insert(LevelingUp);
+        }
+    }
+}
+
+// Slide the pooled note sprites to where the upcoming notes are.
+pub fn update_notes(
+    minigame_query: Query<&Minigame>,
+    mut note_query: Query<(&NoteSprite, &mut Transform, &mut Visibility)>,
+    mut text_query: Query<(&SongText, &mut Text2d)>,
+) {
+    for (note, mut transform, mut visibility) in note_query.iter_mut() {
+        let Ok(Minigame::Rhythm(rhythm)) = minigame_query.get(note.minigame)
+        else {
+            continue;
+        };
+        let x = rhythm
+            .note_seconds(rhythm.next_note + note.slot)
+            .map(|at| HIT_X + (at - rhythm.time) * SCROLL_SPEED)
+            .filter(|x| *x <= AREA.width / 2.0 - NOTE_RADIUS);
+        match x {
+            Some(x) => {
+                transform.translation.x = x;
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+    for (song_text, mut text) in text_query.iter_mut() {
+        let Ok(Minigame::Rhythm(rhythm)) =
+            minigame_query.get(song_text.minigame)
+        else {
+            continue;
+        };
+        let name = tr(rhythm.current_song().name);
+        if text.0 != name {
+            text.0 = name;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Play up to the next note and click right on it.
+    fn hit_next(rhythm: &mut RhythmMinigame) -> Option<Item> {
+        let at = rhythm.note_seconds(rhythm.next_note).unwrap();
+        rhythm.advance(at - rhythm.time);
+        rhythm.strike()
+    }
+
+    #[test]
+    fn a_streak_in_time_makes_mana() {
+        let mut rhythm = RhythmMinigame::default();
+        for _ in 1..STREAK_FOR_MANA {
+            assert!(hit_next(&mut rhythm).is_none());
+        }
+        let mana = hit_next(&mut rhythm).unwrap();
+        let fire = Item::mana(ManaKind::Fire, ManaIntent::Attack, 1.0);
+        assert_eq!(mana.r#type, fire.r#type);
+        assert_eq!(rhythm.hits, STREAK_FOR_MANA as f32);
+    }
+
+    #[test]
+    fn missing_breaks_the_streak() {
+        let mut rhythm = RhythmMinigame::default();
+        hit_next(&mut rhythm);
+        // a click halfway between beats
+        rhythm.advance(BEAT_SECONDS / 2.0);
+        assert!(rhythm.strike().is_none());
+        assert_eq!(rhythm.streak, 0);
+        // and a note let slide by
+        hit_next(&mut rhythm);
+        rhythm.advance(BEAT_SECONDS * 2.0);
+        assert_eq!(rhythm.streak, 0);
+    }
+
+    #[test]
+    fn levels_unlock_songs() {
+        let mut rhythm = RhythmMinigame::default();
+        rhythm.advance(rhythm.song_seconds());
+        assert_eq!(rhythm.song, 0);
+        rhythm.level = 1;
+        rhythm.advance(rhythm.song_seconds());
+        assert_eq!(rhythm.song, 1);
+        assert_eq!(rhythm.current_song().kind, ManaKind::Water);
+    }
+}
@@ -1,19 +1,14 @@
 use bevy::prelude::*;
-use bevy_prototype_lyon::prelude::*;
 
 use crate::entities::*;
 use crate::item::rune::*;
 use crate::libs::*;
 
-// `crate::entities::*` now exports an item `Shape` enum; disambiguate the
-// bevy_prototype_lyon `Shape` component used throughout this file in its favor.
-use bevy_prototype_lyon::prelude::Shape;
-
 pub const ID: &str = "rune";
 pub const POSITION: Vec2 = Vec2::new(-250.0, -200.0);
 
-pub const NAME: &str = "rune";
-pub const DESCRIPTION: &str = "Draw runes!";
+pub const NAME: &str = "rune.name";
+pub const DESCRIPTION: &str = "rune.description";
 
 const MIN_WIDTH: f32 = 100.0;
 const MIN_HEIGHT: f32 = 100.0;
@@ -24,6 +19,14 @@ const PIXEL_AREA: RectangularArea = RectangularArea {
 };
 const PIXEL_ON_COLOR: Color = Color::srgb(0.0, 0.0, 0.0);
 const PIXEL_OFF_COLOR: Color = Color::srgb(1.0, 1.0, 1.0);
+// The hint button sits in the top-left corner, in a strip above the grid.
+const HINT_BUTTON_SIZE: f32 = 16.0;
+const HINT_BUTTON_COLOR: Color = Color::srgb(0.95, 0.85, 0.3);
+const STENCIL_COLOR: Color = Color::srgba(0.2, 0.4, 1.0, 0.3);
+// How many strokes Ctrl+Z can take back.
+const UNDO_STROKES: usize = 20;
+// How long the grid takes to grow into its new size after a levelup.
+const GROW_SECONDS: f32 = 0.4;
 
 #[derive(Debug, Clone, Component)]
 pub struct RuneMinigame {
@@ -31,6 +34,15 @@ pub struct RuneMinigame {
     pub highest_level_rune: Option<Rune>,
     pub pixels: Vec<Vec<bool>>,
     pub erasing: bool,
+    // the rune a hint was bought for; it's only good until that rune is drawn
+    pub hint: Option<Rune>,
+    pub show_hint: bool,
+    // Pixels each stroke changed, oldest stroke first, with what they were
+    // before. A stroke runs from a press to its release.
+    pub strokes: Vec<Vec<(u8, u8, bool)>>,
+    // the grid size (columns, rows) it levelled up from, which its cells
+    // grow out of when it's spawned; not saved
+    pub grown_from: Option<(u8, u8)>,
 }
 
 impl Default for RuneMinigame {
@@ -52,6 +64,10 @@ impl RuneMinigame {
             highest_level_rune: Self::level_to_rune(level),
             pixels,
             erasing: false,
+            hint: None,
+            show_hint: false,
+            strokes: Vec::new(),
+            grown_from: None,
         }
     }
 
@@ -69,12 +85,10 @@ impl RuneMinigame {
 
     pub fn area(&self) -> RectangularArea {
         const BUFFER: f32 = 20.0;
-        let blocks_per_row = self.blocks_per_row();
-        let blocks_per_column = self.blocks_per_column();
+        let grid = self.grid().size();
         RectangularArea {
-            width: BUFFER + MIN_WIDTH.max(PIXEL_SIZE * blocks_per_row as f32),
-            height: BUFFER
-                + MIN_HEIGHT.max(PIXEL_SIZE * blocks_per_column as f32),
+            width: BUFFER + MIN_WIDTH.max(grid.x),
+            height: BUFFER + HINT_BUTTON_SIZE + MIN_HEIGHT.max(grid.y),
         }
     }
 
@@ -86,28 +100,78 @@ impl RuneMinigame {
         Self::new(self.expected_level())
     }
 
+    // The drawing stays where it still fits, so the rune that levelled it
+    // up can be built on, and the new grid grows out of the old one.
+    pub fn carry_over(&self, next: &mut Self) {
+        for (y, row) in self.pixels.iter().enumerate() {
+            for (x, on) in row.iter().enumerate() {
+                next.set_pixel(x as u8, y as u8, *on);
+            }
+        }
+        next.grown_from =
+            Some((self.blocks_per_row(), self.blocks_per_column()));
+    }
+
+    pub fn progress(&self) -> f32 {
+        0.0 // levels all at once, on drawing a higher rune
+    }
+
     pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
-        let (area, blocks_per_row, blocks_per_column) =
-            (self.area(), self.blocks_per_row(), self.blocks_per_column());
+        let (area, grid) = (self.area(), self.grid());
 
         let _background = parent.spawn((
+            GridBackground,
             Sprite {
-                color: Color::srgb(0.5, 0.5, 0.5),
+                color: GRID_BACKGROUND_COLOR,
                 custom_size: Some(area.into()),
                 ..default()
             },
             Transform::from_xyz(0.0, 0.0, -1.0),
         ));
 
-        for y in 0..blocks_per_column {
-            for x in 0..blocks_per_row {
-                parent.spawn(PixelBundle::new(
-                    x,
-                    y,
-                    blocks_per_row,
-                    blocks_per_column,
+        let old_grid = self
+            .grown_from
+            .map(|(cols, rows)| GridWidget::new(cols, rows, PIXEL_SIZE));
+        grid.spawn(parent, |x, y| {
+            (
+                Pixel,
+                Sprite::from_color(
+                    pixel_color(self.get_pixel(x, y)),
+                    PIXEL_AREA.into(),
+                ),
+                GrowingPixel::new(old_grid, x, y),
+            )
+        });
+
+        let minigame = parent.target_entity();
+        parent
+            .spawn(HintButtonBundle::new(
+                minigame,
+                Vec3::new(
+                    area.left() + HINT_BUTTON_SIZE / 2.0 + 2.0,
+                    area.top() - HINT_BUTTON_SIZE / 2.0 - 2.0,
+                    0.5,
+                ),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text2d::new("?"),
+                    TextFont {
+                        font_size: 12.0,
+                        ..default()
+                    },
+                    TextColor(Color::BLACK),
+                    Transform::from_xyz(0.0, 0.0, 0.1),
                 ));
-            }
+            });
+        // hidden until a hint is bought and shown
+        for (x, y) in self.stencil() {
+            parent.spawn((
+                HintStencil { minigame },
+                Sprite::from_color(STENCIL_COLOR, PIXEL_AREA.into()),
+                Transform::from_translation(grid.translation(x, y).with_z(0.5)),
+                Visibility::Hidden,
+            ));
         }
     }
 
@@ -128,6 +192,14 @@ impl RuneMinigame {
         Self::_blocks_per_row(self.level)
     }
 
+    pub fn grid(&self) -> GridWidget {
+        GridWidget::new(
+            self.blocks_per_row(),
+            self.blocks_per_column(),
+            PIXEL_SIZE,
+        )
+    }
+
     pub fn blocks_per_column(&self) -> u8 {
         Self::_blocks_per_column(self.level)
     }
@@ -155,8 +227,7 @@ impl RuneMinigame {
     pub fn set_highest_level_rune(&mut self, rune: Rune) {
         match self.highest_level_rune {
             Some(current)
-                if Self::rune_level(&rune)
-                    <= Self::rune_level(&current) => {}
+                if Self::rune_level(&rune) <= Self::rune_level(&current) => {}
             _ => self.highest_level_rune = Some(rune),
         }
     }
@@ -213,75 +284,276 @@ impl RuneMinigame {
         self.pixels[y][x]
     }
 
+    // The lowest rune not drawn yet, which the grid is sized for.
+    pub fn next_rune(&self) -> Option<Rune> {
+        Self::level_to_rune(self.expected_level() + 1)
+    }
+
+    // The grid cells the next rune covers, from the top-left corner. Parts
+    // that don't fit the grid are left out.
+    pub fn stencil(&self) -> Vec<(u8, u8)> {
+        let Some(rune) = self.next_rune() else {
+            return vec![];
+        };
+        let mut cells = vec![];
+        for (y, row) in rune_to_pixels(&rune).iter().enumerate() {
+            for (x, on) in row.iter().enumerate() {
+                let (x, y) = (x as u8, y as u8);
+                if *on
+                    && x < self.blocks_per_row()
+                    && y < self.blocks_per_column()
+                {
+                    cells.push((x, y));
+                }
+            }
+        }
+        cells
+    }
+
+    pub fn hint_bought(&self) -> bool {
+        self.hint.is_some() && self.hint == self.next_rune()
+    }
+
+    // Toggles the hint. Showing one that hasn't been bought needs an XP item
+    // to spend (`has_xp`); returns true if it was spent.
+    pub fn toggle_hint(&mut self, has_xp: bool) -> bool {
+        if self.show_hint {
+            self.show_hint = false;
+            return false;
+        }
+        if self.next_rune().is_none() {
+            return false;
+        }
+        if self.hint_bought() {
+            self.show_hint = true;
+            return false;
+        }
+        if !has_xp {
+            return false;
+        }
+        self.hint = self.next_rune();
+        self.show_hint = true;
+        true
+    }
+
     pub fn clear(&mut self) {
         for pixel in self.pixels.iter_mut().flatten() {
             *pixel = false;
         }
+        self.strokes.clear();
+    }
+
+    pub fn begin_stroke(&mut self) {
+        if self.strokes.last().is_some_and(|stroke| stroke.is_empty()) {
+            return;
+        }
+        if self.strokes.len() >= UNDO_STROKES {
+            self.strokes.remove(0);
+        }
+        self.strokes.push(Vec::new());
+    }
+
+    // Sets a pixel as part of the current stroke, so undo can put it back.
+    pub fn stroke_pixel(&mut self, x: u8, y: u8, value: bool) {
+        let was = self.get_pixel(x, y);
+        if was == value {
+            return;
+        }
+        self.set_pixel(x, y, value);
+        if self.strokes.is_empty() {
+            self.strokes.push(Vec::new());
+        }
+        if let Some(stroke) = self.strokes.last_mut() {
+            stroke.push((x, y, was));
+        }
+    }
+
+    // Puts back the pixels the last stroke that changed any had changed.
+    // Returns false if there's none.
+    pub fn undo_stroke(&mut self) -> bool {
+        while let Some(stroke) = self.strokes.pop() {
+            if stroke.is_empty() {
+                continue;
+            }
+            for (x, y, was) in stroke.into_iter().rev() {
+                self.set_pixel(x, y, was);
+            }
+            return true;
+        }
+        false
     }
 }
 
-#[derive(Bundle)]
-pub struct PixelBundle {
-    pub pixel: Pixel,
-    pub toggleable: Toggleable,
-    pub shape: Shape,
-    pub transform: Transform,
+fn pixel_color(on: bool) -> Color {
+    if on {
+        PIXEL_ON_COLOR
+    } else {
+        PIXEL_OFF_COLOR
+    }
 }
 
-impl PixelBundle {
-    pub fn new(x: u8, y: u8, cols: u8, rows: u8) -> Self {
-        let t_y = rows - y; // top to bottom
-        let dx = -PIXEL_SIZE * ((cols - 1) as f32 / 2.0);
-        let dy = -PIXEL_SIZE * ((rows + 1) as f32 / 2.0);
+// Marks the rune grid's cells.
+#[derive(Debug, Clone, Component)]
+pub struct Pixel;
+
+// A cell of a grid that has just levelled up, on its way into place: from
+// where it sat in the old grid, or out of nothing where the old grid didn't
+// reach. Cells spawned any other way start in place and finish at once.
+#[derive(Debug, Clone, Component)]
+pub struct GrowingPixel {
+    pub from: Option<Vec3>,
+    pub elapsed: f32,
+}
+
+impl GrowingPixel {
+    pub fn new(old_grid: Option<GridWidget>, x: u8, y: u8) -> Self {
+        let from = old_grid
+            .filter(|old| x < old.cols && y < old.rows)
+            .map(|old| old.translation(x, y));
         Self {
-            pixel: Pixel { x, y },
-            toggleable: Toggleable::new(),
-            shape: ShapeBuilder::with(&shapes::Rectangle {
-                extents: PIXEL_AREA.into(),
-                ..default()
-            })
-            .fill(Fill::color(PIXEL_OFF_COLOR))
-            .build(),
-            transform: Transform::from_xyz(
-                x as f32 * PIXEL_SIZE + dx,
-                t_y as f32 * PIXEL_SIZE + dy,
-                0.0,
-            ),
+            from,
+            elapsed: if old_grid.is_some() {
+                0.0
+            } else {
+                GROW_SECONDS
+            },
         }
     }
 
-    pub fn turn_on(entity: Entity, query: &mut Query<&mut Shape, With<Pixel>>) {
-        if let Ok(mut shape) = query.get_mut(entity) {
-            shape.fill = Some(Fill::color(PIXEL_ON_COLOR));
+    // Where the cell is and how big, `to` being its place in the new grid.
+    pub fn at(&self, to: Vec3) -> (Vec3, f32) {
+        let t = (self.elapsed / GROW_SECONDS).clamp(0.0, 1.0);
+        let eased = t * (2.0 - t);
+        match self.from {
+            Some(from) => (from.lerp(to, eased), 1.0),
+            None => (to, eased),
         }
     }
+}
 
-    pub fn turn_off(entity: Entity, query: &mut Query<&mut Shape, With<Pixel>>) {
-        if let Ok(mut shape) = query.get_mut(entity) {
-            shape.fill = Some(Fill::color(PIXEL_OFF_COLOR));
+#[derive(Bundle)]
+pub struct HintButtonBundle {
+    pub button: HintButton,
+    pub area: RectangularArea,
+    pub clickable: Clickable,
+    pub hoverable: Hoverable,
+    pub hover_text: HoverText,
+    pub sprite: Sprite,
+    pub transform: Transform,
+}
+
+impl HintButtonBundle {
+    pub fn new(minigame: Entity, translation: Vec3) -> Self {
+        let area = RectangularArea {
+            width: HINT_BUTTON_SIZE,
+            height: HINT_BUTTON_SIZE,
+        };
+        Self {
+            button: HintButton { minigame },
+            area,
+            clickable: Clickable::new(CLICK_PRIORITY_CONTENT),
+            hoverable: Hoverable,
+            hover_text: HoverText::new(tr("rune.hint")),
+            sprite: Sprite::from_color(HINT_BUTTON_COLOR, area.into()),
+            transform: Transform::from_translation(translation),
         }
     }
 }
 
 #[derive(Debug, Clone, Component)]
-pub struct Pixel {
-    pub x: u8,
-    pub y: u8,
+pub struct HintButton {
+    pub minigame: Entity,
+}
+
+// One cell of the faint outline of the next rune.
+#[derive(Debug, Clone, Component)]
+pub struct HintStencil {
+    pub minigame: Entity,
+}
+
+// A click on the hint button shows or hides the stencil. Showing it the first
+// time for a rune uses up an XP item stuck to the player, the smallest one.
+pub fn hint_button_update(
+    mut commands: Commands,
+    mut audit: ResMut<AmountAudit>,
+    mut click_events: MessageReader<ClickEvent>,
+    button_query: Query<&HintButton>,
+    mut minigame_query: Query<&mut Minigame, Without<LevelingUp>>,
+    stuck_query: Query<(Entity, &Item), With<Stuck>>,
+) {
+    for click in click_events.read() {
+        if !click.is_release() {
+            continue;
+        }
+        let Some(Ok(button)) = click.target.map(|e| button_query.get(e)) else {
+            continue;
+        };
+        let Ok(mut minigame) = minigame_query.get_mut(button.minigame) else {
+            continue;
+        };
+        let Minigame::Rune(rune) = &mut *minigame else {
+            continue;
+        };
+        let xp = stuck_query
+            .iter()
+            .filter(|(_, item)| item.r#type.is_xp())
+            .min_by(|(_, a), (_, b)| a.amount.total_cmp(&b.amount));
+        if rune.toggle_hint(xp.is_some()) {
+            let (entity, item) = xp.unwrap();
+            audit.ingested += item.amount;
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// Stencils show while their minigame is engaged with its hint on.
+pub fn update_hint_stencil(
+    engaged: Res<Engaged>,
+    minigame_query: Query<&Minigame>,
+    mut stencil_query: Query<(&HintStencil, &mut Visibility)>,
+) {
+    for (stencil, mut visibility) in stencil_query.iter_mut() {
+        let Ok(Minigame::Rune(rune)) = minigame_query.get(stencil.minigame)
+        else {
+            continue;
+        };
+        let shown = rune.show_hint && engaged.game == Some(ID);
+        let wanted = if shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+    }
 }
 
 // Pixel was clicked.
 pub fn pixel_update(
     mut commands: Commands,
     mouse_state: Res<MouseState>,
-    time: Res<Time>,
+    hovered: Res<Hovered>,
+    mut click_events: MessageReader<ClickEvent>,
+    balance: Res<BalanceConfig>,
     mut rune_minigame_query: Query<&mut Minigame>,
     leveling_up_query: Query<&LevelingUp, With<Minigame>>,
-    ready_query: Query<&Ready, With<Minigame>>,
-    pixel_query: Query<(&Pixel, Entity, &ChildOf, &GlobalTransform)>,
-    mut fill_query: Query<&mut Shape, With<Pixel>>,
+    ready_query: Query<&DelayedTrigger<RuneReady>, With<Minigame>>,
+    pixel_query: Query<(&GridCell, &ChildOf), With<Pixel>>,
+    mut sprite_query: Query<&mut Sprite, With<Pixel>>,
 ) {
+    let mut pressed = false;
+    let mut released = false;
+    for click in click_events.read() {
+        if click.is_press() {
+            pressed = true;
+        } else {
+            released = true;
+        }
+    }
+
     // reset erasing state when mouse is released
-    if mouse_state.just_released {
+    if released {
         for minigame in rune_minigame_query.iter_mut() {
             if let Minigame::Rune(minigame) = minigame.into_inner() {
                 minigame.erasing = false;
@@ -294,110 +566,199 @@ pub fn pixel_update(
         return;
     }
 
-    let mouse_position = mouse_state.current_position;
-    for (pixel, pixel_entity, pixel_parent, pixel_global_transform) in
-        pixel_query.iter()
-    {
-        let minigame_entity = pixel_parent.parent();
-        if leveling_up_query.get(minigame_entity).is_ok() {
-            continue;
-        }
-        if PIXEL_AREA.is_within(
-            mouse_position,
-            pixel_global_transform.translation().truncate(),
-        ) {
-            let minigame = match rune_minigame_query
-                .get_mut(minigame_entity)
-                .unwrap()
-                .into_inner()
-            {
-                Minigame::Rune(m) => m,
-                _ => continue,
-            };
-
-            // set erasing state so player can draw/erase multiple pixels
-            if mouse_state.just_pressed {
-                minigame.erasing = minigame.get_pixel(pixel.x, pixel.y);
-            } else if mouse_state.just_released {
-                minigame.erasing = false;
-            }
-            // draw/erase pixel
-            if minigame.erasing {
-                PixelBundle::turn_off(pixel_entity, &mut fill_query);
-                minigame.set_pixel(pixel.x, pixel.y, false);
-            } else {
-                PixelBundle::turn_on(pixel_entity, &mut fill_query);
-                minigame.set_pixel(pixel.x, pixel.y, true);
+    // only the pixel on top under the cursor, so overlapping UI wins
+    let Some(pixel_entity) = hovered.entity else {
+        return;
+    };
+    let Some((minigame_entity, pixel)) =
+        GridCell::hit(Some(pixel_entity), &pixel_query)
+    else {
+        return;
+    };
+    if leveling_up_query.get(minigame_entity).is_ok() {
+        return;
+    }
+    let Ok(minigame) = rune_minigame_query.get_mut(minigame_entity) else {
+        return;
+    };
+    let Minigame::Rune(minigame) = minigame.into_inner() else {
+        return;
+    };
+
+    // set erasing state so player can draw/erase multiple pixels
+    if pressed {
+        minigame.erasing = minigame.get_pixel(pixel.x, pixel.y);
+        minigame.begin_stroke();
+    }
+    // draw/erase pixel
+    let on = !minigame.erasing;
+    minigame.stroke_pixel(pixel.x, pixel.y, on);
+    if let Ok(mut sprite) = sprite_query.get_mut(pixel_entity) {
+        sprite.color = pixel_color(on);
+    }
+    update_ready(
+        &mut commands,
+        &balance,
+        minigame_entity,
+        minigame,
+        ready_query.get(minigame_entity).is_ok(),
+    );
+}
+
+// What a rune minigame's `DelayedTrigger` is for: emitting the rune drawn on
+// it, once it has stayed a rune for `rune.trigger_seconds`.
+#[derive(Debug, Clone, Copy)]
+pub struct RuneReady;
+
+// Arm the trigger when the drawing becomes a rune, disarm it when it stops
+// being one.
+// TODO visual change when drawing is a valid rune
+fn update_ready(
+    commands: &mut Commands,
+    balance: &BalanceConfig,
+    minigame_entity: Entity,
+    minigame: &RuneMinigame,
+    is_ready: bool,
+) {
+    match minigame.to_rune() {
+        Some(_) => {
+            if !is_ready {
+                commands.entity(minigame_entity).insert(DelayedTrigger::<
+                    RuneReady,
+                >::new(
+                    balance.rune.trigger_seconds,
+                ));
             }
-            // emit rune or get ready to
-            // TODO visual change when drawing is a valid rune
-            let is_ready = ready_query.get(minigame_entity).is_ok();
-            match minigame.to_rune() {
-                Some(_) => {
-                    if !is_ready {
-                        commands
-                            .entity(minigame_entity)
-                            .insert(Ready::new(time.elapsed_secs()));
-                    }
-                }
-                None => {
-                    if is_ready {
-                        commands.entity(minigame_entity).remove::<Ready>();
-                    }
-                }
+        }
+        None => {
+            if is_ready {
+                commands
+                    .entity(minigame_entity)
+                    .remove::<DelayedTrigger<RuneReady>>();
             }
         }
     }
 }
 
-const RUNE_TRIGGER_SECONDS: f32 = 2.0;
+// Ctrl+Z takes back the last stroke on the engaged grid. Once the drawing
+// has fired as a rune the grid is cleared, or carried into the next level's,
+// and there's nothing left to take back.
+pub fn undo_input(
+    mut commands: Commands,
+    engaged: Res<Engaged>,
+    minigames: Res<MinigamesResource>,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    balance: Res<BalanceConfig>,
+    mut minigame_query: Query<&mut Minigame, Without<LevelingUp>>,
+    ready_query: Query<&DelayedTrigger<RuneReady>, With<Minigame>>,
+    mut pixel_query: Query<(&GridCell, &ChildOf, &mut Sprite), With<Pixel>>,
+) {
+    if engaged.game != Some(ID) || !undo_pressed(&kb_input) {
+        return;
+    }
+    let Some(minigame_entity) = minigames.entity(ID) else {
+        return;
+    };
+    let Ok(mut minigame) = minigame_query.get_mut(minigame_entity) else {
+        return;
+    };
+    let Minigame::Rune(minigame) = &mut *minigame else {
+        return;
+    };
+    if !minigame.undo_stroke() {
+        return;
+    }
+    paint_grid(minigame_entity, &mut pixel_query, |x, y, sprite| {
+        sprite.color = pixel_color(minigame.get_pixel(x, y));
+    });
+    update_ready(
+        &mut commands,
+        &balance,
+        minigame_entity,
+        minigame,
+        ready_query.get(minigame_entity).is_ok(),
+    );
+}
 
+// Emits the drawn rune once its trigger fires, and clears the grid, unless
+// the rune levels it up: then the drawing carries over into the bigger grid.
 pub fn fixed_update(
     mut commands: Commands,
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut fired: MessageReader<TriggerFired<RuneReady>>,
+    mut rune_minigame_query: Query<
+        (&mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+    mut pixel_query: Query<(&GridCell, &ChildOf, &mut Sprite), With<Pixel>>,
+) {
+    for fired in fired.read() {
+        let minigame_entity = fired.entity;
+        let Ok((minigame, minigame_transform, minigame_area)) =
+            rune_minigame_query.get_mut(minigame_entity)
+        else {
+            continue;
+        };
+        let Minigame::Rune(minigame) = minigame.into_inner() else {
+            continue;
+        };
+        let Some(rune) = minigame.to_rune() else {
+            continue;
+        };
+        minigame.set_highest_level_rune(rune);
+        commands.spawn(ItemBundle::new_from_minigame(
+            &mut images,
+            &mut generated_image_assets,
+            Item::new_abstract(AbstractKind::Rune, rune as u8, 1.0),
+            minigame_transform,
+            minigame_area,
+        ));
+        commands.spawn(ItemBundle::new_from_minigame(
+            &mut images,
+            &mut generated_image_assets,
+            Item::xp(RuneMinigame::rune_level(&rune) as f32),
+            minigame_transform,
+            minigame_area,
+        ));
+        if RuneMinigame::rune_level(&rune) > minigame.level {
+            commands.entity(minigame_entity).insert(LevelingUp);
+        } else {
+            paint_grid(minigame_entity, &mut pixel_query, |_, _, sprite| {
+                sprite.color = PIXEL_OFF_COLOR;
+            });
+            minigame.clear();
+        }
+    }
+}
+
+// Moves the cells of a freshly levelled-up grid into place.
+pub fn grow_pixels(
+    mut commands: Commands,
     time: Res<Time>,
-    mut rune_minigame_query: Query<(
-        &mut Minigame,
-        &GlobalTransform,
-        &RectangularArea,
+    mut pixel_query: Query<(
+        Entity,
+        &mut GrowingPixel,
+        &GridCell,
+        &ChildOf,
+        &mut Transform,
     )>,
-    leveling_up_query: Query<&LevelingUp, With<Minigame>>,
-    ready_query: Query<(&Ready, Entity), With<Minigame>>,
-    pixel_query: Query<(Entity, &ChildOf)>,
-    mut fill_query: Query<&mut Shape, With<Pixel>>,
+    minigame_query: Query<&Minigame>,
 ) {
-    for (ready, minigame_entity) in ready_query.iter() {
-        if leveling_up_query.get(minigame_entity).is_ok() {
+    for (entity, mut growing, cell, parent, mut transform) in
+        pixel_query.iter_mut()
+    {
+        let Ok(Minigame::Rune(minigame)) = minigame_query.get(parent.parent())
+        else {
             continue;
-        }
-        if time.elapsed_secs() - ready.since_time > RUNE_TRIGGER_SECONDS {
-            commands.entity(minigame_entity).remove::<Ready>();
-            let (minigame, minigame_transform, minigame_area) =
-                rune_minigame_query.get_mut(minigame_entity).unwrap();
-            let minigame = match minigame.into_inner() {
-                Minigame::Rune(m) => m,
-                _ => continue,
-            };
-            if let Some(rune) = minigame.to_rune() {
-                for (pixel_entity, pixel_parent) in pixel_query.iter() {
-                    if pixel_parent.parent() == minigame_entity {
-                        PixelBundle::turn_off(pixel_entity, &mut fill_query);
-                    }
-                }
-                minigame.set_highest_level_rune(rune);
-                minigame.clear();
-                commands.spawn(ItemBundle::new_from_minigame(
-                    &mut images,
-                    &mut generated_image_assets,
-                    Item::new_abstract(AbstractKind::Rune, rune as u8, 1.0),
-                    minigame_transform,
-                    minigame_area,
-                ));
-                if RuneMinigame::rune_level(&rune) > minigame.level {
-                    commands.entity(minigame_entity).insert(LevelingUp);
-                }
-            }
+        };
+        growing.elapsed += time.delta_secs();
+        let to = minigame.grid().translation(cell.x, cell.y);
+        let (translation, scale) = growing.at(to);
+        transform.translation = translation.with_z(transform.translation.z);
+        transform.scale = Vec3::splat(scale);
+        if growing.elapsed >= GROW_SECONDS {
+            commands.entity(entity).remove::<GrowingPixel>();
         }
     }
 }
@@ -423,4 +784,99 @@ mod tests {
         minigame.set_highest_level_rune(Rune::InclusiveSelf); // level 1
         assert_eq!(minigame.highest_level_rune, Some(Rune::Shelter));
     }
+
+    #[test]
+    fn undo_takes_back_the_last_stroke() {
+        let mut minigame = RuneMinigame::new(2);
+        minigame.begin_stroke();
+        minigame.stroke_pixel(0, 0, true);
+        minigame.stroke_pixel(0, 1, true);
+        minigame.begin_stroke();
+        minigame.stroke_pixel(0, 1, false);
+        minigame.stroke_pixel(1, 1, true);
+        // a press that changes nothing isn't a stroke of its own
+        minigame.begin_stroke();
+        minigame.stroke_pixel(1, 1, true);
+
+        assert!(minigame.undo_stroke());
+        assert!(minigame.get_pixel(0, 0));
+        assert!(minigame.get_pixel(0, 1));
+        assert!(!minigame.get_pixel(1, 1));
+        assert!(minigame.undo_stroke());
+        assert!(minigame.pixels.iter().flatten().all(|on| !on));
+        assert!(!minigame.undo_stroke());
+
+        // clearing the grid, as firing a rune does, forgets the strokes
+        minigame.begin_stroke();
+        minigame.stroke_pixel(0, 0, true);
+        minigame.clear();
+        assert!(!minigame.undo_stroke());
+    }
+
+    #[test]
+    fn levelup_keeps_the_drawing_that_fits() {
+        let mut minigame = RuneMinigame::new(1);
+        minigame.set_pixel(0, 0, true);
+        minigame.set_pixel(1, 0, true);
+        let mut next = RuneMinigame::new(2);
+        minigame.carry_over(&mut next);
+        assert_eq!(next.pixels, vec![vec![true, true], vec![false, false]]);
+        assert_eq!(next.grown_from, Some((2, 1)));
+
+        // shrinking, as the console might, drops what's off the grid
+        let mut smaller = RuneMinigame::new(0);
+        minigame.carry_over(&mut smaller);
+        assert_eq!(smaller.pixels, vec![vec![true]]);
+    }
+
+    #[test]
+    fn cells_grow_out_of_the_old_grid() {
+        let old = RuneMinigame::new(1).grid();
+        let new = RuneMinigame::new(2).grid();
+        let to = new.translation(1, 0);
+
+        // an old cell slides from its old place to its new one
+        let mut kept = GrowingPixel::new(Some(old), 1, 0);
+        assert_eq!(kept.at(to), (old.translation(1, 0), 1.0));
+        kept.elapsed = GROW_SECONDS;
+        assert_eq!(kept.at(to), (to, 1.0));
+
+        // a new cell grows in place
+        let mut added = GrowingPixel::new(Some(old), 1, 1);
+        let to = new.translation(1, 1);
+        assert_eq!(added.at(to), (to, 0.0));
+        added.elapsed = GROW_SECONDS;
+        assert_eq!(added.at(to), (to, 1.0));
+
+        // without a levelup there's nothing to grow from
+        let spawned = GrowingPixel::new(None, 0, 0);
+        assert_eq!(spawned.at(to), (to, 1.0));
+    }
+
+    #[test]
+    fn hints_cost_xp_once_per_rune() {
+        let mut minigame = RuneMinigame::new(2);
+        assert_eq!(minigame.next_rune(), Some(Rune::ExclusiveSelf));
+        let cells = minigame.stencil();
+        let pattern = rune_to_pixels(&Rune::ExclusiveSelf);
+        let on = pattern.iter().flatten().filter(|on| **on).count();
+        assert_eq!(cells.len(), on);
+
+        // no XP, no hint
+        assert!(!minigame.toggle_hint(false));
+        assert!(!minigame.show_hint);
+        // paid for once, then free to hide and show again
+        assert!(minigame.toggle_hint(true));
+        assert!(minigame.show_hint);
+        assert!(!minigame.toggle_hint(true));
+        assert!(!minigame.show_hint);
+        assert!(!minigame.toggle_hint(false));
+        assert!(minigame.show_hint);
+
+        // drawing the rune moves on to the next, which costs again
+        minigame.set_highest_level_rune(Rune::ExclusiveSelf);
+        minigame.show_hint = false;
+        assert!(!minigame.hint_bought());
+        assert!(!minigame.toggle_hint(false));
+    }
 }
@@ -0,0 +1,366 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// A slot machine that runs on clicks. Every click fed in buys credit, long
+// clicks more of it, and the machine spends one credit per spin. It always
+// pays out something, mostly mud; gold and minigame items are rare, though
+// higher levels make them less so. A pity counter guarantees a jackpot after a
+// long enough dry spell.
+
+pub const ID: &str = "slots";
+pub const POSITION: Vec2 = Vec2::new(-650.0, 150.0);
+
+pub const NAME: &str = "slots.name";
+pub const DESCRIPTION: &str = "slots.description";
+const AREA: RectangularArea = RectangularArea {
+    width: 120.0,
+    height: 140.0,
+};
+
+// Credit per click fed in.
+const SHORT_CLICK_CREDIT: f32 = 1.0;
+const LONG_CLICK_CREDIT: f32 = 5.0;
+const SPIN_COST: f32 = 1.0;
+const SPIN_SECONDS: f32 = 1.0;
+// Spins without a jackpot after which the next one is guaranteed to be.
+pub const PITY_SPINS: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prize {
+    Mud,
+    Dirt,
+    Copper,
+    Gold,
+    Minigame,
+}
+
+impl Prize {
+    pub fn is_jackpot(&self) -> bool {
+        matches!(self, Prize::Gold | Prize::Minigame)
+    }
+
+    pub fn label(&self) -> String {
+        tr(match self {
+            Prize::Mud => "slots.prize.mud",
+            Prize::Dirt => "slots.prize.dirt",
+            Prize::Copper => "slots.prize.copper",
+            Prize::Gold => "slots.prize.gold",
+            Prize::Minigame => "slots.prize.minigame",
+        })
+    }
+
+    pub fn item(&self, random: &mut Random) -> Item {
+        match self {
+            Prize::Mud => Item::liquid(Substance::Mud, 1.0),
+            Prize::Dirt => Item::powder(Substance::Dirt, 1.0),
+            Prize::Copper => {
+                Item::solid(Substance::Copper, BulkShape::Lump, 1.0)
+            }
+            Prize::Gold => Item::solid(Substance::Gold, BulkShape::Lump, 1.0),
+            Prize::Minigame => {
+                let kinds = [
+                    MinigameItemKind::Button,
+                    MinigameItemKind::PrimordialOcean,
+                    MinigameItemKind::Draw,
+                    MinigameItemKind::BlockBreaker,
+                    MinigameItemKind::Tree,
+                ];
+                let kind = *random.pick(&kinds).unwrap();
+                Item::new(
+                    ItemType::Minigame(MinigameItem { kind, variant: 0 }),
+                    1.0,
+                )
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Component)]
+pub struct SlotsMinigame {
+    pub level: u8,
+    // spins paid for but not yet taken
+    pub credits: f32,
+    // seconds until the reels can spin again
+    pub cooldown: f32,
+    // spins taken so far
+    pub spins: f32,
+    // spins since the last jackpot
+    pub since_jackpot: u32,
+    // what the last spin paid, shown on the reels
+    pub last: Option<Prize>,
+    // fed in as items, counts as extra spins
+    pub xp: f32,
+}
+
+impl SlotsMinigame {
+    pub fn new(spins: f32) -> Self {
+        Self {
+            level: Self::level_by_spins(spins),
+            spins,
+            ..default()
+        }
+    }
+
+    //
+    // COMMON
+    //
+
+    pub fn name(&self) -> &str {
+        NAME
+    }
+
+    pub fn description(&self) -> &str {
+        DESCRIPTION
+    }
+
+    pub fn area(&self) -> RectangularArea {
+        AREA
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn levelup(&self) -> Self {
+        Self::new(self.level_basis())
+    }
+
+    pub fn carry_over(&self, next: &mut Self) {
+        next.credits = self.credits;
+        next.spins = self.spins;
+        next.since_jackpot = self.since_jackpot;
+        next.last = self.last;
+        next.xp = self.xp;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.level_basis(), self.level)
+    }
+
+    pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
+        let minigame = parent.target_entity();
+        parent.spawn((
+            Sprite {
+                color: Color::srgb(0.6, 0.1, 0.15),
+                custom_size: Some(AREA.into()),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, -1.0),
+        ));
+        parent.spawn((
+            Sprite::from_color(
+                Color::srgb(0.95, 0.9, 0.8),
+                Vec2::new(AREA.width - 20.0, 50.0),
+            ),
+            Transform::from_xyz(0.0, 15.0, 0.0),
+        ));
+        parent.spawn((
+            ReelText { minigame },
+            Text2d::new(self.reel_label()),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(Color::BLACK),
+            Transform::from_xyz(0.0, 15.0, 0.1),
+        ));
+    }
+
+    pub fn ingest_item(&mut self, item: &Item) -> f32 {
+        let ItemType::Abstract(abstract_item) = item.r#type else {
+            return 0.0;
+        };
+        if abstract_item.kind != AbstractKind::Click {
+            return 0.0;
+        }
+        let credit = match abstract_item.variant {
+            0 => SHORT_CLICK_CREDIT,
+            _ => LONG_CLICK_CREDIT,
+        };
+        self.credits += item.amount * credit;
+        item.amount
+    }
+
+    // How much of `item` `ingest_item` would take.
+    pub fn accepted_amount(item: &Item) -> f32 {
+        match item.r#type {
+            ItemType::Abstract(abstract_item)
+                if abstract_item.kind == AbstractKind::Click =>
+            {
+                item.amount
+            }
+            _ => 0.0,
+        }
+    }
+
+    //
+    // SPECIFIC
+    //
+
+    // Spins plus XP: what the level is computed from.
+    pub fn level_basis(&self) -> f32 {
+        self.spins + self.xp
+    }
+
+    pub fn should_level_up(&self) -> bool {
+        Self::level_by_spins(self.level_basis()) > self.level
+    }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.should_level_up()
+    }
+
+    pub fn level_by_spins(spins: f32) -> u8 {
+        if spins <= 0.0 {
+            0
+        } else {
+            ((spins.log2() + 1.0) as u8).min(99)
+        }
+    }
+
+    // Odds of each prize, out of their sum. Levels shift them toward the
+    // jackpots.
+    pub fn weights(level: u8) -> [(Prize, u64); 5] {
+        let level = level as u64;
+        [
+            (Prize::Mud, 60),
+            (Prize::Dirt, 20),
+            (Prize::Copper, 12),
+            (Prize::Gold, 3 + level),
+            (Prize::Minigame, 1 + level / 2),
+        ]
+    }
+
+    pub fn roll(level: u8, random: &mut Random) -> Prize {
+        random
+            .pick_weighted(&Self::weights(level), |(_, weight)| *weight)
+            .map_or(Prize::Mud, |(prize, _)| *prize)
+    }
+
+    // Spend a credit and spin, if there's credit and the reels have stopped.
+    pub fn spin(&mut self, seconds: f32, random: &mut Random) -> Option<Prize> {
+        self.cooldown = (self.cooldown - seconds).max(0.0);
+        if self.cooldown > 0.0 || self.credits < SPIN_COST {
+            return None;
+        }
+        self.cooldown = SPIN_SECONDS;
+        self.credits -= SPIN_COST;
+        self.spins += 1.0;
+        let prize = if self.since_jackpot + 1 >= PITY_SPINS {
+            Prize::Gold
+        } else {
+            Self::roll(self.level, random)
+        };
+        if prize.is_jackpot() {
+            self.since_jackpot = 0;
+        } else {
+            self.since_jackpot += 1;
+        }
+        self.last = Some(prize);
+        Some(prize)
+    }
+
+    pub fn reel_label(&self) -> String {
+        let last = self.last.map_or("-".to_string(), |prize| prize.label());
+        tr_fmt(
+            "slots.reel",
+            &[
+                ("prize", &last),
+                ("credits", &format_amount(self.credits.floor())),
+            ],
+        )
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct ReelText {
+    pub minigame: Entity,
+}
+
+pub fn fixed_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut random: ResMut<Random>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut minigame_query: Query<
+        (Entity, &mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+) {
+    for (entity, minigame, global_transform, area) in minigame_query.iter_mut()
+    {
+        // idle machines aren't marked changed, so the reels don't redraw
+        let Minigame::Slots(slots) = minigame.as_ref() else {
+            continue;
+        };
+        if slots.credits < SPIN_COST && slots.cooldown <= 0.0 {
+            continue;
+        }
+        let Minigame::Slots(slots) = minigame.into_inner() else {
+            continue;
+        };
+        let Some(prize) = slots.spin(time.delta_secs(), &mut random) else {
+            continue;
+        };
+        commands.spawn(ItemBundle::new_from_minigame(
+            &mut images,
+            &mut generated_image_assets,
+            prize.item(&mut random),
+            global_transform,
+            area,
+        ));
+        if slots.should_level_up() {
+            commands.entity(entity).insert(LevelingUp);
+        }
+    }
+}
+
+pub fn update_reel_text(
+    minigame_query: Query<&Minigame, Changed<Minigame>>,
+    mut text_query: Query<(&ReelText, &mut Text2d)>,
+) {
+    for (reel_text, mut text) in text_query.iter_mut() {
+        let Ok(Minigame::Slots(slots)) = minigame_query.get(reel_text.minigame)
+        else {
+            continue;
+        };
+        text.0 = slots.reel_label();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_clicks_buy_more_spins() {
+        let mut slots = SlotsMinigame::default();
+        let short = Item::new_abstract(AbstractKind::Click, 0, 2.0);
+        let long = Item::new_abstract(AbstractKind::Click, 1, 1.0);
+        assert_eq!(slots.ingest_item(&short), 2.0);
+        assert_eq!(slots.ingest_item(&long), 1.0);
+        assert_eq!(slots.credits, 7.0);
+        assert_eq!(slots.ingest_item(&Item::xp(1.0)), 0.0);
+    }
+
+    #[test]
+    fn pity_guarantees_a_jackpot() {
+        let mut slots = SlotsMinigame {
+            credits: PITY_SPINS as f32,
+            ..default()
+        };
+        let mut random = Random::new(1);
+        let mut jackpot = false;
+        for _ in 0..PITY_SPINS {
+            let prize = slots.spin(SPIN_SECONDS, &mut random).unwrap();
+            jackpot |= prize.is_jackpot();
+        }
+        assert!(jackpot);
+        assert_eq!(slots.credits, 0.0);
+        assert!(slots.spin(SPIN_SECONDS, &mut random).is_none());
+    }
+}
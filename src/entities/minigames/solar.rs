@@ -0,0 +1,308 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// A solar panel under a moving sun. The sun crosses the sky with the world
+// clock; the player tilts the panel (click either side of it) to face it. The
+// better the alignment and the stronger the sunlight, the more Radiant energy
+// it gives off. Nothing at night.
+
+pub const ID: &str = "solar";
+pub const POSITION: Vec2 = Vec2::new(350.0, 550.0);
+
+pub const NAME: &str = "solar.name";
+pub const DESCRIPTION: &str = "solar.description";
+const AREA: RectangularArea = RectangularArea {
+    width: 160.0,
+    height: 120.0,
+};
+
+const PANEL_SIZE: Vec2 = Vec2::new(60.0, 8.0);
+const PANEL_Y: f32 = -35.0;
+const SUN_RADIUS: f32 = 10.0;
+// How far the sun's arc reaches from the panel.
+const SUN_ARC: f32 = 75.0;
+// Radians per click.
+const TILT_STEP: f32 = std::f32::consts::PI / 16.0;
+const MAX_TILT: f32 = std::f32::consts::FRAC_PI_2;
+
+#[derive(Debug, Clone, Default, Component)]
+pub struct SolarMinigame {
+    pub level: u8,
+    // radians from straight up; positive leans right
+    pub tilt: f32,
+    // energy made but not yet a whole item
+    pub charge: f32,
+    // Radiant energy given off so far
+    pub generated: f32,
+    // fed in as items, counts as extra energy generated
+    pub xp: f32,
+}
+
+impl SolarMinigame {
+    pub fn new(generated: f32) -> Self {
+        Self {
+            level: Self::level_by_generated(generated),
+            generated,
+            ..default()
+        }
+    }
+
+    //
+    // COMMON
+    //
+
+    pub fn name(&self) -> &str {
+        NAME
+    }
+
+    pub fn description(&self) -> &str {
+        DESCRIPTION
+    }
+
+    pub fn area(&self) -> RectangularArea {
+        AREA
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn levelup(&self) -> Self {
+        Self::new(self.level_basis())
+    }
+
+    pub fn carry_over(&self, next: &mut Self) {
+        next.tilt = self.tilt;
+        next.charge = self.charge;
+        next.generated = self.generated;
+        next.xp = self.xp;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.level_basis(), self.level)
+    }
+
+    pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
+        let minigame = parent.target_entity();
+        parent.spawn((
+            Sprite {
+                color: Color::srgb(0.75, 0.9, 1.0),
+                custom_size: Some(AREA.into()),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, -1.0),
+        ));
+        // Placed by `fixed_update` once it knows the time of day.
+        parent.spawn((
+            Sun { minigame },
+            ShapeBuilder::with(&shapes::Circle {
+                radius: SUN_RADIUS,
+                ..default()
+            })
+            .fill(Fill::color(Color::srgb(1.0, 0.85, 0.1)))
+            .build(),
+            Transform::from_xyz(0.0, PANEL_Y + SUN_ARC, 0.0),
+            Visibility::Hidden,
+        ));
+        let area = RectangularArea {
+            width: PANEL_SIZE.x,
+            height: PANEL_SIZE.y,
+        };
+        parent.spawn((
+            Panel { minigame },
+            area,
+            Clickable::new(CLICK_PRIORITY_CONTENT),
+            Hoverable,
+            Sprite::from_color(Color::srgb(0.1, 0.15, 0.4), PANEL_SIZE),
+            Transform::from_xyz(0.0, PANEL_Y, 0.0)
+                .with_rotation(Self::panel_rotation(self.tilt)),
+        ));
+    }
+
+    pub fn ingest_item(&self) -> f32 {
+        0.0 // nothing to feed it but XP
+    }
+
+    //
+    // SPECIFIC
+    //
+
+    // Energy generated plus XP: what the level is computed from.
+    pub fn level_basis(&self) -> f32 {
+        self.generated + self.xp
+    }
+
+    pub fn should_level_up(&self) -> bool {
+        Self::level_by_generated(self.level_basis()) > self.level
+    }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.should_level_up()
+    }
+
+    pub fn level_by_generated(generated: f32) -> u8 {
+        if generated <= 0.0 {
+            0
+        } else {
+            ((generated.log2() + 1.0) as u8).min(99)
+        }
+    }
+
+    // Energy per second with the panel facing a noon sun.
+    pub fn peak_output(&self) -> f32 {
+        0.2 * (1.0 + self.level as f32)
+    }
+
+    // The sun's angle from straight up: -90° at dawn, +90° at dusk.
+    pub fn sun_angle(clock: &WorldClock) -> f32 {
+        (clock.time_of_day() - 0.5) * std::f32::consts::TAU
+    }
+
+    // 1 facing the sun, falling to 0 at right angles to it.
+    pub fn alignment(&self, sun_angle: f32) -> f32 {
+        (sun_angle - self.tilt).cos().max(0.0)
+    }
+
+    pub fn output(&self, clock: &WorldClock, seconds: f32) -> f32 {
+        let alignment = self.alignment(Self::sun_angle(clock));
+        clock.sunlight() * alignment * self.peak_output() * seconds
+    }
+
+    // Lean one step toward `direction` (-1 left, 1 right).
+    pub fn tilt_toward(&mut self, direction: f32) {
+        self.tilt =
+            (self.tilt + direction * TILT_STEP).clamp(-MAX_TILT, MAX_TILT);
+    }
+
+    // Positive tilt leans right, which is a clockwise (negative) rotation.
+    pub fn panel_rotation(tilt: f32) -> Quat {
+        Quat::from_rotation_z(-tilt)
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct Panel {
+    pub minigame: Entity,
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct Sun {
+    pub minigame: Entity,
+}
+
+// Clicking left of the panel's middle tilts it left, right tilts it right.
+pub fn panel_click_update(
+    mut click_events: MessageReader<ClickEvent>,
+    mut panel_query: Query<(&Panel, &GlobalTransform, &mut Transform)>,
+    mut minigame_query: Query<&mut Minigame, Without<LevelingUp>>,
+) {
+    for click in click_events.read() {
+        if click.kind != ClickKind::Short {
+            continue;
+        }
+        let Some(Ok((panel, panel_global_transform, mut transform))) =
+            click.target.map(|e| panel_query.get_mut(e))
+        else {
+            continue;
+        };
+        let Ok(minigame) = minigame_query.get_mut(panel.minigame) else {
+            continue;
+        };
+        let Minigame::Solar(minigame) = minigame.into_inner() else {
+            continue;
+        };
+        let center = panel_global_transform.translation().x;
+        minigame.tilt_toward(if click.position.x < center {
+            -1.0
+        } else {
+            1.0
+        });
+        transform.rotation = SolarMinigame::panel_rotation(minigame.tilt);
+    }
+}
+
+// Move the sun and give off whatever energy the panel caught this tick.
+pub fn fixed_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    clock: Res<WorldClock>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut minigame_query: Query<
+        (Entity, &mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+    mut sun_query: Query<(&Sun, &mut Transform, &mut Visibility)>,
+) {
+    let sun_angle = SolarMinigame::sun_angle(&clock);
+    for (sun, mut transform, mut visibility) in sun_query.iter_mut() {
+        if !minigame_query.contains(sun.minigame) {
+            continue;
+        }
+        *visibility = if clock.is_day() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        transform.translation.x = SUN_ARC * sun_angle.sin();
+        transform.translation.y = PANEL_Y + SUN_ARC * sun_angle.cos();
+    }
+
+    for (entity, minigame, global_transform, area) in minigame_query.iter_mut()
+    {
+        let Minigame::Solar(minigame) = minigame.into_inner() else {
+            continue;
+        };
+        minigame.charge += minigame.output(&clock, time.delta_secs());
+        if minigame.charge < 1.0 {
+            continue;
+        }
+        let amount = minigame.charge.floor();
+        minigame.charge -= amount;
+        minigame.generated += amount;
+        commands.spawn(ItemBundle::new_from_minigame(
+            &mut images,
+            &mut generated_image_assets,
+            Item::energy(EnergyKind::Radiant, amount),
+            global_transform,
+            area,
+        ));
+        if minigame.should_level_up() {
+            commands.entity(entity).insert(LevelingUp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clock_at(time_of_day: f32) -> WorldClock {
+        WorldClock {
+            elapsed: DAY_SECONDS * time_of_day,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn facing_the_sun_pays_and_night_does_not() {
+        let solar = SolarMinigame::default();
+        let noon = solar.output(&clock_at(0.5), 1.0);
+        assert_eq!(noon, solar.peak_output());
+        assert_eq!(solar.output(&clock_at(0.9), 1.0), 0.0);
+    }
+
+    #[test]
+    fn tilting_toward_the_sun_helps() {
+        let morning = clock_at(0.35);
+        let mut solar = SolarMinigame::default();
+        let flat = solar.output(&morning, 1.0);
+        solar.tilt_toward(-1.0);
+        solar.tilt_toward(-1.0);
+        assert!(solar.output(&morning, 1.0) > flat);
+    }
+}
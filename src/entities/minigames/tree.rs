@@ -6,8 +6,8 @@ use crate::libs::*;
 pub const ID: &str = "tree";
 pub const POSITION: Vec2 = Vec2::new(-350.0, 250.0);
 
-pub const NAME: &str = "Tree";
-pub const DESCRIPTION: &str = "Pick fruits from the tree!";
+pub const NAME: &str = "tree.name";
+pub const DESCRIPTION: &str = "tree.description";
 const AREA: RectangularArea = RectangularArea {
     width: 300.0,
     height: 300.0,
@@ -21,23 +21,50 @@ const FRUIT_RADIUS: f32 = 8.0;
 // Centers at least this far apart so the fruit sprites don't overlap.
 const FRUIT_SPACING: f32 = FRUIT_RADIUS * 2.0 + 4.0;
 
+// Where grafted branches sprout from the crown, in grafting order: a point on
+// the branch's middle and its angle. One per fruit the tree can take a graft
+// of.
+const BRANCH_SLOTS: [(Vec2, f32); 2] = [
+    (Vec2::new(-105.0, 30.0), -0.4),
+    (Vec2::new(105.0, 30.0), 0.4),
+];
+const BRANCH_SIZE: Vec2 = Vec2::new(60.0, 7.0);
+const BRANCH_COLOR: Color = Color::srgb(0.4, 0.26, 0.13);
+// How often the tree's own fruit grows relative to a grafted branch's. A
+// branch's weight starts at 1 and grows by 1 a level up to this, so a fresh
+// graft bears little until the tree grows.
+const OWN_FRUIT_WEIGHT: u32 = 10;
+// Water drunk from puddles makes the tree lush, which shortens the wait for
+// its next fruit: at `MAX_LUSHNESS` it fruits twice as fast. Growing a fruit
+// uses it up.
+const WATER_PER_LUSHNESS: f32 = 50.0;
+const MAX_LUSHNESS: f32 = 2.0;
+
 #[derive(Debug, Clone, Component)]
 pub struct TreeMinigame {
     pub fruit: Species,
-    pub count: u32,
-    pub _lushness: f32,
+    // grafted branches' fruit, in grafting order
+    pub grafts: Vec<Species>,
+    // fruit on the tree, waiting to be picked
+    pub unpicked: Vec<Species>,
+    // 1 unwatered, up to `MAX_LUSHNESS`; divides the time between fruit
+    pub lushness: f32,
     pub last_fruit_time: f32,
     pub level: u8,
+    // fed in as items; `xp_for_levelup` of it grows the tree a level
+    pub xp: f32,
 }
 
 impl Default for TreeMinigame {
     fn default() -> Self {
         Self {
             fruit: Species::Apple,
-            count: 0,
-            _lushness: 1.0,
+            grafts: Vec::new(),
+            unpicked: Vec::new(),
+            lushness: 1.0,
             last_fruit_time: 0.0,
             level: 0,
+            xp: 0.0,
         }
     }
 }
@@ -71,52 +98,193 @@ impl TreeMinigame {
         Self::new(self.level + 1)
     }
 
+    // Grafts and unpicked fruit grow back on the new tree (see `spawn`).
+    pub fn carry_over(&self, next: &mut Self) {
+        next.fruit = self.fruit;
+        next.grafts = self.grafts.clone();
+        next.unpicked = self.unpicked.clone();
+        next.last_fruit_time = self.last_fruit_time;
+        next.xp = xp_left_after_levelup(self.level, self.xp);
+    }
+
+    pub fn progress(&self) -> f32 {
+        (self.xp / xp_for_levelup(self.level)).clamp(0.0, 1.0)
+    }
+
     pub fn spawn(
         &self,
         parent: &mut ChildSpawnerCommands,
+        random: &mut Random,
         asset_server: &AssetServer,
+        images: &mut Assets<Image>,
+        generated_image_assets: &mut image_gen::GeneratedImageAssets,
     ) {
         parent.spawn((
             Sprite {
-                image: asset_server.load("oak-tree-white-background-300x300.png"),
+                image: asset_server
+                    .load("oak-tree-white-background-300x300.png"),
                 color: Color::srgba(1.0, 1.0, 1.0, 1.0),
                 custom_size: Some(Vec2::new(AREA.width, AREA.height)),
                 ..default()
             },
             Transform::from_xyz(0.0, 0.0, 0.0),
         ));
+
+        for (slot, fruit) in self.grafts.iter().enumerate() {
+            parent.spawn(GraftedBranchBundle::new(*fruit, slot));
+        }
+
+        // fruit carried over from the previous level
+        let mut positions: Vec<Vec2> = Vec::new();
+        for fruit in self.unpicked.iter() {
+            let position = random_canopy_position(random, &positions);
+            positions.push(position);
+            parent.spawn(UnpickedFruitBundle::new(
+                images,
+                generated_image_assets,
+                parent.target_entity(),
+                *fruit,
+                Transform::from_xyz(position.x, position.y, 0.0),
+            ));
+        }
     }
 
-    pub fn ingest_item(&mut self) -> f32 {
-        0.0 // does not ingest items
+    // Fruit of a kind the tree doesn't bear yet grafts on a branch, which
+    // takes one of it. Everything else is refused, other than XP.
+    pub fn ingest_item(
+        &mut self,
+        commands: &mut Commands,
+        minigame_entity: Entity,
+        item: &Item,
+    ) -> f32 {
+        let Some(fruit) = self.graftable(item) else {
+            return 0.0;
+        };
+        let slot = self.grafts.len();
+        self.grafts.push(fruit);
+        commands.entity(minigame_entity).with_children(|parent| {
+            parent.spawn(GraftedBranchBundle::new(fruit, slot));
+        });
+        1.0
+    }
+
+    // How much of `item` `ingest_item` would take: one fruit to graft.
+    pub fn accepted_amount(&self, item: &Item) -> f32 {
+        if self.graftable(item).is_some() {
+            1.0
+        } else {
+            0.0
+        }
     }
 
     //
     // SPECIFIC
     //
 
-    pub fn add_fruit(&mut self) {
-        self.count += 1;
+    // Drinks fresh water until it's as lush as it gets. Returns how much of
+    // `item` it drank.
+    pub fn drink(&mut self, item: &Item) -> f32 {
+        let ItemType::Physical(PhysicalItem::Bulk(bulk)) = item.r#type else {
+            return 0.0;
+        };
+        if bulk.substance != Substance::FreshWater {
+            return 0.0;
+        }
+        let room = (MAX_LUSHNESS - self.lushness) * WATER_PER_LUSHNESS;
+        let drunk = item.amount.min(room.max(0.0));
+        self.lushness += drunk / WATER_PER_LUSHNESS;
+        drunk
+    }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.xp >= xp_for_levelup(self.level)
+    }
+
+    // The fruit `item` would graft on, if the tree has room for a branch of
+    // it.
+    fn graftable(&self, item: &Item) -> Option<Species> {
+        let ItemType::Physical(PhysicalItem::Discrete(d)) = item.r#type else {
+            return None;
+        };
+        if d.species.class() != DiscreteClass::Fruit
+            || d.species == self.fruit
+            || self.grafts.contains(&d.species)
+            || self.grafts.len() >= BRANCH_SLOTS.len()
+        {
+            return None;
+        }
+        Some(d.species)
+    }
+
+    // Each fruit the tree bears, weighted by how often it grows.
+    pub fn fruit_weights(&self) -> Vec<(Species, u32)> {
+        let branch_weight = (1 + self.level as u32).min(OWN_FRUIT_WEIGHT);
+        std::iter::once((self.fruit, OWN_FRUIT_WEIGHT))
+            .chain(self.grafts.iter().map(|fruit| (*fruit, branch_weight)))
+            .collect()
+    }
+
+    fn pick_fruit(&self, random: &mut Random) -> Species {
+        random
+            .pick_weighted(&self.fruit_weights(), |(_, weight)| *weight as u64)
+            .map_or(self.fruit, |(fruit, _)| *fruit)
+    }
+
+    pub fn add_fruit(&mut self, fruit: Species) {
+        self.unpicked.push(fruit);
     }
 
-    pub fn remove_fruit(&mut self) {
-        if self.count > 0 {
-            self.count -= 1;
+    pub fn remove_fruit(&mut self, fruit: Species) {
+        if let Some(index) = self.unpicked.iter().position(|f| *f == fruit) {
+            self.unpicked.swap_remove(index);
         }
     }
 }
 
+#[derive(Bundle)]
+pub struct GraftedBranchBundle {
+    pub branch: GraftedBranch,
+    pub sprite: Sprite,
+    pub transform: Transform,
+}
+
+impl GraftedBranchBundle {
+    pub fn new(fruit: Species, slot: usize) -> Self {
+        let (position, angle) = BRANCH_SLOTS[slot % BRANCH_SLOTS.len()];
+        Self {
+            branch: GraftedBranch { fruit },
+            sprite: Sprite {
+                color: BRANCH_COLOR,
+                custom_size: Some(BRANCH_SIZE),
+                ..default()
+            },
+            // under the fruit, over the tree
+            transform: Transform::from_xyz(position.x, position.y, 0.5)
+                .with_rotation(Quat::from_rotation_z(angle)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct GraftedBranch {
+    pub fruit: Species,
+}
+
 #[derive(Bundle)]
 pub struct UnpickedFruitBundle {
     pub unpicked_fruit: UnpickedFruit,
     pub area: CircularArea,
+    pub clickable: Clickable,
+    pub hoverable: Hoverable,
     pub sprite: Sprite,
     pub transform: Transform,
 }
 
 impl UnpickedFruitBundle {
     pub fn new(
-        asset_server: &AssetServer,
+        images: &mut Assets<Image>,
+        generated_image_assets: &mut image_gen::GeneratedImageAssets,
         minigame: Entity,
         fruit: Species,
         transform: Transform,
@@ -130,9 +298,15 @@ impl UnpickedFruitBundle {
                 minigame,
             },
             area,
+            clickable: Clickable::new(CLICK_PRIORITY_CONTENT),
+            hoverable: Hoverable,
             sprite: Sprite {
-                image: asset_server
-                    .load(Item::fruit(fruit, 1.0).asset()),
+                image: Item::fruit(fruit, 1.0).r#type.texture(
+                    images,
+                    generated_image_assets,
+                    FRUIT_RADIUS,
+                ),
+                custom_size: Some(area.into()),
                 ..default()
             },
             transform: Transform::from_xyz(
@@ -155,52 +329,41 @@ pub fn update(
     mut commands: Commands,
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
-    clickable_query: Query<(
-        Entity,
-        &UnpickedFruit,
-        &GlobalTransform,
-        &CircularArea,
-    )>,
-    camera_query: Query<(&Camera, &GlobalTransform)>,
-    window_query: Query<&Window>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
+    clickable_query: Query<&UnpickedFruit>,
+    mut click_events: MessageReader<ClickEvent>,
     mut tree_minigames_query: Query<(
         &mut Minigame,
         &GlobalTransform,
         &RectangularArea,
     )>,
 ) {
-    let Some(click_position) = get_click_release_position(
-        camera_query,
-        window_query,
-        mouse_button_input,
-    ) else {
-        return;
-    };
-
-    for (entity, fruit, global_transform, area) in clickable_query.iter() {
-        if area.is_within(
-            click_position,
-            global_transform.translation().truncate(),
-        ) {
-            // despawn_recursive so the fruit detaches from the tree minigame's
-            // Children list; a plain despawn leaves a stale child reference that
-            // the levelup despawn_recursive later hits (B0003).
-            commands.entity(entity).despawn();
-            let (minigame, minigame_transform, minigame_area) =
-                tree_minigames_query.get_mut(fruit.minigame).unwrap();
-
-            if let Minigame::Tree(tree_minigame) = minigame.into_inner() {
-                tree_minigame.remove_fruit();
-
-                commands.spawn(ItemBundle::new_from_minigame(
-                    &mut images,
-                    &mut generated_image_assets,
-                    Item::fruit(fruit.form, 1.0),
-                    minigame_transform,
-                    minigame_area,
-                ));
-            }
+    for click in click_events.read() {
+        if !click.is_release() {
+            continue;
+        }
+        let Some(entity) = click.target else {
+            continue;
+        };
+        let Ok(fruit) = clickable_query.get(entity) else {
+            continue;
+        };
+        // despawn_recursive so the fruit detaches from the tree minigame's
+        // Children list; a plain despawn leaves a stale child reference that
+        // the levelup despawn_recursive later hits (B0003).
+        commands.entity(entity).despawn();
+        let (minigame, minigame_transform, minigame_area) =
+            tree_minigames_query.get_mut(fruit.minigame).unwrap();
+
+        if let Minigame::Tree(tree_minigame) = minigame.into_inner() {
+            tree_minigame.remove_fruit(fruit.form);
+
+            commands.spawn(ItemBundle::new_from_minigame(
+                &mut images,
+                &mut generated_image_assets,
+                Item::fruit(fruit.form, 1.0),
+                minigame_transform,
+                minigame_area,
+            ));
         }
     }
 }
@@ -211,13 +374,16 @@ pub fn update(
 fn random_canopy_position(random: &mut Random, existing: &[Vec2]) -> Vec2 {
     let mut candidate = Vec2::ZERO;
     for _ in 0..24 {
-        let fx = (random.next() % 10_000) as f32 / 10_000.0;
-        let fy = (random.next() % 10_000) as f32 / 10_000.0;
+        let fx = random.next_f32();
+        let fy = random.next_f32();
         candidate = Vec2::new(
             CANOPY_MIN.x + fx * (CANOPY_MAX.x - CANOPY_MIN.x),
             CANOPY_MIN.y + fy * (CANOPY_MAX.y - CANOPY_MIN.y),
         );
-        if existing.iter().all(|p| p.distance(candidate) >= FRUIT_SPACING) {
+        if existing
+            .iter()
+            .all(|p| p.distance(candidate) >= FRUIT_SPACING)
+        {
             return candidate;
         }
     }
@@ -228,7 +394,9 @@ fn random_canopy_position(random: &mut Random, existing: &[Vec2]) -> Vec2 {
 pub fn fixed_update(
     mut commands: Commands,
     time: Res<Time>,
-    asset_server: Res<AssetServer>,
+    clock: Res<WorldClock>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
     mut random: ResMut<Random>,
     mut minigame_query: Query<(Entity, &mut Minigame)>,
     leveling_up_query: Query<&LevelingUp>,
@@ -243,13 +411,18 @@ pub fn fixed_update(
             continue;
         };
 
+        // Fruit waits here to be picked, so an unwatched tree stops at this
+        // many and has nothing to batch (batching.rs).
         let max_fruit = 1 + (tree_minigame.level / 10) as u32;
-        if tree_minigame.count >= max_fruit {
+        if tree_minigame.unpicked.len() as u32 >= max_fruit {
             continue;
         }
 
-        let needed_time_seconds =
-            5.0 - (tree_minigame.level as f32 * 0.05).min(4.0);
+        // fruits faster in the rain, and when watered
+        let needed_time_seconds = (5.0
+            - (tree_minigame.level as f32 * 0.05).min(4.0))
+            / clock.growth_rate()
+            / tree_minigame.lushness;
         let elapsed_seconds = time.elapsed_secs();
 
         if elapsed_seconds - tree_minigame.last_fruit_time
@@ -259,8 +432,9 @@ pub fn fixed_update(
         }
 
         tree_minigame.last_fruit_time = elapsed_seconds;
-        tree_minigame.add_fruit();
-        let fruit = tree_minigame.fruit;
+        tree_minigame.lushness = 1.0;
+        let fruit = tree_minigame.pick_fruit(&mut random);
+        tree_minigame.add_fruit(fruit);
 
         // Scatter the new fruit across the canopy, clear of the others.
         let existing: Vec<Vec2> = fruit_query
@@ -272,7 +446,8 @@ pub fn fixed_update(
 
         commands.entity(entity).with_children(|parent| {
             parent.spawn(UnpickedFruitBundle::new(
-                &asset_server,
+                &mut images,
+                &mut generated_image_assets,
                 entity,
                 fruit,
                 Transform::from_xyz(position.x, position.y, 0.0),
@@ -280,3 +455,45 @@ pub fn fixed_update(
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grafts_take_one_fruit_of_each_other_kind() {
+        let mut tree = TreeMinigame::default();
+        assert_eq!(tree.graftable(&Item::fruit(Species::Apple, 1.0)), None);
+        let fish = Item::organism(Species::Fish, LifeStage::Adult, 1.0);
+        assert_eq!(tree.graftable(&fish), None);
+        let lemon = Item::fruit(Species::Lemon, 3.0);
+        assert_eq!(tree.graftable(&lemon), Some(Species::Lemon));
+
+        tree.grafts.push(Species::Lemon);
+        assert_eq!(tree.graftable(&lemon), None, "already grafted");
+        let lime = Item::fruit(Species::Lime, 1.0);
+        assert_eq!(tree.graftable(&lime), Some(Species::Lime));
+    }
+
+    #[test]
+    fn grafted_fruit_grows_more_as_the_tree_levels() {
+        let mut tree = TreeMinigame::new(0);
+        assert_eq!(tree.fruit_weights(), vec![(Species::Apple, 10)]);
+        tree.grafts.push(Species::Lime);
+        assert_eq!(
+            tree.fruit_weights(),
+            vec![(Species::Apple, 10), (Species::Lime, 1)]
+        );
+        tree.level = 30;
+        assert_eq!(
+            tree.fruit_weights(),
+            vec![(Species::Apple, 10), (Species::Lime, 10)]
+        );
+
+        let mut random = Random::new(3);
+        let limes = (0..1000)
+            .filter(|_| tree.pick_fruit(&mut random) == Species::Lime)
+            .count();
+        assert!((400..600).contains(&limes), "{} limes in 1000", limes);
+    }
+}
@@ -0,0 +1,273 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// A turbine whose blades catch whatever flies past. Loose items moving fast
+// enough through its aura are slowed down, and the kinetic energy they lose
+// comes out as Kinetic energy items. Faster and bigger throws pay more.
+
+pub const ID: &str = "turbine";
+pub const POSITION: Vec2 = Vec2::new(-350.0, 600.0);
+
+pub const NAME: &str = "turbine.name";
+pub const DESCRIPTION: &str = "turbine.description";
+const AREA: RectangularArea = RectangularArea {
+    width: 140.0,
+    height: 140.0,
+};
+
+// Items slower than this, in pixels per second, don't turn the blades. Just
+// above the speed minigames spit items out at.
+pub const MIN_SPEED: f32 = 120.0;
+// What's left of an item's speed after it passes.
+const SPEED_KEPT: f32 = 0.5;
+const PIXELS_PER_METER: f32 = 100.0;
+// Share of the kinetic energy taken that comes out as items.
+const EFFICIENCY: f32 = 0.5;
+const BLADE_LENGTH: f32 = 55.0;
+// Blade spin, in radians per second, per unit of energy taken.
+const SPIN_PER_ENERGY: f32 = 4.0;
+// Share of its spin the rotor keeps each second.
+const SPIN_KEPT_PER_SECOND: f32 = 0.5;
+
+#[derive(Debug, Clone, Default, Component)]
+pub struct TurbineMinigame {
+    pub level: u8,
+    // energy taken but not yet a whole item
+    pub charge: f32,
+    // Kinetic energy given off so far
+    pub generated: f32,
+    // fed in as items, counts as extra energy generated
+    pub xp: f32,
+}
+
+impl TurbineMinigame {
+    pub fn new(generated: f32) -> Self {
+        Self {
+            level: Self::level_by_generated(generated),
+            generated,
+            ..default()
+        }
+    }
+
+    //
+    // COMMON
+    //
+
+    pub fn name(&self) -> &str {
+        NAME
+    }
+
+    pub fn description(&self) -> &str {
+        DESCRIPTION
+    }
+
+    pub fn area(&self) -> RectangularArea {
+        AREA
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn levelup(&self) -> Self {
+        Self::new(self.level_basis())
+    }
+
+    pub fn carry_over(&self, next: &mut Self) {
+        next.charge = self.charge;
+        next.generated = self.generated;
+        next.xp = self.xp;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.level_basis(), self.level)
+    }
+
+    pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
+        let minigame = parent.target_entity();
+        parent.spawn((
+            Sprite {
+                color: Color::srgb(0.9, 0.95, 0.9),
+                custom_size: Some(AREA.into()),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, -1.0),
+        ));
+        parent
+            .spawn((
+                Rotor {
+                    minigame,
+                    spin: 0.0,
+                },
+                Transform::default(),
+                Visibility::default(),
+            ))
+            .with_children(|parent| {
+                for i in 0..3 {
+                    let angle = i as f32 * std::f32::consts::TAU / 3.0;
+                    let direction = Vec2::from_angle(angle);
+                    parent.spawn((
+                        Sprite::from_color(
+                            Color::srgb(0.3, 0.3, 0.35),
+                            Vec2::new(BLADE_LENGTH, 8.0),
+                        ),
+                        Transform::from_translation(
+                            (direction * BLADE_LENGTH / 2.0).extend(0.0),
+                        )
+                        .with_rotation(Quat::from_rotation_z(angle)),
+                    ));
+                }
+                parent.spawn((
+                    ShapeBuilder::with(&shapes::Circle {
+                        radius: 8.0,
+                        ..default()
+                    })
+                    .fill(Fill::color(Color::srgb(0.15, 0.15, 0.15)))
+                    .build(),
+                    Transform::from_xyz(0.0, 0.0, 1.0),
+                ));
+            });
+    }
+
+    pub fn ingest_item(&self) -> f32 {
+        0.0 // items pass by; see `catch_items_fixed_update`
+    }
+
+    //
+    // SPECIFIC
+    //
+
+    // Energy generated plus XP: what the level is computed from.
+    pub fn level_basis(&self) -> f32 {
+        self.generated + self.xp
+    }
+
+    pub fn should_level_up(&self) -> bool {
+        Self::level_by_generated(self.level_basis()) > self.level
+    }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.should_level_up()
+    }
+
+    pub fn level_by_generated(generated: f32) -> u8 {
+        if generated <= 0.0 {
+            0
+        } else {
+            ((generated.log2() + 1.0) as u8).min(99)
+        }
+    }
+
+    // Kinetic energy taken from an item of `mass` (its amount) passing at
+    // `speed` pixels per second, slowing it to `SPEED_KEPT` of that. Higher
+    // levels turn more of it into items.
+    pub fn energy_taken(&self, mass: f32, speed: f32) -> f32 {
+        if speed < MIN_SPEED {
+            return 0.0;
+        }
+        let speed = speed / PIXELS_PER_METER;
+        let lost = 0.5 * mass * speed * speed * (1.0 - SPEED_KEPT * SPEED_KEPT);
+        lost * EFFICIENCY * (1.0 + self.level as f32 * 0.1)
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct Rotor {
+    pub minigame: Entity,
+    // radians per second
+    pub spin: f32,
+}
+
+// Items entering the aura fast give up some of their speed to the blades.
+pub fn catch_items_fixed_update(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut contacts: MessageReader<contact::ItemAura>,
+    aura_query: Query<&MinigameAura>,
+    mut item_query: Query<(&Item, &mut Velocity), Without<Stuck>>,
+    mut minigame_query: Query<
+        (&mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+    mut rotor_query: Query<&mut Rotor>,
+) {
+    for contact in contacts.read() {
+        let item_entity = contact.item;
+        let Ok(aura) = aura_query.get(contact.aura) else {
+            continue;
+        };
+        let Ok((minigame, global_transform, area)) =
+            minigame_query.get_mut(aura.minigame)
+        else {
+            continue;
+        };
+        let Minigame::Turbine(minigame) = minigame.into_inner() else {
+            continue;
+        };
+        let Ok((item, mut velocity)) = item_query.get_mut(item_entity) else {
+            continue;
+        };
+
+        let energy =
+            minigame.energy_taken(item.amount, velocity.linear.length());
+        if energy == 0.0 {
+            continue;
+        }
+        velocity.linear *= SPEED_KEPT;
+        for mut rotor in rotor_query.iter_mut() {
+            if rotor.minigame == aura.minigame {
+                rotor.spin += energy * SPIN_PER_ENERGY;
+            }
+        }
+
+        minigame.charge += energy;
+        if minigame.charge < 1.0 {
+            continue;
+        }
+        let amount = minigame.charge.floor();
+        minigame.charge -= amount;
+        minigame.generated += amount;
+        commands.spawn(ItemBundle::new_from_minigame(
+            &mut images,
+            &mut generated_image_assets,
+            Item::energy(EnergyKind::Kinetic, amount),
+            global_transform,
+            area,
+        ));
+        if minigame.should_level_up() {
+            commands.entity(aura.minigame).insert(LevelingUp);
+        }
+    }
+}
+
+// Turn the blades, winding down between throws.
+pub fn spin_update(
+    time: Res<Time>,
+    mut rotor_query: Query<(&mut Rotor, &mut Transform)>,
+) {
+    let seconds = time.delta_secs();
+    for (mut rotor, mut transform) in rotor_query.iter_mut() {
+        transform.rotate_z(-rotor.spin * seconds);
+        rotor.spin *= SPIN_KEPT_PER_SECOND.powf(seconds);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn faster_and_heavier_take_more() {
+        let turbine = TurbineMinigame::default();
+        assert_eq!(turbine.energy_taken(1.0, MIN_SPEED - 1.0), 0.0);
+        let slow = turbine.energy_taken(1.0, 200.0);
+        assert!(turbine.energy_taken(1.0, 400.0) > slow);
+        assert!(turbine.energy_taken(3.0, 200.0) > slow);
+    }
+}
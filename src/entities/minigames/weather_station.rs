@@ -0,0 +1,463 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// A weather station. It reads the world clock out as a forecast: the day, how
+// long until dawn or dusk, and whether it's raining and for how long. It
+// takes Water mana and Radiant energy, and levels on all it's been fed. From
+// `RAIN_LEVEL` a button spends Water mana to bring rain on at once; from
+// `DAYLIGHT_LEVEL` another spends Radiant energy to hold the sun where it is.
+// Each has its own cooldown.
+
+pub const ID: &str = "weather_station";
+pub const POSITION: Vec2 = Vec2::new(700.0, 550.0);
+
+pub const NAME: &str = "weather_station.name";
+pub const DESCRIPTION: &str = "weather_station.description";
+const AREA: RectangularArea = RectangularArea {
+    width: 180.0,
+    height: 140.0,
+};
+
+pub const RAIN_LEVEL: u8 = 2;
+pub const RAIN_COST: f32 = 5.0;
+const RAIN_SECONDS: f32 = 30.0;
+const RAIN_COOLDOWN: f32 = 60.0;
+pub const DAYLIGHT_LEVEL: u8 = 4;
+pub const DAYLIGHT_COST: f32 = 10.0;
+const DAYLIGHT_SECONDS: f32 = 30.0;
+const DAYLIGHT_COOLDOWN: f32 = 120.0;
+
+const CONTROL_SIZE: Vec2 = Vec2::new(80.0, 26.0);
+const CONTROL_Y: f32 = -50.0;
+const CONTROL_COLOR: Color = Color::srgb(0.75, 0.8, 0.85);
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Nudge {
+    Rain,
+    Daylight,
+}
+
+#[derive(Debug, Clone, Default, Component)]
+pub struct WeatherStationMinigame {
+    pub level: u8,
+    // Water mana on hand, for rain
+    pub water: f32,
+    // Radiant energy on hand, for daylight
+    pub light: f32,
+    // everything fed in so far
+    pub fed: f32,
+    // seconds until each can be used again
+    pub rain_cooldown: f32,
+    pub daylight_cooldown: f32,
+    // fed in as items, counts as extra fed
+    pub xp: f32,
+}
+
+impl WeatherStationMinigame {
+    pub fn new(fed: f32) -> Self {
+        Self {
+            level: Self::level_by_fed(fed),
+            fed,
+            ..default()
+        }
+    }
+
+    //
+    // COMMON
+    //
+
+    pub fn name(&self) -> &str {
+        NAME
+    }
+
+    pub fn description(&self) -> &str {
+        DESCRIPTION
+    }
+
+    pub fn area(&self) -> RectangularArea {
+        AREA
+    }
+
+    pub fn level(&self) -> u8 {
+        self.level
+    }
+
+    pub fn levelup(&self) -> Self {
+        Self::new(self.level_basis())
+    }
+
+    pub fn carry_over(&self, next: &mut Self) {
+        next.water = self.water;
+        next.light = self.light;
+        next.fed = self.fed;
+        next.rain_cooldown = self.rain_cooldown;
+        next.daylight_cooldown = self.daylight_cooldown;
+        next.xp = self.xp;
+    }
+
+    pub fn progress(&self) -> f32 {
+        log2_level_progress(self.level_basis(), self.level)
+    }
+
+    pub fn spawn(&self, parent: &mut ChildSpawnerCommands) {
+        let minigame = parent.target_entity();
+        parent.spawn((
+            Sprite {
+                color: Color::srgb(0.55, 0.7, 0.85),
+                custom_size: Some(AREA.into()),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, -1.0),
+        ));
+        // Filled in by `update_forecast`.
+        parent.spawn((
+            Forecast { minigame },
+            Text2d::new(""),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(Color::BLACK),
+            Transform::from_xyz(0.0, 20.0, 0.0),
+        ));
+        let controls = [
+            (Nudge::Rain, RAIN_LEVEL, -AREA.width / 4.0),
+            (Nudge::Daylight, DAYLIGHT_LEVEL, AREA.width / 4.0),
+        ];
+        for (nudge, level, x) in controls {
+            if self.level < level {
+                continue;
+            }
+            parent
+                .spawn((
+                    Control { minigame, nudge },
+                    RectangularArea::new(CONTROL_SIZE.x, CONTROL_SIZE.y),
+                    Clickable::new(CLICK_PRIORITY_CONTENT),
+                    Hoverable,
+                    Sprite::from_color(CONTROL_COLOR, CONTROL_SIZE),
+                    Transform::from_xyz(x, CONTROL_Y, 0.0),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        ControlText { minigame, nudge },
+                        Text2d::new(""),
+                        TextFont {
+                            font_size: 11.0,
+                            ..default()
+                        },
+                        TextColor(Color::BLACK),
+                        Transform::from_xyz(0.0, 0.0, 0.1),
+                    ));
+                });
+        }
+    }
+
+    pub fn ingest_item(&mut self, item: &Item) -> f32 {
+        match Self::store_for(item) {
+            Some(Nudge::Rain) => self.water += item.amount,
+            Some(Nudge::Daylight) => self.light += item.amount,
+            None => return 0.0,
+        }
+        self.fed += item.amount;
+        item.amount
+    }
+
+    //
+    // SPECIFIC
+    //
+
+    // Which button `item` pays for, if any.
+    pub fn store_for(item: &Item) -> Option<Nudge> {
+        match item.r#type {
+            ItemType::Mana(ManaItem {
+                kind: ManaKind::Water,
+                ..
+            }) => Some(Nudge::Rain),
+            ItemType::Energy(EnergyItem {
+                kind: EnergyKind::Radiant,
+            }) => Some(Nudge::Daylight),
+            _ => None,
+        }
+    }
+
+    pub fn accepted_amount(item: &Item) -> f32 {
+        if Self::store_for(item).is_some() {
+            item.amount
+        } else {
+            0.0
+        }
+    }
+
+    // Everything fed plus XP: what the level is computed from.
+    pub fn level_basis(&self) -> f32 {
+        self.fed + self.xp
+    }
+
+    pub fn should_level_up(&self) -> bool {
+        Self::level_by_fed(self.level_basis()) > self.level
+    }
+
+    pub fn add_xp(&mut self, amount: f32) -> bool {
+        self.xp += amount;
+        self.should_level_up()
+    }
+
+    pub fn level_by_fed(fed: f32) -> u8 {
+        if fed <= 0.0 {
+            0
+        } else {
+            ((fed.log2() + 1.0) as u8).min(99)
+        }
+    }
+
+    pub fn cooldown(&self, nudge: Nudge) -> f32 {
+        match nudge {
+            Nudge::Rain => self.rain_cooldown,
+            Nudge::Daylight => self.daylight_cooldown,
+        }
+    }
+
+    // Whether `nudge` can go now: the level's reached, it's off cooldown,
+    // enough is stored, and for daylight, it's day.
+    pub fn can_nudge(&self, nudge: Nudge, clock: &WorldClock) -> bool {
+        let (level, stored, cost) = match nudge {
+            Nudge::Rain => (RAIN_LEVEL, self.water, RAIN_COST),
+            Nudge::Daylight => (DAYLIGHT_LEVEL, self.light, DAYLIGHT_COST),
+        };
+        self.level >= level
+            && self.cooldown(nudge) <= 0.0
+            && stored >= cost
+            && (nudge != Nudge::Daylight || clock.is_day())
+    }
+
+    // Spend what `nudge` costs and apply it to the clock. Returns whether it
+    // went through.
+    pub fn nudge(&mut self, nudge: Nudge, clock: &mut WorldClock) -> bool {
+        if !self.can_nudge(nudge, clock) {
+            return false;
+        }
+        match nudge {
+            Nudge::Rain => {
+                self.water -= RAIN_COST;
+                self.rain_cooldown = RAIN_COOLDOWN;
+                clock.bring_rain(RAIN_SECONDS);
+            }
+            Nudge::Daylight => {
+                self.light -= DAYLIGHT_COST;
+                self.daylight_cooldown = DAYLIGHT_COOLDOWN;
+                clock.hold_daylight(DAYLIGHT_SECONDS);
+            }
+        }
+        true
+    }
+
+    pub fn cool_down(&mut self, seconds: f32) {
+        self.rain_cooldown = (self.rain_cooldown - seconds).max(0.0);
+        self.daylight_cooldown = (self.daylight_cooldown - seconds).max(0.0);
+    }
+
+    pub fn forecast(&self, clock: &WorldClock) -> String {
+        let turn = if clock.is_day() {
+            "weather_station.until_dusk"
+        } else {
+            "weather_station.until_dawn"
+        };
+        let sky = if clock.is_raining() {
+            tr_fmt(
+                "weather_station.raining",
+                &[("seconds", &(clock.rain_left.ceil() as u32))],
+            )
+        } else {
+            tr("weather_station.dry")
+        };
+        let mut lines = vec![
+            tr_fmt("weather_station.day", &[("day", &(clock.day() + 1))]),
+            tr_fmt(turn, &[("seconds", &(clock.until_turn().ceil() as u32))]),
+            sky,
+        ];
+        if clock.is_held() {
+            lines.push(tr_fmt(
+                "weather_station.held",
+                &[("seconds", &(clock.held_left.ceil() as u32))],
+            ));
+        }
+        lines.join("\n")
+    }
+
+    pub fn control_label(&self, nudge: Nudge) -> String {
+        let cooldown = self.cooldown(nudge);
+        if cooldown > 0.0 {
+            return tr_fmt(
+                "weather_station.cooldown",
+                &[("seconds", &(cooldown.ceil() as u32))],
+            );
+        }
+        match nudge {
+            Nudge::Rain => tr_fmt(
+                "weather_station.rain",
+                &[
+                    ("stored", &format_amount(self.water.floor())),
+                    ("cost", &RAIN_COST),
+                ],
+            ),
+            Nudge::Daylight => tr_fmt(
+                "weather_station.daylight",
+                &[
+                    ("stored", &format_amount(self.light.floor())),
+                    ("cost", &DAYLIGHT_COST),
+                ],
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct Forecast {
+    pub minigame: Entity,
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct Control {
+    pub minigame: Entity,
+    pub nudge: Nudge,
+}
+
+#[derive(Debug, Clone, Component)]
+pub struct ControlText {
+    pub minigame: Entity,
+    pub nudge: Nudge,
+}
+
+pub fn control_click_update(
+    mut click_events: MessageReader<ClickEvent>,
+    mut clock: ResMut<WorldClock>,
+    control_query: Query<&Control>,
+    mut minigame_query: Query<&mut Minigame, Without<LevelingUp>>,
+) {
+    for click in click_events.read() {
+        if click.kind != ClickKind::Short {
+            continue;
+        }
+        let Some(Ok(control)) = click.target.map(|e| control_query.get(e))
+        else {
+            continue;
+        };
+        let Ok(minigame) = minigame_query.get_mut(control.minigame) else {
+            continue;
+        };
+        let Minigame::WeatherStation(minigame) = minigame.into_inner() else {
+            continue;
+        };
+        minigame.nudge(control.nudge, &mut clock);
+    }
+}
+
+pub fn fixed_update(
+    time: Res<Time>,
+    mut minigame_query: Query<&mut Minigame, Without<LevelingUp>>,
+) {
+    for minigame in minigame_query.iter_mut() {
+        let Minigame::WeatherStation(minigame) = minigame.into_inner() else {
+            continue;
+        };
+        minigame.cool_down(time.delta_secs());
+    }
+}
+
+pub fn update_forecast(
+    clock: Res<WorldClock>,
+    minigame_query: Query<&Minigame>,
+    mut forecast_query: Query<(&Forecast, &mut Text2d), Without<ControlText>>,
+    mut control_text_query: Query<(&ControlText, &mut Text2d)>,
+) {
+    for (forecast, mut text) in forecast_query.iter_mut() {
+        let Ok(Minigame::WeatherStation(station)) =
+            minigame_query.get(forecast.minigame)
+        else {
+            continue;
+        };
+        let forecast = station.forecast(&clock);
+        if text.0 != forecast {
+            text.0 = forecast;
+        }
+    }
+    for (control, mut text) in control_text_query.iter_mut() {
+        let Ok(Minigame::WeatherStation(station)) =
+            minigame_query.get(control.minigame)
+        else {
+            continue;
+        };
+        let label = station.control_label(control.nudge);
+        if text.0 != label {
+            text.0 = label;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn noon() -> WorldClock {
+        WorldClock {
+            elapsed: DAY_SECONDS / 2.0,
+            ..default()
+        }
+    }
+
+    fn station(level: u8) -> WeatherStationMinigame {
+        WeatherStationMinigame {
+            level,
+            water: RAIN_COST,
+            light: DAYLIGHT_COST,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn takes_water_mana_and_radiant_energy() {
+        let water = Item::mana(ManaKind::Water, ManaIntent::Support, 2.0);
+        let fire = Item::mana(ManaKind::Fire, ManaIntent::Support, 2.0);
+        let light = Item::energy(EnergyKind::Radiant, 3.0);
+        let mut station = WeatherStationMinigame::default();
+        assert_eq!(station.ingest_item(&water), 2.0);
+        assert_eq!(station.ingest_item(&fire), 0.0);
+        assert_eq!(station.ingest_item(&light), 3.0);
+        assert_eq!(
+            (station.water, station.light, station.fed),
+            (2.0, 3.0, 5.0)
+        );
+        assert!(station.should_level_up());
+    }
+
+    #[test]
+    fn rain_needs_the_level_the_mana_and_no_cooldown() {
+        let mut clock = noon();
+        assert!(!station(RAIN_LEVEL - 1).nudge(Nudge::Rain, &mut clock));
+        let mut station = station(RAIN_LEVEL);
+        assert!(station.nudge(Nudge::Rain, &mut clock));
+        assert_eq!(clock.rain_left, RAIN_SECONDS);
+        assert_eq!(station.water, 0.0);
+        station.water = RAIN_COST;
+        assert!(!station.nudge(Nudge::Rain, &mut clock), "cooling down");
+        station.cool_down(RAIN_COOLDOWN);
+        assert!(station.nudge(Nudge::Rain, &mut clock));
+    }
+
+    #[test]
+    fn daylight_holds_only_by_day() {
+        let mut night = WorldClock {
+            elapsed: 0.0,
+            ..default()
+        };
+        let mut station = station(DAYLIGHT_LEVEL);
+        assert!(!station.nudge(Nudge::Daylight, &mut night));
+        assert_eq!(station.light, DAYLIGHT_COST);
+        let mut clock = noon();
+        assert!(station.nudge(Nudge::Daylight, &mut clock));
+        assert!(clock.is_held());
+        assert_eq!(station.daylight_cooldown, DAYLIGHT_COOLDOWN);
+    }
+}
@@ -1,7 +1,29 @@
+pub mod aura_highlight;
+pub mod batching;
+pub mod contact;
+pub mod cosmetics;
+pub mod dash;
+pub mod ejection;
+pub mod electric;
+pub mod gas;
+pub mod heat;
 pub mod item;
+pub mod item_labels;
+pub mod magnet;
 pub mod minigame;
 pub mod minigames;
+pub mod notes;
+pub mod overflow;
+pub mod pins;
+pub mod placement;
 pub mod player;
+pub mod puddle;
+pub mod reaction;
+pub mod recall;
+pub mod routing;
+pub mod sticky;
+pub mod throughput;
+pub mod tutorial;
 
 pub use item::*;
 pub use minigame::*;
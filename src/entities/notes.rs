@@ -0,0 +1,322 @@
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Notes: signposts the player leaves on the board, "feed iron here" or "TODO:
+// build battery farm". N opens a line at the bottom of the screen to write
+// one on; Tab changes its icon, Enter puts it down where the player stands
+// and Escape gives up on it. While the line is open, typed keys go to it
+// instead of moving the player, as with the console. Hovering a note shows
+// its text, and a long click on it pulls it up. Saves keep them.
+
+pub const MAX_NOTE_CHARS: usize = 40;
+const NOTE_RADIUS: f32 = 10.0;
+const GLYPH_FONT_SIZE: f32 = 14.0;
+const COMPOSER_FONT_SIZE: f32 = 16.0;
+// under items and the player
+const NOTE_Z: f32 = -0.5;
+
+#[derive(
+    Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize,
+)]
+pub enum NoteIcon {
+    #[default]
+    Info,
+    Warning,
+    Question,
+    Star,
+}
+
+impl NoteIcon {
+    pub const ALL: [Self; 4] =
+        [Self::Info, Self::Warning, Self::Question, Self::Star];
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|icon| *icon == self);
+        Self::ALL[index.map_or(0, |i| (i + 1) % Self::ALL.len())]
+    }
+
+    pub fn glyph(self) -> &'static str {
+        match self {
+            Self::Info => "i",
+            Self::Warning => "!",
+            Self::Question => "?",
+            Self::Star => "*",
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Info => Color::srgb(0.45, 0.7, 1.0),
+            Self::Warning => Color::srgb(1.0, 0.65, 0.2),
+            Self::Question => Color::srgb(0.75, 0.55, 1.0),
+            Self::Star => Color::srgb(1.0, 0.9, 0.3),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct Note {
+    pub icon: NoteIcon,
+    pub text: String,
+}
+
+// The note being written, while the line is open.
+#[derive(Debug, Default, Resource)]
+pub struct NoteComposer {
+    pub open: bool,
+    // kept from one note to the next
+    pub icon: NoteIcon,
+    pub text: String,
+}
+
+impl NoteComposer {
+    pub fn push(&mut self, c: char) {
+        if !c.is_control() && self.text.chars().count() < MAX_NOTE_CHARS {
+            self.text.push(c);
+        }
+    }
+
+    // Closes the line, handing over what was written on it, if anything.
+    pub fn finish(&mut self) -> Option<Note> {
+        self.open = false;
+        let text = std::mem::take(&mut self.text).trim().to_string();
+        (!text.is_empty()).then_some(Note {
+            icon: self.icon,
+            text,
+        })
+    }
+}
+
+// Run condition for input that mustn't fire while a note is being written.
+pub fn composer_closed(composer: Option<Res<NoteComposer>>) -> bool {
+    composer.is_none_or(|composer| !composer.open)
+}
+
+#[derive(Debug, Component)]
+pub struct ComposerText;
+
+pub fn spawn_note(
+    commands: &mut Commands,
+    note: Note,
+    position: Vec2,
+) -> Entity {
+    let icon = note.icon;
+    commands
+        .spawn((
+            HoverText::new(note.text.clone()),
+            note,
+            CircularArea {
+                radius: NOTE_RADIUS,
+            },
+            Clickable::new(CLICK_PRIORITY_CONTENT),
+            Hoverable,
+            ShapeBuilder::with(&shapes::Circle {
+                radius: NOTE_RADIUS,
+                ..default()
+            })
+            .fill(Fill::color(icon.color()))
+            .stroke(Stroke::new(Color::BLACK, 1.5))
+            .build(),
+            Transform::from_translation(position.extend(NOTE_Z)),
+        ))
+        .with_child((
+            Text2d::new(icon.glyph()),
+            TextFont {
+                font_size: GLYPH_FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::BLACK),
+            Transform::from_xyz(0.0, 0.0, 0.1),
+        ))
+        .id()
+}
+
+pub fn composer_text(composer: &NoteComposer) -> String {
+    tr_fmt(
+        "notes.composer",
+        &[("icon", &composer.icon.glyph()), ("text", &composer.text)],
+    )
+}
+
+pub fn setup_composer(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(48.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_child((
+            ComposerText,
+            Node {
+                padding: UiRect::axes(Val::Px(10.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+            Text::new(""),
+            TextFont {
+                font_size: COMPOSER_FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Visibility::Hidden,
+        ));
+}
+
+// N opens the line; then typed keys write on it until Enter or Escape.
+pub fn compose_note(
+    mut commands: Commands,
+    mut kb_input: ResMut<ButtonInput<KeyCode>>,
+    mut keyboard_events: MessageReader<KeyboardInput>,
+    console: Res<Console>,
+    mut composer: ResMut<NoteComposer>,
+    player_query: Query<&Transform, With<Player>>,
+) {
+    if !composer.open {
+        // the N that opens the line isn't written on it
+        keyboard_events.clear();
+        if kb_input.just_pressed(KeyCode::KeyN) && !console.open {
+            composer.open = true;
+        }
+        return;
+    }
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let note = composer.finish();
+                if let (Some(note), Ok(transform)) =
+                    (note, player_query.single())
+                {
+                    spawn_note(
+                        &mut commands,
+                        note,
+                        transform.translation.truncate(),
+                    );
+                }
+                break;
+            }
+            Key::Escape => {
+                composer.open = false;
+                composer.text.clear();
+                break;
+            }
+            Key::Tab => composer.icon = composer.icon.next(),
+            Key::Backspace => {
+                composer.text.pop();
+            }
+            _ => {
+                let Some(text) = &event.text else {
+                    continue;
+                };
+                for c in text.chars() {
+                    composer.push(c);
+                }
+            }
+        }
+    }
+    if !composer.open {
+        // so closing the line doesn't also quit the game
+        kb_input.clear_just_pressed(KeyCode::Escape);
+    }
+}
+
+// A long click on a note pulls it up, and Ctrl+Z puts it back.
+pub fn note_click_update(
+    mut commands: Commands,
+    mut click_events: MessageReader<ClickEvent>,
+    mut undo: ResMut<UndoStack>,
+    note_query: Query<(&Note, &Transform)>,
+) {
+    for click in click_events.read() {
+        if click.kind != ClickKind::Long {
+            continue;
+        }
+        let Some(entity) = click.target else {
+            continue;
+        };
+        let Ok((note, transform)) = note_query.get(entity) else {
+            continue;
+        };
+        undo.push(UndoAction::NoteDeleted {
+            note: note.clone(),
+            position: transform.translation.truncate(),
+        });
+        commands.entity(entity).despawn();
+    }
+}
+
+pub fn render_composer(
+    composer: Res<NoteComposer>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<ComposerText>>,
+) {
+    if !composer.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        *visibility = if composer.open {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        text.0 = composer_text(&composer);
+    }
+}
+
+pub struct NotesPlugin;
+
+impl Plugin for NotesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NoteComposer>()
+            .add_systems(Startup, setup_composer)
+            .add_systems(
+                Update,
+                (
+                    compose_note
+                        .run_if(photo_mode::photo_mode_off)
+                        // ahead of Escape quitting the game
+                        .before(GameSet::Input),
+                    note_click_update.after(GameSet::Input),
+                    render_composer,
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notes_are_short_and_never_blank() {
+        let mut composer = NoteComposer {
+            open: true,
+            ..default()
+        };
+        for c in "  feed iron here\n".chars() {
+            composer.push(c);
+        }
+        let note = composer.finish().unwrap();
+        assert_eq!(note.text, "feed iron here");
+        assert!(!composer.open);
+
+        composer.open = true;
+        for _ in 0..MAX_NOTE_CHARS * 2 {
+            composer.push('x');
+        }
+        assert_eq!(composer.text.len(), MAX_NOTE_CHARS);
+
+        composer.text = "   ".to_string();
+        assert_eq!(composer.finish(), None);
+        assert_eq!(NoteIcon::Star.next(), NoteIcon::Info);
+    }
+}
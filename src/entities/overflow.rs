@@ -0,0 +1,156 @@
+use std::collections::HashSet;
+
+use bevy::ecs::system::SystemChangeTick;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// What happens when loose items pile up faster than anyone clears them, as
+// they do when minigames run unattended. Every item is an entity with a rigid
+// body, and enough of them bring the simulation to a crawl. Past one count
+// (`overflow.merge_above` in assets/balance.ron), items merge with nearby
+// items of their kind without waiting to touch. Past a second
+// (`overflow.deposit_above`), the oldest go into the nearest chest that takes
+// them. Both work through at most `overflow.per_tick` items a tick, so a
+// backlog clears over a few ticks instead of in one long one.
+
+pub fn crowded(
+    balance: Res<BalanceConfig>,
+    item_query: Query<(), (With<Item>, Without<Stuck>)>,
+) -> bool {
+    item_query.iter().len() > balance.overflow.merge_above
+}
+
+pub fn overcrowded(
+    balance: Res<BalanceConfig>,
+    item_query: Query<(), (With<Item>, Without<Stuck>)>,
+) -> bool {
+    item_query.iter().len() > balance.overflow.deposit_above
+}
+
+// Merges pairs of same-kind items within `overflow.merge_radius` of each
+// other, as if they had touched, charge and all.
+pub fn merge_crowded_items(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    balance: Res<BalanceConfig>,
+    item_grid: Res<ItemGrid>,
+    item_query: Query<
+        (Entity, &Item, &Transform, &Velocity),
+        (Without<Stuck>, Without<ejection::Ejecting>),
+    >,
+    charge_query: Query<&electric::Charge>,
+) {
+    let overflow = &balance.overflow;
+    // each item merges at most once a tick, as when combining on contact
+    let mut merged: HashSet<Entity> = HashSet::new();
+    for (entity1, item1, transform1, velocity1) in item_query.iter() {
+        if merged.len() / 2 >= overflow.per_tick {
+            return;
+        }
+        if merged.contains(&entity1) {
+            continue;
+        }
+        let position = transform1.translation.truncate();
+        let partner = item_query
+            .iter_many(item_grid.near(position, overflow.merge_radius))
+            .find(|(entity2, item2, transform2, _)| {
+                *entity2 != entity1
+                    && !merged.contains(entity2)
+                    && transform2.translation.truncate().distance(position)
+                        <= overflow.merge_radius
+                    && item1.combine(item2).is_some()
+            });
+        let Some((entity2, item2, _, velocity2)) = partner else {
+            continue;
+        };
+        let Some(combined) = item1.combine(item2) else {
+            continue;
+        };
+        merged.insert(entity1);
+        merged.insert(entity2);
+        commands.entity(entity1).despawn();
+        commands.entity(entity2).despawn();
+        let mut survivor = commands.spawn(ItemBundle::new(
+            &mut images,
+            &mut generated_image_assets,
+            combined,
+            *transform1,
+            Velocity {
+                linear: velocity1.linear + velocity2.linear,
+                angular: velocity1.angular + velocity2.angular,
+            },
+        ));
+        let charge: f32 = charge_query
+            .iter_many([entity1, entity2])
+            .map(|charge| charge.0)
+            .sum();
+        if charge > 0.0 {
+            survivor.insert(electric::Charge(charge));
+        }
+    }
+}
+
+// Puts the oldest loose items into chests until the count is back under
+// `overflow.deposit_above`. Items no chest takes stay out.
+pub fn deposit_oldest_items(
+    mut commands: Commands,
+    mut random: ResMut<Random>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut audit: ResMut<AmountAudit>,
+    balance: Res<BalanceConfig>,
+    change_tick: SystemChangeTick,
+    item_query: Query<
+        (Entity, Ref<Item>, &Transform),
+        (Without<Stuck>, Without<ejection::Ejecting>),
+    >,
+    mut minigame_query: Query<
+        (Entity, &mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+) {
+    let overflow = &balance.overflow;
+    let excess = item_query
+        .iter()
+        .len()
+        .saturating_sub(overflow.deposit_above);
+    let mut to_deposit = excess.min(overflow.per_tick);
+    // oldest first; a merged item counts from when it was merged
+    let this_run = change_tick.this_run().get();
+    let mut items: Vec<_> = item_query
+        .iter()
+        .map(|(entity, item, transform)| {
+            (
+                this_run.wrapping_sub(item.added().get()),
+                entity,
+                *item,
+                transform.translation.truncate(),
+            )
+        })
+        .collect();
+    items.sort_by_key(|(age, ..)| std::cmp::Reverse(*age));
+    for (_, entity, item, position) in items {
+        if to_deposit == 0 {
+            return;
+        }
+        let ingested = minigames::chest::deposit_in_nearest(
+            &mut commands,
+            &mut random,
+            &mut images,
+            &mut generated_image_assets,
+            &mut minigame_query,
+            position,
+            &item,
+        );
+        if ingested == 0.0 {
+            continue;
+        }
+        audit.ingested += ingested;
+        commands.entity(entity).despawn();
+        to_deposit -= 1;
+    }
+}
@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Pinned minigames: the pin button on a minigame's header adds it to a short
+// list down the left of the screen, each entry its name, level and progress.
+// Clicking an entry engages that minigame, so the camera pans over to it, and
+// clicking it again lets go, as with the engage button. Pins are kept by id in
+// the order they were pinned, so they survive levelups.
+
+const PIN_HEAD_RADIUS: f32 = 4.0;
+const PINNED_COLOR: Color = Color::srgb(1.0, 0.8, 0.2);
+const UNPINNED_COLOR: Color = Color::srgb(0.85, 0.85, 0.85);
+const ENTRY_FONT_SIZE: f32 = 14.0;
+const ENTRY_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+const ENGAGED_ENTRY_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.35);
+
+#[derive(Debug, Default, Resource)]
+pub struct Pinned {
+    pub ids: Vec<&'static str>,
+}
+
+impl Pinned {
+    // Pins `id` at the end of the list, or unpins it.
+    pub fn toggle(&mut self, id: &'static str) {
+        if let Some(index) = self.ids.iter().position(|pinned| *pinned == id) {
+            self.ids.remove(index);
+        } else {
+            self.ids.push(id);
+        }
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains(&id)
+    }
+}
+
+#[derive(Debug, Copy, Clone, Component)]
+pub struct PinButton {
+    pub minigame: Entity,
+}
+
+// The list of pinned minigames, holding one `PinnedEntry` per pin.
+#[derive(Debug, Component)]
+pub struct PinnedList;
+
+#[derive(Debug, Component)]
+pub struct PinnedEntry(pub &'static str);
+
+pub fn entry_text(name: &str, level: u8, progress: f32) -> String {
+    let percent = (progress.clamp(0.0, 1.0) * 100.0).floor() as u32;
+    tr_fmt(
+        "pins.entry",
+        &[("name", &name), ("level", &level), ("progress", &percent)],
+    )
+}
+
+// Left of the engage button, at `x` on the header.
+pub fn spawn_pin_button(
+    parent: &mut ChildSpawnerCommands,
+    minigame: Entity,
+    x: f32,
+) {
+    parent
+        .spawn((
+            PinButton { minigame },
+            Clickable::new(CLICK_PRIORITY_HEADER),
+            Hoverable,
+            HoverText::new(tr("pins.button")),
+            ShapeBuilder::with(&shapes::Rectangle {
+                extents: Vec2::new(BUTTON_WIDTH, META_HEIGHT),
+                ..default()
+            })
+            .fill(Fill::color(UNPINNED_COLOR))
+            .stroke(Stroke::new(Color::BLACK, 1.0))
+            .build(),
+            Transform::from_xyz(x, 0.0, 0.0),
+            RectangularArea {
+                width: BUTTON_WIDTH,
+                height: META_HEIGHT,
+            },
+        ))
+        .observe(pin_button_clicked)
+        .with_children(|parent| {
+            parent.spawn((
+                ShapeBuilder::with(&shapes::Circle {
+                    radius: PIN_HEAD_RADIUS,
+                    ..default()
+                })
+                .fill(Fill::color(Color::srgb(0.8, 0.1, 0.1)))
+                .stroke(Stroke::new(Color::BLACK, 1.0))
+                .build(),
+                Transform::from_xyz(0.0, 0.0, 1.0),
+            ));
+        });
+}
+
+pub fn pin_button_clicked(
+    clicked: On<Clicked>,
+    button_query: Query<&PinButton>,
+    minigame_query: Query<&Minigame>,
+    mut pinned: ResMut<Pinned>,
+) {
+    if !clicked.click.is_release() {
+        return;
+    }
+    let Ok(pin_button) = button_query.get(clicked.entity) else {
+        return;
+    };
+    let Ok(minigame) = minigame_query.get(pin_button.minigame) else {
+        return;
+    };
+    pinned.toggle(minigame.id());
+}
+
+// Keeps each pin button's fill in sync with `Pinned`, like the engage
+// buttons with `Engaged`.
+pub fn update_pin_button_appearance(
+    mut button_query: Query<(&PinButton, &mut Shape)>,
+    minigame_query: Query<&Minigame>,
+    pinned: Res<Pinned>,
+) {
+    for (pin_button, mut shape) in button_query.iter_mut() {
+        let Ok(minigame) = minigame_query.get(pin_button.minigame) else {
+            continue;
+        };
+        let color = if pinned.contains(minigame.id()) {
+            PINNED_COLOR
+        } else {
+            UNPINNED_COLOR
+        };
+        // writing the shape re-tessellates it, so only when it changes
+        let Some(mut fill) = shape.fill else {
+            continue;
+        };
+        if fill.color != color {
+            fill.color = color;
+            shape.fill = Some(fill);
+        }
+    }
+}
+
+pub fn setup_pinned_list(mut commands: Commands) {
+    commands.spawn((
+        PinnedList,
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(6.0),
+            top: Val::Percent(25.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(2.0),
+            ..default()
+        },
+    ));
+}
+
+// Rebuilds the entries when the pins change, and keeps their text and
+// highlight current.
+pub fn render_pinned_list(
+    mut commands: Commands,
+    pinned: Res<Pinned>,
+    engaged: Res<Engaged>,
+    minigames: Res<MinigamesResource>,
+    minigame_query: Query<&Minigame>,
+    list_query: Query<Entity, With<PinnedList>>,
+    mut entry_query: Query<(&PinnedEntry, &mut Text, &mut BackgroundColor)>,
+) {
+    if pinned.is_changed() {
+        for list in list_query.iter() {
+            commands.entity(list).despawn_children();
+            commands.entity(list).with_children(|parent| {
+                for &id in &pinned.ids {
+                    parent.spawn((
+                        PinnedEntry(id),
+                        Button,
+                        Node {
+                            padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                            ..default()
+                        },
+                        BackgroundColor(ENTRY_COLOR),
+                        Text::new(""),
+                        TextFont {
+                            font_size: ENTRY_FONT_SIZE,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                    ));
+                }
+            });
+        }
+    }
+    for (entry, mut text, mut background) in entry_query.iter_mut() {
+        let Some(Ok(minigame)) =
+            minigames.entity(entry.0).map(|e| minigame_query.get(e))
+        else {
+            continue;
+        };
+        let wanted =
+            entry_text(&minigame.name(), minigame.level(), minigame.progress());
+        if text.0 != wanted {
+            text.0 = wanted;
+        }
+        let color = if engaged.game == Some(entry.0) {
+            ENGAGED_ENTRY_COLOR
+        } else {
+            ENTRY_COLOR
+        };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+}
+
+// Clicking an entry engages its minigame, or lets go of it if it already is.
+pub fn pinned_entry_input(
+    entry_query: Query<(&PinnedEntry, &Interaction), Changed<Interaction>>,
+    mut engaged: ResMut<Engaged>,
+) {
+    for (entry, interaction) in entry_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if engaged.game == Some(entry.0) {
+            engaged.game = None;
+        } else {
+            engaged.game = Some(entry.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pins_toggle_and_keep_their_order() {
+        let mut pinned = Pinned::default();
+        pinned.toggle("a");
+        pinned.toggle("b");
+        pinned.toggle("c");
+        pinned.toggle("b");
+        assert_eq!(pinned.ids, vec!["a", "c"]);
+        assert!(pinned.contains("c") && !pinned.contains("b"));
+        pinned.toggle("b");
+        assert_eq!(pinned.ids, vec!["a", "c", "b"]);
+    }
+
+    // The observer alone, in a bare world: no schedule, no picking.
+    #[test]
+    fn a_released_click_on_the_pin_toggles_it() {
+        let mut world = World::new();
+        world.init_resource::<Pinned>();
+        let minigame = world
+            .spawn(Minigame::from_id(minigames::button::ID).unwrap())
+            .id();
+        let pin = world.spawn(PinButton { minigame }).id();
+        world.entity_mut(pin).observe(pin_button_clicked);
+        let click = |kind| Clicked {
+            entity: pin,
+            click: ClickEvent::targeted(Vec2::ZERO, kind, Some(pin)),
+        };
+
+        world.trigger(click(ClickKind::Press));
+        assert!(world.resource::<Pinned>().ids.is_empty());
+        world.trigger(click(ClickKind::Short));
+        assert_eq!(world.resource::<Pinned>().ids, vec![minigames::button::ID]);
+        world.trigger(click(ClickKind::Long));
+        assert!(world.resource::<Pinned>().ids.is_empty());
+    }
+}
@@ -0,0 +1,245 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Moving minigames: P picks up the engaged minigame, and a ghost of it,
+// header and all, follows the cursor. The ghost is green where the minigame
+// would keep clear of the others and red where it wouldn't. P again puts the
+// minigame down under the ghost, pushing items and the player out of the way,
+// unless the ghost is red: then it stays picked up. Escape leaves it where it
+// was. Where it was put is kept in `MinigamesResource` by id, so levelups and
+// saves keep it there, and Ctrl+Z moves it back if that's still clear.
+
+const CLEAR_COLOR: Color = Color::srgba(0.2, 0.9, 0.3, 0.35);
+const BLOCKED_COLOR: Color = Color::srgba(0.95, 0.15, 0.15, 0.35);
+// over every stacked minigame
+const GHOST_Z: f32 = 500.0;
+
+// The id of the minigame picked up, if any. By id, so a levelup while it's
+// picked up doesn't drop it.
+#[derive(Debug, Default, Resource)]
+pub struct Placing {
+    pub id: Option<&'static str>,
+}
+
+#[derive(Debug, Component)]
+pub struct PlacementGhost;
+
+pub fn ghost_color(clear: bool) -> Color {
+    if clear {
+        CLEAR_COLOR
+    } else {
+        BLOCKED_COLOR
+    }
+}
+
+// The clutter areas of every minigame but `minigame_entity`, which it has to
+// keep clear of.
+pub fn placed_areas<'a>(
+    minigames: impl IntoIterator<Item = (Entity, &'a Minigame, &'a Transform)>,
+    minigame_entity: Entity,
+) -> Vec<PositionedArea> {
+    minigames
+        .into_iter()
+        .filter(|(entity, ..)| *entity != minigame_entity)
+        .map(|(_, other, transform)| other.clutter_area(transform))
+        .collect()
+}
+
+// Whether `minigame_entity`'s minigame would keep clear of all the others at
+// `position`.
+fn clear_at(
+    minigame_query: &Query<(Entity, &Minigame, &Transform)>,
+    minigame_entity: Entity,
+    position: Vec2,
+) -> bool {
+    let Ok((_, minigame, _)) = minigame_query.get(minigame_entity) else {
+        return false;
+    };
+    let others = placed_areas(minigame_query.iter(), minigame_entity);
+    minigame.placement_clear(position, &others)
+}
+
+// Puts the minigame down at `position`, pushing items and the player out of
+// the way.
+fn put_down(
+    commands: &mut Commands,
+    minigame_entity: Entity,
+    minigame: &Minigame,
+    transform: &Transform,
+    position: Vec2,
+    item_grid: &ItemGrid,
+    item_query: &Query<
+        (&Transform, &CircularArea, Entity),
+        (With<Item>, Without<Stuck>),
+    >,
+    player_query: &Query<(&Transform, &CircularArea, Entity), With<Player>>,
+) {
+    let mut moved = *transform;
+    moved.translation = position.extend(transform.translation.z);
+    minigame.clear_clutter(
+        commands,
+        &moved,
+        item_grid,
+        item_query,
+        player_query,
+    );
+    commands.entity(minigame_entity).insert(moved);
+}
+
+pub fn setup_ghost(mut commands: Commands) {
+    commands.spawn((
+        PlacementGhost,
+        Sprite::from_color(CLEAR_COLOR, Vec2::ONE),
+        Transform::from_xyz(0.0, 0.0, GHOST_Z),
+        Visibility::Hidden,
+    ));
+}
+
+// P picks the engaged minigame up, then puts it down at the cursor if it's
+// clear there. Escape gives up.
+pub fn placement_input(
+    mut commands: Commands,
+    mut kb_input: ResMut<ButtonInput<KeyCode>>,
+    mut placing: ResMut<Placing>,
+    engaged: Res<Engaged>,
+    mut minigames: ResMut<MinigamesResource>,
+    mut undo: ResMut<UndoStack>,
+    mouse_state: Res<MouseState>,
+    minigame_query: Query<(Entity, &Minigame, &Transform)>,
+    item_grid: Res<ItemGrid>,
+    item_query: Query<
+        (&Transform, &CircularArea, Entity),
+        (With<Item>, Without<Stuck>),
+    >,
+    player_query: Query<(&Transform, &CircularArea, Entity), With<Player>>,
+) {
+    let Some(id) = placing.id else {
+        if kb_input.just_pressed(KeyCode::KeyP) {
+            placing.id = engaged.game;
+        }
+        return;
+    };
+    if kb_input.just_pressed(KeyCode::Escape) {
+        placing.id = None;
+        // so putting it back doesn't also quit the game
+        kb_input.clear_just_pressed(KeyCode::Escape);
+        return;
+    }
+    if !kb_input.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+    let Some(Ok((entity, minigame, transform))) =
+        minigames.entity(id).map(|e| minigame_query.get(e))
+    else {
+        placing.id = None;
+        return;
+    };
+    let position = mouse_state.current_position;
+    if !clear_at(&minigame_query, entity, position) {
+        return;
+    }
+    put_down(
+        &mut commands,
+        entity,
+        minigame,
+        transform,
+        position,
+        &item_grid,
+        &item_query,
+        &player_query,
+    );
+    minigames.set_moved(id, position);
+    undo.push(UndoAction::Moved {
+        minigame: id,
+        from: transform.translation.truncate(),
+    });
+    placing.id = None;
+}
+
+// Moves each minigame that isn't where it was last moved to over there: after
+// an import, or a move undone.
+pub fn move_to_placements(
+    mut commands: Commands,
+    minigames: Res<MinigamesResource>,
+    minigame_query: Query<(Entity, &Minigame, &Transform)>,
+    item_grid: Res<ItemGrid>,
+    item_query: Query<
+        (&Transform, &CircularArea, Entity),
+        (With<Item>, Without<Stuck>),
+    >,
+    player_query: Query<(&Transform, &CircularArea, Entity), With<Player>>,
+) {
+    if !minigames.is_changed() {
+        return;
+    }
+    for (entity, minigame, transform) in minigame_query.iter() {
+        let Some(position) = minigames.moved(minigame.id()) else {
+            continue;
+        };
+        if transform.translation.truncate() == position {
+            continue;
+        }
+        put_down(
+            &mut commands,
+            entity,
+            minigame,
+            transform,
+            position,
+            &item_grid,
+            &item_query,
+            &player_query,
+        );
+    }
+}
+
+// The ghost follows the cursor while a minigame is picked up, its size and
+// red or green.
+pub fn update_ghost(
+    placing: Res<Placing>,
+    minigames: Res<MinigamesResource>,
+    mouse_state: Res<MouseState>,
+    minigame_query: Query<(Entity, &Minigame, &Transform)>,
+    mut ghost_query: Query<
+        (&mut Sprite, &mut Transform, &mut Visibility),
+        (With<PlacementGhost>, Without<Minigame>),
+    >,
+) {
+    let picked_up = placing
+        .id
+        .and_then(|id| minigames.entity(id))
+        .and_then(|entity| minigame_query.get(entity).ok());
+    for (mut sprite, mut transform, mut visibility) in ghost_query.iter_mut() {
+        let Some((entity, minigame, _)) = picked_up else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let position = mouse_state.current_position;
+        *visibility = Visibility::Inherited;
+        sprite.custom_size = Some(minigame.area_with_header().dimensions());
+        sprite.color = ghost_color(clear_at(&minigame_query, entity, position));
+        transform.translation = position.extend(GHOST_Z);
+    }
+}
+
+pub struct PlacementPlugin;
+
+impl Plugin for PlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Placing>()
+            .add_systems(Startup, setup_ghost)
+            .add_systems(
+                Update,
+                (
+                    placement_input
+                        .run_if(console::console_closed)
+                        .run_if(photo_mode::photo_mode_off)
+                        // ahead of Escape quitting the game
+                        .before(GameSet::Input),
+                    move_to_placements.in_set(GameSet::Minigames),
+                    update_ghost.after(GameSet::Minigames),
+                ),
+            );
+    }
+}
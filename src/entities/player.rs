@@ -12,6 +12,9 @@ use crate::libs::*;
 // bevy_prototype_lyon `Shape` component used here in its favor.
 use bevy_prototype_lyon::prelude::Shape;
 
+// bevy_rapier2d's prelude exports a `Real` too; the clock's is Bevy's.
+use bevy::time::Real;
+
 #[derive(Bundle)]
 pub struct PlayerBundle {
     pub player: Player,
@@ -64,22 +67,10 @@ pub fn setup_player(mut commands: Commands) {
 }
 
 pub fn player_move(
-    mut commands: Commands,
-    mut player_query: Query<(Entity, &mut ExternalImpulse), With<Player>>,
-    stickiness_query: Query<Entity, (With<Sticky>, With<Player>)>,
+    mut player_query: Query<&mut ExternalImpulse, With<Player>>,
     kb_input: Res<ButtonInput<KeyCode>>,
 ) {
-    for (player_entity, mut external_impulse) in player_query.iter_mut() {
-        if kb_input.just_released(KeyCode::Space) {
-            if stickiness_query.get(player_entity).is_ok() {
-                println!("Player is no longer sticky");
-                commands.entity(player_entity).remove::<Sticky>();
-            } else {
-                println!("Player is now sticky");
-                commands.entity(player_entity).insert(Sticky);
-            }
-        }
-
+    for mut external_impulse in player_query.iter_mut() {
         let mut impulse = Vec2::ZERO;
         let mut torque = 0.0;
         if kb_input.pressed(KeyCode::KeyW) {
@@ -115,3 +106,111 @@ pub fn player_move(
         }
     }
 }
+
+#[derive(Debug, Default, Resource)]
+pub struct SweepCooldown(pub f32);
+
+// R pulls every loose item within reach into the nearest chest that takes it,
+// for clearing the floor after a busy minigame. Items no chest takes stay
+// where they are. Reach and cooldown are in assets/balance.ron.
+pub fn sweep_items(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    balance: Res<BalanceConfig>,
+    mut cooldown: ResMut<SweepCooldown>,
+    mut random: ResMut<Random>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut audit: ResMut<AmountAudit>,
+    mut game_events: MessageWriter<GameEvent>,
+    item_grid: Res<ItemGrid>,
+    player_query: Query<&Transform, With<Player>>,
+    item_query: Query<
+        (Entity, &Item, &Transform),
+        (Without<Stuck>, Without<ejection::Ejecting>),
+    >,
+    mut minigame_query: Query<
+        (Entity, &mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+) {
+    cooldown.0 = (cooldown.0 - time.delta_secs()).max(0.0);
+    if !kb_input.just_pressed(KeyCode::KeyR) || cooldown.0 > 0.0 {
+        return;
+    }
+    let Ok(player_transform) = player_query.single() else {
+        return;
+    };
+    let player = player_transform.translation.truncate();
+    cooldown.0 = balance.sweep.cooldown_seconds;
+
+    let mut count = 0;
+    let nearby =
+        item_grid.near(player, balance.sweep.radius + Item::MAX_RADIUS);
+    for (item_entity, item, item_transform) in item_query.iter_many(nearby) {
+        if item_transform.translation.truncate().distance(player)
+            > balance.sweep.radius
+        {
+            continue;
+        }
+        // a chest takes all of an item it accepts
+        let ingested = minigames::chest::deposit_in_nearest(
+            &mut commands,
+            &mut random,
+            &mut images,
+            &mut generated_image_assets,
+            &mut minigame_query,
+            player,
+            item,
+        );
+        if ingested == 0.0 {
+            continue;
+        }
+        audit.ingested += ingested;
+        commands.entity(item_entity).despawn();
+        count += 1;
+    }
+    if count > 0 {
+        game_events.write(GameEvent::Swept { count });
+    }
+}
+
+// The player, their controls and their upgrades.
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SweepCooldown>()
+            .init_resource::<dash::Dash>()
+            .init_resource::<dash::Trail>()
+            .init_resource::<recall::Recall>()
+            .init_resource::<cosmetics::Cosmetics>()
+            .add_systems(Startup, setup_player.in_set(GameSet::Player))
+            .add_systems(
+                Update,
+                (
+                    (
+                        // adds to the walking impulse player_move sets
+                        (player_move, dash::dash).chain(),
+                        sweep_items,
+                        magnet::toggle_magnet,
+                        sticky::toggle_sticky,
+                        recall::recall_input,
+                    )
+                        .run_if(console::console_closed)
+                        .run_if(photo_mode::photo_mode_off),
+                    (dash::leave_trail, dash::fade_trail),
+                    (recall::channel_recall, recall::render_recall_ring)
+                        .chain(),
+                    magnet::unlock_magnet,
+                    magnet::attract_items,
+                    magnet::render_magnet_field,
+                    (sticky::render_sticky_halo, sticky::preview_sticking),
+                    sticky::expire_sticky_ghosts,
+                    (cosmetics::announce_skins, cosmetics::dress_player),
+                )
+                    .in_set(GameSet::Player),
+            );
+    }
+}
@@ -0,0 +1,293 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Puddles. A liquid item holding `puddle.min_amount` or more stops bouncing
+// about as a ball and lies flat: its collider and sprite are squashed into an
+// ellipse `puddle.flatness` as tall as it is wide (the same area as its
+// circle), it doesn't turn, and it soon comes to rest. Other items roll over
+// it rather than off it, since `BoundsHooks` drops their contacts, losing
+// `puddle.drag_per_tick` of their speed each tick they're on it. The player
+// still pushes it about and picks it up, and it still merges with its own
+// kind. Shrunk below the threshold, it's a ball again.
+//
+// Minigames that drink (land, tree) don't swallow a puddle whole as their
+// aura touches it: each tick they sip `puddle.drink_per_tick` from every
+// liquid lying over the aura, so what's left of a puddle once it's too small
+// to be one goes the same way. A puddle lies against the minigame's wall, so
+// the ball it rounds up into falls short of the aura; one a minigame has
+// started on is marked `Sipped` and drunk to the end while it's near.
+
+// how quickly a puddle comes to rest, against an item's 1.0
+const PUDDLE_DAMPING: f32 = 5.0;
+// corners of the polygon standing in for the ellipse, which Rapier lacks
+const OUTLINE_POINTS: usize = 16;
+
+#[derive(Debug, Default, Copy, Clone, Component)]
+pub struct Puddle;
+
+// A liquid `minigame` has been drinking from.
+#[derive(Debug, Copy, Clone, Component)]
+pub struct Sipped {
+    pub minigame: Entity,
+}
+
+pub fn is_puddle(item: &Item, puddle: &PuddleBalance) -> bool {
+    is_liquid(item) && item.amount >= puddle.min_amount
+}
+
+pub fn is_liquid(item: &Item) -> bool {
+    matches!(
+        item.r#type,
+        ItemType::Physical(PhysicalItem::Bulk(bulk))
+            if bulk.structure == BulkStructure::Liquid
+    )
+}
+
+// Half the width and height of the puddle an item of `radius` makes.
+pub fn puddle_half_size(radius: f32, flatness: f32) -> Vec2 {
+    let half_width = radius / flatness.sqrt();
+    Vec2::new(half_width, half_width * flatness)
+}
+
+pub fn puddle_collider(half_size: Vec2) -> Collider {
+    let outline: Vec<Vec2> = (0..OUTLINE_POINTS)
+        .map(|i| {
+            let angle = TAU * i as f32 / OUTLINE_POINTS as f32;
+            Vec2::new(angle.cos(), angle.sin()) * half_size
+        })
+        .collect();
+    Collider::convex_hull(&outline)
+        .unwrap_or_else(|| Collider::ball(half_size.y))
+}
+
+// Whether `point` lies on the puddle of `half_size` centered at `center`.
+pub fn on_puddle(point: Vec2, center: Vec2, half_size: Vec2) -> bool {
+    ((point - center) / half_size).length_squared() <= 1.0
+}
+
+// Whether the puddle lies over any of `rect`.
+pub fn puddle_overlaps(center: Vec2, half_size: Vec2, rect: Rect) -> bool {
+    on_puddle(center.clamp(rect.min, rect.max), center, half_size)
+}
+
+// Flattens liquids into puddles and rounds them out again as their amounts
+// cross the threshold. After `resize_changed_items`, which sizes every item
+// as a ball.
+pub fn shape_puddles(
+    mut commands: Commands,
+    balance: Res<BalanceConfig>,
+    mut item_query: Query<
+        (
+            Entity,
+            &Item,
+            &CircularArea,
+            &mut Collider,
+            &mut Sprite,
+            &mut Damping,
+            &mut Transform,
+            Has<Puddle>,
+        ),
+        Changed<Item>,
+    >,
+) {
+    for (
+        entity,
+        item,
+        area,
+        mut collider,
+        mut sprite,
+        mut damping,
+        mut transform,
+        was_puddle,
+    ) in item_query.iter_mut()
+    {
+        if is_puddle(item, &balance.puddle) {
+            let half_size =
+                puddle_half_size(area.radius, balance.puddle.flatness);
+            *collider = puddle_collider(half_size);
+            sprite.custom_size = Some(half_size * 2.0);
+            damping.linear_damping = PUDDLE_DAMPING;
+            if !was_puddle {
+                transform.rotation = Quat::IDENTITY;
+                commands.entity(entity).insert((
+                    Puddle,
+                    LockedAxes::ROTATION_LOCKED,
+                    ActiveHooks::MODIFY_SOLVER_CONTACTS,
+                ));
+            }
+        } else if was_puddle {
+            // as `ItemBundle::new` makes it
+            *collider = (*area).into();
+            sprite.custom_size = Some((*area).into());
+            damping.linear_damping = 1.0;
+            commands
+                .entity(entity)
+                .remove::<(Puddle, LockedAxes, ActiveHooks)>();
+        }
+    }
+}
+
+// Slows whatever is rolling over a puddle.
+pub fn slow_items_on_puddles(
+    balance: Res<BalanceConfig>,
+    item_grid: Res<ItemGrid>,
+    puddle_query: Query<(&Transform, &CircularArea), With<Puddle>>,
+    mut item_query: Query<
+        (&Transform, &mut Velocity),
+        (With<Item>, Without<Puddle>, Without<Stuck>),
+    >,
+) {
+    let kept = 1.0 - balance.puddle.drag_per_tick.clamp(0.0, 1.0);
+    for (puddle_transform, area) in puddle_query.iter() {
+        let center = puddle_transform.translation.truncate();
+        let half_size = puddle_half_size(area.radius, balance.puddle.flatness);
+        let nearby = item_grid.near(center, half_size.x);
+        let mut nearby_items = item_query.iter_many_mut(nearby);
+        while let Some((transform, mut velocity)) = nearby_items.fetch_next() {
+            if on_puddle(transform.translation.truncate(), center, half_size) {
+                velocity.linear *= kept;
+            }
+        }
+    }
+}
+
+// Minigames that drink sip from the liquids lying over their auras.
+pub fn drink_puddles(
+    mut commands: Commands,
+    mut random: ResMut<Random>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    balance: Res<BalanceConfig>,
+    item_grid: Res<ItemGrid>,
+    mut audit: ResMut<AmountAudit>,
+    mut minigame_query: Query<
+        (Entity, &mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+    mut liquid_query: Query<
+        (
+            Entity,
+            &mut Item,
+            &Transform,
+            &CircularArea,
+            Has<Puddle>,
+            Option<&Sipped>,
+        ),
+        // counted by the audit first, at the amount it came out with
+        (Without<Dropping>, Without<Emitted>),
+    >,
+) {
+    let puddle = &balance.puddle;
+    for (minigame_entity, mut minigame, minigame_transform, minigame_area) in
+        minigame_query.iter_mut()
+    {
+        if !minigame.drinks() {
+            continue;
+        }
+        let aura = Rect::from_center_size(
+            minigame_transform.translation().truncate(),
+            minigame_area.grow(1.0, 1.0).dimensions(),
+        );
+        let reach = puddle_half_size(Item::MAX_RADIUS, puddle.flatness).x;
+        let nearby = item_grid.in_rect(aura.inflate(reach));
+        let mut nearby_liquids = liquid_query.iter_many_mut(nearby);
+        while let Some((entity, mut item, transform, area, is_puddle, sipped)) =
+            nearby_liquids.fetch_next()
+        {
+            if !is_liquid(&item) {
+                continue;
+            }
+            let half_size = if is_puddle {
+                puddle_half_size(area.radius, puddle.flatness)
+            } else {
+                Vec2::splat(area.radius)
+            };
+            let center = transform.translation.truncate();
+            let started = sipped.is_some_and(|s| s.minigame == minigame_entity);
+            if !started && !puddle_overlaps(center, half_size, aura) {
+                continue;
+            }
+            let sip =
+                Item::new(item.r#type, item.amount.min(puddle.drink_per_tick));
+            let drunk = minigame.drink(
+                &mut commands,
+                &mut random,
+                &mut images,
+                &mut generated_image_assets,
+                minigame_entity,
+                minigame_transform,
+                minigame_area,
+                &sip,
+            );
+            if drunk == 0.0 {
+                continue;
+            }
+            audit.ingested += drunk;
+            item.amount -= drunk;
+            if item.amount <= 0.0 {
+                commands.entity(entity).despawn();
+            } else if !started {
+                commands.entity(entity).insert(Sipped {
+                    minigame: minigame_entity,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance() -> PuddleBalance {
+        PuddleBalance {
+            min_amount: 50.0,
+            flatness: 0.5,
+            drag_per_tick: 0.15,
+            drink_per_tick: 0.5,
+        }
+    }
+
+    #[test]
+    fn only_big_liquids_pool() {
+        let puddle = balance();
+        assert!(is_puddle(
+            &Item::liquid(Substance::FreshWater, 50.0),
+            &puddle
+        ));
+        assert!(is_puddle(&Item::liquid(Substance::Mud, 80.0), &puddle));
+        assert!(!is_puddle(
+            &Item::liquid(Substance::SaltWater, 49.0),
+            &puddle
+        ));
+        assert!(!is_puddle(&Item::powder(Substance::Salt, 80.0), &puddle));
+        let lump = Item::solid(Substance::Granite, BulkShape::Lump, 80.0);
+        assert!(!is_puddle(&lump, &puddle));
+    }
+
+    #[test]
+    fn puddles_are_flat_circles_of_the_same_area() {
+        let half_size = puddle_half_size(10.0, 0.5);
+        assert_eq!(half_size.y, half_size.x / 2.0);
+        assert!((half_size.x * half_size.y - 100.0).abs() < 1e-3);
+
+        let center = Vec2::new(100.0, 0.0);
+        assert!(on_puddle(center + Vec2::X * half_size.x, center, half_size));
+        assert!(!on_puddle(
+            center + Vec2::Y * half_size.x,
+            center,
+            half_size
+        ));
+
+        // lying over the edge of a rect, or clear of it
+        let rect = Rect::new(0.0, -50.0, 90.0, 50.0);
+        assert!(puddle_overlaps(center, half_size, rect));
+        let clear = Vec2::new(90.0 + half_size.x + 1.0, 0.0);
+        assert!(!puddle_overlaps(clear, half_size, rect));
+    }
+}
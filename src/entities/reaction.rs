@@ -0,0 +1,320 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Reactions between loose items. When two items start touching,
+// `react_on_contact` applies the first rule in `REACTION_RULES` whose agent
+// matches one of them and whose reagent matches the other. A rule spends
+// `cost` of the agent a unit of reagent, and does nothing unless the agent
+// holds that much; then the reagent turns into what `becomes` makes of it,
+// `product` is given off on it at `rate` a unit of reagent, and `mark` is put
+// on it. A rule that does none of these still claims the pair, so later,
+// broader rules don't.
+//
+// Chemistry is added here, as rules, rather than as systems. Rates that want
+// tuning read `BalanceConfig`.
+
+// How much, a unit of reagent, read from the balance when the rule fires.
+pub type Rate = fn(&BalanceConfig) -> f32;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Mark {
+    // see `heat::Softened`
+    Softened,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct Product {
+    pub item: fn(f32) -> Item,
+    pub rate: Rate,
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct ReactionRule {
+    pub agent: fn(&Item) -> bool,
+    pub reagent: fn(&Item) -> bool,
+    pub cost: Rate,
+    pub becomes: Option<fn(&Item) -> Item>,
+    pub product: Option<Product>,
+    pub mark: Option<Mark>,
+}
+
+impl ReactionRule {
+    // Whether anything comes of it.
+    pub fn is_inert(&self) -> bool {
+        self.becomes.is_none() && self.product.is_none() && self.mark.is_none()
+    }
+}
+
+pub const REACTION_RULES: &[ReactionRule] = &[
+    // Heat warms fresh water, which does nothing yet.
+    ReactionRule {
+        agent: heat::is_thermal,
+        reagent: heat::is_fresh_water,
+        cost: |_| 0.0,
+        becomes: None,
+        product: None,
+        mark: None,
+    },
+    // Heat burns living wood into its corpse, giving off more heat, without
+    // using up the heat that lit it.
+    ReactionRule {
+        agent: heat::is_thermal,
+        reagent: heat::is_living_wood,
+        cost: |_| 0.0,
+        becomes: Some(heat::burnt),
+        product: Some(Product {
+            item: |amount| Item::energy(EnergyKind::Thermal, amount),
+            rate: |balance| balance.heat.burn_heat,
+        }),
+        mark: None,
+    },
+    // Heat softens metal, spending some of itself.
+    ReactionRule {
+        agent: heat::is_thermal,
+        reagent: heat::is_metal,
+        cost: |balance| balance.heat.soften_heat,
+        becomes: None,
+        product: None,
+        mark: Some(Mark::Softened),
+    },
+    // Fresh water dissolves salt into salt water, as much water as land
+    // would dry out of it.
+    ReactionRule {
+        agent: heat::is_fresh_water,
+        reagent: is_salt,
+        cost: |_| 1.0 / minigames::primordial_ocean::SALT_PER_SALT_WATER,
+        becomes: Some(dissolved),
+        product: None,
+        mark: None,
+    },
+];
+
+// What a reaction leaves behind.
+#[derive(Debug, Copy, Clone)]
+pub struct ReactionOutcome {
+    // what's left of the agent
+    pub agent: f32,
+    // what the reagent is now
+    pub reagent: Item,
+    // given off besides
+    pub product: Option<Item>,
+    pub mark: Option<Mark>,
+}
+
+pub fn is_salt(item: &Item) -> bool {
+    matches!(
+        item.r#type,
+        ItemType::Physical(PhysicalItem::Bulk(bulk))
+            if bulk.substance == Substance::Salt
+                && bulk.structure != BulkStructure::Gas
+    )
+}
+
+pub fn dissolved(salt: &Item) -> Item {
+    Item::liquid(
+        Substance::SaltWater,
+        salt.amount / minigames::primordial_ocean::SALT_PER_SALT_WATER,
+    )
+}
+
+// The first rule for two touching items, and which of them is the agent:
+// true if it's `second`.
+pub fn rule_for(
+    first: &Item,
+    second: &Item,
+) -> Option<(&'static ReactionRule, bool)> {
+    REACTION_RULES.iter().find_map(|rule| {
+        if (rule.agent)(first) && (rule.reagent)(second) {
+            Some((rule, false))
+        } else if (rule.agent)(second) && (rule.reagent)(first) {
+            Some((rule, true))
+        } else {
+            None
+        }
+    })
+}
+
+// How `rule` plays out between `agent` and `reagent`, if anything comes of it.
+pub fn react(
+    rule: &ReactionRule,
+    agent: &Item,
+    reagent: &Item,
+    balance: &BalanceConfig,
+) -> Option<ReactionOutcome> {
+    if rule.is_inert() {
+        return None;
+    }
+    let cost = reagent.amount * (rule.cost)(balance);
+    if agent.amount < cost {
+        return None;
+    }
+    Some(ReactionOutcome {
+        agent: agent.amount - cost,
+        reagent: rule.becomes.map_or(*reagent, |becomes| becomes(reagent)),
+        product: rule.product.map(|product| {
+            (product.item)(reagent.amount * (product.rate)(balance))
+        }),
+        mark: rule.mark,
+    })
+}
+
+// Reacts items that start touching, each in at most one reaction a tick.
+pub fn react_on_contact(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    balance: Res<BalanceConfig>,
+    mut audit: ResMut<AmountAudit>,
+    mut item_query: Query<
+        (&mut Item, &Transform, &mut Sprite, Has<heat::Softened>),
+        Without<Dropping>,
+    >,
+    mut contacts: MessageReader<contact::ItemItem>,
+) {
+    let candidates = contacts.read().filter_map(|&contact::ItemItem(a, b)| {
+        let [(item1, ..), (item2, ..)] = item_query.get_many([a, b]).ok()?;
+        let (rule, swapped) = rule_for(item1, item2)?;
+        (!rule.is_inert()).then_some(if swapped { (b, a) } else { (a, b) })
+    });
+    for (agent_entity, reagent_entity) in
+        disjoint_pairs(candidates.collect::<Vec<_>>())
+    {
+        let Ok(
+            [(mut agent, ..), (mut reagent, transform, mut sprite, softened)],
+        ) = item_query.get_many_mut([agent_entity, reagent_entity])
+        else {
+            continue;
+        };
+        let Some((rule, false)) = rule_for(&agent, &reagent) else {
+            continue;
+        };
+        let Some(outcome) = react(rule, &agent, &reagent, &balance) else {
+            continue;
+        };
+        if outcome.mark == Some(Mark::Softened) && softened {
+            continue;
+        }
+        audit.reacted += outcome.agent - agent.amount;
+        agent.amount = outcome.agent;
+        if agent.amount <= 0.0 {
+            commands.entity(agent_entity).despawn();
+        }
+        if reagent.r#type != outcome.reagent.r#type
+            || reagent.amount != outcome.reagent.amount
+        {
+            audit.reacted += outcome.reagent.amount - reagent.amount;
+            // `resize_changed_items` redraws it
+            *reagent = outcome.reagent;
+        }
+        if outcome.mark == Some(Mark::Softened) {
+            sprite.color = heat::SOFTENED_TINT;
+            commands.entity(reagent_entity).insert(heat::Softened);
+        }
+        if let Some(product) = outcome.product {
+            audit.reacted += product.amount;
+            commands.spawn(ItemBundle::new(
+                &mut images,
+                &mut generated_image_assets,
+                product,
+                *transform,
+                Velocity::zero(),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance() -> BalanceConfig {
+        BalanceConfig {
+            heat: HeatBalance {
+                burn_heat: 2.0,
+                soften_heat: 0.5,
+            },
+            ..default()
+        }
+    }
+
+    fn heat(amount: f32) -> Item {
+        Item::energy(EnergyKind::Thermal, amount)
+    }
+
+    // Reacts `agent` with `reagent` by whatever rule they fall under.
+    fn outcome(agent: &Item, reagent: &Item) -> Option<ReactionOutcome> {
+        let (rule, swapped) = rule_for(agent, reagent)?;
+        assert!(!swapped);
+        react(rule, agent, reagent, &balance())
+    }
+
+    #[test]
+    fn the_first_matching_rule_applies_either_way_round() {
+        let water = Item::liquid(Substance::FreshWater, 1.0);
+        let tree = Item::organism(Species::Tree, LifeStage::Adult, 1.0);
+        let stump = Item::organism(Species::Tree, LifeStage::Corpse, 1.0);
+        let iron = Item::ore(Substance::Iron, 1.0);
+        let granite = Item::solid(Substance::Granite, BulkShape::Lump, 1.0);
+        let salt = Item::powder(Substance::Salt, 1.0);
+        let warm = rule_for(&heat(1.0), &water).unwrap();
+        assert!(warm.0.is_inert() && !warm.1);
+        let (burn, swapped) = rule_for(&tree, &heat(1.0)).unwrap();
+        assert!(burn.becomes.is_some() && swapped);
+        assert!(rule_for(&heat(1.0), &stump).is_none());
+        let (soften, _) = rule_for(&heat(1.0), &iron).unwrap();
+        assert_eq!(soften.mark, Some(Mark::Softened));
+        assert!(rule_for(&heat(1.0), &granite).is_none());
+        // water is claimed by heat first, but still dissolves salt
+        let (dissolve, swapped) = rule_for(&salt, &water).unwrap();
+        assert!(!dissolve.is_inert() && swapped);
+    }
+
+    #[test]
+    fn water_only_warms() {
+        let water = Item::liquid(Substance::FreshWater, 1.0);
+        assert!(outcome(&heat(1.0), &water).is_none());
+    }
+
+    #[test]
+    fn wood_burns_into_its_corpse_and_heat() {
+        let bush = Item::organism(Species::Bush, LifeStage::Youth, 3.0);
+        let outcome = outcome(&heat(1.0), &bush).unwrap();
+        assert_eq!(outcome.agent, 1.0);
+        let corpse = Item::organism(Species::Bush, LifeStage::Corpse, 3.0);
+        assert_eq!(outcome.reagent.r#type, corpse.r#type);
+        assert_eq!(outcome.reagent.amount, 3.0);
+        let given_off = outcome.product.unwrap();
+        assert_eq!(given_off.r#type, heat(0.0).r#type);
+        assert_eq!(given_off.amount, 6.0);
+        assert!(outcome.mark.is_none());
+    }
+
+    #[test]
+    fn metal_softens_only_with_enough_heat() {
+        let copper = Item::ore(Substance::Copper, 4.0);
+        assert!(outcome(&heat(1.9), &copper).is_none());
+        let softened = outcome(&heat(3.0), &copper).unwrap();
+        assert_eq!(softened.mark, Some(Mark::Softened));
+        assert_eq!(softened.agent, 1.0);
+        assert_eq!(softened.reagent.amount, 4.0);
+        assert!(softened.product.is_none());
+        // only heat softens
+        let kinetic = Item::energy(EnergyKind::Kinetic, 3.0);
+        assert!(rule_for(&kinetic, &copper).is_none());
+    }
+
+    #[test]
+    fn salt_dissolves_in_enough_fresh_water() {
+        let salt = Item::powder(Substance::Salt, 1.0);
+        let water = |amount| Item::liquid(Substance::FreshWater, amount);
+        assert!(outcome(&water(3.0), &salt).is_none());
+        let dissolved = outcome(&water(5.0), &salt).unwrap();
+        assert_eq!(dissolved.agent, 1.0);
+        let salt_water = Item::liquid(Substance::SaltWater, 4.0);
+        assert_eq!(dissolved.reagent.r#type, salt_water.r#type);
+        assert_eq!(dissolved.reagent.amount, 4.0);
+    }
+}
@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// bevy_rapier2d's prelude exports a `Real` too; the clock's is Bevy's.
+use bevy::time::Real;
+
+// Recall: H channels for `RECALL_SECONDS`, then brings the player, and what's
+// stuck to them, back beside the button minigame, for when they've wandered
+// far off. Loose items that stray get teleported back already; the player
+// had no way home. Walking or H again cancels the channel. The spot they land
+// on is pushed clear of every minigame the way `Minigame::clear_clutter`
+// pushes players out, so they never land inside one.
+
+pub const RECALL_SECONDS: f32 = 3.0;
+const MOVEMENT_KEYS: [KeyCode; 4] =
+    [KeyCode::KeyW, KeyCode::KeyA, KeyCode::KeyS, KeyCode::KeyD];
+const RING_GAP: f32 = 20.0;
+const RING_WIDTH: f32 = 3.0;
+const RING_COLOR: Color = Color::srgb(0.55, 0.75, 1.0);
+
+#[derive(Debug, Default, Resource)]
+pub struct Recall {
+    // seconds until the player is brought home, while channeling
+    pub seconds_left: Option<f32>,
+}
+
+// Shrinks around the player as the channel runs down.
+#[derive(Debug, Component)]
+pub struct RecallRing;
+
+// Moves `start` out of each of `minigame_areas` it's in, as `clear_clutter`
+// would, until it's in none. Pushing out of one can push into a neighbor, so
+// it takes a few passes; after one per minigame it gives up where it is.
+pub fn clear_spot(
+    start: Vec2,
+    player_area: CircularArea,
+    minigame_areas: &[PositionedArea],
+) -> Vec2 {
+    let buffer = Minigame::player_clutter_buffer(&player_area);
+    let mut position = start;
+    for _ in 0..=minigame_areas.len() {
+        let mut moved = false;
+        for minigame_area in minigame_areas {
+            let area =
+                PositionedArea::new(position, Area::Circular(player_area));
+            if let Some(cleared) =
+                Minigame::cleared_position(minigame_area, &area, buffer)
+            {
+                position = cleared;
+                moved = true;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+    position
+}
+
+pub fn recall_input(
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut recall: ResMut<Recall>,
+) {
+    if recall.seconds_left.is_some() {
+        if kb_input.just_pressed(KeyCode::KeyH)
+            || kb_input.any_pressed(MOVEMENT_KEYS)
+        {
+            recall.seconds_left = None;
+        }
+    } else if kb_input.just_pressed(KeyCode::KeyH) {
+        recall.seconds_left = Some(RECALL_SECONDS);
+    }
+}
+
+pub fn channel_recall(
+    time: Res<Time<Real>>,
+    mut recall: ResMut<Recall>,
+    minigames: Res<MinigamesResource>,
+    mut game_events: MessageWriter<GameEvent>,
+    minigame_query: Query<
+        (&Minigame, &Transform),
+        (Without<Player>, Without<Stuck>),
+    >,
+    mut player_query: Query<
+        (Entity, &mut Transform, &CircularArea, &mut Velocity),
+        With<Player>,
+    >,
+    mut stuck_query: Query<
+        (&Stuck, &mut Transform, &mut Velocity),
+        Without<Player>,
+    >,
+) {
+    let Some(seconds_left) = recall.seconds_left else {
+        return;
+    };
+    let seconds_left = seconds_left - time.delta_secs();
+    if seconds_left > 0.0 {
+        recall.seconds_left = Some(seconds_left);
+        return;
+    }
+    recall.seconds_left = None;
+    let Some(Ok((_, button_transform))) = minigames
+        .entity(minigames::button::ID)
+        .map(|e| minigame_query.get(e))
+    else {
+        return;
+    };
+    let minigame_areas: Vec<PositionedArea> = minigame_query
+        .iter()
+        .map(|(minigame, transform)| minigame.clutter_area(transform))
+        .collect();
+    let Ok((player_entity, mut player_transform, &player_area, mut velocity)) =
+        player_query.single_mut()
+    else {
+        return;
+    };
+    let home = clear_spot(
+        button_transform.translation.truncate(),
+        player_area,
+        &minigame_areas,
+    );
+    // stuck items keep their place around the player
+    let shift = home.extend(0.0) - player_transform.translation.with_z(0.0);
+    player_transform.translation += shift;
+    *velocity = Velocity::zero();
+    for (stuck, mut item_transform, mut item_velocity) in stuck_query.iter_mut()
+    {
+        if stuck.player == player_entity {
+            item_transform.translation += shift;
+            *item_velocity = Velocity::zero();
+        }
+    }
+    game_events.write(GameEvent::Recalled);
+}
+
+pub fn render_recall_ring(
+    mut commands: Commands,
+    recall: Res<Recall>,
+    player_query: Query<(Entity, &CircularArea), With<Player>>,
+    mut ring_query: Query<(Entity, &mut Transform), With<RecallRing>>,
+) {
+    let Some(seconds_left) = recall.seconds_left else {
+        for (ring_entity, _) in ring_query.iter() {
+            commands.entity(ring_entity).despawn();
+        }
+        return;
+    };
+    let scale = (seconds_left / RECALL_SECONDS).clamp(0.0, 1.0);
+    if let Ok((_, mut transform)) = ring_query.single_mut() {
+        transform.scale = Vec3::splat(scale);
+        return;
+    }
+    let Ok((player_entity, player_area)) = player_query.single() else {
+        return;
+    };
+    commands.entity(player_entity).with_child((
+        RecallRing,
+        ShapeBuilder::with(&shapes::Circle {
+            radius: player_area.radius + RING_GAP,
+            ..default()
+        })
+        .stroke(Stroke::new(RING_COLOR, RING_WIDTH))
+        .build(),
+        Transform::from_xyz(0.0, 0.0, 1.0).with_scale(Vec3::splat(scale)),
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(position: Vec2, size: f32) -> PositionedArea {
+        PositionedArea::new(
+            position,
+            Area::Rectangular(RectangularArea {
+                width: size,
+                height: size,
+            }),
+        )
+    }
+
+    #[test]
+    fn spots_are_clear_of_every_minigame() {
+        let player = CircularArea { radius: 10.0 };
+        let clear = |position: Vec2, areas: &[PositionedArea]| {
+            let area = PositionedArea::new(position, Area::Circular(player));
+            areas.iter().all(|minigame| !minigame.overlaps(&area))
+        };
+
+        // already clear
+        let areas = [square(Vec2::ZERO, 100.0)];
+        let far = Vec2::new(500.0, 0.0);
+        assert_eq!(clear_spot(far, player, &areas), far);
+
+        // out of the one it's in
+        let spot = clear_spot(Vec2::new(10.0, 0.0), player, &areas);
+        assert!(clear(spot, &areas));
+
+        // out of one and then out of its neighbor
+        let areas = [
+            square(Vec2::ZERO, 100.0),
+            square(Vec2::new(150.0, 0.0), 100.0),
+        ];
+        let spot = clear_spot(Vec2::new(40.0, 0.0), player, &areas);
+        assert!(clear(spot, &areas));
+    }
+}
@@ -0,0 +1,487 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Minigame-to-minigame item routing. Every minigame has two ports on its
+// border: an input on the left and an output on the right. Clicking an output
+// port and then another minigame's input port links them; a long click on an
+// output port unlinks it. While linked, whatever the source minigame puts out
+// (an `Emitted` item at its `ItemBundle::emit_point`, or one it drops) is
+// caught before it lands, queued, and fed into the target's
+// `Minigame::ingest_item` one item every `routing.deliver_seconds`. Only what
+// the target `can_ingest` is caught; what it ends up not taking drops out at
+// its input port. Routes are kept by minigame id, so they survive levelups.
+// Rate and queue length are in assets/balance.ron.
+
+const PORT_SIZE: f32 = 10.0;
+const INPUT_COLOR: Color = Color::srgb(0.3, 0.7, 0.3);
+const OUTPUT_COLOR: Color = Color::srgb(0.9, 0.5, 0.2);
+const LINKING_COLOR: Color = Color::srgb(1.0, 0.9, 0.2);
+const LINE_COLOR: Color = Color::srgba(0.9, 0.5, 0.2, 0.5);
+const LINE_WIDTH: f32 = 2.0;
+// Lines thicken with the items per second delivered along them, up to
+// `MAX_LINE_WIDTH`.
+const LINE_WIDTH_PER_RATE: f32 = 2.0;
+const MAX_LINE_WIDTH: f32 = 8.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortKind {
+    Input,
+    Output,
+}
+
+impl PortKind {
+    // Where the port sits on a minigame with content `area`.
+    pub fn offset(&self, area: &RectangularArea) -> Vec2 {
+        match self {
+            PortKind::Input => Vec2::new(area.left(), 0.0),
+            PortKind::Output => Vec2::new(area.right(), 0.0),
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PortKind::Input => INPUT_COLOR,
+            PortKind::Output => OUTPUT_COLOR,
+        }
+    }
+
+    fn hover_text(&self) -> String {
+        tr(match self {
+            PortKind::Input => "routing.input",
+            PortKind::Output => "routing.output",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Component)]
+pub struct Port {
+    pub minigame: &'static str,
+    pub kind: PortKind,
+}
+
+// The line drawn from a route's output port to its input port.
+#[derive(Debug, Component)]
+pub struct RouteLine {
+    pub from: &'static str,
+}
+
+fn line_width(rate: f32) -> f32 {
+    (LINE_WIDTH + LINE_WIDTH_PER_RATE * rate).min(MAX_LINE_WIDTH)
+}
+
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub to: &'static str,
+    pub queue: VecDeque<Item>,
+    // seconds until the next delivery
+    pub cooldown: f32,
+    pub delivered: throughput::RateWindow,
+}
+
+impl Route {
+    fn new(to: &'static str) -> Self {
+        Self {
+            to,
+            queue: VecDeque::new(),
+            cooldown: 0.0,
+            delivered: throughput::RateWindow::default(),
+        }
+    }
+
+    // Counts down; true once the next item is due and there is one.
+    fn due(&mut self, seconds: f32) -> bool {
+        self.cooldown = (self.cooldown - seconds).max(0.0);
+        self.cooldown == 0.0 && !self.queue.is_empty()
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct Routes {
+    // by the id of the minigame whose output they start at
+    routes: BTreeMap<&'static str, Route>,
+    // the output port picked, waiting for an input port
+    pub linking: Option<&'static str>,
+}
+
+impl Routes {
+    // Links `from`'s output to `to`'s input, replacing where it went before.
+    // Anything already queued goes to the new target. A minigame can't feed
+    // itself.
+    pub fn link(&mut self, from: &'static str, to: &'static str) -> bool {
+        if from == to {
+            return false;
+        }
+        self.routes
+            .entry(from)
+            .and_modify(|route| route.to = to)
+            .or_insert_with(|| Route::new(to));
+        true
+    }
+
+    // Unlinks `from`'s output, handing back what was still queued.
+    pub fn unlink(&mut self, from: &str) -> Vec<Item> {
+        self.routes
+            .remove(from)
+            .map(|route| route.queue.into())
+            .unwrap_or_default()
+    }
+
+    pub fn target(&self, from: &str) -> Option<&'static str> {
+        self.routes.get(from).map(|route| route.to)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &Route)> {
+        self.routes.iter().map(|(from, route)| (*from, route))
+    }
+
+    // Queues `item` on `from`'s route, if it has one with room.
+    pub fn enqueue(&mut self, from: &str, item: Item, limit: usize) -> bool {
+        match self.routes.get_mut(from) {
+            Some(route) if route.queue.len() < limit => {
+                route.queue.push_back(item);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+pub fn spawn_ports(
+    parent: &mut ChildSpawnerCommands,
+    minigame: &'static str,
+    area: RectangularArea,
+) {
+    for kind in [PortKind::Input, PortKind::Output] {
+        parent.spawn((
+            Port { minigame, kind },
+            RectangularArea::new_square(PORT_SIZE),
+            Clickable::new(CLICK_PRIORITY_HEADER),
+            Hoverable,
+            HoverText::new(kind.hover_text()),
+            Sprite::from_color(kind.color(), Vec2::splat(PORT_SIZE)),
+            Transform::from_translation(kind.offset(&area).extend(0.8)),
+        ));
+    }
+}
+
+// A short click on an output port picks it, or lets it go if it was already
+// picked; a long one unlinks it. A click on an input port links the picked
+// output to it.
+pub fn port_click_update(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut routes: ResMut<Routes>,
+    mut undo: ResMut<UndoStack>,
+    minigames: Res<MinigamesResource>,
+    mut click_events: MessageReader<ClickEvent>,
+    port_query: Query<&Port>,
+    minigame_query: Query<(&GlobalTransform, &RectangularArea), With<Minigame>>,
+) {
+    for click in click_events.read() {
+        if !click.is_release() {
+            continue;
+        }
+        let Some(Ok(port)) = click.target.map(|e| port_query.get(e)) else {
+            continue;
+        };
+        match (port.kind, click.kind) {
+            (PortKind::Output, ClickKind::Long) => {
+                routes.linking = None;
+                if let Some(to) = routes.target(port.minigame) {
+                    undo.push(UndoAction::Unlinked {
+                        from: port.minigame,
+                        to,
+                    });
+                }
+                let queued = routes.unlink(port.minigame);
+                let Some(Ok((transform, area))) = minigames
+                    .entity(port.minigame)
+                    .map(|e| minigame_query.get(e))
+                else {
+                    continue;
+                };
+                for item in queued {
+                    commands.spawn(ItemBundle::new_from_minigame(
+                        &mut images,
+                        &mut generated_image_assets,
+                        item,
+                        transform,
+                        area,
+                    ));
+                }
+            }
+            (PortKind::Output, _) => {
+                routes.linking = if routes.linking == Some(port.minigame) {
+                    None
+                } else {
+                    Some(port.minigame)
+                };
+            }
+            (PortKind::Input, _) => {
+                if let Some(from) = routes.linking.take() {
+                    routes.link(from, port.minigame);
+                }
+            }
+        }
+    }
+}
+
+pub fn update_port_colors(
+    routes: Res<Routes>,
+    mut port_query: Query<(Ref<Port>, &mut Sprite)>,
+) {
+    for (port, mut sprite) in port_query.iter_mut() {
+        if !routes.is_changed() && !port.is_added() {
+            continue;
+        }
+        let picked = port.kind == PortKind::Output
+            && routes.linking == Some(port.minigame);
+        sprite.color = if picked {
+            LINKING_COLOR
+        } else {
+            port.kind.color()
+        };
+    }
+}
+
+// Redraws the route lines when a route changes or a minigame (re)spawns.
+pub fn update_route_lines(
+    mut commands: Commands,
+    routes: Res<Routes>,
+    minigames: Res<MinigamesResource>,
+    minigame_query: Query<(&Transform, &RectangularArea), With<Minigame>>,
+    added_query: Query<(), Added<Minigame>>,
+    line_query: Query<Entity, With<RouteLine>>,
+) {
+    if !routes.is_changed() && added_query.is_empty() {
+        return;
+    }
+    for line in line_query.iter() {
+        commands.entity(line).despawn();
+    }
+    let port = |id: &str, kind: PortKind| {
+        let (transform, area) =
+            minigame_query.get(minigames.entity(id)?).ok()?;
+        Some(transform.translation.truncate() + kind.offset(area))
+    };
+    for (from, route) in routes.iter() {
+        let (Some(start), Some(end)) = (
+            port(from, PortKind::Output),
+            port(route.to, PortKind::Input),
+        ) else {
+            continue;
+        };
+        let span = end - start;
+        commands.spawn((
+            RouteLine { from },
+            Sprite::from_color(
+                LINE_COLOR,
+                Vec2::new(span.length(), LINE_WIDTH),
+            ),
+            Transform::from_translation(((start + end) / 2.0).extend(0.5))
+                .with_rotation(Quat::from_rotation_z(span.to_angle())),
+        ));
+    }
+}
+
+// Thickens each route line with how fast items go along it.
+pub fn update_route_line_widths(
+    time: Res<Time<Fixed>>,
+    routes: Res<Routes>,
+    mut line_query: Query<(&RouteLine, &mut Sprite)>,
+) {
+    let now = time.elapsed_secs();
+    for (line, mut sprite) in line_query.iter_mut() {
+        let Some(route) = routes.routes.get(line.from) else {
+            continue;
+        };
+        let width = line_width(route.delivered.rate(now));
+        if let Some(size) = sprite.custom_size.as_mut() {
+            if size.y != width {
+                size.y = width;
+            }
+        }
+    }
+}
+
+// Catches items routed minigames just put out, before the amount audit sees
+// them, so they never count as loose. Items the target wouldn't take are left
+// where they came out, rather than carried over only to drop out there.
+pub fn catch_routed_items(
+    mut commands: Commands,
+    balance: Res<BalanceConfig>,
+    mut routes: ResMut<Routes>,
+    minigames: Res<MinigamesResource>,
+    minigame_query: Query<(&GlobalTransform, &RectangularArea, &Minigame)>,
+    item_query: Query<
+        (Entity, &Item, &Transform, Option<&Dropping>),
+        Added<Emitted>,
+    >,
+) {
+    if item_query.is_empty() {
+        return;
+    }
+    let outputs: Vec<(
+        &'static str,
+        Entity,
+        &GlobalTransform,
+        &RectangularArea,
+        &Minigame,
+    )> = routes
+        .iter()
+        .filter_map(|(from, route)| {
+            let source = minigames.entity(from)?;
+            let (transform, area, _) = minigame_query.get(source).ok()?;
+            let (_, _, target) =
+                minigame_query.get(minigames.entity(route.to)?).ok()?;
+            Some((from, source, transform, area, target))
+        })
+        .collect();
+    for (entity, item, transform, dropping) in item_query.iter() {
+        let position = transform.translation.truncate();
+        let Some((from, _, _, _, target)) =
+            outputs
+                .iter()
+                .find(|(_, source, minigame_transform, area, _)| {
+                    ItemBundle::put_out_by(
+                        position,
+                        dropping,
+                        *source,
+                        minigame_transform,
+                        area,
+                    )
+                })
+        else {
+            continue;
+        };
+        if !target.can_ingest(item) {
+            continue;
+        }
+        if routes.enqueue(from, *item, balance.routing.queue_limit) {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+// Feeds each route's target its next queued item, when it's due.
+pub fn deliver_routed_items(
+    mut commands: Commands,
+    time: Res<Time>,
+    balance: Res<BalanceConfig>,
+    mut random: ResMut<Random>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut routes: ResMut<Routes>,
+    mut throughput: ResMut<throughput::Throughput>,
+    minigames: Res<MinigamesResource>,
+    mut minigame_query: Query<
+        (&mut Minigame, &GlobalTransform, &RectangularArea),
+        Without<LevelingUp>,
+    >,
+) {
+    let now = time.elapsed_secs();
+    for route in routes.routes.values_mut() {
+        if !route.due(time.delta_secs()) {
+            continue;
+        }
+        let Some(target) = minigames.entity(route.to) else {
+            continue;
+        };
+        // a target that's leveling up takes it once it's back
+        let Ok((mut minigame, transform, area)) =
+            minigame_query.get_mut(target)
+        else {
+            continue;
+        };
+        let Some(item) = route.queue.pop_front() else {
+            continue;
+        };
+        route.cooldown = balance.routing.deliver_seconds;
+        route.delivered.record(now);
+        let ingested = minigame.ingest_item(
+            &mut commands,
+            &mut random,
+            &mut images,
+            &mut generated_image_assets,
+            target,
+            transform,
+            area,
+            &item,
+        );
+        if ingested > 0.0 {
+            throughput.record_in(route.to, now);
+        }
+        let remainder = item.amount - ingested;
+        if remainder <= 0.0 {
+            continue;
+        }
+        let position = transform.translation()
+            + (PortKind::Input.offset(area) - Vec2::X * PORT_SIZE).extend(0.0);
+        commands.spawn((
+            ItemBundle::new(
+                &mut images,
+                &mut generated_image_assets,
+                Item {
+                    amount: remainder,
+                    ..item
+                },
+                Transform::from_translation(position),
+                Velocity::linear(Vec2::new(-70.0, 0.0)),
+            ),
+            Emitted,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gold(amount: f32) -> Item {
+        Item::solid(Substance::Gold, BulkShape::Lump, amount)
+    }
+
+    #[test]
+    fn links_replace_and_unlink_with_their_queue() {
+        let mut routes = Routes::default();
+        assert!(!routes.link("quarry", "quarry"), "can't feed itself");
+        assert!(!routes.enqueue("quarry", gold(1.0), 2), "not linked yet");
+
+        assert!(routes.link("quarry", "foundry"));
+        assert!(routes.enqueue("quarry", gold(1.0), 2));
+        assert!(routes.enqueue("quarry", gold(2.0), 2));
+        assert!(!routes.enqueue("quarry", gold(3.0), 2), "queue full");
+
+        // relinking keeps what's queued, for the new target
+        assert!(routes.link("quarry", "chest"));
+        assert_eq!(routes.target("quarry"), Some("chest"));
+        let queued = routes.unlink("quarry");
+        assert_eq!(queued.len(), 2);
+        assert_eq!(queued[1].amount, 2.0);
+        assert_eq!(routes.target("quarry"), None);
+    }
+
+    #[test]
+    fn deliveries_wait_out_the_cooldown() {
+        let mut route = Route::new("chest");
+        assert!(!route.due(0.1), "nothing queued");
+        route.queue.push_back(gold(1.0));
+        assert!(route.due(0.1));
+        route.cooldown = 0.5;
+        assert!(!route.due(0.3));
+        assert!(route.due(0.3));
+    }
+
+    #[test]
+    fn busier_lines_are_thicker_up_to_a_point() {
+        assert_eq!(line_width(0.0), LINE_WIDTH);
+        assert!(line_width(1.0) > line_width(0.5));
+        assert_eq!(line_width(100.0), MAX_LINE_WIDTH);
+    }
+}
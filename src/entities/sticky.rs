@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Stickiness: while the player is `Sticky`, loose items they bump into stick
+// to them (`grab_items`), and turning it off lets them all go
+// (`release_items`). Space switches it. A halo around the player shows it's
+// on, and switching it on briefly rings the loose items close enough to be
+// picked up, so it's clear what a nudge will collect.
+
+const HALO_GAP: f32 = 3.0;
+const HALO_WIDTH: f32 = 2.0;
+const HALO_COLOR: Color = Color::srgba(1.0, 0.85, 0.2, 0.8);
+// how far past the player's edge an item counts as about to stick
+pub const PREVIEW_REACH: f32 = 40.0;
+pub const PREVIEW_SECONDS: f32 = 1.0;
+const GHOST_WIDTH: f32 = 1.5;
+const GHOST_COLOR: Color = Color::srgba(1.0, 0.85, 0.2, 0.5);
+
+// The ring around the player while they're sticky.
+#[derive(Debug, Component)]
+pub struct StickyHalo;
+
+// A ring around an item the player would pick up, gone in `seconds_left`.
+#[derive(Debug, Component)]
+pub struct StickyGhost {
+    pub seconds_left: f32,
+}
+
+// Whether an item at `offset` from the player's center is within
+// `PREVIEW_REACH` of touching them.
+pub fn within_reach(
+    offset: Vec2,
+    player_radius: f32,
+    item_radius: f32,
+) -> bool {
+    offset.length() - player_radius - item_radius <= PREVIEW_REACH
+}
+
+pub fn toggle_sticky(
+    mut commands: Commands,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    player_query: Query<(Entity, Has<Sticky>), With<Player>>,
+) {
+    if !kb_input.just_released(KeyCode::Space) {
+        return;
+    }
+    for (player_entity, sticky) in player_query.iter() {
+        if sticky {
+            commands.entity(player_entity).remove::<Sticky>();
+        } else {
+            commands.entity(player_entity).insert(Sticky);
+        }
+    }
+}
+
+// Puts the halo on when the player becomes sticky and takes it off when they
+// stop.
+pub fn render_sticky_halo(
+    mut commands: Commands,
+    mut unstuck: RemovedComponents<Sticky>,
+    sticky_query: Query<(Entity, &CircularArea), (With<Player>, Added<Sticky>)>,
+    halo_query: Query<(Entity, &ChildOf), With<StickyHalo>>,
+) {
+    for player_entity in unstuck.read() {
+        for (halo_entity, child_of) in halo_query.iter() {
+            if child_of.parent() == player_entity {
+                commands.entity(halo_entity).despawn();
+            }
+        }
+    }
+    for (player_entity, area) in sticky_query.iter() {
+        commands.entity(player_entity).with_child((
+            StickyHalo,
+            ShapeBuilder::with(&shapes::Circle {
+                radius: area.radius + HALO_GAP,
+                ..default()
+            })
+            .stroke(Stroke::new(HALO_COLOR, HALO_WIDTH))
+            .build(),
+            Transform::from_xyz(0.0, 0.0, -1.0),
+        ));
+    }
+}
+
+// Rings the loose items within reach when the player becomes sticky.
+pub fn preview_sticking(
+    mut commands: Commands,
+    item_grid: Res<ItemGrid>,
+    sticky_query: Query<
+        (&Transform, &CircularArea),
+        (With<Player>, Added<Sticky>),
+    >,
+    item_query: Query<
+        (Entity, &Transform, &CircularArea),
+        (With<Item>, Without<Stuck>),
+    >,
+) {
+    for (player_transform, player_area) in sticky_query.iter() {
+        let player = player_transform.translation.truncate();
+        let nearby = item_grid.near(
+            player,
+            player_area.radius + PREVIEW_REACH + Item::MAX_RADIUS,
+        );
+        for (item_entity, item_transform, item_area) in
+            item_query.iter_many(nearby)
+        {
+            let offset = item_transform.translation.truncate() - player;
+            if !within_reach(offset, player_area.radius, item_area.radius) {
+                continue;
+            }
+            commands.entity(item_entity).with_child((
+                StickyGhost {
+                    seconds_left: PREVIEW_SECONDS,
+                },
+                ShapeBuilder::with(&shapes::Circle {
+                    radius: item_area.radius + HALO_GAP,
+                    ..default()
+                })
+                .stroke(Stroke::new(GHOST_COLOR, GHOST_WIDTH))
+                .build(),
+                Transform::from_xyz(0.0, 0.0, 1.0),
+            ));
+        }
+    }
+}
+
+pub fn expire_sticky_ghosts(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut ghost_query: Query<(Entity, &mut StickyGhost)>,
+) {
+    for (ghost_entity, mut ghost) in ghost_query.iter_mut() {
+        ghost.seconds_left -= time.delta_secs();
+        if ghost.seconds_left <= 0.0 {
+            commands.entity(ghost_entity).despawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reach_is_measured_from_the_edges() {
+        // edges 40 apart
+        assert!(within_reach(Vec2::new(75.0, 0.0), 25.0, 10.0));
+        assert!(!within_reach(Vec2::new(76.0, 0.0), 25.0, 10.0));
+        // touching, and overlapping
+        assert!(within_reach(Vec2::new(0.0, -35.0), 25.0, 10.0));
+        assert!(within_reach(Vec2::ZERO, 25.0, 10.0));
+    }
+}
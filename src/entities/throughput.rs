@@ -0,0 +1,205 @@
+use std::collections::{HashMap, VecDeque};
+
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Items per second going into and coming out of each minigame, averaged over
+// the last `WINDOW_SECONDS`. In is whatever a minigame took some of, through
+// its aura or a route; out is what it put out at its emit point. Shown as a
+// small gauge on each minigame's header, and as the thickness of route lines,
+// so a slow link in an automated layout stands out. Times are fixed-clock
+// seconds, so it slows down and pauses with the game.
+
+pub const WINDOW_SECONDS: f32 = 10.0;
+const GAUGE_FONT_SIZE: f32 = 8.0;
+const GAUGE_COLOR: Color = Color::srgb(0.3, 0.3, 0.3);
+
+// When each of the recent events happened, oldest first.
+#[derive(Debug, Clone, Default)]
+pub struct RateWindow {
+    times: VecDeque<f32>,
+}
+
+impl RateWindow {
+    pub fn record(&mut self, now: f32) {
+        while self
+            .times
+            .front()
+            .is_some_and(|time| *time <= now - WINDOW_SECONDS)
+        {
+            self.times.pop_front();
+        }
+        self.times.push_back(now);
+    }
+
+    // Events per second over the window ending `now`.
+    pub fn rate(&self, now: f32) -> f32 {
+        let recent = self
+            .times
+            .iter()
+            .rev()
+            .take_while(|time| **time > now - WINDOW_SECONDS)
+            .count();
+        recent as f32 / WINDOW_SECONDS
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Meter {
+    pub input: RateWindow,
+    pub output: RateWindow,
+}
+
+// By minigame id, so meters carry over levelups like routes do.
+#[derive(Debug, Default, Resource)]
+pub struct Throughput {
+    meters: HashMap<&'static str, Meter>,
+}
+
+impl Throughput {
+    pub fn record_in(&mut self, minigame: &'static str, now: f32) {
+        self.meters.entry(minigame).or_default().input.record(now);
+    }
+
+    pub fn record_out(&mut self, minigame: &'static str, now: f32) {
+        self.meters.entry(minigame).or_default().output.record(now);
+    }
+
+    // Items per second in and out of `minigame`.
+    pub fn rates(&self, minigame: &str, now: f32) -> (f32, f32) {
+        self.meters
+            .get(minigame)
+            .map(|meter| (meter.input.rate(now), meter.output.rate(now)))
+            .unwrap_or_default()
+    }
+}
+
+// What the gauge reads; nothing for a minigame that's idle.
+pub fn gauge_label(rate_in: f32, rate_out: f32) -> String {
+    if rate_in == 0.0 && rate_out == 0.0 {
+        return String::new();
+    }
+    tr_fmt(
+        "throughput.gauge",
+        &[
+            ("in", &format_amount(rate_in)),
+            ("out", &format_amount(rate_out)),
+        ],
+    )
+}
+
+#[derive(Debug, Copy, Clone, Component)]
+pub struct ThroughputGauge {
+    pub minigame: Entity,
+}
+
+// Sits on top of the header's right end, relative to the header's center.
+pub fn spawn_gauge(
+    parent: &mut ChildSpawnerCommands,
+    meta_area: RectangularArea,
+    minigame: Entity,
+) {
+    parent.spawn((
+        ThroughputGauge { minigame },
+        Text2d::new(""),
+        TextFont {
+            font_size: GAUGE_FONT_SIZE,
+            ..default()
+        },
+        TextColor(GAUGE_COLOR),
+        Anchor::BOTTOM_RIGHT,
+        Transform::from_xyz(
+            meta_area.width / 2.0,
+            meta_area.height / 2.0 + 1.0,
+            0.0,
+        ),
+    ));
+}
+
+// Counts what minigames just put out. Runs before routing catches (and
+// despawns) it.
+pub fn meter_output(
+    time: Res<Time>,
+    mut throughput: ResMut<Throughput>,
+    minigame_query: Query<(
+        Entity,
+        &Minigame,
+        &GlobalTransform,
+        &RectangularArea,
+    )>,
+    item_query: Query<(&Transform, Option<&Dropping>), Added<Emitted>>,
+) {
+    let now = time.elapsed_secs();
+    for (transform, dropping) in item_query.iter() {
+        let position = transform.translation.truncate();
+        if let Some((_, minigame, _, _)) = minigame_query.iter().find(
+            |(entity, _, minigame_transform, area)| {
+                ItemBundle::put_out_by(
+                    position,
+                    dropping,
+                    *entity,
+                    minigame_transform,
+                    area,
+                )
+            },
+        ) {
+            throughput.record_out(minigame.id(), now);
+        }
+    }
+}
+
+pub fn update_gauges(
+    time: Res<Time<Fixed>>,
+    throughput: Res<Throughput>,
+    minigame_query: Query<&Minigame>,
+    mut gauge_query: Query<(&ThroughputGauge, &mut Text2d)>,
+) {
+    let now = time.elapsed_secs();
+    for (gauge, mut text) in gauge_query.iter_mut() {
+        let Ok(minigame) = minigame_query.get(gauge.minigame) else {
+            continue;
+        };
+        let (rate_in, rate_out) = throughput.rates(minigame.id(), now);
+        let label = gauge_label(rate_in, rate_out);
+        if text.0 != label {
+            text.0 = label;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rates_average_over_the_window_and_drain() {
+        let mut window = RateWindow::default();
+        assert_eq!(window.rate(0.0), 0.0);
+        for tick in 0..20 {
+            window.record(tick as f32 * 0.5);
+        }
+        // 20 items in the last 10 seconds
+        assert_eq!(window.rate(9.5), 2.0);
+        // the oldest fall out as time passes
+        assert_eq!(window.rate(14.5), 1.0);
+        assert_eq!(window.rate(30.0), 0.0);
+        // recording prunes what's out of the window
+        window.record(30.0);
+        assert_eq!(window.times.len(), 1);
+    }
+
+    #[test]
+    fn meters_are_kept_per_minigame() {
+        let mut throughput = Throughput::default();
+        throughput.record_in("chest", 1.0);
+        throughput.record_out("quarry", 1.0);
+        throughput.record_out("quarry", 2.0);
+        assert_eq!(throughput.rates("chest", 2.0), (0.1, 0.0));
+        assert_eq!(throughput.rates("quarry", 2.0), (0.0, 0.2));
+        assert_eq!(throughput.rates("foundry", 2.0), (0.0, 0.0));
+        assert_eq!(gauge_label(0.0, 0.0), "");
+    }
+}
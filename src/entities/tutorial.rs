@@ -0,0 +1,330 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// The first session, guided. A line of instructions sits at the top of the
+// screen, and an arrow bobs over the minigame it's about, one step at a time:
+// click the button, grab the clicks, feed the ocean until it levels up, draw
+// a rune. Each step waits on what the player really did (the first levelup,
+// the first item grabbed, the first feed), never on a timer. Steps already
+// done when the tutorial gets to them, as after importing a save, go by at
+// once. T skips the rest.
+
+const HINT_FONT_SIZE: f32 = 16.0;
+const ARROW_SIZE: f32 = 24.0;
+// between the arrow's tip and the minigame's header
+const ARROW_GAP: f32 = 12.0;
+const ARROW_BOB: f32 = 8.0;
+const ARROW_BOB_SECONDS: f32 = 1.0;
+const ARROW_COLOR: Color = Color::srgb(1.0, 0.55, 0.1);
+// how long the closing hint stays up
+pub const DONE_SECONDS: f32 = 8.0;
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum TutorialStep {
+    #[default]
+    ClickButton,
+    GrabClicks,
+    FeedOcean,
+    LevelOcean,
+    DrawRune,
+    // the closing hint
+    Done,
+    Finished,
+}
+
+// What the player has done so far, as far as the tutorial cares. Only ever
+// goes from false to true.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TutorialProgress {
+    pub leveled_up: bool,
+    pub grabbed: bool,
+    pub fed_ocean: bool,
+    pub ocean_leveled: bool,
+    pub drew_rune: bool,
+}
+
+impl TutorialStep {
+    pub fn next(self) -> Self {
+        match self {
+            Self::ClickButton => Self::GrabClicks,
+            Self::GrabClicks => Self::FeedOcean,
+            Self::FeedOcean => Self::LevelOcean,
+            Self::LevelOcean => Self::DrawRune,
+            Self::DrawRune => Self::Done,
+            Self::Done | Self::Finished => Self::Finished,
+        }
+    }
+
+    // Whether what the step asks for is done, `seconds` after it came up.
+    pub fn is_done(self, progress: &TutorialProgress, seconds: f32) -> bool {
+        match self {
+            // the button levels up on its first click
+            Self::ClickButton => progress.leveled_up,
+            Self::GrabClicks => progress.grabbed,
+            Self::FeedOcean => progress.fed_ocean,
+            Self::LevelOcean => progress.ocean_leveled,
+            Self::DrawRune => progress.drew_rune,
+            Self::Done => seconds >= DONE_SECONDS,
+            Self::Finished => false,
+        }
+    }
+
+    // The locale key of the step's instructions.
+    pub fn hint(self) -> Option<&'static str> {
+        match self {
+            Self::ClickButton => Some("tutorial.click_button"),
+            Self::GrabClicks => Some("tutorial.grab_clicks"),
+            Self::FeedOcean => Some("tutorial.feed_ocean"),
+            Self::LevelOcean => Some("tutorial.level_ocean"),
+            Self::DrawRune => Some("tutorial.draw_rune"),
+            Self::Done => Some("tutorial.done"),
+            Self::Finished => None,
+        }
+    }
+
+    // The minigame the arrow points at.
+    pub fn target(self) -> Option<&'static str> {
+        match self {
+            Self::ClickButton => Some(minigames::button::ID),
+            Self::FeedOcean | Self::LevelOcean => {
+                Some(minigames::primordial_ocean::ID)
+            }
+            Self::DrawRune => Some(minigames::rune::ID),
+            Self::GrabClicks | Self::Done | Self::Finished => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct Tutorial {
+    pub step: TutorialStep,
+    pub progress: TutorialProgress,
+    // how long the current step has been up
+    pub seconds: f32,
+}
+
+impl Tutorial {
+    // Moves past every step that's done.
+    pub fn advance(&mut self) {
+        while self.step.is_done(&self.progress, self.seconds) {
+            self.step = self.step.next();
+            self.seconds = 0.0;
+        }
+    }
+
+    pub fn skip(&mut self) {
+        self.step = TutorialStep::Finished;
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct TutorialHint;
+
+#[derive(Debug, Component)]
+pub struct TutorialArrow;
+
+pub fn hint_text(step: TutorialStep) -> String {
+    match step.hint() {
+        Some(key) if step == TutorialStep::Done => tr(key),
+        Some(key) => format!("{}\n{}", tr(key), tr("tutorial.skip")),
+        None => String::new(),
+    }
+}
+
+pub fn setup_tutorial(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            // under the speed buttons
+            top: Val::Px(36.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_child((
+            TutorialHint,
+            // so screen readers pick up its text
+            Label,
+            Node {
+                padding: UiRect::axes(Val::Px(10.0), Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+            Text::new(hint_text(TutorialStep::default())),
+            TextFont {
+                font_size: HINT_FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            TextLayout::new_with_justify(Justify::Center),
+        ));
+    commands.spawn((
+        TutorialArrow,
+        ShapeBuilder::with(&shapes::RegularPolygon {
+            sides: 3,
+            feature: RegularPolygonFeature::Radius(ARROW_SIZE / 2.0),
+            ..default()
+        })
+        .fill(Fill::color(ARROW_COLOR))
+        .stroke(Stroke::new(Color::BLACK, 1.5))
+        .build(),
+        // pointing down, over everything
+        Transform::from_xyz(0.0, 0.0, 10.0)
+            .with_rotation(Quat::from_rotation_z(TAU / 2.0)),
+        Visibility::Hidden,
+    ));
+}
+
+pub fn skip_tutorial(
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut tutorial: ResMut<Tutorial>,
+) {
+    if kb_input.just_pressed(KeyCode::KeyT)
+        && tutorial.step != TutorialStep::Finished
+    {
+        tutorial.skip();
+    }
+}
+
+// Notes what the player did, and moves the tutorial along.
+pub fn advance_tutorial(
+    time: Res<Time<Real>>,
+    minigames: Res<MinigamesResource>,
+    throughput: Res<throughput::Throughput>,
+    mut game_events: MessageReader<GameEvent>,
+    grabbed_query: Query<(), Added<Stuck>>,
+    mut tutorial: ResMut<Tutorial>,
+) {
+    if tutorial.step == TutorialStep::Finished {
+        return;
+    }
+    // read them all, so none are left for next time
+    let leveled_up = game_events
+        .read()
+        .filter(|event| matches!(event, GameEvent::LeveledUp { .. }))
+        .count()
+        > 0;
+    let ocean = minigames::primordial_ocean::ID;
+    let now = time.elapsed_secs();
+    let progress = &mut tutorial.progress;
+    progress.leveled_up |=
+        leveled_up || minigames.level(minigames::button::ID) > 0;
+    progress.grabbed |= !grabbed_query.is_empty();
+    progress.fed_ocean |= throughput.rates(ocean, now).0 > 0.0;
+    progress.ocean_leveled |= minigames.level(ocean) > 0;
+    progress.drew_rune |= minigames.level(minigames::rune::ID) > 0;
+    tutorial.seconds += time.delta_secs();
+    tutorial.advance();
+}
+
+pub fn render_tutorial(
+    time: Res<Time<Real>>,
+    tutorial: Res<Tutorial>,
+    minigames: Res<MinigamesResource>,
+    minigame_query: Query<(&Minigame, &GlobalTransform)>,
+    mut hint_query: Query<(&mut Text, &mut Visibility), With<TutorialHint>>,
+    mut arrow_query: Query<
+        (&mut Transform, &mut Visibility),
+        (With<TutorialArrow>, Without<TutorialHint>),
+    >,
+) {
+    for (mut text, mut visibility) in hint_query.iter_mut() {
+        let wanted = hint_text(tutorial.step);
+        if text.0 != wanted {
+            text.0 = wanted;
+        }
+        visibility.set_if_neq(if tutorial.step == TutorialStep::Finished {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        });
+    }
+    let target = tutorial
+        .step
+        .target()
+        .and_then(|id| minigames.entity(id))
+        .and_then(|entity| minigame_query.get(entity).ok());
+    for (mut transform, mut visibility) in arrow_query.iter_mut() {
+        let Some((minigame, minigame_transform)) = target else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+        let bob = ARROW_BOB
+            * (time.elapsed_secs() / ARROW_BOB_SECONDS * TAU).sin().abs();
+        let above = minigame.area_with_header().height / 2.0
+            + ARROW_GAP
+            + ARROW_SIZE / 2.0
+            + bob;
+        let position =
+            minigame_transform.translation().truncate() + Vec2::Y * above;
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tutorial>()
+            .add_systems(Startup, setup_tutorial)
+            .add_systems(
+                Update,
+                (
+                    skip_tutorial
+                        .run_if(console::console_closed)
+                        .run_if(photo_mode::photo_mode_off),
+                    advance_tutorial,
+                    render_tutorial,
+                )
+                    .chain()
+                    .after(GameSet::Minigames),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_wait_for_what_they_ask() {
+        let mut tutorial = Tutorial::default();
+        tutorial.advance();
+        assert_eq!(tutorial.step, TutorialStep::ClickButton);
+
+        tutorial.progress.leveled_up = true;
+        tutorial.advance();
+        assert_eq!(tutorial.step, TutorialStep::GrabClicks);
+
+        // done out of order, so several go by at once
+        tutorial.progress.ocean_leveled = true;
+        tutorial.progress.fed_ocean = true;
+        tutorial.progress.grabbed = true;
+        tutorial.advance();
+        assert_eq!(tutorial.step, TutorialStep::DrawRune);
+
+        tutorial.progress.drew_rune = true;
+        tutorial.advance();
+        assert_eq!(tutorial.step, TutorialStep::Done);
+        tutorial.seconds = DONE_SECONDS;
+        tutorial.advance();
+        assert_eq!(tutorial.step, TutorialStep::Finished);
+        assert_eq!(tutorial.step.hint(), None);
+    }
+
+    #[test]
+    fn arrows_point_at_the_minigame_in_question() {
+        assert_eq!(
+            TutorialStep::ClickButton.target(),
+            Some(minigames::button::ID)
+        );
+        assert_eq!(TutorialStep::GrabClicks.target(), None);
+        assert_eq!(TutorialStep::DrawRune.target(), Some(minigames::rune::ID));
+    }
+}
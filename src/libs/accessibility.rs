@@ -0,0 +1,285 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Settings for players who have trouble telling the game's colours or text
+// apart: item palettes that don't lean on red against green, a high-contrast
+// look for the pixel grids, and a scale for all text. F1 opens the panel;
+// while it's open, 1 and 2 toggle the modes and - and = step the text size.
+// The panel also holds the player's look (see cosmetics.rs), which 3 cycles,
+// and how big numbers are written (see numbers.rs), which 4 switches.
+
+pub const GRID_BACKGROUND_COLOR: Color = Color::srgb(0.5, 0.5, 0.5);
+const HIGH_CONTRAST_GRID_BACKGROUND_COLOR: Color = Color::BLACK;
+
+const TEXT_SCALE_STEP: f32 = 0.25;
+const MIN_TEXT_SCALE: f32 = 0.5;
+const MAX_TEXT_SCALE: f32 = 2.0;
+
+#[derive(Debug, Clone, PartialEq, Resource)]
+pub struct Accessibility {
+    // draw items from assets/palettes-colorblind.ron where it has them
+    pub colorblind: bool,
+    // black grid backgrounds and solid empty cells
+    pub high_contrast: bool,
+    // multiplies every font size
+    pub text_scale: f32,
+    pub notation: Notation,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            colorblind: false,
+            high_contrast: false,
+            text_scale: 1.0,
+            notation: Notation::Short,
+        }
+    }
+}
+
+impl Accessibility {
+    pub fn step_text_scale(&mut self, steps: i32) {
+        self.text_scale = (self.text_scale + steps as f32 * TEXT_SCALE_STEP)
+            .clamp(MIN_TEXT_SCALE, MAX_TEXT_SCALE);
+    }
+
+    pub fn grid_background_color(&self) -> Color {
+        if self.high_contrast {
+            HIGH_CONTRAST_GRID_BACKGROUND_COLOR
+        } else {
+            GRID_BACKGROUND_COLOR
+        }
+    }
+
+    // The text size as a slider: one mark per step, filled up to the current
+    // scale.
+    pub fn text_scale_slider(&self) -> String {
+        let steps =
+            ((MAX_TEXT_SCALE - MIN_TEXT_SCALE) / TEXT_SCALE_STEP) as usize;
+        let filled =
+            ((self.text_scale - MIN_TEXT_SCALE) / TEXT_SCALE_STEP) as usize;
+        format!(
+            "[{}{}] {}%",
+            "#".repeat(filled),
+            "-".repeat(steps - filled),
+            (self.text_scale * 100.0).round()
+        )
+    }
+
+    // `look` is the player's look's line, from `Cosmetics::settings_line`.
+    pub fn panel_text(&self, look: String) -> String {
+        let on_off = |on: bool| {
+            tr(if on {
+                "accessibility.on"
+            } else {
+                "accessibility.off"
+            })
+        };
+        [
+            tr("accessibility.title"),
+            tr_fmt(
+                "accessibility.colorblind",
+                &[("state", &on_off(self.colorblind))],
+            ),
+            tr_fmt(
+                "accessibility.high_contrast",
+                &[("state", &on_off(self.high_contrast))],
+            ),
+            tr_fmt(
+                "accessibility.text_scale",
+                &[("slider", &self.text_scale_slider())],
+            ),
+            look,
+            tr_fmt(
+                "accessibility.notation",
+                &[("example", &format_number(1_234.0, self.notation))],
+            ),
+        ]
+        .join("\n")
+    }
+}
+
+// The backdrop behind a minigame's grid of cells or pixels.
+#[derive(Debug, Component)]
+pub struct GridBackground;
+
+// The font size a text was spawned with, before scaling.
+#[derive(Debug, Component)]
+pub struct UnscaledFontSize(pub f32);
+
+#[derive(Debug, Default, Resource)]
+pub struct AccessibilityPanel {
+    pub open: bool,
+}
+
+#[derive(Debug, Component)]
+pub struct AccessibilityText;
+
+fn setup_accessibility_panel(mut commands: Commands) {
+    commands.spawn((
+        AccessibilityText,
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(6.0),
+            top: Val::Px(6.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Visibility::Hidden,
+    ));
+}
+
+fn accessibility_input(
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut panel: ResMut<AccessibilityPanel>,
+    mut settings: ResMut<Accessibility>,
+    mut cosmetics: ResMut<cosmetics::Cosmetics>,
+    minigames: Res<MinigamesResource>,
+) {
+    if kb_input.just_pressed(KeyCode::F1) {
+        panel.open = !panel.open;
+    }
+    if !panel.open {
+        return;
+    }
+    if kb_input.just_pressed(KeyCode::Digit1) {
+        settings.colorblind = !settings.colorblind;
+    }
+    if kb_input.just_pressed(KeyCode::Digit2) {
+        settings.high_contrast = !settings.high_contrast;
+    }
+    if kb_input.just_pressed(KeyCode::Digit3) {
+        cosmetics.cycle(&minigames);
+    }
+    if kb_input.just_pressed(KeyCode::Digit4) {
+        settings.notation = match settings.notation {
+            Notation::Short => Notation::Scientific,
+            Notation::Scientific => Notation::Short,
+        };
+    }
+    if kb_input.just_pressed(KeyCode::Minus) {
+        settings.step_text_scale(-1);
+    }
+    if kb_input.just_pressed(KeyCode::Equal) {
+        settings.step_text_scale(1);
+    }
+}
+
+fn render_accessibility_panel(
+    panel: Res<AccessibilityPanel>,
+    settings: Res<Accessibility>,
+    cosmetics: Res<cosmetics::Cosmetics>,
+    minigames: Res<MinigamesResource>,
+    mut text_query: Query<
+        (&mut Text, &mut Visibility),
+        With<AccessibilityText>,
+    >,
+) {
+    if !panel.is_changed()
+        && !settings.is_changed()
+        && !cosmetics.is_changed()
+        && !minigames.is_changed()
+    {
+        return;
+    }
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        *visibility = if panel.open {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        text.0 = settings.panel_text(cosmetics.settings_line(&minigames));
+    }
+}
+
+// Scale new text as it appears, and all of it when the scale changes.
+fn scale_text(
+    mut commands: Commands,
+    settings: Res<Accessibility>,
+    mut new_text_query: Query<
+        (Entity, &mut TextFont),
+        Without<UnscaledFontSize>,
+    >,
+    mut text_query: Query<(&UnscaledFontSize, &mut TextFont)>,
+) {
+    for (entity, mut font) in new_text_query.iter_mut() {
+        commands
+            .entity(entity)
+            .insert(UnscaledFontSize(font.font_size));
+        if settings.text_scale != 1.0 {
+            font.font_size *= settings.text_scale;
+        }
+    }
+    if settings.is_changed() {
+        for (unscaled, mut font) in text_query.iter_mut() {
+            font.font_size = unscaled.0 * settings.text_scale;
+        }
+    }
+}
+
+// Numbers are written by a global, for code with no access to resources.
+fn apply_notation(settings: Res<Accessibility>) {
+    if settings.is_changed() {
+        set_notation(settings.notation);
+    }
+}
+
+fn color_grid_backgrounds(
+    settings: Res<Accessibility>,
+    mut background_query: Query<(Ref<GridBackground>, &mut Sprite)>,
+) {
+    for (background, mut sprite) in background_query.iter_mut() {
+        if settings.is_changed() || background.is_added() {
+            sprite.color = settings.grid_background_color();
+        }
+    }
+}
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        // GamePlugin owns Accessibility, since minigames draw by it, and
+        // PlayerPlugin owns Cosmetics
+        app.init_resource::<AccessibilityPanel>()
+            .add_systems(Startup, setup_accessibility_panel)
+            .add_systems(
+                Update,
+                (
+                    accessibility_input.run_if(console::console_closed),
+                    render_accessibility_panel,
+                    scale_text,
+                    apply_notation,
+                    color_grid_backgrounds,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_scale_steps_within_bounds() {
+        let mut settings = Accessibility::default();
+        assert_eq!(settings.text_scale_slider(), "[##----] 100%");
+        settings.step_text_scale(1);
+        assert_eq!(settings.text_scale, 1.25);
+        settings.step_text_scale(10);
+        assert_eq!(settings.text_scale, MAX_TEXT_SCALE);
+        assert_eq!(settings.text_scale_slider(), "[######] 200%");
+        settings.step_text_scale(-10);
+        assert_eq!(settings.text_scale_slider(), "[------] 50%");
+    }
+}
@@ -0,0 +1,425 @@
+use bevy::ecs::message::MessageCursor;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::mouse::{MouseButtonInput, MouseWheel};
+use bevy::input::InputSystems;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Attract mode: a game opened and left alone plays itself, for web embeds and
+// as an end-to-end smoke test. There's no title screen, so "left alone" means
+// no key, mouse button or wheel since launch for `IDLE_SECONDS`; `--attract`
+// starts it at once. A driver then plays with the keys and clicks a player
+// would use, written through the replay layer (`replay::write_inputs`): it goes
+// sticky, fetches the nearest loose item an unlocked minigame takes, carries it
+// into that minigame's aura, then clicks the minigame's engage button so the
+// camera pans over to it for `SHOW_SECONDS`, and moves on to the next one.
+//
+// The driver's inputs come from no window. The first from a real one lets go
+// of its keys and hands the game back for the rest of the run. Never on while
+// a replay records or plays.
+
+const IDLE_SECONDS: f32 = 60.0;
+// how long to chase an item or carry it before giving up on it
+const ERRAND_SECONDS: f32 = 15.0;
+const SHOW_SECONDS: f32 = 4.0;
+// close enough along an axis to stop pressing toward it
+const ARRIVED: f32 = 12.0;
+// close enough to slow down for
+const SLOW_WITHIN: f32 = 80.0;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AttractStart {
+    Idle,
+    AtOnce,
+    Never,
+}
+
+impl AttractStart {
+    // `--attract` starts it at once; `--record` or `--replay` turn it off.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Self {
+        let args: Vec<String> = args.into_iter().collect();
+        if ReplayMode::from_args(args.clone()) != ReplayMode::Off {
+            Self::Never
+        } else if args.iter().any(|arg| arg == "--attract") {
+            Self::AtOnce
+        } else {
+            Self::Idle
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AttractGoal {
+    // heading for a loose item the minigame takes
+    Fetch { item: Entity, minigame: Entity },
+    // carrying it into the minigame's aura
+    Deliver { item: Entity, minigame: Entity },
+    // the camera on the minigame
+    Show { minigame: Entity },
+}
+
+#[derive(Debug, Default)]
+pub struct AttractDriver {
+    goal: Option<AttractGoal>,
+    // before giving up on `goal`
+    seconds_left: f32,
+    // keys it holds down
+    held: Vec<KeyCode>,
+    // a click on its way, at a window position: pressed, then released the
+    // frame after
+    click: Option<(Vec2, bool)>,
+    // the next minigame to go to, by its place among the unlocked ones
+    turn: usize,
+}
+
+impl AttractDriver {
+    pub fn goal(&self) -> Option<AttractGoal> {
+        self.goal
+    }
+}
+
+#[derive(Debug, Resource)]
+pub enum Attract {
+    // seconds since launch, untouched
+    Waiting(f32),
+    Playing(AttractDriver),
+    // a player took over
+    Off,
+}
+
+// The keys that head from `from` toward `to`: none once there, and slowed
+// down near it.
+pub fn steer(from: Vec2, to: Vec2) -> Vec<KeyCode> {
+    let offset = to - from;
+    let mut keys = vec![];
+    if offset.y > ARRIVED {
+        keys.push(KeyCode::KeyW);
+    } else if offset.y < -ARRIVED {
+        keys.push(KeyCode::KeyS);
+    }
+    if offset.x > ARRIVED {
+        keys.push(KeyCode::KeyD);
+    } else if offset.x < -ARRIVED {
+        keys.push(KeyCode::KeyA);
+    }
+    if !keys.is_empty() && offset.length() < SLOW_WITHIN {
+        keys.push(KeyCode::ControlLeft);
+    }
+    keys
+}
+
+// The key inputs that take `held` down keys to `wanted` ones, letting go
+// first.
+pub fn key_changes(held: &[KeyCode], wanted: &[KeyCode]) -> Vec<ReplayInput> {
+    let released = held
+        .iter()
+        .filter(|key| !wanted.contains(key))
+        .map(|key| (key, false));
+    let pressed = wanted
+        .iter()
+        .filter(|key| !held.contains(key))
+        .map(|key| (key, true));
+    released
+        .chain(pressed)
+        .filter_map(|(key, pressed)| {
+            Some(ReplayInput::Key {
+                code: variant_name(key)?,
+                pressed,
+                text: None,
+            })
+        })
+        .collect()
+}
+
+fn left_click(pressed: bool) -> ReplayInput {
+    ReplayInput::Mouse {
+        button: "Left".into(),
+        pressed,
+    }
+}
+
+// Reads each input message once, to tell a player's from the driver's.
+#[derive(Default)]
+struct InputCursors {
+    mouse: MessageCursor<MouseButtonInput>,
+    keyboard: MessageCursor<KeyboardInput>,
+    wheel: MessageCursor<MouseWheel>,
+}
+
+impl InputCursors {
+    // Whether any message since the last look came from a window.
+    fn any_real(
+        &mut self,
+        mouse: &Messages<MouseButtonInput>,
+        keyboard: &Messages<KeyboardInput>,
+        wheel: &Messages<MouseWheel>,
+    ) -> bool {
+        let real = |window: Entity| window != Entity::PLACEHOLDER;
+        let mouse = self.mouse.read(mouse).filter(|m| real(m.window)).count();
+        let keyboard = self
+            .keyboard
+            .read(keyboard)
+            .filter(|m| real(m.window))
+            .count();
+        let wheel = self.wheel.read(wheel).filter(|m| real(m.window)).count();
+        mouse + keyboard + wheel > 0
+    }
+}
+
+// Runs before the input systems, as replay's `inject_inputs` does.
+fn drive(
+    mut attract: ResMut<Attract>,
+    time: Res<Time<Real>>,
+    minigames: Res<MinigamesResource>,
+    engaged: Res<Engaged>,
+    mut cursors: Local<InputCursors>,
+    mut mouse_events: ResMut<Messages<MouseButtonInput>>,
+    mut keyboard_events: ResMut<Messages<KeyboardInput>>,
+    mut wheel_events: ResMut<Messages<MouseWheel>>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    player_query: Query<(Entity, &Transform, Has<Sticky>), With<Player>>,
+    item_query: Query<(Entity, &Item, &Transform, Option<&Stuck>)>,
+    minigame_query: Query<(&Minigame, &GlobalTransform)>,
+    button_query: Query<(&MinigameEngageButton, &GlobalTransform)>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
+) {
+    let real = cursors.any_real(&mouse_events, &keyboard_events, &wheel_events);
+    let driver = match &mut *attract {
+        Attract::Off => return,
+        Attract::Waiting(_) if real => {
+            *attract = Attract::Off;
+            return;
+        }
+        Attract::Waiting(seconds) => {
+            *seconds += time.delta_secs();
+            if *seconds >= IDLE_SECONDS {
+                *attract = Attract::Playing(default());
+            }
+            return;
+        }
+        Attract::Playing(driver) => driver,
+    };
+
+    let mut inputs = vec![];
+    let mut cursor = None;
+    let mut write = |inputs: Vec<ReplayInput>, cursor: &mut Option<Vec2>| {
+        write_inputs(
+            inputs,
+            Entity::PLACEHOLDER,
+            cursor,
+            &mut mouse_events,
+            &mut keyboard_events,
+            &mut wheel_events,
+        );
+    };
+    if real {
+        inputs.extend(key_changes(&driver.held, &[]));
+        if let Some((_, true)) = driver.click {
+            inputs.push(left_click(false));
+        }
+        write(inputs, &mut cursor);
+        *attract = Attract::Off;
+        return;
+    }
+    let Ok((player, player_transform, sticky)) = player_query.single() else {
+        return;
+    };
+    let from = player_transform.translation.truncate();
+    let item_at = |item: Entity| {
+        item_query.get(item).ok().map(|(_, _, transform, stuck)| {
+            (transform.translation.truncate(), stuck.map(|s| s.player))
+        })
+    };
+    let minigame_at = |minigame: Entity| {
+        minigame_query
+            .get(minigame)
+            .ok()
+            .map(|(_, transform)| transform.translation().truncate())
+    };
+    let engage_button_at = |minigame: Entity| {
+        let (camera, camera_transform) = camera_query.single().ok()?;
+        let (_, button) = button_query
+            .iter()
+            .find(|(button, _)| button.minigame == minigame)?;
+        camera
+            .world_to_viewport(camera_transform, button.translation())
+            .ok()
+    };
+    let is_engaged = |minigame: Entity| {
+        minigame_query
+            .get(minigame)
+            .is_ok_and(|(minigame, _)| engaged.game == Some(minigame.id()))
+    };
+
+    driver.seconds_left -= time.delta_secs();
+    let timed_out = driver.seconds_left <= 0.0;
+    driver.goal = match driver.goal {
+        Some(AttractGoal::Fetch { item, minigame }) => match item_at(item) {
+            _ if timed_out || minigame_at(minigame).is_none() => None,
+            Some((_, Some(holder))) if holder == player => {
+                Some(AttractGoal::Deliver { item, minigame })
+            }
+            Some(_) => driver.goal,
+            None => None,
+        },
+        Some(AttractGoal::Deliver { item, minigame }) => match item_at(item) {
+            // taken in
+            None => {
+                driver.seconds_left = SHOW_SECONDS;
+                if !is_engaged(minigame) {
+                    driver.click =
+                        engage_button_at(minigame).map(|at| (at, false));
+                }
+                Some(AttractGoal::Show { minigame })
+            }
+            _ if timed_out => None,
+            Some((_, Some(_))) => driver.goal,
+            // dropped on the way
+            Some((_, None)) => None,
+        },
+        Some(AttractGoal::Show { minigame }) if timed_out => {
+            if is_engaged(minigame) {
+                driver.click = engage_button_at(minigame).map(|at| (at, false));
+            }
+            None
+        }
+        goal => goal,
+    };
+    if driver.goal.is_none() {
+        let unlocked: Vec<Entity> = minigames
+            .ids()
+            .filter_map(|id| minigames.entity(id))
+            .collect();
+        let errand = (0..unlocked.len()).find_map(|k| {
+            let turn = (driver.turn + k) % unlocked.len();
+            let (minigame, _) = minigame_query.get(unlocked[turn]).ok()?;
+            let (item, ..) = item_query
+                .iter()
+                .filter(|(_, item, _, stuck)| {
+                    stuck.is_none() && minigame.can_ingest(item)
+                })
+                .min_by(|(_, _, a, _), (_, _, b, _)| {
+                    let a = a.translation.truncate().distance_squared(from);
+                    let b = b.translation.truncate().distance_squared(from);
+                    a.total_cmp(&b)
+                })?;
+            Some((turn, item, unlocked[turn]))
+        });
+        if let Some((turn, item, minigame)) = errand {
+            driver.turn = turn + 1;
+            driver.seconds_left = ERRAND_SECONDS;
+            driver.goal = Some(AttractGoal::Fetch { item, minigame });
+        } else if !unlocked.is_empty() {
+            // nothing to feed anywhere: show one off anyway
+            let minigame = unlocked[driver.turn % unlocked.len()];
+            driver.turn += 1;
+            driver.seconds_left = SHOW_SECONDS;
+            if !is_engaged(minigame) {
+                driver.click = engage_button_at(minigame).map(|at| (at, false));
+            }
+            driver.goal = Some(AttractGoal::Show { minigame });
+        }
+    }
+
+    let mut wanted = match driver.goal {
+        Some(AttractGoal::Fetch { item, .. }) => {
+            item_at(item).map_or(vec![], |(to, _)| steer(from, to))
+        }
+        Some(AttractGoal::Deliver { minigame, .. }) => {
+            minigame_at(minigame).map_or(vec![], |to| steer(from, to))
+        }
+        Some(AttractGoal::Show { .. }) | None => vec![],
+    };
+    // a tap of Space, down one frame and up the next, makes the player sticky
+    let errand = matches!(
+        driver.goal,
+        Some(AttractGoal::Fetch { .. } | AttractGoal::Deliver { .. })
+    );
+    if errand && !sticky && !driver.held.contains(&KeyCode::Space) {
+        wanted.push(KeyCode::Space);
+    }
+    inputs.extend(key_changes(&driver.held, &wanted));
+    driver.held = wanted;
+    match driver.click {
+        Some((at, false)) => {
+            inputs.push(ReplayInput::Cursor(Some((at.x, at.y))));
+            inputs.push(left_click(true));
+            driver.click = Some((at, true));
+        }
+        Some((_, true)) => {
+            inputs.push(left_click(false));
+            driver.click = None;
+        }
+        None => {}
+    }
+    write(inputs, &mut cursor);
+    if cursor.is_some() {
+        if let Ok(mut window) = window_query.single_mut() {
+            window.set_cursor_position(cursor);
+        }
+    }
+}
+
+pub struct AttractPlugin {
+    pub start: AttractStart,
+}
+
+impl Plugin for AttractPlugin {
+    fn build(&self, app: &mut App) {
+        let attract = match self.start {
+            AttractStart::Never => return,
+            AttractStart::Idle => Attract::Waiting(0.0),
+            AttractStart::AtOnce => Attract::Playing(default()),
+        };
+        app.insert_resource(attract)
+            .add_systems(PreUpdate, drive.before(InputSystems));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_start_from_args() {
+        let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+        assert_eq!(
+            AttractStart::from_args(args("galaxia")),
+            AttractStart::Idle
+        );
+        assert_eq!(
+            AttractStart::from_args(args("galaxia --attract")),
+            AttractStart::AtOnce
+        );
+        assert_eq!(
+            AttractStart::from_args(args("galaxia --attract --replay a.jsonl")),
+            AttractStart::Never
+        );
+    }
+
+    #[test]
+    fn steers_toward_the_target_and_slows_near_it() {
+        let far = steer(Vec2::ZERO, Vec2::new(-300.0, 200.0));
+        assert_eq!(far, vec![KeyCode::KeyW, KeyCode::KeyA]);
+        let near = steer(Vec2::ZERO, Vec2::new(40.0, 5.0));
+        assert_eq!(near, vec![KeyCode::KeyD, KeyCode::ControlLeft]);
+        assert!(steer(Vec2::ZERO, Vec2::new(5.0, -5.0)).is_empty());
+    }
+
+    #[test]
+    fn key_changes_let_go_before_pressing() {
+        let changes = key_changes(
+            &[KeyCode::KeyW, KeyCode::Space],
+            &[KeyCode::KeyW, KeyCode::KeyD],
+        );
+        let key = |code: &str, pressed| ReplayInput::Key {
+            code: code.into(),
+            pressed,
+            text: None,
+        };
+        assert_eq!(changes, vec![key("Space", false), key("KeyD", true)]);
+    }
+}
@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+
+// Debug check that item amounts are conserved. Every loose item's amount is
+// summed each `AUDIT_INTERVAL` fixed ticks, and the change is compared against
+// the known flows across the loose-item boundary: amounts minigames took in
+// (`ingested`, counted by `minigame::ingest_item` and
+// `puddle::drink_puddles`), amounts they put out (items spawned `Emitted`),
+// amounts gases lost to the air (`dissipated`, counted by
+// `gas::dissipate_gases`) and what reactions between items made or used up
+// (`reacted`, counted by `reaction::react_on_contact` and
+// `electric::arc_to_metals`). Whatever is left over was created or destroyed
+// by plumbing that should only ever move matter around — the ingest remainder
+// respawn and `combine_loose_items` are the usual suspects — and is logged as
+// a warning.
+//
+// What minigames do with matter internally (cooking ore, consuming salt water,
+// burning energy) is their own business, so it isn't checked; the amount they
+// currently hold is reported alongside for context.
+
+pub const AUDIT_INTERVAL: u32 = 20; // fixed ticks: one second
+
+// Relative, since f32 sums over many items drift a little on their own.
+const TOLERANCE: f32 = 1e-4;
+
+// Marks an item that entered the world from a minigame (or the console) rather
+// than being respawned from other loose items. Removed once counted.
+#[derive(Debug, Copy, Clone, Default, Component)]
+#[component(storage = "SparseSet")]
+pub struct Emitted;
+
+#[derive(Debug, Default, Resource)]
+pub struct AmountAudit {
+    // flows since the last check
+    pub ingested: f32,
+    pub emitted: f32,
+    pub dissipated: f32,
+    // what reactions made, less what they used up
+    pub reacted: f32,
+    // running sum of everything logged so far
+    pub unaccounted: f32,
+    ticks: u32,
+    last_loose: Option<f32>,
+}
+
+pub fn audit_amounts(
+    mut commands: Commands,
+    mut audit: ResMut<AmountAudit>,
+    emitted_query: Query<(Entity, &Item), With<Emitted>>,
+    item_query: Query<&Item>,
+    minigame_query: Query<&Minigame>,
+) {
+    for (entity, item) in emitted_query.iter() {
+        audit.emitted += item.amount;
+        commands.entity(entity).remove::<Emitted>();
+    }
+
+    audit.ticks += 1;
+    if audit.ticks < AUDIT_INTERVAL && audit.last_loose.is_some() {
+        return;
+    }
+    audit.ticks = 0;
+
+    let loose: f32 = item_query.iter().map(|item| item.amount).sum();
+    if let Some(last_loose) = audit.last_loose {
+        let expected =
+            audit.emitted - audit.ingested - audit.dissipated + audit.reacted;
+        let unaccounted = (loose - last_loose) - expected;
+        if unaccounted.abs() > TOLERANCE * loose.max(last_loose).max(1.0) {
+            let held: f32 =
+                minigame_query.iter().map(Minigame::held_amount).sum();
+            warn!(
+                unaccounted,
+                last_loose,
+                loose,
+                emitted = audit.emitted,
+                ingested = audit.ingested,
+                dissipated = audit.dissipated,
+                reacted = audit.reacted,
+                held,
+                "amount audit: matter created or destroyed"
+            );
+            audit.unaccounted += unaccounted;
+        }
+    }
+    audit.last_loose = Some(loose);
+    audit.emitted = 0.0;
+    audit.ingested = 0.0;
+    audit.dissipated = 0.0;
+    audit.reacted = 0.0;
+}
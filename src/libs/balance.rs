@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, RwLock};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Minigame tuning numbers, read from `assets/balance.ron` so they can be
+// tweaked without a rebuild. Loaded at startup and re-read whenever the file
+// changes; a copy compiled in is used until then and in tests.
+
+pub const BALANCE_PATH: &str = "assets/balance.ron";
+const BUILT_IN: &str = include_str!("../../assets/balance.ron");
+
+static BUILT_IN_BALANCE: LazyLock<Arc<BalanceConfig>> = LazyLock::new(|| {
+    Arc::new(ron::from_str(BUILT_IN).expect("built-in balance should parse"))
+});
+
+// The resource's config, for minigame code that has no access to resources
+// (levelup checks, block toughness). Systems read the resource itself.
+static ACTIVE_BALANCE: RwLock<Option<Arc<BalanceConfig>>> = RwLock::new(None);
+
+pub fn balance() -> Arc<BalanceConfig> {
+    ACTIVE_BALANCE
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| BUILT_IN_BALANCE.clone())
+}
+
+#[derive(Debug, Clone, Deserialize, Resource)]
+pub struct BalanceConfig {
+    pub levelup: LevelupBalance,
+    pub ball_breaker: BallBreakerBalance,
+    pub rune: RuneBalance,
+    pub sweep: SweepBalance,
+    pub magnet: MagnetBalance,
+    pub dash: DashBalance,
+    pub overflow: OverflowBalance,
+    pub land: LandBalance,
+    pub routing: RoutingBalance,
+    pub labels: LabelsBalance,
+    pub puddle: PuddleBalance,
+    pub gas: GasBalance,
+    pub heat: HeatBalance,
+    pub electric: ElectricBalance,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LevelupBalance {
+    pub xp_growth: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BallBreakerBalance {
+    pub block_size: f32,
+    pub ball_speed: f32,
+    pub paddle_speed: f32,
+    pub aim_spread_degrees: f32,
+    pub aim_sweep_seconds: f32,
+    pub ball_cost: f32,
+    pub points_per_bonus_powder: u32,
+    pub toughness: HashMap<Substance, u32>,
+    pub damage: HashMap<Substance, u32>,
+    pub unlisted_toughness: u32,
+    pub unlisted_damage: u32,
+    pub difficulty: BallBreakerDifficulty,
+}
+
+// How recent play bends the makeup of the next board; see
+// `ball_breaker::Performance`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BallBreakerDifficulty {
+    pub memory: f32,
+    pub loss_weight: f32,
+    pub max_rerolls: u32,
+    pub unobtainium_above: f32,
+    pub unobtainium_odds: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RuneBalance {
+    pub trigger_seconds: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SweepBalance {
+    pub radius: f32,
+    pub cooldown_seconds: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct MagnetBalance {
+    pub radius: f32,
+    pub acceleration: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DashBalance {
+    pub impulse: f32,
+    pub cooldown_seconds: f32,
+    pub double_tap_seconds: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct OverflowBalance {
+    pub merge_above: usize,
+    pub merge_radius: f32,
+    pub deposit_above: usize,
+    pub per_tick: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LandBalance {
+    pub lake_cells: usize,
+    pub forest_cells: usize,
+    pub bonus_ticks: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoutingBalance {
+    pub deliver_seconds: f32,
+    pub queue_limit: usize,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LabelsBalance {
+    pub min_amount: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PuddleBalance {
+    pub min_amount: f32,
+    pub flatness: f32,
+    pub drag_per_tick: f32,
+    pub drink_per_tick: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct GasBalance {
+    pub dissipate_per_tick: f32,
+    pub min_amount: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct HeatBalance {
+    pub burn_heat: f32,
+    pub soften_heat: f32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ElectricBalance {
+    pub arc_seconds: f32,
+    pub arc_range: f32,
+    pub arc_amount: f32,
+}
+
+impl Default for BalanceConfig {
+    fn default() -> Self {
+        BUILT_IN_BALANCE.as_ref().clone()
+    }
+}
+
+impl BalanceConfig {
+    // Make this the config minigame code sees.
+    pub fn publish(&self) {
+        *ACTIVE_BALANCE.write().unwrap() = Some(Arc::new(self.clone()));
+    }
+}
+
+#[derive(Debug, Resource)]
+pub struct BalanceFile(pub WatchedFile);
+
+impl Default for BalanceFile {
+    fn default() -> Self {
+        Self(WatchedFile::new(BALANCE_PATH))
+    }
+}
+
+// Read `file` again if it changed. Returns the new config, if there is one.
+pub fn reload_balance(
+    file: &mut WatchedFile,
+) -> Result<Option<BalanceConfig>, GalaxiaError> {
+    let Some(ron) = file.read_if_changed()? else {
+        return Ok(None);
+    };
+    let config = ron::from_str(&ron).map_err(|source| GalaxiaError::Parse {
+        path: file.path.clone(),
+        source: Box::new(source),
+    })?;
+    Ok(Some(config))
+}
+
+fn watch_balance(
+    time: Res<Time<Real>>,
+    mut file: ResMut<BalanceFile>,
+    mut config: ResMut<BalanceConfig>,
+    mut notifications: ResMut<Notifications>,
+) {
+    if !file.0.tick(time.delta_secs()) {
+        return;
+    }
+    match reload_balance(&mut file.0) {
+        Ok(None) => {}
+        Ok(Some(reloaded)) => {
+            reloaded.publish();
+            *config = reloaded;
+        }
+        Err(error) => notifications.push(error.to_string()),
+    }
+}
+
+pub struct BalancePlugin;
+
+impl Plugin for BalancePlugin {
+    fn build(&self, app: &mut App) {
+        // GamePlugin owns BalanceConfig, so the headless app has it too
+        app.init_resource::<BalanceFile>()
+            .add_systems(Update, watch_balance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_balance_matches_the_old_constants() {
+        let config = BalanceConfig::default();
+        assert_eq!(config.levelup.xp_growth, 2.0);
+        assert_eq!(config.ball_breaker.block_size, 20.0);
+        assert_eq!(config.ball_breaker.toughness[&Substance::Iron], 8);
+        assert_eq!(config.ball_breaker.damage[&Substance::Bronze], 8);
+        assert_eq!(config.rune.trigger_seconds, 2.0);
+    }
+
+    #[test]
+    fn broken_balance_file_is_an_error() {
+        let path = std::env::temp_dir().join("galaxia-balance-test.ron");
+        std::fs::write(&path, "(levelup: (xp_growth: 3.0))").unwrap();
+        let mut file = WatchedFile::new(path.to_str().unwrap());
+        assert!(matches!(
+            reload_balance(&mut file),
+            Err(GalaxiaError::Parse { .. })
+        ));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
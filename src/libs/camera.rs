@@ -2,14 +2,20 @@ use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
 
 use crate::entities::*;
+use crate::libs::*;
 
 #[derive(Resource)]
 pub struct CameraController {
     pub dead_zone_squared: f32,
 }
 
+// The camera the board is seen and clicked through. Photo mode's cameras,
+// which draw into images, aren't it.
+#[derive(Debug, Copy, Clone, Default, Component)]
+pub struct MainCamera;
+
 pub fn setup_camera(mut commands: Commands) {
-    commands.spawn(Camera2d);
+    commands.spawn((Camera2d, MainCamera));
 }
 
 const MIN_ZOOM: f32 = 0.2;
@@ -17,25 +23,29 @@ const MAX_ZOOM: f32 = 3.0;
 
 pub fn update_camera(
     camera_controller: ResMut<CameraController>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
     engaged: Res<Engaged>,
     minigames: Res<MinigamesResource>,
     mut evr_scroll: MessageReader<MouseWheel>,
     mut camera_query: Query<
         (&mut Transform, &mut Projection),
-        (With<Camera2d>, Without<player::Player>),
+        (With<MainCamera>, Without<player::Player>),
+    >,
+    player_query: Query<
+        &Transform,
+        (With<player::Player>, Without<MainCamera>),
     >,
-    player_query: Query<&Transform, (With<player::Player>, Without<Camera2d>)>,
     minigame_query: Query<
         &Transform,
-        (With<Minigame>, Without<player::Player>, Without<Camera2d>),
+        (With<Minigame>, Without<player::Player>, Without<MainCamera>),
     >,
 ) {
     let Ok(camera) = camera_query.single_mut() else {
         return;
     };
     let (mut camera_transform, mut projection) = camera;
-    let Projection::Orthographic(camera_projection) = projection.as_mut() else {
+    let Projection::Orthographic(camera_projection) = projection.as_mut()
+    else {
         return;
     };
 
@@ -65,14 +75,13 @@ pub fn update_camera(
 
     // focused on player
 
-    let direction =
-        player.translation.with_z(camera_transform.translation.z);
+    let direction = player.translation.with_z(camera_transform.translation.z);
 
     // Applies a smooth effect to camera movement using interpolation between
     // the camera position and the player position on the x and y axes.
-    // Here we use the in-game time, to get the elapsed time (in seconds)
-    // since the previous update. This avoids jittery movement when tracking
-    // the player.
+    // Here we use the real time, to get the elapsed time (in seconds)
+    // since the previous update, whatever the simulation speed. This avoids
+    // jittery movement when tracking the player.
     if (player.translation - camera_transform.translation).length_squared()
         > camera_controller.dead_zone_squared
     {
@@ -92,3 +101,20 @@ pub fn update_camera(
         camera_projection.scale -= ev.y * 0.1;
     }
 }
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraController {
+            dead_zone_squared: 1000.0,
+        })
+        .add_systems(Startup, setup_camera.in_set(GameSet::Camera))
+        .add_systems(
+            Update,
+            update_camera
+                .run_if(photo_mode::photo_mode_off)
+                .in_set(GameSet::Camera),
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use std::time::Duration;
+
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic,
+};
+use bevy::prelude::*;
+
+// Fixed-timestep catch-up. FixedUpdate runs as many steps as the frame's time
+// holds, so after a long frame it runs several, which makes the next frame
+// long too, and a slow machine with a big board falls further behind every
+// frame. The virtual clock counts no more than `MAX_FIXED_STEPS` fixed steps
+// of real time a frame (so twice as many steps at 2x, see time_scale.rs), and
+// whatever a frame took beyond that is dropped: the game slows down instead
+// of spiraling.
+//
+// An ordinary frame runs as many steps as its real time holds at the current
+// speed, give or take one (`expected_steps`): at 60 fps, one or two at 4x.
+// What's ordinary comes from an average of recent real frame times, so a
+// long frame runs more than expected. On a frame that does, work that can
+// wait for the next (redrawing the inventory, evolving the land) runs on the
+// expected steps only; gate it with `not_catching_up`. The steps and the
+// time dropped are Bevy diagnostics, shown on the F5 overlay.
+
+pub const MAX_FIXED_STEPS: u32 = 4;
+// steps a frame may run beyond what its time holds and still be ordinary
+const STEP_MARGIN: u32 = 1;
+// how much of each frame's real time goes into the average
+const FRAME_SMOOTHING: f32 = 0.1;
+pub const FIXED_STEPS: DiagnosticPath =
+    DiagnosticPath::const_new("galaxia/fixed_steps");
+pub const DROPPED: DiagnosticPath =
+    DiagnosticPath::const_new("galaxia/catch_up_dropped");
+
+#[derive(Debug, Default, Resource)]
+pub struct CatchUp {
+    // fixed steps run so far this frame
+    pub steps: u32,
+    // last frame's, for anyone asking after the frame
+    pub last_steps: u32,
+    // the real time last frame took beyond `MAX_FIXED_STEPS`, not simulated
+    pub dropped: Duration,
+    // the average real frame, in seconds; none before the first
+    pub frame_seconds: f32,
+}
+
+// The most steps an ordinary frame of `frame_seconds` of real time runs at
+// `speed`, with fixed steps of `timestep_seconds`: as many as it holds,
+// rounded up, and `STEP_MARGIN` more. At least one and the margin.
+pub fn expected_steps(
+    frame_seconds: f32,
+    speed: f32,
+    timestep_seconds: f32,
+) -> u32 {
+    let held = frame_seconds * speed / timestep_seconds;
+    held.ceil().max(1.0) as u32 + STEP_MARGIN
+}
+
+// Within a frame's expected steps, at the current speed.
+pub fn not_catching_up(
+    catch_up: Res<CatchUp>,
+    virtual_time: Res<Time<Virtual>>,
+    fixed_time: Res<Time<Fixed>>,
+) -> bool {
+    catch_up.steps
+        <= expected_steps(
+            catch_up.frame_seconds,
+            virtual_time.relative_speed(),
+            fixed_time.timestep().as_secs_f32(),
+        )
+}
+
+// The longest real frame the virtual clock takes, for a fixed step of
+// `timestep`.
+pub fn max_frame(timestep: Duration) -> Duration {
+    timestep * MAX_FIXED_STEPS
+}
+
+fn count_fixed_step(mut catch_up: ResMut<CatchUp>) {
+    catch_up.steps += 1;
+}
+
+// After the frame: what it ran and dropped, and the cap for the next one.
+// The cap is on the real frame, before the speed scales it.
+fn finish_catch_up(
+    mut catch_up: ResMut<CatchUp>,
+    real_time: Res<Time<Real>>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    fixed_time: Res<Time<Fixed>>,
+    mut diagnostics: Diagnostics,
+) {
+    catch_up.last_steps = std::mem::take(&mut catch_up.steps);
+    let frame = real_time.delta_secs();
+    catch_up.frame_seconds = if catch_up.frame_seconds == 0.0 {
+        frame
+    } else {
+        catch_up.frame_seconds.lerp(frame, FRAME_SMOOTHING)
+    };
+    catch_up.dropped = if virtual_time.is_paused() {
+        Duration::ZERO
+    } else {
+        real_time.delta().saturating_sub(virtual_time.max_delta())
+    };
+    let steps = catch_up.last_steps;
+    let dropped = catch_up.dropped;
+    diagnostics.add_measurement(&FIXED_STEPS, || steps as f64);
+    diagnostics.add_measurement(&DROPPED, || dropped.as_secs_f64() * 1000.0);
+
+    let cap = max_frame(fixed_time.timestep());
+    if virtual_time.max_delta() != cap {
+        virtual_time.set_max_delta(cap);
+    }
+}
+
+// In `GamePlugin`, so the headless app keeps it too.
+pub struct CatchUpPlugin;
+
+impl Plugin for CatchUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CatchUp>()
+            .register_diagnostic(Diagnostic::new(FIXED_STEPS))
+            .register_diagnostic(Diagnostic::new(DROPPED).with_suffix("ms"))
+            .add_systems(FixedFirst, count_fixed_step)
+            .add_systems(Last, finish_catch_up);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn the_cap_follows_the_step() {
+        // 20 Hz
+        assert_eq!(
+            max_frame(Duration::from_millis(50)),
+            Duration::from_millis(200)
+        );
+    }
+
+    #[test]
+    fn faster_speeds_expect_more_steps() {
+        // a 20 Hz step, a frame of two steps
+        assert_eq!(expected_steps(0.1, 1.0, 0.05), 2 + STEP_MARGIN);
+        assert_eq!(expected_steps(0.1, 2.0, 0.05), 4 + STEP_MARGIN);
+        // a frame much shorter than a step still runs one now and then
+        assert_eq!(expected_steps(0.001, 0.5, 0.05), 1 + STEP_MARGIN);
+    }
+
+    // At 60 fps and 4x, a frame holds 1.33 steps of 20 Hz: four is behind,
+    // not the speed.
+    #[test]
+    fn fast_frames_at_high_speed_catch_up() {
+        let mut world = World::new();
+        let mut virtual_time = Time::<Virtual>::default();
+        virtual_time.set_relative_speed(4.0);
+        world.insert_resource(virtual_time);
+        world.insert_resource(Time::<Fixed>::from_hz(20.0));
+        let catching_up = |world: &mut World, steps| {
+            world.insert_resource(CatchUp {
+                steps,
+                frame_seconds: 1.0 / 60.0,
+                ..default()
+            });
+            !world.run_system_once(not_catching_up).unwrap()
+        };
+        assert!(!catching_up(&mut world, 1));
+        assert!(!catching_up(&mut world, 2));
+        assert!(!catching_up(&mut world, 2 + STEP_MARGIN));
+        assert!(catching_up(&mut world, 3 + STEP_MARGIN));
+        assert!(catching_up(&mut world, MAX_FIXED_STEPS * 4));
+    }
+}
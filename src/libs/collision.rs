@@ -1,33 +1,154 @@
 use bevy_rapier2d::prelude::*;
 
+// Who collides with whom. Each collider is a member of one group, and its
+// filter, the groups it may touch, comes from `COLLISIONS`, so every pair is
+// declared once and holds both ways: Rapier only lets two colliders touch
+// when each is in the other's filter. A new group goes in `GROUPS` and gets
+// its pairs here, and the tests check the pairs that must never happen.
+
 pub const PLAYER_GROUP: Group = Group::GROUP_1;
 pub const ETHER_GROUP: Group = Group::GROUP_2; // mostly LooseResource
 pub const MINIGAME_CONTENTS_GROUP: Group = Group::GROUP_3; // stuff inside of minigames
 pub const MINIGAME_AURA_GROUP: Group = Group::GROUP_4; // ether-minigame interaction
 pub const BORDER_GROUP: Group = Group::GROUP_32; // borders around minigames
 
+pub const GROUPS: [Group; 5] = [
+    PLAYER_GROUP,
+    ETHER_GROUP,
+    MINIGAME_CONTENTS_GROUP,
+    MINIGAME_AURA_GROUP,
+    BORDER_GROUP,
+];
+
+// The pairs that collide, either way round. Every group collides with its
+// own kind except auras, which only feel the ether; the ether never reaches
+// inside a minigame, and only its border keeps the contents in.
+pub const COLLISIONS: [(Group, Group); 9] = [
+    (PLAYER_GROUP, PLAYER_GROUP),
+    (PLAYER_GROUP, ETHER_GROUP),
+    (PLAYER_GROUP, BORDER_GROUP),
+    (ETHER_GROUP, ETHER_GROUP),
+    (ETHER_GROUP, MINIGAME_AURA_GROUP),
+    (ETHER_GROUP, BORDER_GROUP),
+    (MINIGAME_CONTENTS_GROUP, MINIGAME_CONTENTS_GROUP),
+    (MINIGAME_CONTENTS_GROUP, BORDER_GROUP),
+    (BORDER_GROUP, BORDER_GROUP),
+];
+
+// Every group `group` collides with, per `COLLISIONS`.
+pub const fn filter(group: Group) -> Group {
+    let mut filter = Group::NONE;
+    let mut i = 0;
+    while i < COLLISIONS.len() {
+        let (a, b) = COLLISIONS[i];
+        if a.bits() == group.bits() {
+            filter = filter.union(b);
+        }
+        if b.bits() == group.bits() {
+            filter = filter.union(a);
+        }
+        i += 1;
+    }
+    filter
+}
+
 #[inline]
 pub fn player_filter() -> Group {
-    PLAYER_GROUP | ETHER_GROUP | BORDER_GROUP
+    filter(PLAYER_GROUP)
 }
 
 #[inline]
 pub fn ether_filter() -> Group {
-    ETHER_GROUP | PLAYER_GROUP | MINIGAME_AURA_GROUP | BORDER_GROUP
+    filter(ETHER_GROUP)
 }
 
 #[inline]
 pub fn minigame_contents_filter() -> Group {
-    MINIGAME_CONTENTS_GROUP | BORDER_GROUP
+    filter(MINIGAME_CONTENTS_GROUP)
 }
 
 #[inline]
 pub fn minigame_aura_filter() -> Group {
-    ETHER_GROUP
+    filter(MINIGAME_AURA_GROUP)
 }
 
 #[inline]
 pub fn border_filter() -> Group {
-    // !MINIGAME_AURA_GROUP
-    BORDER_GROUP | PLAYER_GROUP | ETHER_GROUP | MINIGAME_CONTENTS_GROUP
+    filter(BORDER_GROUP)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_rapier2d::rapier::geometry::InteractionGroups;
+
+    use super::*;
+
+    // As Rapier decides it, for a collider of each group.
+    fn collide(a: Group, b: Group) -> bool {
+        let groups = |group| {
+            InteractionGroups::from(CollisionGroups::new(group, filter(group)))
+        };
+        groups(a).test(groups(b))
+    }
+
+    #[test]
+    fn groups_are_distinct_and_each_has_its_pairs() {
+        for (i, a) in GROUPS.iter().enumerate() {
+            assert_eq!(a.bits().count_ones(), 1, "{:?}", a);
+            for b in &GROUPS[i + 1..] {
+                assert!(!a.intersects(*b), "{:?} and {:?}", a, b);
+            }
+            assert!(!filter(*a).is_empty(), "{:?} collides with nothing", a);
+        }
+        for (a, b) in COLLISIONS {
+            assert!(GROUPS.contains(&a) && GROUPS.contains(&b));
+        }
+    }
+
+    #[test]
+    fn every_pair_holds_both_ways() {
+        for a in GROUPS {
+            for b in GROUPS {
+                assert_eq!(collide(a, b), collide(b, a), "{:?} {:?}", a, b);
+                assert_eq!(collide(a, b), filter(a).contains(b));
+            }
+        }
+    }
+
+    #[test]
+    fn what_must_and_mustnt_touch() {
+        // loose items never disturb what's inside a minigame
+        assert!(!collide(ETHER_GROUP, MINIGAME_CONTENTS_GROUP));
+        // auras only feel loose items, and borders don't block them
+        assert!(collide(ETHER_GROUP, MINIGAME_AURA_GROUP));
+        assert!(!collide(PLAYER_GROUP, MINIGAME_AURA_GROUP));
+        assert!(!collide(MINIGAME_CONTENTS_GROUP, MINIGAME_AURA_GROUP));
+        assert!(!collide(BORDER_GROUP, MINIGAME_AURA_GROUP));
+        assert!(!collide(MINIGAME_AURA_GROUP, MINIGAME_AURA_GROUP));
+        // the player can't walk into a minigame's contents
+        assert!(!collide(PLAYER_GROUP, MINIGAME_CONTENTS_GROUP));
+        // borders hold in everything but auras
+        for group in [PLAYER_GROUP, ETHER_GROUP, MINIGAME_CONTENTS_GROUP] {
+            assert!(collide(BORDER_GROUP, group), "{:?}", group);
+        }
+    }
+
+    // The filters as they were written out by hand, before `COLLISIONS`.
+    #[test]
+    fn filters_are_unchanged() {
+        assert_eq!(player_filter(), PLAYER_GROUP | ETHER_GROUP | BORDER_GROUP);
+        assert_eq!(
+            ether_filter(),
+            ETHER_GROUP | PLAYER_GROUP | MINIGAME_AURA_GROUP | BORDER_GROUP
+        );
+        assert_eq!(
+            minigame_contents_filter(),
+            MINIGAME_CONTENTS_GROUP | BORDER_GROUP
+        );
+        assert_eq!(minigame_aura_filter(), ETHER_GROUP);
+        assert_eq!(
+            border_filter(),
+            BORDER_GROUP | PLAYER_GROUP | ETHER_GROUP | MINIGAME_CONTENTS_GROUP
+        );
+    }
 }
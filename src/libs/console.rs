@@ -0,0 +1,349 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Developer console, toggled with backtick. While it is open, typed keys edit
+// the console line instead of moving the player; Enter runs the line through
+// the same spawn/levelup/unlock paths the game itself uses:
+//
+//   give physical/Lump/Gold 50   spawn an item next to the player
+//   levelup ball_breaker         level an unlocked minigame up once
+//   unlock foundry               spawn a locked minigame at its home position
+//   tp 200 -300                  move the player
+
+const MAX_LOG_LINES: usize = 8;
+const GIVE_OFFSET: Vec3 = Vec3::new(0.0, 60.0, 0.0);
+
+#[derive(Debug, Default, Resource)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub log: Vec<String>,
+}
+
+impl Console {
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        if self.log.len() > MAX_LOG_LINES {
+            self.log.remove(0);
+        }
+    }
+}
+
+// Run condition for gameplay input that must not fire while typing, into the
+// console or onto a note.
+pub fn console_closed(
+    console: Res<Console>,
+    composer: Option<Res<notes::NoteComposer>>,
+) -> bool {
+    !console.open && notes::composer_closed(composer)
+}
+
+#[derive(Debug, Clone, PartialEq, Message)]
+pub enum ConsoleCommand {
+    Give { item_type: ItemType, amount: f32 },
+    LevelUp(&'static str),
+    Unlock(&'static str),
+    Teleport(Vec2),
+}
+
+impl ConsoleCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let Some(verb) = words.next() else {
+            return Err("empty command".to_string());
+        };
+        let args: Vec<&str> = words.collect();
+        match (verb, args.as_slice()) {
+            ("give", [uid]) => Self::give(uid, "1"),
+            ("give", [uid, amount]) => Self::give(uid, amount),
+            ("levelup", [id]) => Ok(Self::LevelUp(minigame_id(id)?)),
+            ("unlock", [id]) => Ok(Self::Unlock(minigame_id(id)?)),
+            ("tp", [x, y]) => Ok(Self::Teleport(Vec2::new(
+                parse_number(x)?,
+                parse_number(y)?,
+            ))),
+            ("give", _) => Err("usage: give <item uid> [amount]".to_string()),
+            ("levelup", _) => Err("usage: levelup <minigame>".to_string()),
+            ("unlock", _) => Err("usage: unlock <minigame>".to_string()),
+            ("tp", _) => Err("usage: tp <x> <y>".to_string()),
+            _ => Err(format!("unknown command: {}", verb)),
+        }
+    }
+
+    fn give(uid: &str, amount: &str) -> Result<Self, String> {
+        let item_type = ItemType::from_uid(uid)
+            .ok_or_else(|| format!("unknown item: {}", uid))?;
+        let amount = parse_number(amount)?;
+        if amount <= 0.0 {
+            return Err("amount must be positive".to_string());
+        }
+        Ok(Self::Give { item_type, amount })
+    }
+}
+
+fn minigame_id(id: &str) -> Result<&'static str, String> {
+    Minigame::from_id(id)
+        .map(|minigame| minigame.id())
+        .ok_or_else(|| format!("unknown minigame: {}", id))
+}
+
+fn parse_number(s: &str) -> Result<f32, String> {
+    s.parse::<f32>()
+        .ok()
+        .filter(|n| n.is_finite())
+        .ok_or_else(|| format!("not a number: {}", s))
+}
+
+pub fn console_input(
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut keyboard_events: MessageReader<KeyboardInput>,
+    mut console: ResMut<Console>,
+    mut console_commands: MessageWriter<ConsoleCommand>,
+) {
+    if kb_input.just_pressed(KeyCode::Backquote) {
+        console.open = !console.open;
+        console.input.clear();
+    }
+    if !console.open {
+        keyboard_events.clear();
+        return;
+    }
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                if line.trim().is_empty() {
+                    continue;
+                }
+                console.print(format!("> {}", line));
+                match ConsoleCommand::parse(&line) {
+                    Ok(command) => {
+                        console_commands.write(command);
+                    }
+                    Err(error) => console.print(error),
+                }
+            }
+            Key::Backspace => {
+                console.input.pop();
+            }
+            _ => {
+                let Some(text) = &event.text else {
+                    continue;
+                };
+                for c in text.chars() {
+                    if !c.is_control() && c != '`' {
+                        console.input.push(c);
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub fn run_console_commands(
+    mut commands: Commands,
+    mut console_commands: MessageReader<ConsoleCommand>,
+    mut console: ResMut<Console>,
+    mut random: ResMut<Random>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut minigames: ResMut<MinigamesResource>,
+    item_grid: Res<ItemGrid>,
+    item_query: Query<
+        (&Transform, &CircularArea, Entity),
+        (With<Item>, Without<Stuck>),
+    >,
+    player_query: Query<(&Transform, &CircularArea, Entity), With<Player>>,
+) {
+    for command in console_commands.read() {
+        match command {
+            ConsoleCommand::Give { item_type, amount } => {
+                let Ok((player_transform, _, _)) = player_query.single() else {
+                    continue;
+                };
+                // A missing PNG just gets the placeholder, but some catalog
+                // types (species without a palette, say) still panic when
+                // drawn. Refuse those here rather than take the game down;
+                // a trial draw at the smallest size is cheap.
+                let item = Item::new(*item_type, *amount);
+                let drawable = catch_unwind(AssertUnwindSafe(|| {
+                    item_type.draw(
+                        &mut Random::for_drawing(),
+                        item::texture_size(0.0),
+                    )
+                }))
+                .is_ok();
+                if !drawable {
+                    console.print(format!(
+                        "give: {} has no artwork yet",
+                        item.uid()
+                    ));
+                    continue;
+                }
+                commands.spawn((
+                    ItemBundle::new(
+                        &mut images,
+                        &mut generated_image_assets,
+                        item,
+                        Transform::from_translation(
+                            player_transform.translation + GIVE_OFFSET,
+                        ),
+                        Velocity::zero(),
+                    ),
+                    Emitted,
+                ));
+                console.print(format!("gave {} {}", amount, item.uid()));
+            }
+            ConsoleCommand::LevelUp(id) => match minigames.entity(id) {
+                Some(entity) => {
+                    commands.entity(entity).insert(LevelingUp);
+                    console.print(format!("leveling up {}", id));
+                }
+                None => console.print(format!("levelup: {} is locked", id)),
+            },
+            ConsoleCommand::Unlock(id) => {
+                if minigames.is_unlocked(id) {
+                    console.print(format!("unlock: {} already unlocked", id));
+                    continue;
+                }
+                spawn_unlocked(
+                    id,
+                    &mut commands,
+                    &mut random,
+                    &asset_server,
+                    &mut images,
+                    &mut generated_image_assets,
+                    &mut minigames,
+                    &item_grid,
+                    &item_query,
+                    &player_query,
+                );
+                console.print(format!("unlocked {}", id));
+            }
+            ConsoleCommand::Teleport(position) => {
+                let Ok((_, _, player_entity)) = player_query.single() else {
+                    continue;
+                };
+                commands.entity(player_entity).insert((
+                    Transform::from_translation(position.extend(0.0)),
+                    Velocity::zero(),
+                ));
+                console.print(format!("at {}, {}", position.x, position.y));
+            }
+        }
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct ConsoleText;
+
+fn setup_console(mut commands: Commands) {
+    commands.spawn((
+        ConsoleText,
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(0.0),
+            right: Val::Px(0.0),
+            bottom: Val::Px(0.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Visibility::Hidden,
+    ));
+}
+
+fn render_console(
+    console: Res<Console>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        *visibility = if console.open {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        let mut lines = console.log.clone();
+        lines.push(format!("` {}_", console.input));
+        text.0 = lines.join("\n");
+    }
+}
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_message::<ConsoleCommand>()
+            .add_systems(Startup, setup_console)
+            .add_systems(
+                Update,
+                (
+                    console_input.run_if(notes::composer_closed),
+                    run_console_commands,
+                    render_console,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            ConsoleCommand::parse("give physical/Lump/Gold 50"),
+            Ok(ConsoleCommand::Give {
+                item_type: Item::solid(Substance::Gold, BulkShape::Lump, 1.0)
+                    .r#type,
+                amount: 50.0,
+            })
+        );
+        assert_eq!(
+            ConsoleCommand::parse("levelup ball_breaker"),
+            Ok(ConsoleCommand::LevelUp(minigames::ball_breaker::ID))
+        );
+        assert_eq!(
+            ConsoleCommand::parse("unlock foundry"),
+            Ok(ConsoleCommand::Unlock(minigames::foundry::ID))
+        );
+        assert_eq!(
+            ConsoleCommand::parse("  tp 200   -300 "),
+            Ok(ConsoleCommand::Teleport(Vec2::new(200.0, -300.0)))
+        );
+    }
+
+    #[test]
+    fn rejects_bad_commands() {
+        assert!(ConsoleCommand::parse("").is_err());
+        assert!(ConsoleCommand::parse("fly").is_err());
+        assert!(ConsoleCommand::parse("give physical/Lump/Cheese").is_err());
+        assert!(ConsoleCommand::parse("give physical/Lump/Gold -1").is_err());
+        assert!(ConsoleCommand::parse("levelup chess").is_err());
+        assert!(ConsoleCommand::parse("tp 1").is_err());
+        assert!(ConsoleCommand::parse("tp 1 NaN").is_err());
+    }
+}
@@ -0,0 +1,349 @@
+use std::io::Write;
+use std::panic::PanicHookInfo;
+use std::sync::{Arc, Mutex, TryLockError};
+
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Crash recovery. Paths nobody has finished yet (an item type with no recipe,
+// say) still panic, and a panic shouldn't cost the player their session.
+// Every `SNAPSHOT_SECONDS` the world is captured into `CrashSnapshot`: the
+// save string F4 would export, the event log's last lines and the seed. The
+// panic hook `CrashPlugin` installs reaches it without the world, which a
+// panicking system can't hand over. On a panic the hook writes the save to
+// `CRASH_SAVE_PATH` and a report (the panic and where it happened, the
+// versions, the recent events) to `CRASH_REPORT_PATH`, then hands on to the
+// hook before it, which prints the panic as usual.
+//
+// On the next launch, if the crash save is there, a dialog offers it: Enter
+// restores it as an import would, Escape starts afresh. Either way the crash
+// save is removed, so it's offered once; the report stays for a bug report.
+// While the dialog is up nothing is snapshot, so crashing again before
+// answering it doesn't replace the save with the fresh board.
+
+pub const CRASH_SAVE_PATH: &str = "saves/crash.txt";
+pub const CRASH_REPORT_PATH: &str = "saves/crash-report.txt";
+const SAVE_DIR: &str = "saves";
+// at most this much play is lost
+const SNAPSHOT_SECONDS: f32 = 5.0;
+const REPORT_EVENTS: usize = 20;
+const DIALOG_FONT_SIZE: f32 = 16.0;
+
+// What the panic hook writes out.
+#[derive(Debug, Default, Clone)]
+pub struct Snapshot {
+    // as `SaveGame::export`; none until the first snapshot
+    pub save: Option<String>,
+    // the event log's last `REPORT_EVENTS` lines, oldest first
+    pub events: Vec<String>,
+    pub seed: u64,
+}
+
+// Shared with the panic hook.
+#[derive(Debug, Default, Clone, Resource)]
+pub struct CrashSnapshot(pub Arc<Mutex<Snapshot>>);
+
+// The report, in English whatever the locale, as it's for whoever fixes the
+// crash. `saved` is whether the emergency save was written.
+pub fn crash_report(
+    panic: &str,
+    location: Option<String>,
+    snapshot: &Snapshot,
+    saved: bool,
+) -> String {
+    let mut lines = vec![
+        format!("Galaxia {} crashed", env!("CARGO_PKG_VERSION")),
+        format!("Panic: {}", panic),
+        format!("At: {}", location.as_deref().unwrap_or("unknown")),
+        format!("Save version: {}", SAVE_VERSION),
+        format!(
+            "Build: {} {} {}",
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            }
+        ),
+        format!("Seed: {}", snapshot.seed),
+        format!(
+            "Emergency save: {}",
+            match (&snapshot.save, saved) {
+                (None, _) => "none, the game crashed before the first snapshot",
+                (Some(_), true) => CRASH_SAVE_PATH,
+                (Some(_), false) => "couldn't be written",
+            }
+        ),
+        "Recent events:".to_string(),
+    ];
+    lines.extend(snapshot.events.iter().map(|event| format!("  {}", event)));
+    lines.join("\n") + "\n"
+}
+
+fn write_file(path: &str, text: &str) -> Result<(), GalaxiaError> {
+    storage::create_dir_all(SAVE_DIR)
+        .and_then(|_| {
+            let mut file = storage::create(path)?;
+            file.write_all(text.as_bytes())?;
+            file.flush()
+        })
+        .map_err(|source| GalaxiaError::Write {
+            path: path.to_string(),
+            source,
+        })
+}
+
+fn write_crash_files(info: &PanicHookInfo, snapshot: &Snapshot) {
+    let saved = snapshot.save.as_ref().is_some_and(|save| {
+        write_file(CRASH_SAVE_PATH, save)
+            .inspect_err(|error| error!(%error, "emergency save failed"))
+            .is_ok()
+    });
+    let panic = info.payload_as_str().unwrap_or("(not a message)");
+    let location = info.location().map(ToString::to_string);
+    let report = crash_report(panic, location, snapshot, saved);
+    if let Err(error) = write_file(CRASH_REPORT_PATH, &report) {
+        error!(%error, "crash report not written");
+    }
+}
+
+// Chains onto whatever hook was there, so add after `DefaultPlugins`, whose
+// hook in a browser sends panics to the console.
+fn install_panic_hook(snapshot: CrashSnapshot) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // a panic while the snapshot is being taken on this thread would
+        // wait on itself; the report goes out without it
+        let snapshot = match snapshot.0.try_lock() {
+            Ok(snapshot) => snapshot.clone(),
+            Err(TryLockError::Poisoned(poisoned)) => {
+                poisoned.into_inner().clone()
+            }
+            Err(TryLockError::WouldBlock) => Snapshot::default(),
+        };
+        write_crash_files(info, &snapshot);
+        previous(info);
+    }));
+}
+
+pub fn snapshot_for_crash(
+    time: Res<Time<Real>>,
+    mut due: Local<f32>,
+    crash: Res<CrashSnapshot>,
+    minigames: Res<MinigamesResource>,
+    random: Res<Random>,
+    notifications: Option<Res<Notifications>>,
+    minigame_query: Query<&Minigame>,
+    note_query: Query<(&notes::Note, &Transform)>,
+    cosmetics: Res<cosmetics::Cosmetics>,
+) {
+    *due -= time.delta_secs();
+    if *due > 0.0 {
+        return;
+    }
+    *due = SNAPSHOT_SECONDS;
+    let save = SaveGame::capture(
+        &minigames,
+        minigame_query.iter(),
+        note_query.iter(),
+        &cosmetics,
+    )
+    .export();
+    let events = notifications.map_or_else(Vec::new, |notifications| {
+        let skip = notifications.log.len().saturating_sub(REPORT_EVENTS);
+        notifications
+            .log
+            .iter()
+            .skip(skip)
+            .map(|(message, count)| match count {
+                1 => message.clone(),
+                _ => format!("{} (x{})", message, count),
+            })
+            .collect()
+    });
+    let mut snapshot = match crash.0.lock() {
+        Ok(snapshot) => snapshot,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    *snapshot = Snapshot {
+        save: Some(save),
+        events,
+        seed: random.seed(),
+    };
+}
+
+// The save the last session left when it crashed, until the player answers
+// the dialog.
+#[derive(Debug, Default, Resource)]
+pub struct CrashRecovery {
+    pub save: Option<SaveGame>,
+}
+
+pub fn recovery_answered(recovery: Res<CrashRecovery>) -> bool {
+    recovery.save.is_none()
+}
+
+#[derive(Debug, Component)]
+pub struct CrashDialogText;
+
+fn forget_crash_save() {
+    if let Err(error) = storage::remove_file(CRASH_SAVE_PATH) {
+        warn!(%error, path = CRASH_SAVE_PATH, "crash save not removed");
+    }
+}
+
+fn check_for_crash(
+    mut commands: Commands,
+    mut recovery: ResMut<CrashRecovery>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let Ok(text) = storage::read_to_string(CRASH_SAVE_PATH) else {
+        return;
+    };
+    match SaveGame::import(&text) {
+        Ok(save) => recovery.save = Some(save),
+        Err(error) => {
+            notifications
+                .push(tr_fmt("crash.unreadable", &[("error", &error)]));
+            forget_crash_save();
+            return;
+        }
+    }
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(30.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_child((
+            CrashDialogText,
+            // so screen readers pick up its text
+            Label,
+            Node {
+                max_width: Val::Percent(60.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.85)),
+            Text::new(tr_fmt(
+                "crash.dialog",
+                &[("report", &CRASH_REPORT_PATH)],
+            )),
+            TextFont {
+                font_size: DIALOG_FONT_SIZE,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            TextLayout::new_with_justify(Justify::Center),
+        ));
+}
+
+// Enter restores the crash save, Escape drops it.
+fn crash_dialog_input(
+    mut commands: Commands,
+    mut kb_input: ResMut<ButtonInput<KeyCode>>,
+    mut recovery: ResMut<CrashRecovery>,
+    mut targets: ResMut<LevelTargets>,
+    mut minigames: ResMut<MinigamesResource>,
+    mut notifications: ResMut<Notifications>,
+    mut minigame_query: Query<&mut Minigame>,
+    note_query: Query<Entity, With<notes::Note>>,
+    dialog_query: Query<&ChildOf, With<CrashDialogText>>,
+    mut cosmetics: ResMut<cosmetics::Cosmetics>,
+) {
+    if kb_input.just_pressed(KeyCode::Enter) {
+        let Some(save) = recovery.save.take() else {
+            return;
+        };
+        let unknown = apply_save(
+            save,
+            &mut commands,
+            &mut targets,
+            &mut minigames,
+            &mut minigame_query,
+            &note_query,
+            &mut cosmetics,
+        );
+        notifications.push(tr_fmt("crash.restored", &[("unknown", &unknown)]));
+    } else if kb_input.just_pressed(KeyCode::Escape) {
+        recovery.save = None;
+        // so dismissing the dialog doesn't also quit the game
+        kb_input.clear_just_pressed(KeyCode::Escape);
+    } else {
+        return;
+    }
+    forget_crash_save();
+    for child_of in dialog_query.iter() {
+        commands.entity(child_of.parent()).despawn();
+    }
+}
+
+// For the windowed game only: the panic hook is the whole process's, and the
+// headless app's tests panic on purpose.
+pub struct CrashPlugin;
+
+impl Plugin for CrashPlugin {
+    fn build(&self, app: &mut App) {
+        let snapshot = CrashSnapshot::default();
+        install_panic_hook(snapshot.clone());
+        app.insert_resource(snapshot)
+            .init_resource::<CrashRecovery>()
+            .add_systems(Startup, check_for_crash)
+            .add_systems(
+                Update,
+                (
+                    crash_dialog_input
+                        .run_if(not(recovery_answered))
+                        .run_if(console::console_closed)
+                        // ahead of Escape quitting the game
+                        .before(GameSet::Input),
+                    snapshot_for_crash
+                        .run_if(recovery_answered)
+                        .after(GameSet::Minigames),
+                ),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_say_what_happened() {
+        let snapshot = Snapshot {
+            save: Some("AAAA".to_string()),
+            events: vec!["Quarry reached level 3".to_string()],
+            seed: 1234,
+        };
+        let report = crash_report(
+            "not yet implemented: Apple",
+            Some("src/entities/item.rs:10:5".to_string()),
+            &snapshot,
+            true,
+        );
+        assert!(report.starts_with(&format!(
+            "Galaxia {} crashed\n",
+            env!("CARGO_PKG_VERSION")
+        )));
+        assert!(report.contains("Panic: not yet implemented: Apple\n"));
+        assert!(report.contains("At: src/entities/item.rs:10:5\n"));
+        assert!(report.contains("Seed: 1234\n"));
+        assert!(
+            report.contains(&format!("Emergency save: {}\n", CRASH_SAVE_PATH))
+        );
+        assert!(report.ends_with("Recent events:\n  Quarry reached level 3\n"));
+    }
+
+    #[test]
+    fn reports_say_when_there_was_nothing_to_save() {
+        let report = crash_report("boom", None, &Snapshot::default(), false);
+        assert!(report.contains("At: unknown\n"));
+        assert!(report.contains("Emergency save: none"));
+    }
+}
@@ -0,0 +1,108 @@
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+// Arm, wait, fire. An entity with a `DelayedTrigger<T>` fires once its timer
+// runs out: `tick_delayed_triggers::<T>` takes the trigger off and writes a
+// `TriggerFired<T>` naming the entity. What firing does is up to whoever reads
+// the message. `T` is a marker saying what the trigger is for, so one entity
+// can wait on several things at once and each reader only hears its own.
+// Each `T` needs its tick system and its message registered.
+
+#[derive(Debug, Clone, Component)]
+pub struct DelayedTrigger<T: Send + Sync + 'static> {
+    pub timer: Timer,
+    marker: PhantomData<T>,
+}
+
+impl<T: Send + Sync + 'static> DelayedTrigger<T> {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            marker: PhantomData,
+        }
+    }
+
+    // How far along the wait is, 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        self.timer.fraction()
+    }
+}
+
+#[derive(Debug, Message)]
+pub struct TriggerFired<T: Send + Sync + 'static> {
+    pub entity: Entity,
+    marker: PhantomData<T>,
+}
+
+impl<T: Send + Sync + 'static> TriggerFired<T> {
+    pub fn new(entity: Entity) -> Self {
+        Self {
+            entity,
+            marker: PhantomData,
+        }
+    }
+}
+
+pub fn tick_delayed_triggers<T: Send + Sync + 'static>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut DelayedTrigger<T>)>,
+    mut fired: MessageWriter<TriggerFired<T>>,
+) {
+    for (entity, mut trigger) in query.iter_mut() {
+        trigger.timer.tick(time.delta());
+        if trigger.timer.is_finished() {
+            commands.entity(entity).remove::<DelayedTrigger<T>>();
+            fired.write(TriggerFired::new(entity));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::message::Messages;
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[derive(Debug, Clone)]
+    struct Marker;
+
+    fn step(world: &mut World, seconds: f32) -> Vec<Entity> {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        world
+            .run_system_once(tick_delayed_triggers::<Marker>)
+            .unwrap();
+        let mut messages =
+            world.resource_mut::<Messages<TriggerFired<Marker>>>();
+        let fired = messages
+            .iter_current_update_messages()
+            .map(|fired| fired.entity)
+            .collect();
+        messages.clear();
+        fired
+    }
+
+    #[test]
+    fn fires_once_when_its_time_is_up() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Messages<TriggerFired<Marker>>>();
+        let quick = world.spawn(DelayedTrigger::<Marker>::new(0.5)).id();
+        let slow = world.spawn(DelayedTrigger::<Marker>::new(1.5)).id();
+
+        assert!(step(&mut world, 0.25).is_empty());
+        let trigger = world.get::<DelayedTrigger<Marker>>(slow).unwrap();
+        assert!((trigger.fraction() - 1.0 / 6.0).abs() < 1e-4);
+        assert_eq!(step(&mut world, 0.25), vec![quick]);
+        assert!(world.get::<DelayedTrigger<Marker>>(quick).is_none());
+        assert!(step(&mut world, 0.5).is_empty());
+        assert_eq!(step(&mut world, 0.5), vec![slow]);
+        assert!(step(&mut world, 5.0).is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+use std::fmt;
+
+// What can go wrong in the game's own code. Failures that the game can live
+// through (a missing texture, say) come back as one of these rather than a
+// panic, so the caller can fall back and tell the player.
+
+#[derive(Debug)]
+pub enum GalaxiaError {
+    // the file couldn't be opened or read
+    Io {
+        path: String,
+        source: std::io::Error,
+    },
+    // the file was read but isn't an image we can decode
+    Decode {
+        path: String,
+        source: image::ImageError,
+    },
+    // the file couldn't be created or written
+    Write {
+        path: String,
+        source: std::io::Error,
+    },
+    // the image couldn't be encoded into the file
+    Encode {
+        path: String,
+        source: image::ImageError,
+    },
+    // the asset server couldn't load the file; boxed for the same reason as
+    // `Parse`
+    Load {
+        path: String,
+        source: Box<bevy::asset::AssetLoadError>,
+    },
+    // the file was read but isn't valid RON for what it should hold; boxed,
+    // as it's several times the size of the other variants
+    Parse {
+        path: String,
+        source: Box<ron::error::SpannedError>,
+    },
+}
+
+impl fmt::Display for GalaxiaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GalaxiaError::Io { path, source } => {
+                write!(f, "can't read {}: {}", path, source)
+            }
+            GalaxiaError::Decode { path, source } => {
+                write!(f, "can't decode {}: {}", path, source)
+            }
+            GalaxiaError::Write { path, source } => {
+                write!(f, "can't write {}: {}", path, source)
+            }
+            GalaxiaError::Encode { path, source } => {
+                write!(f, "can't save {}: {}", path, source)
+            }
+            GalaxiaError::Load { path, source } => {
+                write!(f, "can't load {}: {}", path, source)
+            }
+            GalaxiaError::Parse { path, source } => {
+                write!(f, "can't parse {}: {}", path, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for GalaxiaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GalaxiaError::Io { source, .. } => Some(source),
+            GalaxiaError::Decode { source, .. } => Some(source),
+            GalaxiaError::Write { source, .. } => Some(source),
+            GalaxiaError::Encode { source, .. } => Some(source),
+            GalaxiaError::Load { source, .. } => Some(source),
+            GalaxiaError::Parse { source, .. } => Some(source),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// A scrollable text record of what happened in the world: items minigames
+// made, levels gained, minigames and upgrades unlocked, plus every
+// notification. Useful for catching up on what happened off camera, and,
+// being plain text marked as a label, readable by a screen reader. F2 opens
+// it; Page Up and Page Down scroll.
+//
+// Game systems write `GameEvent`s; the log lives in `Notifications`, which
+// only the windowed game has.
+
+const LOG_PAGE_LINES: usize = 12;
+
+#[derive(Debug, Clone, PartialEq, Message)]
+pub enum GameEvent {
+    Produced { item: String, amount: f32 },
+    LeveledUp { minigame: String, level: u8 },
+    Unlocked { minigame: String },
+    Swept { count: u32 },
+    Recalled,
+    Upgraded { upgrade: String },
+    SkinEarned { skin: String },
+    // `action` is already translated
+    Undid { action: String },
+    CouldNotUndo { action: String },
+}
+
+impl GameEvent {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::Produced { item, amount } => tr_fmt(
+                "event.produced",
+                &[("amount", &format_amount(*amount)), ("item", item)],
+            ),
+            Self::LeveledUp { minigame, level } => tr_fmt(
+                "event.leveled_up",
+                &[("minigame", minigame), ("level", level)],
+            ),
+            Self::Unlocked { minigame } => {
+                tr_fmt("event.unlocked", &[("minigame", minigame)])
+            }
+            Self::Swept { count } => tr_fmt("event.swept", &[("count", count)]),
+            Self::Recalled => tr("event.recalled"),
+            Self::Upgraded { upgrade } => {
+                tr_fmt("event.upgraded", &[("upgrade", upgrade)])
+            }
+            Self::SkinEarned { skin } => {
+                tr_fmt("event.skin_earned", &[("skin", skin)])
+            }
+            Self::Undid { action } => {
+                tr_fmt("event.undid", &[("action", action)])
+            }
+            Self::CouldNotUndo { action } => {
+                tr_fmt("event.could_not_undo", &[("action", action)])
+            }
+        }
+    }
+}
+
+// Log what minigames emitted this tick. Runs before the amount audit, which
+// takes `Emitted` off again.
+pub fn log_emitted_items(
+    item_query: Query<&Item, Added<Emitted>>,
+    mut game_events: MessageWriter<GameEvent>,
+) {
+    for item in item_query.iter() {
+        game_events.write(GameEvent::Produced {
+            item: item.name(),
+            amount: item.amount,
+        });
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct EventLogPanel {
+    pub open: bool,
+    // lines scrolled back from the newest
+    pub scroll: usize,
+}
+
+impl EventLogPanel {
+    // The page of `log` the panel shows, oldest first.
+    pub fn page(
+        &self,
+        log: &std::collections::VecDeque<(String, u32)>,
+    ) -> String {
+        let end = log.len().saturating_sub(self.scroll);
+        let start = end.saturating_sub(LOG_PAGE_LINES);
+        let mut lines = vec![tr("event.title")];
+        lines.extend(log.range(start..end).map(|(message, count)| {
+            if *count > 1 {
+                format!("{} (x{})", message, count)
+            } else {
+                message.clone()
+            }
+        }));
+        lines.join("\n")
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct EventLogText;
+
+fn setup_event_log(mut commands: Commands) {
+    commands.spawn((
+        EventLogText,
+        // so screen readers pick up its text
+        Label,
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(6.0),
+            bottom: Val::Px(6.0),
+            max_width: Val::Percent(50.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Visibility::Hidden,
+    ));
+}
+
+fn record_game_events(
+    mut game_events: MessageReader<GameEvent>,
+    mut notifications: ResMut<Notifications>,
+) {
+    for event in game_events.read() {
+        notifications.record(event.describe());
+    }
+}
+
+fn event_log_input(
+    kb_input: Res<ButtonInput<KeyCode>>,
+    notifications: Res<Notifications>,
+    mut panel: ResMut<EventLogPanel>,
+) {
+    if kb_input.just_pressed(KeyCode::F2) {
+        panel.open = !panel.open;
+        panel.scroll = 0;
+    }
+    if !panel.open {
+        return;
+    }
+    let max_scroll = notifications.log.len().saturating_sub(LOG_PAGE_LINES);
+    if kb_input.just_pressed(KeyCode::PageUp) {
+        panel.scroll = (panel.scroll + LOG_PAGE_LINES).min(max_scroll);
+    }
+    if kb_input.just_pressed(KeyCode::PageDown) {
+        panel.scroll = panel.scroll.saturating_sub(LOG_PAGE_LINES);
+    }
+}
+
+fn render_event_log(
+    panel: Res<EventLogPanel>,
+    notifications: Res<Notifications>,
+    mut text_query: Query<
+        (&mut Text, &mut Visibility, &mut Label),
+        With<EventLogText>,
+    >,
+) {
+    let log_changed = panel.open && notifications.is_changed();
+    if !panel.is_changed() && !log_changed {
+        return;
+    }
+    for (mut text, mut visibility, mut label) in text_query.iter_mut() {
+        *visibility = if panel.open {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        text.0 = panel.page(&notifications.log);
+        // bevy_ui re-reads a label's text for screen readers when it changes
+        label.set_changed();
+    }
+}
+
+pub struct EventLogPlugin;
+
+impl Plugin for EventLogPlugin {
+    fn build(&self, app: &mut App) {
+        // GamePlugin adds the GameEvent message, so the headless app has it
+        app.init_resource::<EventLogPanel>()
+            .add_systems(Startup, setup_event_log)
+            .add_systems(
+                Update,
+                (
+                    record_game_events,
+                    event_log_input.run_if(console::console_closed),
+                    render_event_log,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panel_pages_back_from_the_newest() {
+        let mut notifications = Notifications::default();
+        for i in 0..LOG_PAGE_LINES * 2 {
+            notifications.record(format!("event {}", i));
+        }
+        notifications.record(format!("event {}", LOG_PAGE_LINES * 2 - 1));
+        let mut panel = EventLogPanel::default();
+        let page = panel.page(&notifications.log);
+        assert!(
+            page.ends_with(&format!("event {} (x2)", LOG_PAGE_LINES * 2 - 1))
+        );
+        assert!(!page.contains("event 0\n"));
+
+        panel.scroll = LOG_PAGE_LINES;
+        let page = panel.page(&notifications.log);
+        assert!(page.contains("event 0\n"));
+        assert!(page.ends_with(&format!("event {}", LOG_PAGE_LINES - 1)));
+    }
+
+    #[test]
+    fn events_read_as_sentences() {
+        let event = GameEvent::LeveledUp {
+            minigame: "Quarry".to_string(),
+            level: 3,
+        };
+        assert_eq!(event.describe(), "Quarry reached level 3");
+        let event = GameEvent::Produced {
+            item: "Gold Lump".to_string(),
+            amount: 0.1 + 0.2,
+        };
+        assert_eq!(event.describe(), "Made 0.3 Gold Lump");
+    }
+}
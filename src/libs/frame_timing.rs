@@ -0,0 +1,263 @@
+use std::time::Duration;
+
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore,
+    EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+    RegisterDiagnostic,
+};
+use bevy::prelude::*;
+
+use crate::libs::*;
+
+// Where a frame's time goes, to pin the hitches players report on whatever
+// caused them. The heavy systems (combining loose items, ingesting them, the
+// levelup respawn) add what they spent to `FrameTimings` as they run, fixed
+// updates and all, and drawing item textures on a cache miss adds its time
+// to `GeneratedImageAssets`. Once a frame `publish_frame_timings` turns those
+// into Bevy diagnostics, in milliseconds, zero when nothing ran. F5 shows them
+// over the game, smoothed, beside Bevy's frame time and entity count.
+//
+// The overlay also shows the fixed steps each frame ran and the time dropped
+// to keep up (see catch_up.rs), and how many item pictures are cached and
+// roughly what they take.
+//
+// The same work opens `tracing` spans, so a profiler shows it on a timeline
+// too: build with Bevy's `trace_tracy` or `trace_chrome` feature.
+
+const OVERLAY_FONT_SIZE: f32 = 13.0;
+pub const CACHED_PICTURES: DiagnosticPath =
+    DiagnosticPath::const_new("galaxia/cached_pictures");
+pub const CACHED_PICTURE_MIB: DiagnosticPath =
+    DiagnosticPath::const_new("galaxia/cached_picture_mib");
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Timed {
+    Combine,
+    Ingest,
+    Levelup,
+    Draw,
+}
+
+impl Timed {
+    pub const ALL: [Self; 4] =
+        [Self::Combine, Self::Ingest, Self::Levelup, Self::Draw];
+
+    pub const fn path(self) -> DiagnosticPath {
+        match self {
+            Self::Combine => DiagnosticPath::const_new("galaxia/combine"),
+            Self::Ingest => DiagnosticPath::const_new("galaxia/ingest"),
+            Self::Levelup => DiagnosticPath::const_new("galaxia/levelup"),
+            Self::Draw => DiagnosticPath::const_new("galaxia/draw_textures"),
+        }
+    }
+
+    // The locale key of its line on the overlay.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Combine => "timing.combine",
+            Self::Ingest => "timing.ingest",
+            Self::Levelup => "timing.levelup",
+            Self::Draw => "timing.draw",
+        }
+    }
+}
+
+// Time spent so far this frame, by `Timed`.
+#[derive(Debug, Default, Resource)]
+pub struct FrameTimings {
+    spent: [Duration; Timed::ALL.len()],
+}
+
+impl FrameTimings {
+    pub fn add(&mut self, timed: Timed, spent: Duration) {
+        self.spent[timed as usize] += spent;
+    }
+
+    pub fn take(&mut self, timed: Timed) -> Duration {
+        std::mem::take(&mut self.spent[timed as usize])
+    }
+}
+
+pub fn publish_frame_timings(
+    mut timings: ResMut<FrameTimings>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    images: Res<Assets<Image>>,
+    mut diagnostics: Diagnostics,
+) {
+    let drawing = generated_image_assets.take_drawing();
+    timings.add(Timed::Draw, drawing);
+    for timed in Timed::ALL {
+        let spent = timings.take(timed);
+        diagnostics
+            .add_measurement(&timed.path(), || spent.as_secs_f64() * 1000.0);
+    }
+    diagnostics.add_measurement(&CACHED_PICTURES, || {
+        generated_image_assets.len() as f64
+    });
+    diagnostics.add_measurement(&CACHED_PICTURE_MIB, || {
+        generated_image_assets.estimated_bytes(&images) as f64
+            / (1024.0 * 1024.0)
+    });
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct TimingOverlay {
+    pub open: bool,
+}
+
+#[derive(Debug, Component)]
+pub struct TimingText;
+
+// The overlay's lines: each diagnostic's smoothed value, or a dash before
+// there is one.
+pub fn overlay_text(store: &DiagnosticsStore) -> String {
+    let value = |path: &DiagnosticPath| {
+        store
+            .get(path)
+            .and_then(Diagnostic::smoothed)
+            .map_or("-".to_string(), |value| format!("{:.2}", value))
+    };
+    let mut lines = vec![
+        tr("timing.title"),
+        tr_fmt(
+            "timing.frame",
+            &[
+                ("ms", &value(&FrameTimeDiagnosticsPlugin::FRAME_TIME)),
+                ("fps", &value(&FrameTimeDiagnosticsPlugin::FPS)),
+            ],
+        ),
+    ];
+    for timed in Timed::ALL {
+        lines.push(tr_fmt(timed.label(), &[("ms", &value(&timed.path()))]));
+    }
+    lines.push(tr_fmt(
+        "timing.fixed_steps",
+        &[
+            ("steps", &value(&catch_up::FIXED_STEPS)),
+            ("dropped", &value(&catch_up::DROPPED)),
+        ],
+    ));
+    lines.push(tr_fmt(
+        "timing.entities",
+        &[("count", &value(&EntityCountDiagnosticsPlugin::ENTITY_COUNT))],
+    ));
+    lines.push(tr_fmt(
+        "timing.pictures",
+        &[
+            ("count", &value(&CACHED_PICTURES)),
+            ("mib", &value(&CACHED_PICTURE_MIB)),
+        ],
+    ));
+    lines.join("\n")
+}
+
+fn setup_timing_overlay(mut commands: Commands) {
+    commands.spawn((
+        TimingText,
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(6.0),
+            bottom: Val::Px(6.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        Text::new(""),
+        TextFont {
+            font_size: OVERLAY_FONT_SIZE,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Visibility::Hidden,
+    ));
+}
+
+fn timing_overlay_input(
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<TimingOverlay>,
+) {
+    if kb_input.just_pressed(KeyCode::F5) {
+        overlay.open = !overlay.open;
+    }
+}
+
+fn render_timing_overlay(
+    overlay: Res<TimingOverlay>,
+    store: Res<DiagnosticsStore>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<TimingText>>,
+) {
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        if !overlay.open {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        }
+        visibility.set_if_neq(Visibility::Visible);
+        text.0 = overlay_text(&store);
+    }
+}
+
+// The timing, in `GamePlugin`, so the headless app keeps it too.
+pub struct FrameTimingPlugin;
+
+impl Plugin for FrameTimingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameTimings>()
+            .add_systems(Last, publish_frame_timings);
+        for timed in Timed::ALL {
+            app.register_diagnostic(
+                Diagnostic::new(timed.path()).with_suffix("ms"),
+            );
+        }
+        app.register_diagnostic(Diagnostic::new(CACHED_PICTURES))
+            .register_diagnostic(
+                Diagnostic::new(CACHED_PICTURE_MIB).with_suffix("MiB"),
+            );
+    }
+}
+
+// The F5 overlay, for the windowed game.
+pub struct TimingOverlayPlugin;
+
+impl Plugin for TimingOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            FrameTimeDiagnosticsPlugin::default(),
+            EntityCountDiagnosticsPlugin::default(),
+        ))
+        .init_resource::<TimingOverlay>()
+        .add_systems(Startup, setup_timing_overlay)
+        .add_systems(
+            Update,
+            (
+                timing_overlay_input.run_if(console::console_closed),
+                render_timing_overlay,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timings_add_up_until_taken() {
+        let mut timings = FrameTimings::default();
+        timings.add(Timed::Combine, Duration::from_millis(2));
+        timings.add(Timed::Combine, Duration::from_millis(3));
+        timings.add(Timed::Draw, Duration::from_millis(1));
+        assert_eq!(timings.take(Timed::Combine), Duration::from_millis(5));
+        assert_eq!(timings.take(Timed::Combine), Duration::ZERO);
+        assert_eq!(timings.take(Timed::Ingest), Duration::ZERO);
+        assert_eq!(timings.take(Timed::Draw), Duration::from_millis(1));
+    }
+
+    #[test]
+    fn the_overlay_has_a_line_for_each() {
+        let text = overlay_text(&DiagnosticsStore::default());
+        // the title, the frame, each timed system, the fixed steps, the
+        // entity count and the cached pictures
+        assert_eq!(text.lines().count(), Timed::ALL.len() + 5);
+    }
+}
@@ -0,0 +1,38 @@
+use bevy::prelude::*;
+
+// The stages the game's systems run in. Each domain's plugin puts its systems
+// in its set, and `GamePlugin` orders the sets per schedule:
+//
+// - Startup: Minigames, Player, Camera
+// - Update: Input, Camera, Player, Items, Minigames, Inventory, Pointer
+// - FixedUpdate: Clock, Minigames, Items, Inventory, Audit
+//
+// A system only names another domain's system when it needs a finer order
+// than its set gives.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub enum GameSet {
+    // this frame's keys and clicks, before anything reads them
+    Input,
+    // the world clock, before the minigames that read it
+    Clock,
+    Camera,
+    Player,
+    Items,
+    Minigames,
+    Inventory,
+    // hover text and highlights, over everything that moved this frame
+    Pointer,
+    // bookkeeping over what the rest of the tick did
+    Audit,
+}
+
+// Systems that only have work in some ticks. `GamePlugin` gives each set a
+// run condition, so idle frames skip them. These cut across `GameSet`: a
+// system can be in both.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub enum WorkSet {
+    // levelup animations and respawns, while anything is `LevelingUp`
+    Levelup,
+    // reactions to contacts starting, in ticks Rapier reported some
+    Collisions,
+}
@@ -0,0 +1,124 @@
+use bevy::ecs::query::QueryFilter;
+use bevy::prelude::*;
+
+use crate::libs::*;
+
+// A grid of square cells laid out inside a minigame, for minigames that are
+// played on one. Cell (0, 0) is top-left and rows count down; the grid is
+// centered on the minigame. Each cell is a child entity with a `GridCell`,
+// its own `RectangularArea` and a `Sprite`, clickable and hoverable, so
+// picking finds the cell under the cursor like anything else. Minigames add a
+// marker of their own to tell their cells apart in queries.
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct GridWidget {
+    pub cols: u8,
+    pub rows: u8,
+    // the pitch between cell centers
+    pub cell_size: f32,
+}
+
+impl GridWidget {
+    pub fn new(cols: u8, rows: u8, cell_size: f32) -> Self {
+        Self {
+            cols,
+            rows,
+            cell_size,
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.cols as f32, self.rows as f32) * self.cell_size
+    }
+
+    pub fn cell_area(&self) -> RectangularArea {
+        RectangularArea {
+            width: self.cell_size,
+            height: self.cell_size,
+        }
+    }
+
+    // Where the center of cell (x, y) sits, relative to the grid's center.
+    pub fn translation(&self, x: u8, y: u8) -> Vec3 {
+        Vec3::new(
+            (x as f32 - (self.cols as f32 - 1.0) / 2.0) * self.cell_size,
+            ((self.rows as f32 - 1.0) / 2.0 - y as f32) * self.cell_size,
+            0.0,
+        )
+    }
+
+    // Spawns every cell under `parent`, row by row, each with what `cell`
+    // gives for its coordinates: at least a `Sprite` and the minigame's
+    // marker.
+    pub fn spawn<B: Bundle>(
+        &self,
+        parent: &mut ChildSpawnerCommands,
+        mut cell: impl FnMut(u8, u8) -> B,
+    ) {
+        for y in 0..self.rows {
+            for x in 0..self.cols {
+                parent.spawn((
+                    GridCell { x, y },
+                    self.cell_area(),
+                    Clickable::new(CLICK_PRIORITY_CONTENT),
+                    Hoverable,
+                    Transform::from_translation(self.translation(x, y)),
+                    cell(x, y),
+                ));
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Component)]
+pub struct GridCell {
+    pub x: u8,
+    pub y: u8,
+}
+
+impl GridCell {
+    // The cell `target` is, if it's one the query sees, with the entity of
+    // the minigame it's in. For resolving a click's or hover's target.
+    pub fn hit<F: QueryFilter>(
+        target: Option<Entity>,
+        query: &Query<(&GridCell, &ChildOf), F>,
+    ) -> Option<(Entity, GridCell)> {
+        let (cell, parent) = query.get(target?).ok()?;
+        Some((parent.parent(), *cell))
+    }
+}
+
+// Recolors or retextures all of `grid`'s cells at once: `paint` gets each
+// cell's coordinates and its sprite.
+pub fn paint_grid<F: QueryFilter>(
+    grid: Entity,
+    query: &mut Query<(&GridCell, &ChildOf, &mut Sprite), F>,
+    mut paint: impl FnMut(u8, u8, &mut Sprite),
+) {
+    for (cell, parent, mut sprite) in query.iter_mut() {
+        if parent.parent() == grid {
+            paint(cell.x, cell.y, &mut sprite);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_lay_out_from_the_top_left() {
+        let grid = GridWidget::new(3, 2, 10.0);
+        assert_eq!(grid.size(), Vec2::new(30.0, 20.0));
+        // row 0 is on top
+        assert_eq!(grid.translation(0, 0), Vec3::new(-10.0, 5.0, 0.0));
+        assert_eq!(grid.translation(2, 1), Vec3::new(10.0, -5.0, 0.0));
+        // cells don't overlap and fill the grid
+        let corner = grid.translation(2, 0).truncate() + grid.cell_size / 2.0;
+        assert_eq!(corner, grid.size() / 2.0);
+
+        // a single cell sits on the center
+        let one = GridWidget::new(1, 1, 25.0);
+        assert_eq!(one.translation(0, 0), Vec3::ZERO);
+    }
+}
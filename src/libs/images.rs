@@ -1,58 +1,280 @@
-use bevy::prelude::Image;
-use bevy::asset::RenderAssetUsages;
+use std::sync::Mutex;
+
+use bevy::asset::{AssetLoadFailedEvent, RenderAssetUsages};
+use bevy::prelude::*;
 use bevy::render::render_resource::{
     Extent3d, TextureDimension, TextureFormat,
 };
-use image::ImageReader;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageReader};
+
+use crate::libs::*;
+
+// Failures from `load_image_or_placeholder`, waiting for
+// `report_load_failures` to tell the player. Drawing happens deep inside
+// item code with no access to resources, hence the queue.
+static LOAD_FAILURES: Mutex<Vec<GalaxiaError>> = Mutex::new(Vec::new());
 
-pub fn load_image(path: &String) -> Image {
+fn read_image(path: &String) -> Result<image::RgbaImage, GalaxiaError> {
     let img = ImageReader::open(path)
-        .unwrap()
+        .map_err(|source| GalaxiaError::Io {
+            path: path.clone(),
+            source,
+        })?
         .decode()
-        .unwrap()
+        .map_err(|source| GalaxiaError::Decode {
+            path: path.clone(),
+            source,
+        })?
         .into_rgba8();
+    Ok(img)
+}
+
+pub fn load_image(path: &String) -> Result<Image, GalaxiaError> {
+    let img = read_image(path)?;
+    Ok(rgba_image(img.width(), img.height(), img.into_raw()))
+}
+
+// Loads the file scaled down to fit `size` pixels square (never up). A missing
+// or broken file gets the placeholder, and the failure is queued for the
+// player to see, rather than taking the game down.
+pub fn load_image_or_placeholder(path: &String, size: u32) -> Image {
+    match read_image(path) {
+        Ok(img) => {
+            let img = if img.width() > size || img.height() > size {
+                DynamicImage::ImageRgba8(img)
+                    .resize(size, size, FilterType::Triangle)
+                    .into_rgba8()
+            } else {
+                img
+            };
+            rgba_image(img.width(), img.height(), img.into_raw())
+        }
+        Err(error) => {
+            if let Ok(mut failures) = LOAD_FAILURES.lock() {
+                failures.push(error);
+            }
+            placeholder_image()
+        }
+    }
+}
+
+// A picture the asset server couldn't load gets the placeholder in its place,
+// and the failure is queued like any other.
+pub fn replace_failed_pictures(
+    mut images: ResMut<Assets<Image>>,
+    mut failures: MessageReader<AssetLoadFailedEvent<Image>>,
+) {
+    for failure in failures.read() {
+        let _ = images.insert(failure.id, placeholder_image());
+        if let Ok(mut queued) = LOAD_FAILURES.lock() {
+            queued.push(GalaxiaError::Load {
+                path: failure.path.to_string(),
+                source: Box::new(failure.error.clone()),
+            });
+        }
+    }
+}
+
+// Item pictures nobody has seen for this long are dropped from the cache, to
+// be drawn again if they come back.
+pub const UNSEEN_MINUTES: f32 = 5.0;
+const SWEEP_SECONDS: f32 = 30.0;
+
+// Every so often, forgets the cached item pictures that haven't been on show
+// or asked for in `UNSEEN_MINUTES`, so the cache doesn't only ever grow.
+pub fn evict_unseen_pictures(
+    time: Res<Time<Real>>,
+    mut due: Local<f32>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    sprites: Query<&Sprite>,
+) {
+    let now = time.elapsed_secs();
+    if now < *due {
+        return;
+    }
+    *due = now + SWEEP_SECONDS;
+    let on_show = sprites.iter().map(|sprite| sprite.image.id()).collect();
+    let evicted =
+        generated_image_assets.sweep(now, &on_show, UNSEEN_MINUTES * 60.0);
+    if evicted > 0 {
+        debug!("Evicted {} unseen item pictures", evicted);
+    }
+}
+
+pub fn take_load_failures() -> Vec<GalaxiaError> {
+    match LOAD_FAILURES.lock() {
+        Ok(mut failures) => std::mem::take(&mut *failures),
+        Err(_) => Vec::new(),
+    }
+}
+
+// Magenta and black checks: unmissable, so a missing texture gets noticed.
+pub fn placeholder_image() -> Image {
+    const SIZE: u32 = 8;
+    let mut pixels = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            if (x + y) % 2 == 0 {
+                pixels.extend_from_slice(&[255, 0, 255, 255]);
+            } else {
+                pixels.extend_from_slice(&[0, 0, 0, 255]);
+            }
+        }
+    }
+    rgba_image(SIZE, SIZE, pixels)
+}
+
+// Every texture, loaded or drawn, is 8-bit RGBA holding sRGB values: that's
+// what PNGs store and what the palettes are written in. Tagging them all as
+// sRGB lets the GPU convert to linear when sampling, so art from either
+// source comes out equally bright.
+pub const TEXTURE_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+pub fn rgba_image(width: u32, height: u32, pixels: Vec<u8>) -> Image {
     Image::new(
         Extent3d {
-            width: img.width(),
-            height: img.height(),
+            width,
+            height,
             depth_or_array_layers: 1,
         },
         TextureDimension::D2,
-        img.into_raw(), // converts to Vec<u8>
-        TextureFormat::Rgba8Unorm,
+        pixels,
+        TEXTURE_FORMAT,
         RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
     )
 }
 
 pub mod image_gen {
-    use std::collections::HashMap;
+    use std::collections::{HashMap, HashSet};
+    use std::time::Duration;
 
-    use bevy::asset::Handle;
+    use crate::libs::random::Random;
+    use bevy::asset::{AssetId, AssetServer, Assets, Handle};
     use bevy::ecs::prelude::Resource;
-    use bevy::prelude::Image;
-    use bevy::asset::RenderAssetUsages;
-    use bevy::render::render_resource::{
-        Extent3d, TextureDimension, TextureFormat,
-    };
     use bevy::image::ImageSampler;
-    use wyrand::WyRand;
+    use bevy::prelude::Image;
+    use serde::Deserialize;
 
     use crate::item::rune;
 
-    // For images that have already been generated.
+    // An image kept for reuse, and when it was last asked for or on show.
+    struct Cached {
+        image: Handle<Image>,
+        seen: f32,
+    }
+
+    // For images that have already been generated. Ones unseen for a while
+    // are forgotten by `sweep`, and drawn again if they're asked for later.
     #[derive(Default, Resource)]
-    pub struct GeneratedImageAssets(pub HashMap<String, Handle<Image>>);
+    pub struct GeneratedImageAssets {
+        images: HashMap<String, Cached>,
+        // seconds of game time, as of the last sweep
+        now: f32,
+        // When set, every lookup hits and gets this instead, so nothing is
+        // ever drawn. Used by the headless app, where nobody sees the images.
+        placeholder: Option<Handle<Image>>,
+        // Loads pictures, when there is one. Without it they're read
+        // straight from disk, which only works natively.
+        asset_server: Option<AssetServer>,
+        // spent drawing since `take_drawing` last asked
+        drawing: Duration,
+    }
 
     impl GeneratedImageAssets {
+        pub fn stubbed(placeholder: Handle<Image>) -> Self {
+            Self {
+                placeholder: Some(placeholder),
+                ..Self::default()
+            }
+        }
+
+        pub fn with_asset_server(asset_server: AssetServer) -> Self {
+            Self {
+                asset_server: Some(asset_server),
+                ..Self::default()
+            }
+        }
+
+        // A handle to the picture at `path` under assets/, loading in the
+        // background; None if there's no asset server to load it with.
+        pub fn load_picture(&self, path: &str) -> Option<Handle<Image>> {
+            let asset_server = self.asset_server.as_ref()?;
+            Some(asset_server.load(path.to_string()))
+        }
+
         pub fn insert(&mut self, uid: String, image: &Handle<Image>) {
-            self.0.insert(uid, image.clone());
+            let cached = Cached {
+                image: image.clone(),
+                seen: self.now,
+            };
+            self.images.insert(uid, cached);
+        }
+
+        pub fn get(&mut self, uid: &String) -> Option<Handle<Image>> {
+            let Some(cached) = self.images.get_mut(uid) else {
+                return self.placeholder.clone();
+            };
+            cached.seen = self.now;
+            Some(cached.image.clone())
+        }
+
+        pub fn len(&self) -> usize {
+            self.images.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.images.is_empty()
+        }
+
+        // What the images kept take, at four bytes a pixel. A picture still
+        // loading counts nothing yet.
+        pub fn estimated_bytes(&self, images: &Assets<Image>) -> u64 {
+            self.images
+                .values()
+                .filter_map(|cached| images.get(&cached.image))
+                .map(|image| {
+                    u64::from(image.width()) * u64::from(image.height()) * 4
+                })
+                .sum()
+        }
+
+        // Marks the images `on_show` as seen at `now`, then forgets those
+        // unseen for longer than `unseen_seconds`. Returns how many it
+        // forgot. An image forgotten here lives on while anything still
+        // holds its handle.
+        pub fn sweep(
+            &mut self,
+            now: f32,
+            on_show: &HashSet<AssetId<Image>>,
+            unseen_seconds: f32,
+        ) -> usize {
+            self.now = now;
+            let before = self.images.len();
+            self.images.retain(|_, cached| {
+                if on_show.contains(&cached.image.id()) {
+                    cached.seen = now;
+                }
+                now - cached.seen <= unseen_seconds
+            });
+            before - self.images.len()
+        }
+
+        pub fn add_drawing(&mut self, spent: Duration) {
+            self.drawing += spent;
+        }
+
+        pub fn take_drawing(&mut self) -> Duration {
+            std::mem::take(&mut self.drawing)
         }
 
-        pub fn get(&self, uid: &String) -> Option<Handle<Image>> {
-            self.0.get(uid).cloned()
+        // Forget every image, so each is drawn afresh when next asked for.
+        pub fn clear(&mut self) {
+            self.images.clear();
         }
     }
 
+    #[derive(Clone, Debug)]
     pub struct ColorPalette {
         pub colorants: Vec<Colorant>,
         pub total_weight: u64,
@@ -72,10 +294,7 @@ pub mod image_gen {
             self
         }
 
-        pub fn adjust_alpha_looseness(
-            &self,
-            alpha_looseness: u8,
-        ) -> Self {
+        pub fn adjust_alpha_looseness(&self, alpha_looseness: u8) -> Self {
             let mut new_palette = Self::new();
             for colorant in &self.colorants {
                 new_palette.add_colorant(
@@ -85,23 +304,18 @@ pub mod image_gen {
             new_palette
         }
 
-        pub fn pick(&self, rand: &mut WyRand) -> Colorant {
-            let mut pick = rand.rand() % self.total_weight;
-            for color in &self.colorants {
-                if pick < color.weight as u64 {
-                    return *color;
-                }
-                pick -= color.weight as u64;
-            }
-            panic!("ColorPalette::pick: should never get here");
+        pub fn pick(&self, rand: &mut Random) -> Colorant {
+            *rand
+                .pick_weighted(&self.colorants, |color| color.weight as u64)
+                .expect("ColorPalette::pick: the palette weighs nothing")
         }
 
-        pub fn pick_color(&self, rand: &mut WyRand) -> Color {
+        pub fn pick_color(&self, rand: &mut Random) -> Color {
             self.pick(rand).pick(rand)
         }
 
         // simply draw a pixel for each coordinate
-        pub fn draw_block(&self, rand: &mut WyRand, size: u32) -> Image {
+        pub fn draw_block(&self, rand: &mut Random, size: u32) -> Image {
             let mut colors = Colors::new(size, size);
             for _ in 0..(size * size) {
                 colors.add_color(self.pick_color(rand));
@@ -111,7 +325,7 @@ pub mod image_gen {
 
         // draw a non-transparent pixel for each coordinate within a radius
         // draw a fully transparent pixel for each coordinate outside the radius
-        pub fn draw_ball(&self, rand: &mut WyRand, size: u32) -> Image {
+        pub fn draw_ball(&self, rand: &mut Random, size: u32) -> Image {
             let radius = size / 2;
             let radius2 = (radius * radius) as i32;
             let mut colors = Colors::new(size, size);
@@ -132,7 +346,7 @@ pub mod image_gen {
 
         // draw a triangle with a rounded top
         // (written by claude)
-        pub fn draw_powder(&self, rand: &mut WyRand, size: u32) -> Image {
+        pub fn draw_powder(&self, rand: &mut Random, size: u32) -> Image {
             let radius = size / 2;
             let radius2 = radius * radius;
             let mut colors = Colors::new(size, size);
@@ -172,7 +386,7 @@ pub mod image_gen {
 
         // draw four irregularly overlapping circles
         // (written by claude)
-        pub fn draw_lump(&self, rand: &mut WyRand, size: u32) -> Image {
+        pub fn draw_lump(&self, rand: &mut Random, size: u32) -> Image {
             let radius = size / 2;
             let small_radius = (radius as f32 * 0.6) as u32;
             let small_radius2 = small_radius * small_radius;
@@ -181,10 +395,8 @@ pub mod image_gen {
             // Generate four random centers using u64 and converting to appropriate range
             let mut centers = Vec::with_capacity(4);
             for _ in 0..4 {
-                let offset_x =
-                    (rand.rand() as f32 / u64::MAX as f32 - 0.5) * 0.8;
-                let offset_y =
-                    (rand.rand() as f32 / u64::MAX as f32 - 0.5) * 0.8;
+                let offset_x = (rand.next_f32() - 0.5) * 0.8;
+                let offset_y = (rand.next_f32() - 0.5) * 0.8;
                 centers.push((offset_x, offset_y));
             }
 
@@ -218,7 +430,7 @@ pub mod image_gen {
             colors.to_image()
         }
 
-        pub fn draw_shovel_head(&self, rand: &mut WyRand, size: u32) -> Image {
+        pub fn draw_shovel_head(&self, rand: &mut Random, size: u32) -> Image {
             let radius = size / 2;
             let radius2 = radius * radius;
             let mut colors = Colors::new(size, size);
@@ -253,20 +465,34 @@ pub mod image_gen {
         }
     }
 
-    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    // Deserialized from assets/palettes.ron, where everything but the colour
+    // itself may be left out.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
     pub struct Colorant {
         pub red: u8,
         pub green: u8,
         pub blue: u8,
+        #[serde(default = "opaque")]
         pub alpha: u8,
         // how often this color should appear in the ColorPalette
+        #[serde(default = "one")]
         pub weight: u8,
         // how much the color varies; 0 is none and 255 is all
         // (128 is all if t he colors are all 127 or 128)
+        #[serde(default)]
         pub looseness: u8,
+        #[serde(default)]
         pub alpha_looseness: u8,
     }
 
+    fn opaque() -> u8 {
+        255
+    }
+
+    fn one() -> u8 {
+        1
+    }
+
     impl Colorant {
         pub fn new(
             red: u8,
@@ -325,7 +551,7 @@ pub mod image_gen {
             }
         }
 
-        pub fn pick(&self, rand: &mut WyRand) -> Color {
+        pub fn pick(&self, rand: &mut Random) -> Color {
             let (red, green, blue) = if self.looseness == 0 {
                 (self.red, self.green, self.blue)
             } else {
@@ -343,19 +569,16 @@ pub mod image_gen {
             Color::new(red, green, blue, alpha)
         }
 
-        fn random_of_color(base: u8, rand: &mut WyRand, looseness: u8) -> u8 {
-            let r = rand.rand() % (looseness as u64 + 1);
-            if r < looseness as u64 / 2 {
-                if r > base as u64 {
-                    0
-                } else {
-                    base - r as u8
-                }
-            } else if r + base as u64 > 255 {
-                255
-            } else {
-                base + r as u8
-            }
+        // `base` jittered evenly either way by up to half the looseness
+        // (rounded up), clamped to a valid channel.
+        pub fn random_of_color(
+            base: u8,
+            rand: &mut Random,
+            looseness: u8,
+        ) -> u8 {
+            let reach = (looseness as i64 + 1) / 2;
+            let offset = rand.next_range(2 * reach as u64 + 1) as i64 - reach;
+            (base as i64 + offset).clamp(0, 255) as u8
         }
     }
 
@@ -409,18 +632,7 @@ pub mod image_gen {
         }
 
         pub fn to_image(&self) -> Image {
-            Image::new(
-                Extent3d {
-                    width: self.width,
-                    height: self.height,
-                    depth_or_array_layers: 1,
-                },
-                TextureDimension::D2,
-                self.bytes.clone(),
-                // TextureFormat::Rgba8Unorm,
-                TextureFormat::Rgba8UnormSrgb,
-                RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
-            )
+            super::rgba_image(self.width, self.height, self.bytes.clone())
         }
     }
 
@@ -441,18 +653,88 @@ pub mod image_gen {
                 data.extend_from_slice(&[0, 0, 0, 0]);
             }
         }
-        let mut image = Image::new(
-            Extent3d {
-                width: width as u32,
-                height: width as u32,
-                depth_or_array_layers: 1,
-            },
-            TextureDimension::D2,
-            data,
-            TextureFormat::Rgba8Unorm,
-            RenderAssetUsages::RENDER_WORLD | RenderAssetUsages::MAIN_WORLD,
-        );
+        let mut image = super::rgba_image(width as u32, width as u32, data);
         image.sampler = ImageSampler::nearest();
         image
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_image_falls_back_to_placeholder() {
+        let path = "assets/no/such/image.png".to_string();
+        assert!(matches!(load_image(&path), Err(GalaxiaError::Io { .. })));
+        let image = load_image_or_placeholder(&path, 64);
+        assert_eq!(image.width(), placeholder_image().width());
+    }
+
+    #[test]
+    fn loaded_images_shrink_to_fit() {
+        let path = "assets/physical/Apple.png".to_string();
+        let full = load_image(&path).unwrap();
+        let small = load_image_or_placeholder(&path, 8);
+        assert!(small.width() <= 8 && small.height() <= 8);
+        assert!(small.width() < full.width());
+    }
+
+    #[test]
+    fn jitter_is_even_and_bounded() {
+        let mut rand = Random::new(7);
+        let samples = 10_000;
+        let mut sum = 0i64;
+        for _ in 0..samples {
+            let c = image_gen::Colorant::random_of_color(128, &mut rand, 20);
+            assert!((118..=138).contains(&c));
+            sum += c as i64 - 128;
+        }
+        // centred: the mean offset is near zero
+        assert!((sum as f64 / samples as f64).abs() < 0.5);
+        for _ in 0..100 {
+            let c = image_gen::Colorant::random_of_color(3, &mut rand, 20);
+            assert!(c <= 13);
+            let c = image_gen::Colorant::random_of_color(50, &mut rand, 0);
+            assert_eq!(c, 50);
+        }
+    }
+
+    #[test]
+    fn unseen_pictures_are_evicted() {
+        let mut images = Assets::<Image>::default();
+        let mut cache = image_gen::GeneratedImageAssets::default();
+        let shown = images.add(placeholder_image());
+        let asked = images.add(placeholder_image());
+        let forgotten = images.add(placeholder_image());
+        for (uid, image) in [("shown", &shown), ("asked", &asked)] {
+            cache.insert(uid.to_string(), image);
+        }
+        cache.insert("forgotten".to_string(), &forgotten);
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.estimated_bytes(&images), 3 * 8 * 8 * 4);
+
+        let on_show = [shown.id()].into_iter().collect();
+        assert_eq!(cache.sweep(100.0, &on_show, 300.0), 0);
+        assert!(cache.get(&"asked".to_string()).is_some());
+        assert_eq!(cache.sweep(350.0, &on_show, 300.0), 1);
+        assert!(cache.get(&"forgotten".to_string()).is_none());
+        assert!(cache.get(&"asked".to_string()).is_some());
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.sweep(700.0, &Default::default(), 300.0), 2);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn every_texture_is_srgb() {
+        let mut rand = Random::new(7);
+        let mut palette = image_gen::ColorPalette::new();
+        palette.add_colorant(image_gen::Colorant::new_tight(10, 20, 30, 1));
+        let drawn = palette.draw_ball(&mut rand, 16);
+        let loaded = load_image(&"assets/physical/Apple.png".to_string());
+        let rune = image_gen::draw_rune(crate::item::rune::Rune::InclusiveSelf);
+        for image in [drawn, loaded.unwrap(), placeholder_image(), rune] {
+            assert_eq!(image.texture_descriptor.format, TEXTURE_FORMAT);
+        }
+    }
+}
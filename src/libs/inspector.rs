@@ -0,0 +1,399 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Entity inspector, for debugging without println. Built only with the
+// `debug-ui` feature. Alt+click an entity in the world to inspect it: a panel
+// down the right of the screen shows its components (transform, velocity,
+// area, and the item or minigame inside it) and follows them live, every
+// frame. Alt+click on nothing closes it. Anything with an area can be picked,
+// not only what's `Clickable`, so loose items and the player are too. The
+// Alt+click is the inspector's alone: mouse.rs sends it as an `InspectClick`,
+// not a click.
+//
+// The numbers that matter most while debugging can be changed live (see
+// `Field`): Alt+Up and Alt+Down pick one, Alt+Left and Alt+Right nudge it,
+// ten times as far with Shift. A changed item amount counts as emitted, so
+// the audit stays balanced. A minigame's own state is shown, not edited.
+
+// a minigame's state runs long; the rest is cut off
+const MAX_LINES: usize = 60;
+const FONT_SIZE: f32 = 12.0;
+
+#[derive(Debug, Default, Resource)]
+pub struct Inspected {
+    pub entity: Option<Entity>,
+    // which of its fields the arrows change, of those it has
+    pub field: usize,
+}
+
+// A number the inspector can change.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Field {
+    X,
+    Y,
+    LinearX,
+    LinearY,
+    Angular,
+    Amount,
+    Charge,
+}
+
+impl Field {
+    pub const ALL: [Field; 7] = [
+        Field::X,
+        Field::Y,
+        Field::LinearX,
+        Field::LinearY,
+        Field::Angular,
+        Field::Amount,
+        Field::Charge,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Field::X => "Transform.x",
+            Field::Y => "Transform.y",
+            Field::LinearX => "Velocity.linear.x",
+            Field::LinearY => "Velocity.linear.y",
+            Field::Angular => "Velocity.angular",
+            Field::Amount => "Item.amount",
+            Field::Charge => "Charge",
+        }
+    }
+
+    // how far one nudge moves it
+    fn step(self) -> f32 {
+        match self {
+            Field::X | Field::Y => 1.0,
+            Field::LinearX | Field::LinearY => 10.0,
+            Field::Angular => 0.5,
+            Field::Amount => 0.1,
+            Field::Charge => 1.0,
+        }
+    }
+
+    // Nudged one step in `direction`'s sign, ten steps if `coarse`. Amounts
+    // stay above nothing and charges at or above it.
+    pub fn nudged(self, value: f32, direction: f32, coarse: bool) -> f32 {
+        let step = if coarse {
+            self.step() * 10.0
+        } else {
+            self.step()
+        };
+        let nudged = value + step * direction.signum();
+        match self {
+            Field::Amount => nudged.max(self.step()),
+            Field::Charge => nudged.max(0.0),
+            _ => nudged,
+        }
+    }
+
+    pub fn get(
+        self,
+        transform: Option<&Transform>,
+        velocity: Option<&Velocity>,
+        item: Option<&Item>,
+        charge: Option<&electric::Charge>,
+    ) -> Option<f32> {
+        match self {
+            Field::X => transform.map(|t| t.translation.x),
+            Field::Y => transform.map(|t| t.translation.y),
+            Field::LinearX => velocity.map(|v| v.linear.x),
+            Field::LinearY => velocity.map(|v| v.linear.y),
+            Field::Angular => velocity.map(|v| v.angular),
+            Field::Amount => item.map(|i| i.amount),
+            Field::Charge => charge.map(|c| c.0),
+        }
+    }
+}
+
+// The fields an entity has, of `Field::ALL`, by whether it has each.
+pub fn fields_present(has: impl Fn(Field) -> bool) -> Vec<Field> {
+    Field::ALL.into_iter().filter(|&field| has(field)).collect()
+}
+
+// `selected` moved `by` through `count` fields, wrapping around.
+pub fn select_field(selected: usize, by: isize, count: usize) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    (selected as isize + by).rem_euclid(count as isize) as usize
+}
+
+#[derive(Debug, Component)]
+pub struct InspectorText;
+
+// The components shown, by name, each as its `{:#?}`, cut to `MAX_LINES`.
+pub fn inspector_text(entity: Entity, components: &[(&str, String)]) -> String {
+    let mut lines = vec![format!("{:?}", entity)];
+    for (name, value) in components {
+        lines.push(format!("{}: {}", name, value));
+    }
+    let all: Vec<&str> = lines.iter().flat_map(|line| line.lines()).collect();
+    if all.len() <= MAX_LINES {
+        return all.join("\n");
+    }
+    let mut text = all[..MAX_LINES].join("\n");
+    text.push_str(&format!("\n... {} more lines", all.len() - MAX_LINES));
+    text
+}
+
+fn setup_inspector(mut commands: Commands) {
+    commands.spawn((
+        InspectorText,
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(6.0),
+            top: Val::Px(40.0),
+            max_width: Val::Percent(35.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        Text::new(""),
+        TextFont {
+            font_size: FONT_SIZE,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Visibility::Hidden,
+    ));
+}
+
+pub fn inspect_input(
+    mut inspect_clicks: MessageReader<InspectClick>,
+    mut inspected: ResMut<Inspected>,
+    area_query: Query<(
+        Entity,
+        &GlobalTransform,
+        Option<&RectangularArea>,
+        Option<&CircularArea>,
+    )>,
+) {
+    for click in inspect_clicks.read() {
+        inspected.field = 0;
+        inspected.entity = topmost(area_query.iter().filter_map(
+            |(entity, global_transform, rectangle, circle)| {
+                let translation = global_transform.translation();
+                let center = translation.truncate();
+                let hit = match (rectangle, circle) {
+                    (Some(rectangle), _) => {
+                        rectangle.is_within(click.position, center)
+                    }
+                    (None, Some(circle)) => {
+                        circle.is_within(click.position, center)
+                    }
+                    (None, None) => false,
+                };
+                hit.then_some((entity, 0.0, 0, translation.z))
+            },
+        ));
+    }
+}
+
+// Alt+Up/Down picks a field of the inspected entity, Alt+Left/Right nudges
+// it.
+pub fn edit_inspected(
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut inspected: ResMut<Inspected>,
+    mut audit: ResMut<AmountAudit>,
+    mut component_query: Query<(
+        Option<&mut Transform>,
+        Option<&mut Velocity>,
+        Option<&mut Item>,
+        Option<&mut electric::Charge>,
+    )>,
+) {
+    if !kb_input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) {
+        return;
+    }
+    let Some(Ok((mut transform, mut velocity, mut item, mut charge))) =
+        inspected
+            .entity
+            .map(|entity| component_query.get_mut(entity))
+    else {
+        return;
+    };
+    let fields = fields_present(|field| {
+        field
+            .get(
+                transform.as_deref(),
+                velocity.as_deref(),
+                item.as_deref(),
+                charge.as_deref(),
+            )
+            .is_some()
+    });
+    let selected = select_field(
+        inspected.field,
+        kb_input.just_pressed(KeyCode::ArrowDown) as isize
+            - kb_input.just_pressed(KeyCode::ArrowUp) as isize,
+        fields.len(),
+    );
+    if inspected.field != selected {
+        inspected.field = selected;
+    }
+    let direction = kb_input.just_pressed(KeyCode::ArrowRight) as i32
+        - kb_input.just_pressed(KeyCode::ArrowLeft) as i32;
+    let Some(&field) = fields.get(selected).filter(|_| direction != 0) else {
+        return;
+    };
+    let coarse =
+        kb_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let nudge = |value: f32| field.nudged(value, direction as f32, coarse);
+    match field {
+        Field::X => {
+            let x = &mut transform.as_mut().unwrap().translation.x;
+            *x = nudge(*x);
+        }
+        Field::Y => {
+            let y = &mut transform.as_mut().unwrap().translation.y;
+            *y = nudge(*y);
+        }
+        Field::LinearX => {
+            let x = &mut velocity.as_mut().unwrap().linear.x;
+            *x = nudge(*x);
+        }
+        Field::LinearY => {
+            let y = &mut velocity.as_mut().unwrap().linear.y;
+            *y = nudge(*y);
+        }
+        Field::Angular => {
+            let angular = &mut velocity.as_mut().unwrap().angular;
+            *angular = nudge(*angular);
+        }
+        Field::Amount => {
+            // `resize_changed_items` resizes it to match
+            let item = item.as_mut().unwrap();
+            let amount = nudge(item.amount);
+            audit.emitted += amount - item.amount;
+            item.amount = amount;
+        }
+        Field::Charge => {
+            let charge = &mut charge.as_mut().unwrap().0;
+            *charge = nudge(*charge);
+        }
+    }
+}
+
+pub fn render_inspector(
+    inspected: Res<Inspected>,
+    component_query: Query<(
+        Option<&Transform>,
+        Option<&Velocity>,
+        Option<&RectangularArea>,
+        Option<&CircularArea>,
+        Option<&Item>,
+        Option<&Stuck>,
+        Option<&Minigame>,
+        Option<&electric::Charge>,
+    )>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<InspectorText>>,
+) {
+    let found = inspected
+        .entity
+        .and_then(|entity| Some((entity, component_query.get(entity).ok()?)));
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        let Some((entity, components)) = found else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        let (
+            transform,
+            velocity,
+            rectangle,
+            circle,
+            item,
+            stuck,
+            minigame,
+            charge,
+        ) = components;
+        let fields = fields_present(|field| {
+            field.get(transform, velocity, item, charge).is_some()
+        });
+        let editing = fields.get(inspected.field).map(|&field| {
+            let value = field.get(transform, velocity, item, charge);
+            format!(
+                "{} = {:.2} (Alt+arrows, Shift for 10x)",
+                field.name(),
+                value.unwrap_or_default(),
+            )
+        });
+        let shown: Vec<(&str, String)> = [
+            ("Editing", editing),
+            ("Transform", transform.map(|c| format!("{:#?}", c))),
+            ("Velocity", velocity.map(|c| format!("{:#?}", c))),
+            ("RectangularArea", rectangle.map(|c| format!("{:?}", c))),
+            ("CircularArea", circle.map(|c| format!("{:?}", c))),
+            ("Item", item.map(|c| format!("{:#?}", c))),
+            ("Stuck", stuck.map(|c| format!("{:?}", c))),
+            ("Minigame", minigame.map(|c| format!("{:#?}", c))),
+            ("Charge", charge.map(|c| format!("{:?}", c))),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value?)))
+        .collect();
+        let wanted = inspector_text(entity, &shown);
+        if text.0 != wanted {
+            text.0 = wanted;
+        }
+        visibility.set_if_neq(Visibility::Visible);
+    }
+}
+
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inspected>()
+            .add_systems(Startup, setup_inspector)
+            .add_systems(
+                Update,
+                (inspect_input, edit_inspected, render_inspector)
+                    .chain()
+                    .after(GameSet::Input),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_state_is_cut_off() {
+        let entity = Entity::from_raw_u32(7).unwrap();
+        let short = inspector_text(entity, &[("Stuck", "Stuck".to_string())]);
+        assert_eq!(short.lines().count(), 2);
+
+        let long = (0..MAX_LINES * 2)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let text = inspector_text(entity, &[("Minigame", long)]);
+        assert_eq!(text.lines().count(), MAX_LINES + 1);
+        assert!(text.ends_with(&format!("{} more lines", MAX_LINES + 1)));
+    }
+
+    #[test]
+    fn fields_are_picked_and_nudged() {
+        let item_fields = fields_present(|field| {
+            matches!(field, Field::X | Field::Y | Field::Amount)
+        });
+        assert_eq!(item_fields, vec![Field::X, Field::Y, Field::Amount]);
+        assert_eq!(select_field(0, 1, 3), 1);
+        assert_eq!(select_field(0, -1, 3), 2, "wraps to the last");
+        assert_eq!(select_field(2, 1, 3), 0, "wraps to the first");
+        assert_eq!(select_field(5, 0, 0), 0);
+
+        assert_eq!(Field::X.nudged(10.0, 1.0, false), 11.0);
+        assert_eq!(Field::X.nudged(10.0, -1.0, true), 0.0);
+        assert_eq!(Field::LinearY.nudged(0.0, 1.0, false), 10.0);
+        // an item never runs out, nor a charge below nothing
+        assert_eq!(Field::Amount.nudged(0.15, -1.0, false), 0.1);
+        assert_eq!(Field::Charge.nudged(3.0, -1.0, true), 0.0);
+    }
+}
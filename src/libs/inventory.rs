@@ -3,7 +3,6 @@ use std::collections::HashMap;
 use bevy::ecs::system::EntityCommands;
 use bevy::prelude::*;
 use bevy_prototype_lyon::prelude::*;
-use wyrand::WyRand;
 
 use crate::entities::item::*;
 use crate::entities::minigame::*;
@@ -81,8 +80,7 @@ impl InventoryBundle {
                     }
                 }
                 // Paging controls, just below the slot grid.
-                let button_y =
-                    -inventory_size.y / 2.0 - SCROLL_BUTTON_SIZE;
+                let button_y = -inventory_size.y / 2.0 - SCROLL_BUTTON_SIZE;
                 parent.spawn(ScrollButtonBundle::new(
                     inventory_entity,
                     true,
@@ -132,6 +130,8 @@ impl Inventory {
 pub struct SlotBundle {
     pub slot: Slot,
     pub area: RectangularArea,
+    pub clickable: Clickable,
+    pub hoverable: Hoverable,
     pub sprite: Sprite,
     pub transform: Transform,
 }
@@ -145,14 +145,13 @@ impl SlotBundle {
     ) -> Self {
         let area = RectangularArea::new(slot_size.x, slot_size.y);
         let sprite = Self::missing_sprite();
-        let transform = Self::slot_transform(
-            slot_size,
-            slot_position,
-            inventory_area,
-        );
+        let transform =
+            Self::slot_transform(slot_size, slot_position, inventory_area);
         Self {
             slot,
             area,
+            clickable: Clickable::new(CLICK_PRIORITY_INVENTORY),
+            hoverable: Hoverable,
             sprite,
             transform,
         }
@@ -167,12 +166,7 @@ impl SlotBundle {
         inventory_area: RectangularArea,
     ) -> Entity {
         parent
-            .spawn(Self::new(
-                slot,
-                slot_position,
-                slot_size,
-                inventory_area,
-            ))
+            .spawn(Self::new(slot, slot_position, slot_size, inventory_area))
             .with_children(|parent| {
                 let _background = parent.spawn((
                     Sprite {
@@ -194,8 +188,11 @@ impl SlotBundle {
         size: Vec2,
     ) {
         if let Some(item) = slot.item {
-            let texture =
-                Self::get_texture(images, generated_image_assets, &item);
+            let texture = item.texture(
+                images,
+                generated_image_assets,
+                size.max_element() / 2.0,
+            );
             commands.insert(Self::present_sprite(texture, &size));
         } else {
             commands.insert(Self::missing_sprite());
@@ -231,22 +228,6 @@ impl SlotBundle {
         ))
     }
 
-    fn get_texture(
-        images: &mut Assets<Image>,
-        generated_image_assets: &mut image_gen::GeneratedImageAssets,
-        item: &ItemType,
-    ) -> Handle<Image> {
-        match generated_image_assets.get(&item.uid()) {
-            Some(texture) => texture.clone(),
-            None => {
-                let image = item.draw(&mut WyRand::new(SEED));
-                let texture = images.add(image.clone());
-                generated_image_assets.insert(item.uid(), &texture);
-                texture
-            }
-        }
-    }
-
     fn missing_texture() -> Handle<Image> {
         Handle::<Image>::default()
     }
@@ -361,6 +342,8 @@ struct TextBox;
 struct ScrollButtonBundle {
     button: ScrollButton,
     area: RectangularArea,
+    clickable: Clickable,
+    hoverable: Hoverable,
     shape: Shape,
     transform: Transform,
 }
@@ -384,10 +367,9 @@ impl ScrollButtonBundle {
         };
         Self {
             button: ScrollButton { inventory, left },
-            area: RectangularArea::new(
-                SCROLL_BUTTON_SIZE,
-                SCROLL_BUTTON_SIZE,
-            ),
+            area: RectangularArea::new(SCROLL_BUTTON_SIZE, SCROLL_BUTTON_SIZE),
+            clickable: Clickable::new(CLICK_PRIORITY_INVENTORY),
+            hoverable: Hoverable,
             shape: ShapeBuilder::with(&shapes::Polygon {
                 points,
                 closed: true,
@@ -414,103 +396,106 @@ pub fn handle_slot_click(
     mut commands: Commands,
     mut images: ResMut<Assets<Image>>,
     mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
-    mouse_state: Res<MouseState>,
+    mut click_events: MessageReader<ClickEvent>,
+    mut undo: ResMut<UndoStack>,
     inventory_query: Query<&Inventory>,
     mut minigame_query: Query<(&mut Minigame, &GlobalTransform)>,
-    mut slot_query: Query<(&mut Slot, &GlobalTransform, &RectangularArea)>,
+    mut slot_query: Query<&mut Slot>,
 ) {
-    if !mouse_state.just_released {
-        return;
-    }
-    let click_position = mouse_state.current_position;
-
-    let Some((mut slot, _, _)) =
-        slot_query.iter_mut().find(|(_, transform, area)| {
-            area.is_within(click_position, transform.translation().truncate())
-        })
-    else {
-        return;
-    };
+    for click in click_events.read() {
+        let eject_all = match click.kind {
+            ClickKind::Press => continue,
+            ClickKind::Short => false,
+            ClickKind::Long => true,
+        };
+        let Some(Ok(mut slot)) = click.target.map(|e| slot_query.get_mut(e))
+        else {
+            continue;
+        };
 
-    let Some(item_type) = slot.item else {
-        return;
-    };
+        let Some(item_type) = slot.item else {
+            continue;
+        };
 
-    let inventory: &Inventory = inventory_query.get(slot.inventory).unwrap();
-    let (mut minigame, minigame_transform) =
-        minigame_query.get_mut(inventory.owner).unwrap();
-    let minigame_transform = *minigame_transform;
-    let minigame_area = minigame.area();
-    let Some(items) = minigame.items_mut() else {
-        return;
-    };
+        let inventory: &Inventory =
+            inventory_query.get(slot.inventory).unwrap();
+        let (mut minigame, minigame_transform) =
+            minigame_query.get_mut(inventory.owner).unwrap();
+        let minigame_transform = *minigame_transform;
+        let minigame_area = minigame.area();
+        let minigame_id = minigame.id();
+        let Some(items) = minigame.items_mut() else {
+            continue;
+        };
 
-    let Some(amount) = items.get(&item_type) else {
-        return;
-    };
-    let amount: f32 = match mouse_state.get_click_type() {
-        ClickType::Short => amount.min(1.0),
-        ClickType::Long => *amount,
-        ClickType::Invalid => return,
-    };
-    let (removed, remaining) = remove_item(items, item_type, amount);
-    commands.spawn(ItemBundle::new_from_minigame(
-        &mut images,
-        &mut generated_image_assets,
-        Item::new(item_type, removed),
-        &minigame_transform,
-        &minigame_area,
-    ));
-    if remaining == 0.0 {
-        slot.item.take();
+        let Some(amount) = items.get(&item_type) else {
+            continue;
+        };
+        let amount: f32 = if eject_all { *amount } else { amount.min(1.0) };
+        let (removed, remaining) = remove_item(items, item_type, amount);
+        let item = commands
+            .spawn(ItemBundle::new_from_minigame(
+                &mut images,
+                &mut generated_image_assets,
+                Item::new(item_type, removed),
+                &minigame_transform,
+                &minigame_area,
+            ))
+            .id();
+        if eject_all {
+            undo.push(UndoAction::StackEjected {
+                minigame: minigame_id,
+                item,
+            });
+        }
+        if remaining == 0.0 {
+            slot.item.take();
+        }
     }
 }
 
 // Click a scroll button to page the inventory. Mutating `Inventory::page`
 // trips `Changed<Inventory>`, which makes `set_slots` repaint the new page.
 pub fn handle_scroll_click(
-    mouse_state: Res<MouseState>,
+    mut click_events: MessageReader<ClickEvent>,
     mut inventory_query: Query<&mut Inventory>,
     minigame_query: Query<&Minigame>,
-    button_query: Query<(&ScrollButton, &GlobalTransform, &RectangularArea)>,
+    button_query: Query<&ScrollButton>,
 ) {
-    if !mouse_state.just_released {
-        return;
-    }
-    let click_position = mouse_state.current_position;
-
-    let Some((button, _, _)) =
-        button_query.iter().find(|(_, transform, area)| {
-            area.is_within(click_position, transform.translation().truncate())
-        })
-    else {
-        return;
-    };
+    for click in click_events.read() {
+        if !click.is_release() {
+            continue;
+        }
+        let Some(Ok(button)) = click.target.map(|e| button_query.get(e)) else {
+            continue;
+        };
 
-    let Ok(mut inventory) = inventory_query.get_mut(button.inventory) else {
-        return;
-    };
+        let Ok(mut inventory) = inventory_query.get_mut(button.inventory)
+        else {
+            continue;
+        };
 
-    if button.left {
-        if inventory.page > 0 {
-            inventory.page -= 1;
+        if button.left {
+            if inventory.page > 0 {
+                inventory.page -= 1;
+            }
+            continue;
         }
-        return;
-    }
 
-    // Advancing only makes sense if there's a page past the current one.
-    let Ok(minigame) = minigame_query.get(inventory.owner) else {
-        return;
-    };
-    let Some(stored) = minigame.items() else {
-        return;
-    };
-    let (width, height) = inventory.dimensions;
-    let per_page = (width * height) as usize;
-    let total = count_filtered_items(stored, &inventory.filter);
-    let last_page = total.saturating_sub(1) / per_page;
-    if inventory.page < last_page {
-        inventory.page += 1;
+        // Advancing only makes sense if there's a page past the current one.
+        let Ok(minigame) = minigame_query.get(inventory.owner) else {
+            continue;
+        };
+        let Some(stored) = minigame.items() else {
+            continue;
+        };
+        let (width, height) = inventory.dimensions;
+        let per_page = (width * height) as usize;
+        let total = count_filtered_items(stored, &inventory.filter);
+        let last_page = total.saturating_sub(1) / per_page;
+        if inventory.page < last_page {
+            inventory.page += 1;
+        }
     }
 }
 
@@ -541,7 +526,11 @@ pub fn set_slots(
         );
         for (index, slot_entity) in inventory.slots.iter().enumerate() {
             let mut slot = slot_query.get_mut(*slot_entity).unwrap();
-            slot.item = items.get(index).map(|item| item.r#type);
+            let item = items.get(index).map(|item| item.r#type);
+            // only slots whose item changed get redrawn
+            if slot.item != item {
+                slot.item = item;
+            }
         }
     }
 }
@@ -563,6 +552,25 @@ pub fn redraw_slots(
     }
 }
 
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (handle_slot_click, handle_scroll_click)
+                .chain()
+                .in_set(GameSet::Inventory),
+        )
+        .add_systems(
+            FixedUpdate,
+            (set_slots, redraw_slots.run_if(catch_up::not_catching_up))
+                .chain()
+                .in_set(GameSet::Inventory),
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
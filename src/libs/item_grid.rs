@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::entities::*;
+
+// A spatial hash of the loose items, so code that cares about one region of
+// the world only looks at the items near it instead of all of them. The world
+// is cut into square cells and each loose item is filed under the cell its
+// center is in. It's updated incrementally at the start of each frame: only
+// items that moved, came loose, got stuck or went away are touched.
+//
+// Lookups return every item in the cells a region overlaps, a superset of the
+// items in the region; callers check positions themselves. Positions are as
+// of the end of the last frame, so callers pad their region by how far an
+// item could have moved since (`Item::MAX_RADIUS` is plenty).
+
+#[derive(Debug, Default, Resource)]
+pub struct ItemGrid {
+    cells: HashMap<IVec2, Vec<Entity>>,
+    cell_of: HashMap<Entity, IVec2>,
+}
+
+impl ItemGrid {
+    pub const CELL_SIZE: f32 = 128.0;
+
+    fn cell(position: Vec2) -> IVec2 {
+        (position / Self::CELL_SIZE).floor().as_ivec2()
+    }
+
+    // Files `entity` under the cell `position` is in, moving it if it was
+    // filed elsewhere.
+    pub fn insert(&mut self, entity: Entity, position: Vec2) {
+        let cell = Self::cell(position);
+        match self.cell_of.insert(entity, cell) {
+            Some(old) if old == cell => return,
+            Some(old) => self.remove_from_cell(entity, old),
+            None => {}
+        }
+        self.cells.entry(cell).or_default().push(entity);
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        if let Some(cell) = self.cell_of.remove(&entity) {
+            self.remove_from_cell(entity, cell);
+        }
+    }
+
+    fn remove_from_cell(&mut self, entity: Entity, cell: IVec2) {
+        let Some(entities) = self.cells.get_mut(&cell) else {
+            return;
+        };
+        if let Some(index) = entities.iter().position(|e| *e == entity) {
+            entities.swap_remove(index);
+        }
+        if entities.is_empty() {
+            self.cells.remove(&cell);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.cell_of.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cell_of.is_empty()
+    }
+
+    // Every item in a cell that `rect` overlaps.
+    pub fn in_rect(&self, rect: Rect) -> Vec<Entity> {
+        let min = Self::cell(rect.min);
+        let max = Self::cell(rect.max);
+        let spanned = (max - min + IVec2::ONE).as_i64vec2();
+        // a huge region spans more cells than there are items; walk the
+        // occupied cells instead
+        if spanned.x * spanned.y > self.cells.len() as i64 {
+            return self
+                .cells
+                .iter()
+                .filter(|(cell, _)| {
+                    cell.cmpge(min).all() && cell.cmple(max).all()
+                })
+                .flat_map(|(_, entities)| entities.iter().copied())
+                .collect();
+        }
+        let mut entities = vec![];
+        for x in min.x..=max.x {
+            for y in min.y..=max.y {
+                if let Some(cell) = self.cells.get(&IVec2::new(x, y)) {
+                    entities.extend_from_slice(cell);
+                }
+            }
+        }
+        entities
+    }
+
+    // Every item in a cell that the circle of `radius` around `center`
+    // overlaps.
+    pub fn near(&self, center: Vec2, radius: f32) -> Vec<Entity> {
+        self.in_rect(Rect::from_center_half_size(center, Vec2::splat(radius)))
+    }
+}
+
+// Keeps the grid in step with the loose items. Runs in PreUpdate, so it picks
+// up last frame's physics and commands before anything in this frame asks.
+pub fn index_loose_items(
+    mut item_grid: ResMut<ItemGrid>,
+    moved_query: Query<
+        (Entity, &Transform),
+        (
+            With<Item>,
+            Without<Stuck>,
+            Without<ejection::Ejecting>,
+            Changed<Transform>,
+        ),
+    >,
+    loose_query: Query<
+        &Transform,
+        (With<Item>, Without<Stuck>, Without<ejection::Ejecting>),
+    >,
+    stuck_query: Query<Entity, (With<Item>, Added<Stuck>)>,
+    mut removed_items: RemovedComponents<Item>,
+    mut released_items: RemovedComponents<Stuck>,
+) {
+    for entity in removed_items.read() {
+        item_grid.remove(entity);
+    }
+    for entity in stuck_query.iter() {
+        item_grid.remove(entity);
+    }
+    // a released item may not have moved yet
+    for entity in released_items.read() {
+        if let Ok(transform) = loose_query.get(entity) {
+            item_grid.insert(entity, transform.translation.truncate());
+        }
+    }
+    for (entity, transform) in moved_query.iter() {
+        item_grid.insert(entity, transform.translation.truncate());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items_move_between_cells() {
+        let mut grid = ItemGrid::default();
+        let a = Entity::from_raw_u32(1).unwrap();
+        let b = Entity::from_raw_u32(2).unwrap();
+        grid.insert(a, Vec2::new(10.0, 10.0));
+        grid.insert(b, Vec2::new(1000.0, 10.0));
+        assert_eq!(grid.near(Vec2::ZERO, 50.0), vec![a]);
+
+        grid.insert(a, Vec2::new(1010.0, -10.0));
+        assert!(grid.near(Vec2::ZERO, 50.0).is_empty());
+        let near = grid.near(Vec2::new(1000.0, 0.0), 50.0);
+        assert_eq!(near.len(), 2);
+        assert!(near.contains(&a) && near.contains(&b));
+
+        grid.remove(b);
+        assert_eq!(grid.near(Vec2::new(1000.0, 0.0), 50.0), vec![a]);
+        assert_eq!(grid.len(), 1);
+    }
+
+    #[test]
+    fn huge_regions_walk_occupied_cells() {
+        let mut grid = ItemGrid::default();
+        let a = Entity::from_raw_u32(1).unwrap();
+        grid.insert(a, Vec2::new(-5000.0, 5000.0));
+        assert_eq!(grid.near(Vec2::ZERO, 1e9), vec![a]);
+        assert!(grid.near(Vec2::ZERO, 4000.0).is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::{LazyLock, RwLock};
+
+use bevy::prelude::*;
+
+use crate::libs::*;
+
+// Player-facing text, looked up by key in `assets/locales/<id>.ron`. English
+// is compiled in and fills in any key the chosen locale leaves out, so a
+// partial translation still shows every string. Pick a locale with
+// `--locale <id>`; text already on screen stays as it was drawn.
+//
+// The developer console and error notifications stay in English.
+
+pub const DEFAULT_LOCALE: &str = "en";
+const ENGLISH: &str = include_str!("../../assets/locales/en.ron");
+
+static ENGLISH_STRINGS: LazyLock<HashMap<String, String>> =
+    LazyLock::new(|| {
+        ron::from_str(ENGLISH).expect("built-in English strings should parse")
+    });
+
+// The chosen locale's strings, for code with no access to resources (minigame
+// and item names). None means English.
+static ACTIVE_STRINGS: RwLock<Option<HashMap<String, String>>> =
+    RwLock::new(None);
+
+#[derive(Debug, Clone, PartialEq, Eq, Resource)]
+pub struct LocaleId(pub String);
+
+impl Default for LocaleId {
+    fn default() -> Self {
+        Self(DEFAULT_LOCALE.to_string())
+    }
+}
+
+impl LocaleId {
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Self {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--locale" {
+                return args.next().map_or_else(Self::default, Self);
+            }
+        }
+        Self::default()
+    }
+
+    pub fn path(&self) -> String {
+        format!("assets/locales/{}.ron", self.0)
+    }
+
+    pub fn load(&self) -> Result<HashMap<String, String>, GalaxiaError> {
+        let path = self.path();
+        let ron = storage::read_to_string(&path).map_err(|source| {
+            GalaxiaError::Io {
+                path: path.clone(),
+                source,
+            }
+        })?;
+        ron::from_str(&ron).map_err(|source| GalaxiaError::Parse {
+            path,
+            source: Box::new(source),
+        })
+    }
+}
+
+// The text for `key`, or `fallback` if no locale has it.
+pub fn tr_or(key: &str, fallback: &str) -> String {
+    let active = ACTIVE_STRINGS.read().unwrap();
+    active
+        .as_ref()
+        .and_then(|strings| strings.get(key))
+        .or_else(|| ENGLISH_STRINGS.get(key))
+        .map_or_else(|| fallback.to_string(), String::clone)
+}
+
+// The text for `key`. A missing key shows as itself, so it's easy to spot.
+pub fn tr(key: &str) -> String {
+    tr_or(key, key)
+}
+
+// The text for `key` with each `{name}` in it replaced by its value.
+pub fn tr_fmt(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = tr(key);
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+fn apply_locale(
+    locale: Res<LocaleId>,
+    mut notifications: ResMut<Notifications>,
+) {
+    if !locale.is_changed() {
+        return;
+    }
+    let strings = if locale.0 == DEFAULT_LOCALE {
+        None
+    } else {
+        match locale.load() {
+            Ok(strings) => Some(strings),
+            Err(error) => {
+                notifications.push(error.to_string());
+                None
+            }
+        }
+    };
+    *ACTIVE_STRINGS.write().unwrap() = strings;
+}
+
+pub struct LocalePlugin {
+    pub locale: LocaleId,
+}
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        // before Startup, so the board is spawned with translated names
+        app.insert_resource(self.locale.clone())
+            .add_systems(PreStartup, apply_locale)
+            .add_systems(Update, apply_locale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_comes_from_args() {
+        let args = ["galaxia", "--locale", "es"].map(String::from);
+        assert_eq!(LocaleId::from_args(args).0, "es");
+        let args = ["galaxia"].map(String::from);
+        assert_eq!(LocaleId::from_args(args).0, DEFAULT_LOCALE);
+    }
+
+    #[test]
+    fn every_locale_parses_and_fills_in_args() {
+        let spanish = LocaleId("es".to_string()).load().unwrap();
+        // nothing translated that English doesn't have, bar item words
+        for key in spanish.keys().filter(|key| !key.starts_with("word.")) {
+            assert!(ENGLISH_STRINGS.contains_key(key), "stray key {}", key);
+        }
+        assert_eq!(tr_fmt("button.clicks", &[("count", &3)]), "Clicks: 3");
+        assert_eq!(tr("no.such.key"), "no.such.key");
+        assert_eq!(tr_or("word.NoSuchWord", "NoSuchWord"), "NoSuchWord");
+    }
+}
@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// A small map of the board down the right of the screen: a mark for each
+// minigame where it lies, its play area to scale. Hovering a mark shows that
+// minigame's thumbnail (see thumbnails.rs) beside the map. The marks are
+// rebuilt only when a minigame appears, goes or moves.
+
+pub const MAP_SIZE: f32 = 160.0;
+// between the board's edge and the map's
+const MAP_PADDING: f32 = 6.0;
+const MAP_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+const MARK_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.35);
+const HOVERED_MARK_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.7);
+
+#[derive(Debug, Component)]
+pub struct Minimap;
+
+#[derive(Debug, Component)]
+pub struct MinimapMark(pub &'static str);
+
+// The hovered minigame's thumbnail, left of the map.
+#[derive(Debug, Component)]
+pub struct MinimapPreview;
+
+// Where each minigame's mark goes on the map, in pixels from its top left:
+// the whole board scaled to fit, centered, with y pointing down as in the UI.
+pub fn mark_rects(areas: &[(&'static str, Rect)]) -> Vec<(&'static str, Rect)> {
+    let Some(board) = areas
+        .iter()
+        .map(|(_, rect)| *rect)
+        .reduce(|board, rect| board.union(rect))
+    else {
+        return Vec::new();
+    };
+    let inside = MAP_SIZE - MAP_PADDING * 2.0;
+    let scale = inside / board.width().max(board.height()).max(1.0);
+    let margin = (Vec2::splat(inside) - board.size() * scale) / 2.0
+        + Vec2::splat(MAP_PADDING);
+    areas
+        .iter()
+        .map(|&(id, rect)| {
+            let top_left =
+                Vec2::new(rect.min.x - board.min.x, board.max.y - rect.max.y)
+                    * scale
+                    + margin;
+            (
+                id,
+                Rect::from_corners(top_left, top_left + rect.size() * scale),
+            )
+        })
+        .collect()
+}
+
+fn setup_minimap(mut commands: Commands) {
+    commands.spawn((
+        Minimap,
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(6.0),
+            top: Val::Percent(25.0),
+            width: Val::Px(MAP_SIZE),
+            height: Val::Px(MAP_SIZE),
+            ..default()
+        },
+        BackgroundColor(MAP_COLOR),
+    ));
+    commands.spawn((
+        MinimapPreview,
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(6.0 + MAP_SIZE + 6.0),
+            top: Val::Percent(25.0),
+            width: Val::Px(THUMBNAIL_SIZE as f32),
+            height: Val::Px(THUMBNAIL_SIZE as f32),
+            ..default()
+        },
+        ImageNode::default(),
+        Visibility::Hidden,
+    ));
+}
+
+fn render_minimap(
+    mut commands: Commands,
+    mut drawn: Local<Vec<(&'static str, Rect)>>,
+    minigame_query: Query<(&Minigame, &GlobalTransform, &RectangularArea)>,
+    map_query: Query<Entity, With<Minimap>>,
+) {
+    let mut areas: Vec<(&'static str, Rect)> = minigame_query
+        .iter()
+        .map(|(minigame, transform, area)| {
+            let center = transform.translation().truncate();
+            (
+                minigame.id(),
+                Rect::from_center_size(center, area.dimensions()),
+            )
+        })
+        .collect();
+    areas.sort_unstable_by_key(|(id, _)| *id);
+    if areas == *drawn {
+        return;
+    }
+    let marks = mark_rects(&areas);
+    *drawn = areas;
+    for map in map_query.iter() {
+        commands.entity(map).despawn_children();
+        commands.entity(map).with_children(|parent| {
+            for &(id, rect) in &marks {
+                parent.spawn((
+                    MinimapMark(id),
+                    Button,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(rect.min.x),
+                        top: Val::Px(rect.min.y),
+                        width: Val::Px(rect.width()),
+                        height: Val::Px(rect.height()),
+                        ..default()
+                    },
+                    BackgroundColor(MARK_COLOR),
+                ));
+            }
+        });
+    }
+}
+
+// Shows the hovered mark's thumbnail, once it has one.
+fn preview_hovered_minigame(
+    thumbnails: Res<MinigameThumbnails>,
+    mut mark_query: Query<(&MinimapMark, &Interaction, &mut BackgroundColor)>,
+    mut preview_query: Query<
+        (&mut ImageNode, &mut Visibility),
+        With<MinimapPreview>,
+    >,
+) {
+    let mut hovered = None;
+    for (mark, interaction, mut background) in mark_query.iter_mut() {
+        let color = if *interaction == Interaction::None {
+            MARK_COLOR
+        } else {
+            hovered = Some(mark.0);
+            HOVERED_MARK_COLOR
+        };
+        if background.0 != color {
+            background.0 = color;
+        }
+    }
+    let thumbnail = hovered.and_then(|id| thumbnails.get(id));
+    for (mut image, mut visibility) in preview_query.iter_mut() {
+        let Some(thumbnail) = thumbnail else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        if image.image != *thumbnail {
+            image.image = thumbnail.clone();
+        }
+        visibility.set_if_neq(Visibility::Inherited);
+    }
+}
+
+// For the windowed game, with `ThumbnailPlugin`.
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_minimap).add_systems(
+            Update,
+            (render_minimap, preview_hovered_minigame)
+                .chain()
+                .after(GameSet::Minigames),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_board_fits_the_map_to_scale() {
+        let areas = [
+            ("wide", Rect::new(-200.0, 0.0, 0.0, 100.0)),
+            ("below", Rect::new(100.0, -200.0, 200.0, -100.0)),
+        ];
+        let marks = mark_rects(&areas);
+        let inside = MAP_SIZE - MAP_PADDING * 2.0;
+        // 400 wide and 300 tall: the width fills the map, the height is
+        // centered in it
+        let scale = inside / 400.0;
+        let top = MAP_PADDING + (inside - 300.0 * scale) / 2.0;
+        assert_eq!(marks[0].0, "wide");
+        assert_eq!(marks[0].1.min, Vec2::new(MAP_PADDING, top));
+        assert_eq!(marks[0].1.size(), Vec2::new(200.0, 100.0) * scale);
+        // the lower one is further down the map
+        assert_eq!(marks[1].1.max.x, MAP_SIZE - MAP_PADDING);
+        assert!((marks[1].1.max.y - (MAP_SIZE - top)).abs() < 1e-4);
+        assert!(mark_rects(&[]).is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 use bevy::prelude::*;
 
-pub fn mark_component_changed<T: Component<Mutability = bevy::ecs::component::Mutable>>(
+pub fn mark_component_changed<
+    T: Component<Mutability = bevy::ecs::component::Mutable>,
+>(
     commands: &mut Commands,
     entity: Entity,
 ) {
@@ -12,3 +14,9 @@ pub fn mark_component_changed<T: Component<Mutability = bevy::ecs::component::Mu
         }
     });
 }
+
+// Ctrl+Z, with either Ctrl key.
+pub fn undo_pressed(kb_input: &ButtonInput<KeyCode>) -> bool {
+    kb_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight])
+        && kb_input.just_pressed(KeyCode::KeyZ)
+}
@@ -1,23 +1,83 @@
+pub mod accessibility;
 pub mod area;
+pub mod attract;
+pub mod audit;
+pub mod balance;
 pub mod camera;
+pub mod catch_up;
 pub mod collision;
+pub mod console;
 pub mod constant_velocity;
+pub mod crash;
+pub mod delayed_trigger;
+pub mod error;
+pub mod event_log;
+pub mod frame_timing;
+pub mod game_set;
+pub mod grid_widget;
 pub mod images;
+#[cfg(feature = "debug-ui")]
+pub mod inspector;
 pub mod inventory;
+pub mod item_grid;
+pub mod locale;
+pub mod minimap;
 pub mod misc;
 pub mod mouse;
+pub mod notifications;
+pub mod numbers;
+pub mod palettes;
+pub mod photo_mode;
+pub mod picking;
 pub mod random;
-pub mod ready;
+pub mod replay;
+pub mod save;
+pub mod status_effect;
+pub mod storage;
+pub mod thumbnails;
+pub mod time_scale;
 pub mod toggleable;
+pub mod undo;
+pub mod watched_file;
+pub mod world_clock;
 
+pub use accessibility::*;
 pub use area::*;
+pub use attract::*;
+pub use audit::*;
+pub use balance::*;
 pub use camera::*;
+pub use catch_up::*;
 pub use collision::*;
+pub use console::*;
 pub use constant_velocity::*;
+pub use crash::*;
+pub use delayed_trigger::*;
+pub use error::*;
+pub use event_log::*;
+pub use frame_timing::*;
+pub use game_set::*;
+pub use grid_widget::*;
 pub use images::*;
+#[cfg(feature = "debug-ui")]
+pub use inspector::*;
 pub use inventory::*;
+pub use item_grid::*;
+pub use locale::*;
+pub use minimap::*;
 pub use misc::*;
 pub use mouse::*;
+pub use notifications::*;
+pub use numbers::*;
+pub use palettes::*;
+pub use photo_mode::*;
+pub use picking::*;
 pub use random::*;
-pub use ready::*;
+pub use replay::*;
+pub use save::*;
+pub use thumbnails::*;
+pub use time_scale::*;
 pub use toggleable::*;
+pub use undo::*;
+pub use watched_file::*;
+pub use world_clock::*;
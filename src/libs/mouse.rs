@@ -3,14 +3,24 @@ use bevy_prototype_lyon::prelude::*;
 
 use crate::libs::*;
 
+// MouseState tracks the continuous state of the mouse (position, whether a
+// press is being held and for how long). Discrete clicks are not read from it;
+// `update_mouse_state` turns each press/release edge into a `ClickEvent`
+// message, which every reader sees exactly once regardless of system order.
+// Each click is targeted at the topmost `Clickable` under it (see picking.rs).
+// With the `debug-ui` feature, a press made while Alt is held is the
+// inspector's: it's sent as an `InspectClick` instead, and neither it nor its
+// release becomes a `ClickEvent` or is tracked as a press, so inspecting a
+// minigame doesn't also click it.
+//
 // MouseState process looks like:
 // 0. Position starts at (0,0) until the second frame.
 // 1. Unpressed. Position is always tracked.
 //    Request for click type returns Invalid.
-// 2. Mouse is "just_pressed" -> start tracking time
-// 2. Each frame, update time and position
+// 2. Mouse is "just_pressed" -> start tracking time, emit ClickKind::Press
+// 3. Each frame, update time and position
 // 4. Request for click type returns Short or Long.
-// 5. Mouse is "just_released" -> stop tracking time
+// 5. Mouse is "just_released" -> stop tracking time, emit Short or Long
 // 6. For one more frame, request for click type returns Short or Long
 // 7. After one frame, request for click type returns Invalid.
 #[derive(Resource, Default)]
@@ -91,21 +101,140 @@ pub enum ClickType {
     Invalid,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ClickKind {
+    // button went down
+    Press,
+    // button came up before the long click threshold
+    Short,
+    // button came up after the long click threshold
+    Long,
+}
+
+#[derive(Message, Debug, Copy, Clone, PartialEq)]
+pub struct ClickEvent {
+    pub position: Vec2,
+    pub kind: ClickKind,
+    // the topmost `Clickable` under the cursor, if any
+    pub target: Option<Entity>,
+}
+
+impl ClickEvent {
+    pub fn new(position: Vec2, kind: ClickKind) -> Self {
+        Self {
+            position,
+            kind,
+            target: None,
+        }
+    }
+
+    pub fn targeted(
+        position: Vec2,
+        kind: ClickKind,
+        target: Option<Entity>,
+    ) -> Self {
+        Self {
+            position,
+            kind,
+            target,
+        }
+    }
+
+    pub fn is_press(&self) -> bool {
+        self.kind == ClickKind::Press
+    }
+
+    pub fn is_release(&self) -> bool {
+        !self.is_press()
+    }
+}
+
+// An Alt+click, for the inspector (inspector.rs), in place of a `ClickEvent`.
+#[derive(Message, Debug, Copy, Clone, PartialEq)]
+pub struct InspectClick {
+    pub position: Vec2,
+}
+
+// A click, triggered on its target for the target's own observers
+// (`EntityCommands::observe`). A clickable that handles its clicks this way
+// runs only when it's clicked, rather than a system of its own reading every
+// click every frame to find the ones that are its.
+#[derive(EntityEvent, Debug, Copy, Clone)]
+pub struct Clicked {
+    pub entity: Entity,
+    pub click: ClickEvent,
+}
+
+// Right after picking, so observers see a click the frame it happens. Reads
+// the messages rather than the mouse, so a click written by a test or a
+// replay triggers them too.
+pub fn trigger_clicks(
+    mut commands: Commands,
+    mut click_events: MessageReader<ClickEvent>,
+) {
+    for click in click_events.read() {
+        if let Some(entity) = click.target {
+            commands.trigger(Clicked {
+                entity,
+                click: *click,
+            });
+        }
+    }
+}
+
 pub fn update_mouse_state(
-    camera_query: Query<(&Camera, &GlobalTransform)>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     window_query: Query<&Window>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
     mouse_button_input: Res<ButtonInput<MouseButton>>,
+    kb_input: Res<ButtonInput<KeyCode>>,
     mut mouse_state: ResMut<MouseState>,
+    mut hovered: ResMut<Hovered>,
+    // whether the press now held is the inspector's
+    mut inspecting: Local<bool>,
+    mut click_events: MessageWriter<ClickEvent>,
+    mut inspect_clicks: MessageWriter<InspectClick>,
+    clickable_query: ClickableQuery,
+    layer_query: LayerQuery,
 ) {
     if let Some(position) = get_mouse_position(&camera_query, &window_query) {
         mouse_state.update_state(position, time.elapsed_secs());
     }
+    let position = mouse_state.current_position;
+    let target = pick(position, &clickable_query, &layer_query);
+    if hovered.entity != target {
+        hovered.entity = target;
+    }
 
+    if *inspecting {
+        if mouse_button_input.just_released(MouseButton::Left) {
+            *inspecting = false;
+        }
+        return;
+    }
+    let alt = kb_input.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    if cfg!(feature = "debug-ui")
+        && alt
+        && mouse_button_input.just_pressed(MouseButton::Left)
+    {
+        *inspecting = true;
+        inspect_clicks.write(InspectClick { position });
+        return;
+    }
     if mouse_button_input.just_pressed(MouseButton::Left) {
         mouse_state.start_press(time.elapsed_secs());
+        click_events.write(ClickEvent::targeted(
+            position,
+            ClickKind::Press,
+            target,
+        ));
     } else if mouse_button_input.just_released(MouseButton::Left) {
+        let kind = match mouse_state.get_click_type() {
+            ClickType::Long => ClickKind::Long,
+            _ => ClickKind::Short,
+        };
         mouse_state.end_press();
+        click_events.write(ClickEvent::targeted(position, kind, target));
     } else if mouse_state.just_released {
         mouse_state.unpressed();
     } else {
@@ -172,32 +301,8 @@ pub fn follow_mouse_update(
     }
 }
 
-pub fn get_click_press_position(
-    camera_query: Query<(&Camera, &GlobalTransform)>,
-    window_query: Query<&Window>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
-) -> Option<Vec2> {
-    // TODO: https://bevy-cheatbook.github.io/programming/run-conditions.html
-    if !mouse_button_input.just_pressed(MouseButton::Left) {
-        return None;
-    }
-    get_mouse_position(&camera_query, &window_query)
-}
-
-pub fn get_click_release_position(
-    camera_query: Query<(&Camera, &GlobalTransform)>,
-    window_query: Query<&Window>,
-    mouse_button_input: Res<ButtonInput<MouseButton>>,
-) -> Option<Vec2> {
-    // TODO: https://bevy-cheatbook.github.io/programming/run-conditions.html
-    if !mouse_button_input.just_released(MouseButton::Left) {
-        return None;
-    }
-    get_mouse_position(&camera_query, &window_query)
-}
-
 fn get_mouse_position(
-    camera_query: &Query<(&Camera, &GlobalTransform)>,
+    camera_query: &Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     window_query: &Query<&Window>,
 ) -> Option<Vec2> {
     let Ok((camera, camera_transform)) = camera_query.single() else {
@@ -217,7 +322,9 @@ fn translate_to_world_position(
 ) -> Option<Vec2> {
     window
         .cursor_position()
-        .and_then(|cursor| camera.viewport_to_world(camera_transform, cursor).ok())
+        .and_then(|cursor| {
+            camera.viewport_to_world(camera_transform, cursor).ok()
+        })
         .map(|ray| ray.origin.truncate())
 }
 
@@ -252,7 +359,7 @@ fn manage_click_indicator(
     mouse_state: Res<MouseState>,
     indicator_config: Res<ClickIndicatorConfig>,
     mut indicator_query: Query<(Entity, &mut Shape), With<ClickIndicator>>,
-    time: Res<Time>,
+    time: Res<Time<Real>>,
 ) {
     if !mouse_state.dragging() {
         // Remove the indicator when mouse is not dragging
@@ -262,8 +369,7 @@ fn manage_click_indicator(
         return;
     }
 
-    let elapsed =
-        time.elapsed_secs() - mouse_state.start_time.unwrap_or(0.0);
+    let elapsed = time.elapsed_secs() - mouse_state.start_time.unwrap_or(0.0);
     if elapsed < mouse_state.long_click_threshold / 5.0 {
         return; // not pressed long enough to show indicator
     }
@@ -333,12 +439,11 @@ impl HoverText {
 
 pub fn update_hover_text(
     mut commands: Commands,
-    camera_query: Query<(&Camera, &GlobalTransform)>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<MainCamera>>,
     window_query: Query<&Window>,
     mut hover_text_query: Query<(Entity, &mut HoverText, &GlobalTransform)>,
 ) {
-    let Some(mouse_position) =
-        get_mouse_position(&camera_query, &window_query)
+    let Some(mouse_position) = get_mouse_position(&camera_query, &window_query)
     else {
         return;
     };
@@ -0,0 +1,165 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::libs::*;
+
+// Short messages for the player, shown in the top right corner for a few
+// seconds each. Anything can `push` one; things that happen outside a system
+// (like a texture failing to load mid-draw) are queued and picked up by
+// `report_load_failures`.
+//
+// Every message also goes into a longer log, along with game events too
+// frequent to pop up (see `event_log.rs`).
+
+const NOTIFICATION_SECONDS: f32 = 5.0;
+const MAX_NOTIFICATIONS: usize = 4;
+pub const MAX_LOG_LINES: usize = 100;
+
+#[derive(Debug, Default, Resource)]
+pub struct Notifications {
+    // message and seconds it has left on screen, oldest first
+    pub shown: Vec<(String, f32)>,
+    // message and how many times in a row it happened, oldest first
+    pub log: VecDeque<(String, u32)>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        self.record(message.clone());
+        self.shown.push((message, NOTIFICATION_SECONDS));
+        if self.shown.len() > MAX_NOTIFICATIONS {
+            self.shown.remove(0);
+        }
+    }
+
+    // Add to the log without showing it. A repeat of the last line counts
+    // up instead of taking another.
+    pub fn record(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        if let Some((last, count)) = self.log.back_mut() {
+            if *last == message {
+                *count += 1;
+                return;
+            }
+        }
+        self.log.push_back((message, 1));
+        if self.log.len() > MAX_LOG_LINES {
+            self.log.pop_front();
+        }
+    }
+
+    pub fn tick(&mut self, seconds: f32) {
+        for (_, left) in self.shown.iter_mut() {
+            *left -= seconds;
+        }
+        self.shown.retain(|(_, left)| *left > 0.0);
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct NotificationText;
+
+fn setup_notifications(mut commands: Commands) {
+    commands.spawn((
+        NotificationText,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(6.0),
+            right: Val::Px(6.0),
+            ..default()
+        },
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.9, 0.4)),
+        TextLayout::new_with_justify(Justify::Right),
+    ));
+}
+
+pub fn report_load_failures(mut notifications: ResMut<Notifications>) {
+    for failure in take_load_failures() {
+        notifications.push(failure.to_string());
+    }
+}
+
+fn expire_notifications(
+    time: Res<Time<Real>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    if !notifications.shown.is_empty() {
+        notifications.tick(time.delta_secs());
+    }
+}
+
+fn render_notifications(
+    notifications: Res<Notifications>,
+    mut text_query: Query<&mut Text, With<NotificationText>>,
+) {
+    if !notifications.is_changed() {
+        return;
+    }
+    for mut text in text_query.iter_mut() {
+        let lines: Vec<&str> = notifications
+            .shown
+            .iter()
+            .map(|(message, _)| message.as_str())
+            .collect();
+        text.0 = lines.join("\n");
+    }
+}
+
+pub struct NotificationsPlugin;
+
+impl Plugin for NotificationsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Notifications>()
+            .add_systems(Startup, setup_notifications)
+            .add_systems(
+                Update,
+                (
+                    report_load_failures,
+                    expire_notifications,
+                    render_notifications,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn notifications_expire_and_cap() {
+        let mut notifications = Notifications::default();
+        for i in 0..MAX_NOTIFICATIONS + 1 {
+            notifications.push(format!("message {}", i));
+        }
+        assert_eq!(notifications.shown.len(), MAX_NOTIFICATIONS);
+        assert_eq!(notifications.shown[0].0, "message 1");
+        notifications.tick(NOTIFICATION_SECONDS);
+        assert!(notifications.shown.is_empty());
+        // the log keeps them
+        assert_eq!(notifications.log.len(), MAX_NOTIFICATIONS + 1);
+    }
+
+    #[test]
+    fn log_counts_repeats_and_caps() {
+        let mut notifications = Notifications::default();
+        notifications.record("made mud");
+        notifications.record("made mud");
+        notifications.record("made dirt");
+        assert_eq!(notifications.log[0], ("made mud".to_string(), 2));
+        for i in 0..MAX_LOG_LINES {
+            notifications.record(format!("event {}", i));
+        }
+        assert_eq!(notifications.log.len(), MAX_LOG_LINES);
+        assert_eq!(notifications.log[0].0, "event 0");
+        assert!(notifications.shown.is_empty());
+    }
+}
@@ -0,0 +1,81 @@
+use std::sync::RwLock;
+
+// Amounts as the player reads them. Item amounts are unbounded floats, so
+// anything shown goes through `format_amount`: up to two decimals below a
+// thousand, then 1.2K and 3.4M, and scientific (1.2e9) past that. The
+// scientific notation setting (F1) uses 1.2e3 from a thousand up instead.
+// Like the locale, the notation is global, for code with no access to
+// resources; `Accessibility` sets it.
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Notation {
+    #[default]
+    Short,
+    Scientific,
+}
+
+static ACTIVE_NOTATION: RwLock<Notation> = RwLock::new(Notation::Short);
+
+const SHORT_UNITS: [(f32, &str); 2] = [(1_000.0, "K"), (1_000_000.0, "M")];
+
+pub fn set_notation(notation: Notation) {
+    *ACTIVE_NOTATION.write().unwrap() = notation;
+}
+
+// `value` in the chosen notation.
+pub fn format_amount(value: f32) -> String {
+    format_number(value, *ACTIVE_NOTATION.read().unwrap())
+}
+
+// `value` to `places` decimals, without trailing zeros.
+fn trimmed(value: f32, places: usize) -> String {
+    let text = format!("{:.*}", places, value);
+    if !text.contains('.') {
+        return text;
+    }
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+pub fn format_number(value: f32, notation: Notation) -> String {
+    if value.abs() < 1_000.0 {
+        return trimmed(value, 2);
+    }
+    if notation == Notation::Short {
+        for (scale, suffix) in SHORT_UNITS {
+            // rounded first, so 999,990 reads 1M rather than 1000K
+            let scaled = (value / scale * 10.0).round() / 10.0;
+            if scaled.abs() < 1_000.0 {
+                return format!("{}{}", trimmed(scaled, 1), suffix);
+            }
+        }
+    }
+    format!("{:.1e}", value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_notation_shortens_thousands_and_millions() {
+        let short = |value| format_number(value, Notation::Short);
+        assert_eq!(short(5.0), "5");
+        assert_eq!(short(2.5), "2.5");
+        assert_eq!(short(0.25), "0.25");
+        assert_eq!(short(500.0), "500");
+        assert_eq!(short(1_234.0), "1.2K");
+        assert_eq!(short(1_000.0), "1K");
+        assert_eq!(short(999_990.0), "1M");
+        assert_eq!(short(3_400_000.0), "3.4M");
+        assert_eq!(short(-2_500.0), "-2.5K");
+        assert_eq!(short(1.2e9), "1.2e9");
+    }
+
+    #[test]
+    fn scientific_notation_starts_at_a_thousand() {
+        let scientific = |value| format_number(value, Notation::Scientific);
+        assert_eq!(scientific(999.0), "999");
+        assert_eq!(scientific(1_234.0), "1.2e3");
+        assert_eq!(scientific(3_400_000.0), "3.4e6");
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// The colours items are drawn in, read from `assets/palettes.ron` so they can
+// be tuned without a rebuild. The file is loaded at startup and re-read
+// whenever it changes on disk. A copy of it is compiled in and used until
+// then, and for any palette a broken edit leaves out.
+//
+// In colorblind mode, palettes in `assets/palettes-colorblind.ron` replace
+// their namesakes, so things told apart by hue alone differ in brightness too.
+
+pub const PALETTES_PATH: &str = "assets/palettes.ron";
+pub const COLORBLIND_PALETTES_PATH: &str = "assets/palettes-colorblind.ron";
+const BUILT_IN: &str = include_str!("../../assets/palettes.ron");
+const BUILT_IN_COLORBLIND: &str =
+    include_str!("../../assets/palettes-colorblind.ron");
+
+static BUILT_IN_PALETTES: LazyLock<HashMap<String, image_gen::ColorPalette>> =
+    LazyLock::new(|| {
+        parse_palettes(BUILT_IN).expect("built-in palettes should parse")
+    });
+
+static BUILT_IN_COLORBLIND_PALETTES: LazyLock<
+    HashMap<String, image_gen::ColorPalette>,
+> = LazyLock::new(|| {
+    parse_palettes(BUILT_IN_COLORBLIND)
+        .expect("built-in colorblind palettes should parse")
+});
+
+// The registry's palettes, for drawing code. Drawing happens deep inside item
+// code with no access to resources, so the registry publishes a copy here.
+static ACTIVE_PALETTES: RwLock<
+    Option<HashMap<String, image_gen::ColorPalette>>,
+> = RwLock::new(None);
+
+fn parse_palettes(
+    ron: &str,
+) -> Result<HashMap<String, image_gen::ColorPalette>, ron::error::SpannedError>
+{
+    let colorants: HashMap<String, Vec<image_gen::Colorant>> =
+        ron::from_str(ron)?;
+    Ok(colorants
+        .into_iter()
+        .map(|(name, colorants)| {
+            let mut palette = image_gen::ColorPalette::new();
+            for colorant in colorants {
+                palette.add_colorant(colorant);
+            }
+            (name, palette)
+        })
+        .collect())
+}
+
+// The palette called `name`, from the file if it has one.
+pub fn palette(name: &str) -> image_gen::ColorPalette {
+    let active = ACTIVE_PALETTES.read().unwrap();
+    active
+        .as_ref()
+        .and_then(|palettes| palettes.get(name))
+        .filter(|palette| palette.total_weight > 0)
+        .or_else(|| BUILT_IN_PALETTES.get(name))
+        .unwrap_or_else(|| panic!("no palette named {}", name))
+        .clone()
+}
+
+// Read `file` again if it changed. Returns the new palettes, if there are any.
+fn reload_file(
+    file: &mut WatchedFile,
+) -> Result<Option<HashMap<String, image_gen::ColorPalette>>, GalaxiaError> {
+    let Some(ron) = file.read_if_changed()? else {
+        return Ok(None);
+    };
+    let palettes =
+        parse_palettes(&ron).map_err(|source| GalaxiaError::Parse {
+            path: file.path.clone(),
+            source: Box::new(source),
+        })?;
+    Ok(Some(palettes))
+}
+
+#[derive(Debug, Resource)]
+pub struct PaletteRegistry {
+    pub file: WatchedFile,
+    pub palettes: HashMap<String, image_gen::ColorPalette>,
+    pub colorblind_file: WatchedFile,
+    pub colorblind_palettes: HashMap<String, image_gen::ColorPalette>,
+    // whether the published palettes are the colorblind ones; None until
+    // they're published
+    pub published_colorblind: Option<bool>,
+}
+
+impl Default for PaletteRegistry {
+    fn default() -> Self {
+        Self::new(PALETTES_PATH, COLORBLIND_PALETTES_PATH)
+    }
+}
+
+impl PaletteRegistry {
+    pub fn new(path: &str, colorblind_path: &str) -> Self {
+        Self {
+            file: WatchedFile::new(path),
+            palettes: BUILT_IN_PALETTES.clone(),
+            colorblind_file: WatchedFile::new(colorblind_path),
+            colorblind_palettes: BUILT_IN_COLORBLIND_PALETTES.clone(),
+            published_colorblind: None,
+        }
+    }
+
+    // Read the files again if they changed since they were last read.
+    // Returns whether the palettes changed; on an error the old ones are
+    // kept.
+    pub fn reload(&mut self) -> Result<bool, GalaxiaError> {
+        let mut changed = false;
+        if let Some(palettes) = reload_file(&mut self.file)? {
+            self.palettes = palettes;
+            changed = true;
+        }
+        if let Some(palettes) = reload_file(&mut self.colorblind_file)? {
+            self.colorblind_palettes = palettes;
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    // The palettes items are drawn in, with the colorblind ones on top if
+    // `colorblind`.
+    pub fn merged(
+        &self,
+        colorblind: bool,
+    ) -> HashMap<String, image_gen::ColorPalette> {
+        let mut palettes = self.palettes.clone();
+        if colorblind {
+            palettes.extend(self.colorblind_palettes.clone());
+        }
+        palettes
+    }
+
+    // Make these the palettes items are drawn in.
+    pub fn publish(&mut self, colorblind: bool) {
+        *ACTIVE_PALETTES.write().unwrap() = Some(self.merged(colorblind));
+        self.published_colorblind = Some(colorblind);
+    }
+}
+
+fn watch_palettes(
+    time: Res<Time<Real>>,
+    accessibility: Res<Accessibility>,
+    mut registry: ResMut<PaletteRegistry>,
+    mut images: ResMut<Assets<Image>>,
+    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
+    mut item_query: Query<(&Item, &CircularArea, &mut Sprite)>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let mut changed =
+        registry.published_colorblind != Some(accessibility.colorblind);
+    if registry.file.tick(time.delta_secs()) {
+        match registry.reload() {
+            Ok(reloaded) => changed |= reloaded,
+            Err(error) => notifications.push(error.to_string()),
+        }
+    }
+    if !changed {
+        return;
+    }
+    registry.publish(accessibility.colorblind);
+    // drop old textures and redraw loose items in the new colours; cells
+    // repaint themselves
+    generated_image_assets.clear();
+    for (item, area, mut sprite) in item_query.iter_mut() {
+        sprite.image = item.r#type.texture(
+            &mut images,
+            &mut generated_image_assets,
+            area.radius,
+        );
+    }
+}
+
+pub struct PalettesPlugin;
+
+impl Plugin for PalettesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PaletteRegistry>()
+            .add_systems(Update, watch_palettes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn built_in_palettes_parse() {
+        let palettes = parse_palettes(BUILT_IN).unwrap();
+        let archaea = &palettes["Archaea"].colorants[0];
+        assert_eq!(archaea.alpha, 200);
+        assert_eq!(archaea.alpha_looseness, 10);
+        // omitted fields take their defaults
+        let mud = &palettes["Mud"].colorants[0];
+        assert_eq!((mud.alpha, mud.weight), (255, 1));
+    }
+
+    #[test]
+    fn reload_only_when_the_file_changes() {
+        let path = std::env::temp_dir().join("galaxia-palettes-test.ron");
+        std::fs::write(&path, "{\"Mud\": [(red: 1, green: 2, blue: 3)]}")
+            .unwrap();
+        let colorblind_path =
+            std::env::temp_dir().join("galaxia-palettes-colorblind-test.ron");
+        std::fs::write(&colorblind_path, "{}").unwrap();
+        let mut registry = PaletteRegistry::new(
+            path.to_str().unwrap(),
+            colorblind_path.to_str().unwrap(),
+        );
+        assert!(registry.reload().unwrap());
+        assert_eq!(registry.palettes["Mud"].colorants[0].red, 1);
+        assert!(!registry.reload().unwrap());
+
+        // a broken file is reported but keeps what was there
+        std::fs::write(&path, "{\"Mud\": [(red: 1,").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(
+            std::time::SystemTime::now() + std::time::Duration::from_secs(1),
+        )
+        .unwrap();
+        assert!(registry.reload().is_err());
+        assert_eq!(registry.palettes["Mud"].colorants[0].red, 1);
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&colorblind_path).unwrap();
+    }
+
+    #[test]
+    fn colorblind_palettes_replace_their_namesakes() {
+        let registry = PaletteRegistry::default();
+        for name in registry.colorblind_palettes.keys() {
+            assert!(registry.palettes.contains_key(name), "stray {}", name);
+        }
+        let red = |palettes: &HashMap<String, image_gen::ColorPalette>| {
+            palettes["Moss"].colorants[0].red
+        };
+        assert_eq!(red(&registry.merged(false)), red(&registry.palettes));
+        assert_eq!(
+            red(&registry.merged(true)),
+            red(&registry.colorblind_palettes)
+        );
+        // everything else is left alone
+        assert_eq!(registry.merged(true)["Iron"].colorants[0].red, 110);
+    }
+}
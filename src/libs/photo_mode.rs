@@ -0,0 +1,305 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::camera::RenderTarget;
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy::render::view::screenshot::{Screenshot, ScreenshotCaptured};
+use bevy::window::PrimaryWindow;
+
+use crate::libs::*;
+
+// Photo mode, for taking pictures of the board. F3 toggles it. While it's on,
+// the UI is hidden, the player stays put and the camera flies free: WASD or
+// the arrow keys move it and the mouse wheel zooms. Enter saves a PNG under
+// `screenshots/`; Tab cycles between 1x, 2x and 4x, which renders the same
+// view at that multiple of the window's resolution.
+
+pub const SCREENSHOT_DIR: &str = "screenshots";
+const SUPERSAMPLES: [u32; 3] = [1, 2, 4];
+// camera pixels per second at zoom 1
+const FLY_SPEED: f32 = 600.0;
+const MIN_ZOOM: f32 = 0.05;
+const MAX_ZOOM: f32 = 10.0;
+// frames to let a supersampling camera render before capturing it
+const CAPTURE_DELAY_FRAMES: u8 = 2;
+
+#[derive(Debug, Resource)]
+pub struct PhotoMode {
+    pub active: bool,
+    // times the window's resolution a screenshot is rendered at
+    pub supersample: u32,
+}
+
+impl Default for PhotoMode {
+    fn default() -> Self {
+        Self {
+            active: false,
+            supersample: 1,
+        }
+    }
+}
+
+impl PhotoMode {
+    pub fn cycle_supersample(&mut self) {
+        let index = SUPERSAMPLES
+            .iter()
+            .position(|&factor| factor == self.supersample)
+            .unwrap_or(0);
+        self.supersample = SUPERSAMPLES[(index + 1) % SUPERSAMPLES.len()];
+    }
+}
+
+pub fn photo_mode_off(photo_mode: Res<PhotoMode>) -> bool {
+    !photo_mode.active
+}
+
+// Where a screenshot taken at `seconds` since the epoch is saved.
+pub fn screenshot_path(seconds: u64, supersample: u32) -> String {
+    if supersample == 1 {
+        format!("{}/galaxia-{}.png", SCREENSHOT_DIR, seconds)
+    } else {
+        format!(
+            "{}/galaxia-{}-{}x.png",
+            SCREENSHOT_DIR, seconds, supersample
+        )
+    }
+}
+
+// A UI node hidden for photo mode, with the visibility to give back after.
+#[derive(Debug, Component)]
+pub struct HiddenForPhoto(pub Visibility);
+
+// An extra camera rendering the view into an image for a supersampled
+// screenshot. Despawned once the screenshot is taken.
+#[derive(Debug, Component)]
+pub struct PhotoCamera {
+    pub image: Handle<Image>,
+    pub path: String,
+    pub frames_left: u8,
+}
+
+fn toggle_photo_mode(
+    mut commands: Commands,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut photo_mode: ResMut<PhotoMode>,
+    mut ui_query: Query<
+        (Entity, &mut Visibility),
+        (With<Node>, Without<ChildOf>, Without<HiddenForPhoto>),
+    >,
+    mut hidden_query: Query<(Entity, &HiddenForPhoto, &mut Visibility)>,
+) {
+    if !kb_input.just_pressed(KeyCode::F3) {
+        return;
+    }
+    photo_mode.active = !photo_mode.active;
+    if photo_mode.active {
+        for (entity, mut visibility) in ui_query.iter_mut() {
+            commands.entity(entity).insert(HiddenForPhoto(*visibility));
+            *visibility = Visibility::Hidden;
+        }
+    } else {
+        for (entity, hidden, mut visibility) in hidden_query.iter_mut() {
+            *visibility = hidden.0;
+            commands.entity(entity).remove::<HiddenForPhoto>();
+        }
+    }
+}
+
+fn fly_camera(
+    time: Res<Time<Real>>,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut evr_scroll: MessageReader<MouseWheel>,
+    mut camera_query: Query<
+        (&mut Transform, &mut Projection),
+        With<MainCamera>,
+    >,
+) {
+    let Ok((mut transform, mut projection)) = camera_query.single_mut() else {
+        return;
+    };
+    let Projection::Orthographic(projection) = projection.as_mut() else {
+        return;
+    };
+
+    let mut direction = Vec2::ZERO;
+    if kb_input.any_pressed([KeyCode::KeyW, KeyCode::ArrowUp]) {
+        direction.y += 1.0;
+    }
+    if kb_input.any_pressed([KeyCode::KeyS, KeyCode::ArrowDown]) {
+        direction.y -= 1.0;
+    }
+    if kb_input.any_pressed([KeyCode::KeyA, KeyCode::ArrowLeft]) {
+        direction.x -= 1.0;
+    }
+    if kb_input.any_pressed([KeyCode::KeyD, KeyCode::ArrowRight]) {
+        direction.x += 1.0;
+    }
+    // the same speed on screen at any zoom
+    let step = direction.normalize_or_zero()
+        * FLY_SPEED
+        * projection.scale
+        * time.delta_secs();
+    transform.translation += step.extend(0.0);
+
+    for ev in evr_scroll.read() {
+        projection.scale =
+            (projection.scale * (1.0 - ev.y * 0.1)).clamp(MIN_ZOOM, MAX_ZOOM);
+    }
+}
+
+fn take_photo(
+    mut commands: Commands,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut photo_mode: ResMut<PhotoMode>,
+    mut images: ResMut<Assets<Image>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Transform, &Projection), With<MainCamera>>,
+    mut notifications: ResMut<Notifications>,
+) {
+    if kb_input.just_pressed(KeyCode::Tab) {
+        photo_mode.cycle_supersample();
+        notifications.record(tr_fmt(
+            "photo.supersample",
+            &[("factor", &photo_mode.supersample)],
+        ));
+    }
+    if !kb_input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    if let Err(source) = std::fs::create_dir_all(SCREENSHOT_DIR) {
+        notifications.push(
+            GalaxiaError::Write {
+                path: SCREENSHOT_DIR.to_string(),
+                source,
+            }
+            .to_string(),
+        );
+        return;
+    }
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let path = screenshot_path(seconds, photo_mode.supersample);
+
+    if photo_mode.supersample == 1 {
+        commands
+            .spawn(Screenshot::primary_window())
+            .observe(save_photo(path));
+        return;
+    }
+
+    let (Ok(window), Ok((transform, projection))) =
+        (window_query.single(), camera_query.single())
+    else {
+        return;
+    };
+    let Projection::Orthographic(projection) = projection else {
+        return;
+    };
+    // the same view, with `supersample` times the pixels along each side
+    let image = images.add(Image::new_target_texture(
+        window.physical_width() * photo_mode.supersample,
+        window.physical_height() * photo_mode.supersample,
+        TEXTURE_FORMAT,
+        None,
+    ));
+    commands.spawn((
+        PhotoCamera {
+            image: image.clone(),
+            path,
+            frames_left: CAPTURE_DELAY_FRAMES,
+        },
+        Camera2d,
+        RenderTarget::Image(image.into()),
+        *transform,
+        Projection::Orthographic(OrthographicProjection {
+            scale: projection.scale / photo_mode.supersample as f32,
+            ..projection.clone()
+        }),
+    ));
+}
+
+fn capture_photo_cameras(
+    mut commands: Commands,
+    mut camera_query: Query<(Entity, &mut PhotoCamera)>,
+) {
+    for (entity, mut camera) in camera_query.iter_mut() {
+        if camera.frames_left > 0 {
+            camera.frames_left -= 1;
+            continue;
+        }
+        commands
+            .spawn(Screenshot::image(camera.image.clone()))
+            .observe(save_photo(camera.path.clone()));
+        commands.entity(entity).despawn();
+    }
+}
+
+// Saves a captured screenshot to `path` and says so.
+fn save_photo(
+    path: String,
+) -> impl FnMut(On<ScreenshotCaptured>, ResMut<Notifications>) {
+    move |captured, mut notifications| {
+        let saved = captured
+            .image
+            .clone()
+            .try_into_dynamic()
+            .map_err(|error| format!("can't save {}: {}", path, error))
+            .and_then(|image| {
+                // drop alpha, which the window's image doesn't use
+                image.to_rgb8().save(&path).map_err(|source| {
+                    GalaxiaError::Encode {
+                        path: path.clone(),
+                        source,
+                    }
+                    .to_string()
+                })
+            });
+        notifications.push(match saved {
+            Ok(()) => tr_fmt("photo.saved", &[("path", &path)]),
+            Err(error) => error,
+        });
+    }
+}
+
+pub struct PhotoModePlugin;
+
+impl Plugin for PhotoModePlugin {
+    fn build(&self, app: &mut App) {
+        // GamePlugin owns PhotoMode, since its camera and player systems stop
+        // for it
+        app.add_systems(
+            Update,
+            (
+                toggle_photo_mode.run_if(console::console_closed),
+                (fly_camera, take_photo)
+                    .run_if(console::console_closed)
+                    .run_if(not(photo_mode_off)),
+                capture_photo_cameras,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn supersample_cycles_through_each_factor() {
+        let mut photo_mode = PhotoMode::default();
+        let mut seen = vec![photo_mode.supersample];
+        for _ in 0..SUPERSAMPLES.len() {
+            photo_mode.cycle_supersample();
+            seen.push(photo_mode.supersample);
+        }
+        assert_eq!(seen, [1, 2, 4, 1]);
+    }
+
+    #[test]
+    fn screenshot_paths_name_the_supersample() {
+        assert_eq!(screenshot_path(7, 1), "screenshots/galaxia-7.png");
+        assert_eq!(screenshot_path(7, 4), "screenshots/galaxia-7-4x.png");
+    }
+}
@@ -0,0 +1,210 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::libs::*;
+
+// Central hit-testing. Anything the player can click carries `Clickable` next
+// to its `RectangularArea` or `CircularArea`. Each frame `update_mouse_state`
+// asks `pick` for the single topmost clickable under the cursor, records it in
+// `Hovered`, and stamps it on any `ClickEvent` as the `target`. Click systems
+// then react only to clicks targeted at their own entities, so overlapping
+// elements no longer all fire at once.
+//
+// Where two minigames overlap, the one drawn on top takes the click: the
+// layer, the z of the root of a clickable's hierarchy (its minigame, for most),
+// comes first. `MinigameStack` sets those, bringing the engaged minigame to
+// the front.
+
+// Within a layer, higher priority wins regardless of z. Within a priority,
+// higher global z wins.
+pub const CLICK_PRIORITY_CONTENT: i32 = 0; // pixels, cells, paddles, ...
+pub const CLICK_PRIORITY_INVENTORY: i32 = 1; // slots and scroll buttons
+pub const CLICK_PRIORITY_HEADER: i32 = 2; // engage buttons
+
+#[derive(Debug, Copy, Clone, Component)]
+pub struct Clickable {
+    pub priority: i32,
+}
+
+impl Clickable {
+    pub fn new(priority: i32) -> Self {
+        Self { priority }
+    }
+}
+
+// Gets an outline while it is the hovered entity, so the player can tell what
+// is clickable. Needs a `RectangularArea` or `CircularArea` to size it.
+#[derive(Debug, Copy, Clone, Default, Component)]
+pub struct Hoverable;
+
+// The outline child spawned on the hovered `Hoverable`.
+#[derive(Debug, Component)]
+pub struct HoverHighlight;
+
+const HIGHLIGHT_COLOR: Color = Color::srgba(1.0, 0.85, 0.0, 0.9);
+const HIGHLIGHT_WIDTH: f32 = 2.0;
+
+// The topmost clickable under the cursor this frame, if any.
+#[derive(Debug, Default, Resource)]
+pub struct Hovered {
+    pub entity: Option<Entity>,
+}
+
+pub type ClickableQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Clickable,
+        &'static GlobalTransform,
+        Option<&'static RectangularArea>,
+        Option<&'static CircularArea>,
+    ),
+>;
+
+pub type LayerQuery<'w, 's> =
+    Query<'w, 's, (&'static GlobalTransform, Option<&'static ChildOf>)>;
+
+pub fn pick(
+    position: Vec2,
+    query: &ClickableQuery,
+    layer_query: &LayerQuery,
+) -> Option<Entity> {
+    topmost(query.iter().filter_map(
+        |(entity, clickable, global_transform, rectangle, circle)| {
+            let translation = global_transform.translation();
+            let center = translation.truncate();
+            let hit = match (rectangle, circle) {
+                (Some(rectangle), _) => rectangle.is_within(position, center),
+                (None, Some(circle)) => circle.is_within(position, center),
+                (None, None) => false,
+            };
+            hit.then(|| {
+                let layer = layer(entity, layer_query);
+                (entity, layer, clickable.priority, translation.z)
+            })
+        },
+    ))
+}
+
+// The z of the root of `entity`'s hierarchy, which everything under it is
+// drawn relative to.
+pub fn layer(entity: Entity, query: &LayerQuery) -> f32 {
+    let mut entity = entity;
+    loop {
+        match query.get(entity) {
+            Ok((_, Some(child_of))) => entity = child_of.parent(),
+            Ok((global_transform, None)) => {
+                return global_transform.translation().z
+            }
+            Err(_) => return 0.0,
+        }
+    }
+}
+
+// Moves the outline whenever `Hovered` changes. The outline is a child of the
+// hovered entity so it follows it and is despawned along with it.
+pub fn update_hover_highlight(
+    mut commands: Commands,
+    hovered: Res<Hovered>,
+    highlight_query: Query<Entity, With<HoverHighlight>>,
+    hoverable_query: Query<
+        (Option<&RectangularArea>, Option<&CircularArea>),
+        With<Hoverable>,
+    >,
+) {
+    if !hovered.is_changed() {
+        return;
+    }
+    for highlight in highlight_query.iter() {
+        commands.entity(highlight).try_despawn();
+    }
+
+    let Some(entity) = hovered.entity else {
+        return;
+    };
+    let Ok((rectangle, circle)) = hoverable_query.get(entity) else {
+        return;
+    };
+    let builder = match (rectangle, circle) {
+        (Some(rectangle), _) => ShapeBuilder::with(&shapes::Rectangle {
+            extents: rectangle.dimensions(),
+            ..default()
+        }),
+        (None, Some(circle)) => ShapeBuilder::with(&shapes::Circle {
+            radius: circle.radius,
+            ..default()
+        }),
+        (None, None) => return,
+    };
+    commands.entity(entity).with_child((
+        HoverHighlight,
+        builder
+            .stroke(Stroke::new(HIGHLIGHT_COLOR, HIGHLIGHT_WIDTH))
+            .build(),
+        Transform::from_xyz(0.0, 0.0, 0.5),
+    ));
+}
+
+// Picks the winner among entities already known to be under the cursor, each
+// with its layer, priority and z.
+pub fn topmost(
+    hits: impl IntoIterator<Item = (Entity, f32, i32, f32)>,
+) -> Option<Entity> {
+    hits.into_iter()
+        .max_by(
+            |(_, a_layer, a_priority, a_z), (_, b_layer, b_priority, b_z)| {
+                a_layer
+                    .total_cmp(b_layer)
+                    .then(a_priority.cmp(b_priority))
+                    .then(a_z.total_cmp(b_z))
+            },
+        )
+        .map(|(entity, ..)| entity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn priority_beats_z() {
+        let low = Entity::from_raw_u32(1).unwrap();
+        let high = Entity::from_raw_u32(2).unwrap();
+        assert_eq!(
+            topmost([
+                (low, 0.0, CLICK_PRIORITY_CONTENT, 10.0),
+                (high, 0.0, CLICK_PRIORITY_HEADER, 0.0),
+            ]),
+            Some(high)
+        );
+    }
+
+    #[test]
+    fn z_breaks_priority_ties() {
+        let below = Entity::from_raw_u32(1).unwrap();
+        let above = Entity::from_raw_u32(2).unwrap();
+        assert_eq!(
+            topmost([
+                (above, 0.0, CLICK_PRIORITY_CONTENT, 1.0),
+                (below, 0.0, CLICK_PRIORITY_CONTENT, 0.5),
+            ]),
+            Some(above)
+        );
+        assert_eq!(topmost([]), None);
+    }
+
+    // Content of the minigame on top beats the header of one underneath.
+    #[test]
+    fn the_top_layer_beats_priority() {
+        let under = Entity::from_raw_u32(1).unwrap();
+        let over = Entity::from_raw_u32(2).unwrap();
+        assert_eq!(
+            topmost([
+                (under, 0.0, CLICK_PRIORITY_HEADER, 1.0),
+                (over, 4.0, CLICK_PRIORITY_CONTENT, 3.5),
+            ]),
+            Some(over)
+        );
+    }
+}
@@ -1,19 +1,226 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
 use bevy::prelude::*;
 use wyrand::WyRand;
 
+use crate::libs::*;
+
+// Every random number the game uses comes from a `Random`. The world's is a
+// resource, seeded once per game: `--seed <n>` on the command line picks the
+// seed, and otherwise a new game gets a fresh one. The seed is shown when the
+// game starts, so a world can be shared and replayed. Given the same seed and
+// the same inputs, a game plays out the same.
+//
+// Item textures are drawn when first seen, which depends on where the camera
+// is, so drawing takes from its own `Random` with a fixed seed rather than the
+// world's; otherwise looking around would change what the world does next.
+
+// the seed texture drawing starts from each time
+pub const DRAWING_SEED: u64 = 91;
+
 #[derive(Resource)]
 pub struct Random {
     rng: WyRand,
+    seed: u64,
 }
 
 impl Random {
     pub fn new(seed: u64) -> Self {
         Self {
             rng: WyRand::new(seed),
+            seed,
         }
     }
 
+    // For drawing one texture; the same every time.
+    pub fn for_drawing() -> Self {
+        Self::new(DRAWING_SEED)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
     pub fn next(&mut self) -> u64 {
         self.rng.rand()
     }
+
+    // Uniform in `0..n`. Plain `next() % n` favors the low end whenever `n`
+    // doesn't divide 2^64, so the few values past the last whole multiple of
+    // `n` are thrown out and drawn again.
+    pub fn next_range(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "next_range: empty range");
+        // 2^64 mod n: how many low values to throw out
+        let threshold = n.wrapping_neg() % n;
+        loop {
+            let value = self.next();
+            if value >= threshold {
+                return value % n;
+            }
+        }
+    }
+
+    // Uniform in `[0, 1)`, on the 2^24 steps an f32 holds exactly there.
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    // True with probability `p`: never at 0 or below, always at 1 or above.
+    pub fn chance(&mut self, p: f32) -> bool {
+        self.next_f32() < p
+    }
+
+    // Any one of `items`, each as likely; None if there are none.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            return None;
+        }
+        items.get(self.next_range(items.len() as u64) as usize)
+    }
+
+    // One of `items`, each as likely as its share of the total weight. None
+    // if there are none or they all weigh nothing.
+    pub fn pick_weighted<'a, T>(
+        &mut self,
+        items: &'a [T],
+        weight: impl Fn(&T) -> u64,
+    ) -> Option<&'a T> {
+        let total: u64 = items.iter().map(&weight).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = self.next_range(total);
+        items.iter().find(|item| {
+            let weight = weight(item);
+            if roll < weight {
+                return true;
+            }
+            roll -= weight;
+            false
+        })
+    }
+}
+
+// Looks for `--seed <n>` on the command line.
+pub fn seed_from_args(args: impl IntoIterator<Item = String>) -> Option<u64> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            return args.next().and_then(|seed| seed.parse().ok());
+        }
+    }
+    None
+}
+
+// A seed no two games are likely to share. Comes from the hasher keys std
+// draws from the OS, so it works in a browser too, where the clock can't be
+// read this way.
+pub fn fresh_seed() -> u64 {
+    RandomState::new().hash_one(0u8)
+}
+
+// Seeds the world for a new game. Add after `GamePlugin`, whose default seed
+// this replaces, and before `ReplayPlugin`, which records it.
+pub struct SeedPlugin {
+    pub seed: u64,
+}
+
+impl Plugin for SeedPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Random::new(self.seed))
+            .add_systems(Startup, announce_seed);
+    }
+}
+
+// Runs after a replay has swapped in its own seed, so it shows that one.
+fn announce_seed(
+    random: Res<Random>,
+    mut notifications: ResMut<Notifications>,
+) {
+    let seed = random.seed();
+    println!("World seed: {}", seed);
+    notifications.push(tr_fmt("seed.announce", &[("seed", &seed)]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_comes_from_args() {
+        let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+        assert_eq!(seed_from_args(args("galaxia --seed 1234")), Some(1234));
+        assert_eq!(seed_from_args(args("galaxia")), None);
+        assert_eq!(seed_from_args(args("galaxia --seed")), None);
+        assert_eq!(seed_from_args(args("galaxia --seed pumpkin")), None);
+    }
+
+    #[test]
+    fn a_seed_repeats_its_numbers() {
+        let mut first = Random::new(5);
+        let mut second = Random::new(5);
+        for _ in 0..100 {
+            assert_eq!(first.next(), second.next());
+        }
+        let drawn = Random::for_drawing().next();
+        assert_eq!(drawn, Random::for_drawing().next());
+    }
+
+    #[test]
+    fn ranges_stay_in_bounds() {
+        let mut random = Random::new(1);
+        for n in [1, 2, 3, 7, 1000, u64::MAX] {
+            for _ in 0..1000 {
+                assert!(random.next_range(n) < n);
+            }
+        }
+        for _ in 0..10_000 {
+            let f = random.next_f32();
+            assert!((0.0..1.0).contains(&f));
+        }
+        assert!((0..100).all(|_| !random.chance(0.0)));
+        assert!((0..100).all(|_| random.chance(1.0)));
+        assert_eq!(random.pick::<u8>(&[]), None);
+        assert_eq!(random.pick(&[4]), Some(&4));
+    }
+
+    // With `n` two thirds of 2^64, `next() % n` would land in the lower half
+    // of the range twice as often as the upper half.
+    #[test]
+    fn ranges_are_unbiased() {
+        let mut random = Random::new(2);
+        let n = u64::MAX / 3 * 2;
+        let samples = 30_000;
+        let low = (0..samples)
+            .filter(|_| random.next_range(n) < n / 2)
+            .count();
+        assert!((14_000..16_000).contains(&low), "{} of {}", low, samples);
+
+        let mut counts = [0; 3];
+        for _ in 0..samples {
+            counts[random.next_range(3) as usize] += 1;
+        }
+        assert!(counts.iter().all(|count| (9_500..10_500).contains(count)));
+
+        let hits = (0..samples).filter(|_| random.chance(0.25)).count();
+        assert!((7_000..8_000).contains(&hits));
+    }
+
+    #[test]
+    fn weighted_picks_follow_their_weights() {
+        let mut random = Random::new(3);
+        let items = [("never", 0), ("rare", 1), ("common", 3)];
+        let mut common = 0;
+        for _ in 0..8_000 {
+            let (name, _) = random.pick_weighted(&items, |(_, w)| *w).unwrap();
+            assert_ne!(*name, "never");
+            if *name == "common" {
+                common += 1;
+            }
+        }
+        assert!((5_700..6_300).contains(&common), "{}", common);
+        let weightless = [("a", 0), ("b", 0)];
+        assert_eq!(random.pick_weighted(&weightless, |(_, w)| *w), None);
+    }
 }
@@ -0,0 +1,473 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bevy::ecs::schedule::{ExecutorKind, ScheduleLabel};
+use bevy::input::keyboard::{Key, KeyboardInput, NativeKey};
+use bevy::input::mouse::{MouseButtonInput, MouseScrollUnit, MouseWheel};
+use bevy::input::{ButtonState, InputSystems};
+use bevy::prelude::*;
+use bevy::reflect::{
+    DynamicEnum, DynamicVariant, Enum, Typed, VariantInfo, VariantType,
+};
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::PrimaryWindow;
+use serde::{Deserialize, Serialize};
+
+use crate::libs::*;
+
+// Input recording and playback, for reproducing bugs that depend on exact
+// timing. `--record <file>` writes every frame's real time delta and raw input
+// (cursor, mouse buttons, keys, wheel) as JSON lines, flushed as it goes so a
+// crash still leaves a usable file. `--replay <file>` reseeds `Random`, then
+// feeds the recording back in: each frame gets the recorded delta and the
+// recorded input messages, written before Bevy's input systems turn them into
+// `ButtonInput` state. Live mouse and keyboard input is dropped until the
+// recording runs out, after which the game carries on normally.
+//
+// Both modes run the main schedules single-threaded so system order can't
+// vary between the two runs.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayMode {
+    Off,
+    Record(PathBuf),
+    Play(PathBuf),
+}
+
+impl ReplayMode {
+    // Looks for `--record <file>` or `--replay <file>` on the command line.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Self {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mode: fn(PathBuf) -> Self = match arg.as_str() {
+                "--record" => Self::Record,
+                "--replay" => Self::Play,
+                _ => continue,
+            };
+            return args.next().map_or(Self::Off, |path| mode(path.into()));
+        }
+        Self::Off
+    }
+}
+
+// First line of a replay file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayHeader {
+    pub seed: u64,
+}
+
+// Every later line: one per frame, in order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    pub frame: u64,
+    pub delta_nanos: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<ReplayInput>,
+}
+
+// Bevy's input types aren't serializable without features we don't enable,
+// so keys and buttons are stored by variant name.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ReplayInput {
+    // Logical window position; None once the cursor leaves the window.
+    Cursor(Option<(f32, f32)>),
+    Mouse {
+        button: String,
+        pressed: bool,
+    },
+    Key {
+        code: String,
+        pressed: bool,
+        text: Option<String>,
+    },
+    Wheel {
+        lines: bool,
+        x: f32,
+        y: f32,
+    },
+}
+
+// Unit variants only: `KeyCode::Unidentified(..)` and `MouseButton::Other(..)`
+// carry data and aren't worth recording.
+pub fn variant_name<T: Enum>(value: &T) -> Option<String> {
+    (value.variant_type() == VariantType::Unit)
+        .then(|| value.variant_name().to_string())
+}
+
+fn from_variant_name<T: FromReflect + Typed>(name: &str) -> Option<T> {
+    // The derived `from_reflect` panics on an unknown variant, so check first.
+    let variant = T::type_info().as_enum().ok()?.variant(name)?;
+    if !matches!(variant, VariantInfo::Unit(_)) {
+        return None;
+    }
+    T::from_reflect(&DynamicEnum::new(name, DynamicVariant::Unit))
+}
+
+fn pressed(state: ButtonState) -> bool {
+    state == ButtonState::Pressed
+}
+
+fn button_state(pressed: bool) -> ButtonState {
+    if pressed {
+        ButtonState::Pressed
+    } else {
+        ButtonState::Released
+    }
+}
+
+#[derive(Resource)]
+struct Recorder {
+    file: BufWriter<Box<dyn Write + Send + Sync>>,
+    frame: u64,
+    cursor: Option<Vec2>,
+}
+
+impl Recorder {
+    fn create(path: &Path, header: &ReplayHeader) -> std::io::Result<Self> {
+        let mut file = BufWriter::new(storage::create(path)?);
+        writeln!(file, "{}", serde_json::to_string(header)?)?;
+        Ok(Self {
+            file,
+            frame: 0,
+            cursor: None,
+        })
+    }
+
+    fn write(&mut self, frame: &ReplayFrame) -> std::io::Result<()> {
+        writeln!(self.file, "{}", serde_json::to_string(frame)?)?;
+        self.file.flush()
+    }
+}
+
+#[derive(Resource)]
+struct Playback {
+    frames: VecDeque<ReplayFrame>,
+    cursor: Option<Vec2>,
+}
+
+pub fn read_replay(
+    path: &Path,
+) -> Result<(ReplayHeader, Vec<ReplayFrame>), String> {
+    let file = storage::open(path).map_err(|e| e.to_string())?;
+    let mut lines = file.lines();
+    let header = lines.next().ok_or("empty replay file")?;
+    let header: ReplayHeader =
+        serde_json::from_str(&header.map_err(|e| e.to_string())?)
+            .map_err(|e| e.to_string())?;
+    let frames = lines
+        .map(|line| {
+            serde_json::from_str(&line.map_err(|e| e.to_string())?)
+                .map_err(|e| e.to_string())
+        })
+        .collect::<Result<Vec<ReplayFrame>, String>>()?;
+    Ok((header, frames))
+}
+
+fn record_inputs(
+    mut recorder: ResMut<Recorder>,
+    time: Res<Time<Real>>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut mouse_events: MessageReader<MouseButtonInput>,
+    mut keyboard_events: MessageReader<KeyboardInput>,
+    mut wheel_events: MessageReader<MouseWheel>,
+) {
+    let mut inputs = vec![];
+    let cursor = window_query
+        .single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    if cursor != recorder.cursor {
+        recorder.cursor = cursor;
+        inputs.push(ReplayInput::Cursor(cursor.map(|c| (c.x, c.y))));
+    }
+    for event in mouse_events.read() {
+        if let Some(button) = variant_name(&event.button) {
+            inputs.push(ReplayInput::Mouse {
+                button,
+                pressed: pressed(event.state),
+            });
+        }
+    }
+    for event in keyboard_events.read() {
+        if let Some(code) = variant_name(&event.key_code) {
+            inputs.push(ReplayInput::Key {
+                code,
+                pressed: pressed(event.state),
+                text: event.text.as_ref().map(|text| text.to_string()),
+            });
+        }
+    }
+    for event in wheel_events.read() {
+        inputs.push(ReplayInput::Wheel {
+            lines: event.unit == MouseScrollUnit::Line,
+            x: event.x,
+            y: event.y,
+        });
+    }
+
+    let frame = ReplayFrame {
+        frame: recorder.frame,
+        delta_nanos: time.delta().as_nanos() as u64,
+        inputs,
+    };
+    recorder.frame += 1;
+    if let Err(error) = recorder.write(&frame) {
+        error!(frame = frame.frame, %error, "failed to write replay frame");
+    }
+}
+
+// Writes `inputs` as Bevy's input messages from `window`, leaving `cursor`
+// where they put it. Attract mode makes up its own inputs and writes them
+// through here too.
+pub fn write_inputs(
+    inputs: Vec<ReplayInput>,
+    window: Entity,
+    cursor: &mut Option<Vec2>,
+    mouse_events: &mut Messages<MouseButtonInput>,
+    keyboard_events: &mut Messages<KeyboardInput>,
+    wheel_events: &mut Messages<MouseWheel>,
+) {
+    for input in inputs {
+        match input {
+            ReplayInput::Cursor(position) => {
+                *cursor = position.map(|(x, y)| Vec2::new(x, y));
+            }
+            ReplayInput::Mouse { button, pressed } => {
+                let Some(button) = from_variant_name(&button) else {
+                    continue;
+                };
+                mouse_events.write(MouseButtonInput {
+                    button,
+                    state: button_state(pressed),
+                    window,
+                });
+            }
+            ReplayInput::Key {
+                code,
+                pressed,
+                text,
+            } => {
+                let Some(key_code) = from_variant_name(&code) else {
+                    continue;
+                };
+                // The console reads `logical_key` for Enter and Backspace and
+                // `text` for everything else; nothing else reads either.
+                let logical_key = match (key_code, &text) {
+                    (KeyCode::Enter, _) => Key::Enter,
+                    (KeyCode::Backspace, _) => Key::Backspace,
+                    (_, Some(text)) => Key::Character(text.as_str().into()),
+                    (_, None) => Key::Unidentified(NativeKey::Unidentified),
+                };
+                keyboard_events.write(KeyboardInput {
+                    key_code,
+                    logical_key,
+                    state: button_state(pressed),
+                    text: text.map(|text| text.as_str().into()),
+                    repeat: false,
+                    window,
+                });
+            }
+            ReplayInput::Wheel { lines, x, y } => {
+                wheel_events.write(MouseWheel {
+                    unit: if lines {
+                        MouseScrollUnit::Line
+                    } else {
+                        MouseScrollUnit::Pixel
+                    },
+                    x,
+                    y,
+                    window,
+                });
+            }
+        }
+    }
+}
+
+// Runs before the input systems, so the injected messages are what
+// `ButtonInput<KeyCode>` / `ButtonInput<MouseButton>` see this frame.
+fn inject_inputs(
+    mut commands: Commands,
+    mut playback: ResMut<Playback>,
+    mut window_query: Query<(Entity, &mut Window), With<PrimaryWindow>>,
+    mut mouse_events: ResMut<Messages<MouseButtonInput>>,
+    mut keyboard_events: ResMut<Messages<KeyboardInput>>,
+    mut wheel_events: ResMut<Messages<MouseWheel>>,
+) {
+    let Some(frame) = playback.frames.pop_front() else {
+        println!("Replay finished");
+        commands.remove_resource::<Playback>();
+        commands.insert_resource(TimeUpdateStrategy::Automatic);
+        return;
+    };
+    // No window in the headless app; messages still need some entity.
+    let mut window = window_query.single_mut().ok();
+    let window_entity = window
+        .as_ref()
+        .map_or(Entity::PLACEHOLDER, |(entity, _)| *entity);
+    mouse_events.clear();
+    keyboard_events.clear();
+    wheel_events.clear();
+    write_inputs(
+        frame.inputs,
+        window_entity,
+        &mut playback.cursor,
+        &mut mouse_events,
+        &mut keyboard_events,
+        &mut wheel_events,
+    );
+    // Every frame, not just on change, so a stray real mouse move can't stick.
+    if let Some((_, window)) = window.as_mut() {
+        window.set_cursor_position(playback.cursor);
+    }
+}
+
+// Time advances in `First`, before `inject_inputs` pops the frame, so the
+// next frame's delta is queued at the end of this one.
+fn queue_next_delta(
+    mut time_update_strategy: ResMut<TimeUpdateStrategy>,
+    playback: Res<Playback>,
+) {
+    if let Some(frame) = playback.frames.front() {
+        *time_update_strategy = TimeUpdateStrategy::ManualDuration(
+            Duration::from_nanos(frame.delta_nanos),
+        );
+    }
+}
+
+fn run_single_threaded(app: &mut App) {
+    for schedule in [
+        First.intern(),
+        PreUpdate.intern(),
+        Update.intern(),
+        FixedUpdate.intern(),
+        PostUpdate.intern(),
+        Last.intern(),
+    ] {
+        app.edit_schedule(schedule, |schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        });
+    }
+}
+
+// Add after everything that inserts `Random`.
+pub struct ReplayPlugin {
+    pub mode: ReplayMode,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        match &self.mode {
+            ReplayMode::Off => {}
+            ReplayMode::Record(path) => {
+                let header = ReplayHeader {
+                    seed: app.world().resource::<Random>().seed(),
+                };
+                match Recorder::create(path, &header) {
+                    Ok(recorder) => {
+                        run_single_threaded(app);
+                        app.insert_resource(recorder)
+                            .add_systems(Last, record_inputs);
+                    }
+                    Err(error) => println!(
+                        "Error: can't record to {}: {}",
+                        path.display(),
+                        error
+                    ),
+                }
+            }
+            ReplayMode::Play(path) => match read_replay(path) {
+                Ok((header, frames)) => {
+                    let first_delta = frames
+                        .first()
+                        .map(|frame| Duration::from_nanos(frame.delta_nanos))
+                        .unwrap_or_default();
+                    run_single_threaded(app);
+                    app.insert_resource(Random::new(header.seed))
+                        .insert_resource(TimeUpdateStrategy::ManualDuration(
+                            first_delta,
+                        ))
+                        .insert_resource(Playback {
+                            frames: frames.into(),
+                            cursor: None,
+                        })
+                        .add_systems(
+                            PreUpdate,
+                            inject_inputs
+                                .before(InputSystems)
+                                .run_if(resource_exists::<Playback>),
+                        )
+                        .add_systems(
+                            Last,
+                            queue_next_delta
+                                .run_if(resource_exists::<Playback>),
+                        );
+                }
+                Err(error) => println!(
+                    "Error: can't replay {}: {}",
+                    path.display(),
+                    error
+                ),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mode_from_args() {
+        let args = |s: &str| s.split(' ').map(String::from).collect::<Vec<_>>();
+        assert_eq!(ReplayMode::from_args(args("galaxia")), ReplayMode::Off);
+        assert_eq!(
+            ReplayMode::from_args(args("galaxia --record run.replay")),
+            ReplayMode::Record("run.replay".into())
+        );
+        assert_eq!(
+            ReplayMode::from_args(args("galaxia --replay run.replay")),
+            ReplayMode::Play("run.replay".into())
+        );
+        assert_eq!(
+            ReplayMode::from_args(args("galaxia -v --replay run.replay")),
+            ReplayMode::Play("run.replay".into())
+        );
+        assert_eq!(
+            ReplayMode::from_args(args("galaxia --replay")),
+            ReplayMode::Off
+        );
+    }
+
+    #[test]
+    fn inputs_round_trip_by_name() {
+        assert_eq!(
+            variant_name(&KeyCode::Backquote).as_deref(),
+            Some("Backquote")
+        );
+        assert_eq!(from_variant_name("KeyW"), Some(KeyCode::KeyW));
+        assert_eq!(
+            from_variant_name::<MouseButton>("Left"),
+            Some(MouseButton::Left)
+        );
+        assert_eq!(variant_name(&MouseButton::Other(7)), None);
+        assert_eq!(from_variant_name::<KeyCode>("NotAKey"), None);
+        assert_eq!(from_variant_name::<KeyCode>("Unidentified"), None);
+
+        let frame = ReplayFrame {
+            frame: 3,
+            delta_nanos: 16_666_667,
+            inputs: vec![
+                ReplayInput::Cursor(Some((1.5, -2.0))),
+                ReplayInput::Key {
+                    code: "KeyA".into(),
+                    pressed: true,
+                    text: Some("a".into()),
+                },
+            ],
+        };
+        let line = serde_json::to_string(&frame).unwrap();
+        assert_eq!(serde_json::from_str::<ReplayFrame>(&line).unwrap(), frame);
+    }
+}
@@ -0,0 +1,549 @@
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+
+use base64::prelude::*;
+use bevy::prelude::*;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Progress as a string, for carrying it to another machine. F4 opens the
+// panel; while it's open, 1 exports and 2 imports. An export is shown in the
+// panel and written to `EXPORT_PATH` to copy from; an import reads whatever
+// was pasted into `IMPORT_PATH`. A save holds how many times each unlocked
+// minigame has leveled up (`MinigamesResource::level`, what unlocks go by),
+// what the chest holds, the notes on the board, the player's chosen look and
+// where minigames were moved to. Importing puts the chest's contents back,
+// replaces the notes and moves the minigames at once, and levels minigames up, one levelup at a time, until they've leveled up
+// as often as in the save; the ones they unlock come along the way. It never
+// takes levelups away. Nothing else a minigame holds is saved, so one whose
+// level comes from what it has collected starts collecting afresh.
+//
+// The string is base64 of a version byte, a CRC-32 of what follows
+// (big-endian), and then the save as deflated JSON. A string damaged in
+// transit, or from a newer version, is refused whole. One from an older
+// version is brought up to date on import by `MIGRATIONS`, one version at a
+// time, before it's read as a `SaveGame`.
+//
+// Changing what a `SaveGame` holds, or renaming a minigame id or an item uid,
+// means bumping `SAVE_VERSION`, adding the migration from the old version,
+// and adding a fixture for the old version to `save_fixtures/`.
+
+pub const SAVE_VERSION: u8 = 5;
+pub const EXPORT_PATH: &str = "saves/export.txt";
+pub const IMPORT_PATH: &str = "saves/import.txt";
+const SAVE_DIR: &str = "saves";
+// the version byte and the checksum
+const HEADER_LEN: usize = 5;
+
+// Takes a save's JSON from one version to the next.
+type Migration = fn(Value) -> Result<Value, String>;
+
+// `MIGRATIONS[i]` takes version `i + 1` to version `i + 2`.
+const MIGRATIONS: &[Migration] =
+    &[levels_to_levelups, add_notes, add_skin, add_positions];
+
+// 1 to 2: `levels` held levelup counts all along, and is now named for them.
+fn levels_to_levelups(mut save: Value) -> Result<Value, String> {
+    let object = save
+        .as_object_mut()
+        .ok_or_else(|| "the save isn't an object".to_string())?;
+    if let Some(levels) = object.remove("levels") {
+        object.insert("levelups".to_string(), levels);
+    }
+    Ok(save)
+}
+
+// 2 to 3: notes came along, and older boards had none.
+fn add_notes(mut save: Value) -> Result<Value, String> {
+    let object = save
+        .as_object_mut()
+        .ok_or_else(|| "the save isn't an object".to_string())?;
+    object
+        .entry("notes")
+        .or_insert_with(|| Value::Array(Vec::new()));
+    Ok(save)
+}
+
+// 3 to 4: the player's look came along, and older players were plain.
+fn add_skin(mut save: Value) -> Result<Value, String> {
+    let object = save
+        .as_object_mut()
+        .ok_or_else(|| "the save isn't an object".to_string())?;
+    object
+        .entry("skin")
+        .or_insert_with(|| Value::String(cosmetics::PLAIN.to_string()));
+    Ok(save)
+}
+
+// 4 to 5: minigames could be moved, and older ones were all at home.
+fn add_positions(mut save: Value) -> Result<Value, String> {
+    let object = save
+        .as_object_mut()
+        .ok_or_else(|| "the save isn't an object".to_string())?;
+    object
+        .entry("positions")
+        .or_insert_with(|| Value::Object(Default::default()));
+    Ok(save)
+}
+
+// Brings a save of version `version` up to `SAVE_VERSION`.
+fn migrate(mut save: Value, version: u8) -> Result<Value, String> {
+    if version == 0 || version > SAVE_VERSION {
+        return Err(format!(
+            "save version {} isn't one this game reads",
+            version
+        ));
+    }
+    for migration in &MIGRATIONS[usize::from(version - 1)..] {
+        save = migration(save)?;
+    }
+    Ok(save)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SaveGame {
+    // levelups of each unlocked minigame, by id
+    pub levelups: BTreeMap<String, u8>,
+    // the chest's contents, by item uid
+    pub chest: BTreeMap<String, f32>,
+    pub notes: Vec<SavedNote>,
+    // the chosen skin's id
+    pub skin: String,
+    // where moved minigames were moved to, by id
+    pub positions: BTreeMap<String, SavedPosition>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SavedPosition {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedNote {
+    pub x: f32,
+    pub y: f32,
+    pub icon: notes::NoteIcon,
+    pub text: String,
+}
+
+impl SaveGame {
+    // `unlocked` is every minigame there is to save.
+    pub fn capture<'a>(
+        minigames: &MinigamesResource,
+        unlocked: impl IntoIterator<Item = &'a Minigame>,
+        notes: impl IntoIterator<Item = (&'a notes::Note, &'a Transform)>,
+        cosmetics: &cosmetics::Cosmetics,
+    ) -> Self {
+        let mut save = Self {
+            skin: cosmetics.selected.clone(),
+            ..Self::default()
+        };
+        for minigame in unlocked {
+            let id = minigame.id();
+            save.levelups.insert(id.to_string(), minigames.level(id));
+            if let Minigame::Chest(chest) = minigame {
+                save.chest = chest
+                    .items
+                    .iter()
+                    .map(|(item_type, amount)| (item_type.uid(), *amount))
+                    .collect();
+            }
+        }
+        save.positions = minigames
+            .moved_positions()
+            .iter()
+            .map(|(id, position)| {
+                let (x, y) = (position.x, position.y);
+                (id.clone(), SavedPosition { x, y })
+            })
+            .collect();
+        save.notes = notes
+            .into_iter()
+            .map(|(note, transform)| SavedNote {
+                x: transform.translation.x,
+                y: transform.translation.y,
+                icon: note.icon,
+                text: note.text.clone(),
+            })
+            .collect();
+        save
+    }
+
+    pub fn export(&self) -> String {
+        let json = serde_json::to_vec(self).expect("a save always serializes");
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+        encoder
+            .write_all(&json)
+            .expect("compressing into memory can't fail");
+        let body = encoder
+            .finish()
+            .expect("compressing into memory can't fail");
+        let mut bytes = vec![SAVE_VERSION];
+        bytes.extend_from_slice(&crc32fast::hash(&body).to_be_bytes());
+        bytes.extend_from_slice(&body);
+        BASE64_STANDARD.encode(bytes)
+    }
+
+    // Whitespace is ignored, so a string that got wrapped when pasted still
+    // reads.
+    pub fn import(text: &str) -> Result<Self, String> {
+        let text: String = text.split_whitespace().collect();
+        let bytes = BASE64_STANDARD
+            .decode(text)
+            .map_err(|_| "not a save string".to_string())?;
+        if bytes.len() < HEADER_LEN {
+            return Err("the save string is too short".to_string());
+        }
+        let (header, body) = bytes.split_at(HEADER_LEN);
+        let checksum =
+            u32::from_be_bytes([header[1], header[2], header[3], header[4]]);
+        if crc32fast::hash(body) != checksum {
+            return Err("the save string is damaged".to_string());
+        }
+        let mut json = Vec::new();
+        DeflateDecoder::new(body)
+            .read_to_end(&mut json)
+            .map_err(|error| format!("can't decompress the save: {}", error))?;
+        let read_error = |error| format!("can't read the save: {}", error);
+        let save = serde_json::from_slice(&json).map_err(read_error)?;
+        serde_json::from_value(migrate(save, header[0])?).map_err(read_error)
+    }
+}
+
+// Levels an import is still leveling minigames up to, by minigame id.
+#[derive(Debug, Default, Resource)]
+pub struct LevelTargets(pub BTreeMap<String, u8>);
+
+// Refills the chest, puts the save's notes in place of the board's, puts the
+// player's look on, moves the minigames the save moved (see
+// `placement::move_to_placements`), and sets the levels to reach. Returns how many of the
+// chest's item types this game doesn't know, which are left out.
+pub fn apply_save(
+    save: SaveGame,
+    commands: &mut Commands,
+    targets: &mut LevelTargets,
+    minigames: &mut MinigamesResource,
+    minigame_query: &mut Query<&mut Minigame>,
+    note_query: &Query<Entity, With<notes::Note>>,
+    cosmetics: &mut cosmetics::Cosmetics,
+) -> usize {
+    let mut unknown = 0;
+    for mut minigame in minigame_query.iter_mut() {
+        let Minigame::Chest(chest) = minigame.as_mut() else {
+            continue;
+        };
+        chest.items = save
+            .chest
+            .iter()
+            .filter_map(|(uid, amount)| {
+                let item_type = ItemType::from_uid(uid);
+                if item_type.is_none() {
+                    unknown += 1;
+                }
+                Some((item_type?, *amount))
+            })
+            .collect();
+        if let Some(inventory) = chest.inventory {
+            mark_component_changed::<Inventory>(commands, inventory);
+        }
+    }
+    for note in note_query.iter() {
+        commands.entity(note).despawn();
+    }
+    for note in save.notes {
+        let position = Vec2::new(note.x, note.y);
+        let note = notes::Note {
+            icon: note.icon,
+            text: note.text,
+        };
+        notes::spawn_note(commands, note, position);
+    }
+    cosmetics.select(&save.skin);
+    for (id, position) in save.positions {
+        minigames.set_moved(&id, Vec2::new(position.x, position.y));
+    }
+    targets.0 = save.levelups;
+    unknown
+}
+
+// Levels up each minigame still short of its imported level, one levelup at
+// a time. A minigame that isn't unlocked yet waits until it is.
+pub fn pursue_level_targets(
+    mut commands: Commands,
+    mut targets: ResMut<LevelTargets>,
+    minigames: Res<MinigamesResource>,
+    leveling_up_query: Query<Has<LevelingUp>>,
+) {
+    if targets.0.is_empty() {
+        return;
+    }
+    targets.0.retain(|id, target| {
+        if minigames.level(id) >= *target {
+            return false;
+        }
+        let Some(entity) = minigames.entity(id) else {
+            return true;
+        };
+        if matches!(leveling_up_query.get(entity), Ok(false)) {
+            commands.entity(entity).insert(LevelingUp);
+        }
+        true
+    });
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct SavePanel {
+    pub open: bool,
+    // under the title: the last export, or how the last import went
+    pub message: String,
+}
+
+#[derive(Debug, Component)]
+pub struct SaveText;
+
+fn setup_save_panel(mut commands: Commands) {
+    commands.spawn((
+        SaveText,
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(25.0),
+            top: Val::Px(6.0),
+            max_width: Val::Percent(50.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.75)),
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        // the save string has no spaces to break at
+        TextLayout::new_with_linebreak(LineBreak::AnyCharacter),
+        Visibility::Hidden,
+    ));
+}
+
+fn write_export(text: &str) -> Result<(), GalaxiaError> {
+    storage::create_dir_all(SAVE_DIR)
+        .and_then(|_| {
+            let mut file = storage::create(EXPORT_PATH)?;
+            file.write_all(text.as_bytes())?;
+            file.flush()
+        })
+        .map_err(|source| GalaxiaError::Write {
+            path: EXPORT_PATH.to_string(),
+            source,
+        })
+}
+
+fn export_message(
+    minigames: &MinigamesResource,
+    minigame_query: &Query<&mut Minigame>,
+    note_query: &Query<(&notes::Note, &Transform)>,
+    cosmetics: &cosmetics::Cosmetics,
+) -> String {
+    let text = SaveGame::capture(
+        minigames,
+        minigame_query.iter(),
+        note_query.iter(),
+        cosmetics,
+    )
+    .export();
+    match write_export(&text) {
+        Ok(()) => {
+            tr_fmt("save.exported", &[("path", &EXPORT_PATH), ("save", &text)])
+        }
+        Err(error) => tr_fmt(
+            "save.export_unwritten",
+            &[("error", &error), ("save", &text)],
+        ),
+    }
+}
+
+fn save_input(
+    mut commands: Commands,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    mut panel: ResMut<SavePanel>,
+    mut targets: ResMut<LevelTargets>,
+    mut minigames: ResMut<MinigamesResource>,
+    mut minigame_query: Query<&mut Minigame>,
+    note_query: Query<(&notes::Note, &Transform)>,
+    note_entity_query: Query<Entity, With<notes::Note>>,
+    mut cosmetics: ResMut<cosmetics::Cosmetics>,
+) {
+    if kb_input.just_pressed(KeyCode::F4) {
+        panel.open = !panel.open;
+    }
+    if !panel.open {
+        return;
+    }
+    if kb_input.just_pressed(KeyCode::Digit1) {
+        panel.message = export_message(
+            &minigames,
+            &minigame_query,
+            &note_query,
+            &cosmetics,
+        );
+    }
+    if kb_input.just_pressed(KeyCode::Digit2) {
+        let save = storage::read_to_string(IMPORT_PATH)
+            .map_err(|error| format!("{}: {}", IMPORT_PATH, error))
+            .and_then(|text| SaveGame::import(&text));
+        panel.message = match save {
+            Ok(save) => {
+                let unknown = apply_save(
+                    save,
+                    &mut commands,
+                    &mut targets,
+                    &mut minigames,
+                    &mut minigame_query,
+                    &note_entity_query,
+                    &mut cosmetics,
+                );
+                tr_fmt("save.imported", &[("unknown", &unknown)])
+            }
+            Err(error) => tr_fmt("save.import_failed", &[("error", &error)]),
+        };
+    }
+}
+
+fn render_save_panel(
+    panel: Res<SavePanel>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<SaveText>>,
+) {
+    if !panel.is_changed() {
+        return;
+    }
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        *visibility = if panel.open {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        text.0 = [tr("save.title"), panel.message.clone()]
+            .join("\n")
+            .trim_end()
+            .to_string();
+    }
+}
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        // MinigamesPlugin owns LevelTargets, since leveling is its business
+        app.init_resource::<SavePanel>()
+            .add_systems(Startup, setup_save_panel)
+            .add_systems(
+                Update,
+                (
+                    save_input.run_if(console::console_closed),
+                    render_save_panel,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save() -> SaveGame {
+        SaveGame {
+            levelups: BTreeMap::from([
+                ("chest".to_string(), 3),
+                ("primordial_ocean".to_string(), 5),
+            ]),
+            chest: BTreeMap::from([("physical/Lump/Gold".to_string(), 2.5)]),
+            notes: Vec::new(),
+            skin: cosmetics::PLAIN.to_string(),
+            positions: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn saves_survive_the_round_trip() {
+        let text = save().export();
+        assert_eq!(SaveGame::import(&text), Ok(save()));
+        // wrapped across lines when pasted
+        let wrapped: String = text
+            .chars()
+            .enumerate()
+            .flat_map(|(i, c)| {
+                (i % 10 == 9).then_some('\n').into_iter().chain([c])
+            })
+            .collect();
+        assert_eq!(SaveGame::import(&wrapped), Ok(save()));
+
+        let mut noted = save();
+        noted.notes.push(SavedNote {
+            x: -120.0,
+            y: 48.5,
+            icon: notes::NoteIcon::Warning,
+            text: "TODO: build battery farm".to_string(),
+        });
+        assert_eq!(SaveGame::import(&noted.export()), Ok(noted));
+
+        let mut moved = save();
+        let position = SavedPosition { x: 640.0, y: -96.5 };
+        moved.positions.insert("quarry".to_string(), position);
+        assert_eq!(SaveGame::import(&moved.export()), Ok(moved));
+    }
+
+    #[test]
+    fn bad_strings_are_refused() {
+        let mut bytes = BASE64_STANDARD.decode(save().export()).unwrap();
+        assert!(SaveGame::import("not base64!").is_err());
+        assert!(SaveGame::import("AAAA").is_err(), "too short");
+
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        let damaged = SaveGame::import(&BASE64_STANDARD.encode(&bytes));
+        assert_eq!(damaged, Err("the save string is damaged".to_string()));
+
+        bytes[last] ^= 1;
+        bytes[0] = SAVE_VERSION + 1;
+        let newer = SaveGame::import(&BASE64_STANDARD.encode(&bytes));
+        assert!(newer.unwrap_err().contains("version"));
+        bytes[0] = 0;
+        let unversioned = SaveGame::import(&BASE64_STANDARD.encode(&bytes));
+        assert!(unversioned.unwrap_err().contains("version"));
+    }
+
+    #[test]
+    fn every_version_has_a_migration() {
+        assert_eq!(MIGRATIONS.len(), usize::from(SAVE_VERSION - 1));
+    }
+
+    // Each fixture is `save()` as that version exported it.
+    #[test]
+    fn old_saves_still_import() {
+        let fixtures = [
+            include_str!("save_fixtures/v1.txt"),
+            include_str!("save_fixtures/v2.txt"),
+            include_str!("save_fixtures/v3.txt"),
+            include_str!("save_fixtures/v4.txt"),
+        ];
+        assert_eq!(fixtures.len(), usize::from(SAVE_VERSION - 1));
+        for (version, fixture) in (1..).zip(fixtures) {
+            let bytes = BASE64_STANDARD.decode(fixture.trim()).unwrap();
+            assert_eq!(bytes[0], version);
+            assert_eq!(
+                SaveGame::import(fixture),
+                Ok(save()),
+                "version {}",
+                version
+            );
+        }
+    }
+}
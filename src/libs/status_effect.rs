@@ -0,0 +1,380 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use bevy::prelude::*;
+
+use crate::libs::*;
+
+// Buffs and debuffs: an entity with a `StatusEffect<T>` is under `T` for a
+// while, shown by a small icon over it. `T` says what it is (a ball breaker
+// power-up, a shelter shield, an obsidian burn) through the `Status` trait:
+// how a second dose stacks, how often it ticks, and its icon. Write an
+// `ApplyStatus<T>` to put one on. While it lasts it writes a `StatusTicked<T>`
+// every `T::TICK_SECONDS`, and a `StatusExpired<T>` when it wears off; what
+// those do is up to whoever reads them, as with `DelayedTrigger`. Each `T`
+// needs its own `StatusEffectPlugin::<T>`.
+
+const ICON_FONT_SIZE: f32 = 10.0;
+const ICON_GAP: f32 = 4.0;
+const ICON_SPACING: f32 = 14.0;
+
+// What applying a status to an entity already under it does.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Stacking {
+    // starts the duration over, unless more than that was left
+    Refresh,
+    // adds to what's left
+    Extend,
+    // adds a stack, up to `max`, and starts the duration over as `Refresh`
+    // does
+    Stack { max: u32 },
+}
+
+pub trait Status: Send + Sync + 'static {
+    const STACKING: Stacking;
+    // Seconds between ticks, for statuses that act while they last, like a
+    // burn hurting. None for ones that only have to be there, like a shield.
+    const TICK_SECONDS: Option<f32>;
+    // A character or two drawn over the entity.
+    const ICON: &'static str;
+    const COLOR: Color;
+    // Where the icon goes in the row over the entity, so several statuses on
+    // one entity don't cover each other.
+    const SLOT: u8;
+}
+
+#[derive(Debug, Component)]
+pub struct StatusEffect<T: Status> {
+    pub seconds_left: f32,
+    pub stacks: u32,
+    since_tick: f32,
+    marker: PhantomData<T>,
+}
+
+impl<T: Status> StatusEffect<T> {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            seconds_left: seconds,
+            stacks: 1,
+            since_tick: 0.0,
+            marker: PhantomData,
+        }
+    }
+
+    // Applies it again for `seconds`, as `T::STACKING` says.
+    pub fn reapply(&mut self, seconds: f32) {
+        match T::STACKING {
+            Stacking::Refresh => {
+                self.seconds_left = self.seconds_left.max(seconds);
+            }
+            Stacking::Extend => self.seconds_left += seconds,
+            Stacking::Stack { max } => {
+                self.stacks = (self.stacks + 1).min(max);
+                self.seconds_left = self.seconds_left.max(seconds);
+            }
+        }
+    }
+
+    // Runs it for `seconds`. Returns how many ticks fell in them.
+    pub fn advance(&mut self, seconds: f32) -> u32 {
+        let seconds = seconds.min(self.seconds_left);
+        self.seconds_left -= seconds;
+        let Some(tick_seconds) = T::TICK_SECONDS else {
+            return 0;
+        };
+        self.since_tick += seconds;
+        let ticks = (self.since_tick / tick_seconds) as u32;
+        self.since_tick -= ticks as f32 * tick_seconds;
+        ticks
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.seconds_left <= 0.0
+    }
+
+    pub fn icon_text(&self) -> String {
+        if self.stacks > 1 {
+            format!("{}{}", T::ICON, self.stacks)
+        } else {
+            T::ICON.to_string()
+        }
+    }
+}
+
+#[derive(Debug, Message)]
+pub struct ApplyStatus<T: Status> {
+    pub entity: Entity,
+    pub seconds: f32,
+    marker: PhantomData<T>,
+}
+
+impl<T: Status> ApplyStatus<T> {
+    pub fn new(entity: Entity, seconds: f32) -> Self {
+        Self {
+            entity,
+            seconds,
+            marker: PhantomData,
+        }
+    }
+}
+
+#[derive(Debug, Message)]
+pub struct StatusTicked<T: Status> {
+    pub entity: Entity,
+    pub stacks: u32,
+    marker: PhantomData<T>,
+}
+
+#[derive(Debug, Message)]
+pub struct StatusExpired<T: Status> {
+    pub entity: Entity,
+    marker: PhantomData<T>,
+}
+
+// The icon over an entity under `T`, a child of it.
+#[derive(Debug, Component)]
+pub struct StatusIcon<T: Status>(PhantomData<T>);
+
+pub fn apply_statuses<T: Status>(
+    mut commands: Commands,
+    mut applied: MessageReader<ApplyStatus<T>>,
+    mut query: Query<&mut StatusEffect<T>>,
+) {
+    // several doses for an entity not yet under it stack up here first
+    let mut added: HashMap<Entity, StatusEffect<T>> = HashMap::new();
+    for apply in applied.read() {
+        if let Ok(mut effect) = query.get_mut(apply.entity) {
+            effect.reapply(apply.seconds);
+        } else if let Some(effect) = added.get_mut(&apply.entity) {
+            effect.reapply(apply.seconds);
+        } else {
+            added.insert(apply.entity, StatusEffect::new(apply.seconds));
+        }
+    }
+    for (entity, effect) in added {
+        if let Ok(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.try_insert(effect);
+        }
+    }
+}
+
+pub fn tick_statuses<T: Status>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut StatusEffect<T>)>,
+    mut ticked: MessageWriter<StatusTicked<T>>,
+    mut expired: MessageWriter<StatusExpired<T>>,
+) {
+    for (entity, mut effect) in query.iter_mut() {
+        for _ in 0..effect.advance(time.delta_secs()) {
+            ticked.write(StatusTicked {
+                entity,
+                stacks: effect.stacks,
+                marker: PhantomData,
+            });
+        }
+        if effect.is_over() {
+            commands.entity(entity).remove::<StatusEffect<T>>();
+            expired.write(StatusExpired {
+                entity,
+                marker: PhantomData,
+            });
+        }
+    }
+}
+
+// Adds, rewrites and removes icons, and keeps them upright over entities
+// that turn.
+pub fn render_status_icons<T: Status>(
+    mut commands: Commands,
+    effect_query: Query<(
+        Entity,
+        &StatusEffect<T>,
+        &Transform,
+        Option<&CircularArea>,
+        Option<&Children>,
+    )>,
+    mut icon_query: Query<
+        (Entity, &ChildOf, &mut Text2d, &mut Transform),
+        (With<StatusIcon<T>>, Without<StatusEffect<T>>),
+    >,
+) {
+    for (icon_entity, child_of, _, _) in icon_query.iter() {
+        if !effect_query.contains(child_of.parent()) {
+            commands.entity(icon_entity).despawn();
+        }
+    }
+    for (entity, effect, transform, area, children) in effect_query.iter() {
+        let unturn = transform.rotation.inverse();
+        let above = area.map_or(0.0, |area| area.radius) + ICON_GAP;
+        let offset =
+            unturn * Vec3::new(T::SLOT as f32 * ICON_SPACING, above, 1.0);
+        let placed = Transform::from_translation(offset).with_rotation(unturn);
+        let text = effect.icon_text();
+        let icon = children.and_then(|children| {
+            children.iter().find(|child| icon_query.contains(*child))
+        });
+        let Some(icon) = icon else {
+            commands.entity(entity).with_child((
+                StatusIcon::<T>(PhantomData),
+                Text2d::new(text),
+                TextFont {
+                    font_size: ICON_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(T::COLOR),
+                placed,
+            ));
+            continue;
+        };
+        let Ok((_, _, mut icon_text, mut icon_transform)) =
+            icon_query.get_mut(icon)
+        else {
+            continue;
+        };
+        if icon_text.0 != text {
+            icon_text.0 = text;
+        }
+        if *icon_transform != placed {
+            *icon_transform = placed;
+        }
+    }
+}
+
+// Registers `T`: its messages, and the systems that apply, tick and draw it.
+pub struct StatusEffectPlugin<T: Status>(PhantomData<T>);
+
+impl<T: Status> Default for StatusEffectPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Status> Plugin for StatusEffectPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_message::<ApplyStatus<T>>()
+            .add_message::<StatusTicked<T>>()
+            .add_message::<StatusExpired<T>>()
+            .add_systems(
+                FixedUpdate,
+                (apply_statuses::<T>, tick_statuses::<T>).chain(),
+            )
+            .add_systems(Update, render_status_icons::<T>);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::message::Messages;
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[derive(Debug)]
+    struct Burn;
+
+    impl Status for Burn {
+        const STACKING: Stacking = Stacking::Stack { max: 3 };
+        const TICK_SECONDS: Option<f32> = Some(0.5);
+        const ICON: &'static str = "B";
+        const COLOR: Color = Color::srgb(1.0, 0.4, 0.0);
+        const SLOT: u8 = 0;
+    }
+
+    #[derive(Debug)]
+    struct Shield;
+
+    impl Status for Shield {
+        const STACKING: Stacking = Stacking::Extend;
+        const TICK_SECONDS: Option<f32> = None;
+        const ICON: &'static str = "S";
+        const COLOR: Color = Color::srgb(0.4, 0.7, 1.0);
+        const SLOT: u8 = 1;
+    }
+
+    #[test]
+    fn doses_stack_as_the_status_says() {
+        let mut burn = StatusEffect::<Burn>::new(2.0);
+        burn.reapply(1.0);
+        assert_eq!((burn.stacks, burn.seconds_left), (2, 2.0));
+        burn.reapply(3.0);
+        burn.reapply(3.0);
+        assert_eq!((burn.stacks, burn.seconds_left), (3, 3.0));
+        assert_eq!(burn.icon_text(), "B3");
+
+        let mut shield = StatusEffect::<Shield>::new(2.0);
+        shield.reapply(1.0);
+        assert_eq!((shield.stacks, shield.seconds_left), (1, 3.0));
+        assert_eq!(shield.icon_text(), "S");
+    }
+
+    #[test]
+    fn ticks_while_it_lasts() {
+        let mut burn = StatusEffect::<Burn>::new(1.2);
+        assert_eq!(burn.advance(0.3), 0);
+        assert_eq!(burn.advance(0.3), 1);
+        // only the time that's left counts
+        assert_eq!(burn.advance(5.0), 1);
+        assert!(burn.is_over());
+
+        let mut shield = StatusEffect::<Shield>::new(1.0);
+        assert_eq!(shield.advance(2.0), 0);
+        assert!(shield.is_over());
+    }
+
+    fn step(world: &mut World, seconds: f32) -> (u32, bool) {
+        world
+            .resource_mut::<Time>()
+            .advance_by(Duration::from_secs_f32(seconds));
+        world.run_system_once(apply_statuses::<Burn>).unwrap();
+        world.run_system_once(tick_statuses::<Burn>).unwrap();
+        world.run_system_once(render_status_icons::<Burn>).unwrap();
+        // each run is a new reader, so what it read mustn't be read again
+        world.resource_mut::<Messages<ApplyStatus<Burn>>>().clear();
+        let mut ticked = world.resource_mut::<Messages<StatusTicked<Burn>>>();
+        let stacks = ticked
+            .iter_current_update_messages()
+            .map(|t| t.stacks)
+            .sum();
+        ticked.clear();
+        let mut expired = world.resource_mut::<Messages<StatusExpired<Burn>>>();
+        let over = !expired.is_empty();
+        expired.clear();
+        (stacks, over)
+    }
+
+    #[test]
+    fn applied_statuses_tick_show_and_wear_off() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.init_resource::<Messages<ApplyStatus<Burn>>>();
+        world.init_resource::<Messages<StatusTicked<Burn>>>();
+        world.init_resource::<Messages<StatusExpired<Burn>>>();
+        let entity = world
+            .spawn((Transform::default(), CircularArea { radius: 10.0 }))
+            .id();
+        let icons = |world: &mut World| {
+            world
+                .query_filtered::<&Text2d, With<StatusIcon<Burn>>>()
+                .iter(world)
+                .map(|text| text.0.clone())
+                .collect::<Vec<_>>()
+        };
+
+        // two doses at once make two stacks
+        world.write_message(ApplyStatus::<Burn>::new(entity, 1.0));
+        world.write_message(ApplyStatus::<Burn>::new(entity, 1.0));
+        assert_eq!(step(&mut world, 0.0), (0, false));
+        assert_eq!(world.get::<StatusEffect<Burn>>(entity).unwrap().stacks, 2);
+        assert_eq!(icons(&mut world), vec!["B2"]);
+
+        // each tick carries the stacks
+        assert_eq!(step(&mut world, 0.5), (2, false));
+        assert_eq!(step(&mut world, 0.6), (2, true));
+        assert!(world.get::<StatusEffect<Burn>>(entity).is_none());
+        step(&mut world, 0.0);
+        assert!(icons(&mut world).is_empty());
+    }
+}
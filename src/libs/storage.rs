@@ -0,0 +1,184 @@
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::time::SystemTime;
+
+// Where the game reads and writes its own files. Natively that's the
+// filesystem, relative to the working directory. A browser has none: there
+// the data files the game reads (balance, palettes, locales) are compiled in
+// as they were at build time, and files it writes (replays) go to the page's
+// localStorage, one key per path. Pictures don't come through here; they load
+// through Bevy's asset server, which fetches them over HTTP on the web.
+
+pub use backend::{
+    create, create_dir_all, modified, open, read_to_string, remove_file,
+};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod backend {
+    use std::fs::File;
+    use std::io::BufReader;
+
+    use super::*;
+
+    pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    pub fn modified(path: impl AsRef<Path>) -> io::Result<SystemTime> {
+        std::fs::metadata(path)?.modified()
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Box<dyn BufRead>> {
+        Ok(Box::new(BufReader::new(File::open(path)?)))
+    }
+
+    pub fn create(
+        path: impl AsRef<Path>,
+    ) -> io::Result<Box<dyn Write + Send + Sync>> {
+        Ok(Box::new(File::create(path)?))
+    }
+
+    pub fn create_dir_all(path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    pub fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::remove_file(path)
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod backend {
+    use wasm_bindgen::prelude::*;
+
+    use super::*;
+
+    // Both throw when the page may not use storage, and setItem when it's
+    // full.
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(catch, js_namespace = localStorage, js_name = getItem)]
+        fn get_item(key: &str) -> Result<Option<String>, JsValue>;
+        #[wasm_bindgen(catch, js_namespace = localStorage, js_name = setItem)]
+        fn set_item(key: &str, value: &str) -> Result<(), JsValue>;
+        #[wasm_bindgen(catch, js_namespace = localStorage, js_name = removeItem)]
+        fn remove_item(key: &str) -> Result<(), JsValue>;
+    }
+
+    const BUNDLED: &[(&str, &str)] = &[
+        (
+            "assets/balance.ron",
+            include_str!("../../assets/balance.ron"),
+        ),
+        (
+            "assets/palettes.ron",
+            include_str!("../../assets/palettes.ron"),
+        ),
+        (
+            "assets/palettes-colorblind.ron",
+            include_str!("../../assets/palettes-colorblind.ron"),
+        ),
+        (
+            "assets/locales/en.ron",
+            include_str!("../../assets/locales/en.ron"),
+        ),
+        (
+            "assets/locales/es.ron",
+            include_str!("../../assets/locales/es.ron"),
+        ),
+    ];
+
+    fn key(path: impl AsRef<Path>) -> String {
+        path.as_ref().to_string_lossy().into_owned()
+    }
+
+    fn stored(key: &str) -> io::Result<String> {
+        get_item(key)
+            .map_err(|_| io::Error::other("localStorage is unavailable"))?
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))
+    }
+
+    pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+        let key = key(path);
+        match BUNDLED.iter().find(|(bundled, _)| *bundled == key) {
+            Some((_, contents)) => Ok(contents.to_string()),
+            None => stored(&key),
+        }
+    }
+
+    // Nothing here changes behind the game's back, so every file that
+    // exists has the same timestamp and is read once.
+    pub fn modified(path: impl AsRef<Path>) -> io::Result<SystemTime> {
+        read_to_string(path).map(|_| SystemTime::UNIX_EPOCH)
+    }
+
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Box<dyn BufRead>> {
+        let contents = read_to_string(path)?;
+        Ok(Box::new(io::Cursor::new(contents.into_bytes())))
+    }
+
+    pub fn create(
+        path: impl AsRef<Path>,
+    ) -> io::Result<Box<dyn Write + Send + Sync>> {
+        let file = StoredFile {
+            key: key(path),
+            contents: Vec::new(),
+        };
+        set_item(&file.key, "")
+            .map_err(|_| io::Error::other("localStorage is unavailable"))?;
+        Ok(Box::new(file))
+    }
+
+    // Keys are whole paths, so there are no directories to make.
+    pub fn create_dir_all(_path: impl AsRef<Path>) -> io::Result<()> {
+        Ok(())
+    }
+
+    pub fn remove_file(path: impl AsRef<Path>) -> io::Result<()> {
+        let key = key(path);
+        stored(&key)?;
+        remove_item(&key)
+            .map_err(|_| io::Error::other("localStorage is unavailable"))
+    }
+
+    // Buffers everything written, and stores the lot on each flush.
+    struct StoredFile {
+        key: String,
+        contents: Vec<u8>,
+    }
+
+    impl Write for StoredFile {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.contents.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            set_item(&self.key, &String::from_utf8_lossy(&self.contents))
+                .map_err(|_| io::Error::other("localStorage is full"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn written_files_read_back() {
+        let path = std::env::temp_dir().join("galaxia-storage-test.txt");
+        let mut file = create(&path).unwrap();
+        writeln!(file, "first").unwrap();
+        writeln!(file, "second").unwrap();
+        file.flush().unwrap();
+        drop(file);
+
+        assert_eq!(read_to_string(&path).unwrap(), "first\nsecond\n");
+        assert!(modified(&path).is_ok());
+        let lines: Vec<String> =
+            open(&path).unwrap().lines().map(Result::unwrap).collect();
+        assert_eq!(lines, ["first", "second"]);
+        remove_file(&path).unwrap();
+        assert!(read_to_string(&path).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use bevy::camera::RenderTarget;
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Thumbnails of the minigames, for views that show a minigame smaller than it
+// is. Each minigame gets a camera of its own, a child of it, rendering its
+// play area into a `THUMBNAIL_SIZE` square image kept in `MinigameThumbnails`
+// by id, so the image outlives levelups. Rendering every one every frame
+// would double the frame's cost, so the cameras stay off, and every
+// `CAPTURE_SECONDS` the next minigame's, in order of id, renders one frame.
+// The minimap shows them when a minigame's mark is hovered (see minimap.rs).
+
+pub const THUMBNAIL_SIZE: u32 = 96;
+const CAPTURE_SECONDS: f32 = 0.5;
+// before the main camera, which renders at 0
+const CAMERA_ORDER: isize = -1;
+
+#[derive(Debug, Default, Resource)]
+pub struct MinigameThumbnails {
+    pub images: HashMap<&'static str, Handle<Image>>,
+    // captured last
+    pub last: Option<&'static str>,
+}
+
+impl MinigameThumbnails {
+    pub fn get(&self, id: &str) -> Option<&Handle<Image>> {
+        self.images.get(id)
+    }
+}
+
+// The id after `last`, in order, wrapping round; the first without one.
+pub fn next_capture(
+    ids: &[&'static str],
+    last: Option<&str>,
+) -> Option<&'static str> {
+    let mut sorted = ids.to_vec();
+    sorted.sort_unstable();
+    last.and_then(|last| sorted.iter().find(|id| **id > last))
+        .or(sorted.first())
+        .copied()
+}
+
+// A minigame's own camera. Queries for the main camera ask for `MainCamera`,
+// which these don't have.
+#[derive(Debug, Component)]
+pub struct ThumbnailCamera {
+    pub minigame: Entity,
+}
+
+fn add_thumbnail_cameras(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut thumbnails: ResMut<MinigameThumbnails>,
+    minigame_query: Query<
+        (Entity, &Minigame, &RectangularArea),
+        Added<Minigame>,
+    >,
+) {
+    for (entity, minigame, area) in minigame_query.iter() {
+        let image = thumbnails
+            .images
+            .entry(minigame.id())
+            .or_insert_with(|| {
+                images.add(Image::new_target_texture(
+                    THUMBNAIL_SIZE,
+                    THUMBNAIL_SIZE,
+                    TEXTURE_FORMAT,
+                    None,
+                ))
+            })
+            .clone();
+        commands.entity(entity).with_child((
+            ThumbnailCamera { minigame: entity },
+            Camera2d,
+            Camera {
+                is_active: false,
+                order: CAMERA_ORDER,
+                ..default()
+            },
+            RenderTarget::Image(image.into()),
+            // the whole play area, whichever side is longer
+            Projection::Orthographic(OrthographicProjection {
+                scale: area.width.max(area.height) / THUMBNAIL_SIZE as f32,
+                ..OrthographicProjection::default_2d()
+            }),
+        ));
+    }
+}
+
+fn capture_thumbnails(
+    time: Res<Time<Real>>,
+    mut due: Local<f32>,
+    mut thumbnails: ResMut<MinigameThumbnails>,
+    minigame_query: Query<&Minigame>,
+    mut camera_query: Query<(&ThumbnailCamera, &mut Camera)>,
+) {
+    // last frame's capture is done
+    for (_, mut camera) in camera_query.iter_mut() {
+        if camera.is_active {
+            camera.is_active = false;
+        }
+    }
+    *due -= time.delta_secs();
+    if *due > 0.0 {
+        return;
+    }
+    *due = CAPTURE_SECONDS;
+    let ids: Vec<&'static str> = camera_query
+        .iter()
+        .filter_map(|(thumbnail, _)| {
+            minigame_query
+                .get(thumbnail.minigame)
+                .ok()
+                .map(Minigame::id)
+        })
+        .collect();
+    let Some(next) = next_capture(&ids, thumbnails.last) else {
+        return;
+    };
+    thumbnails.last = Some(next);
+    for (thumbnail, mut camera) in camera_query.iter_mut() {
+        if minigame_query
+            .get(thumbnail.minigame)
+            .is_ok_and(|minigame| minigame.id() == next)
+        {
+            camera.is_active = true;
+        }
+    }
+}
+
+// For the windowed game: the headless app renders nothing.
+pub struct ThumbnailPlugin;
+
+impl Plugin for ThumbnailPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MinigameThumbnails>().add_systems(
+            Update,
+            (add_thumbnail_cameras, capture_thumbnails)
+                .chain()
+                .after(GameSet::Minigames),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn captures_go_round_in_order_of_id() {
+        let ids = ["tree", "button", "quarry"];
+        assert_eq!(next_capture(&ids, None), Some("button"));
+        assert_eq!(next_capture(&ids, Some("button")), Some("quarry"));
+        assert_eq!(next_capture(&ids, Some("quarry")), Some("tree"));
+        assert_eq!(next_capture(&ids, Some("tree")), Some("button"));
+        // one that's gone since is passed over
+        assert_eq!(next_capture(&ids, Some("rune")), Some("tree"));
+        assert_eq!(next_capture(&[], Some("tree")), None);
+    }
+}
@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::libs::*;
+
+// How fast the simulation runs, from half speed to four times. The speed is
+// the virtual clock's relative speed, so minigames and physics speed up
+// together: FixedUpdate runs more steps a frame, each still one fixed step of
+// game time, and Rapier steps further each frame. The camera, the player's
+// controls and the interface read `Time<Real>` and stay in real time. `[` and
+// `]` step the speed, `\` resets it, and the buttons at the top of the screen
+// pick one.
+
+pub const SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+// FixedUpdate's rate in game time; at 2x it runs twice as often in real time
+pub const BASE_FIXED_HZ: f64 = 20.0;
+// Rapier's default longest step; at higher speeds a frame's step is split
+// into substeps no longer than this, so fast bodies don't tunnel
+const BASE_MAX_DT: f32 = 1.0 / 60.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Resource)]
+pub struct TimeScale(pub f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl TimeScale {
+    // The next speed up (`steps` > 0) or down, stopping at either end.
+    pub fn step(&mut self, steps: i32) {
+        let index = SPEEDS
+            .iter()
+            .position(|&speed| speed == self.0)
+            .unwrap_or(1) as i32;
+        let index = (index + steps).clamp(0, SPEEDS.len() as i32 - 1);
+        self.0 = SPEEDS[index as usize];
+    }
+
+    pub fn timestep_mode(&self) -> TimestepMode {
+        TimestepMode::Variable {
+            max_dt: BASE_MAX_DT * self.0,
+            // the virtual clock's delta is already scaled
+            time_scale: 1.0,
+            substeps: self.0.ceil().max(1.0) as usize,
+        }
+    }
+
+    pub fn label(&self) -> String {
+        format!("{}x", self.0)
+    }
+}
+
+pub fn apply_time_scale(
+    time_scale: Res<TimeScale>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut timestep_mode: ResMut<TimestepMode>,
+) {
+    if !time_scale.is_changed() {
+        return;
+    }
+    virtual_time.set_relative_speed(time_scale.0);
+    *timestep_mode = time_scale.timestep_mode();
+}
+
+// One of the speed buttons.
+#[derive(Debug, Component)]
+pub struct SpeedButton(pub f32);
+
+const SELECTED_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.35);
+const UNSELECTED_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+
+fn setup_speed_buttons(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(6.0),
+            left: Val::Percent(50.0),
+            column_gap: Val::Px(4.0),
+            ..default()
+        })
+        .with_children(|parent| {
+            for speed in SPEEDS {
+                parent.spawn((
+                    SpeedButton(speed),
+                    Button,
+                    Node {
+                        padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+                        ..default()
+                    },
+                    BackgroundColor(UNSELECTED_COLOR),
+                    Text::new(TimeScale(speed).label()),
+                    TextFont {
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+            }
+        });
+}
+
+fn time_scale_input(
+    kb_input: Res<ButtonInput<KeyCode>>,
+    button_query: Query<(&SpeedButton, &Interaction), Changed<Interaction>>,
+    mut time_scale: ResMut<TimeScale>,
+) {
+    if kb_input.just_pressed(KeyCode::BracketLeft) {
+        time_scale.step(-1);
+    }
+    if kb_input.just_pressed(KeyCode::BracketRight) {
+        time_scale.step(1);
+    }
+    if kb_input.just_pressed(KeyCode::Backslash) {
+        *time_scale = TimeScale::default();
+    }
+    for (button, interaction) in button_query.iter() {
+        if *interaction == Interaction::Pressed {
+            time_scale.0 = button.0;
+        }
+    }
+}
+
+fn render_speed_buttons(
+    time_scale: Res<TimeScale>,
+    mut button_query: Query<(&SpeedButton, &mut BackgroundColor)>,
+) {
+    if !time_scale.is_changed() {
+        return;
+    }
+    for (button, mut background) in button_query.iter_mut() {
+        background.0 = if button.0 == time_scale.0 {
+            SELECTED_COLOR
+        } else {
+            UNSELECTED_COLOR
+        };
+    }
+}
+
+pub struct TimeScalePlugin;
+
+impl Plugin for TimeScalePlugin {
+    fn build(&self, app: &mut App) {
+        // GamePlugin owns TimeScale and applies it, so the headless app runs
+        // at whatever speed it's set to
+        app.add_systems(Startup, setup_speed_buttons).add_systems(
+            Update,
+            (
+                time_scale_input.run_if(console::console_closed),
+                render_speed_buttons,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speed_steps_stop_at_either_end() {
+        let mut time_scale = TimeScale::default();
+        time_scale.step(1);
+        assert_eq!(time_scale.0, 2.0);
+        time_scale.step(5);
+        assert_eq!(time_scale.0, 4.0);
+        time_scale.step(-5);
+        assert_eq!(time_scale.0, 0.5);
+        assert_eq!(time_scale.label(), "0.5x");
+    }
+
+    #[test]
+    fn physics_substeps_never_exceed_the_base_step() {
+        for speed in SPEEDS {
+            let TimestepMode::Variable {
+                max_dt, substeps, ..
+            } = TimeScale(speed).timestep_mode()
+            else {
+                panic!("expected a variable timestep");
+            };
+            assert!(max_dt / substeps as f32 <= BASE_MAX_DT);
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::entities::*;
+use crate::libs::*;
+
+// Board undo. Ctrl+Z takes back the last of the board-level actions a slip
+// of the mouse can do: pulling up a note, ejecting a whole stack from a
+// minigame's inventory with a long click, unlinking a route, and moving a
+// minigame. Each is pushed onto `UndoStack` as it happens, with what it takes
+// to put things back, and only the last `UNDO_DEPTH` are kept. An ejected
+// stack only goes back while its item is still whole in the world; once it's
+// been merged into another or fed to a minigame there's nothing to take back,
+// and the event log says so. A moved minigame only goes back if it would keep
+// clear of the others there, as when it was put down. While Land or the rune
+// is engaged, Ctrl+Z undoes their strokes instead.
+
+pub const UNDO_DEPTH: usize = 20;
+// minigames whose own Ctrl+Z goes first while they're engaged
+const OWN_UNDO: [&str; 2] = [minigames::land::ID, minigames::rune::ID];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UndoAction {
+    NoteDeleted {
+        note: notes::Note,
+        position: Vec2,
+    },
+    // `item` is the ejected stack, loose in the world
+    StackEjected {
+        minigame: &'static str,
+        item: Entity,
+    },
+    Unlinked {
+        from: &'static str,
+        to: &'static str,
+    },
+    // `from` is where it was before
+    Moved {
+        minigame: &'static str,
+        from: Vec2,
+    },
+}
+
+impl UndoAction {
+    // The locale key of what it did.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::NoteDeleted { .. } => "undo.note_deleted",
+            Self::StackEjected { .. } => "undo.stack_ejected",
+            Self::Unlinked { .. } => "undo.unlinked",
+            Self::Moved { .. } => "undo.moved",
+        }
+    }
+}
+
+#[derive(Debug, Default, Resource)]
+pub struct UndoStack {
+    // oldest first
+    actions: VecDeque<UndoAction>,
+}
+
+impl UndoStack {
+    pub fn push(&mut self, action: UndoAction) {
+        self.actions.push_back(action);
+        if self.actions.len() > UNDO_DEPTH {
+            self.actions.pop_front();
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<UndoAction> {
+        self.actions.pop_back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+// Puts the ejected stack `item` back into `minigame`. Whether it was still
+// there to put back.
+fn take_back(
+    commands: &mut Commands,
+    minigame: &'static str,
+    item: Entity,
+    minigames: &MinigamesResource,
+    audit: &mut AmountAudit,
+    item_query: &Query<&Item>,
+    minigame_query: &mut Query<(
+        Entity,
+        &mut Minigame,
+        &Transform,
+        Has<LevelingUp>,
+    )>,
+    inventory_query: &Query<(Entity, &Inventory)>,
+) -> bool {
+    let Ok(&Item { r#type, amount, .. }) = item_query.get(item) else {
+        return false;
+    };
+    let Some(entity) = minigames.entity(minigame) else {
+        return false;
+    };
+    let Ok((_, mut minigame, _, false)) = minigame_query.get_mut(entity) else {
+        return false;
+    };
+    let Some(items) = minigame.items_mut() else {
+        return false;
+    };
+    *items.entry(r#type).or_insert(0.0) += amount;
+    // back across the loose-item boundary, as if ingested
+    audit.ingested += amount;
+    commands.entity(item).despawn();
+    for (inventory_entity, inventory) in inventory_query.iter() {
+        if inventory.owner == entity {
+            mark_component_changed::<Inventory>(commands, inventory_entity);
+        }
+    }
+    true
+}
+
+// Moves `minigame` back to `from` if it would keep clear of the others there.
+// Whether it went back.
+fn move_back(
+    minigame: &'static str,
+    from: Vec2,
+    minigames: &mut MinigamesResource,
+    minigame_query: &Query<(
+        Entity,
+        &mut Minigame,
+        &Transform,
+        Has<LevelingUp>,
+    )>,
+) -> bool {
+    let Some(entity) = minigames.entity(minigame) else {
+        return false;
+    };
+    let Ok((_, moved, _, false)) = minigame_query.get(entity) else {
+        return false;
+    };
+    let others = placement::placed_areas(
+        minigame_query
+            .iter()
+            .map(|(entity, other, transform, _)| (entity, other, transform)),
+        entity,
+    );
+    if !moved.placement_clear(from, &others) {
+        return false;
+    }
+    // `placement::move_to_placements` moves it
+    minigames.set_moved(minigame, from);
+    true
+}
+
+pub fn undo_board_action(
+    mut commands: Commands,
+    kb_input: Res<ButtonInput<KeyCode>>,
+    engaged: Res<Engaged>,
+    mut minigames: ResMut<MinigamesResource>,
+    mut undo: ResMut<UndoStack>,
+    mut routes: ResMut<routing::Routes>,
+    mut audit: ResMut<AmountAudit>,
+    mut game_events: MessageWriter<GameEvent>,
+    item_query: Query<&Item>,
+    mut minigame_query: Query<(
+        Entity,
+        &mut Minigame,
+        &Transform,
+        Has<LevelingUp>,
+    )>,
+    inventory_query: Query<(Entity, &Inventory)>,
+) {
+    if !undo_pressed(&kb_input)
+        || engaged.game.is_some_and(|id| OWN_UNDO.contains(&id))
+    {
+        return;
+    }
+    let Some(action) = undo.pop() else {
+        return;
+    };
+    let name = tr(action.name());
+    let undone = match action {
+        UndoAction::NoteDeleted { note, position } => {
+            notes::spawn_note(&mut commands, note, position);
+            true
+        }
+        UndoAction::StackEjected { minigame, item } => take_back(
+            &mut commands,
+            minigame,
+            item,
+            &minigames,
+            &mut audit,
+            &item_query,
+            &mut minigame_query,
+            &inventory_query,
+        ),
+        UndoAction::Unlinked { from, to } => {
+            minigames.is_unlocked(from)
+                && minigames.is_unlocked(to)
+                && routes.link(from, to)
+        }
+        UndoAction::Moved { minigame, from } => {
+            move_back(minigame, from, &mut minigames, &minigame_query)
+        }
+    };
+    game_events.write(if undone {
+        GameEvent::Undid { action: name }
+    } else {
+        GameEvent::CouldNotUndo { action: name }
+    });
+}
+
+pub struct UndoPlugin;
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UndoStack>().add_systems(
+            Update,
+            undo_board_action
+                .run_if(console::console_closed)
+                .run_if(photo_mode::photo_mode_off)
+                .after(GameSet::Input)
+                .before(GameSet::Minigames),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_stack_keeps_only_the_latest() {
+        let mut undo = UndoStack::default();
+        assert_eq!(undo.pop(), None);
+        for i in 0..UNDO_DEPTH + 5 {
+            undo.push(UndoAction::NoteDeleted {
+                note: notes::Note {
+                    icon: notes::NoteIcon::Info,
+                    text: i.to_string(),
+                },
+                position: Vec2::ZERO,
+            });
+        }
+        assert_eq!(undo.len(), UNDO_DEPTH);
+        let Some(UndoAction::NoteDeleted { note, .. }) = undo.pop() else {
+            panic!("nothing to undo");
+        };
+        assert_eq!(note.text, (UNDO_DEPTH + 4).to_string(), "newest first");
+    }
+}
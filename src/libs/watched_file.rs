@@ -0,0 +1,85 @@
+use std::time::SystemTime;
+
+use crate::libs::*;
+
+// A data file under assets/ that the game re-reads when it changes on disk,
+// so it can be tuned while the game runs. Its owner calls `tick` every frame
+// and, when that says it's time, `read_if_changed`. Reads go through
+// `storage`, so in a browser it's the bundled copy, read once.
+
+// How often to look at the file for changes.
+const WATCH_SECONDS: f32 = 1.0;
+
+#[derive(Debug)]
+pub struct WatchedFile {
+    pub path: String,
+    // when the file was last read; None until it has been
+    modified: Option<SystemTime>,
+    // the file couldn't be found last time, and that's been reported
+    missing: bool,
+    // seconds until the file is looked at again
+    cooldown: f32,
+}
+
+impl WatchedFile {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            modified: None,
+            missing: false,
+            cooldown: 0.0,
+        }
+    }
+
+    // Whether it's time to look at the file again.
+    pub fn tick(&mut self, seconds: f32) -> bool {
+        self.cooldown -= seconds;
+        if self.cooldown > 0.0 {
+            return false;
+        }
+        self.cooldown = WATCH_SECONDS;
+        true
+    }
+
+    // The file's contents, if it changed since it was last read. A file that
+    // fails to parse isn't read again until it changes again, and a missing
+    // one is only reported once until it comes back.
+    pub fn read_if_changed(&mut self) -> Result<Option<String>, GalaxiaError> {
+        let io_error = |source| GalaxiaError::Io {
+            path: self.path.clone(),
+            source,
+        };
+        let modified = match storage::modified(&self.path) {
+            Ok(modified) => modified,
+            Err(_) if self.missing => return Ok(None),
+            Err(error) => {
+                self.missing = true;
+                return Err(io_error(error));
+            }
+        };
+        self.missing = false;
+        if self.modified == Some(modified) {
+            return Ok(None);
+        }
+        let contents = storage::read_to_string(&self.path).map_err(io_error)?;
+        self.modified = Some(modified);
+        Ok(Some(contents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_file_is_reported_once() {
+        let path = std::env::temp_dir().join("galaxia-watched-file-test");
+        let mut file = WatchedFile::new(path.to_str().unwrap());
+        assert!(file.read_if_changed().is_err());
+        assert!(file.read_if_changed().unwrap().is_none());
+
+        std::fs::write(&path, "back").unwrap();
+        assert_eq!(file.read_if_changed().unwrap().unwrap(), "back");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+
+use crate::libs::*;
+
+// The world's shared sense of time: a day/night cycle and the odd rain shower.
+// Minigames that care read it rather than keeping timers of their own. The
+// weather station can bring rain on early and hold the sun where it is for a
+// while.
+
+// One full day, midnight to midnight.
+pub const DAY_SECONDS: f32 = 240.0;
+// Chance per fixed tick of rain starting, as 1 in this many. At 20 ticks a
+// second, about once every two minutes.
+const RAIN_ODDS: u64 = 2400;
+const RAIN_MIN_SECONDS: f32 = 20.0;
+const RAIN_MAX_SECONDS: f32 = 60.0;
+
+#[derive(Debug, Clone, Resource)]
+pub struct WorldClock {
+    // seconds since the world began, starting at dawn of day 0
+    pub elapsed: f32,
+    // seconds of rain remaining; 0 when dry
+    pub rain_left: f32,
+    // seconds the sun stands still for; time of day only moves on once
+    // they're spent, and only by day
+    pub held_left: f32,
+}
+
+impl Default for WorldClock {
+    fn default() -> Self {
+        Self {
+            elapsed: DAY_SECONDS / 4.0,
+            rain_left: 0.0,
+            held_left: 0.0,
+        }
+    }
+}
+
+impl WorldClock {
+    pub fn day(&self) -> u32 {
+        (self.elapsed / DAY_SECONDS) as u32
+    }
+
+    // 0 and 1 are midnight, 0.5 is noon.
+    pub fn time_of_day(&self) -> f32 {
+        (self.elapsed % DAY_SECONDS) / DAY_SECONDS
+    }
+
+    pub fn is_day(&self) -> bool {
+        (0.25..0.75).contains(&self.time_of_day())
+    }
+
+    // How strong the sun is: 0 all night, rising to 1 at noon.
+    pub fn sunlight(&self) -> f32 {
+        let angle = (self.time_of_day() - 0.25) * std::f32::consts::TAU;
+        angle.sin().max(0.0)
+    }
+
+    // Seconds until dusk by day, or until dawn by night.
+    pub fn until_turn(&self) -> f32 {
+        let turn = if self.is_day() { 0.75 } else { 0.25 };
+        (turn - self.time_of_day()).rem_euclid(1.0) * DAY_SECONDS
+    }
+
+    pub fn is_raining(&self) -> bool {
+        self.rain_left > 0.0
+    }
+
+    pub fn is_held(&self) -> bool {
+        self.held_left > 0.0 && self.is_day()
+    }
+
+    // Rain for at least `seconds` from now.
+    pub fn bring_rain(&mut self, seconds: f32) {
+        self.rain_left = self.rain_left.max(seconds);
+    }
+
+    // Stop the sun for `seconds` more. Only by day: there's no holding the
+    // night.
+    pub fn hold_daylight(&mut self, seconds: f32) -> bool {
+        if !self.is_day() {
+            return false;
+        }
+        self.held_left += seconds;
+        true
+    }
+
+    // The tide comes in with the night.
+    pub fn is_high_tide(&self) -> bool {
+        !self.is_day()
+    }
+
+    // How much faster things grow right now.
+    pub fn growth_rate(&self) -> f32 {
+        if self.is_raining() {
+            2.0
+        } else {
+            1.0
+        }
+    }
+
+    // Multiplier on what the ocean gives up.
+    pub fn tide_yield(&self) -> f32 {
+        if self.is_high_tide() {
+            2.0
+        } else {
+            1.0
+        }
+    }
+}
+
+pub fn advance_world_clock(
+    time: Res<Time>,
+    mut clock: ResMut<WorldClock>,
+    mut random: ResMut<Random>,
+) {
+    let delta = time.delta_secs();
+    if clock.is_held() {
+        clock.held_left = (clock.held_left - delta).max(0.0);
+    } else {
+        clock.elapsed += delta;
+    }
+    if clock.is_raining() {
+        clock.rain_left = (clock.rain_left - delta).max(0.0);
+    } else if random.next().is_multiple_of(RAIN_ODDS) {
+        let range = (RAIN_MAX_SECONDS - RAIN_MIN_SECONDS) as u64;
+        clock.rain_left = RAIN_MIN_SECONDS + random.next_range(range) as f32;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time_of_day: f32) -> WorldClock {
+        WorldClock {
+            elapsed: DAY_SECONDS * (3.0 + time_of_day),
+            ..default()
+        }
+    }
+
+    #[test]
+    fn sun_shines_only_by_day() {
+        assert_eq!(at(0.5).sunlight(), 1.0);
+        assert!(at(0.3).sunlight() > 0.0);
+        assert_eq!(at(0.0).sunlight(), 0.0);
+        assert_eq!(at(0.9).sunlight(), 0.0);
+        assert_eq!(at(0.5).day(), 3);
+    }
+
+    #[test]
+    fn tide_is_high_at_night() {
+        assert!(at(0.1).is_high_tide());
+        assert!(!at(0.5).is_high_tide());
+    }
+
+    #[test]
+    fn turns_come_at_dawn_and_dusk() {
+        assert!((at(0.5).until_turn() - DAY_SECONDS / 4.0).abs() < 1e-3);
+        assert!((at(0.9).until_turn() - DAY_SECONDS * 0.35).abs() < 1e-3);
+    }
+
+    #[test]
+    fn only_daylight_holds() {
+        let mut night = at(0.1);
+        assert!(!night.hold_daylight(10.0));
+        assert!(!night.is_held());
+        let mut noon = at(0.5);
+        assert!(noon.hold_daylight(10.0));
+        assert!(noon.is_held());
+    }
+}
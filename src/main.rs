@@ -1,9 +1,17 @@
-// #![allow(warnings)]
+// The item and minigame types are plain enums; keep the crate free of unsafe.
+#![forbid(unsafe_code)]
 
+#[cfg(all(test, feature = "bench"))]
+mod bench;
+#[cfg(any(test, feature = "bot"))]
+mod bot;
 mod entities;
+#[cfg(any(test, feature = "headless"))]
+mod headless;
 mod libs;
 
 use bevy::app::AppExit;
+use bevy::asset::AssetMetaCheck;
 use bevy::prelude::*;
 use bevy_framepace::{FramepacePlugin, FramepaceSettings};
 use bevy_prototype_lyon::prelude::*;
@@ -24,153 +32,201 @@ fn setup_physics(mut config_query: Query<&mut RapierConfiguration>) {
 }
 
 fn main() {
+    #[cfg(feature = "bot")]
+    if let Some(minutes) = bot::soak_from_args(std::env::args()) {
+        let seed = seed_from_args(std::env::args()).unwrap_or_else(fresh_seed);
+        bot::soak(seed, minutes);
+        return;
+    }
     App::new()
         .add_plugins((
-            DefaultPlugins,
+            // No .meta files sit beside the assets; not looking for them
+            // saves a failed request per picture in a browser.
+            DefaultPlugins.set(AssetPlugin {
+                meta_check: AssetMetaCheck::Never,
+                ..default()
+            }),
             ShapePlugin,
-            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0),
             // RapierDebugRenderPlugin::default(),
             FramepacePlugin {},
             ClickIndicatorPlugin,
+            ConsolePlugin,
+            NotificationsPlugin,
+            PalettesPlugin,
+            BalancePlugin,
+            LocalePlugin {
+                locale: LocaleId::from_args(std::env::args()),
+            },
+            // the F-key panels
+            (
+                AccessibilityPlugin,
+                EventLogPlugin,
+                PhotoModePlugin,
+                SavePlugin,
+                TimingOverlayPlugin,
+            ),
+            (TimeScalePlugin, ThumbnailPlugin, MinimapPlugin),
+            // the game, and what it leaves behind when it panics
+            (GamePlugin, CrashPlugin),
+            #[cfg(feature = "debug-ui")]
+            InspectorPlugin,
+            // a new game's seed, then a replay's in its place
+            SeedPlugin {
+                seed: seed_from_args(std::env::args())
+                    .unwrap_or_else(fresh_seed),
+            },
+            // input played back, or made up while nobody plays
+            (
+                ReplayPlugin {
+                    mode: ReplayMode::from_args(std::env::args()),
+                },
+                AttractPlugin {
+                    start: AttractStart::from_args(std::env::args()),
+                },
+            ),
         ))
-        .add_systems(
+        .insert_resource(FramepaceSettings {
+            // limiter: Limiter::from_framerate(10.0),
+            ..default()
+        })
+        .run();
+}
+
+// The game's own systems and resources, plus physics. Everything that decides
+// what happens in the world lives here; the windowed game adds rendering and
+// input plugins around it, and the headless app adds MinimalPlugins instead.
+// Each domain has its own plugin; this one adds them, orders their
+// `GameSet`s, and holds what's shared between them.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            RapierPhysicsPlugin::<BoundsHooks>::pixels_per_meter(100.0),
+            contact::ContactPlugin,
+            MinigamesPlugin,
+            PlayerPlugin,
+            CameraPlugin,
+            ItemsPlugin,
+            InventoryPlugin,
+            tutorial::TutorialPlugin,
+            notes::NotesPlugin,
+            placement::PlacementPlugin,
+            UndoPlugin,
+            FrameTimingPlugin,
+            CatchUpPlugin,
+        ))
+        .configure_sets(
             Startup,
+            (GameSet::Minigames, GameSet::Player, GameSet::Camera).chain(),
+        )
+        .configure_sets(
+            Update,
             (
-                // Chained: setup_minigame_unlocks must register the minigame
-                // ids before setup_board's set_entity calls can record their
-                // entities (set_entity no-ops on an unknown id).
-                setup_minigame_unlocks,
-                setup_board,
-                setup_player,
-                setup_camera,
+                GameSet::Input,
+                GameSet::Camera,
+                GameSet::Player,
+                GameSet::Items,
+                GameSet::Minigames,
+                GameSet::Inventory,
+                GameSet::Pointer,
             )
                 .chain(),
         )
-        .add_systems(Startup, setup_physics)
-        .add_systems(
-            Update,
+        .configure_sets(
+            FixedUpdate,
             (
-                exit_system,
-                update_camera,
-                player_move,
-                constant_velocity_system,
-                grab_items,
-                release_items,
-                engage_button_update,
-                update_engage_button_appearance,
-                minigames::button::update,
-                minigames::rune::pixel_update,
-                minigames::tree::update,
-                minigames::life::cell_update,
-                minigames::land::cell_update,
-                minigames::ball_breaker::unselected_paddle_update,
-                minigames::primordial_ocean::update,
-                inventory::handle_slot_click,
-                inventory::handle_scroll_click,
-                mouse::update_mouse_state,
-                mouse::follow_mouse_update,
-                mouse::update_hover_text,
+                GameSet::Clock,
+                GameSet::Minigames,
+                GameSet::Items,
+                GameSet::Inventory,
+                GameSet::Audit,
             )
                 .chain(),
         )
-        .add_systems(
+        .configure_sets(
+            Update,
+            WorkSet::Collisions.run_if(on_message::<CollisionEvent>),
+        )
+        .configure_sets(
             FixedUpdate,
             (
-                minigame::levelup,
-                minigame::ingest_item,
-                minigames::rune::fixed_update,
-                minigames::tree::fixed_update,
-                minigames::ball_breaker::hit_block_fixed_update,
-                minigames::foundry::cook_fixed_update,
-                item::teleport_distant_loose_items,
-                item::combine_loose_items,
+                WorkSet::Levelup.run_if(any_with_component::<LevelingUp>),
+                WorkSet::Collisions.run_if(
+                    on_message::<CollisionEvent>
+                        .or(on_message::<contact::ItemAura>),
+                ),
             ),
         )
+        .add_systems(Startup, setup_physics)
+        // Rapier brings `GlobalTransform`s up to date before reading them, but
+        // without marking the trees that moved, so a parent moved in Update
+        // (the player, under its skin) went unseen and was put back where
+        // its body was.
         .add_systems(
-            FixedUpdate,
-            (inventory::set_slots, inventory::redraw_slots).chain(),
+            PostUpdate,
+            bevy::transform::systems::mark_dirty_trees
+                .in_set(PhysicsSet::SyncBackend)
+                .before(RapierTransformPropagateSet),
         )
         .add_systems(
-            FixedUpdate,
+            Update,
             (
-                minigames::life::evolve_fixed_update,
-                minigames::life::render_cells,
+                exit_system.run_if(console::console_closed),
+                // first, so this frame's clicks are visible to every reader
+                mouse::update_mouse_state,
+                mouse::trigger_clicks,
             )
-                .chain(),
+                .chain()
+                .in_set(GameSet::Input),
+        )
+        .add_systems(
+            Update,
+            (
+                mouse::follow_mouse_update,
+                (mouse::update_hover_text, picking::update_hover_highlight),
+            )
+                .chain()
+                .in_set(GameSet::Pointer),
         )
         .add_systems(
             FixedUpdate,
             (
-                minigames::land::evolve_fixed_update,
-                minigames::land::render_cells,
+                // sees each emitted item before the audit takes its
+                // `Emitted` off
+                event_log::log_emitted_items,
+                audit::audit_amounts.run_if(|| cfg!(debug_assertions)),
             )
-                .chain(),
+                .chain()
+                .in_set(GameSet::Audit),
         )
+        .add_systems(Update, time_scale::apply_time_scale)
+        .add_message::<mouse::ClickEvent>()
+        .add_message::<mouse::InspectClick>()
+        .add_message::<GameEvent>()
         .insert_resource(mouse::MouseState::new(1.0))
-        .insert_resource(Time::<Fixed>::from_hz(20.0))
-        .insert_resource(camera::CameraController {
-            dead_zone_squared: 1000.0,
-        })
-        .insert_resource(FramepaceSettings {
-            // limiter: Limiter::from_framerate(10.0),
-            ..default()
-        })
+        .insert_resource(Time::<Fixed>::from_hz(time_scale::BASE_FIXED_HZ))
         .insert_resource(random::Random::new(42))
-        .insert_resource(entities::minigame::Engaged { game: None })
-        .init_resource::<MinigamesResource>()
-        .init_resource::<image_gen::GeneratedImageAssets>()
-        .run();
-}
-
-fn setup_board(
-    mut commands: Commands,
-    mut minigames: ResMut<MinigamesResource>,
-    asset_server: Res<AssetServer>,
-    mut random: ResMut<random::Random>,
-    mut images: ResMut<Assets<Image>>,
-    mut generated_image_assets: ResMut<image_gen::GeneratedImageAssets>,
-    item_query: Query<
-        (&Transform, &CircularArea, Entity),
-        (With<Item>, Without<Stuck>),
-    >,
-    player_query: Query<(&Transform, &CircularArea, Entity), With<Player>>,
-) {
-    let mut spawn = |minigame: Minigame, transform: Transform| -> Entity {
-        minigame.spawn(
-            &mut commands,
-            transform,
-            &mut random,
-            &asset_server,
-            &mut images,
-            &mut generated_image_assets,
-            &item_query,
-            &player_query,
+        .init_resource::<picking::Hovered>()
+        .init_resource::<audit::AmountAudit>()
+        .init_resource::<BalanceConfig>()
+        .init_resource::<Accessibility>()
+        .init_resource::<PhotoMode>()
+        .init_resource::<TimeScale>();
+        // Item pictures load through the asset server, which can fetch them in
+        // a browser too. The headless app swaps in a stub.
+        let asset_server = app.world().resource::<AssetServer>().clone();
+        app.insert_resource(
+            image_gen::GeneratedImageAssets::with_asset_server(asset_server),
         )
-    };
-
-    minigames.set_entity(
-        entities::minigames::button::ID,
-        spawn(
-            Minigame::Button(minigames::button::ButtonMinigame { ..default() }),
-            Transform::from_xyz(0.0, 200.0, 0.0),
-        ),
-    );
-    minigames.set_entity(
-        minigames::primordial_ocean::ID,
-        spawn(
-            Minigame::PrimordialOcean(
-                minigames::primordial_ocean::PrimordialOceanMinigame::new(0.0),
+        .add_systems(
+            Update,
+            (
+                images::replace_failed_pictures,
+                images::evict_unseen_pictures,
             ),
-            Transform::from_xyz(200.0, -200.0, 0.0),
-        ),
-    );
-    minigames.set_entity(
-        entities::minigames::rune::ID,
-        spawn(
-            Minigame::Rune(entities::minigames::rune::RuneMinigame::new(0)),
-            Transform::from_xyz(-200.0, -200.0, 0.0),
-        ),
-    );
+        );
+    }
 }
 
 fn exit_system(